@@ -0,0 +1,46 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use lib_ruby_parser::source::SourceBytes;
+use lib_ruby_parser::{Parser, ParserOptions};
+
+// Ruby files from the repository itself,
+// `gems/repos` is too big to be downloaded on CI
+const FILES: &[&[u8]] = &[
+    include_bytes!("../vendor/codegen/lexer.rb"),
+    include_bytes!("../vendor/codegen/parser.rb"),
+    include_bytes!("../gems/download.rb"),
+    include_bytes!("../scripts/bench.rb"),
+];
+
+// same as `parse --drop-tokens`
+fn options() -> ParserOptions {
+    ParserOptions {
+        record_tokens: false,
+        ..Default::default()
+    }
+}
+
+fn parse_with_new_parser(c: &mut Criterion) {
+    c.bench_function("new parser", |b| {
+        b.iter(|| {
+            for file in FILES {
+                let parser = Parser::new(SourceBytes::Static(file), options());
+                black_box(parser.do_parse());
+            }
+        })
+    });
+}
+
+fn parse_with_reused_parser(c: &mut Criterion) {
+    let mut parser = Parser::new(SourceBytes::Static(b""), options());
+    c.bench_function("reused parser", |b| {
+        b.iter(|| {
+            for file in FILES {
+                parser.reset(SourceBytes::Static(file), options());
+                black_box(parser.do_parse_in_place());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, parse_with_new_parser, parse_with_reused_parser);
+criterion_main!(benches);
@@ -0,0 +1,79 @@
+use super::node_types::*;
+
+pub(crate) fn codegen() {
+    std::fs::write("src/fields/fields_gen.rs", fields_contents()).unwrap();
+}
+
+fn fields_contents() -> String {
+    format!(
+        "// This file is autogenerated by codegen/rust/fields.rs
+
+use super::FieldValue;
+use crate::Node;
+
+/// Returns names of fields of the node in the order of fields of its struct
+/// (like `recv`, `method_name`, `args`, ..., `expression_l` for `Send`)
+pub fn field_names(node: &Node) -> &'static [&'static str] {{
+    match node {{
+{names_arms}
+    }}
+}}
+
+/// Returns the value of the field at `idx` (an index in `field_names`),
+/// `None` if `idx` is out of range
+pub fn field_value(node: &Node, idx: usize) -> Option<FieldValue<'_>> {{
+    let value = match (node, idx) {{
+{value_arms}
+        _ => return None,
+    }};
+    Some(value)
+}}
+",
+        names_arms = map_node_types(names_arm),
+        value_arms = map_node_types(value_arms),
+    )
+}
+
+fn names_arm(node_type: &NodeType) -> String {
+    let names = node_type
+        .fields
+        .iter()
+        .map(|field| format!("\"{}\"", field.name))
+        .collect::<Vec<_>>();
+
+    format!(
+        "        Node::{name}(_) => &[{names}],",
+        name = node_type.name,
+        names = names.join(", ")
+    )
+}
+
+fn value_arms(node_type: &NodeType) -> String {
+    node_type
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let value = match field.kind {
+                Node => format!("FieldValue::Node(&inner.{})", field.name),
+                MaybeNode => format!("FieldValue::MaybeNode(inner.{}.as_deref())", field.name),
+                Nodes => format!("FieldValue::Nodes(&inner.{})", field.name),
+                Str => format!("FieldValue::Str(&inner.{})", field.name),
+                MaybeStr | Chars => {
+                    format!("FieldValue::MaybeStr(inner.{}.as_deref())", field.name)
+                }
+                StringValue => format!("FieldValue::Bytes(&inner.{})", field.name),
+                U8 => format!("FieldValue::U8(inner.{})", field.name),
+                Loc => format!("FieldValue::Loc(inner.{})", field.name),
+                MaybeLoc => format!("FieldValue::MaybeLoc(inner.{})", field.name),
+            };
+            format!(
+                "        (Node::{name}(inner), {idx}) => {value},",
+                name = node_type.name,
+                idx = idx,
+                value = value
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,5 +1,16 @@
+mod fields;
+mod node_types;
+mod node_types_test;
+mod query;
 mod reserved_words;
+mod rewrite;
+mod syntax_tree;
 
 pub(crate) fn codegen() {
     reserved_words::codegen();
+    rewrite::codegen();
+    query::codegen();
+    syntax_tree::codegen();
+    fields::codegen();
+    node_types_test::codegen();
 }
@@ -0,0 +1,1365 @@
+// Node types and their fields, in the same order as in structs from `lib_ruby_parser_ast::nodes`.
+//
+// The list is used to generate code for every node type (see `rewrite.rs`, `query.rs`, `syntax_tree.rs` and `fields.rs`),
+// `node_types_test.rs` generates a test that checks it against the AST crate.
+
+pub(crate) enum FieldKind {
+    // Box<Node>
+    Node,
+    // Option<Box<Node>>
+    MaybeNode,
+    // Vec<Node>
+    Nodes,
+    // String
+    Str,
+    // Option<String>
+    MaybeStr,
+    // Option<String> that `Node::inspect` prints char by char (options of `RegOpt`)
+    Chars,
+    // Bytes
+    StringValue,
+    // u8
+    U8,
+    // Loc
+    Loc,
+    // Option<Loc>
+    MaybeLoc,
+}
+pub(crate) use FieldKind::*;
+
+pub(crate) struct Field {
+    pub(crate) name: &'static str,
+    pub(crate) kind: FieldKind,
+    // `true` if `Node::inspect` prints `nil` for an absent value
+    pub(crate) or_nil: bool,
+}
+
+const fn field(name: &'static str, kind: FieldKind) -> Field {
+    Field {
+        name,
+        kind,
+        or_nil: false,
+    }
+}
+
+const fn field_or_nil(name: &'static str, kind: FieldKind) -> Field {
+    Field {
+        name,
+        kind,
+        or_nil: true,
+    }
+}
+
+pub(crate) struct NodeType {
+    pub(crate) name: &'static str,
+    pub(crate) snake: &'static str,
+    // `Node::str_type`
+    pub(crate) str_type: &'static str,
+    pub(crate) fields: &'static [Field],
+}
+
+impl NodeType {
+    // Fields with child nodes, in the order of `lib_ruby_parser_ast::traverse::visitor`
+    pub(crate) fn node_fields(&self) -> impl Iterator<Item = &Field> {
+        self.fields
+            .iter()
+            .filter(|field| matches!(field.kind, Node | MaybeNode | Nodes))
+    }
+}
+
+pub(crate) fn map_node_types(f: fn(&NodeType) -> String) -> String {
+    NODE_TYPES.iter().map(f).collect::<Vec<_>>().join("\n")
+}
+
+pub(crate) const NODE_TYPES: &[NodeType] = &[
+    NodeType {
+        name: "Alias",
+        snake: "alias",
+        str_type: "alias",
+        fields: &[
+            field("to", Node),
+            field("from", Node),
+            field("keyword_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "And",
+        snake: "and",
+        str_type: "and",
+        fields: &[
+            field("lhs", Node),
+            field("rhs", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "AndAsgn",
+        snake: "and_asgn",
+        str_type: "and_asgn",
+        fields: &[
+            field("recv", Node),
+            field("value", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Arg",
+        snake: "arg",
+        str_type: "arg",
+        fields: &[field("name", Str), field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Args",
+        snake: "args",
+        str_type: "args",
+        fields: &[
+            field("args", Nodes),
+            field("expression_l", Loc),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+        ],
+    },
+    NodeType {
+        name: "Array",
+        snake: "array",
+        str_type: "array",
+        fields: &[
+            field("elements", Nodes),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "ArrayPattern",
+        snake: "array_pattern",
+        str_type: "array_pattern",
+        fields: &[
+            field("elements", Nodes),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "ArrayPatternWithTail",
+        snake: "array_pattern_with_tail",
+        str_type: "array_pattern_with_tail",
+        fields: &[
+            field("elements", Nodes),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "BackRef",
+        snake: "back_ref",
+        str_type: "back_ref",
+        fields: &[field("name", Str), field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Begin",
+        snake: "begin",
+        str_type: "begin",
+        fields: &[
+            field("statements", Nodes),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Block",
+        snake: "block",
+        str_type: "block",
+        fields: &[
+            field("call", Node),
+            field_or_nil("args", MaybeNode),
+            field_or_nil("body", MaybeNode),
+            field("begin_l", Loc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Blockarg",
+        snake: "blockarg",
+        str_type: "blockarg",
+        fields: &[
+            field_or_nil("name", MaybeStr),
+            field("operator_l", Loc),
+            field("name_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "BlockPass",
+        snake: "block_pass",
+        str_type: "block_pass",
+        fields: &[
+            field_or_nil("value", MaybeNode),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Break",
+        snake: "break",
+        str_type: "break",
+        fields: &[
+            field("args", Nodes),
+            field("keyword_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Case",
+        snake: "case",
+        str_type: "case",
+        fields: &[
+            field_or_nil("expr", MaybeNode),
+            field("when_bodies", Nodes),
+            field_or_nil("else_body", MaybeNode),
+            field("keyword_l", Loc),
+            field("else_l", MaybeLoc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "CaseMatch",
+        snake: "case_match",
+        str_type: "case_match",
+        fields: &[
+            field("expr", Node),
+            field("in_bodies", Nodes),
+            field_or_nil("else_body", MaybeNode),
+            field("keyword_l", Loc),
+            field("else_l", MaybeLoc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Casgn",
+        snake: "casgn",
+        str_type: "casgn",
+        fields: &[
+            field_or_nil("scope", MaybeNode),
+            field("name", Str),
+            field("value", MaybeNode),
+            field("double_colon_l", MaybeLoc),
+            field("name_l", Loc),
+            field("operator_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Cbase",
+        snake: "cbase",
+        str_type: "cbase",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Class",
+        snake: "class",
+        str_type: "class",
+        fields: &[
+            field("name", Node),
+            field_or_nil("superclass", MaybeNode),
+            field_or_nil("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("operator_l", MaybeLoc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Complex",
+        snake: "complex",
+        str_type: "complex",
+        fields: &[
+            field("value", Str),
+            field("operator_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Const",
+        snake: "const",
+        str_type: "const",
+        fields: &[
+            field_or_nil("scope", MaybeNode),
+            field("name", Str),
+            field("double_colon_l", MaybeLoc),
+            field("name_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "ConstPattern",
+        snake: "const_pattern",
+        str_type: "const_pattern",
+        fields: &[
+            field("const_", Node),
+            field("pattern", Node),
+            field("begin_l", Loc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "CSend",
+        snake: "c_send",
+        str_type: "csend",
+        fields: &[
+            field("recv", Node),
+            field("method_name", Str),
+            field("args", Nodes),
+            field("dot_l", Loc),
+            field("selector_l", MaybeLoc),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("operator_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Cvar",
+        snake: "cvar",
+        str_type: "cvar",
+        fields: &[field("name", Str), field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Cvasgn",
+        snake: "cvasgn",
+        str_type: "cvasgn",
+        fields: &[
+            field("name", Str),
+            field("value", MaybeNode),
+            field("name_l", Loc),
+            field("operator_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Def",
+        snake: "def",
+        str_type: "def",
+        fields: &[
+            field("name", Str),
+            field_or_nil("args", MaybeNode),
+            field_or_nil("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("name_l", Loc),
+            field("end_l", MaybeLoc),
+            field("assignment_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Defined",
+        snake: "defined",
+        str_type: "defined?",
+        fields: &[
+            field("value", Node),
+            field("keyword_l", Loc),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Defs",
+        snake: "defs",
+        str_type: "defs",
+        fields: &[
+            field("definee", Node),
+            field("name", Str),
+            field_or_nil("args", MaybeNode),
+            field_or_nil("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("operator_l", Loc),
+            field("name_l", Loc),
+            field("assignment_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Dstr",
+        snake: "dstr",
+        str_type: "dstr",
+        fields: &[
+            field("parts", Nodes),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Dsym",
+        snake: "dsym",
+        str_type: "dsym",
+        fields: &[
+            field("parts", Nodes),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "EFlipFlop",
+        snake: "e_flip_flop",
+        str_type: "eflipflop",
+        fields: &[
+            field_or_nil("left", MaybeNode),
+            field_or_nil("right", MaybeNode),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "EmptyElse",
+        snake: "empty_else",
+        str_type: "empty_else",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Encoding",
+        snake: "encoding",
+        str_type: "__ENCODING__",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Ensure",
+        snake: "ensure",
+        str_type: "ensure",
+        fields: &[
+            field_or_nil("body", MaybeNode),
+            field_or_nil("ensure", MaybeNode),
+            field("keyword_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Erange",
+        snake: "erange",
+        str_type: "erange",
+        fields: &[
+            field_or_nil("left", MaybeNode),
+            field_or_nil("right", MaybeNode),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "False",
+        snake: "false",
+        str_type: "false",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "File",
+        snake: "file",
+        str_type: "__FILE__",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "FindPattern",
+        snake: "find_pattern",
+        str_type: "find_pattern",
+        fields: &[
+            field("elements", Nodes),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Float",
+        snake: "float",
+        str_type: "float",
+        fields: &[
+            field("value", Str),
+            field("operator_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "For",
+        snake: "for",
+        str_type: "for",
+        fields: &[
+            field("iterator", Node),
+            field("iteratee", Node),
+            field_or_nil("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("operator_l", Loc),
+            field("begin_l", Loc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "ForwardArg",
+        snake: "forward_arg",
+        str_type: "forward_arg",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "ForwardedArgs",
+        snake: "forwarded_args",
+        str_type: "forwarded_args",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Gvar",
+        snake: "gvar",
+        str_type: "gvar",
+        fields: &[field("name", Str), field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Gvasgn",
+        snake: "gvasgn",
+        str_type: "gvasgn",
+        fields: &[
+            field("name", Str),
+            field("value", MaybeNode),
+            field("name_l", Loc),
+            field("operator_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Hash",
+        snake: "hash",
+        str_type: "hash",
+        fields: &[
+            field("pairs", Nodes),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "HashPattern",
+        snake: "hash_pattern",
+        str_type: "hash_pattern",
+        fields: &[
+            field("elements", Nodes),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Heredoc",
+        snake: "heredoc",
+        str_type: "dstr",
+        fields: &[
+            field("parts", Nodes),
+            field("heredoc_body_l", Loc),
+            field("heredoc_end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "If",
+        snake: "if",
+        str_type: "if",
+        fields: &[
+            field("cond", Node),
+            field_or_nil("if_true", MaybeNode),
+            field_or_nil("if_false", MaybeNode),
+            field("keyword_l", Loc),
+            field("begin_l", Loc),
+            field("else_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "IfGuard",
+        snake: "if_guard",
+        str_type: "if_guard",
+        fields: &[
+            field("cond", Node),
+            field("keyword_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "IFlipFlop",
+        snake: "i_flip_flop",
+        str_type: "iflipflop",
+        fields: &[
+            field_or_nil("left", MaybeNode),
+            field_or_nil("right", MaybeNode),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "IfMod",
+        snake: "if_mod",
+        str_type: "if",
+        fields: &[
+            field("cond", Node),
+            field_or_nil("if_true", MaybeNode),
+            field_or_nil("if_false", MaybeNode),
+            field("keyword_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "IfTernary",
+        snake: "if_ternary",
+        str_type: "if",
+        fields: &[
+            field("cond", Node),
+            field("if_true", Node),
+            field("if_false", Node),
+            field("question_l", Loc),
+            field("colon_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Index",
+        snake: "index",
+        str_type: "index",
+        fields: &[
+            field("recv", Node),
+            field("indexes", Nodes),
+            field("begin_l", Loc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "IndexAsgn",
+        snake: "index_asgn",
+        str_type: "indexasgn",
+        fields: &[
+            field("recv", Node),
+            field("indexes", Nodes),
+            field("value", MaybeNode),
+            field("begin_l", Loc),
+            field("end_l", Loc),
+            field("operator_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "InPattern",
+        snake: "in_pattern",
+        str_type: "in_pattern",
+        fields: &[
+            field("pattern", Node),
+            field_or_nil("guard", MaybeNode),
+            field_or_nil("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("begin_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Int",
+        snake: "int",
+        str_type: "int",
+        fields: &[
+            field("value", Str),
+            field("operator_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Irange",
+        snake: "irange",
+        str_type: "irange",
+        fields: &[
+            field_or_nil("left", MaybeNode),
+            field_or_nil("right", MaybeNode),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Ivar",
+        snake: "ivar",
+        str_type: "ivar",
+        fields: &[field("name", Str), field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Ivasgn",
+        snake: "ivasgn",
+        str_type: "ivasgn",
+        fields: &[
+            field("name", Str),
+            field("value", MaybeNode),
+            field("name_l", Loc),
+            field("operator_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Kwarg",
+        snake: "kwarg",
+        str_type: "kwarg",
+        fields: &[
+            field("name", Str),
+            field("name_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Kwargs",
+        snake: "kwargs",
+        str_type: "kwargs",
+        fields: &[field("pairs", Nodes), field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "KwBegin",
+        snake: "kw_begin",
+        str_type: "kwbegin",
+        fields: &[
+            field("statements", Nodes),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Kwnilarg",
+        snake: "kwnilarg",
+        str_type: "kwnilarg",
+        fields: &[field("name_l", Loc), field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Kwoptarg",
+        snake: "kwoptarg",
+        str_type: "kwoptarg",
+        fields: &[
+            field("name", Str),
+            field("default", Node),
+            field("name_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Kwrestarg",
+        snake: "kwrestarg",
+        str_type: "kwrestarg",
+        fields: &[
+            field("name", MaybeStr),
+            field("operator_l", Loc),
+            field("name_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Kwsplat",
+        snake: "kwsplat",
+        str_type: "kwsplat",
+        fields: &[
+            field("value", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Lambda",
+        snake: "lambda",
+        str_type: "lambda",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Line",
+        snake: "line",
+        str_type: "__LINE__",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Lvar",
+        snake: "lvar",
+        str_type: "lvar",
+        fields: &[field("name", Str), field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Lvasgn",
+        snake: "lvasgn",
+        str_type: "lvasgn",
+        fields: &[
+            field("name", Str),
+            field("value", MaybeNode),
+            field("name_l", Loc),
+            field("operator_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Masgn",
+        snake: "masgn",
+        str_type: "masgn",
+        fields: &[
+            field("lhs", Node),
+            field("rhs", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "MatchAlt",
+        snake: "match_alt",
+        str_type: "match_alt",
+        fields: &[
+            field("lhs", Node),
+            field("rhs", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "MatchAs",
+        snake: "match_as",
+        str_type: "match_as",
+        fields: &[
+            field("value", Node),
+            field("as_", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "MatchCurrentLine",
+        snake: "match_current_line",
+        str_type: "match_current_line",
+        fields: &[field("re", Node), field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "MatchNilPattern",
+        snake: "match_nil_pattern",
+        str_type: "match_nil_pattern",
+        fields: &[
+            field("operator_l", Loc),
+            field("name_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "MatchPattern",
+        snake: "match_pattern",
+        str_type: "match_pattern",
+        fields: &[
+            field("value", Node),
+            field("pattern", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "MatchPatternP",
+        snake: "match_pattern_p",
+        str_type: "match_pattern_p",
+        fields: &[
+            field("value", Node),
+            field("pattern", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "MatchRest",
+        snake: "match_rest",
+        str_type: "match_rest",
+        fields: &[
+            field("name", MaybeNode),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "MatchVar",
+        snake: "match_var",
+        str_type: "match_var",
+        fields: &[
+            field("name", Str),
+            field("name_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "MatchWithLvasgn",
+        snake: "match_with_lvasgn",
+        str_type: "match_with_lvasgn",
+        fields: &[
+            field("re", Node),
+            field("value", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Mlhs",
+        snake: "mlhs",
+        str_type: "mlhs",
+        fields: &[
+            field("items", Nodes),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Module",
+        snake: "module",
+        str_type: "module",
+        fields: &[
+            field("name", Node),
+            field_or_nil("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Next",
+        snake: "next",
+        str_type: "next",
+        fields: &[
+            field("args", Nodes),
+            field("keyword_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Nil",
+        snake: "nil",
+        str_type: "nil",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "NthRef",
+        snake: "nth_ref",
+        str_type: "nth_ref",
+        fields: &[field("name", Str), field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Numblock",
+        snake: "numblock",
+        str_type: "numblock",
+        fields: &[
+            field("call", Node),
+            field("numargs", U8),
+            field("body", Node),
+            field("begin_l", Loc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "OpAsgn",
+        snake: "op_asgn",
+        str_type: "op_asgn",
+        fields: &[
+            field("recv", Node),
+            field("operator", Str),
+            field("value", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Optarg",
+        snake: "optarg",
+        str_type: "optarg",
+        fields: &[
+            field("name", Str),
+            field("default", Node),
+            field("name_l", Loc),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Or",
+        snake: "or",
+        str_type: "or",
+        fields: &[
+            field("lhs", Node),
+            field("rhs", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "OrAsgn",
+        snake: "or_asgn",
+        str_type: "or_asgn",
+        fields: &[
+            field("recv", Node),
+            field("value", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Pair",
+        snake: "pair",
+        str_type: "pair",
+        fields: &[
+            field("key", Node),
+            field("value", Node),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Pin",
+        snake: "pin",
+        str_type: "pin",
+        fields: &[
+            field("var", Node),
+            field("selector_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Postexe",
+        snake: "postexe",
+        str_type: "postexe",
+        fields: &[
+            field("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("begin_l", Loc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Preexe",
+        snake: "preexe",
+        str_type: "preexe",
+        fields: &[
+            field("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("begin_l", Loc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Procarg0",
+        snake: "procarg0",
+        str_type: "procarg0",
+        fields: &[
+            field("args", Nodes),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Rational",
+        snake: "rational",
+        str_type: "rational",
+        fields: &[
+            field("value", Str),
+            field("operator_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Redo",
+        snake: "redo",
+        str_type: "redo",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Regexp",
+        snake: "regexp",
+        str_type: "regexp",
+        fields: &[
+            field("parts", Nodes),
+            field("options", MaybeNode),
+            field("begin_l", Loc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "RegOpt",
+        snake: "reg_opt",
+        str_type: "regopt",
+        fields: &[field("options", Chars), field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Rescue",
+        snake: "rescue",
+        str_type: "rescue",
+        fields: &[
+            field_or_nil("body", MaybeNode),
+            field("rescue_bodies", Nodes),
+            field_or_nil("else_", MaybeNode),
+            field("else_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "RescueBody",
+        snake: "rescue_body",
+        str_type: "resbody",
+        fields: &[
+            field_or_nil("exc_list", MaybeNode),
+            field_or_nil("exc_var", MaybeNode),
+            field_or_nil("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("assoc_l", MaybeLoc),
+            field("begin_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Restarg",
+        snake: "restarg",
+        str_type: "restarg",
+        fields: &[
+            field("name", MaybeStr),
+            field("operator_l", Loc),
+            field("name_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Retry",
+        snake: "retry",
+        str_type: "retry",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Return",
+        snake: "return",
+        str_type: "return",
+        fields: &[
+            field("args", Nodes),
+            field("keyword_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "SClass",
+        snake: "s_class",
+        str_type: "sclass",
+        fields: &[
+            field("expr", Node),
+            field_or_nil("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("operator_l", Loc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Self_",
+        snake: "self_",
+        str_type: "self",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Send",
+        snake: "send",
+        str_type: "send",
+        fields: &[
+            field_or_nil("recv", MaybeNode),
+            field("method_name", Str),
+            field("args", Nodes),
+            field("dot_l", MaybeLoc),
+            field("selector_l", MaybeLoc),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("operator_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Shadowarg",
+        snake: "shadowarg",
+        str_type: "shadowarg",
+        fields: &[field("name", Str), field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Splat",
+        snake: "splat",
+        str_type: "splat",
+        fields: &[
+            field("value", MaybeNode),
+            field("operator_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Str",
+        snake: "str",
+        str_type: "str",
+        fields: &[
+            field("value", StringValue),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Super",
+        snake: "super",
+        str_type: "super",
+        fields: &[
+            field("args", Nodes),
+            field("keyword_l", Loc),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Sym",
+        snake: "sym",
+        str_type: "sym",
+        fields: &[
+            field("name", StringValue),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "True",
+        snake: "true",
+        str_type: "true",
+        fields: &[field("expression_l", Loc)],
+    },
+    NodeType {
+        name: "Undef",
+        snake: "undef",
+        str_type: "undef",
+        fields: &[
+            field("names", Nodes),
+            field("keyword_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "UnlessGuard",
+        snake: "unless_guard",
+        str_type: "unless_guard",
+        fields: &[
+            field("cond", Node),
+            field("keyword_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Until",
+        snake: "until",
+        str_type: "until",
+        fields: &[
+            field("cond", Node),
+            field_or_nil("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "UntilPost",
+        snake: "until_post",
+        str_type: "until_post",
+        fields: &[
+            field("cond", Node),
+            field("body", Node),
+            field("keyword_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "When",
+        snake: "when",
+        str_type: "when",
+        fields: &[
+            field("patterns", Nodes),
+            field_or_nil("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("begin_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "While",
+        snake: "while",
+        str_type: "while",
+        fields: &[
+            field("cond", Node),
+            field_or_nil("body", MaybeNode),
+            field("keyword_l", Loc),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "WhilePost",
+        snake: "while_post",
+        str_type: "while_post",
+        fields: &[
+            field("cond", Node),
+            field("body", Node),
+            field("keyword_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "XHeredoc",
+        snake: "x_heredoc",
+        str_type: "xstr",
+        fields: &[
+            field("parts", Nodes),
+            field("heredoc_body_l", Loc),
+            field("heredoc_end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Xstr",
+        snake: "xstr",
+        str_type: "xstr",
+        fields: &[
+            field("parts", Nodes),
+            field("begin_l", Loc),
+            field("end_l", Loc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "Yield",
+        snake: "yield",
+        str_type: "yield",
+        fields: &[
+            field("args", Nodes),
+            field("keyword_l", Loc),
+            field("begin_l", MaybeLoc),
+            field("end_l", MaybeLoc),
+            field("expression_l", Loc),
+        ],
+    },
+    NodeType {
+        name: "ZSuper",
+        snake: "z_super",
+        str_type: "zsuper",
+        fields: &[field("expression_l", Loc)],
+    },
+];
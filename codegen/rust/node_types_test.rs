@@ -0,0 +1,61 @@
+use super::node_types::*;
+
+pub(crate) fn codegen() {
+    std::fs::write("src/tests/node_types/gen.rs", contents()).unwrap();
+}
+
+fn contents() -> String {
+    format!(
+        "// This file is autogenerated by codegen/rust/node_types_test.rs
+
+use crate::nodes::*;
+use crate::{{Bytes, Loc, Node}};
+
+// Returns a node of every type with all fields from `codegen/rust/node_types.rs`,
+// so a field that is missing in the table (or doesn't exist in the struct) is a compile error.
+//
+// Child nodes are created by `child`, all other values are the same in every node.
+pub(crate) fn sample_nodes(mut child: impl FnMut() -> Node) -> Vec<Node> {{
+    let loc = Loc {{ begin: 0, end: 0 }};
+    vec![
+{nodes}
+    ]
+}}
+",
+        nodes = map_node_types(sample_node)
+    )
+}
+
+fn sample_node(node_type: &NodeType) -> String {
+    let fields = node_type
+        .fields
+        .iter()
+        .map(|field| {
+            let value = match field.kind {
+                Node => "Box::new(child())",
+                MaybeNode => "Some(Box::new(child()))",
+                Nodes => "vec![child(), child()]",
+                Str => "String::from(\"a\")",
+                MaybeStr => "Some(String::from(\"a\"))",
+                Chars => "Some(String::from(\"im\"))",
+                StringValue => "Bytes::new(b\"a\".to_vec())",
+                U8 => "1",
+                Loc => "loc",
+                MaybeLoc => "Some(loc)",
+            };
+            format!(
+                "            {name}: {value},",
+                name = field.name,
+                value = value
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "        Node::{name}({name} {{
+{fields}
+        }}),",
+        name = node_type.name,
+        fields = fields.join("\n")
+    )
+}
@@ -0,0 +1,81 @@
+use super::node_types::*;
+
+pub(crate) fn codegen() {
+    std::fs::write("src/query/children_gen.rs", children_contents()).unwrap();
+}
+
+fn children_contents() -> String {
+    let mut str_types = NODE_TYPES
+        .iter()
+        .map(|node_type| format!("    \"{}\",", node_type.str_type))
+        .collect::<Vec<_>>();
+    str_types.sort();
+    str_types.dedup();
+
+    format!(
+        "// This file is autogenerated by codegen/rust/query.rs
+
+use super::children::Children;
+use super::Value;
+use crate::Node;
+
+/// Returns children of the node in the same order as `Node::inspect` prints them
+/// (i.e. `recv`, `method_name` and then `args` for `Send`).
+///
+/// Absent optional nodes that are printed as `nil` are returned as `Value::Nil`.
+pub fn children(node: &Node) -> Vec<Value<'_>> {{
+    let mut children = Children::new();
+    match node {{
+{arms}
+    }}
+    children.into_values()
+}}
+
+// All values of `Node::str_type`
+pub(crate) const NODE_TYPES: &[&str] = &[
+{str_types}
+];
+",
+        arms = map_node_types(children_arm),
+        str_types = str_types.join("\n")
+    )
+}
+
+fn children_arm(node_type: &NodeType) -> String {
+    let fields = node_type
+        .fields
+        .iter()
+        .filter_map(|field| {
+            let method = match (&field.kind, field.or_nil) {
+                (Node, _) => "node",
+                (MaybeNode, false) => "maybe_node",
+                (MaybeNode, true) => "maybe_node_or_nil",
+                (Nodes, _) => "nodes",
+                (Str, _) => "str",
+                (MaybeStr, false) => "maybe_str",
+                (MaybeStr, true) => "maybe_str_or_nil",
+                (Chars, _) => "chars",
+                (StringValue, _) => "bytes",
+                (U8, _) => "u8",
+                (Loc, _) | (MaybeLoc, _) => return None,
+            };
+            Some(format!(
+                "            children.{method}(&inner.{name});",
+                method = method,
+                name = field.name
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    if fields.is_empty() {
+        return format!("        Node::{name}(_) => {{}}", name = node_type.name);
+    }
+
+    format!(
+        "        Node::{name}(inner) => {{
+{fields}
+        }}",
+        name = node_type.name,
+        fields = fields.join("\n")
+    )
+}
@@ -0,0 +1,324 @@
+use super::node_types::*;
+
+pub(crate) fn codegen() {
+    std::fs::write("src/rewrite/fold_gen.rs", fold_contents()).unwrap();
+    std::fs::write("src/rewrite/visitor_mut_gen.rs", visitor_mut_contents()).unwrap();
+    std::fs::write("src/rewrite/expression_gen.rs", expression_contents()).unwrap();
+    std::fs::write("src/rewrite/children_gen.rs", children_contents()).unwrap();
+}
+
+fn fold_contents() -> String {
+    format!(
+        "// This file is autogenerated by codegen/rust/rewrite.rs
+
+use crate::nodes::*;
+use crate::Node;
+
+/// Common trait for all folders.
+///
+/// Unlike `Visitor` it takes nodes by value and returns new nodes,
+/// so a node can be replaced by a node of a different type.
+pub trait Fold: Sized {{
+{on_methods}
+
+    /// Folds a list of child nodes (like statements of `Begin` or arguments of `Send`).
+    ///
+    /// Has a default implementation that folds every node,
+    /// override it to remove or insert nodes.
+    fn fold_nodes(&mut self, nodes: Vec<Node>) -> Vec<Node> {{
+        nodes.into_iter().map(|node| self.fold(node)).collect()
+    }}
+
+    /// Generic `fold` router that calls `on_<type>` for a given node
+    fn fold(&mut self, node: Node) -> Node {{
+        match node {{
+{router}
+        }}
+    }}
+}}
+
+{fold_fns}
+",
+        on_methods = map_node_types(fold_on_method),
+        router = map_node_types(|node_type| format!(
+            "            Node::{name}(inner) => self.on_{snake}(inner),",
+            name = node_type.name,
+            snake = node_type.snake
+        )),
+        fold_fns = map_node_types(fold_fn),
+    )
+}
+
+fn fold_on_method(node_type: &NodeType) -> String {
+    format!(
+        "    /// Invoked by a `Fold` on entering into `{name}` node.
+    ///
+    /// Has a default implementation, but you can override it and (optionally) call
+    /// `fold_{snake}(node)` to continue traversing.
+    fn on_{snake}(&mut self, node: {name}) -> Node {{
+        fold_{snake}(self, node)
+    }}
+",
+        name = node_type.name,
+        snake = node_type.snake
+    )
+}
+
+fn fold_fn(node_type: &NodeType) -> String {
+    let fields = node_type
+        .node_fields()
+        .map(|field| match field.kind {
+            Node => format!(
+                "    node.{name} = Box::new(folder.fold(*node.{name}));",
+                name = field.name
+            ),
+            MaybeNode => format!(
+                "    node.{name} = node.{name}.map(|inner| Box::new(folder.fold(*inner)));",
+                name = field.name
+            ),
+            Nodes => format!(
+                "    node.{name} = folder.fold_nodes(node.{name});",
+                name = field.name
+            ),
+            _ => unreachable!("{} is not a child node", field.name),
+        })
+        .collect::<Vec<_>>();
+
+    if fields.is_empty() {
+        format!(
+            "/// Folds all children of {name} node
+pub fn fold_{snake}<F: Fold>(_folder: &mut F, node: {name}) -> Node {{
+    Node::{name}(node)
+}}
+",
+            name = node_type.name,
+            snake = node_type.snake
+        )
+    } else {
+        format!(
+            "/// Folds all children of {name} node
+pub fn fold_{snake}<F: Fold>(folder: &mut F, mut node: {name}) -> Node {{
+{fields}
+    Node::{name}(node)
+}}
+",
+            name = node_type.name,
+            snake = node_type.snake,
+            fields = fields.join("\n")
+        )
+    }
+}
+
+fn visitor_mut_contents() -> String {
+    format!(
+        "// This file is autogenerated by codegen/rust/rewrite.rs
+
+use crate::nodes::*;
+use crate::Node;
+
+/// Common trait for all mutable visitors.
+///
+/// Unlike `Visitor` it takes mutable references, so nodes can be changed in place.
+pub trait VisitorMut: Sized {{
+{on_methods}
+
+    /// Visits a list of child nodes (like statements of `Begin` or arguments of `Send`).
+    ///
+    /// Has a default implementation that visits every node,
+    /// override it to remove or insert nodes.
+    fn visit_nodes(&mut self, nodes: &mut Vec<Node>) {{
+        for node in nodes.iter_mut() {{
+            self.visit(node);
+        }}
+    }}
+
+    /// Generic `visit` router that calls `on_<type>` for a given node
+    fn visit(&mut self, node: &mut Node) {{
+        match node {{
+{router}
+        }}
+    }}
+}}
+
+{visit_fns}
+",
+        on_methods = map_node_types(visitor_mut_on_method),
+        router = map_node_types(|node_type| format!(
+            "            Node::{name}(inner) => self.on_{snake}(inner),",
+            name = node_type.name,
+            snake = node_type.snake
+        )),
+        visit_fns = map_node_types(visit_fn),
+    )
+}
+
+fn visitor_mut_on_method(node_type: &NodeType) -> String {
+    format!(
+        "    /// Invoked by a `VisitorMut` on entering into `{name}` node.
+    ///
+    /// Has a default implementation, but you can override it and (optionally) call
+    /// `visit_{snake}(node)` to continue traversing.
+    fn on_{snake}(&mut self, node: &mut {name}) {{
+        visit_{snake}(self, node);
+    }}
+",
+        name = node_type.name,
+        snake = node_type.snake
+    )
+}
+
+fn visit_fn(node_type: &NodeType) -> String {
+    let fields = node_type
+        .node_fields()
+        .map(|field| match field.kind {
+            Node => format!("    visitor.visit(&mut node.{name});", name = field.name),
+            MaybeNode => format!(
+                "    if let Some(inner) = node.{name}.as_mut() {{
+        visitor.visit(inner);
+    }}",
+                name = field.name
+            ),
+            Nodes => format!(
+                "    visitor.visit_nodes(&mut node.{name});",
+                name = field.name
+            ),
+            _ => unreachable!("{} is not a child node", field.name),
+        })
+        .collect::<Vec<_>>();
+
+    if fields.is_empty() {
+        format!(
+            "/// Visits all children of {name} node
+pub fn visit_{snake}<V: VisitorMut>(_visitor: &mut V, _node: &mut {name}) {{}}
+",
+            name = node_type.name,
+            snake = node_type.snake
+        )
+    } else {
+        format!(
+            "/// Visits all children of {name} node
+pub fn visit_{snake}<V: VisitorMut>(visitor: &mut V, node: &mut {name}) {{
+{fields}
+}}
+",
+            name = node_type.name,
+            snake = node_type.snake,
+            fields = fields.join("\n")
+        )
+    }
+}
+
+fn expression_contents() -> String {
+    format!(
+        "// This file is autogenerated by codegen/rust/rewrite.rs
+
+use crate::{{Loc, Node}};
+
+/// Returns a mutable reference to `expression_l` of the node
+pub fn expression_mut(node: &mut Node) -> &mut Loc {{
+    match node {{
+{arms}
+    }}
+}}
+",
+        arms = map_node_types(|node_type| format!(
+            "        Node::{name}(inner) => &mut inner.expression_l,",
+            name = node_type.name
+        ))
+    )
+}
+
+fn children_contents() -> String {
+    format!(
+        "// This file is autogenerated by codegen/rust/rewrite.rs
+
+use crate::Node;
+
+/// Moves all children of the node to `children`,
+/// required children (like `lhs` of `And`) are replaced with `nil`
+pub(crate) fn take_children(node: &mut Node, children: &mut Vec<Node>) {{
+    match node {{
+{take_arms}
+    }}
+}}
+
+/// Calls `f` for every direct child of the node in the order of `traverse::visitor::Visitor`
+pub(crate) fn for_each_child<'a, F: FnMut(&'a Node)>(node: &'a Node, f: &mut F) {{
+    match node {{
+{for_each_arms}
+    }}
+}}
+",
+        take_arms = map_node_types(take_children_arm),
+        for_each_arms = map_node_types(for_each_child_arm)
+    )
+}
+
+fn take_children_arm(node_type: &NodeType) -> String {
+    if node_type.node_fields().next().is_none() {
+        return format!("        Node::{name}(_) => {{}}", name = node_type.name);
+    }
+
+    let fields = node_type
+        .node_fields()
+        .map(|field| match field.kind {
+            Node => format!(
+                "            children.push(super::take(&mut inner.{name}));",
+                name = field.name
+            ),
+            MaybeNode => format!(
+                "            if let Some(child) = inner.{name}.take() {{
+                children.push(*child);
+            }}",
+                name = field.name
+            ),
+            Nodes => format!(
+                "            children.append(&mut inner.{name});",
+                name = field.name
+            ),
+            _ => unreachable!("{} is not a child node", field.name),
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "        Node::{name}(inner) => {{
+{fields}
+        }}",
+        name = node_type.name,
+        fields = fields.join("\n")
+    )
+}
+
+fn for_each_child_arm(node_type: &NodeType) -> String {
+    if node_type.node_fields().next().is_none() {
+        return format!("        Node::{name}(_) => {{}}", name = node_type.name);
+    }
+
+    let fields = node_type
+        .node_fields()
+        .map(|field| match field.kind {
+            Node => format!("            f(&inner.{name});", name = field.name),
+            MaybeNode => format!(
+                "            if let Some(child) = inner.{name}.as_deref() {{
+                f(child)
+            }}",
+                name = field.name
+            ),
+            Nodes => format!(
+                "            for child in &inner.{name} {{
+                f(child)
+            }}",
+                name = field.name
+            ),
+            _ => unreachable!("{} is not a child node", field.name),
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "        Node::{name}(inner) => {{
+{fields}
+        }}",
+        name = node_type.name,
+        fields = fields.join("\n")
+    )
+}
@@ -0,0 +1,90 @@
+use super::node_types::*;
+
+pub(crate) fn codegen() {
+    std::fs::write("src/syntax_tree/kind_gen.rs", kind_contents()).unwrap();
+}
+
+fn kind_contents() -> String {
+    format!(
+        "// This file is autogenerated by codegen/rust/syntax_tree.rs
+
+use crate::Node;
+
+/// Kind of a node of `SyntaxTree`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SyntaxKind {{
+    /// Root node, covers the whole input
+    Root,
+
+    /// Argument list of a method call, `super`, `yield` or index,
+    /// with parentheses/brackets if they are present
+    ArgumentList,
+
+    /// `then` clause of `if`/`unless`/`when`/`in`/`rescue`,
+    /// starts with `then` keyword or a terminator (`;` or a newline)
+    /// and includes the body of the branch
+    Then,
+
+    /// `else` clause of `if`/`unless`/`case`/`begin`,
+    /// starts with `else` keyword and includes the body of the branch
+    Else,
+
+    /// `do ... end` part of a block (with block arguments)
+    DoBody,
+
+    /// `{{ ... }}` part of a block (with block arguments)
+    BraceBody,
+
+{variants}
+}}
+
+impl SyntaxKind {{
+    pub(crate) fn of_node(node: &Node) -> Self {{
+        match node {{
+{of_node_arms}
+        }}
+    }}
+
+    /// Name of the kind, `Node::str_type` for kinds of AST nodes
+    pub fn name(&self) -> &'static str {{
+        match self {{
+            Self::Root => \"root\",
+            Self::ArgumentList => \"argument_list\",
+            Self::Then => \"then\",
+            Self::Else => \"else\",
+            Self::DoBody => \"do_body\",
+            Self::BraceBody => \"brace_body\",
+{name_arms}
+        }}
+    }}
+}}
+",
+        variants = map_node_types(variant),
+        of_node_arms = map_node_types(of_node_arm),
+        name_arms = map_node_types(name_arm),
+    )
+}
+
+fn variant(node_type: &NodeType) -> String {
+    format!(
+        "    /// `{name}` node
+    {name},",
+        name = node_type.name
+    )
+}
+
+fn of_node_arm(node_type: &NodeType) -> String {
+    format!(
+        "            Node::{name}(_) => Self::{name},",
+        name = node_type.name
+    )
+}
+
+fn name_arm(node_type: &NodeType) -> String {
+    format!(
+        "            Self::{name} => \"{str_type}\",",
+        name = node_type.name,
+        str_type = node_type.str_type
+    )
+}
@@ -0,0 +1,512 @@
+/* Generated with cbindgen, run `make header` in `ffi/` to regenerate */
+
+#ifndef LIB_RUBY_PARSER_H
+#define LIB_RUBY_PARSER_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Version of the C API, incremented on every incompatible change of the header
+ */
+#define LRP_API_VERSION 1
+
+/**
+ * Kind of the comment
+ */
+typedef enum LrpCommentType {
+  /**
+   * `# comment`
+   */
+  LRP_COMMENT_TYPE_INLINE,
+  /**
+   * `=begin`/`=end` comment
+   */
+  LRP_COMMENT_TYPE_DOCUMENT,
+  /**
+   * Unknown comment type
+   */
+  LRP_COMMENT_TYPE_UNKNOWN,
+} LrpCommentType;
+
+/**
+ * Level of the diagnostic
+ */
+typedef enum LrpErrorLevel {
+  /**
+   * Warning
+   */
+  LRP_ERROR_LEVEL_WARNING,
+  /**
+   * Error
+   */
+  LRP_ERROR_LEVEL_ERROR,
+} LrpErrorLevel;
+
+/**
+ * Kind of the field of a node
+ */
+typedef enum LrpFieldKind {
+  /**
+   * Child node (like `recv` of `send`), `LrpField::node`
+   */
+  LRP_FIELD_KIND_NODE,
+  /**
+   * List of child nodes (like `args` of `send`), `LrpField::nodes`
+   */
+  LRP_FIELD_KIND_NODES,
+  /**
+   * Name, value of a literal or options of a regexp, `LrpField::str`
+   */
+  LRP_FIELD_KIND_STR,
+  /**
+   * Number of parameters of `numblock`, `LrpField::integer`
+   */
+  LRP_FIELD_KIND_INTEGER,
+  /**
+   * Location (like `selector_l`), `LrpField::loc`
+   */
+  LRP_FIELD_KIND_LOC,
+  /**
+   * Returned for an index that is out of range, no value is set
+   */
+  LRP_FIELD_KIND_INVALID,
+} LrpFieldKind;
+
+/**
+ * Node of the AST
+ */
+typedef struct LrpNode LrpNode;
+
+/**
+ * Parser that hasn't been run yet
+ */
+typedef struct LrpParser LrpParser;
+
+/**
+ * Result of parsing
+ */
+typedef struct LrpParserResult LrpParserResult;
+
+/**
+ * Configuration of the parser, numeric limits are disabled if set to 0
+ */
+typedef struct LrpParserOptions {
+  /**
+   * Name of the buffer used in diagnostics, NULL means `(eval)`
+   */
+  const uint8_t *buffer_name;
+  /**
+   * Length of `buffer_name`
+   */
+  size_t buffer_name_len;
+  /**
+   * See `ParserOptions::record_tokens`
+   */
+  bool record_tokens;
+  /**
+   * See `ParserOptions::record_trivia`
+   */
+  bool record_trivia;
+  /**
+   * See `ParserOptions::verbose`
+   */
+  bool verbose;
+  /**
+   * See `ParserOptions::timeout`, in milliseconds
+   */
+  uint64_t timeout_ms;
+  /**
+   * See `ParserOptions::max_depth`
+   */
+  size_t max_depth;
+  /**
+   * See `ParserOptions::max_input_size`
+   */
+  size_t max_input_size;
+  /**
+   * See `ParserOptions::max_tokens`
+   */
+  size_t max_tokens;
+} LrpParserOptions;
+
+/**
+ * Borrowed (not NUL-terminated) string or byte array
+ */
+typedef struct LrpStr {
+  /**
+   * Pointer to the first byte
+   */
+  const uint8_t *ptr;
+  /**
+   * Number of bytes
+   */
+  size_t len;
+} LrpStr;
+
+/**
+ * Byte range in the input, the end is exclusive
+ */
+typedef struct LrpLoc {
+  /**
+   * Begin of the range
+   */
+  size_t begin;
+  /**
+   * End of the range
+   */
+  size_t end;
+} LrpLoc;
+
+/**
+ * Diagnostic (error or warning)
+ */
+typedef struct LrpDiagnostic {
+  /**
+   * Level of the diagnostic
+   */
+  enum LrpErrorLevel level;
+  /**
+   * Rendered message, like `unexpected token tRPAREN`
+   */
+  struct LrpStr message;
+  /**
+   * Location of the diagnostic
+   */
+  struct LrpLoc loc;
+} LrpDiagnostic;
+
+/**
+ * Token
+ */
+typedef struct LrpToken {
+  /**
+   * Numeric type of the token
+   */
+  int32_t token_type;
+  /**
+   * Name of the token type, like `tINTEGER`
+   */
+  struct LrpStr name;
+  /**
+   * Value of the token
+   */
+  struct LrpStr value;
+  /**
+   * Location of the token
+   */
+  struct LrpLoc loc;
+} LrpToken;
+
+/**
+ * Comment
+ */
+typedef struct LrpComment {
+  /**
+   * Kind of the comment
+   */
+  enum LrpCommentType kind;
+  /**
+   * Location of the comment
+   */
+  struct LrpLoc loc;
+} LrpComment;
+
+/**
+ * Borrowed array of nodes
+ */
+typedef struct LrpNodeArray {
+  /**
+   * Pointer to the first node
+   */
+  const struct LrpNode *const *ptr;
+  /**
+   * Number of nodes
+   */
+  size_t len;
+} LrpNodeArray;
+
+/**
+ * Field of a node, only the value that matches `kind` is set
+ */
+typedef struct LrpField {
+  /**
+   * Name of the field like `recv` or `expression_l`
+   */
+  struct LrpStr name;
+  /**
+   * Kind of the field
+   */
+  enum LrpFieldKind kind;
+  /**
+   * `false` if the field is optional and absent (like `recv` of `foo()`)
+   */
+  bool present;
+  /**
+   * Child node, NULL if absent
+   */
+  const struct LrpNode *node;
+  /**
+   * Child nodes
+   */
+  struct LrpNodeArray nodes;
+  /**
+   * String value
+   */
+  struct LrpStr str;
+  /**
+   * Integer value
+   */
+  size_t integer;
+  /**
+   * Location
+   */
+  struct LrpLoc loc;
+} LrpField;
+
+/**
+ * Owned (not NUL-terminated) string, must be freed using `lrp_string_free`
+ */
+typedef struct LrpString {
+  /**
+   * Pointer to the first byte
+   */
+  uint8_t *ptr;
+  /**
+   * Number of bytes
+   */
+  size_t len;
+} LrpString;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Returns `LRP_API_VERSION` of the library
+ */
+uint32_t lrp_api_version(void);
+
+/**
+ * Returns default options (the same as `ParserOptions::default()`)
+ */
+struct LrpParserOptions lrp_parser_options_default(void);
+
+/**
+ * Constructs a parser, `input` is copied.
+ *
+ * # Safety
+ *
+ * `input` must point to `input_len` readable bytes,
+ * `options` must be NULL (to use default options) or a valid pointer.
+ */
+struct LrpParser *lrp_parser_new(const uint8_t *input,
+                                 size_t input_len,
+                                 const struct LrpParserOptions *options);
+
+/**
+ * Frees a parser that hasn't been run
+ *
+ * # Safety
+ *
+ * `parser` must be NULL or a pointer returned by `lrp_parser_new`
+ */
+void lrp_parser_free(struct LrpParser *parser);
+
+/**
+ * Runs the parser and frees it, returns the result that must be freed using `lrp_result_free`
+ *
+ * # Safety
+ *
+ * `parser` must be a pointer returned by `lrp_parser_new`,
+ * it can't be used after this call
+ */
+struct LrpParserResult *lrp_parser_do_parse(struct LrpParser *parser);
+
+/**
+ * Shortcut for `lrp_parser_new` followed by `lrp_parser_do_parse`
+ *
+ * # Safety
+ *
+ * See `lrp_parser_new`
+ */
+struct LrpParserResult *lrp_parse(const uint8_t *input,
+                                  size_t input_len,
+                                  const struct LrpParserOptions *options);
+
+/**
+ * Frees the result and everything it owns (nodes, tokens, diagnostics)
+ *
+ * # Safety
+ *
+ * `result` must be NULL or a pointer returned by `lrp_parser_do_parse`
+ */
+void lrp_result_free(struct LrpParserResult *result);
+
+/**
+ * Returns the root node of the AST, NULL if the input is empty or has fatal errors
+ *
+ * # Safety
+ *
+ * `result` must be a valid pointer returned by `lrp_parser_do_parse`
+ */
+const struct LrpNode *lrp_result_ast(const struct LrpParserResult *result);
+
+/**
+ * Returns the input after decoding
+ *
+ * # Safety
+ *
+ * `result` must be a valid pointer returned by `lrp_parser_do_parse`
+ */
+struct LrpStr lrp_result_input(const struct LrpParserResult *result);
+
+/**
+ * Returns the number of diagnostics
+ *
+ * # Safety
+ *
+ * `result` must be a valid pointer returned by `lrp_parser_do_parse`
+ */
+size_t lrp_result_diagnostics_count(const struct LrpParserResult *result);
+
+/**
+ * Returns the diagnostic at `idx`
+ *
+ * # Safety
+ *
+ * `result` must be a valid pointer returned by `lrp_parser_do_parse`,
+ * `idx` must be less than `lrp_result_diagnostics_count`
+ */
+struct LrpDiagnostic lrp_result_diagnostic_at(const struct LrpParserResult *result, size_t idx);
+
+/**
+ * Returns the number of tokens (0 if `record_tokens` is false)
+ *
+ * # Safety
+ *
+ * `result` must be a valid pointer returned by `lrp_parser_do_parse`
+ */
+size_t lrp_result_tokens_count(const struct LrpParserResult *result);
+
+/**
+ * Returns the token at `idx`
+ *
+ * # Safety
+ *
+ * `result` must be a valid pointer returned by `lrp_parser_do_parse`,
+ * `idx` must be less than `lrp_result_tokens_count`
+ */
+struct LrpToken lrp_result_token_at(const struct LrpParserResult *result, size_t idx);
+
+/**
+ * Returns the number of comments
+ *
+ * # Safety
+ *
+ * `result` must be a valid pointer returned by `lrp_parser_do_parse`
+ */
+size_t lrp_result_comments_count(const struct LrpParserResult *result);
+
+/**
+ * Returns the comment at `idx`
+ *
+ * # Safety
+ *
+ * `result` must be a valid pointer returned by `lrp_parser_do_parse`,
+ * `idx` must be less than `lrp_result_comments_count`
+ */
+struct LrpComment lrp_result_comment_at(const struct LrpParserResult *result, size_t idx);
+
+/**
+ * Returns the type of the node like `send` or `int` (the same as in `s(:send, ...)`)
+ *
+ * # Safety
+ *
+ * `node` must be a valid pointer
+ */
+struct LrpStr lrp_node_type(const struct LrpNode *node);
+
+/**
+ * Returns `expression_l` of the node
+ *
+ * # Safety
+ *
+ * `node` must be a valid pointer
+ */
+struct LrpLoc lrp_node_loc(const struct LrpNode *node);
+
+/**
+ * Returns direct children of the node in the order of `Visitor`
+ * (the same as the order of fields with nodes).
+ *
+ * The array is owned by the result, children are not collected on every call.
+ *
+ * # Safety
+ *
+ * `result` must be a valid pointer returned by `lrp_parser_do_parse`,
+ * `node` must be a valid pointer to a node of this result
+ */
+struct LrpNodeArray lrp_node_children(const struct LrpParserResult *result,
+                                      const struct LrpNode *node);
+
+/**
+ * Returns the number of fields of the node
+ * (all fields of its struct, including locations)
+ *
+ * # Safety
+ *
+ * `node` must be a valid pointer
+ */
+size_t lrp_node_fields_count(const struct LrpNode *node);
+
+/**
+ * Returns the field of the node at `idx` (in the order of fields of its struct),
+ * a field of kind `Invalid` if `idx` is not less than `lrp_node_fields_count`
+ *
+ * # Safety
+ *
+ * `result` must be a valid pointer returned by `lrp_parser_do_parse`,
+ * `node` must be a valid pointer to a node of this result
+ */
+struct LrpField lrp_node_field_at(const struct LrpParserResult *result,
+                                  const struct LrpNode *node,
+                                  size_t idx);
+
+/**
+ * Returns the index of the field with a given name (like `recv`), -1 if the node has no such field
+ *
+ * # Safety
+ *
+ * `node` must be a valid pointer,
+ * `name` must point to `name_len` readable bytes
+ */
+ptrdiff_t lrp_node_field_index(const struct LrpNode *node, const uint8_t *name, size_t name_len);
+
+/**
+ * Renders the node (and its children) like `s(:send, nil, "foo")`,
+ * returned string must be freed using `lrp_string_free`
+ *
+ * # Safety
+ *
+ * `node` must be a valid pointer
+ */
+struct LrpString lrp_node_inspect(const struct LrpNode *node);
+
+/**
+ * Frees a string returned by `lrp_node_inspect`
+ *
+ * # Safety
+ *
+ * `s` must be returned by this library and can't be freed twice
+ */
+void lrp_string_free(struct LrpString s);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* LIB_RUBY_PARSER_H */
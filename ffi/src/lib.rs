@@ -0,0 +1,719 @@
+//! C API of `lib-ruby-parser`, see `include/lib_ruby_parser.h`.
+//!
+//! All pointers returned by functions of this crate are owned by the `LrpParserResult`
+//! they come from and stay valid until `lrp_result_free` is called,
+//! the only exception is `LrpString` that must be freed using `lrp_string_free`.
+
+#![warn(missing_docs)]
+#![warn(missing_debug_implementations)]
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::Duration;
+
+use lib_ruby_parser::fields::{self, FieldValue};
+use lib_ruby_parser::source::CommentType;
+use lib_ruby_parser::{ErrorLevel, Node, Parser, ParserOptions, ParserResult};
+
+/// Version of the C API, incremented on every incompatible change of the header
+pub const LRP_API_VERSION: u32 = 1;
+
+/// Borrowed (not NUL-terminated) string or byte array
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LrpStr {
+    /// Pointer to the first byte
+    pub ptr: *const u8,
+    /// Number of bytes
+    pub len: usize,
+}
+
+impl LrpStr {
+    fn new(bytes: &[u8]) -> Self {
+        Self {
+            ptr: bytes.as_ptr(),
+            len: bytes.len(),
+        }
+    }
+}
+
+/// Owned (not NUL-terminated) string, must be freed using `lrp_string_free`
+#[repr(C)]
+#[derive(Debug)]
+pub struct LrpString {
+    /// Pointer to the first byte
+    pub ptr: *mut u8,
+    /// Number of bytes
+    pub len: usize,
+}
+
+impl From<String> for LrpString {
+    fn from(s: String) -> Self {
+        let bytes = Box::into_raw(s.into_bytes().into_boxed_slice());
+        Self {
+            ptr: bytes as *mut u8,
+            len: bytes.len(),
+        }
+    }
+}
+
+/// Byte range in the input, the end is exclusive
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LrpLoc {
+    /// Begin of the range
+    pub begin: usize,
+    /// End of the range
+    pub end: usize,
+}
+
+impl From<&lib_ruby_parser::Loc> for LrpLoc {
+    fn from(loc: &lib_ruby_parser::Loc) -> Self {
+        Self {
+            begin: loc.begin,
+            end: loc.end,
+        }
+    }
+}
+
+/// Configuration of the parser, numeric limits are disabled if set to 0
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LrpParserOptions {
+    /// Name of the buffer used in diagnostics, NULL means `(eval)`
+    pub buffer_name: *const u8,
+    /// Length of `buffer_name`
+    pub buffer_name_len: usize,
+    /// See `ParserOptions::record_tokens`
+    pub record_tokens: bool,
+    /// See `ParserOptions::record_trivia`
+    pub record_trivia: bool,
+    /// See `ParserOptions::verbose`
+    pub verbose: bool,
+    /// See `ParserOptions::timeout`, in milliseconds
+    pub timeout_ms: u64,
+    /// See `ParserOptions::max_depth`
+    pub max_depth: usize,
+    /// See `ParserOptions::max_input_size`
+    pub max_input_size: usize,
+    /// See `ParserOptions::max_tokens`
+    pub max_tokens: usize,
+}
+
+/// Level of the diagnostic
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LrpErrorLevel {
+    /// Warning
+    Warning,
+    /// Error
+    Error,
+}
+
+/// Diagnostic (error or warning)
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LrpDiagnostic {
+    /// Level of the diagnostic
+    pub level: LrpErrorLevel,
+    /// Rendered message, like `unexpected token tRPAREN`
+    pub message: LrpStr,
+    /// Location of the diagnostic
+    pub loc: LrpLoc,
+}
+
+/// Token
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LrpToken {
+    /// Numeric type of the token
+    pub token_type: i32,
+    /// Name of the token type, like `tINTEGER`
+    pub name: LrpStr,
+    /// Value of the token
+    pub value: LrpStr,
+    /// Location of the token
+    pub loc: LrpLoc,
+}
+
+/// Kind of the comment
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LrpCommentType {
+    /// `# comment`
+    Inline,
+    /// `=begin`/`=end` comment
+    Document,
+    /// Unknown comment type
+    Unknown,
+}
+
+/// Comment
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LrpComment {
+    /// Kind of the comment
+    pub kind: LrpCommentType,
+    /// Location of the comment
+    pub loc: LrpLoc,
+}
+
+/// Borrowed array of nodes
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LrpNodeArray {
+    /// Pointer to the first node
+    pub ptr: *const *const LrpNode,
+    /// Number of nodes
+    pub len: usize,
+}
+
+impl LrpNodeArray {
+    fn new(nodes: &[*const LrpNode]) -> Self {
+        Self {
+            ptr: nodes.as_ptr(),
+            len: nodes.len(),
+        }
+    }
+}
+
+/// Kind of the field of a node
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LrpFieldKind {
+    /// Child node (like `recv` of `send`), `LrpField::node`
+    Node,
+    /// List of child nodes (like `args` of `send`), `LrpField::nodes`
+    Nodes,
+    /// Name, value of a literal or options of a regexp, `LrpField::str`
+    Str,
+    /// Number of parameters of `numblock`, `LrpField::integer`
+    Integer,
+    /// Location (like `selector_l`), `LrpField::loc`
+    Loc,
+    /// Returned for an index that is out of range, no value is set
+    Invalid,
+}
+
+/// Field of a node, only the value that matches `kind` is set
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LrpField {
+    /// Name of the field like `recv` or `expression_l`
+    pub name: LrpStr,
+    /// Kind of the field
+    pub kind: LrpFieldKind,
+    /// `false` if the field is optional and absent (like `recv` of `foo()`)
+    pub present: bool,
+    /// Child node, NULL if absent
+    pub node: *const LrpNode,
+    /// Child nodes
+    pub nodes: LrpNodeArray,
+    /// String value
+    pub str: LrpStr,
+    /// Integer value
+    pub integer: usize,
+    /// Location
+    pub loc: LrpLoc,
+}
+
+impl LrpField {
+    fn new(name: &'static str, kind: LrpFieldKind) -> Self {
+        Self {
+            name: LrpStr::new(name.as_bytes()),
+            kind,
+            present: true,
+            node: std::ptr::null(),
+            nodes: LrpNodeArray::new(&[]),
+            str: LrpStr::new(&[]),
+            integer: 0,
+            loc: LrpLoc { begin: 0, end: 0 },
+        }
+    }
+}
+
+/// Parser that hasn't been run yet
+#[derive(Debug)]
+pub struct LrpParser {
+    parser: Parser,
+}
+
+/// Result of parsing
+#[derive(Debug)]
+pub struct LrpParserResult {
+    result: ParserResult,
+    // rendered messages of diagnostics
+    messages: Vec<String>,
+    children: Children,
+}
+
+// Direct children of all nodes, collected once after parsing,
+// so C code can traverse the AST without allocations
+#[derive(Debug, Default)]
+struct Children {
+    // children of every node are stored next to each other,
+    // in the order of fields
+    nodes: Vec<*const LrpNode>,
+    ranges: HashMap<*const Node, Range<usize>>,
+}
+
+impl Children {
+    fn new(ast: Option<&Node>) -> Self {
+        let mut children = Self::default();
+        let mut stack = ast.into_iter().collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            let start = children.nodes.len();
+            for (_, value) in fields::fields(node) {
+                let nodes = match value {
+                    FieldValue::Node(child) | FieldValue::MaybeNode(Some(child)) => {
+                        std::slice::from_ref(child)
+                    }
+                    FieldValue::Nodes(nodes) => nodes,
+                    _ => continue,
+                };
+                children.nodes.extend(nodes.iter().map(LrpNode::from_node));
+                stack.extend(nodes);
+            }
+            children.ranges.insert(node, start..children.nodes.len());
+        }
+        children
+    }
+
+    fn of(&self, node: &Node) -> &[*const LrpNode] {
+        match self.ranges.get(&(node as *const Node)) {
+            Some(range) => &self.nodes[range.clone()],
+            None => &[],
+        }
+    }
+}
+
+/// Node of the AST
+// Opaque for C, pointers to it are pointers to `Node`
+#[derive(Debug)]
+pub struct LrpNode {
+    _private: [u8; 0],
+}
+
+impl LrpNode {
+    fn from_node(node: &Node) -> *const LrpNode {
+        node as *const Node as *const LrpNode
+    }
+
+    unsafe fn as_node<'a>(node: *const LrpNode) -> &'a Node {
+        &*(node as *const Node)
+    }
+}
+
+/// Returns `LRP_API_VERSION` of the library
+#[no_mangle]
+pub extern "C" fn lrp_api_version() -> u32 {
+    LRP_API_VERSION
+}
+
+/// Returns default options (the same as `ParserOptions::default()`)
+#[no_mangle]
+pub extern "C" fn lrp_parser_options_default() -> LrpParserOptions {
+    let options = ParserOptions::default();
+    LrpParserOptions {
+        buffer_name: std::ptr::null(),
+        buffer_name_len: 0,
+        record_tokens: options.record_tokens,
+        record_trivia: options.record_trivia,
+        verbose: options.verbose,
+        timeout_ms: 0,
+        max_depth: 0,
+        max_input_size: 0,
+        max_tokens: 0,
+    }
+}
+
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> &'a [u8] {
+    if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ptr, len)
+    }
+}
+
+fn non_zero<T: PartialEq + Default>(value: T) -> Option<T> {
+    if value == T::default() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Constructs a parser, `input` is copied.
+///
+/// # Safety
+///
+/// `input` must point to `input_len` readable bytes,
+/// `options` must be NULL (to use default options) or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn lrp_parser_new(
+    input: *const u8,
+    input_len: usize,
+    options: *const LrpParserOptions,
+) -> *mut LrpParser {
+    let options = options
+        .as_ref()
+        .copied()
+        .unwrap_or_else(|| lrp_parser_options_default());
+    let mut parser_options = ParserOptions {
+        record_tokens: options.record_tokens,
+        record_trivia: options.record_trivia,
+        verbose: options.verbose,
+        timeout: non_zero(options.timeout_ms).map(Duration::from_millis),
+        max_depth: non_zero(options.max_depth),
+        max_input_size: non_zero(options.max_input_size),
+        max_tokens: non_zero(options.max_tokens),
+        ..Default::default()
+    };
+    if !options.buffer_name.is_null() {
+        let name = bytes(options.buffer_name, options.buffer_name_len);
+        parser_options.buffer_name = String::from_utf8_lossy(name).into_owned();
+    }
+
+    let parser = Parser::new(bytes(input, input_len), parser_options);
+    Box::into_raw(Box::new(LrpParser { parser }))
+}
+
+/// Frees a parser that hasn't been run
+///
+/// # Safety
+///
+/// `parser` must be NULL or a pointer returned by `lrp_parser_new`
+#[no_mangle]
+pub unsafe extern "C" fn lrp_parser_free(parser: *mut LrpParser) {
+    if !parser.is_null() {
+        drop(Box::from_raw(parser))
+    }
+}
+
+/// Runs the parser and frees it, returns the result that must be freed using `lrp_result_free`
+///
+/// # Safety
+///
+/// `parser` must be a pointer returned by `lrp_parser_new`,
+/// it can't be used after this call
+#[no_mangle]
+pub unsafe extern "C" fn lrp_parser_do_parse(parser: *mut LrpParser) -> *mut LrpParserResult {
+    let LrpParser { parser } = *Box::from_raw(parser);
+    let result = parser.do_parse();
+    let messages = result
+        .diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.render_message())
+        .collect();
+    let children = Children::new(result.ast.as_deref());
+    Box::into_raw(Box::new(LrpParserResult {
+        result,
+        messages,
+        children,
+    }))
+}
+
+/// Shortcut for `lrp_parser_new` followed by `lrp_parser_do_parse`
+///
+/// # Safety
+///
+/// See `lrp_parser_new`
+#[no_mangle]
+pub unsafe extern "C" fn lrp_parse(
+    input: *const u8,
+    input_len: usize,
+    options: *const LrpParserOptions,
+) -> *mut LrpParserResult {
+    lrp_parser_do_parse(lrp_parser_new(input, input_len, options))
+}
+
+/// Frees the result and everything it owns (nodes, tokens, diagnostics)
+///
+/// # Safety
+///
+/// `result` must be NULL or a pointer returned by `lrp_parser_do_parse`
+#[no_mangle]
+pub unsafe extern "C" fn lrp_result_free(result: *mut LrpParserResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result))
+    }
+}
+
+/// Returns the root node of the AST, NULL if the input is empty or has fatal errors
+///
+/// # Safety
+///
+/// `result` must be a valid pointer returned by `lrp_parser_do_parse`
+#[no_mangle]
+pub unsafe extern "C" fn lrp_result_ast(result: *const LrpParserResult) -> *const LrpNode {
+    match (*result).result.ast.as_deref() {
+        Some(node) => LrpNode::from_node(node),
+        None => std::ptr::null(),
+    }
+}
+
+/// Returns the input after decoding
+///
+/// # Safety
+///
+/// `result` must be a valid pointer returned by `lrp_parser_do_parse`
+#[no_mangle]
+pub unsafe extern "C" fn lrp_result_input(result: *const LrpParserResult) -> LrpStr {
+    LrpStr::new((*result).result.input.as_shared_bytes())
+}
+
+/// Returns the number of diagnostics
+///
+/// # Safety
+///
+/// `result` must be a valid pointer returned by `lrp_parser_do_parse`
+#[no_mangle]
+pub unsafe extern "C" fn lrp_result_diagnostics_count(result: *const LrpParserResult) -> usize {
+    (*result).result.diagnostics.len()
+}
+
+/// Returns the diagnostic at `idx`
+///
+/// # Safety
+///
+/// `result` must be a valid pointer returned by `lrp_parser_do_parse`,
+/// `idx` must be less than `lrp_result_diagnostics_count`
+#[no_mangle]
+pub unsafe extern "C" fn lrp_result_diagnostic_at(
+    result: *const LrpParserResult,
+    idx: usize,
+) -> LrpDiagnostic {
+    let result = &*result;
+    let diagnostic = &result.result.diagnostics[idx];
+    LrpDiagnostic {
+        level: match diagnostic.level {
+            ErrorLevel::Warning => LrpErrorLevel::Warning,
+            ErrorLevel::Error => LrpErrorLevel::Error,
+        },
+        message: LrpStr::new(result.messages[idx].as_bytes()),
+        loc: LrpLoc::from(&diagnostic.loc),
+    }
+}
+
+/// Returns the number of tokens (0 if `record_tokens` is false)
+///
+/// # Safety
+///
+/// `result` must be a valid pointer returned by `lrp_parser_do_parse`
+#[no_mangle]
+pub unsafe extern "C" fn lrp_result_tokens_count(result: *const LrpParserResult) -> usize {
+    (*result).result.tokens.len()
+}
+
+/// Returns the token at `idx`
+///
+/// # Safety
+///
+/// `result` must be a valid pointer returned by `lrp_parser_do_parse`,
+/// `idx` must be less than `lrp_result_tokens_count`
+#[no_mangle]
+pub unsafe extern "C" fn lrp_result_token_at(
+    result: *const LrpParserResult,
+    idx: usize,
+) -> LrpToken {
+    let result = &*result;
+    let token = &result.result.tokens[idx];
+    LrpToken {
+        token_type: token.token_type,
+        name: LrpStr::new(token.token_name().as_bytes()),
+        value: LrpStr::new(token.as_bytes()),
+        loc: LrpLoc::from(&token.loc),
+    }
+}
+
+/// Returns the number of comments
+///
+/// # Safety
+///
+/// `result` must be a valid pointer returned by `lrp_parser_do_parse`
+#[no_mangle]
+pub unsafe extern "C" fn lrp_result_comments_count(result: *const LrpParserResult) -> usize {
+    (*result).result.comments.len()
+}
+
+/// Returns the comment at `idx`
+///
+/// # Safety
+///
+/// `result` must be a valid pointer returned by `lrp_parser_do_parse`,
+/// `idx` must be less than `lrp_result_comments_count`
+#[no_mangle]
+pub unsafe extern "C" fn lrp_result_comment_at(
+    result: *const LrpParserResult,
+    idx: usize,
+) -> LrpComment {
+    let result = &*result;
+    let comment = &result.result.comments[idx];
+    LrpComment {
+        kind: match comment.kind {
+            CommentType::Inline => LrpCommentType::Inline,
+            CommentType::Document => LrpCommentType::Document,
+            CommentType::Unknown => LrpCommentType::Unknown,
+        },
+        loc: LrpLoc::from(&comment.location),
+    }
+}
+
+/// Returns the type of the node like `send` or `int` (the same as in `s(:send, ...)`)
+///
+/// # Safety
+///
+/// `node` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn lrp_node_type(node: *const LrpNode) -> LrpStr {
+    LrpStr::new(LrpNode::as_node(node).str_type().as_bytes())
+}
+
+/// Returns `expression_l` of the node
+///
+/// # Safety
+///
+/// `node` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn lrp_node_loc(node: *const LrpNode) -> LrpLoc {
+    LrpLoc::from(LrpNode::as_node(node).expression())
+}
+
+/// Returns direct children of the node in the order of `Visitor`
+/// (the same as the order of fields with nodes).
+///
+/// The array is owned by the result, children are not collected on every call.
+///
+/// # Safety
+///
+/// `result` must be a valid pointer returned by `lrp_parser_do_parse`,
+/// `node` must be a valid pointer to a node of this result
+#[no_mangle]
+pub unsafe extern "C" fn lrp_node_children(
+    result: *const LrpParserResult,
+    node: *const LrpNode,
+) -> LrpNodeArray {
+    LrpNodeArray::new((*result).children.of(LrpNode::as_node(node)))
+}
+
+/// Returns the number of fields of the node
+/// (all fields of its struct, including locations)
+///
+/// # Safety
+///
+/// `node` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn lrp_node_fields_count(node: *const LrpNode) -> usize {
+    fields::field_names(LrpNode::as_node(node)).len()
+}
+
+/// Returns the field of the node at `idx` (in the order of fields of its struct),
+/// a field of kind `Invalid` if `idx` is not less than `lrp_node_fields_count`
+///
+/// # Safety
+///
+/// `result` must be a valid pointer returned by `lrp_parser_do_parse`,
+/// `node` must be a valid pointer to a node of this result
+#[no_mangle]
+pub unsafe extern "C" fn lrp_node_field_at(
+    result: *const LrpParserResult,
+    node: *const LrpNode,
+    idx: usize,
+) -> LrpField {
+    let node = LrpNode::as_node(node);
+    let value = match fields::field_value(node, idx) {
+        Some(value) => value,
+        None => {
+            let mut field = LrpField::new("", LrpFieldKind::Invalid);
+            field.present = false;
+            return field;
+        }
+    };
+    let name = fields::field_names(node)[idx];
+
+    let kind = match value {
+        FieldValue::Node(_) | FieldValue::MaybeNode(_) => LrpFieldKind::Node,
+        FieldValue::Nodes(_) => LrpFieldKind::Nodes,
+        FieldValue::Str(_) | FieldValue::MaybeStr(_) | FieldValue::Bytes(_) => LrpFieldKind::Str,
+        FieldValue::U8(_) => LrpFieldKind::Integer,
+        FieldValue::Loc(_) | FieldValue::MaybeLoc(_) => LrpFieldKind::Loc,
+    };
+    let mut field = LrpField::new(name, kind);
+
+    match value {
+        FieldValue::Node(child) => field.node = LrpNode::from_node(child),
+        FieldValue::MaybeNode(child) => match child {
+            Some(child) => field.node = LrpNode::from_node(child),
+            None => field.present = false,
+        },
+        FieldValue::Nodes(nodes) => {
+            // lists of nodes are stored in the array of children, after preceding child nodes
+            let offset = (0..idx)
+                .map(|idx| match fields::field_value(node, idx) {
+                    Some(FieldValue::Node(_)) | Some(FieldValue::MaybeNode(Some(_))) => 1,
+                    Some(FieldValue::Nodes(nodes)) => nodes.len(),
+                    _ => 0,
+                })
+                .sum::<usize>();
+            let children = (*result).children.of(node);
+            field.nodes = LrpNodeArray::new(&children[offset..offset + nodes.len()]);
+        }
+        FieldValue::Str(s) => field.str = LrpStr::new(s.as_bytes()),
+        FieldValue::MaybeStr(s) => match s {
+            Some(s) => field.str = LrpStr::new(s.as_bytes()),
+            None => field.present = false,
+        },
+        FieldValue::Bytes(bytes) => field.str = LrpStr::new(bytes.as_raw()),
+        FieldValue::U8(n) => field.integer = usize::from(n),
+        FieldValue::Loc(loc) => field.loc = LrpLoc::from(&loc),
+        FieldValue::MaybeLoc(loc) => match loc {
+            Some(loc) => field.loc = LrpLoc::from(&loc),
+            None => field.present = false,
+        },
+    }
+    field
+}
+
+/// Returns the index of the field with a given name (like `recv`), -1 if the node has no such field
+///
+/// # Safety
+///
+/// `node` must be a valid pointer,
+/// `name` must point to `name_len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn lrp_node_field_index(
+    node: *const LrpNode,
+    name: *const u8,
+    name_len: usize,
+) -> isize {
+    let name = bytes(name, name_len);
+    fields::field_names(LrpNode::as_node(node))
+        .iter()
+        .position(|field| field.as_bytes() == name)
+        .map_or(-1, |idx| idx as isize)
+}
+
+/// Renders the node (and its children) like `s(:send, nil, "foo")`,
+/// returned string must be freed using `lrp_string_free`
+///
+/// # Safety
+///
+/// `node` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn lrp_node_inspect(node: *const LrpNode) -> LrpString {
+    LrpString::from(LrpNode::as_node(node).inspect(0))
+}
+
+/// Frees a string returned by `lrp_node_inspect`
+///
+/// # Safety
+///
+/// `s` must be returned by this library and can't be freed twice
+#[no_mangle]
+pub unsafe extern "C" fn lrp_string_free(s: LrpString) {
+    if !s.ptr.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+            s.ptr, s.len,
+        )))
+    }
+}
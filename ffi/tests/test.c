@@ -0,0 +1,190 @@
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "lib_ruby_parser.h"
+
+static int str_eq(LrpStr s, const char *expected)
+{
+    return s.len == strlen(expected) && memcmp(s.ptr, expected, s.len) == 0;
+}
+
+static LrpParserResult *parse(const char *code, const LrpParserOptions *options)
+{
+    return lrp_parse((const uint8_t *)code, strlen(code), options);
+}
+
+static void print_node(const LrpParserResult *result, const LrpNode *node, int depth)
+{
+    LrpStr type = lrp_node_type(node);
+    LrpLoc loc = lrp_node_loc(node);
+    printf("%*s%.*s %zu...%zu\n", depth * 2, "", (int)type.len, type.ptr, loc.begin, loc.end);
+
+    LrpNodeArray children = lrp_node_children(result, node);
+    for (size_t i = 0; i < children.len; i++)
+    {
+        print_node(result, children.ptr[i], depth + 1);
+    }
+}
+
+static LrpField field(const LrpParserResult *result, const LrpNode *node, const char *name)
+{
+    intptr_t idx = lrp_node_field_index(node, (const uint8_t *)name, strlen(name));
+    assert(idx >= 0);
+    LrpField field = lrp_node_field_at(result, node, (size_t)idx);
+    assert(str_eq(field.name, name));
+    return field;
+}
+
+static void test_version(void)
+{
+    assert(lrp_api_version() == LRP_API_VERSION);
+}
+
+static void test_ast(void)
+{
+    LrpParserResult *result = parse("foo(1, bar) # comment", NULL);
+
+    const LrpNode *ast = lrp_result_ast(result);
+    assert(ast != NULL);
+    print_node(result, ast, 0);
+
+    assert(str_eq(lrp_node_type(ast), "send"));
+    assert(lrp_node_loc(ast).begin == 0);
+    assert(lrp_node_loc(ast).end == 11);
+    LrpNodeArray children = lrp_node_children(result, ast);
+    assert(children.len == 2);
+    assert(str_eq(lrp_node_type(children.ptr[0]), "int"));
+    assert(str_eq(lrp_node_type(children.ptr[1]), "send"));
+    // the same array is returned on every call
+    assert(lrp_node_children(result, ast).ptr == children.ptr);
+
+    LrpString inspected = lrp_node_inspect(children.ptr[0]);
+    assert(inspected.len == strlen("s(:int, \"1\")"));
+    assert(memcmp(inspected.ptr, "s(:int, \"1\")", inspected.len) == 0);
+    lrp_string_free(inspected);
+
+    assert(lrp_result_comments_count(result) == 1);
+    LrpComment comment = lrp_result_comment_at(result, 0);
+    assert(comment.kind == LRP_COMMENT_TYPE_INLINE);
+    assert(comment.loc.begin == 12);
+
+    assert(str_eq(lrp_result_input(result), "foo(1, bar) # comment"));
+    assert(lrp_result_diagnostics_count(result) == 0);
+
+    lrp_result_free(result);
+}
+
+static void test_fields(void)
+{
+    LrpParserResult *result = parse("foo(1, bar)", NULL);
+    const LrpNode *ast = lrp_result_ast(result);
+    LrpNodeArray children = lrp_node_children(result, ast);
+
+    assert(lrp_node_fields_count(ast) == 9);
+    assert(lrp_node_field_index(ast, (const uint8_t *)"value", 5) == -1);
+
+    LrpField recv = field(result, ast, "recv");
+    assert(recv.kind == LRP_FIELD_KIND_NODE);
+    assert(!recv.present);
+    assert(recv.node == NULL);
+
+    LrpField method_name = field(result, ast, "method_name");
+    assert(method_name.kind == LRP_FIELD_KIND_STR);
+    assert(str_eq(method_name.str, "foo"));
+
+    LrpField args = field(result, ast, "args");
+    assert(args.kind == LRP_FIELD_KIND_NODES);
+    assert(args.nodes.len == 2);
+    assert(args.nodes.ptr == children.ptr);
+
+    LrpField dot_l = field(result, ast, "dot_l");
+    assert(dot_l.kind == LRP_FIELD_KIND_LOC);
+    assert(!dot_l.present);
+
+    LrpField begin_l = field(result, ast, "begin_l");
+    assert(begin_l.present);
+    assert(begin_l.loc.begin == 3);
+    assert(begin_l.loc.end == 4);
+
+    LrpField value = field(result, args.nodes.ptr[0], "value");
+    assert(str_eq(value.str, "1"));
+
+    LrpField invalid = lrp_node_field_at(result, ast, lrp_node_fields_count(ast));
+    assert(invalid.kind == LRP_FIELD_KIND_INVALID);
+    assert(!invalid.present);
+
+    lrp_result_free(result);
+}
+
+static void test_tokens(void)
+{
+    LrpParserOptions options = lrp_parser_options_default();
+    options.record_tokens = true;
+    LrpParserResult *result = parse("42", &options);
+
+    // tINTEGER + EOF
+    assert(lrp_result_tokens_count(result) == 2);
+    LrpToken token = lrp_result_token_at(result, 0);
+    assert(str_eq(token.name, "tINTEGER"));
+    assert(str_eq(token.value, "42"));
+    assert(token.loc.begin == 0);
+    assert(token.loc.end == 2);
+
+    lrp_result_free(result);
+}
+
+static void test_diagnostics(void)
+{
+    const char *buffer_name = "(test)";
+    LrpParserOptions options = lrp_parser_options_default();
+    options.buffer_name = (const uint8_t *)buffer_name;
+    options.buffer_name_len = strlen(buffer_name);
+
+    LrpParserResult *result = parse("foo(", &options);
+
+    assert(lrp_result_ast(result) == NULL);
+    assert(lrp_result_diagnostics_count(result) > 0);
+    LrpDiagnostic diagnostic = lrp_result_diagnostic_at(result, 0);
+    assert(diagnostic.level == LRP_ERROR_LEVEL_ERROR);
+    printf("%.*s\n", (int)diagnostic.message.len, diagnostic.message.ptr);
+
+    lrp_result_free(result);
+}
+
+static void test_limits(void)
+{
+    LrpParserOptions options = lrp_parser_options_default();
+    options.max_depth = 10;
+
+    LrpParserResult *result = parse("[[[[[[[[[[[[[[[[[[[[1]]]]]]]]]]]]]]]]]]]]", &options);
+
+    assert(lrp_result_ast(result) == NULL);
+    assert(lrp_result_diagnostics_count(result) == 1);
+    LrpDiagnostic diagnostic = lrp_result_diagnostic_at(result, 0);
+    assert(str_eq(diagnostic.message, "nesting is too deep (more than 10 levels)"));
+
+    lrp_result_free(result);
+}
+
+static void test_free_unused_parser(void)
+{
+    LrpParser *parser = lrp_parser_new((const uint8_t *)"1", 1, NULL);
+    lrp_parser_free(parser);
+    lrp_parser_free(NULL);
+    lrp_result_free(NULL);
+}
+
+int main(void)
+{
+    test_version();
+    test_ast();
+    test_fields();
+    test_tokens();
+    test_diagnostics();
+    test_limits();
+    test_free_unused_parser();
+
+    printf("OK\n");
+    return 0;
+}
@@ -7,6 +7,8 @@ fuzz_target!(|source: &[u8]| {
     let options = ParserOptions {
         buffer_name: String::from("(eval)"),
         record_tokens: false,
+        max_depth: Some(1_000),
+        max_tokens: Some(100_000),
         ..Default::default()
     };
     Parser::new(source, options).do_parse();
@@ -0,0 +1,61 @@
+// Generates the list of node classes (see `node_classes!` in `src/nodes.rs`)
+// from the table of node types that is used by codegen of the main crate.
+
+#[allow(dead_code)]
+#[path = "../codegen/rust/node_types.rs"]
+mod node_types;
+use node_types::*;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=../codegen/rust/node_types.rs");
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let path = std::path::Path::new(&out_dir).join("nodes_gen.rs");
+    std::fs::write(path, contents()).unwrap();
+}
+
+fn contents() -> String {
+    format!(
+        "// This file is autogenerated by build.rs
+
+node_classes! {{
+{classes}
+}}
+",
+        classes = map_node_types(class)
+    )
+}
+
+// `expression_l` is not listed because it's stored in the base class
+fn class(node_type: &NodeType) -> String {
+    let fields = node_type
+        .fields
+        .iter()
+        .filter(|field| field.name != "expression_l")
+        .map(|field| {
+            let kind = match field.kind {
+                Node => "Node",
+                MaybeNode => "MaybeNode",
+                Nodes => "Nodes",
+                Str => "Str",
+                MaybeStr | Chars => "MaybeStr",
+                StringValue => "Bytes",
+                U8 => "U8",
+                Loc => "Loc",
+                MaybeLoc => "MaybeLoc",
+            };
+            format!("        {}: {},\n", field.name, kind)
+        })
+        .collect::<String>();
+
+    format!(
+        "    /// `{str_type}` node, see `lib_ruby_parser::nodes::{name}`
+    {name}(\"{str_type}\", on_{snake}) {{
+{fields}    }},",
+        name = node_type.name,
+        str_type = node_type.str_type,
+        snake = node_type.snake,
+        fields = fields
+    )
+}
@@ -0,0 +1,113 @@
+//! Python bindings of `lib-ruby-parser`.
+//!
+//! `parse(source, buffer_name="(eval)", record_tokens=True)` returns a `ParserResult`
+//! with the AST converted to Python objects (a subclass of `Node` for every node type),
+//! tokens, diagnostics, comments and magic comments.
+
+#![warn(missing_debug_implementations)]
+// false positive in code generated by `#[pyfunction]`/`#[pymethods]`
+#![allow(clippy::useless_conversion)]
+
+use lib_ruby_parser::{Parser, ParserOptions};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+
+mod nodes;
+mod types;
+mod visitor;
+
+use types::{Comment, Diagnostic, MagicComment, Token};
+
+/// Result of `parse`
+#[pyclass(frozen, module = "lib_ruby_parser")]
+#[derive(Debug)]
+pub struct ParserResult {
+    /// Root node of the AST, `None` if the input is empty or has fatal errors
+    #[pyo3(get)]
+    ast: Option<PyObject>,
+    /// List of tokens, empty if `record_tokens` is `False`
+    #[pyo3(get)]
+    tokens: Vec<Py<Token>>,
+    /// List of errors and warnings
+    #[pyo3(get)]
+    diagnostics: Vec<Py<Diagnostic>>,
+    /// List of comments
+    #[pyo3(get)]
+    comments: Vec<Py<Comment>>,
+    /// List of magic comments
+    #[pyo3(get)]
+    magic_comments: Vec<Py<MagicComment>>,
+    /// Input after decoding, locations are byte offsets in it
+    #[pyo3(get)]
+    input: Py<PyBytes>,
+}
+
+/// Parses `source` (str or bytes) and returns a `ParserResult`
+#[pyfunction]
+#[pyo3(signature = (source, buffer_name = "(eval)", record_tokens = true))]
+fn parse(
+    py: Python<'_>,
+    source: &Bound<'_, PyAny>,
+    buffer_name: &str,
+    record_tokens: bool,
+) -> PyResult<ParserResult> {
+    let source = match source.downcast::<PyString>() {
+        Ok(source) => source.to_str()?.as_bytes().to_vec(),
+        Err(_) => source.downcast::<PyBytes>()?.as_bytes().to_vec(),
+    };
+    let buffer_name = buffer_name.to_string();
+
+    // `ParserOptions` are not `Send`, so they are constructed in the closure
+    let result = py.allow_threads(move || {
+        let options = ParserOptions {
+            buffer_name,
+            record_tokens,
+            ..Default::default()
+        };
+        Parser::new(source, options).do_parse()
+    });
+
+    let ast = match result.ast.as_deref() {
+        Some(ast) => Some(nodes::convert(py, ast)?),
+        None => None,
+    };
+    Ok(ParserResult {
+        ast,
+        tokens: result
+            .tokens
+            .iter()
+            .map(|token| Py::new(py, Token::new(py, token)))
+            .collect::<PyResult<_>>()?,
+        diagnostics: result
+            .diagnostics
+            .iter()
+            .map(|diagnostic| Py::new(py, Diagnostic::new(diagnostic, &result.input)))
+            .collect::<PyResult<_>>()?,
+        comments: result
+            .comments
+            .iter()
+            .map(|comment| Py::new(py, Comment::from(comment)))
+            .collect::<PyResult<_>>()?,
+        magic_comments: result
+            .magic_comments
+            .iter()
+            .map(|magic_comment| Py::new(py, MagicComment::from(magic_comment)))
+            .collect::<PyResult<_>>()?,
+        input: PyBytes::new_bound(py, result.input.as_shared_bytes()).unbind(),
+    })
+}
+
+#[pymodule]
+#[pyo3(name = "lib_ruby_parser")]
+fn init_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(parse, m)?)?;
+    m.add_class::<ParserResult>()?;
+    m.add_class::<types::Loc>()?;
+    m.add_class::<Token>()?;
+    m.add_class::<Diagnostic>()?;
+    m.add_class::<Comment>()?;
+    m.add_class::<MagicComment>()?;
+    m.add_class::<visitor::Visitor>()?;
+    nodes::add_classes(m)?;
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use lib_ruby_parser::Node as RustNode;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::types::Loc;
+
+/// Base class of all nodes
+#[pyclass(frozen, subclass, module = "lib_ruby_parser")]
+#[derive(Debug)]
+pub struct Node {
+    /// Type of the node, the same as in `s(:send, ...)`
+    #[pyo3(get, name = "type")]
+    pub node_type: &'static str,
+    /// Name of the `Visitor` method, like `on_send`
+    pub visit_method: &'static str,
+    /// Location of the whole node
+    #[pyo3(get)]
+    pub expression_l: Loc,
+    // child nodes in the order of `Visitor`
+    children: Vec<PyObject>,
+}
+
+#[pymethods]
+impl Node {
+    /// Returns direct child nodes in the order of `Visitor`
+    fn children(&self, py: Python<'_>) -> Vec<PyObject> {
+        self.children
+            .iter()
+            .map(|child| child.clone_ref(py))
+            .collect()
+    }
+
+    fn __repr__(slf: &Bound<'_, Self>) -> PyResult<String> {
+        let node = slf.get();
+        Ok(format!(
+            "<{} {}...{}>",
+            slf.get_type().qualname()?,
+            node.expression_l.begin,
+            node.expression_l.end
+        ))
+    }
+}
+
+impl Node {
+    pub(crate) fn children_ref(&self) -> &[PyObject] {
+        &self.children
+    }
+}
+
+// Python type of a field of the given kind
+macro_rules! py_type {
+    (Node) => { PyObject };
+    (MaybeNode) => { Option<PyObject> };
+    (Nodes) => { Vec<PyObject> };
+    (Str) => { String };
+    (MaybeStr) => { Option<String> };
+    (Bytes) => { Py<PyBytes> };
+    (U8) => { u8 };
+    (Loc) => { Loc };
+    (MaybeLoc) => { Option<Loc> };
+}
+
+// Converts a field of the given kind, child nodes are also pushed to `$children`
+macro_rules! convert_field {
+    ($py:ident, $children:ident, Node, $value:expr) => {{
+        let node = convert($py, $value)?;
+        $children.push(node.clone_ref($py));
+        node
+    }};
+    ($py:ident, $children:ident, MaybeNode, $value:expr) => {
+        match $value {
+            Some(node) => Some(convert_field!($py, $children, Node, node)),
+            None => None,
+        }
+    };
+    ($py:ident, $children:ident, Nodes, $value:expr) => {
+        $value
+            .iter()
+            .map(|node| Ok(convert_field!($py, $children, Node, node)))
+            .collect::<PyResult<Vec<_>>>()?
+    };
+    ($py:ident, $children:ident, Str, $value:expr) => {
+        $value.clone()
+    };
+    ($py:ident, $children:ident, MaybeStr, $value:expr) => {
+        $value.clone()
+    };
+    ($py:ident, $children:ident, Bytes, $value:expr) => {
+        PyBytes::new_bound($py, $value.as_raw()).unbind()
+    };
+    ($py:ident, $children:ident, U8, $value:expr) => {
+        *$value
+    };
+    ($py:ident, $children:ident, Loc, $value:expr) => {
+        Loc::from($value)
+    };
+    ($py:ident, $children:ident, MaybeLoc, $value:expr) => {
+        $value.as_ref().map(Loc::from)
+    };
+}
+
+// Defines a subclass of `Node` for every node type,
+// `expression_l` is not listed because it's stored in `Node`
+macro_rules! node_classes {
+    ($(
+        $(#[$meta:meta])*
+        $name:ident($str_type:literal, $visit_method:ident) {
+            $($field:ident: $kind:ident,)*
+        }
+    ),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            #[pyclass(frozen, extends = Node, module = "lib_ruby_parser")]
+            #[derive(Debug)]
+            pub struct $name {
+                $(
+                    #[pyo3(get)]
+                    pub $field: py_type!($kind),
+                )*
+            }
+        )*
+
+        pub(crate) fn add_classes(m: &Bound<'_, PyModule>) -> PyResult<()> {
+            m.add_class::<Node>()?;
+            $(m.add_class::<$name>()?;)*
+            Ok(())
+        }
+
+        /// Converts a Rust node (with all its children) to a Python object
+        pub(crate) fn convert(py: Python<'_>, node: &RustNode) -> PyResult<PyObject> {
+            match node {
+                $(
+                    RustNode::$name(inner) => {
+                        #[allow(unused_mut)]
+                        let mut children: Vec<PyObject> = vec![];
+                        $(let $field = convert_field!(py, children, $kind, &inner.$field);)*
+                        let base = Node {
+                            node_type: $str_type,
+                            visit_method: stringify!($visit_method),
+                            expression_l: Loc::from(&inner.expression_l),
+                            children,
+                        };
+                        let node = $name { $($field),* };
+                        let init = PyClassInitializer::from(base).add_subclass(node);
+                        Ok(Py::new(py, init)?.into_any())
+                    }
+                )*
+            }
+        }
+    };
+}
+
+// Node types and their fields, generated by `build.rs` from the table in `codegen/rust/node_types.rs`
+include!(concat!(env!("OUT_DIR"), "/nodes_gen.rs"));
@@ -0,0 +1,215 @@
+use lib_ruby_parser::source::{Comment as RustComment, CommentType};
+use lib_ruby_parser::source::{MagicComment as RustMagicComment, MagicCommentKind};
+use lib_ruby_parser::{Diagnostic as RustDiagnostic, ErrorLevel, Token as RustToken};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+/// Byte range in the input, `end` is exclusive
+#[pyclass(frozen, eq, hash, module = "lib_ruby_parser")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Loc {
+    /// Begin of the range
+    #[pyo3(get)]
+    pub begin: usize,
+    /// End of the range
+    #[pyo3(get)]
+    pub end: usize,
+}
+
+#[pymethods]
+impl Loc {
+    #[new]
+    fn new(begin: usize, end: usize) -> Self {
+        Self { begin, end }
+    }
+
+    /// Returns the number of bytes in the range
+    fn size(&self) -> usize {
+        self.end - self.begin
+    }
+
+    /// Returns the part of `source` (str or bytes) covered by the range
+    fn source<'py>(&self, source: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        source.get_item(pyo3::types::PySlice::new_bound(
+            source.py(),
+            self.begin as isize,
+            self.end as isize,
+            1,
+        ))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Loc({}, {})", self.begin, self.end)
+    }
+}
+
+impl From<&lib_ruby_parser::Loc> for Loc {
+    fn from(loc: &lib_ruby_parser::Loc) -> Self {
+        Self {
+            begin: loc.begin,
+            end: loc.end,
+        }
+    }
+}
+
+/// Token emitted by the lexer
+#[pyclass(frozen, module = "lib_ruby_parser")]
+#[derive(Debug)]
+pub struct Token {
+    /// Numeric type of the token
+    #[pyo3(get)]
+    pub token_type: i32,
+    /// Name of the token type, like `tINTEGER`
+    #[pyo3(get)]
+    pub token_name: &'static str,
+    /// Value of the token
+    #[pyo3(get)]
+    pub token_value: Py<PyBytes>,
+    /// Location of the token
+    #[pyo3(get)]
+    pub loc: Loc,
+}
+
+#[pymethods]
+impl Token {
+    fn __repr__(&self, py: Python<'_>) -> String {
+        format!(
+            "Token({}, {:?}, {})",
+            self.token_name,
+            String::from_utf8_lossy(self.token_value.bind(py).as_bytes()),
+            self.loc.__repr__()
+        )
+    }
+}
+
+impl Token {
+    pub(crate) fn new(py: Python<'_>, token: &RustToken) -> Self {
+        Self {
+            token_type: token.token_type,
+            token_name: token.token_name(),
+            token_value: PyBytes::new_bound(py, token.as_bytes()).unbind(),
+            loc: Loc::from(&token.loc),
+        }
+    }
+}
+
+/// Error or warning
+#[pyclass(frozen, module = "lib_ruby_parser")]
+#[derive(Debug)]
+pub struct Diagnostic {
+    /// `"error"` or `"warning"`
+    #[pyo3(get)]
+    pub level: &'static str,
+    /// Rendered message, like `unexpected token tRPAREN`
+    #[pyo3(get)]
+    pub message: String,
+    /// Location of the diagnostic
+    #[pyo3(get)]
+    pub loc: Loc,
+    /// Message with a source line, like `rustc` errors
+    #[pyo3(get)]
+    pub rendered: Option<String>,
+}
+
+#[pymethods]
+impl Diagnostic {
+    fn __repr__(&self) -> String {
+        format!(
+            "Diagnostic({}, {:?}, {})",
+            self.level,
+            self.message,
+            self.loc.__repr__()
+        )
+    }
+}
+
+impl Diagnostic {
+    pub(crate) fn new(
+        diagnostic: &RustDiagnostic,
+        input: &lib_ruby_parser::source::DecodedInput,
+    ) -> Self {
+        Self {
+            level: match diagnostic.level {
+                ErrorLevel::Warning => "warning",
+                ErrorLevel::Error => "error",
+            },
+            message: diagnostic.render_message(),
+            loc: Loc::from(&diagnostic.loc),
+            rendered: diagnostic.render(input),
+        }
+    }
+}
+
+/// Comment
+#[pyclass(frozen, module = "lib_ruby_parser")]
+#[derive(Debug)]
+pub struct Comment {
+    /// `"inline"`, `"document"` or `"unknown"`
+    #[pyo3(get)]
+    pub kind: &'static str,
+    /// Location of the comment
+    #[pyo3(get)]
+    pub loc: Loc,
+}
+
+#[pymethods]
+impl Comment {
+    fn __repr__(&self) -> String {
+        format!("Comment({}, {})", self.kind, self.loc.__repr__())
+    }
+}
+
+impl From<&RustComment> for Comment {
+    fn from(comment: &RustComment) -> Self {
+        Self {
+            kind: match comment.kind {
+                CommentType::Inline => "inline",
+                CommentType::Document => "document",
+                CommentType::Unknown => "unknown",
+            },
+            loc: Loc::from(&comment.location),
+        }
+    }
+}
+
+/// Magic comment like `# frozen_string_literal: true`
+#[pyclass(frozen, module = "lib_ruby_parser")]
+#[derive(Debug)]
+pub struct MagicComment {
+    /// `"encoding"`, `"frozen_string_literal"`, `"warn_indent"` or `"shareable_constant_value"`
+    #[pyo3(get)]
+    pub kind: &'static str,
+    /// Location of the key
+    #[pyo3(get)]
+    pub key_l: Loc,
+    /// Location of the value
+    #[pyo3(get)]
+    pub value_l: Loc,
+}
+
+#[pymethods]
+impl MagicComment {
+    fn __repr__(&self) -> String {
+        format!(
+            "MagicComment({}, {}, {})",
+            self.kind,
+            self.key_l.__repr__(),
+            self.value_l.__repr__()
+        )
+    }
+}
+
+impl From<&RustMagicComment> for MagicComment {
+    fn from(magic_comment: &RustMagicComment) -> Self {
+        Self {
+            kind: match magic_comment.kind {
+                MagicCommentKind::Encoding => "encoding",
+                MagicCommentKind::FrozenStringLiteral => "frozen_string_literal",
+                MagicCommentKind::WarnIndent => "warn_indent",
+                MagicCommentKind::ShareableConstantValue => "shareable_constant_value",
+            },
+            key_l: Loc::from(&magic_comment.key_l),
+            value_l: Loc::from(&magic_comment.value_l),
+        }
+    }
+}
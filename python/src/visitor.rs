@@ -0,0 +1,43 @@
+use pyo3::prelude::*;
+
+use crate::nodes::Node;
+
+/// Base class for visitors.
+///
+/// `visit(node)` calls `on_<type>(node)` method of the subclass
+/// (the same names as in Rust `Visitor`, like `on_send` or `on_self_`)
+/// if it's defined, otherwise visits children of the node.
+/// Call `visit_children(node)` in `on_<type>` to keep going deeper.
+#[pyclass(subclass, module = "lib_ruby_parser")]
+#[derive(Debug)]
+pub struct Visitor;
+
+#[pymethods]
+impl Visitor {
+    #[new]
+    fn new() -> Self {
+        Self
+    }
+
+    /// Visits the node, `None` is ignored
+    fn visit(slf: &Bound<'_, Self>, node: &Bound<'_, PyAny>) -> PyResult<()> {
+        if node.is_none() {
+            return Ok(());
+        }
+        let visit_method = node.downcast::<Node>()?.get().visit_method;
+        if slf.hasattr(visit_method)? {
+            slf.call_method1(visit_method, (node,))?;
+            Ok(())
+        } else {
+            Self::visit_children(slf, node.downcast::<Node>()?)
+        }
+    }
+
+    /// Visits direct children of the node
+    fn visit_children(slf: &Bound<'_, Self>, node: &Bound<'_, Node>) -> PyResult<()> {
+        for child in node.get().children_ref() {
+            slf.call_method1("visit", (child,))?;
+        }
+        Ok(())
+    }
+}
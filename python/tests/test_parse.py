@@ -0,0 +1,110 @@
+import unittest
+
+import lib_ruby_parser
+from lib_ruby_parser import Loc, Node, Visitor, parse
+
+
+class TestParse(unittest.TestCase):
+    def test_ast(self):
+        result = parse("foo.bar(1, :sym)")
+        ast = result.ast
+
+        self.assertIsInstance(ast, lib_ruby_parser.Send)
+        self.assertIsInstance(ast, Node)
+        self.assertEqual(ast.type, "send")
+        self.assertEqual(ast.method_name, "bar")
+        self.assertEqual(ast.expression_l, Loc(0, 16))
+        self.assertEqual(ast.dot_l, Loc(3, 4))
+        self.assertEqual(ast.begin_l, Loc(7, 8))
+        self.assertIsNone(ast.operator_l)
+
+        self.assertEqual(ast.recv.type, "send")
+        self.assertIsNone(ast.recv.recv)
+        self.assertEqual(ast.recv.method_name, "foo")
+
+        int_, sym = ast.args
+        self.assertIsInstance(int_, lib_ruby_parser.Int)
+        self.assertEqual(int_.value, "1")
+        self.assertIsInstance(sym, lib_ruby_parser.Sym)
+        self.assertEqual(sym.name, b"sym")
+
+        self.assertEqual([child.type for child in ast.children()], ["send", "int", "sym"])
+        self.assertEqual(repr(int_), "<Int 8...9>")
+
+    def test_bytes_input(self):
+        result = parse(b"'\xff'")
+        self.assertEqual(result.ast.value, b"\xff")
+        self.assertEqual(result.input, b"'\xff'")
+
+    def test_empty_input(self):
+        result = parse("")
+        self.assertIsNone(result.ast)
+        self.assertEqual(result.diagnostics, [])
+
+    def test_tokens(self):
+        result = parse("a = 42")
+        tokens = [(t.token_name, t.token_value, t.loc) for t in result.tokens]
+        self.assertEqual(
+            tokens[:3],
+            [
+                ("tIDENTIFIER", b"a", Loc(0, 1)),
+                ("tEQL", b"=", Loc(2, 3)),
+                ("tINTEGER", b"42", Loc(4, 6)),
+            ],
+        )
+
+        result = parse("a = 42", record_tokens=False)
+        self.assertEqual(result.tokens, [])
+
+    def test_diagnostics(self):
+        result = parse("foo(", buffer_name="(test)")
+        self.assertIsNone(result.ast)
+
+        diagnostic = result.diagnostics[0]
+        self.assertEqual(diagnostic.level, "error")
+        self.assertEqual(diagnostic.message, "unexpected END_OF_INPUT")
+        self.assertTrue(diagnostic.rendered.startswith("(test):1:4: error:"))
+
+    def test_comments(self):
+        result = parse("# frozen_string_literal: true\n# comment\n=begin\ndoc\n=end\n1")
+
+        self.assertEqual([c.kind for c in result.comments], ["inline", "inline", "document"])
+        self.assertTrue(result.comments[1].loc.source(result.input).startswith(b"# comment"))
+
+        (magic_comment,) = result.magic_comments
+        self.assertEqual(magic_comment.kind, "frozen_string_literal")
+        self.assertEqual(magic_comment.key_l.source(result.input), b"frozen_string_literal")
+        self.assertEqual(magic_comment.value_l.source(result.input), b"true")
+
+    def test_visitor(self):
+        class MethodCalls(Visitor):
+            def __init__(self):
+                self.calls = []
+
+            def on_send(self, node):
+                self.calls.append(node.method_name)
+                self.visit_children(node)
+
+            def on_self_(self, node):
+                self.calls.append("self")
+
+        visitor = MethodCalls()
+        visitor.visit(parse("def m; foo(bar, self.baz); end").ast)
+        self.assertEqual(visitor.calls, ["foo", "bar", "baz", "self"])
+
+        visitor.visit(None)
+
+
+class TestLoc(unittest.TestCase):
+    def test_loc(self):
+        loc = Loc(1, 3)
+        self.assertEqual((loc.begin, loc.end, loc.size()), (1, 3, 2))
+        self.assertEqual(loc.source("abcd"), "bc")
+        self.assertEqual(repr(loc), "Loc(1, 3)")
+        self.assertEqual(loc, Loc(1, 3))
+        self.assertNotEqual(loc, Loc(1, 4))
+        self.assertEqual(len({loc, Loc(1, 3)}), 1)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -16,6 +16,14 @@ function run_lib_ruby_parser {
     done
 }
 
+function run_lib_ruby_parser_reusing_parser {
+    for (( x = 1; x <= REPEAT; x += 1));
+    do
+        echo "Run $x:"
+        ./target/release/parse --print=N --drop-tokens --reuse-parser --run-timer --glob "gems/repos/**/*.rb"
+    done
+}
+
 function run_ripper {
     echo "Running MRI/ripper"
 
@@ -32,4 +40,9 @@ run_lib_ruby_parser
 
 echo "--------"
 
+echo "Running lib-ruby-parser (reusing parser)"
+run_lib_ruby_parser_reusing_parser
+
+echo "--------"
+
 run_ripper
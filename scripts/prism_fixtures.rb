@@ -0,0 +1,26 @@
+#!/usr/bin/env ruby
+
+# Writes expected Prism output for a sample of parser fixtures
+# to src/tests/fixtures/prism, they are compared with `prism::convert`
+# by src/tests/prism.rs. Requires Ruby 3.3+ (or the `prism` gem).
+
+require 'prism'
+require 'json'
+
+FIXTURES = %w[
+    assigned_but_unused_variable_at_top_level
+    assigned_but_unused_variable_in_def
+]
+
+FIXTURES.each do |name|
+    content = File.read("src/tests/fixtures/parser/manual/#{name}")
+    input = content
+        .lines(chomp: true)
+        .drop_while { |line| line != '--INPUT' }
+        .drop(1)
+        .take_while { |line| !line.start_with?('--') }
+        .reject { |line| line.start_with?('// ') }
+        .join("\n")
+
+    File.write("src/tests/fixtures/prism/#{name}.json", Prism.parse(input).value.to_json + "\n")
+end
@@ -0,0 +1,111 @@
+use crate::rewrite::{drop_node, take};
+use crate::Node;
+
+/// Root node of the AST that is returned by `Parser`.
+///
+/// Derefs to `Node`. Unlike `Box<Node>` it's dropped without recursion,
+/// so arbitrarily deep AST (like `[[[[...]]]]` from untrusted input)
+/// can't overflow the stack when it goes out of scope.
+#[derive(Clone, PartialEq)]
+pub struct Ast {
+    root: Box<Node>,
+}
+
+impl Ast {
+    /// Constructs AST with a given root node
+    pub fn new(root: Node) -> Self {
+        Self {
+            root: Box::new(root),
+        }
+    }
+
+    /// Consumes itself and returns the root node,
+    /// dropping of the returned node is recursive
+    pub fn into_node(mut self) -> Node {
+        take(&mut self.root)
+    }
+}
+
+impl Drop for Ast {
+    fn drop(&mut self) {
+        drop_node(take(&mut self.root))
+    }
+}
+
+impl From<Node> for Ast {
+    fn from(root: Node) -> Self {
+        Self::new(root)
+    }
+}
+
+impl From<Box<Node>> for Ast {
+    fn from(root: Box<Node>) -> Self {
+        Self { root }
+    }
+}
+
+impl std::ops::Deref for Ast {
+    type Target = Node;
+
+    fn deref(&self) -> &Node {
+        &self.root
+    }
+}
+
+impl std::ops::DerefMut for Ast {
+    fn deref_mut(&mut self) -> &mut Node {
+        &mut self.root
+    }
+}
+
+impl AsRef<Node> for Ast {
+    fn as_ref(&self) -> &Node {
+        &self.root
+    }
+}
+
+impl std::fmt::Debug for Ast {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.root.fmt(f)
+    }
+}
+
+#[cfg(test)]
+use crate::nodes::{Array, Nil};
+#[cfg(test)]
+use crate::Loc;
+
+// [[[...nil...]]]
+#[cfg(test)]
+fn nested_arrays(depth: usize) -> Node {
+    let loc = Loc { begin: 0, end: 0 };
+    let mut node = Node::Nil(Nil { expression_l: loc });
+    for _ in 0..depth {
+        node = Node::Array(Array {
+            elements: vec![node],
+            begin_l: Some(loc),
+            end_l: Some(loc),
+            expression_l: loc,
+        });
+    }
+    node
+}
+
+#[test]
+fn test_drop_on_small_stack() {
+    let ast = Ast::new(nested_arrays(100_000));
+    std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(move || drop(ast))
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn test_into_node() {
+    let ast = Ast::new(nested_arrays(2));
+    assert_eq!(ast.str_type(), "array");
+    let node = ast.clone().into_node();
+    assert_eq!(&node, &*ast);
+}
@@ -0,0 +1,167 @@
+use std::ffi::OsString;
+
+use lib_ruby_parser::format::{format, FormatOptions, QuoteStyle};
+use lib_ruby_parser::{Parser, ParserOptions};
+
+const HELP: &str = "
+Format Ruby code using lib-ruby-parser
+
+USAGE:
+    format [OPTIONS]
+
+OPTIONS:
+
+    -e  <code>                    Code to format
+    --glob <glob>                 File/dir to format, supports glob patterns
+    --write                       Overwrite files with formatted code
+    --check                       Print files that are not formatted and exit with 1 if any
+    --line-width <n>              Maximum width of a line (default: 80)
+    --indent-width <n>            Number of spaces per indentation level (default: 2)
+    --quotes <style>              Quotes of string literals
+                                  double (default)
+                                  single
+                                  preserve
+    --trailing-commas             Print trailing commas in multiline calls, arrays and hashes
+";
+
+fn print_help_and_exit() -> ! {
+    eprintln!("{}", HELP);
+    std::process::exit(1);
+}
+
+#[derive(Debug)]
+enum Input {
+    Eval(OsString),
+    Glob(OsString),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Mode {
+    Print,
+    Write,
+    Check,
+}
+
+#[derive(Debug)]
+struct Args {
+    input: Input,
+    mode: Mode,
+    options: FormatOptions,
+}
+
+fn parse_args() -> Result<Args, lexopt::Error> {
+    use lexopt::prelude::*;
+
+    let mut input = None;
+    let mut mode = Mode::Print;
+    let mut options = FormatOptions::default();
+
+    let mut parser = lexopt::Parser::from_env();
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Long("glob") => input = Some(Input::Glob(parser.value()?)),
+
+            Short('e') => input = Some(Input::Eval(parser.value()?)),
+
+            Long("write") => mode = Mode::Write,
+
+            Long("check") => mode = Mode::Check,
+
+            Long("line-width") => options.line_width = parser.value()?.parse()?,
+
+            Long("indent-width") => options.indent_width = parser.value()?.parse()?,
+
+            Long("quotes") => {
+                options.quote_style = match parser.value()?.string()?.as_str() {
+                    "double" => QuoteStyle::Double,
+                    "single" => QuoteStyle::Single,
+                    "preserve" => QuoteStyle::Preserve,
+                    other => {
+                        return Err(lexopt::Error::from(format!(
+                            "Invalid quotes argument {:?}, expected double, single or preserve",
+                            other
+                        )))
+                    }
+                }
+            }
+
+            Long("trailing-commas") => options.trailing_commas = true,
+
+            _ => return Err(arg.unexpected()),
+        }
+    }
+
+    let input = input.ok_or_else(|| {
+        eprintln!("You must provide either --glob or -e");
+        lexopt::Error::MissingValue {
+            option: Some(String::from("--glob or -e is required")),
+        }
+    })?;
+
+    Ok(Args {
+        input,
+        mode,
+        options,
+    })
+}
+
+fn files(input: Input) -> Vec<(String, Vec<u8>)> {
+    match input {
+        Input::Eval(code) => vec![(String::from("(eval)"), code.into_encoded_bytes())],
+        Input::Glob(pattern) => glob::glob(pattern.to_str().unwrap())
+            .expect("invalid glob pattern")
+            .map(|f| f.unwrap().to_str().unwrap().to_string())
+            .map(|filepath| (filepath.clone(), std::fs::read(&filepath).unwrap()))
+            .collect(),
+    }
+}
+
+fn main() {
+    let Args {
+        input,
+        mode,
+        options,
+    } = parse_args().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        print_help_and_exit();
+    });
+
+    let mut has_errors = false;
+    let mut unformatted = false;
+
+    for (filepath, code) in files(input) {
+        let parser_options = ParserOptions {
+            buffer_name: filepath.clone(),
+            ..Default::default()
+        };
+        let result = Parser::new(code.clone(), parser_options).do_parse();
+
+        let formatted = match format(&result, &options) {
+            Ok(formatted) => formatted,
+            Err(err) => {
+                eprintln!("{}: {}", filepath, err);
+                has_errors = true;
+                continue;
+            }
+        };
+
+        match mode {
+            Mode::Print => print!("{}", formatted),
+            Mode::Write => {
+                if formatted.as_bytes() != code {
+                    std::fs::write(&filepath, formatted).unwrap();
+                }
+            }
+            Mode::Check => {
+                if formatted.as_bytes() != code {
+                    println!("{}", filepath);
+                    unformatted = true;
+                }
+            }
+        }
+    }
+
+    if has_errors || unformatted {
+        std::process::exit(1);
+    }
+}
@@ -2,7 +2,7 @@ mod timer;
 pub(crate) use timer::Timer;
 
 mod parse;
-pub(crate) use parse::parse;
+pub(crate) use parse::{parse, parse_reusing};
 
 mod profiler;
 pub(crate) use profiler::Profiler;
@@ -1,11 +1,35 @@
 use super::InputFile;
 use lib_ruby_parser::{Parser, ParserOptions, ParserResult};
 
-pub(crate) fn parse(input: InputFile, drop_tokens: bool) -> ParserResult {
-    let options = ParserOptions {
-        buffer_name: input.filepath,
-        record_tokens: !drop_tokens,
+fn options(filepath: String, drop_tokens: bool, record_lex_states: bool) -> ParserOptions {
+    ParserOptions {
+        buffer_name: filepath,
+        record_tokens: !drop_tokens || record_lex_states,
+        record_trivia: record_lex_states,
+        record_lex_states,
         ..Default::default()
-    };
+    }
+}
+
+pub(crate) fn parse(input: InputFile, drop_tokens: bool, record_lex_states: bool) -> ParserResult {
+    let options = options(input.filepath, drop_tokens, record_lex_states);
     Parser::new(input.code, options).do_parse()
 }
+
+pub(crate) fn parse_reusing(
+    parser: &mut Option<Parser>,
+    input: InputFile,
+    drop_tokens: bool,
+    record_lex_states: bool,
+) -> ParserResult {
+    let options = options(input.filepath, drop_tokens, record_lex_states);
+    match parser {
+        Some(parser) => {
+            parser.reset(input.code, options);
+            parser.do_parse_in_place()
+        }
+        None => parser
+            .insert(Parser::new(input.code, options))
+            .do_parse_in_place(),
+    }
+}
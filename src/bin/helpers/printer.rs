@@ -1,14 +1,19 @@
 use std::ffi::OsString;
 
-use lib_ruby_parser::ParserResult;
+use lib_ruby_parser::query::{Query, Value};
+use lib_ruby_parser::{prism, ripper};
+use lib_ruby_parser::{LocExt, ParserResult};
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub(crate) enum Printer {
     Nothing,
     FullAst,
     CompactAstWithLocations,
     Diagnostics,
     CompactAst,
+    Ripper,
+    Prism,
+    QueryMatches(Query),
 }
 
 impl Default for Printer {
@@ -24,12 +29,18 @@ impl From<OsString> for Printer {
             "F" => Self::FullAst,
             "L" => Self::CompactAstWithLocations,
             "D" => Self::Diagnostics,
-            _ => panic!("Invalid printer argument, expected N, F, L, D or nothing"),
+            "R" => Self::Ripper,
+            "P" => Self::Prism,
+            _ => panic!("Invalid printer argument, expected N, F, L, D, R, P or nothing"),
         }
     }
 }
 
 impl Printer {
+    pub(crate) fn needs_lex_states(&self) -> bool {
+        matches!(self, Self::Ripper)
+    }
+
     pub(crate) fn print(&self, result: &ParserResult) {
         match self {
             Self::Nothing => {}
@@ -37,6 +48,9 @@ impl Printer {
             Self::CompactAstWithLocations => print_compact_ast_with_locations(result),
             Self::Diagnostics => print_only_diagnostics(result),
             Self::CompactAst => print_compact_ast(result),
+            Self::Ripper => print_ripper(result),
+            Self::Prism => print_prism(result),
+            Self::QueryMatches(query) => print_query_matches(query, result),
         }
     }
 }
@@ -68,6 +82,54 @@ fn print_compact_ast(result: &ParserResult) {
     }
 }
 
+fn print_ripper(result: &ParserResult) {
+    print_only_diagnostics(result);
+    for token in ripper::lex(result) {
+        println!("{}", token);
+    }
+    match ripper::sexp_raw(result) {
+        Some(sexp) => println!("{:#}", sexp),
+        None => println!("nil"),
+    }
+}
+
+fn print_prism(result: &ParserResult) {
+    print_only_diagnostics(result);
+    println!("{}", prism::convert(result).to_json());
+}
+
 fn print_full_ast(result: &ParserResult) {
     println!("{:#?}", result)
 }
+
+fn print_query_matches(query: &Query, result: &ParserResult) {
+    let ast = match result.ast.as_ref() {
+        Some(ast) => ast,
+        None => return,
+    };
+    let first_line = |source: String| source.lines().next().unwrap_or_default().to_string();
+
+    for m in query.find_all(ast) {
+        let (line, col) = m.loc.begin_line_col(&result.input).unwrap_or_default();
+        let source = m.loc.source(&result.input).unwrap_or_default();
+        println!(
+            "{}:{}:{}: {}",
+            result.input.name,
+            line + 1,
+            col + 1,
+            first_line(source)
+        );
+        for (idx, capture) in m.captures.iter().enumerate() {
+            let capture = match capture {
+                Value::Node(node) => {
+                    first_line(node.expression().source(&result.input).unwrap_or_default())
+                }
+                Value::Nil => String::from("nil"),
+                Value::Str(s) => s.to_string(),
+                Value::Bytes(bytes) => bytes.to_string_lossy(),
+                Value::Integer(n) => n.to_string(),
+            };
+            println!("    ${} = {}", idx + 1, capture);
+        }
+    }
+}
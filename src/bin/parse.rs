@@ -1,6 +1,10 @@
 mod helpers;
 
-use helpers::{parse, print_build_info, InputToParse, Printer, Profiler, Repeater, Timer};
+use lib_ruby_parser::query::Query;
+
+use helpers::{
+    parse, parse_reusing, print_build_info, InputToParse, Printer, Profiler, Repeater, Timer,
+};
 
 #[cfg(not(windows))]
 #[cfg(feature = "jemallocator")]
@@ -22,9 +26,14 @@ OPTIONS:
                                   F = Full AST
                                   L = Compact AST with locations
                                   D = Only Diagnostics
+                                  R = Ripper.lex and Ripper.sexp_raw output
+                                  P = Prism AST as JSON
                                   default = Compact AST
+    --query <pattern>             Print nodes that match the pattern (and captured values),
+                                  i.e. '(send nil :require (str $_))'
     --run-profiler                Run profiling
     --drop-tokens                 Drop tokens info
+    --reuse-parser                Parse all files with a single parser (using Parser::reset)
     --run-timer                   Measure time spent on benchmarking
     --print-build-info            Prints information about executable
     --repeat <n>                  Repeat parsing N times
@@ -41,6 +50,7 @@ struct Args {
     printer: Printer,
     profiler: Profiler,
     drop_tokens: bool,
+    reuse_parser: bool,
     timer: Timer,
     repeater: Repeater,
 }
@@ -52,6 +62,7 @@ fn parse_args() -> Result<Args, lexopt::Error> {
     let mut printer = Printer::default();
     let mut profiler = Profiler::disabled();
     let mut drop_tokens = false;
+    let mut reuse_parser = false;
     let mut timer = Timer::default();
     let mut repeater = Repeater::default();
 
@@ -64,10 +75,21 @@ fn parse_args() -> Result<Args, lexopt::Error> {
 
             Long("print") => printer = Printer::from(parser.value()?),
 
+            Long("query") => {
+                let pattern = parser.value()?.string()?;
+                let query = Query::new(&pattern).unwrap_or_else(|err| {
+                    eprintln!("Invalid query: {} at {}", err, err.loc.begin);
+                    std::process::exit(1);
+                });
+                printer = Printer::QueryMatches(query)
+            }
+
             Long("run-profiler") => profiler = Profiler::enabled(),
 
             Long("drop-tokens") => drop_tokens = true,
 
+            Long("reuse-parser") => reuse_parser = true,
+
             Long("run-timer") => timer = Timer::enabled(),
 
             Long("print-build-info") => print_build_info(),
@@ -90,6 +112,7 @@ fn parse_args() -> Result<Args, lexopt::Error> {
         printer,
         profiler,
         drop_tokens,
+        reuse_parser,
         timer,
         repeater,
     })
@@ -101,6 +124,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         printer,
         mut profiler,
         drop_tokens,
+        reuse_parser,
         mut timer,
         repeater,
     } = parse_args().unwrap_or_else(|err| {
@@ -115,8 +139,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     profiler.start();
     timer.start();
 
+    let record_lex_states = printer.needs_lex_states();
+    let mut parser = None;
     for file in files {
-        let result = parse(file, drop_tokens);
+        let result = if reuse_parser {
+            parse_reusing(&mut parser, file, drop_tokens, record_lex_states)
+        } else {
+            parse(file, drop_tokens, record_lex_states)
+        };
         printer.print(&result);
     }
 
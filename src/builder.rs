@@ -1,6 +1,9 @@
 #[cfg(feature = "onig")]
 use onig::{Regex, RegexOptions};
 
+#[cfg(not(feature = "onig"))]
+use crate::regexp::RegexpTree;
+
 use std::collections::HashMap;
 use std::convert::TryInto;
 
@@ -9,12 +12,15 @@ use alloc_from_pool::{Factory as PoolFactory, PoolValue};
 use crate::error::Diagnostics;
 #[allow(unused_imports)]
 use crate::nodes::*;
+use crate::numeric::{parse_float, parse_integer, BigInt};
+use crate::rewrite::take;
+use crate::source::StringContentMaps;
 use crate::Loc;
 use crate::{
     Bytes, CurrentArgStack, Lexer, MaxNumparamStack, Node, SharedContext, StaticEnvironment, Token,
     VariablesStack,
 };
-use crate::{Diagnostic, DiagnosticMessage, ErrorLevel};
+use crate::{Diagnostic, DiagnosticMessage, ErrorLevel, Message};
 
 #[derive(Debug, PartialEq)]
 pub(crate) enum LoopType {
@@ -58,6 +64,55 @@ pub(crate) enum ArgsType {
     Numargs(u8),
 }
 
+// Value of the `when` literal that can be compared at parse time,
+// mirrors `rb_node_case_when_optimizable_literal` from MRI
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum CaseLiteral {
+    Integer(BigInt),
+    Float(u64),
+    Symbol(Vec<u8>),
+    String(Vec<u8>),
+    Nil,
+    True,
+    False,
+}
+
+impl CaseLiteral {
+    fn new(node: &Node) -> Option<Self> {
+        let literal = match node {
+            Node::Int(Int { value, .. }) => Self::Integer(parse_integer(value)?),
+            Node::Float(Float { value, .. }) => {
+                let value = parse_float(value)?.value;
+                if value.is_finite() && value.fract() == 0.0 {
+                    // `when 1` and `when 1.0` are the same
+                    Self::Integer(parse_integer(&format!("{:.0}", value))?)
+                } else {
+                    Self::Float(value.to_bits())
+                }
+            }
+            Node::Sym(Sym { name, .. }) => Self::Symbol(name.as_raw().to_vec()),
+            Node::Str(Str { value, .. }) => Self::String(value.as_raw().to_vec()),
+            Node::Dstr(Dstr { parts, .. }) => {
+                let mut value = vec![];
+                for part in parts {
+                    match part {
+                        Node::Str(Str { value: part, .. }) => {
+                            value.extend_from_slice(part.as_raw())
+                        }
+                        _ => return None,
+                    }
+                }
+                Self::String(value)
+            }
+            Node::Nil(_) => Self::Nil,
+            Node::True(_) => Self::True,
+            Node::False(_) => Self::False,
+            _ => return None,
+        };
+        Some(literal)
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct Builder {
     static_env: StaticEnvironment,
@@ -67,7 +122,9 @@ pub(crate) struct Builder {
     pattern_variables: VariablesStack,
     pattern_hash_keys: VariablesStack,
     diagnostics: Diagnostics,
+    string_content_maps: StringContentMaps,
     pool_factory: PoolFactory<Token>,
+    verbose: bool,
 }
 
 impl Builder {
@@ -79,7 +136,9 @@ impl Builder {
         pattern_variables: VariablesStack,
         pattern_hash_keys: VariablesStack,
         diagnostics: Diagnostics,
+        string_content_maps: StringContentMaps,
         pool_factory: PoolFactory<Token>,
+        verbose: bool,
     ) -> Self {
         Self {
             static_env,
@@ -89,7 +148,9 @@ impl Builder {
             pattern_variables,
             pattern_hash_keys,
             diagnostics,
+            string_content_maps,
             pool_factory,
+            verbose,
         }
     }
 
@@ -236,7 +297,7 @@ impl Builder {
 
     pub(crate) fn string_internal(&self, mut string_t: PoolValue<Token>) -> Box<Node> {
         let expression_l = self.loc(&string_t);
-        let value = string_t.take_value().token_value;
+        let value = string_t.take_value().token_value.into_bytes();
         Box::new(Node::Str(Str {
             value,
             begin_l: None,
@@ -312,7 +373,7 @@ impl Builder {
         let end_l = None;
         let expression_l = str_loc;
 
-        let value = char_t.take_value().token_value;
+        let value = char_t.take_value().token_value.into_bytes();
         Box::new(Node::Str(Str {
             value,
             begin_l,
@@ -347,7 +408,7 @@ impl Builder {
     ) -> Box<Node> {
         let expression_l = self.loc(&start_t).join(&self.loc(&value_t));
         let begin_l = Some(self.loc(&start_t));
-        let value = value_t.take_value().token_value;
+        let value = value_t.take_value().token_value.into_bytes();
         self.validate_sym_value(&value, &expression_l);
         Box::new(Node::Sym(Sym {
             name: value,
@@ -359,7 +420,7 @@ impl Builder {
 
     pub(crate) fn symbol_internal(&self, mut symbol_t: PoolValue<Token>) -> Box<Node> {
         let expression_l = self.loc(&symbol_t);
-        let value = symbol_t.take_value().token_value;
+        let value = symbol_t.take_value().token_value.into_bytes();
         self.validate_sym_value(&value, &expression_l);
         Box::new(Node::Sym(Sym {
             name: value,
@@ -466,7 +527,10 @@ impl Builder {
                         end_l,
                         expression_l,
                     }) => {
+                        let len = value.len();
                         let value = Self::dedent_string(value, dedent_level);
+                        self.string_content_maps
+                            .remove_prefix(&expression_l, len - value.len());
                         if value.is_empty() {
                             None
                         } else {
@@ -756,7 +820,7 @@ impl Builder {
         let colon_l = key_loc.with_begin(key_loc.end - 1);
         let expression_l = key_loc.join(value.expression());
 
-        let key = key_t.take_value().token_value;
+        let key = key_t.take_value().token_value.into_bytes();
         self.validate_sym_value(&key, &key_l);
 
         Box::new(Node::Pair(Pair {
@@ -1104,6 +1168,8 @@ impl Builder {
                         }
                     }
 
+                    self.static_env.mark_as_used(name_s);
+
                     Box::new(Node::Lvar(Lvar { name, expression_l }))
                 }
                 _ => unreachable!(),
@@ -1230,7 +1296,7 @@ impl Builder {
                 self.check_assignment_to_numparam(name_s, &expression_l)?;
                 self.check_reserved_for_numparam(name_s, &expression_l)?;
 
-                self.static_env.declare(name_s);
+                self.static_env.declare_assigned(name_s, expression_l);
 
                 Node::Lvasgn(Lvasgn {
                     name,
@@ -1416,9 +1482,12 @@ impl Builder {
         let expression_l = join_exprs(&lhs, &rhs);
 
         match &*lhs {
+            Node::Lvasgn(Lvasgn { name, .. }) => {
+                // `a += 1` reads `a`
+                self.static_env.mark_as_used(name);
+            }
             Node::Gvasgn(_)
             | Node::Ivasgn(_)
-            | Node::Lvasgn(_)
             | Node::Cvasgn(_)
             | Node::Casgn(_)
             | Node::Send(_)
@@ -2373,7 +2442,11 @@ impl Builder {
         let result = match self.static_regexp_captures(&receiver) {
             Some(captures) => {
                 for capture in captures {
-                    self.static_env.declare(&capture);
+                    // like MRI, names that can't be local variables are not declared
+                    if is_local_variable_name(&capture) {
+                        self.static_env
+                            .declare_assigned(&capture, *receiver.expression());
+                    }
                 }
 
                 Node::MatchWithLvasgn(MatchWithLvasgn {
@@ -2549,6 +2622,8 @@ impl Builder {
         let else_l = self.maybe_loc(&else_t);
         let end_l = self.maybe_loc(&end_t);
 
+        self.warn_literal_in_condition(&cond);
+
         Box::new(Node::If(If {
             cond: Self::check_condition(cond),
             if_true,
@@ -2578,6 +2653,8 @@ impl Builder {
         let expression_l = pre.expression().join(cond.expression());
         let keyword_l = self.loc(&cond_t);
 
+        self.warn_literal_in_condition(&cond);
+
         Box::new(Node::IfMod(IfMod {
             cond: Self::check_condition(cond),
             if_true,
@@ -2599,6 +2676,8 @@ impl Builder {
         let question_l = self.loc(&question_t);
         let colon_l = self.loc(&colon_t);
 
+        self.warn_literal_in_condition(&cond);
+
         Box::new(Node::IfTernary(IfTernary {
             cond,
             if_true,
@@ -2676,6 +2755,7 @@ impl Builder {
         let end_l = self.loc(&end_t);
         let expression_l = self.loc(&keyword_t).join(&end_l);
 
+        self.warn_literal_in_condition(&cond);
         let cond = Self::check_condition(cond);
 
         match loop_type {
@@ -2708,6 +2788,7 @@ impl Builder {
         let expression_l = body.expression().join(cond.expression());
         let keyword_l = self.loc(&keyword_t);
 
+        self.warn_literal_in_condition(&cond);
         let cond = Self::check_condition(cond);
 
         match (loop_type, &*body) {
@@ -3045,6 +3126,8 @@ impl Builder {
     //
 
     pub(crate) fn compstmt(&self, statements: Vec<Node>) -> Option<Box<Node>> {
+        self.warn_void_statements(&statements);
+
         match &statements[..] {
             [] => None,
             [_] => Some(Box::new(statements.into_iter().next().unwrap())),
@@ -3290,7 +3373,7 @@ impl Builder {
 
         self.check_lvar_name(name.as_str(), &name_l)?;
         self.check_duplicate_pattern_variable(name.as_str(), &name_l)?;
-        self.static_env.declare(name.as_str());
+        self.static_env.declare_assigned(name.as_str(), name_l);
 
         Ok(Box::new(Node::MatchVar(MatchVar {
             name,
@@ -3307,7 +3390,7 @@ impl Builder {
 
         self.check_lvar_name(name.as_str(), &name_l)?;
         self.check_duplicate_pattern_variable(name.as_str(), &name_l)?;
-        self.static_env.declare(name.as_str());
+        self.static_env.declare_assigned(name.as_str(), name_l);
 
         Ok(Box::new(Node::MatchVar(MatchVar {
             name,
@@ -3321,15 +3404,21 @@ impl Builder {
         mut strings: Vec<Node>,
         end_t: PoolValue<Token>,
     ) -> Result<Box<Node>, ()> {
-        if strings.len() != 1 {
-            self.error(
-                DiagnosticMessage::SymbolLiteralWithInterpolation {},
-                &self.loc(&begin_t).join(&self.loc(&end_t)),
-            );
-            return Err(());
-        }
+        // `"#{(("a"))}":` is unwrapped without recursion
+        let string = loop {
+            if strings.len() != 1 {
+                self.error(
+                    DiagnosticMessage::SymbolLiteralWithInterpolation {},
+                    &self.loc(&begin_t).join(&self.loc(&end_t)),
+                );
+                return Err(());
+            }
+            match strings.remove(0) {
+                Node::Begin(Begin { statements, .. }) => strings = statements,
+                string => break string,
+            }
+        };
 
-        let string = strings.remove(0);
         let result = match string {
             Node::Str(Str {
                 value,
@@ -3343,7 +3432,7 @@ impl Builder {
                 self.check_lvar_name(name.as_str(), &name_l)?;
                 self.check_duplicate_pattern_variable(name.as_str(), &name_l)?;
 
-                self.static_env.declare(name.as_str());
+                self.static_env.declare_assigned(name.as_str(), name_l);
 
                 if let Some(begin_l) = begin_l.as_ref() {
                     let begin_d: i32 = begin_l
@@ -3371,9 +3460,6 @@ impl Builder {
                     expression_l,
                 }))
             }
-            Node::Begin(Begin { statements, .. }) => {
-                self.match_hash_var_from_str(begin_t, statements, end_t)?
-            }
             _ => {
                 self.error(
                     DiagnosticMessage::SymbolLiteralWithInterpolation {},
@@ -3615,104 +3701,303 @@ impl Builder {
     // Verification
     //
 
-    pub(crate) fn check_condition(cond: Box<Node>) -> Box<Node> {
-        let cond = cond;
+    // `a && b && ...` can be nested arbitrarily deep,
+    // so the condition is rewritten in place without recursion
+    pub(crate) fn check_condition(mut cond: Box<Node>) -> Box<Node> {
+        let mut stack: Vec<&mut Node> = vec![&mut cond];
 
-        match *cond {
-            Node::Begin(Begin {
-                statements,
-                begin_l,
-                end_l,
-                expression_l,
-            }) => {
-                if statements.len() == 1 {
-                    let stmt = statements.into_iter().next().unwrap();
-                    let stmt = *Self::check_condition(Box::new(stmt));
-                    Box::new(Node::Begin(Begin {
-                        statements: vec![stmt],
-                        begin_l,
-                        end_l,
-                        expression_l,
-                    }))
-                } else {
-                    Box::new(Node::Begin(Begin {
-                        statements,
-                        begin_l,
-                        end_l,
+        while let Some(node) = stack.pop() {
+            let is_range = matches!(node, Node::Irange(_) | Node::Erange(_));
+            match node {
+                Node::Irange(_) | Node::Erange(_) => {
+                    *node = match take(node) {
+                        Node::Irange(Irange {
+                            left,
+                            right,
+                            operator_l,
+                            expression_l,
+                        }) => Node::IFlipFlop(IFlipFlop {
+                            left,
+                            right,
+                            operator_l,
+                            expression_l,
+                        }),
+                        Node::Erange(Erange {
+                            left,
+                            right,
+                            operator_l,
+                            expression_l,
+                        }) => Node::EFlipFlop(EFlipFlop {
+                            left,
+                            right,
+                            operator_l,
+                            expression_l,
+                        }),
+                        _ => unreachable!(),
+                    };
+                }
+                Node::Regexp(_) => {
+                    let expression_l = *node.expression();
+                    let regexp = take(node);
+                    *node = Node::MatchCurrentLine(MatchCurrentLine {
+                        re: Box::new(regexp),
                         expression_l,
-                    }))
+                    });
                 }
+                _ => {}
             }
-            Node::And(And {
-                lhs,
-                rhs,
-                operator_l,
-                expression_l,
-            }) => {
-                let lhs = Self::check_condition(lhs);
-                let rhs = Self::check_condition(rhs);
-                Box::new(Node::And(And {
-                    lhs,
-                    rhs,
-                    operator_l,
-                    expression_l,
-                }))
+
+            match node {
+                Node::Begin(Begin { statements, .. }) if statements.len() == 1 => {
+                    stack.push(&mut statements[0]);
+                }
+                Node::And(And { lhs, rhs, .. }) | Node::Or(Or { lhs, rhs, .. }) => {
+                    stack.push(lhs);
+                    stack.push(rhs);
+                }
+                // bounds of the flip-flop that has been just converted from a range
+                Node::IFlipFlop(IFlipFlop { left, right, .. })
+                | Node::EFlipFlop(EFlipFlop { left, right, .. })
+                    if is_range =>
+                {
+                    stack.extend(
+                        [left, right]
+                            .into_iter()
+                            .flatten()
+                            .map(|bound| &mut **bound),
+                    );
+                }
+                _ => {}
             }
-            Node::Or(Or {
-                lhs,
-                rhs,
-                operator_l,
-                expression_l,
-            }) => {
-                let lhs = Self::check_condition(lhs);
-                let rhs = Self::check_condition(rhs);
-                Box::new(Node::Or(Or {
-                    lhs,
-                    rhs,
-                    operator_l,
-                    expression_l,
-                }))
+        }
+
+        cond
+    }
+
+    // Mirrors `void_stmts` and `block_append` from MRI,
+    // the value of every statement except the last one is ignored
+    fn warn_void_statements(&self, statements: &[Node]) {
+        if !self.verbose {
+            return;
+        }
+        let void_statements = match statements.split_last() {
+            Some((_, void_statements)) => void_statements,
+            None => return,
+        };
+        for (idx, stmt) in void_statements.iter().enumerate() {
+            if idx == 0 && Self::is_ignored_literal(stmt) {
+                self.warn(Message::UnusedLiteral {}, stmt.expression());
+            } else if let Some(subject) = Self::void_context_subject(stmt) {
+                self.warn(
+                    Message::UselessUseInVoidContext {
+                        subject: subject.to_string(),
+                    },
+                    stmt.expression(),
+                );
             }
-            Node::Irange(Irange {
-                left,
-                right,
-                operator_l,
-                expression_l,
-            }) => Box::new(Node::IFlipFlop(IFlipFlop {
-                left: left.map(Self::check_condition),
-                right: right.map(Self::check_condition),
-                operator_l,
-                expression_l,
-            })),
-            Node::Erange(Erange {
-                left,
-                right,
-                operator_l,
-                expression_l,
-            }) => Box::new(Node::EFlipFlop(EFlipFlop {
-                left: left.map(Self::check_condition),
-                right: right.map(Self::check_condition),
-                operator_l,
-                expression_l,
-            })),
-            regexp if matches!(regexp, Node::Regexp(_)) => {
-                let expression_l = *regexp.expression();
+        }
+    }
 
-                Box::new(Node::MatchCurrentLine(MatchCurrentLine {
-                    re: Box::new(regexp),
-                    expression_l,
-                }))
+    // Literals that MRI drops if they are followed by another statement
+    fn is_ignored_literal(node: &Node) -> bool {
+        match node {
+            Node::Int(_)
+            | Node::Float(_)
+            | Node::Rational(_)
+            | Node::Complex(_)
+            | Node::Sym(_)
+            | Node::Str(_)
+            | Node::File(_)
+            | Node::Line(_)
+            | Node::Encoding(_)
+            | Node::Self_(_)
+            | Node::True(_)
+            | Node::False(_)
+            | Node::Nil(_) => true,
+            Node::Dstr(Dstr { parts, .. })
+            | Node::Heredoc(Heredoc { parts, .. })
+            | Node::Regexp(Regexp { parts, .. }) => Self::is_static_parts(parts),
+            _ => false,
+        }
+    }
+
+    // Mirrors `void_expr` from MRI
+    fn void_context_subject(mut node: &Node) -> Option<&str> {
+        // `((((a))))` is unwrapped without recursion
+        while let Node::Begin(Begin {
+            statements,
+            begin_l: Some(_),
+            ..
+        }) = node
+        {
+            if statements.len() != 1 {
+                break;
+            }
+            node = &statements[0];
+        }
+
+        let subject = match node {
+            Node::Send(Send {
+                recv: Some(_),
+                dot_l: None,
+                method_name,
+                ..
+            }) => match method_name.as_str() {
+                "+" | "-" | "*" | "/" | "%" | "**" | "+@" | "-@" | "|" | "^" | "&" | "<=>"
+                | ">" | ">=" | "<" | "<=" | "==" | "!=" => method_name.as_str(),
+                _ => return None,
+            },
+            Node::Lvar(_)
+            | Node::Ivar(_)
+            | Node::Gvar(_)
+            | Node::Cvar(_)
+            | Node::NthRef(_)
+            | Node::BackRef(_) => "a variable",
+            Node::Const(Const { scope: None, .. }) => "a constant",
+            Node::Const(_) => "::",
+            Node::Int(_)
+            | Node::Float(_)
+            | Node::Rational(_)
+            | Node::Complex(_)
+            | Node::Sym(_)
+            | Node::Str(_)
+            | Node::Dstr(_)
+            | Node::Heredoc(_)
+            | Node::File(_)
+            | Node::Line(_)
+            | Node::Encoding(_) => "a literal",
+            Node::Regexp(Regexp { parts, .. }) if Self::is_static_parts(parts) => "a literal",
+            Node::Array(Array { elements, .. }) if elements.is_empty() => "a literal",
+            Node::Irange(_) => "..",
+            Node::Erange(_) => "...",
+            Node::Self_(_) => "self",
+            Node::Nil(_) => "nil",
+            Node::True(_) => "true",
+            Node::False(_) => "false",
+            Node::Defined(_) => "defined?",
+            _ => return None,
+        };
+        Some(subject)
+    }
+
+    // Mirrors `cond0` from MRI: string literals are always reported,
+    // other literals only in verbose mode
+    fn warn_literal_in_condition(&self, cond: &Node) {
+        // nodes with a flag that is set for bounds of flip-flops,
+        // a stack is used instead of recursion like in `check_condition`
+        let mut stack = vec![(cond, false)];
+
+        while let Some((node, is_flip_flop_bound)) = stack.pop() {
+            match node {
+                Node::Begin(Begin { statements, .. }) if statements.len() == 1 => {
+                    stack.push((&statements[0], is_flip_flop_bound))
+                }
+                Node::And(And { lhs, rhs, .. }) | Node::Or(Or { lhs, rhs, .. }) => {
+                    stack.push((rhs, false));
+                    stack.push((lhs, false));
+                }
+                Node::Irange(Irange { left, right, .. })
+                | Node::Erange(Erange { left, right, .. }) => {
+                    for bound in [right, left].into_iter().flatten() {
+                        stack.push((bound, true))
+                    }
+                }
+                _ if is_flip_flop_bound => self.warn_literal_in_flip_flop(node),
+                _ => {
+                    let kind = Self::condition_literal_kind(node)
+                        .filter(|kind| *kind == "string" || self.verbose);
+                    if let Some(kind) = kind {
+                        self.warn(
+                            Message::LiteralInCondition {
+                                kind: kind.to_string(),
+                            },
+                            node.expression(),
+                        )
+                    }
+                }
             }
-            other => Box::new(other),
         }
     }
 
+    // Mirrors `range_op` from MRI: integer and string literals are always reported,
+    // other literals only in verbose mode
+    fn warn_literal_in_flip_flop(&self, bound: &Node) {
+        let kind = match bound {
+            Node::Int(_) => Some("integer"),
+            _ => Self::condition_literal_kind(bound),
+        };
+        let kind = kind.filter(|kind| matches!(*kind, "integer" | "string") || self.verbose);
+        if let Some(kind) = kind {
+            self.warn(
+                Message::LiteralInFlipFlop {
+                    kind: kind.to_string(),
+                },
+                bound.expression(),
+            )
+        }
+    }
+
+    fn condition_literal_kind(node: &Node) -> Option<&'static str> {
+        match node {
+            Node::Str(_) | Node::Dstr(_) | Node::Heredoc(_) | Node::File(_) => Some("string"),
+            Node::Regexp(_) => Some("regex"),
+            Node::Sym(_) | Node::Dsym(_) => Some("symbol"),
+            Node::Int(_)
+            | Node::Float(_)
+            | Node::Rational(_)
+            | Node::Complex(_)
+            | Node::Line(_)
+            | Node::Encoding(_) => Some(""),
+            _ => None,
+        }
+    }
+
+    fn is_static_parts(parts: &[Node]) -> bool {
+        parts.iter().all(|part| matches!(part, Node::Str(_)))
+    }
+
+    // Mirrors `check_literal_when` from MRI, returns pairs of
+    // (location of the duplicated `when` literal, location of its first occurrence)
+    pub(crate) fn duplicated_when_clauses(when_bodies: &[Node]) -> Vec<(Loc, Loc)> {
+        let mut seen = HashMap::new();
+        let mut duplicates = vec![];
+        for when_body in when_bodies {
+            let patterns = match when_body {
+                Node::When(When { patterns, .. }) => patterns,
+                _ => continue,
+            };
+            for pattern in patterns {
+                let literal = match CaseLiteral::new(pattern) {
+                    Some(literal) => literal,
+                    None => continue,
+                };
+                match seen.get(&literal) {
+                    Some(first_l) => duplicates.push((*pattern.expression(), *first_l)),
+                    None => {
+                        seen.insert(literal, *pattern.expression());
+                    }
+                }
+            }
+        }
+        duplicates
+    }
+
     pub(crate) fn check_duplicate_args<'a>(
         &self,
         args: &'a [Node],
         map: &mut HashMap<String, &'a Node>,
     ) {
-        for arg in args {
+        // nested `Mlhs` nodes are traversed without recursion
+        let mut stack = vec![args.iter()];
+
+        while let Some(args) = stack.last_mut() {
+            let arg = match args.next() {
+                Some(arg) => arg,
+                None => {
+                    stack.pop();
+                    continue;
+                }
+            };
             match arg {
                 Node::Arg(_)
                 | Node::Optarg(_)
@@ -3725,10 +4010,10 @@ impl Builder {
                     self.check_duplicate_arg(arg, map);
                 }
                 Node::Mlhs(Mlhs { items, .. }) => {
-                    self.check_duplicate_args(items, map);
+                    stack.push(items.iter());
                 }
                 Node::Procarg0(Procarg0 { args, .. }) => {
-                    self.check_duplicate_args(args, map);
+                    stack.push(args.iter());
                 }
                 Node::ForwardArg(_) | Node::Kwnilarg(_) => {
                     // ignore
@@ -3933,23 +4218,24 @@ impl Builder {
 
     pub(crate) fn static_string(nodes: &[Node]) -> Option<String> {
         let mut result = String::from("");
+        // statements of nested `Begin` nodes are traversed without recursion
+        let mut stack = vec![nodes.iter()];
 
-        for node in nodes {
-            match node {
-                Node::Str(Str { value, .. }) => {
+        while let Some(nodes) = stack.last_mut() {
+            match nodes.next() {
+                Some(Node::Str(Str { value, .. })) => {
                     let value = value.to_string_lossy();
                     result.push_str(value.as_str())
                 }
-                Node::Begin(Begin { statements, .. }) => {
-                    if let Some(s) = Self::static_string(statements) {
-                        result.push_str(&s)
-                    } else {
-                        return None;
-                    }
+                Some(Node::Begin(Begin { statements, .. })) => {
+                    stack.push(statements.iter());
                 }
-                _ => {
+                Some(_) => {
                     return None;
                 }
+                None => {
+                    stack.pop();
+                }
             }
         }
 
@@ -3998,13 +4284,33 @@ impl Builder {
         self.build_static_regexp(parts, options, loc);
     }
 
+    #[cfg(not(feature = "onig"))]
+    pub(crate) fn build_static_regexp(
+        &self,
+        parts: &[Node],
+        options: &Option<String>,
+        loc: &Loc,
+    ) -> Option<RegexpTree> {
+        let source = Self::static_string(parts)?;
+        let options = options.as_deref().unwrap_or("");
+
+        match crate::regexp::parse(source.as_bytes(), options) {
+            Ok(tree) => Some(tree),
+            Err(err) => {
+                self.error(DiagnosticMessage::RegexError { error: err.message }, loc);
+                None
+            }
+        }
+    }
+
     #[cfg(not(feature = "onig"))]
     pub(crate) fn validate_static_regexp(
         &self,
-        _parts: &[Node],
-        _options: &Option<String>,
-        _loc: &Loc,
+        parts: &[Node],
+        options: &Option<String>,
+        loc: &Loc,
     ) {
+        self.build_static_regexp(parts, options, loc);
     }
 
     #[cfg(feature = "onig")]
@@ -4035,7 +4341,22 @@ impl Builder {
     }
 
     #[cfg(not(feature = "onig"))]
-    pub(crate) fn static_regexp_captures(&self, _node: &Node) -> Option<Vec<String>> {
+    pub(crate) fn static_regexp_captures(&self, node: &Node) -> Option<Vec<String>> {
+        if let Node::Regexp(Regexp { parts, options, .. }) = node {
+            let mut re_options = "";
+            if let Some(Node::RegOpt(RegOpt {
+                options: Some(options),
+                ..
+            })) = options.as_deref()
+            {
+                re_options = options;
+            };
+            let source = Self::static_string(parts)?;
+            // errors have been reported in `regexp_compose`
+            let tree = crate::regexp::parse(source.as_bytes(), re_options).ok()?;
+
+            return Some(tree.named_captures);
+        }
         None
     }
 
@@ -4100,18 +4421,18 @@ impl Builder {
         }
     }
 
-    pub(crate) fn error(&self, message: DiagnosticMessage, loc: &Loc) {
+    pub(crate) fn error(&self, message: impl Into<Message>, loc: &Loc) {
         self.diagnostics.emit(Diagnostic {
             level: ErrorLevel::Error,
-            message,
+            message: message.into(),
             loc: *loc,
         })
     }
 
-    pub(crate) fn warn(&self, message: DiagnosticMessage, loc: &Loc) {
+    pub(crate) fn warn(&self, message: impl Into<Message>, loc: &Loc) {
         self.diagnostics.emit(Diagnostic {
             level: ErrorLevel::Warning,
-            message,
+            message: message.into(),
             loc: *loc,
         })
     }
@@ -4128,59 +4449,55 @@ impl Builder {
         }
     }
 
-    fn void_value<'a>(node: &'a Node) -> Option<&'a Node> {
-        let check_stmts = |statements: &'a Vec<Node>| {
-            if let Some(last_stmt) = statements.last() {
-                Self::void_value(last_stmt)
-            } else {
-                None
-            }
-        };
-
-        let check_condition = |if_true: &'a Node, if_false: &'a Node| {
-            if Self::void_value(if_true).is_some() && Self::void_value(if_false).is_some() {
-                Some(if_true)
-            } else {
-                None
-            }
-        };
+    // Every node on the stack must have a void value, the outermost `if`
+    // (or the first void node if there's no `if`) is returned
+    fn void_value(node: &Node) -> Option<&Node> {
+        let mut void_node = None;
+        let mut stack = vec![node];
 
-        let check_maybe_condition =
-            |if_true: &'a Option<Box<Node>>, if_false: &'a Option<Box<Node>>| match (
-                if_true.as_ref(),
-                if_false.as_ref(),
-            ) {
-                (None, None) | (None, Some(_)) | (Some(_), None) => None,
-                (Some(if_true), Some(if_false)) => check_condition(if_true, if_false),
-            };
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Return(_)
+                | Node::Break(_)
+                | Node::Next(_)
+                | Node::Redo(_)
+                | Node::Retry(_) => {
+                    void_node.get_or_insert(node);
+                }
 
-        match node {
-            Node::Return(_) | Node::Break(_) | Node::Next(_) | Node::Redo(_) | Node::Retry(_) => {
-                Some(node)
-            }
+                Node::MatchPattern(MatchPattern { value, .. })
+                | Node::MatchPatternP(MatchPatternP { value, .. }) => stack.push(value),
 
-            Node::MatchPattern(MatchPattern { value, .. })
-            | Node::MatchPatternP(MatchPatternP { value, .. }) => Self::void_value(value),
+                Node::Begin(Begin { statements, .. })
+                | Node::KwBegin(KwBegin { statements, .. }) => stack.push(statements.last()?),
 
-            Node::Begin(Begin { statements, .. }) | Node::KwBegin(KwBegin { statements, .. }) => {
-                check_stmts(statements)
-            }
-
-            Node::IfTernary(IfTernary {
-                if_true, if_false, ..
-            }) => check_condition(if_true, if_false),
+                Node::IfTernary(IfTernary {
+                    if_true, if_false, ..
+                }) => {
+                    void_node.get_or_insert(&**if_true);
+                    stack.push(if_false);
+                    stack.push(if_true);
+                }
 
-            Node::If(If {
-                if_true, if_false, ..
-            })
-            | Node::IfMod(IfMod {
-                if_true, if_false, ..
-            }) => check_maybe_condition(if_true, if_false),
+                Node::If(If {
+                    if_true, if_false, ..
+                })
+                | Node::IfMod(IfMod {
+                    if_true, if_false, ..
+                }) => {
+                    let (if_true, if_false) = (if_true.as_deref()?, if_false.as_deref()?);
+                    void_node.get_or_insert(if_true);
+                    stack.push(if_false);
+                    stack.push(if_true);
+                }
 
-            Node::And(And { lhs, .. }) | Node::Or(Or { lhs, .. }) => Self::void_value(lhs),
+                Node::And(And { lhs, .. }) | Node::Or(Or { lhs, .. }) => stack.push(lhs),
 
-            _ => None,
+                _ => return None,
+            }
         }
+
+        void_node
     }
 
     fn rewrite_hash_args_to_kwargs(&self, args: &mut Vec<Node>) {
@@ -4309,6 +4626,13 @@ pub(crate) fn maybe_value(token: Option<PoolValue<Token>>) -> Option<String> {
     token.map(value)
 }
 
+pub(crate) fn is_local_variable_name(name: &str) -> bool {
+    match name.chars().next() {
+        Some(c) => c == '_' || c.is_ascii_lowercase() || !c.is_ascii(),
+        None => false,
+    }
+}
+
 pub(crate) fn join_exprs(lhs: &Node, rhs: &Node) -> Loc {
     lhs.expression().join(rhs.expression())
 }
@@ -0,0 +1,153 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::Message;
+
+/// A flag that can be used to cancel parsing from another thread.
+///
+/// Clones of the token share the same flag.
+///
+/// # Example
+/// ```rust
+/// use lib_ruby_parser::{CancellationToken, Message, Parser, ParserOptions};
+///
+/// let token = CancellationToken::new();
+/// let options = ParserOptions {
+///     cancellation_token: Some(token.clone()),
+///     ..Default::default()
+/// };
+/// let parser = Parser::new(b"foo(1)".to_vec(), options);
+/// token.cancel();
+///
+/// let result = parser.do_parse();
+/// assert!(result.ast.is_none());
+/// assert_eq!(result.diagnostics[0].message, Message::Cancelled {});
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Constructs a new token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels parsing that uses this token (or any of its clones)
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed)
+    }
+
+    /// Returns true if `cancel` has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+// State of cancellation that is checked by the lexer before reading every token
+#[derive(Debug, Default)]
+pub(crate) struct Cancellation {
+    token: Option<CancellationToken>,
+    timeout: Option<Duration>,
+    // computed when the first token is requested
+    deadline: Option<Instant>,
+    tokens_until_check: usize,
+    reason: Option<Message>,
+}
+
+impl Cancellation {
+    // Checking the clock is relatively slow, so both the flag and the deadline
+    // are checked once per this number of tokens
+    const CHECK_EVERY: usize = 64;
+
+    pub(crate) fn new(token: Option<CancellationToken>, timeout: Option<Duration>) -> Self {
+        Self {
+            token,
+            timeout,
+            ..Self::default()
+        }
+    }
+
+    // Returns a message of the diagnostic if parsing must be cancelled
+    pub(crate) fn check(&mut self) -> Option<Message> {
+        if self.reason.is_some() || (self.token.is_none() && self.timeout.is_none()) {
+            return None;
+        }
+        if self.tokens_until_check > 0 {
+            self.tokens_until_check -= 1;
+            return None;
+        }
+        self.tokens_until_check = Self::CHECK_EVERY - 1;
+
+        let reason = if self.token.as_ref().is_some_and(|t| t.is_cancelled()) {
+            Message::Cancelled {}
+        } else if let Some(timeout) = self.timeout {
+            let now = Instant::now();
+            let deadline = match self.deadline {
+                Some(deadline) => deadline,
+                None => match now.checked_add(timeout) {
+                    Some(deadline) => *self.deadline.insert(deadline),
+                    None => {
+                        // too far in the future to be ever reached
+                        self.timeout = None;
+                        return None;
+                    }
+                },
+            };
+            if now < deadline {
+                return None;
+            }
+            Message::TimedOut {
+                timeout_ms: u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX),
+            }
+        } else {
+            return None;
+        };
+        Some(reason)
+    }
+
+    // Stops parsing (also used when the parser exceeds one of the `Limits`),
+    // once stopped `is_cancelled` always returns true
+    pub(crate) fn stop(&mut self, reason: Message) {
+        self.reason = Some(reason)
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.reason.is_some()
+    }
+
+    pub(crate) fn reason(&self) -> Option<&Message> {
+        self.reason.as_ref()
+    }
+}
+
+#[test]
+fn test_cancellation() {
+    let mut cancellation = Cancellation::new(None, None);
+    assert_eq!(cancellation.check(), None);
+    assert!(!cancellation.is_cancelled());
+
+    let token = CancellationToken::new();
+    let mut cancellation = Cancellation::new(Some(token.clone()), None);
+    assert_eq!(cancellation.check(), None);
+    token.cancel();
+    // checked once per CHECK_EVERY tokens
+    for _ in 0..Cancellation::CHECK_EVERY - 1 {
+        assert_eq!(cancellation.check(), None);
+    }
+    assert_eq!(cancellation.check(), Some(Message::Cancelled {}));
+    assert!(!cancellation.is_cancelled());
+    cancellation.stop(Message::Cancelled {});
+    assert!(cancellation.is_cancelled());
+    assert_eq!(cancellation.reason(), Some(&Message::Cancelled {}));
+    // checked only before stopping
+    assert_eq!(cancellation.check(), None);
+
+    let mut cancellation = Cancellation::new(None, Some(Duration::ZERO));
+    assert_eq!(
+        cancellation.check(),
+        Some(Message::TimedOut { timeout_ms: 0 })
+    );
+}
@@ -69,6 +69,10 @@ impl SharedContext {
     pub(crate) fn is_empty(&self) -> bool {
         self.value.borrow().is_empty()
     }
+
+    pub(crate) fn clear(&self) {
+        *self.value.borrow_mut() = Context::default()
+    }
 }
 
 impl Context {
@@ -25,6 +25,10 @@ impl CurrentArgStack {
         self.stack.borrow().is_empty()
     }
 
+    pub(crate) fn clear(&self) {
+        self.stack.borrow_mut().clear()
+    }
+
     pub(crate) fn push(&self, value: Option<String>) {
         self.stack.borrow_mut().push(value)
     }
@@ -1,7 +1,7 @@
 use crate::loc_ext::LocExt;
 use crate::source::DecodedInput;
 use crate::Loc;
-use crate::{DiagnosticMessage, ErrorLevel};
+use crate::{ErrorLevel, Message};
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -13,7 +13,7 @@ pub struct Diagnostic {
     pub level: ErrorLevel,
 
     /// Message of the diagnostic
-    pub message: DiagnosticMessage,
+    pub message: Message,
 
     /// Location of the diagnostic
     pub loc: Loc,
@@ -92,6 +92,10 @@ impl Diagnostics {
         self.list.borrow_mut().push(diagnostic)
     }
 
+    pub(crate) fn clear(&self) {
+        self.list.borrow_mut().clear()
+    }
+
     pub(crate) fn take_inner(self) -> Vec<Diagnostic> {
         self.list.replace(vec![])
     }
@@ -105,7 +109,7 @@ fn test_renders() {
 
     let error = Diagnostic {
         level: ErrorLevel::Warning,
-        message: DiagnosticMessage::FractionAfterNumeric {},
+        message: crate::DiagnosticMessage::FractionAfterNumeric {}.into(),
         loc: Loc { begin: 8, end: 12 },
     };
 
@@ -124,13 +128,13 @@ fn test_renders() {
 fn test_predicates() {
     let error = Diagnostic {
         level: ErrorLevel::Error,
-        message: DiagnosticMessage::AliasNthRef {},
+        message: crate::DiagnosticMessage::AliasNthRef {}.into(),
         loc: Loc { begin: 1, end: 2 },
     };
 
     let warning = Diagnostic {
         level: ErrorLevel::Warning,
-        message: DiagnosticMessage::AliasNthRef {},
+        message: crate::DiagnosticMessage::AliasNthRef {}.into(),
         loc: Loc { begin: 1, end: 2 },
     };
 
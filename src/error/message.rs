@@ -0,0 +1,287 @@
+use crate::{DiagnosticMessage, Loc};
+
+/// Message of the `Diagnostic`.
+///
+/// Most of messages are shared with other `lib-ruby-parser` packages
+/// and so they are defined in `DiagnosticMessage`, this enum
+/// adds messages that are specific to this crate.
+///
+/// New messages can be added in minor releases, so matching on it
+/// requires a wildcard arm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+#[non_exhaustive]
+pub enum Message {
+    /// Message defined in `DiagnosticMessage`
+    Common(DiagnosticMessage),
+
+    /// Emitted for string literals that have both `\u` escapes (that are always UTF-8)
+    /// and non-ASCII chars of the source that has a different encoding, like
+    ///
+    /// ```text
+    /// # encoding: koi8-r
+    /// "éы"
+    /// ```
+    Utf8MixedWithinSource {
+        /// Encoding of the source (from the magic comment)
+        source_encoding: String,
+    },
+
+    /// Emitted in verbose mode for local variables that are assigned but never read, like
+    ///
+    /// ```text
+    /// def m
+    ///   foo = 42
+    /// end
+    /// ```
+    AssignedButUnusedVariable {
+        /// Name of the variable
+        name: String,
+    },
+
+    /// Emitted in verbose mode (or with `# warn_indent: true` magic comment)
+    /// for keywords that are not aligned with the keyword that opens the block, like
+    ///
+    /// ```text
+    /// def m
+    ///   end
+    /// ```
+    MismatchedIndentations {
+        /// Closing (or intermediate) keyword, like `end` or `else`
+        token: String,
+
+        /// Keyword that opens the block, like `def`
+        opening_token: String,
+
+        /// Line of the opening keyword (1-based)
+        opening_line: usize,
+
+        /// Location of the opening keyword
+        opening_l: Loc,
+    },
+
+    /// Emitted in verbose mode for statements whose value is not used
+    /// and that most likely have no side effects, like
+    ///
+    /// ```text
+    /// def m
+    ///   foo + 1
+    ///   bar
+    /// end
+    /// ```
+    UselessUseInVoidContext {
+        /// Description of the statement, like `+`, `a variable` or `a literal`
+        subject: String,
+    },
+
+    /// Emitted in verbose mode for a literal that is the first statement
+    /// of the multi-statement body, like
+    ///
+    /// ```text
+    /// 42; foo
+    /// ```
+    UnusedLiteral {},
+
+    /// Emitted for literals that are used as conditions (string literals are always reported,
+    /// other literals only in verbose mode), like
+    ///
+    /// ```text
+    /// if "foo"; end
+    /// ```
+    LiteralInCondition {
+        /// Kind of the literal: `string`, `regex`, `symbol`, or empty for other literals
+        kind: String,
+    },
+
+    /// Emitted for literals that are used as bounds of the flip-flop (integer and string literals
+    /// are always reported, other literals only in verbose mode), like
+    ///
+    /// ```text
+    /// if 1..2; end
+    /// ```
+    LiteralInFlipFlop {
+        /// Kind of the literal: `integer`, `string`, `regex`, `symbol`, or empty for other literals
+        kind: String,
+    },
+
+    /// Emitted in verbose mode for `when` clauses that repeat a literal of the previous clause, like
+    ///
+    /// ```text
+    /// case foo
+    /// when 1
+    /// when 1
+    /// end
+    /// ```
+    DuplicatedWhenClause {
+        /// Line of the first clause with the same literal (1-based)
+        line: usize,
+    },
+
+    /// Emitted (as an error) if parsing has been cancelled
+    /// using `ParserOptions::cancellation_token`
+    Cancelled {},
+
+    /// Emitted (as an error) if parsing takes longer than `ParserOptions::timeout`
+    TimedOut {
+        /// Timeout in milliseconds
+        timeout_ms: u64,
+    },
+
+    /// Emitted (as an error) if the input is larger than `ParserOptions::max_input_size`
+    InputTooLarge {
+        /// Maximum size of the input in bytes
+        max_input_size: usize,
+    },
+
+    /// Emitted (as an error) if the input has more tokens than `ParserOptions::max_tokens`
+    TooManyTokens {
+        /// Maximum number of tokens
+        max_tokens: usize,
+    },
+
+    /// Emitted (as an error) if parentheses, brackets or braces
+    /// or nodes of the AST are nested deeper than `ParserOptions::max_depth`
+    NestingTooDeep {
+        /// Maximum nesting depth
+        max_depth: usize,
+    },
+}
+
+impl Message {
+    /// Returns the inner `DiagnosticMessage` if this message is one of the shared messages
+    pub fn as_common(&self) -> Option<&DiagnosticMessage> {
+        match self {
+            Self::Common(message) => Some(message),
+            _ => None,
+        }
+    }
+
+    /// Renders message into a string (with the same wording that MRI uses)
+    pub fn render(&self) -> String {
+        match self {
+            Self::Common(message) => message.render(),
+            Self::Utf8MixedWithinSource { source_encoding } => {
+                format!("UTF-8 mixed within {} source", source_encoding)
+            }
+            Self::AssignedButUnusedVariable { name } => {
+                format!("assigned but unused variable - {}", name)
+            }
+            Self::MismatchedIndentations {
+                token,
+                opening_token,
+                opening_line,
+                ..
+            } => format!(
+                "mismatched indentations at '{}' with '{}' at {}",
+                token, opening_token, opening_line
+            ),
+            Self::UselessUseInVoidContext { subject } => {
+                format!("possibly useless use of {} in void context", subject)
+            }
+            Self::UnusedLiteral {} => "unused literal ignored".to_string(),
+            Self::LiteralInCondition { kind } => {
+                format!("{}literal in condition", literal_kind_prefix(kind))
+            }
+            Self::LiteralInFlipFlop { kind } => {
+                format!("{}literal in flip-flop", literal_kind_prefix(kind))
+            }
+            Self::DuplicatedWhenClause { line } => {
+                format!("duplicated `when' clause with line {} is ignored", line)
+            }
+            Self::Cancelled {} => "parsing has been cancelled".to_string(),
+            Self::TimedOut { timeout_ms } => {
+                format!("parsing has timed out after {}ms", timeout_ms)
+            }
+            Self::InputTooLarge { max_input_size } => {
+                format!("input is too large (more than {} bytes)", max_input_size)
+            }
+            Self::TooManyTokens { max_tokens } => {
+                format!("too many tokens (more than {})", max_tokens)
+            }
+            Self::NestingTooDeep { max_depth } => {
+                format!("nesting is too deep (more than {} levels)", max_depth)
+            }
+        }
+    }
+}
+
+fn literal_kind_prefix(kind: &str) -> String {
+    if kind.is_empty() {
+        String::new()
+    } else {
+        format!("{} ", kind)
+    }
+}
+
+impl From<DiagnosticMessage> for Message {
+    fn from(message: DiagnosticMessage) -> Self {
+        Self::Common(message)
+    }
+}
+
+impl PartialEq<DiagnosticMessage> for Message {
+    fn eq(&self, other: &DiagnosticMessage) -> bool {
+        matches!(self, Self::Common(message) if message == other)
+    }
+}
+
+#[test]
+fn test_render() {
+    assert_eq!(
+        Message::from(DiagnosticMessage::InvalidMultibyteChar {}).render(),
+        "invalid multibyte char (UTF-8)"
+    );
+    assert_eq!(
+        Message::Utf8MixedWithinSource {
+            source_encoding: String::from("KOI8-R")
+        }
+        .render(),
+        "UTF-8 mixed within KOI8-R source"
+    );
+    assert_eq!(
+        Message::AssignedButUnusedVariable {
+            name: String::from("foo")
+        }
+        .render(),
+        "assigned but unused variable - foo"
+    );
+    assert_eq!(
+        Message::MismatchedIndentations {
+            token: String::from("end"),
+            opening_token: String::from("def"),
+            opening_line: 3,
+            opening_l: Loc::default()
+        }
+        .render(),
+        "mismatched indentations at 'end' with 'def' at 3"
+    );
+    assert_eq!(
+        Message::LiteralInCondition {
+            kind: String::from("string")
+        }
+        .render(),
+        "string literal in condition"
+    );
+    assert_eq!(
+        Message::LiteralInFlipFlop {
+            kind: String::new()
+        }
+        .render(),
+        "literal in flip-flop"
+    );
+    assert_eq!(
+        Message::TimedOut { timeout_ms: 100 }.render(),
+        "parsing has timed out after 100ms"
+    );
+}
+
+#[test]
+fn test_as_common() {
+    let message = Message::from(DiagnosticMessage::InvalidMultibyteChar {});
+    assert_eq!(
+        message.as_common(),
+        Some(&DiagnosticMessage::InvalidMultibyteChar {})
+    );
+    assert_eq!(message, DiagnosticMessage::InvalidMultibyteChar {});
+    assert_eq!(Message::Cancelled {}.as_common(), None);
+}
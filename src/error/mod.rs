@@ -1,6 +1,8 @@
 pub(crate) mod diagnostic;
 pub(crate) mod level;
+pub(crate) mod message;
 
 pub use diagnostic::Diagnostic;
 pub(crate) use diagnostic::Diagnostics;
 pub use level::ErrorLevel;
+pub use message::Message;
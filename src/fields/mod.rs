@@ -0,0 +1,65 @@
+//! Access to fields of nodes by their names or indexes,
+//! without matching on every node type
+
+mod fields_gen;
+pub use fields_gen::{field_names, field_value};
+
+use crate::{Bytes, Loc, Node};
+
+/// Value of a field of a node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldValue<'a> {
+    /// Child node (like `recv` of `CSend`)
+    Node(&'a Node),
+
+    /// Optional child node (like `recv` of `Send`)
+    MaybeNode(Option<&'a Node>),
+
+    /// List of child nodes (like `args` of `Send`)
+    Nodes(&'a [Node]),
+
+    /// Name of a method/variable/constant or value of a numeric literal
+    Str(&'a str),
+
+    /// Optional name (like a name of `*`) or options of a regexp
+    MaybeStr(Option<&'a str>),
+
+    /// Value of a string or a symbol literal
+    Bytes(&'a Bytes),
+
+    /// Number of parameters of `Numblock`
+    U8(u8),
+
+    /// Location (like `expression_l`)
+    Loc(Loc),
+
+    /// Optional location (like `begin_l` of `Send`)
+    MaybeLoc(Option<Loc>),
+}
+
+/// Returns the value of the field with a given name, `None` if the node has no such field
+///
+/// ```
+/// use lib_ruby_parser::fields::{field, FieldValue};
+/// use lib_ruby_parser::{Parser, ParserOptions};
+///
+/// let ast = Parser::new(b"foo(1)".to_vec(), ParserOptions::default())
+///     .do_parse()
+///     .ast
+///     .unwrap();
+///
+/// assert_eq!(field(&ast, "method_name"), Some(FieldValue::Str("foo")));
+/// assert_eq!(field(&ast, "value"), None);
+/// ```
+pub fn field<'a>(node: &'a Node, name: &str) -> Option<FieldValue<'a>> {
+    let idx = field_names(node).iter().position(|field| *field == name)?;
+    field_value(node, idx)
+}
+
+/// Returns names and values of all fields of the node
+pub fn fields(node: &Node) -> impl Iterator<Item = (&'static str, FieldValue<'_>)> {
+    field_names(node)
+        .iter()
+        .enumerate()
+        .filter_map(move |(idx, name)| Some((*name, field_value(node, idx)?)))
+}
@@ -0,0 +1,24 @@
+use crate::Diagnostic;
+
+/// Error that is returned if the code can't be formatted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// Code has syntax errors, contains the first one
+    SyntaxError(Diagnostic),
+
+    /// Code (after decoding) is not a valid UTF-8 string
+    InvalidUtf8,
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SyntaxError(diagnostic) => {
+                write!(f, "syntax error: {}", diagnostic.render_message())
+            }
+            Self::InvalidUtf8 => f.write_str("input is not a valid UTF-8 string"),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
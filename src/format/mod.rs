@@ -0,0 +1,230 @@
+mod error;
+pub use error::FormatError;
+
+mod options;
+pub use options::{FormatOptions, QuoteStyle};
+
+mod printer;
+use printer::Printer;
+
+use crate::ParserResult;
+
+/// Formats the code, takes the AST and comments from the result of parsing.
+///
+/// Comments and heredocs are kept as they are, constructions that contain them
+/// (except statements of bodies) are copied from the source code,
+/// so the formatted code has the same AST as the input.
+/// Formatting is idempotent, i.e. formatting of the output doesn't change it.
+///
+/// Returns an error if the code has syntax errors.
+pub fn format(result: &ParserResult, options: &FormatOptions) -> Result<String, FormatError> {
+    if let Some(error) = result.diagnostics.iter().find(|d| d.is_error()) {
+        return Err(FormatError::SyntaxError(error.clone()));
+    }
+    let source = std::str::from_utf8(result.input.as_shared_bytes())
+        .map_err(|_| FormatError::InvalidUtf8)?;
+    Ok(Printer::new(source, &result.comments, options).print(result.ast.as_deref()))
+}
+
+#[cfg(test)]
+use crate::nodes::{Arg, Args, Block, Def, Heredoc, Int, Lvar, Send, Str};
+#[cfg(test)]
+use crate::source::{Comment, CommentType};
+#[cfg(test)]
+use crate::{Bytes, Loc, Node};
+
+#[cfg(test)]
+fn loc(begin: usize, end: usize) -> Loc {
+    Loc { begin, end }
+}
+
+#[cfg(test)]
+fn call(method_name: &str, args: Vec<Node>, selector_l: Loc, parens: Option<(Loc, Loc)>) -> Node {
+    let end = parens.map_or(selector_l.end, |(_, end_l)| end_l.end);
+    Node::Send(Send {
+        recv: None,
+        method_name: String::from(method_name),
+        args,
+        dot_l: None,
+        selector_l: Some(selector_l),
+        begin_l: parens.map(|(begin_l, _)| begin_l),
+        end_l: parens.map(|(_, end_l)| end_l),
+        operator_l: None,
+        expression_l: loc(selector_l.begin, end),
+    })
+}
+
+#[cfg(test)]
+fn print(source: &str, ast: &Node, comments: &[Comment], options: &FormatOptions) -> String {
+    Printer::new(source, comments, options).print(Some(ast))
+}
+
+#[test]
+fn test_format_call() {
+    // foo(a,'b')
+    let source = "foo(a,'b')";
+    let ast = call(
+        "foo",
+        vec![
+            Node::Lvar(Lvar {
+                name: String::from("a"),
+                expression_l: loc(4, 5),
+            }),
+            Node::Str(Str {
+                value: Bytes::new(b"b".to_vec()),
+                begin_l: Some(loc(6, 7)),
+                end_l: Some(loc(8, 9)),
+                expression_l: loc(6, 9),
+            }),
+        ],
+        loc(0, 3),
+        Some((loc(3, 4), loc(9, 10))),
+    );
+
+    let options = FormatOptions::default();
+    assert_eq!(print(source, &ast, &[], &options), "foo(a, \"b\")\n");
+
+    let options = FormatOptions {
+        line_width: 8,
+        quote_style: QuoteStyle::Preserve,
+        ..Default::default()
+    };
+    assert_eq!(print(source, &ast, &[], &options), "foo(\n  a,\n  'b'\n)\n");
+
+    let options = FormatOptions {
+        line_width: 8,
+        indent_width: 4,
+        trailing_commas: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        print(source, &ast, &[], &options),
+        "foo(\n    a,\n    \"b\",\n)\n"
+    );
+}
+
+#[test]
+fn test_format_def_with_comments() {
+    // # c1
+    // def foo(a) # c2
+    // a;   bar # c3
+    //
+    //
+    // # c4
+    // end
+    let source = "# c1\ndef foo(a) # c2\na;   bar # c3\n\n\n# c4\nend\n";
+    let ast = Node::Def(Def {
+        name: String::from("foo"),
+        args: Some(Box::new(Node::Args(Args {
+            args: vec![Node::Arg(Arg {
+                name: String::from("a"),
+                expression_l: loc(13, 14),
+            })],
+            expression_l: loc(12, 15),
+            begin_l: Some(loc(12, 13)),
+            end_l: Some(loc(14, 15)),
+        }))),
+        body: Some(Box::new(Node::Begin(crate::nodes::Begin {
+            statements: vec![
+                Node::Lvar(Lvar {
+                    name: String::from("a"),
+                    expression_l: loc(21, 22),
+                }),
+                call("bar", vec![], loc(26, 29), None),
+            ],
+            begin_l: None,
+            end_l: None,
+            expression_l: loc(21, 29),
+        }))),
+        keyword_l: loc(5, 8),
+        name_l: loc(9, 12),
+        end_l: Some(loc(42, 45)),
+        assignment_l: None,
+        expression_l: loc(5, 45),
+    });
+    let comments = [(0, 5), (16, 21), (30, 35), (37, 42)]
+        .iter()
+        .map(|(begin, end)| Comment {
+            location: loc(*begin, *end),
+            kind: CommentType::Inline,
+        })
+        .collect::<Vec<_>>();
+
+    let options = FormatOptions::default();
+    let formatted = print(source, &ast, &comments, &options);
+    assert_eq!(
+        formatted,
+        "# c1\ndef foo(a) # c2\n  a\n  bar # c3\n\n  # c4\nend\n"
+    );
+}
+
+#[test]
+fn test_format_heredoc() {
+    // foo(<<~A) { |x|   x }
+    //   text
+    // A
+    let source = "foo(<<~A) { |x|   x }\n  text\nA\n";
+    let heredoc = Node::Heredoc(Heredoc {
+        parts: vec![Node::Str(Str {
+            value: Bytes::new(b"text\n".to_vec()),
+            begin_l: None,
+            end_l: None,
+            expression_l: loc(22, 29),
+        })],
+        heredoc_body_l: loc(22, 29),
+        heredoc_end_l: loc(29, 31),
+        expression_l: loc(4, 8),
+    });
+    let ast = Node::Block(Block {
+        call: Box::new(call(
+            "foo",
+            vec![heredoc],
+            loc(0, 3),
+            Some((loc(3, 4), loc(8, 9))),
+        )),
+        args: Some(Box::new(Node::Args(Args {
+            args: vec![Node::Arg(Arg {
+                name: String::from("x"),
+                expression_l: loc(13, 14),
+            })],
+            expression_l: loc(12, 15),
+            begin_l: Some(loc(12, 13)),
+            end_l: Some(loc(14, 15)),
+        }))),
+        body: Some(Box::new(Node::Lvar(Lvar {
+            name: String::from("x"),
+            expression_l: loc(18, 19),
+        }))),
+        begin_l: loc(10, 11),
+        end_l: loc(20, 21),
+        expression_l: loc(0, 21),
+    });
+
+    let options = FormatOptions::default();
+    assert_eq!(print(source, &ast, &[], &options), source);
+
+    // the block doesn't fit and is printed on multiple lines
+    let options = FormatOptions {
+        line_width: 10,
+        ..Default::default()
+    };
+    assert_eq!(
+        print(source, &ast, &[], &options),
+        "foo(<<~A) { |x|\n  text\nA\n  x\n}\n"
+    );
+}
+
+#[test]
+fn test_format_end_data() {
+    // 1
+    // __END__
+    // data
+    let source = "1\n__END__\ndata";
+    let ast = Node::Int(Int {
+        value: String::from("1"),
+        operator_l: None,
+        expression_l: loc(0, 1),
+    });
+    let options = FormatOptions::default();
+    assert_eq!(print(source, &ast, &[], &options), "1\n__END__\ndata\n");
+}
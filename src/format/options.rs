@@ -0,0 +1,41 @@
+/// Quotes of string literals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// Use double quotes if it doesn't require escaping
+    Double,
+
+    /// Use single quotes if it doesn't require escaping
+    Single,
+
+    /// Keep quotes as they are in the source code
+    Preserve,
+}
+
+/// Configuration of the formatter
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// Maximum width of a line, calls, arrays and hashes that don't fit
+    /// are printed with one item per line
+    pub line_width: usize,
+
+    /// Number of spaces per indentation level
+    pub indent_width: usize,
+
+    /// Preferred quotes of string literals
+    pub quote_style: QuoteStyle,
+
+    /// Print a trailing comma after the last item
+    /// of calls, arrays and hashes that are printed with one item per line
+    pub trailing_commas: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            line_width: 80,
+            indent_width: 2,
+            quote_style: QuoteStyle::Double,
+            trailing_commas: false,
+        }
+    }
+}
@@ -0,0 +1,1227 @@
+use super::{FormatOptions, QuoteStyle};
+use crate::nodes::{If, Pair, Rescue, RescueBody, Str};
+use crate::rewrite::for_each_child;
+use crate::source::{Comment, CommentType};
+use crate::{Loc, Node};
+
+const BINARY_OPERATORS: &[&str] = &[
+    "+", "-", "*", "/", "%", "**", "==", "!=", "<", ">", "<=", ">=", "<=>", "<<", ">>", "&", "|",
+    "^", "=~", "!~", "===",
+];
+
+const UNARY_OPERATORS: &[&str] = &["-@", "+@", "!", "~"];
+
+// Receiver, dot, selector and arguments of a call without parentheses
+type CommandCall<'n> = (Option<&'n Node>, Option<Loc>, Option<Loc>, &'n [Node]);
+
+/// Prints the AST back to the source code.
+///
+/// Only the layout of statements, calls and literals is printed from the AST,
+/// everything else (and everything that contains comments or heredocs)
+/// is copied from the source code, so the output has the same AST as the input.
+pub(crate) struct Printer<'a> {
+    source: &'a str,
+    options: &'a FormatOptions,
+    comments: Vec<&'a Comment>,
+    emitted: Vec<bool>,
+    first_pending_comment: usize,
+    line_starts: Vec<usize>,
+    heredocs: Vec<Loc>,
+    indent: usize,
+    out: String,
+}
+
+impl<'a> Printer<'a> {
+    pub(crate) fn new(
+        source: &'a str,
+        comments: &'a [Comment],
+        options: &'a FormatOptions,
+    ) -> Self {
+        let mut comments = comments.iter().collect::<Vec<_>>();
+        comments.sort_by_key(|comment| comment.location.begin);
+        let mut line_starts = vec![0];
+        line_starts.extend(
+            source
+                .bytes()
+                .enumerate()
+                .filter(|(_, c)| *c == b'\n')
+                .map(|(idx, _)| idx + 1),
+        );
+
+        Self {
+            source,
+            options,
+            emitted: vec![false; comments.len()],
+            comments,
+            first_pending_comment: 0,
+            line_starts,
+            heredocs: vec![],
+            indent: 0,
+            out: String::new(),
+        }
+    }
+
+    pub(crate) fn print(mut self, ast: Option<&Node>) -> String {
+        let mut prev_end = None;
+        if self.source.starts_with("#!") {
+            let shebang_end = self.source.find('\n').unwrap_or(self.source.len());
+            self.write(self.source[..shebang_end].trim_end());
+            prev_end = Some(shebang_end);
+        }
+
+        let data = self.end_data(ast);
+        let stmts = ast.map(statements).unwrap_or_default();
+        self.statements(&stmts, data.unwrap_or(self.source.len()), prev_end);
+        self.comments_before(usize::MAX, &mut None);
+
+        if let Some(data) = data {
+            self.ensure_line();
+            self.out.push_str(&self.source[data..]);
+        }
+        self.ensure_line();
+        self.out
+    }
+
+    // Statements
+
+    fn statements(&mut self, stmts: &[&Node], end: usize, mut prev_end: Option<usize>) {
+        for stmt in stmts {
+            let loc = *stmt.expression();
+            self.comments_before(loc.begin, &mut prev_end);
+            self.separate(prev_end, loc.begin);
+            self.node(stmt);
+            self.trailing_comment(loc.end);
+            prev_end = Some(self.source_end(stmt));
+        }
+        self.comments_before(end, &mut prev_end);
+    }
+
+    // Starts a new line, keeps (at most one) blank line between items
+    fn separate(&mut self, prev_end: Option<usize>, begin: usize) {
+        self.ensure_line();
+        if let Some(prev_end) = prev_end {
+            if self.line_of(begin) > self.line_of(prev_end.saturating_sub(1)) + 1 {
+                self.out.push('\n');
+            }
+        }
+    }
+
+    fn body(&mut self, node: Option<&Node>, end: usize) {
+        match node {
+            Some(Node::Rescue(rescue)) if !self.is_rescue_mod(rescue) => self.rescue(rescue, end),
+            Some(Node::Ensure(ensure)) => {
+                self.body(ensure.body.as_deref(), ensure.keyword_l.begin);
+                self.clause(ensure.keyword_l);
+                self.body(ensure.ensure.as_deref(), end);
+            }
+            _ => self.body_statements(&node.map(statements).unwrap_or_default(), end),
+        }
+    }
+
+    fn body_statements(&mut self, stmts: &[&Node], end: usize) {
+        self.indent += 1;
+        self.statements(stmts, end, None);
+        self.indent -= 1;
+    }
+
+    fn rescue(&mut self, node: &Rescue, end: usize) {
+        let rescue_bodies = node
+            .rescue_bodies
+            .iter()
+            .filter_map(|rescue_body| match rescue_body {
+                Node::RescueBody(rescue_body) => Some(rescue_body),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let else_begin = node.else_l.map_or(end, |else_l| else_l.begin);
+        let clause_begin = |idx: usize| {
+            rescue_bodies
+                .get(idx)
+                .map_or(else_begin, |rescue_body| rescue_body.keyword_l.begin)
+        };
+
+        self.body(node.body.as_deref(), clause_begin(0));
+        for (idx, rescue_body) in rescue_bodies.iter().enumerate() {
+            self.rescue_body(rescue_body, clause_begin(idx + 1));
+        }
+        if let Some(else_l) = node.else_l {
+            self.clause(else_l);
+            self.body(node.else_.as_deref(), end);
+        }
+    }
+
+    fn rescue_body(&mut self, node: &RescueBody, end: usize) {
+        let mut header = node.keyword_l;
+        let parts = [node.exc_list.as_deref(), node.exc_var.as_deref()];
+        for part in parts.iter().flatten() {
+            header.end = header.end.max(part.expression().end);
+        }
+        self.ensure_line();
+        self.header(header, &parts);
+        self.body(node.body.as_deref(), end);
+    }
+
+    // `rescue`/`else`/`ensure` keywords
+    fn clause(&mut self, keyword_l: Loc) {
+        self.ensure_line();
+        self.write(self.slice(keyword_l));
+        self.trailing_comment(keyword_l.end);
+    }
+
+    fn end_keyword(&mut self) {
+        self.ensure_line();
+        self.write("end");
+    }
+
+    // Header of the definition (like `def foo(a, b)`), copied from the source
+    fn header(&mut self, loc: Loc, parts: &[Option<&Node>]) {
+        self.write_source(loc, false);
+        for part in parts.iter().flatten() {
+            self.push_heredocs(part, loc);
+        }
+        self.trailing_comment(loc.end);
+    }
+
+    // Comments
+
+    fn comments_before(&mut self, pos: usize, prev_end: &mut Option<usize>) {
+        loop {
+            while self.first_pending_comment < self.comments.len()
+                && self.emitted[self.first_pending_comment]
+            {
+                self.first_pending_comment += 1;
+            }
+            let idx = self.first_pending_comment;
+            let begin = match self.comments.get(idx) {
+                Some(comment) if comment.location.begin < pos => comment.location.begin,
+                _ => break,
+            };
+            self.separate(*prev_end, begin);
+            self.comment(idx);
+            *prev_end = Some(begin + self.comment_text(idx).len());
+        }
+    }
+
+    // Prints a comment that is on the same line after `pos` (if any)
+    fn trailing_comment(&mut self, pos: usize) {
+        let idx = self
+            .comments
+            .partition_point(|comment| comment.location.begin < pos);
+        let comment = match self.comments.get(idx) {
+            Some(comment) => comment,
+            None => return,
+        };
+        if self.emitted[idx]
+            || comment.kind == CommentType::Document
+            || self.line_of(comment.location.begin) != self.line_of(pos)
+            || !is_trivial_gap(&self.source[pos..comment.location.begin])
+        {
+            return;
+        }
+        if !self.at_line_start() {
+            self.out.push(' ');
+        }
+        self.comment(idx);
+    }
+
+    fn comment(&mut self, idx: usize) {
+        self.emitted[idx] = true;
+        if self.comments[idx].kind == CommentType::Document {
+            // `=begin` must be at the beginning of the line
+            self.ensure_line();
+            self.out.push_str(self.comment_text(idx));
+        } else {
+            self.write(self.comment_text(idx));
+        }
+    }
+
+    fn comment_text(&self, idx: usize) -> &'a str {
+        let text = self.slice(self.comments[idx].location);
+        match self.comments[idx].kind {
+            CommentType::Document => text,
+            _ => text.trim_end_matches(['\r', '\n']),
+        }
+    }
+
+    fn has_comments(&self, loc: Loc) -> bool {
+        let idx = self
+            .comments
+            .partition_point(|comment| comment.location.begin < loc.begin);
+        self.comments
+            .get(idx)
+            .is_some_and(|comment| comment.location.begin < loc.end)
+    }
+
+    fn mark_comments(&mut self, loc: Loc) {
+        let mut idx = self
+            .comments
+            .partition_point(|comment| comment.location.begin < loc.begin);
+        while idx < self.comments.len() && self.comments[idx].location.begin < loc.end {
+            self.emitted[idx] = true;
+            idx += 1;
+        }
+    }
+
+    // Nodes
+
+    fn node(&mut self, node: &Node) {
+        let is_construct = match node {
+            Node::Def(def) => def.end_l.is_some(),
+            Node::Defs(defs) => defs.end_l.is_some(),
+            Node::While(while_) => while_.end_l.is_some(),
+            Node::Until(until) => until.end_l.is_some(),
+            Node::KwBegin(kw_begin) => kw_begin.end_l.is_some(),
+            Node::Class(_) | Node::Module(_) | Node::SClass(_) | Node::If(_) | Node::Case(_) => {
+                true
+            }
+            Node::Block(_) | Node::Numblock(_) => {
+                if let Some(flat) = self.flat(node) {
+                    if self.fits(&flat) {
+                        self.write(&flat);
+                        return;
+                    }
+                }
+                true
+            }
+            _ => false,
+        };
+
+        let source = self.slice(*node.expression());
+        if !is_construct {
+            self.expr(node);
+        } else if !source.contains('\n') && self.fits(source) {
+            // one-liners are kept as they are
+            self.verbatim(node);
+        } else {
+            self.construct(node);
+        }
+    }
+
+    fn construct(&mut self, node: &Node) {
+        match node {
+            Node::Def(def) => {
+                let header_end = def
+                    .args
+                    .as_ref()
+                    .map_or(def.name_l.end, |args| args.expression().end);
+                self.header(def.keyword_l.with_end(header_end), &[def.args.as_deref()]);
+                self.body(def.body.as_deref(), end_of(def.end_l, def.expression_l));
+                self.end_keyword();
+            }
+            Node::Defs(defs) => {
+                let header_end = defs
+                    .args
+                    .as_ref()
+                    .map_or(defs.name_l.end, |args| args.expression().end);
+                self.header(
+                    defs.keyword_l.with_end(header_end),
+                    &[Some(&defs.definee), defs.args.as_deref()],
+                );
+                self.body(defs.body.as_deref(), end_of(defs.end_l, defs.expression_l));
+                self.end_keyword();
+            }
+            Node::Class(class) => {
+                let header_end = class
+                    .superclass
+                    .as_ref()
+                    .map_or(class.name.expression().end, |superclass| {
+                        superclass.expression().end
+                    });
+                self.header(
+                    class.keyword_l.with_end(header_end),
+                    &[Some(&class.name), class.superclass.as_deref()],
+                );
+                self.body(class.body.as_deref(), class.end_l.begin);
+                self.end_keyword();
+            }
+            Node::Module(module) => {
+                let header_end = module.name.expression().end;
+                self.header(module.keyword_l.with_end(header_end), &[Some(&module.name)]);
+                self.body(module.body.as_deref(), module.end_l.begin);
+                self.end_keyword();
+            }
+            Node::SClass(sclass) => {
+                let header_end = sclass.expr.expression().end;
+                self.header(sclass.keyword_l.with_end(header_end), &[Some(&sclass.expr)]);
+                self.body(sclass.body.as_deref(), sclass.end_l.begin);
+                self.end_keyword();
+            }
+            Node::If(if_) => {
+                self.if_(if_, end_of(if_.end_l, if_.expression_l));
+            }
+            Node::While(while_) => self.loop_(
+                while_.keyword_l,
+                &while_.cond,
+                while_.body.as_deref(),
+                while_.end_l,
+            ),
+            Node::Until(until) => self.loop_(
+                until.keyword_l,
+                &until.cond,
+                until.body.as_deref(),
+                until.end_l,
+            ),
+            Node::Case(case) => {
+                self.write("case");
+                let mut header_end = case.keyword_l.end;
+                if let Some(expr) = &case.expr {
+                    self.write(" ");
+                    self.node(expr);
+                    header_end = expr.expression().end;
+                }
+                self.trailing_comment(header_end);
+
+                let else_begin = case.else_l.map_or(case.end_l.begin, |else_l| else_l.begin);
+                for (idx, when) in case.when_bodies.iter().enumerate() {
+                    let when = match when {
+                        Node::When(when) => when,
+                        other => unreachable!("expected When, got {:?}", other),
+                    };
+                    let end = case
+                        .when_bodies
+                        .get(idx + 1)
+                        .map_or(else_begin, |next| next.expression().begin);
+                    self.ensure_line();
+                    self.write("when ");
+                    self.separated(&when.patterns.iter().collect::<Vec<_>>());
+                    let header_end = when
+                        .patterns
+                        .last()
+                        .map_or(when.keyword_l.end, |pattern| pattern.expression().end);
+                    self.trailing_comment(header_end);
+                    self.body(when.body.as_deref(), end);
+                }
+                if let Some(else_l) = case.else_l {
+                    self.clause(else_l);
+                    self.body(case.else_body.as_deref(), case.end_l.begin);
+                }
+                self.end_keyword();
+            }
+            Node::KwBegin(kw_begin) => {
+                self.write("begin");
+                let begin_l = kw_begin.begin_l.unwrap_or(kw_begin.expression_l);
+                self.trailing_comment(begin_l.end);
+                let end = end_of(kw_begin.end_l, kw_begin.expression_l);
+                match &kw_begin.statements[..] {
+                    [stmt] => self.body(Some(stmt), end),
+                    stmts => self.body_statements(&stmts.iter().collect::<Vec<_>>(), end),
+                }
+                self.end_keyword();
+            }
+            Node::Block(block) => self.block(
+                &block.call,
+                block.args.as_deref(),
+                block.body.as_deref(),
+                block.begin_l,
+                block.end_l,
+            ),
+            Node::Numblock(numblock) => self.block(
+                &numblock.call,
+                None,
+                Some(&numblock.body),
+                numblock.begin_l,
+                numblock.end_l,
+            ),
+            _ => self.verbatim(node),
+        }
+    }
+
+    fn if_(&mut self, node: &If, end: usize) {
+        let keyword = self.slice(node.keyword_l);
+        let (body, else_) = if keyword == "unless" {
+            (node.if_false.as_deref(), node.if_true.as_deref())
+        } else {
+            (node.if_true.as_deref(), node.if_false.as_deref())
+        };
+        let elsif = match else_ {
+            Some(Node::If(elsif)) if self.slice(elsif.keyword_l) == "elsif" => Some(elsif),
+            _ => None,
+        };
+
+        self.write(keyword);
+        self.write(" ");
+        self.node(&node.cond);
+        self.trailing_comment(node.cond.expression().end);
+
+        let body_end = match (elsif, node.else_l) {
+            (Some(elsif), _) => elsif.keyword_l.begin,
+            (None, Some(else_l)) => else_l.begin,
+            (None, None) => end,
+        };
+        self.body(body, body_end);
+
+        if let Some(elsif) = elsif {
+            self.ensure_line();
+            self.if_(elsif, end);
+        } else if let Some(else_l) = node.else_l {
+            self.clause(else_l);
+            self.body(else_, end);
+        }
+
+        if keyword != "elsif" {
+            self.end_keyword();
+        }
+    }
+
+    fn loop_(&mut self, keyword_l: Loc, cond: &Node, body: Option<&Node>, end_l: Option<Loc>) {
+        self.write(self.slice(keyword_l));
+        self.write(" ");
+        self.node(cond);
+        self.trailing_comment(cond.expression().end);
+        self.body(body, end_of(end_l, *cond.expression()));
+        self.end_keyword();
+    }
+
+    fn block(
+        &mut self,
+        call: &Node,
+        args: Option<&Node>,
+        body: Option<&Node>,
+        begin_l: Loc,
+        end_l: Loc,
+    ) {
+        let mut header_end = begin_l.end;
+        if let Node::Lambda(_) = call {
+            self.write("->");
+            if let Some(args) = args {
+                if self.slice(*args.expression()).starts_with('(') {
+                    self.write_source(*args.expression(), false);
+                } else {
+                    self.write(" ");
+                    self.write_source(*args.expression(), false);
+                }
+            }
+            self.write(" ");
+            self.write(self.slice(begin_l));
+        } else {
+            self.node(call);
+            self.write(" ");
+            self.write(self.slice(begin_l));
+            if let Some(args) = args {
+                self.write(" ");
+                self.write_source(*args.expression(), false);
+                header_end = header_end.max(args.expression().end);
+            }
+        }
+        if let Some(args) = args {
+            self.push_heredocs(args, *args.expression());
+        }
+        self.trailing_comment(header_end);
+        self.body(body, end_l.begin);
+        self.ensure_line();
+        self.write(self.slice(end_l));
+    }
+
+    fn expr(&mut self, node: &Node) {
+        let flat = self.flat(node);
+        if let Some(flat) = &flat {
+            if self.fits(flat) || !self.is_breakable(node) {
+                self.write(flat);
+                return;
+            }
+        }
+
+        if let Some((prefix, operator_l, value)) = self.assignment(node) {
+            let prefix_l = Loc {
+                begin: node.expression().begin,
+                end: operator_l.begin,
+            };
+            if !prefix.contains('\n') && !self.has_comments(prefix_l) {
+                self.write(prefix);
+                self.write(" ");
+                self.write(self.slice(operator_l));
+                self.write(" ");
+                self.node(value);
+                return;
+            }
+        }
+
+        if let Node::Pair(pair) = node {
+            if let Some(prefix) = self.pair_prefix(pair) {
+                self.write(&prefix);
+                self.write(" ");
+                self.node(&pair.value);
+                return;
+            }
+        }
+
+        if let Some((recv, dot_l, selector_l, args)) = self.command_call(node) {
+            self.callee(recv, dot_l, selector_l);
+            self.write(" ");
+            self.separated(&args.iter().collect::<Vec<_>>());
+            return;
+        }
+
+        if self.has_comments(*node.expression()) || has_heredoc(node) {
+            self.verbatim(node);
+            return;
+        }
+
+        match node {
+            Node::Send(send) if send.begin_l.is_some() && send.selector_l.is_some() => {
+                self.callee(send.recv.as_deref(), send.dot_l, send.selector_l);
+                self.list("(", &call_args(&send.args), ")");
+            }
+            Node::CSend(csend) if csend.begin_l.is_some() && csend.selector_l.is_some() => {
+                self.callee(Some(&csend.recv), Some(csend.dot_l), csend.selector_l);
+                self.list("(", &call_args(&csend.args), ")");
+            }
+            Node::Send(send) if self.is_binary_operation(node) => {
+                let recv = send
+                    .recv
+                    .as_deref()
+                    .expect("binary operation has a receiver");
+                self.binary(
+                    recv,
+                    send.selector_l.expect("binary operation has a selector"),
+                    &send.args[0],
+                );
+            }
+            Node::Super(super_) if super_.begin_l.is_some() => {
+                self.write("super");
+                self.list("(", &call_args(&super_.args), ")");
+            }
+            Node::Yield(yield_) if yield_.begin_l.is_some() => {
+                self.write("yield");
+                self.list("(", &call_args(&yield_.args), ")");
+            }
+            Node::Array(array) if self.is_bracketed(array.begin_l, "[") => {
+                self.list("[", &array.elements.iter().collect::<Vec<_>>(), "]");
+            }
+            Node::Hash(hash) if self.is_bracketed(hash.begin_l, "{") => {
+                self.list("{", &hash.pairs.iter().collect::<Vec<_>>(), "}");
+            }
+            Node::And(and) => self.binary(&and.lhs, and.operator_l, &and.rhs),
+            Node::Or(or) => self.binary(&or.lhs, or.operator_l, &or.rhs),
+            _ => match flat {
+                Some(flat) => self.write(&flat),
+                None => self.verbatim(node),
+            },
+        }
+    }
+
+    fn is_breakable(&self, node: &Node) -> bool {
+        if self.assignment(node).is_some() || self.command_call(node).is_some() {
+            return true;
+        }
+        match node {
+            Node::Send(send) => {
+                (send.begin_l.is_some() && !send.args.is_empty()) || self.is_binary_operation(node)
+            }
+            Node::CSend(csend) => csend.begin_l.is_some() && !csend.args.is_empty(),
+            Node::Super(super_) => super_.begin_l.is_some() && !super_.args.is_empty(),
+            Node::Yield(yield_) => yield_.begin_l.is_some() && !yield_.args.is_empty(),
+            Node::Array(array) => {
+                self.is_bracketed(array.begin_l, "[") && !array.elements.is_empty()
+            }
+            Node::Hash(hash) => self.is_bracketed(hash.begin_l, "{") && !hash.pairs.is_empty(),
+            Node::Pair(_) | Node::And(_) | Node::Or(_) => true,
+            _ => false,
+        }
+    }
+
+    fn callee(&mut self, recv: Option<&Node>, dot_l: Option<Loc>, selector_l: Option<Loc>) {
+        if let (Some(recv), Some(dot_l)) = (recv, dot_l) {
+            self.node(recv);
+            self.write(self.slice(dot_l));
+        }
+        if let Some(selector_l) = selector_l {
+            self.write(self.slice(selector_l));
+        }
+    }
+
+    fn binary(&mut self, lhs: &Node, operator_l: Loc, rhs: &Node) {
+        self.node(lhs);
+        self.write(" ");
+        self.write(self.slice(operator_l));
+        self.write(" ");
+        self.node(rhs);
+    }
+
+    // Comma-separated items on the same line
+    fn separated(&mut self, items: &[&Node]) {
+        for (idx, item) in items.iter().enumerate() {
+            if idx > 0 {
+                self.write(", ");
+            }
+            self.node(item);
+        }
+    }
+
+    // Items on separate lines
+    fn list(&mut self, open: &str, items: &[&Node], close: &str) {
+        self.write(open);
+        self.indent += 1;
+        for (idx, item) in items.iter().enumerate() {
+            self.ensure_line();
+            self.node(item);
+            let is_last = idx + 1 == items.len();
+            if !is_last || (self.options.trailing_commas && allows_trailing_comma(item)) {
+                self.write(",");
+            }
+        }
+        self.indent -= 1;
+        self.ensure_line();
+        self.write(close);
+    }
+
+    fn verbatim(&mut self, node: &Node) {
+        let loc = *node.expression();
+        self.write_source(loc, !self.has_multiline_string(node));
+        self.push_heredocs(node, loc);
+    }
+
+    // Heredocs of the node with bodies outside of `printed` are printed after the current line
+    fn push_heredocs(&mut self, node: &Node, printed: Loc) {
+        let body = match node {
+            Node::Heredoc(heredoc) => Some((heredoc.heredoc_body_l, heredoc.heredoc_end_l)),
+            Node::XHeredoc(heredoc) => Some((heredoc.heredoc_body_l, heredoc.heredoc_end_l)),
+            _ => None,
+        };
+        if let Some((body_l, end_l)) = body {
+            if body_l.begin < printed.begin || body_l.begin >= printed.end {
+                self.heredocs.push(body_l.with_end(end_l.end));
+            }
+        }
+        for_each_child(node, &mut |child| self.push_heredocs(child, printed));
+    }
+
+    // Flat rendering
+
+    // Returns the node printed on a single line,
+    // `None` if it's not possible (or if the node has comments or heredocs)
+    fn flat(&self, node: &Node) -> Option<String> {
+        let loc = *node.expression();
+        if self.has_comments(loc) || has_heredoc(node) {
+            return None;
+        }
+
+        if let Some((prefix, operator_l, value)) = self.assignment(node) {
+            return Some(format!(
+                "{} {} {}",
+                single_line(prefix)?,
+                self.slice(operator_l),
+                self.flat(value)?
+            ));
+        }
+
+        match node {
+            Node::Str(str_) => self.flat_str(str_),
+            Node::Send(send) => {
+                let selector = match send.selector_l {
+                    Some(selector_l) => self.slice(selector_l),
+                    None => return self.flat_source(loc),
+                };
+                match (&send.recv, send.dot_l) {
+                    (Some(recv), None) => {
+                        if self.is_binary_operation(node) {
+                            Some(format!(
+                                "{} {} {}",
+                                self.flat(recv)?,
+                                selector,
+                                self.flat(&send.args[0])?
+                            ))
+                        } else if UNARY_OPERATORS.contains(&send.method_name.as_str())
+                            && send.args.is_empty()
+                            && selector != "not"
+                        {
+                            Some(format!("{}{}", selector, self.flat(recv)?))
+                        } else {
+                            self.flat_source(loc)
+                        }
+                    }
+                    (Some(recv), Some(dot_l)) => {
+                        let callee =
+                            format!("{}{}{}", self.flat(recv)?, self.slice(dot_l), selector);
+                        self.flat_call(callee, &send.args, send.begin_l)
+                    }
+                    (None, _) => self.flat_call(selector.to_string(), &send.args, send.begin_l),
+                }
+            }
+            Node::CSend(csend) => match csend.selector_l {
+                Some(selector_l) => {
+                    let callee = format!(
+                        "{}{}{}",
+                        self.flat(&csend.recv)?,
+                        self.slice(csend.dot_l),
+                        self.slice(selector_l)
+                    );
+                    self.flat_call(callee, &csend.args, csend.begin_l)
+                }
+                None => self.flat_source(loc),
+            },
+            Node::Super(super_) => {
+                self.flat_call(String::from("super"), &super_.args, super_.begin_l)
+            }
+            Node::Yield(yield_) => {
+                self.flat_call(String::from("yield"), &yield_.args, yield_.begin_l)
+            }
+            Node::Return(return_) => self.flat_call(String::from("return"), &return_.args, None),
+            Node::Break(break_) => self.flat_call(String::from("break"), &break_.args, None),
+            Node::Next(next) => self.flat_call(String::from("next"), &next.args, None),
+            Node::Index(index) => Some(format!(
+                "{}[{}]",
+                self.flat(&index.recv)?,
+                self.flat_list(&index.indexes)?
+            )),
+            Node::Array(array) => match array.begin_l {
+                None => self.flat_list(&array.elements),
+                Some(_) if self.is_bracketed(array.begin_l, "[") => {
+                    Some(format!("[{}]", self.flat_list(&array.elements)?))
+                }
+                Some(_) => self.flat_source(loc),
+            },
+            Node::Hash(hash) => match hash.begin_l {
+                None => self.flat_list(&hash.pairs),
+                Some(_) if self.is_bracketed(hash.begin_l, "{") => {
+                    if hash.pairs.is_empty() {
+                        Some(String::from("{}"))
+                    } else {
+                        Some(format!("{{ {} }}", self.flat_list(&hash.pairs)?))
+                    }
+                }
+                Some(_) => self.flat_source(loc),
+            },
+            Node::Kwargs(kwargs) => self.flat_list(&kwargs.pairs),
+            Node::Pair(pair) => match self.pair_prefix(pair) {
+                Some(prefix) => Some(format!("{} {}", prefix, self.flat(&pair.value)?)),
+                None => self.flat_source(loc),
+            },
+            Node::And(and) => Some(format!(
+                "{} {} {}",
+                self.flat(&and.lhs)?,
+                self.slice(and.operator_l),
+                self.flat(&and.rhs)?
+            )),
+            Node::Or(or) => Some(format!(
+                "{} {} {}",
+                self.flat(&or.lhs)?,
+                self.slice(or.operator_l),
+                self.flat(&or.rhs)?
+            )),
+            Node::Splat(splat) => self.flat_prefixed(splat.operator_l, splat.value.as_deref()),
+            Node::Kwsplat(kwsplat) => self.flat_prefixed(kwsplat.operator_l, Some(&kwsplat.value)),
+            Node::BlockPass(block_pass) => {
+                self.flat_prefixed(block_pass.operator_l, block_pass.value.as_deref())
+            }
+            Node::Begin(begin) if self.is_bracketed(begin.begin_l, "(") => {
+                let stmts = begin
+                    .statements
+                    .iter()
+                    .map(|stmt| self.flat(stmt))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(format!("({})", stmts.join("; ")))
+            }
+            Node::Block(block) => self.flat_block(
+                &block.call,
+                block.args.as_deref(),
+                block.body.as_deref(),
+                block.begin_l,
+            ),
+            Node::Numblock(numblock) => {
+                self.flat_block(&numblock.call, None, Some(&numblock.body), numblock.begin_l)
+            }
+            _ => self.flat_source(loc),
+        }
+    }
+
+    fn flat_source(&self, loc: Loc) -> Option<String> {
+        single_line(self.slice(loc)).map(String::from)
+    }
+
+    fn flat_list(&self, nodes: &[Node]) -> Option<String> {
+        let items = nodes
+            .iter()
+            .map(|node| self.flat(node))
+            .collect::<Option<Vec<_>>>()?;
+        Some(items.join(", "))
+    }
+
+    fn flat_call(&self, callee: String, args: &[Node], begin_l: Option<Loc>) -> Option<String> {
+        if begin_l.is_some() {
+            Some(format!("{}({})", callee, self.flat_list(args)?))
+        } else if args.is_empty() {
+            Some(callee)
+        } else {
+            Some(format!("{} {}", callee, self.flat_list(args)?))
+        }
+    }
+
+    fn flat_prefixed(&self, operator_l: Loc, value: Option<&Node>) -> Option<String> {
+        let value = match value {
+            Some(value) => self.flat(value)?,
+            None => String::new(),
+        };
+        Some(format!("{}{}", self.slice(operator_l), value))
+    }
+
+    fn flat_block(
+        &self,
+        call: &Node,
+        args: Option<&Node>,
+        body: Option<&Node>,
+        begin_l: Loc,
+    ) -> Option<String> {
+        if self.slice(begin_l) != "{" {
+            return None;
+        }
+
+        let mut out = match call {
+            Node::Lambda(_) => {
+                let mut out = String::from("->");
+                if let Some(args) = args {
+                    let args = self.flat_source(*args.expression())?;
+                    if !args.starts_with('(') {
+                        out.push(' ');
+                    }
+                    out.push_str(&args);
+                }
+                out.push_str(" {");
+                out
+            }
+            _ => {
+                let mut out = self.flat(call)?;
+                out.push_str(" {");
+                if let Some(args) = args {
+                    out.push(' ');
+                    out.push_str(&self.flat_source(*args.expression())?);
+                }
+                out
+            }
+        };
+
+        match &body.map(statements).unwrap_or_default()[..] {
+            [] if out.ends_with('{') => out.push('}'),
+            [] => out.push_str(" }"),
+            [stmt] => {
+                out.push(' ');
+                out.push_str(&self.flat(stmt)?);
+                out.push_str(" }");
+            }
+            _ => return None,
+        }
+        Some(out)
+    }
+
+    fn flat_str(&self, node: &Str) -> Option<String> {
+        let source = single_line(self.slice(node.expression_l))?;
+        let (begin_l, end_l) = match (node.begin_l, node.end_l) {
+            (Some(begin_l), Some(end_l)) => (begin_l, end_l),
+            _ => return Some(source.to_string()),
+        };
+        let quote = self.slice(begin_l);
+        if quote != self.slice(end_l) {
+            return Some(source.to_string());
+        }
+        let content = &self.source[begin_l.end..end_l.begin];
+        let is_plain = |quote: char| !content.contains(['\\', '#', quote]);
+
+        let requoted = match (self.options.quote_style, quote) {
+            (QuoteStyle::Double, "'") if is_plain('"') => format!("\"{}\"", content),
+            (QuoteStyle::Single, "\"") if is_plain('\'') => format!("'{}'", content),
+            _ => source.to_string(),
+        };
+        Some(requoted)
+    }
+
+    // Helpers
+
+    // Returns source of the left-hand side, operator and the value of the assignment
+    fn assignment<'n>(&self, node: &'n Node) -> Option<(&'a str, Loc, &'n Node)> {
+        let (operator_l, value) = match node {
+            Node::Lvasgn(asgn) => (asgn.operator_l?, asgn.value.as_deref()?),
+            Node::Ivasgn(asgn) => (asgn.operator_l?, asgn.value.as_deref()?),
+            Node::Gvasgn(asgn) => (asgn.operator_l?, asgn.value.as_deref()?),
+            Node::Cvasgn(asgn) => (asgn.operator_l?, asgn.value.as_deref()?),
+            Node::Casgn(asgn) => (asgn.operator_l?, asgn.value.as_deref()?),
+            Node::IndexAsgn(asgn) => (asgn.operator_l?, asgn.value.as_deref()?),
+            Node::Send(send) if send.args.len() == 1 => (send.operator_l?, &send.args[0]),
+            Node::CSend(csend) if csend.args.len() == 1 => (csend.operator_l?, &csend.args[0]),
+            Node::OpAsgn(asgn) => (asgn.operator_l, &*asgn.value),
+            Node::OrAsgn(asgn) => (asgn.operator_l, &*asgn.value),
+            Node::AndAsgn(asgn) => (asgn.operator_l, &*asgn.value),
+            Node::Masgn(asgn) => (asgn.operator_l, &*asgn.rhs),
+            _ => return None,
+        };
+        if value.expression().begin < operator_l.end {
+            return None;
+        }
+        let prefix = &self.source[node.expression().begin..operator_l.begin];
+        Some((prefix.trim_end(), operator_l, value))
+    }
+
+    // Returns a key of the pair with an operator (like `a:` or `a =>`)
+    fn pair_prefix(&self, node: &Pair) -> Option<String> {
+        if node.value.expression().begin < node.operator_l.end {
+            // value omission, `{ a: }`
+            return None;
+        }
+        if self.slice(node.operator_l) == "=>" {
+            Some(format!("{} =>", self.flat(&node.key)?))
+        } else {
+            single_line(&self.source[node.key.expression().begin..node.operator_l.end])
+                .map(String::from)
+        }
+    }
+
+    // Returns parts of a call without parentheses that has arguments, like `puts a, b`
+    fn command_call<'n>(&self, node: &'n Node) -> Option<CommandCall<'n>> {
+        let (recv, dot_l, selector_l, args, begin_l, operator_l) = match node {
+            Node::Send(send) => (
+                send.recv.as_deref(),
+                send.dot_l,
+                send.selector_l,
+                &send.args,
+                send.begin_l,
+                send.operator_l,
+            ),
+            Node::CSend(csend) => (
+                Some(&*csend.recv),
+                Some(csend.dot_l),
+                csend.selector_l,
+                &csend.args,
+                csend.begin_l,
+                csend.operator_l,
+            ),
+            _ => return None,
+        };
+        let is_command = selector_l.is_some()
+            && begin_l.is_none()
+            && operator_l.is_none()
+            && !args.is_empty()
+            && (recv.is_none() || dot_l.is_some());
+        is_command.then_some((recv, dot_l, selector_l, &args[..]))
+    }
+
+    fn is_binary_operation(&self, node: &Node) -> bool {
+        match node {
+            Node::Send(send) => {
+                send.recv.is_some()
+                    && send.dot_l.is_none()
+                    && send.begin_l.is_none()
+                    && send.selector_l.is_some()
+                    && send.args.len() == 1
+                    && BINARY_OPERATORS.contains(&send.method_name.as_str())
+            }
+            _ => false,
+        }
+    }
+
+    fn is_bracketed(&self, begin_l: Option<Loc>, bracket: &str) -> bool {
+        begin_l.is_some_and(|begin_l| self.slice(begin_l) == bracket)
+    }
+
+    // Modifier `rescue` (`foo rescue bar`) is printed as an expression
+    fn is_rescue_mod(&self, node: &Rescue) -> bool {
+        match (&node.body, &node.rescue_bodies[..]) {
+            (Some(body), [Node::RescueBody(rescue_body)]) => self.source
+                [body.expression().end..rescue_body.keyword_l.begin]
+                .bytes()
+                .all(|c| c == b' ' || c == b'\t'),
+            _ => false,
+        }
+    }
+
+    // Strings (and heredocs) that span multiple lines can't be re-indented
+    fn has_multiline_string(&self, node: &Node) -> bool {
+        match node {
+            Node::Heredoc(_) | Node::XHeredoc(_) => return true,
+            Node::Str(_)
+            | Node::Dstr(_)
+            | Node::Xstr(_)
+            | Node::Sym(_)
+            | Node::Dsym(_)
+            | Node::Regexp(_)
+                if self.slice(*node.expression()).contains('\n') =>
+            {
+                return true
+            }
+            _ => {}
+        }
+        let mut result = false;
+        for_each_child(node, &mut |child| {
+            result = result || self.has_multiline_string(child);
+        });
+        result
+    }
+
+    // Returns end of the node including bodies of its heredocs
+    fn source_end(&self, node: &Node) -> usize {
+        let mut end = node.expression().end;
+        match node {
+            Node::Heredoc(heredoc) => end = end.max(heredoc.heredoc_end_l.end),
+            Node::XHeredoc(heredoc) => end = end.max(heredoc.heredoc_end_l.end),
+            _ => {}
+        }
+        for_each_child(node, &mut |child| end = end.max(self.source_end(child)));
+        end
+    }
+
+    // Returns beginning of `__END__` (if any)
+    fn end_data(&self, ast: Option<&Node>) -> Option<usize> {
+        let code_end = ast.map_or(0, |ast| self.source_end(ast));
+        let comments_end = self
+            .comments
+            .last()
+            .map_or(0, |comment| comment.location.end);
+        let start = code_end.max(comments_end);
+        self.line_starts
+            .iter()
+            .copied()
+            .filter(|line_start| *line_start >= start && *line_start < self.source.len())
+            .find(|line_start| {
+                let line = self.source[*line_start..].lines().next().unwrap_or("");
+                line.trim_end_matches('\r') == "__END__"
+            })
+    }
+
+    // Output
+
+    fn write(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        self.write_indent();
+        self.out.push_str(s);
+    }
+
+    fn write_indent(&mut self) {
+        if self.at_line_start() {
+            let width = self.indent * self.options.indent_width;
+            self.out.extend(std::iter::repeat_n(' ', width));
+        }
+    }
+
+    // Copies source code of `loc`, nested lines are shifted
+    // by the same number of columns as the first line (if `reindent` is true)
+    fn write_source(&mut self, loc: Loc, reindent: bool) {
+        self.mark_comments(loc);
+        let text = self.slice(loc);
+        let reindent = reindent && !self.has_document_comments(loc);
+
+        self.write_indent();
+        let line_start = self.line_starts[self.line_of(loc.begin)];
+        let old_column = self.source[line_start..loc.begin].chars().count() as isize;
+        let delta = self.column() as isize - old_column;
+
+        let mut lines = text.split('\n');
+        self.out.push_str(lines.next().unwrap_or_default());
+        for line in lines {
+            self.out.push('\n');
+            if !reindent || delta == 0 {
+                self.out.push_str(line);
+            } else if line.trim().is_empty() {
+                // blank line, nothing to shift
+            } else if delta > 0 {
+                self.out.extend(std::iter::repeat_n(' ', delta as usize));
+                self.out.push_str(line);
+            } else {
+                let spaces = line.len() - line.trim_start_matches(' ').len();
+                self.out.push_str(&line[spaces.min(delta.unsigned_abs())..]);
+            }
+        }
+    }
+
+    fn has_document_comments(&self, loc: Loc) -> bool {
+        self.comments.iter().any(|comment| {
+            comment.kind == CommentType::Document
+                && comment.location.begin >= loc.begin
+                && comment.location.begin < loc.end
+        })
+    }
+
+    fn ensure_line(&mut self) {
+        if !self.at_line_start() {
+            self.newline();
+        }
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        let mut heredocs = std::mem::take(&mut self.heredocs);
+        heredocs.sort_by_key(|loc| loc.begin);
+        for loc in heredocs {
+            self.out.push_str(self.slice(loc));
+            if !self.out.ends_with('\n') {
+                self.out.push('\n');
+            }
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn column(&self) -> usize {
+        self.out
+            .rsplit('\n')
+            .next()
+            .map_or(0, |line| line.chars().count())
+    }
+
+    fn fits(&self, s: &str) -> bool {
+        let column = if self.at_line_start() {
+            self.indent * self.options.indent_width
+        } else {
+            self.column()
+        };
+        column + s.chars().count() <= self.options.line_width
+    }
+
+    fn slice(&self, loc: Loc) -> &'a str {
+        &self.source[loc.begin..loc.end]
+    }
+
+    fn line_of(&self, pos: usize) -> usize {
+        self.line_starts
+            .partition_point(|line_start| *line_start <= pos)
+            - 1
+    }
+}
+
+// Returns position of the `end` keyword
+fn end_of(end_l: Option<Loc>, expression_l: Loc) -> usize {
+    end_l.map_or(expression_l.end, |end_l| end_l.begin)
+}
+
+fn statements(node: &Node) -> Vec<&Node> {
+    match node {
+        Node::Begin(begin) if begin.begin_l.is_none() => begin.statements.iter().collect(),
+        _ => vec![node],
+    }
+}
+
+// Keyword arguments are printed as separate items
+fn call_args(args: &[Node]) -> Vec<&Node> {
+    let mut items = vec![];
+    for arg in args {
+        match arg {
+            Node::Kwargs(kwargs) => items.extend(kwargs.pairs.iter()),
+            _ => items.push(arg),
+        }
+    }
+    items
+}
+
+fn allows_trailing_comma(node: &Node) -> bool {
+    !matches!(node, Node::BlockPass(_) | Node::ForwardedArgs(_))
+}
+
+fn has_heredoc(node: &Node) -> bool {
+    if let Node::Heredoc(_) | Node::XHeredoc(_) = node {
+        return true;
+    }
+    let mut result = false;
+    for_each_child(node, &mut |child| result = result || has_heredoc(child));
+    result
+}
+
+fn single_line(s: &str) -> Option<&str> {
+    (!s.contains('\n')).then_some(s)
+}
+
+// Only whitespaces, `;`, `then` and `do` can be between a code and a trailing comment
+fn is_trivial_gap(s: &str) -> bool {
+    s.split(|c: char| c.is_whitespace() || c == ';')
+        .all(|word| word.is_empty() || word == "then" || word == "do")
+}
@@ -6,16 +6,21 @@ use crate::source::buffer::*;
 use crate::source::Comment;
 use crate::source::Decoder;
 use crate::source::MagicComment;
+use crate::source::SourceBytes;
+use crate::source::{ContentMapBuilder, StringContentMaps};
 use crate::str_term::{str_types::*, HeredocEnd, StrTerm, StringLiteral};
+use crate::Cancellation;
+use crate::Limits;
 use crate::Loc;
 use crate::SharedContext;
 use crate::StackState;
 use crate::StaticEnvironment;
 use crate::Token;
 use crate::TokenBuf;
+use crate::TokenValue;
 use crate::{error::Diagnostics, Bytes};
 use crate::{lex_states::*, LexState};
-use crate::{Diagnostic, DiagnosticMessage, ErrorLevel};
+use crate::{Diagnostic, DiagnosticMessage, ErrorLevel, Message};
 
 /// A struct responsible for converting a given input
 /// into a sequence of tokens
@@ -26,6 +31,7 @@ pub struct Lexer {
     pub(crate) lval: Option<Bytes>,
     pub(crate) lval_start: Option<usize>,
     pub(crate) lval_end: Option<usize>,
+    pub(crate) lval_content_map: Option<ContentMapBuilder>,
 
     pub(crate) strterm: Option<Box<StrTerm>>,
     /// Current state of the lexer, used internally for testing
@@ -79,9 +85,17 @@ pub struct Lexer {
     pub(crate) diagnostics: Diagnostics,
     pub(crate) comments: Vec<Comment>,
     pub(crate) magic_comments: Vec<MagicComment>,
+    // value of the `warn_indent` magic comment
+    pub(crate) warn_indent: Option<bool>,
+    pub(crate) string_content_maps: StringContentMaps,
+    pub(crate) record_string_content_maps: bool,
 
     #[doc(hidden)]
     pub tokens_factory: PoolFactory<Token>,
+
+    pub(crate) cancellation: Cancellation,
+    pub(crate) limits: Limits,
+    pub(crate) tokens_count: usize,
 }
 
 impl Lexer {
@@ -94,7 +108,7 @@ impl Lexer {
     /// Constructs an instance of Lexer
     pub fn new<Bytes, Name>(bytes: Bytes, name: Name, decoder: Option<Decoder>) -> Self
     where
-        Bytes: Into<Vec<u8>>,
+        Bytes: Into<SourceBytes>,
         Name: Into<String>,
     {
         Self {
@@ -106,6 +120,81 @@ impl Lexer {
         }
     }
 
+    // Re-initializes the lexer with a new input,
+    // keeps shared state (that must be cleared by the caller)
+    // and allocated memory of the token buffer
+    pub(crate) fn reset(&mut self, bytes: SourceBytes, name: String, decoder: Option<Decoder>) {
+        // fields are reset in place, so buffers keep their allocated memory;
+        // exhaustive destructuring makes sure that new fields are not forgotten
+        let Self {
+            buffer,
+            lval,
+            lval_start,
+            lval_end,
+            lval_content_map,
+            strterm,
+            lex_state,
+            paren_nest,
+            lpar_beg,
+            brace_nest,
+            cond,
+            cmdarg,
+            tokenbuf,
+            context: _,
+            command_start,
+            token_seen,
+            static_env: _,
+            diagnostics: _,
+            comments,
+            magic_comments,
+            warn_indent,
+            string_content_maps: _,
+            record_string_content_maps,
+            tokens_factory: _,
+            cancellation,
+            limits,
+            tokens_count,
+        } = self;
+
+        // decoded input of the previous run is moved to its result,
+        // so there's nothing to reuse in the buffer
+        *buffer = Buffer::new(name, bytes, decoder);
+        *lval = None;
+        *lval_start = None;
+        *lval_end = None;
+        *lval_content_map = None;
+        *strterm = None;
+        *lex_state = LexState::default();
+        *paren_nest = 0;
+        *lpar_beg = -1;
+        *brace_nest = 0;
+        *cond = StackState::new("cond");
+        *cmdarg = StackState::new("cmdarg");
+        tokenbuf.clear();
+        *command_start = false;
+        *token_seen = false;
+        comments.clear();
+        magic_comments.clear();
+        *warn_indent = None;
+        *record_string_content_maps = false;
+        *cancellation = Cancellation::default();
+        *limits = Limits::default();
+        *tokens_count = 0;
+    }
+
+    // Stops lexing with an error, all subsequent tokens are END_OF_INPUT
+    pub(crate) fn stop(&mut self, reason: Message) {
+        self.diagnostics.emit(Diagnostic {
+            level: ErrorLevel::Error,
+            message: reason.clone(),
+            loc: Loc {
+                begin: self.buffer.pcur,
+                end: self.buffer.pcur,
+            },
+        });
+        self.cancellation.stop(reason);
+    }
+
     /// Tokenizes given input until EOF
     ///
     /// Keep in mind that Lexer in Ruby is driven by Parser,
@@ -128,29 +217,52 @@ impl Lexer {
     }
 
     pub(crate) fn yylex(&mut self) -> PoolValue<Token> {
+        if !self.cancellation.is_cancelled() {
+            self.tokens_count += 1;
+            let reason = self
+                .limits
+                .check_token(self.tokens_count, self.paren_nest)
+                .or_else(|| self.cancellation.check());
+            if let Some(reason) = reason {
+                self.stop(reason);
+            }
+        }
+        if self.cancellation.is_cancelled() {
+            // stops the parser as if the input ends here
+            let loc = Loc {
+                begin: self.buffer.pcur,
+                end: self.buffer.pcur,
+            };
+            return self.tokens_factory.alloc(Token {
+                token_type: Self::END_OF_INPUT,
+                token_value: TokenValue::default(),
+                loc,
+            });
+        }
+
         self.lval = None;
+        self.lval_content_map = None;
 
         let token_type = self.parser_yylex();
 
         let begin = std::mem::take(&mut self.lval_start).unwrap_or(self.buffer.ptok);
         let mut end = std::mem::take(&mut self.lval_end).unwrap_or(self.buffer.pcur);
 
-        let mut token_value = self
-            .lval
-            .take()
-            .or_else(|| {
-                // take raw value if nothing was manually captured
-                self.buffer
-                    .substr_at(begin, end)
-                    .map(|s| Bytes::new(Vec::from(s)))
-            })
-            .unwrap_or_else(|| Bytes::new(vec![]));
+        // raw value is taken if nothing was manually captured
+        let mut token_value = self.buffer.token_value(self.lval.take(), begin, end);
 
         if token_type == Self::tNL {
-            token_value = Bytes::new(vec![b'\n']);
+            token_value = TokenValue::from(vec![b'\n']);
             end = begin + 1;
         }
 
+        if token_type == Self::tSTRING_CONTENT {
+            if let Some(content_map) = self.lval_content_map.take() {
+                self.string_content_maps
+                    .push(content_map.build(Loc { begin, end }));
+            }
+        }
+
         let token = self.tokens_factory.alloc(Token {
             token_type,
             token_value,
@@ -1093,7 +1205,8 @@ impl Lexer {
         Self::tNL
     }
 
-    pub(crate) fn warn(&mut self, message: DiagnosticMessage, loc: Loc) {
+    pub(crate) fn warn(&mut self, message: impl Into<Message>, loc: Loc) {
+        let message = message.into();
         println_if_debug_lexer!("WARNING: {}", message.render());
         let diagnostic = Diagnostic {
             level: ErrorLevel::Warning,
@@ -1126,7 +1239,8 @@ impl Lexer {
         token_type
     }
 
-    pub(crate) fn compile_error(&mut self, message: DiagnosticMessage, loc: Loc) {
+    pub(crate) fn compile_error(&mut self, message: impl Into<Message>, loc: Loc) {
+        let message = message.into();
         println_if_debug_lexer!("Compile error: {}", message.render());
         let diagnostic = Diagnostic {
             level: ErrorLevel::Error,
@@ -1183,11 +1297,12 @@ impl Lexer {
         // nop
     }
 
-    pub(crate) fn yyerror0(&mut self, message: DiagnosticMessage) {
+    pub(crate) fn yyerror0(&mut self, message: impl Into<Message>) {
         self.yyerror1(message, self.current_loc());
     }
 
-    pub(crate) fn yyerror1(&mut self, message: DiagnosticMessage, loc: Loc) {
+    pub(crate) fn yyerror1(&mut self, message: impl Into<Message>, loc: Loc) {
+        let message = message.into();
         println_if_debug_lexer!("yyerror0: {}", message.render());
         let diagnostic = Diagnostic {
             level: ErrorLevel::Error,
@@ -1244,7 +1359,7 @@ impl Lexer {
         Ok(())
     }
 
-    fn _multibyte_char_len(&self, ptr: usize) -> Option<usize> {
+    pub(crate) fn _multibyte_char_len(&self, ptr: usize) -> Option<usize> {
         let c1 = self.buffer.byte_at(ptr).as_option()?;
 
         let len = if c1 & 0x80 == 0 {
@@ -134,6 +134,11 @@ impl Lexer {
             return self.here_document_error(&here, eos, len);
         }
         let bol = self.buffer.was_bol();
+        if bol {
+            // `nextc` has moved to the next line, it goes after bodies of heredocs
+            // that start earlier on the same line (i.e. after `<<A` body in `<<A + <<B`)
+            self.lval_start = Some(self.buffer.pbeg);
+        }
         if !bol {
             /* not beginning of line, cannot be the terminator */
         } else if self.buffer.heredoc_line_indent == -1 {
@@ -181,7 +186,7 @@ impl Lexer {
                 }
 
                 match self.buffer.substr_at(ptr, ptr_end) {
-                    Some(s) => str_.append(s),
+                    Some(s) => str_.append_source(s, ptr),
                     _ => panic!(
                         "no substr {}..{} (len = {})",
                         ptr,
@@ -190,7 +195,10 @@ impl Lexer {
                     ),
                 };
                 if ptr_end < self.buffer.pend {
-                    str_.push(b'\n')
+                    // `\r\n` or `\r`
+                    str_.begin_source(ptr_end);
+                    str_.push(b'\n');
+                    str_.end_source(self.buffer.pend);
                 }
                 self.buffer.goto_eol();
                 if self.buffer.heredoc_indent > 0 {
@@ -225,7 +233,9 @@ impl Lexer {
                 if let Some(t) = t {
                     return t;
                 }
+                self.tokenbuf.begin_source(self.buffer.pcur - 1);
                 self.tokadd(b'#');
+                self.tokenbuf.end_source(self.buffer.pcur);
                 c = self.nextc();
             }
             loop {
@@ -247,8 +257,10 @@ impl Lexer {
                     }
                     return self.heredoc_flush();
                 }
+                self.tokenbuf.begin_source(self.buffer.pcur);
                 let cc = self.nextc();
                 self.tokadd(cc);
+                self.tokenbuf.end_source(self.buffer.pcur);
                 if self.buffer.heredoc_indent > 0 {
                     self.buffer.goto_eol();
                     return self.heredoc_flush();
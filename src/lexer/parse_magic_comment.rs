@@ -150,6 +150,18 @@ impl Lexer {
         0
     }
 
+    // Mirrors `parser_get_bool` from MRI
+    fn magic_comment_bool(&self, vbeg: usize, vend: usize) -> Option<bool> {
+        let value = self.buffer.substr_at(vbeg, vend)?;
+        if value.eq_ignore_ascii_case(b"true") {
+            Some(true)
+        } else if value.eq_ignore_ascii_case(b"false") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn magic_comment(&mut self, mut str_: usize, mut len: usize) -> Result<bool, ()> {
         let mut indicator = false;
         let mut name;
@@ -353,6 +365,10 @@ impl Lexer {
                         }
                     }
 
+                    if kind == &MagicCommentKind::WarnIndent && !self.token_seen {
+                        self.warn_indent = self.magic_comment_bool(vbeg, vend);
+                    }
+
                     let key_l = self.loc(beg, beg + n);
                     let value_l = self.loc(vbeg, vend);
 
@@ -4,9 +4,9 @@ use std::io::Write;
 use crate::maybe_byte::*;
 use crate::source::buffer::*;
 use crate::str_term::{str_types::*, StrTerm};
-use crate::TokenBuf;
-use crate::{lex_states::*, DiagnosticMessage};
+use crate::{lex_states::*, DiagnosticMessage, Message};
 use crate::{lexer::*, str_term::StringLiteral};
+use crate::{Loc, TokenBuf, TokenEncoding};
 
 const ESCAPE_CONTROL: usize = 1;
 const ESCAPE_META: usize = 2;
@@ -92,7 +92,9 @@ impl Lexer {
                 self.restore_strterm(quote);
                 return t;
             }
+            self.tokenbuf.begin_source(self.buffer.pcur - 1);
             self.tokadd(b'#');
+            self.tokenbuf.end_source(self.buffer.pcur);
             c = self.nextc();
         }
         self.buffer.pushback(c);
@@ -245,10 +247,15 @@ impl Lexer {
         let _erred = false;
 
         loop {
+            // bytes added on the previous iteration come from the previous char (or escape sequence)
+            self.tokenbuf.end_source(self.buffer.pcur);
+            let char_begin = self.buffer.pcur;
             c = self.nextc();
             if c.is_eof() {
                 break;
             }
+            // `nextc` may switch to the next line (which is not necessarily adjacent)
+            self.tokenbuf.begin_source(char_begin.max(self.buffer.pbeg));
 
             if self.buffer.heredoc_indent > 0 {
                 self.update_heredoc_indent(c);
@@ -360,7 +367,7 @@ impl Lexer {
                     }
                 }
             } else if !self.is_ascii() {
-                self.tokadd(c);
+                self.tokadd_non_ascii(c);
                 continue;
             } else if (func & STR_FUNC_QWORDS) != 0 && c.is_space() {
                 self.buffer.pushback(c);
@@ -368,10 +375,64 @@ impl Lexer {
             }
             self.tokadd(c);
         }
+        self.tokenbuf.end_source(self.buffer.pcur);
 
         Some(c)
     }
 
+    // Adds a non-ASCII char of the source, it must be valid in the source encoding
+    fn tokadd_non_ascii(&mut self, c: MaybeByte) {
+        let char_begin = self.buffer.pcur - 1;
+        let char_len = self._multibyte_char_len(char_begin);
+        let char_end = char_begin + char_len.unwrap_or(1);
+        if !self.set_token_encoding(TokenEncoding::Source, self.loc(char_begin, char_end)) {
+            // mixed char is skipped
+            self.buffer.pcur = char_end;
+            return;
+        }
+        match char_len {
+            Some(len) => {
+                self.tokadd(c);
+                self.buffer.pcur += len - 1;
+                self.tokcopy(len - 1);
+            }
+            None => {
+                // any byte sequence is valid in binary strings
+                if self.buffer.input.decoded.encoding.as_deref() != Some("ASCII-8BIT") {
+                    self.yyerror1(
+                        DiagnosticMessage::InvalidMultibyteChar {},
+                        self.loc(char_begin, char_end),
+                    );
+                }
+                self.tokadd(c);
+            }
+        }
+    }
+
+    // Records where non-ASCII chars of the current token come from,
+    // reports an error if source chars are mixed with UTF-8 escapes
+    // in a source that is not in UTF-8
+    fn set_token_encoding(&mut self, encoding: TokenEncoding, loc: Loc) -> bool {
+        let source_encoding = self
+            .buffer
+            .input
+            .decoded
+            .encoding
+            .clone()
+            .unwrap_or_else(|| String::from("UTF-8"));
+
+        match self.tokenbuf.encoding {
+            Some(current) if current != encoding && source_encoding != "UTF-8" => {
+                self.compile_error(Message::Utf8MixedWithinSource { source_encoding }, loc);
+                false
+            }
+            _ => {
+                self.tokenbuf.encoding = Some(encoding);
+                true
+            }
+        }
+    }
+
     pub(crate) fn flush_string_content(&mut self) {
         // noop
     }
@@ -478,9 +539,10 @@ impl Lexer {
         if regexp_literal != 0 {
             self.tokcopy(numlen);
         } else if codepoint >= 0x80 {
-            // if self.buffer.encoding != "utf-8" {
-            //     panic!("UTF-8 mixed within source");
-            // }
+            let loc = self.loc(self.buffer.pcur - numlen, self.buffer.pcur);
+            if !self.set_token_encoding(TokenEncoding::Utf8, loc) {
+                return wide;
+            }
             self.tokaddmbc(codepoint);
         } else {
             self.tokadd(codepoint as u8)
@@ -26,6 +26,9 @@ impl Lexer {
     pub(crate) fn set_yylval_str(&mut self, value: &TokenBuf) {
         println_if_debug_lexer!("set_yylval_str {:#?}", value);
         self.lval = Some(value.bytes.clone());
+        if self.record_string_content_maps {
+            self.lval_content_map = Some(value.content_map.clone());
+        }
     }
 
     pub(crate) fn set_yylval_name(&mut self) {
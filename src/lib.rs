@@ -40,12 +40,22 @@ pub use static_environment::StaticEnvironment;
 
 pub(crate) mod parse_value;
 
+mod cancellation;
+pub use cancellation::CancellationToken;
+pub(crate) use cancellation::Cancellation;
+
+mod limits;
+pub(crate) use limits::Limits;
+
 mod parser_options;
 pub use parser_options::ParserOptions;
 
 mod parser_result;
 pub use parser_result::ParserResult;
 
+mod ast;
+pub use ast::Ast;
+
 mod parser;
 pub use parser::Parser;
 
@@ -61,8 +71,14 @@ pub(crate) use max_numparam_stack::MaxNumparamStack;
 mod variables_stack;
 pub(crate) use variables_stack::VariablesStack;
 
+mod token_info;
+pub(crate) use token_info::TokenInfoStack;
+
+mod trivia;
+pub(crate) use trivia::{insert_trivia, insert_trivia_with_lex_states};
+
 mod error;
-pub use error::{Diagnostic, ErrorLevel};
+pub use error::{Diagnostic, ErrorLevel, Message};
 
 pub(crate) mod maybe_byte;
 
@@ -71,7 +87,7 @@ pub use lex_state::lex_states;
 pub use lex_state::LexState;
 
 mod token_buf;
-pub(crate) use token_buf::TokenBuf;
+pub(crate) use token_buf::{TokenBuf, TokenEncoding};
 
 mod reserved_words;
 pub use reserved_words::{reserved_word, ReservedWord};
@@ -90,5 +106,41 @@ pub use lib_ruby_parser_ast::traverse;
 mod token;
 pub use token::Token;
 
+mod token_value;
+pub use token_value::TokenValue;
+
+/// Module with a parser of regexp literals (Onigmo syntax)
+pub mod regexp;
+
+/// Module to compute values of numeric literals
+pub mod numeric;
+
+/// Module to analyze lexical scopes and resolve local variables
+pub mod scope;
+
+/// Module to find nodes by their source locations
+pub mod lookup;
+
+/// Module to access fields of nodes by their names
+pub mod fields;
+
+/// Module to search nodes using a pattern-matching query language
+pub mod query;
+
+/// Module to rewrite AST using `Fold` and `VisitorMut` traits
+pub mod rewrite;
+
+/// Module with a lossless syntax tree built from the AST and tokens (including trivia)
+pub mod syntax_tree;
+
+/// Module to format Ruby code
+pub mod format;
+
+/// Module to convert tokens and AST to the output of Ruby's `Ripper`
+pub mod ripper;
+
+/// Module to convert AST to the shape of Prism's syntax tree
+pub mod prism;
+
 #[cfg(test)]
 mod tests;
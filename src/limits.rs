@@ -0,0 +1,87 @@
+use crate::rewrite::for_each_child;
+use crate::{Loc, Message, Node};
+
+// Limits of resources that can be used by the parser, see `ParserOptions`
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Limits {
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_input_size: Option<usize>,
+    pub(crate) max_tokens: Option<usize>,
+}
+
+impl Limits {
+    pub(crate) fn check_input_size(&self, size: usize) -> Option<Message> {
+        let max_input_size = self.max_input_size?;
+        if size > max_input_size {
+            Some(Message::InputTooLarge { max_input_size })
+        } else {
+            None
+        }
+    }
+
+    // Checks the number of tokens read so far and the nesting of
+    // parentheses, brackets and braces tracked by the lexer
+    pub(crate) fn check_token(&self, tokens_count: usize, paren_nest: i32) -> Option<Message> {
+        if let Some(max_tokens) = self.max_tokens {
+            if tokens_count > max_tokens {
+                return Some(Message::TooManyTokens { max_tokens });
+            }
+        }
+        if let Some(max_depth) = self.max_depth {
+            if usize::try_from(paren_nest).is_ok_and(|nest| nest > max_depth) {
+                return Some(Message::NestingTooDeep { max_depth });
+            }
+        }
+        None
+    }
+
+    // Returns location of the first node (in the order of `Visitor`)
+    // that is nested deeper than `max_depth` (the root node has depth 1),
+    // traverses the tree without recursion
+    pub(crate) fn find_too_deep_node(&self, root: &Node) -> Option<Loc> {
+        let max_depth = self.max_depth?;
+        let mut stack = vec![(root, 1)];
+        let mut children = vec![];
+        while let Some((node, depth)) = stack.pop() {
+            if depth > max_depth {
+                return Some(*node.expression());
+            }
+            for_each_child(node, &mut |child| children.push(child));
+            stack.extend(children.drain(..).rev().map(|child| (child, depth + 1)));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+use crate::nodes::{Array, Int};
+
+#[test]
+fn test_find_too_deep_node() {
+    let loc = |begin, end| Loc { begin, end };
+    // [[1]]
+    let ast = Node::Array(Array {
+        elements: vec![Node::Array(Array {
+            elements: vec![Node::Int(Int {
+                value: String::from("1"),
+                operator_l: None,
+                expression_l: loc(2, 3),
+            })],
+            begin_l: Some(loc(1, 2)),
+            end_l: Some(loc(3, 4)),
+            expression_l: loc(1, 4),
+        })],
+        begin_l: Some(loc(0, 1)),
+        end_l: Some(loc(4, 5)),
+        expression_l: loc(0, 5),
+    });
+
+    let limits = |max_depth| Limits {
+        max_depth: Some(max_depth),
+        ..Default::default()
+    };
+    assert_eq!(limits(3).find_too_deep_node(&ast), None);
+    assert_eq!(limits(2).find_too_deep_node(&ast), Some(loc(2, 3)));
+    assert_eq!(limits(1).find_too_deep_node(&ast), Some(loc(1, 4)));
+    assert_eq!(Limits::default().find_too_deep_node(&ast), None);
+}
@@ -9,6 +9,12 @@ pub trait LocExt {
     fn expand_to_line(&self, input: &DecodedInput) -> Option<(usize, Loc)>;
     /// Returns source code of the current `Loc` on a given `Input`
     fn source(&self, input: &DecodedInput) -> Option<String>;
+    /// Converts `Loc` (that points to decoded bytes) to a range of bytes in the original input
+    /// (i.e. the one that has been passed to the `Parser` before decoding)
+    fn original_loc(&self, input: &DecodedInput) -> Option<Loc>;
+    /// Returns original bytes of the current `Loc`,
+    /// `original` must be the input that has been passed to the `Parser`
+    fn original_source<'a>(&self, input: &DecodedInput, original: &'a [u8]) -> Option<&'a [u8]>;
 }
 
 impl LocExt for Loc {
@@ -34,6 +40,18 @@ impl LocExt for Loc {
         let bytes = input.substr_at(self.begin, self.end)?;
         Some(String::from_utf8_lossy(bytes).into_owned())
     }
+
+    fn original_loc(&self, input: &DecodedInput) -> Option<Loc> {
+        if self.begin > self.end || self.end > input.len() {
+            return None;
+        }
+        Some(input.offset_map.original_loc(*self))
+    }
+
+    fn original_source<'a>(&self, input: &DecodedInput, original: &'a [u8]) -> Option<&'a [u8]> {
+        let Loc { begin, end } = self.original_loc(input)?;
+        original.get(begin..end)
+    }
 }
 
 // impl Loc {
@@ -0,0 +1,300 @@
+//! Lookup of nodes by source locations
+
+use crate::rewrite::for_each_child;
+use crate::{Loc, Node};
+
+/// Chain of nodes from the root of the AST to some node,
+/// each node is a child of the previous one.
+///
+/// ```
+/// use lib_ruby_parser::{lookup, Parser, ParserOptions};
+///
+/// let parser = Parser::new(b"foo(1 + 2)".to_vec(), ParserOptions::default());
+/// let ast = parser.do_parse().ast.unwrap();
+///
+/// let path = lookup::node_at(&ast, 8).unwrap();
+/// assert_eq!(path.node().str_type(), "int");
+/// assert_eq!(
+///     path.ancestors().map(|node| node.str_type()).collect::<Vec<_>>(),
+///     vec!["send", "send"]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodePath<'a> {
+    /// Nodes of the chain, the first one is the root
+    pub nodes: Vec<&'a Node>,
+}
+
+impl<'a> NodePath<'a> {
+    /// Returns the last node of the chain
+    pub fn node(&self) -> &'a Node {
+        self.nodes[self.nodes.len() - 1]
+    }
+
+    /// Returns the root of the AST
+    pub fn root(&self) -> &'a Node {
+        self.nodes[0]
+    }
+
+    /// Returns the direct parent of the node, `None` for the root
+    pub fn parent(&self) -> Option<&'a Node> {
+        self.ancestors().next()
+    }
+
+    /// Returns all ancestors of the node, from the direct parent to the root
+    pub fn ancestors(&self) -> impl Iterator<Item = &'a Node> + '_ {
+        self.nodes[..self.nodes.len() - 1].iter().rev().copied()
+    }
+}
+
+/// Returns a chain of nodes from `root` to the deepest node that contains `offset`,
+/// `None` if `offset` is outside of the AST.
+///
+/// A node contains an offset if it's in its `expression_l` (the end is exclusive).
+/// Bodies of heredocs (including terminators) are outside of `expression_l`
+/// of the heredoc and its parents, so `Heredoc` and `XHeredoc` nodes
+/// also contain offsets of their `heredoc_body_l` and `heredoc_end_l`.
+pub fn node_at(root: &Node, offset: usize) -> Option<NodePath<'_>> {
+    let range = Loc {
+        begin: offset,
+        end: offset + 1,
+    };
+    let visited = walk(root, &range);
+    let mut best: Option<usize> = None;
+    for (idx, node) in visited.iter().enumerate() {
+        if node.overlaps && best.is_none_or(|best| visited[best].depth < node.depth) {
+            best = Some(idx)
+        }
+    }
+    best.map(|idx| path_to(&visited, idx))
+}
+
+/// Returns chains of nodes from `root` to every node that overlaps `loc`,
+/// parents go before their children.
+///
+/// Just like in `node_at` bodies of heredocs belong to `Heredoc` and `XHeredoc` nodes.
+/// An empty `loc` overlaps nodes that contain its `begin`.
+pub fn nodes_overlapping<'a>(root: &'a Node, loc: &Loc) -> Vec<NodePath<'a>> {
+    let range = Loc {
+        begin: loc.begin,
+        end: loc.end.max(loc.begin + 1),
+    };
+    let visited = walk(root, &range);
+    (0..visited.len())
+        .filter(|idx| visited[*idx].overlaps)
+        .map(|idx| path_to(&visited, idx))
+        .collect()
+}
+
+fn overlaps(node: &Node, range: &Loc) -> bool {
+    let overlaps = |loc: &Loc| loc.begin < range.end && range.begin < loc.end;
+
+    match node {
+        Node::Heredoc(heredoc) => {
+            overlaps(&heredoc.expression_l)
+                || overlaps(&heredoc.heredoc_body_l)
+                || overlaps(&heredoc.heredoc_end_l)
+        }
+        Node::XHeredoc(heredoc) => {
+            overlaps(&heredoc.expression_l)
+                || overlaps(&heredoc.heredoc_body_l)
+                || overlaps(&heredoc.heredoc_end_l)
+        }
+        _ => overlaps(node.expression()),
+    }
+}
+
+// A node visited by `walk`
+struct Visited<'a> {
+    node: &'a Node,
+    // Index of the parent in the list of visited nodes, `None` for the root
+    parent: Option<usize>,
+    depth: usize,
+    // `false` for ancestors of heredocs that are visited only to reach their bodies
+    overlaps: bool,
+}
+
+// Visits nodes that overlap `range` (parents go before their children),
+// subtrees that don't overlap it are skipped.
+//
+// The only exception is heredocs: their bodies are outside of `expression_l`
+// of their parents, so the walk also enters nodes that contain
+// an identifier of the heredoc with the body that overlaps `range`.
+fn walk<'a>(root: &'a Node, range: &Loc) -> Vec<Visited<'a>> {
+    let mut heredocs = vec![];
+    find_heredocs(root, range, &mut heredocs);
+
+    let mut visited = vec![];
+    visit(root, None, range, &heredocs, &mut visited);
+    visited
+}
+
+fn visit<'a>(
+    node: &'a Node,
+    parent: Option<usize>,
+    range: &Loc,
+    heredocs: &[Loc],
+    visited: &mut Vec<Visited<'a>>,
+) {
+    let matches = overlaps(node, range);
+    if !matches && !heredocs.iter().any(|heredoc| overlaps(node, heredoc)) {
+        return;
+    }
+
+    let idx = visited.len();
+    visited.push(Visited {
+        node,
+        parent,
+        depth: parent.map_or(0, |parent| visited[parent].depth + 1),
+        overlaps: matches,
+    });
+    for_each_child(node, &mut |child| {
+        visit(child, Some(idx), range, heredocs, visited)
+    });
+}
+
+// Collects `expression_l` (i.e. identifiers) of heredocs with bodies or terminators that overlap `range`.
+//
+// Bodies always go after identifiers, so subtrees that start after `range` are skipped.
+fn find_heredocs(node: &Node, range: &Loc, heredocs: &mut Vec<Loc>) {
+    if node.expression().begin >= range.end {
+        return;
+    }
+    match node {
+        Node::Heredoc(heredoc) if overlaps(node, range) => heredocs.push(heredoc.expression_l),
+        Node::XHeredoc(heredoc) if overlaps(node, range) => heredocs.push(heredoc.expression_l),
+        _ => {}
+    }
+    for_each_child(node, &mut |child| find_heredocs(child, range, heredocs));
+}
+
+fn path_to<'a>(visited: &[Visited<'a>], mut idx: usize) -> NodePath<'a> {
+    let mut nodes = Vec::with_capacity(visited[idx].depth + 1);
+    nodes.push(visited[idx].node);
+    while let Some(parent) = visited[idx].parent {
+        nodes.push(visited[parent].node);
+        idx = parent;
+    }
+    nodes.reverse();
+    NodePath { nodes }
+}
+
+/// Returns direct children of the node in the order of `traverse::visitor::Visitor`
+pub fn children(node: &Node) -> Vec<&Node> {
+    let mut children = vec![];
+    for_each_child(node, &mut |child| children.push(child));
+    children
+}
+
+#[cfg(test)]
+fn heredoc_ast() -> Node {
+    use crate::nodes::{Heredoc, Int, Send, Str};
+    use crate::Bytes;
+
+    // foo(<<~A, 1)\n  x\nA\n
+    let loc = |begin, end| Loc { begin, end };
+    Node::Send(Send {
+        recv: None,
+        method_name: String::from("foo"),
+        args: vec![
+            Node::Heredoc(Heredoc {
+                parts: vec![Node::Str(Str {
+                    value: Bytes::new(b"x\n".to_vec()),
+                    begin_l: None,
+                    end_l: None,
+                    expression_l: loc(13, 17),
+                })],
+                heredoc_body_l: loc(13, 17),
+                heredoc_end_l: loc(17, 18),
+                expression_l: loc(4, 8),
+            }),
+            Node::Int(Int {
+                value: String::from("1"),
+                operator_l: None,
+                expression_l: loc(10, 11),
+            }),
+        ],
+        dot_l: None,
+        selector_l: Some(loc(0, 3)),
+        begin_l: Some(loc(3, 4)),
+        end_l: Some(loc(11, 12)),
+        operator_l: None,
+        expression_l: loc(0, 12),
+    })
+}
+
+#[cfg(test)]
+fn types(nodes: impl Iterator<Item = &'static Node>) -> Vec<&'static str> {
+    nodes.map(|node| node.str_type()).collect()
+}
+
+#[test]
+fn test_node_at() {
+    let ast = Box::leak(Box::new(heredoc_ast()));
+
+    let path = node_at(ast, 1).unwrap();
+    assert_eq!(path.node().str_type(), "send");
+    assert_eq!(path.parent(), None);
+
+    let path = node_at(ast, 4).unwrap();
+    assert_eq!(path.node().str_type(), "dstr");
+    assert_eq!(types(path.ancestors()), vec!["send"]);
+
+    let path = node_at(ast, 10).unwrap();
+    assert_eq!(path.node().str_type(), "int");
+
+    assert_eq!(node_at(ast, 12), None);
+}
+
+#[test]
+fn test_node_at_heredoc_body() {
+    let ast = Box::leak(Box::new(heredoc_ast()));
+
+    let path = node_at(ast, 15).unwrap();
+    assert_eq!(path.node().str_type(), "str");
+    assert_eq!(types(path.ancestors()), vec!["dstr", "send"]);
+    assert_eq!(path.root(), &*ast);
+
+    let path = node_at(ast, 17).unwrap();
+    assert_eq!(path.node().str_type(), "dstr");
+}
+
+#[test]
+fn test_nodes_overlapping() {
+    let ast = Box::leak(Box::new(heredoc_ast()));
+
+    let paths = nodes_overlapping(ast, &Loc { begin: 9, end: 14 });
+    assert_eq!(
+        types(paths.iter().map(|path| path.node())),
+        vec!["send", "dstr", "str", "int"]
+    );
+    assert_eq!(
+        types(paths[2].nodes.iter().copied()),
+        vec!["send", "dstr", "str"]
+    );
+
+    let paths = nodes_overlapping(ast, &Loc { begin: 10, end: 10 });
+    assert_eq!(
+        types(paths.iter().map(|path| path.node())),
+        vec!["send", "int"]
+    );
+}
+
+#[test]
+fn test_walk_skips_other_subtrees() {
+    let ast = heredoc_ast();
+    let visited = |begin, end| {
+        walk(&ast, &Loc { begin, end })
+            .iter()
+            .map(|visited| (visited.node.str_type(), visited.overlaps))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(visited(10, 11), vec![("send", true), ("int", true)]);
+    // `send` doesn't contain the body, it's visited only to reach the heredoc
+    assert_eq!(
+        visited(15, 16),
+        vec![("send", false), ("dstr", true), ("str", true)]
+    );
+    assert_eq!(visited(18, 19), vec![]);
+}
@@ -25,6 +25,10 @@ impl MaxNumparamStack {
         self.stack.borrow().is_empty()
     }
 
+    pub(crate) fn clear(&self) {
+        self.stack.borrow_mut().clear()
+    }
+
     pub(crate) fn set_has_ordinary_params(&self) {
         self.set(Self::ORDINARY_PARAMS)
     }
@@ -0,0 +1,226 @@
+use std::cmp::Ordering;
+
+/// Arbitrary-precision integer
+///
+/// Supports only operations that are required to compute values of numeric literals.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    // little-endian base 2^32 digits, no trailing zeroes (so zero is an empty list)
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    /// Constructs zero
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    /// Parses a sequence of digits in a given base (2..=36), without sign, prefix or underscores
+    ///
+    /// Returns `None` if `digits` is empty or has a character that is not a digit in `radix`
+    pub fn from_str_radix(digits: &str, radix: u32) -> Option<Self> {
+        if digits.is_empty() || !(2..=36).contains(&radix) {
+            return None;
+        }
+        let mut result = Self::zero();
+        for c in digits.chars() {
+            let digit = c.to_digit(radix)?;
+            result.mul_add_small(radix, digit);
+        }
+        Some(result)
+    }
+
+    /// Returns `true` if number is zero
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    /// Returns `true` if number is less than zero
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Converts to `i64`, returns `None` if the number doesn't fit
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let mut abs: u64 = 0;
+        for digit in self.magnitude.iter().rev() {
+            abs = (abs << 32) | (*digit as u64);
+        }
+        if self.negative {
+            if abs <= i64::MAX as u64 + 1 {
+                Some((abs as i64).wrapping_neg())
+            } else {
+                None
+            }
+        } else {
+            i64::try_from(abs).ok()
+        }
+    }
+
+    /// Converts to the closest `f64`
+    pub fn to_f64(&self) -> f64 {
+        // decimal representation is correctly rounded by the std
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    // self = self * mul + add
+    pub(crate) fn mul_add_small(&mut self, mul: u32, add: u32) {
+        let mut carry = add as u64;
+        for digit in self.magnitude.iter_mut() {
+            let value = (*digit as u64) * (mul as u64) + carry;
+            *digit = value as u32;
+            carry = value >> 32;
+        }
+        if carry != 0 {
+            self.magnitude.push(carry as u32);
+        }
+        self.normalize();
+    }
+
+    // self = self / divisor, returns remainder
+    pub(crate) fn div_rem_small(&mut self, divisor: u32) -> u32 {
+        let mut rem: u64 = 0;
+        for digit in self.magnitude.iter_mut().rev() {
+            let value = (rem << 32) | (*digit as u64);
+            *digit = (value / divisor as u64) as u32;
+            rem = value % divisor as u64;
+        }
+        self.normalize();
+        rem as u32
+    }
+
+    // Returns `true` if number is divisible by `divisor`
+    pub(crate) fn is_divisible_by(&self, divisor: u32) -> bool {
+        let mut rem: u64 = 0;
+        for digit in self.magnitude.iter().rev() {
+            rem = ((rem << 32) | (*digit as u64)) % divisor as u64;
+        }
+        rem == 0
+    }
+
+    fn normalize(&mut self) {
+        while self.magnitude.last() == Some(&0) {
+            self.magnitude.pop();
+        }
+        if self.magnitude.is_empty() {
+            self.negative = false;
+        }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let abs = value.unsigned_abs();
+        let mut result = Self {
+            negative: value < 0,
+            magnitude: vec![abs as u32, (abs >> 32) as u32],
+        };
+        result.normalize();
+        result
+    }
+}
+
+impl std::ops::Neg for BigInt {
+    type Output = Self;
+
+    fn neg(mut self) -> Self {
+        if !self.is_zero() {
+            self.negative = !self.negative;
+        }
+        self
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (negative, _) => {
+                let abs = self
+                    .magnitude
+                    .len()
+                    .cmp(&other.magnitude.len())
+                    .then_with(|| {
+                        self.magnitude
+                            .iter()
+                            .rev()
+                            .cmp(other.magnitude.iter().rev())
+                    });
+                if negative {
+                    abs.reverse()
+                } else {
+                    abs
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return f.write_str("0");
+        }
+
+        // split into base 10^9 chunks
+        let mut chunks = vec![];
+        let mut n = self.clone();
+        while !n.is_zero() {
+            chunks.push(n.div_rem_small(1_000_000_000));
+        }
+
+        let mut result = String::new();
+        if self.negative {
+            result.push('-');
+        }
+        let mut chunks = chunks.into_iter().rev();
+        if let Some(first) = chunks.next() {
+            result.push_str(&first.to_string());
+        }
+        for chunk in chunks {
+            result.push_str(&format!("{:09}", chunk));
+        }
+        f.write_str(&result)
+    }
+}
+
+#[test]
+fn test_from_str_radix() {
+    let n = BigInt::from_str_radix("123456789012345678901234567890", 10).unwrap();
+    assert_eq!(n.to_string(), "123456789012345678901234567890");
+    assert_eq!(n.to_i64(), None);
+
+    let n = BigInt::from_str_radix("ffffffffffffffff", 16).unwrap();
+    assert_eq!(n.to_string(), "18446744073709551615");
+
+    assert_eq!(
+        BigInt::from_str_radix("777", 8).unwrap().to_i64(),
+        Some(511)
+    );
+    assert_eq!(BigInt::from_str_radix("102", 2), None);
+    assert_eq!(BigInt::from_str_radix("", 10), None);
+}
+
+#[test]
+fn test_i64_conversions() {
+    for value in [0, 1, -1, 42, i64::MAX, i64::MIN] {
+        let n = BigInt::from(value);
+        assert_eq!(n.to_i64(), Some(value));
+        assert_eq!(n.to_string(), value.to_string());
+    }
+    assert!(BigInt::from(-5) < BigInt::from(3));
+    assert!(BigInt::from(-5) < BigInt::from(-3));
+    assert_eq!(-BigInt::from(0), BigInt::zero());
+    assert_eq!(-BigInt::from(7), BigInt::from(-7));
+}
@@ -0,0 +1,319 @@
+mod bigint;
+pub use bigint::BigInt;
+
+use crate::Node;
+
+/// Value of the numeric literal
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumericValue {
+    /// Value of the `Int` node
+    Integer(BigInt),
+
+    /// Value of the `Float` node
+    Float(FloatValue),
+
+    /// Value of the `Rational` node
+    Rational(RationalValue),
+
+    /// Value of the `Complex` node
+    Complex(ComplexValue),
+}
+
+impl NumericValue {
+    /// Computes value of the `Int`, `Float`, `Rational` or `Complex` node.
+    ///
+    /// Sign that is attached to the literal (i.e. `-42`) is included.
+    /// Returns `None` for any other node.
+    pub fn from_node(node: &Node) -> Option<Self> {
+        match node {
+            Node::Int(int) => parse_integer(&int.value).map(Self::Integer),
+            Node::Float(float) => parse_float(&float.value).map(Self::Float),
+            Node::Rational(rational) => parse_rational(&rational.value).map(Self::Rational),
+            Node::Complex(complex) => parse_complex(&complex.value).map(Self::Complex),
+            _ => None,
+        }
+    }
+}
+
+/// Value of the float literal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FloatValue {
+    /// Closest `f64` (rounded in the same way as MRI does it)
+    pub value: f64,
+
+    /// `true` if literal is too big (and so `value` is infinity)
+    /// or too small (and so `value` is zero).
+    ///
+    /// MRI reports it as a warning `Float 1e999 out of range`
+    pub out_of_range: bool,
+}
+
+/// Value of the rational literal, always normalized
+/// (i.e. `1.50r` is `3/2`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RationalValue {
+    /// Numerator, has the sign of the whole number
+    pub numerator: BigInt,
+
+    /// Denominator, always positive
+    pub denominator: BigInt,
+}
+
+/// Value of the imaginary literal, real part is always zero
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexValue {
+    /// Imaginary part, `Integer`, `Float` or `Rational`
+    pub imaginary: Box<NumericValue>,
+}
+
+/// Parses value of the `Int` node (like `-0x_FF`)
+pub fn parse_integer(value: &str) -> Option<BigInt> {
+    let (negative, value) = split_sign(value);
+    let (radix, digits) = split_radix(value);
+    let digits = digits.replace('_', "");
+    let result = BigInt::from_str_radix(&digits, radix)?;
+    Some(if negative { -result } else { result })
+}
+
+/// Parses value of the `Float` node (like `-1_000.5e-3`)
+pub fn parse_float(value: &str) -> Option<FloatValue> {
+    let value = value.replace('_', "");
+    // std also accepts `inf` and `NaN`
+    if !value
+        .bytes()
+        .all(|c| c.is_ascii_digit() || b".eE+-".contains(&c))
+    {
+        return None;
+    }
+    let result = value.parse::<f64>().ok()?;
+
+    let mantissa = value.split(['e', 'E']).next().unwrap_or_default();
+    let underflow = result == 0.0 && mantissa.bytes().any(|c| matches!(c, b'1'..=b'9'));
+
+    Some(FloatValue {
+        value: result,
+        out_of_range: result.is_infinite() || underflow,
+    })
+}
+
+/// Parses value of the `Rational` node (like `1.5r` or `0x10r`)
+pub fn parse_rational(value: &str) -> Option<RationalValue> {
+    let value = value.strip_suffix('r')?;
+    if !value.contains('.') {
+        return Some(RationalValue {
+            numerator: parse_integer(value)?,
+            denominator: BigInt::from(1),
+        });
+    }
+
+    let (negative, value) = split_sign(value);
+    let value = value.replace('_', "");
+    let (int_part, frac_part) = value.split_once('.')?;
+
+    let mut numerator = BigInt::from_str_radix(&format!("{}{}", int_part, frac_part), 10)?;
+    if negative {
+        numerator = -numerator;
+    }
+    let mut denominator = BigInt::from(1);
+    for _ in 0..frac_part.len() {
+        denominator.mul_add_small(10, 0);
+    }
+
+    // denominator is a power of 10, so 2 and 5 are the only common factors
+    for factor in [2, 5] {
+        while denominator.is_divisible_by(factor) && numerator.is_divisible_by(factor) {
+            denominator.div_rem_small(factor);
+            numerator.div_rem_small(factor);
+        }
+    }
+
+    Some(RationalValue {
+        numerator,
+        denominator,
+    })
+}
+
+/// Parses value of the `Complex` node (like `2.5i` or `1ri`)
+pub fn parse_complex(value: &str) -> Option<ComplexValue> {
+    let value = value.strip_suffix('i')?;
+    let imaginary = if value.ends_with('r') {
+        NumericValue::Rational(parse_rational(value)?)
+    } else if is_decimal_float(value) {
+        NumericValue::Float(parse_float(value)?)
+    } else {
+        NumericValue::Integer(parse_integer(value)?)
+    };
+    Some(ComplexValue {
+        imaginary: Box::new(imaginary),
+    })
+}
+
+fn split_sign(value: &str) -> (bool, &str) {
+    if let Some(value) = value.strip_prefix('-') {
+        (true, value)
+    } else {
+        (false, value.strip_prefix('+').unwrap_or(value))
+    }
+}
+
+// Returns radix and remaining digits of the integer literal without sign
+fn split_radix(value: &str) -> (u32, &str) {
+    let bytes = value.as_bytes();
+    if bytes.len() < 2 || bytes[0] != b'0' {
+        return (10, value);
+    }
+    match bytes[1] {
+        b'x' | b'X' => (16, &value[2..]),
+        b'b' | b'B' => (2, &value[2..]),
+        b'o' | b'O' => (8, &value[2..]),
+        b'd' | b'D' => (10, &value[2..]),
+        // `017` and `0_17`, but not `0.5` or `0e1`
+        b'0'..=b'9' | b'_' => (8, &value[1..]),
+        _ => (10, value),
+    }
+}
+
+// Returns `true` if literal (without suffixes) is a float literal like `1.5` or `1e3`
+fn is_decimal_float(value: &str) -> bool {
+    let (_, value) = split_sign(value);
+    if split_radix(value).0 != 10 {
+        return false;
+    }
+    value.contains(['.', 'e', 'E'])
+}
+
+#[test]
+fn test_from_node() {
+    use crate::nodes::{Int, Str};
+    use crate::{Bytes, Loc};
+
+    let node = Node::Int(Int {
+        value: String::from("-0b11"),
+        operator_l: Some(Loc { begin: 0, end: 1 }),
+        expression_l: Loc { begin: 0, end: 5 },
+    });
+    assert_eq!(
+        NumericValue::from_node(&node),
+        Some(NumericValue::Integer(BigInt::from(-3)))
+    );
+
+    let node = Node::Str(Str {
+        value: Bytes::new(b"42".to_vec()),
+        begin_l: None,
+        end_l: None,
+        expression_l: Loc { begin: 0, end: 2 },
+    });
+    assert_eq!(NumericValue::from_node(&node), None);
+}
+
+#[test]
+fn test_integer() {
+    let int = |value: &str| parse_integer(value).map(|n| n.to_string());
+
+    assert_eq!(int("42"), Some("42".to_string()));
+    assert_eq!(int("1_000"), Some("1000".to_string()));
+    assert_eq!(int("-42"), Some("-42".to_string()));
+    assert_eq!(int("+42"), Some("42".to_string()));
+    assert_eq!(int("0"), Some("0".to_string()));
+    assert_eq!(int("0x_FF"), Some("255".to_string()));
+    assert_eq!(int("0b1010"), Some("10".to_string()));
+    assert_eq!(int("0o17"), Some("15".to_string()));
+    assert_eq!(int("017"), Some("15".to_string()));
+    assert_eq!(int("0_17"), Some("15".to_string()));
+    assert_eq!(int("0d19"), Some("19".to_string()));
+    assert_eq!(
+        int("-0xFFFFFFFFFFFFFFFFFFFF"),
+        Some("-1208925819614629174706175".to_string())
+    );
+}
+
+#[test]
+fn test_float() {
+    let float = |value: &str| parse_float(value).unwrap();
+
+    assert_eq!(float("1.5").value, 1.5);
+    assert_eq!(float("-1_000.25").value, -1000.25);
+    assert_eq!(float("2.5e3").value, 2500.0);
+    assert_eq!(float("1E-2").value, 0.01);
+    assert_eq!(float("0.1").value, 0.1);
+    assert!(!float("0.0").out_of_range);
+    assert!(!float("0e999").out_of_range);
+
+    let big = float("1e999");
+    assert!(big.value.is_infinite() && big.out_of_range);
+    let big = float("-1e999");
+    assert!(big.value == f64::NEG_INFINITY && big.out_of_range);
+    let small = float("1e-999");
+    assert!(small.value == 0.0 && small.out_of_range);
+}
+
+#[test]
+fn test_rational() {
+    let rational = |value: &str| {
+        let RationalValue {
+            numerator,
+            denominator,
+        } = parse_rational(value).unwrap();
+        format!("{}/{}", numerator, denominator)
+    };
+
+    assert_eq!(rational("3r"), "3/1");
+    assert_eq!(rational("1_000r"), "1000/1");
+    assert_eq!(rational("0x10r"), "16/1");
+    assert_eq!(rational("1.5r"), "3/2");
+    assert_eq!(rational("-1.50r"), "-3/2");
+    assert_eq!(rational("0.125r"), "1/8");
+    assert_eq!(rational("0.3r"), "3/10");
+    assert_eq!(rational("2.0r"), "2/1");
+    assert_eq!(rational("0.5r"), "1/2");
+    assert_eq!(rational("0r"), "0/1");
+}
+
+#[test]
+fn test_complex() {
+    let imaginary = |value: &str| *parse_complex(value).unwrap().imaginary;
+
+    assert_eq!(imaginary("2i"), NumericValue::Integer(BigInt::from(2)));
+    assert_eq!(imaginary("-0xEi"), NumericValue::Integer(BigInt::from(-14)));
+    assert_eq!(
+        imaginary("2.5e3i"),
+        NumericValue::Float(FloatValue {
+            value: 2500.0,
+            out_of_range: false
+        })
+    );
+    assert_eq!(
+        imaginary("0.5i"),
+        NumericValue::Float(FloatValue {
+            value: 0.5,
+            out_of_range: false
+        })
+    );
+    assert_eq!(
+        imaginary("0e1i"),
+        NumericValue::Float(FloatValue {
+            value: 0.0,
+            out_of_range: false
+        })
+    );
+    assert_eq!(imaginary("017i"), NumericValue::Integer(BigInt::from(15)));
+    assert_eq!(imaginary("0i"), NumericValue::Integer(BigInt::from(0)));
+    match imaginary("-0.0i") {
+        NumericValue::Float(float) => assert!(float.value == 0.0 && float.value.is_sign_negative()),
+        other => panic!("expected Float, got {:?}", other),
+    }
+    assert_eq!(
+        imaginary("-0.5ri"),
+        NumericValue::Rational(RationalValue {
+            numerator: BigInt::from(-1),
+            denominator: BigInt::from(2)
+        })
+    );
+    assert_eq!(
+        imaginary("1.5ri"),
+        NumericValue::Rational(RationalValue {
+            numerator: BigInt::from(3),
+            denominator: BigInt::from(2)
+        })
+    );
+}
@@ -2,6 +2,7 @@ use alloc_from_pool::PoolValue;
 
 use crate::builder::{ArgsType, PKwLabel};
 use crate::context::Context;
+use crate::rewrite::drop_node;
 use crate::str_term::StrTerm;
 use crate::Node;
 use crate::Token;
@@ -9,7 +10,7 @@ use crate::Token;
 impl From<ParseValue> for Node {
     fn from(value: ParseValue) -> Node {
         match value {
-            ParseValue::Node(value) => *value,
+            ParseValue::Node(value) => *value.unwrap(),
             other => unreachable!("expected Node, got {:?}", other),
         }
     }
@@ -21,7 +22,7 @@ pub(crate) mod BoxedNode {
 
     pub(crate) fn from(value: ParseValue) -> Box<Node> {
         match value {
-            ParseValue::Node(value) => value,
+            ParseValue::Node(value) => value.unwrap(),
             other => unreachable!("expected BoxedNode, got {:?}", other),
         }
     }
@@ -66,7 +67,7 @@ pub(crate) mod NodeList {
 
     pub(crate) fn from(value: ParseValue) -> Vec<Node> {
         match value {
-            ParseValue::NodeList(value) => *value,
+            ParseValue::NodeList(value) => *value.unwrap(),
             other => unreachable!("expected NodeList, got {:?}", other),
         }
     }
@@ -79,7 +80,7 @@ pub(crate) mod BoxedNodeList {
 
     pub(crate) fn from(value: ParseValue) -> Box<Vec<Node>> {
         match value {
-            ParseValue::NodeList(value) => value,
+            ParseValue::NodeList(value) => value.unwrap(),
             other => unreachable!("expected NodeList, got {:?}", other),
         }
     }
@@ -434,7 +435,7 @@ pub(crate) mod MaybeNode {
 
     pub(crate) fn from(value: ParseValue) -> Option<Node> {
         match value {
-            ParseValue::MaybeNode(maybe_node) => maybe_node.map(|node| *node),
+            ParseValue::MaybeNode(maybe_node) => maybe_node.into_inner().map(|node| *node),
             other => unreachable!("expected MaybeNode, got {:?}", other),
         }
     }
@@ -446,7 +447,7 @@ pub(crate) mod MaybeBoxedNode {
 
     pub(crate) fn from(value: ParseValue) -> Option<Box<Node>> {
         match value {
-            ParseValue::MaybeNode(value) => value,
+            ParseValue::MaybeNode(value) => value.into_inner(),
             other => unreachable!("expected MaybeNode, got {:?}", other),
         }
     }
@@ -508,6 +509,55 @@ impl NoKwRest {
     }
 }
 
+/// Nodes on the parser stack.
+///
+/// Values that are left on the stack when parsing fails (or is cancelled) are dropped
+/// by the parser, nodes can be nested arbitrarily deep so they are dropped without recursion.
+#[derive(Clone)]
+pub(crate) struct StackNodes<T: DropNodes>(Option<Box<T>>);
+
+impl<T: DropNodes> StackNodes<T> {
+    fn into_inner(mut self) -> Option<Box<T>> {
+        self.0.take()
+    }
+
+    // `Node` and `NodeList` values are always present
+    fn unwrap(self) -> Box<T> {
+        self.into_inner()
+            .unwrap_or_else(|| unreachable!("value of the parser stack is taken"))
+    }
+}
+
+impl<T: DropNodes> Drop for StackNodes<T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.0.take() {
+            value.drop_nodes()
+        }
+    }
+}
+
+impl<T: DropNodes + std::fmt::Debug> std::fmt::Debug for StackNodes<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+pub(crate) trait DropNodes {
+    fn drop_nodes(self: Box<Self>);
+}
+
+impl DropNodes for Node {
+    fn drop_nodes(self: Box<Self>) {
+        drop_node(*self)
+    }
+}
+
+impl DropNodes for Vec<Node> {
+    fn drop_nodes(self: Box<Self>) {
+        self.into_iter().for_each(drop_node)
+    }
+}
+
 #[allow(clippy::box_collection)]
 #[derive(Clone, Debug)]
 pub(crate) enum ParseValue {
@@ -517,8 +567,8 @@ pub(crate) enum ParseValue {
     Token(PoolValue<Token>),
     TokenWithContext(Box<TokenWithContext>),
     Context(Context),
-    Node(Box<Node>),
-    NodeList(Box<Vec<Node>>),
+    Node(StackNodes<Node>),
+    NodeList(StackNodes<Vec<Node>>),
     Bool(bool),
     MaybeStrTerm(Option<Box<StrTerm>>),
     Num(i32),
@@ -587,7 +637,7 @@ pub(crate) enum ParseValue {
     PCaseBody(Box<PCaseBody>),
 
     /* For custom compstmt rule */
-    MaybeNode(Option<Box<Node>>),
+    MaybeNode(StackNodes<Node>),
 
     /* For custom do_body rule */
     DoBody(Box<DoBody>),
@@ -614,6 +664,23 @@ impl ParseValue {
         matches!(self, Self::Uninitialized)
     }
 
+    pub(crate) fn new_node(value: Box<Node>) -> Self {
+        Self::Node(StackNodes(Some(value)))
+    }
+    #[allow(clippy::box_collection)]
+    pub(crate) fn new_node_list(value: Box<Vec<Node>>) -> Self {
+        Self::NodeList(StackNodes(Some(value)))
+    }
+    pub(crate) fn new_maybe_node(value: Option<Box<Node>>) -> Self {
+        Self::MaybeNode(StackNodes(value))
+    }
+    pub(crate) fn as_node(&self) -> &Node {
+        match self {
+            Self::Node(StackNodes(Some(value))) => value,
+            other => unreachable!("expected Node, got {:?}", other),
+        }
+    }
+
     pub(crate) fn new_superclass(value: Superclass) -> Self {
         Self::Superclass(Box::new(value))
     }
@@ -698,3 +765,29 @@ impl Default for ParseValue {
         Self::Stolen
     }
 }
+
+#[test]
+fn test_stack_nodes_drop_on_small_stack() {
+    use crate::nodes::{Begin, Nil};
+    use crate::Loc;
+
+    // (((...nil...)))
+    let loc = Loc { begin: 0, end: 0 };
+    let mut node = Node::Nil(Nil { expression_l: loc });
+    for _ in 0..100_000 {
+        node = Node::Begin(Begin {
+            statements: vec![node],
+            begin_l: Some(loc),
+            end_l: Some(loc),
+            expression_l: loc,
+        });
+    }
+    let value = StackNodes(Some(Box::new(vec![node])));
+
+    std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(move || drop(value))
+        .unwrap()
+        .join()
+        .unwrap();
+}
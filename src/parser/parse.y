@@ -45,6 +45,11 @@
     tokens: Vec<Token>,
     diagnostics: Diagnostics,
     record_tokens: bool,
+    record_trivia: bool,
+    record_lex_states: bool,
+    lex_states: Vec<LexState>,
+    verbose: bool,
+    token_info: TokenInfoStack,
 
     #[allow(dead_code)]
     tokens_pool: Pool<Token>,
@@ -53,9 +58,10 @@
 %code use {
 
 use alloc_from_pool::{Pool, PoolValue};
-use crate::{ParserOptions, ParserResult};
+use crate::{Ast, Cancellation, Limits, ParserOptions, ParserResult};
 use crate::{Token};
-use crate::{Lexer, Builder, CurrentArgStack, StaticEnvironment, MaxNumparamStack, VariablesStack};
+use crate::{Lexer, Builder, CurrentArgStack, StaticEnvironment, MaxNumparamStack, VariablesStack, TokenInfoStack};
+use crate::insert_trivia_with_lex_states;
 use crate::lex_states::*;
 use crate::{SharedContext as ParserContext, context::Context};
 use crate::builder::{LoopType, KeywordCmd, LogicalOp, PKwLabel, ArgsType};
@@ -64,9 +70,11 @@ use crate::parse_value::ParseValue as Value;
 use crate::parse_value::*;
 use crate::Node;
 use crate::nodes;
-use crate::{Diagnostic, DiagnosticMessage, ErrorLevel};
+use crate::{Diagnostic, DiagnosticMessage, ErrorLevel, Message};
 use crate::error::Diagnostics;
+use crate::source::{SourceBytes, StringContentMaps};
 use crate::Loc;
+use crate::LexState;
 
 }
 
@@ -235,6 +243,14 @@ use crate::Loc;
 %token <token> tSLASH_F         "escaped form feed"
 %token <token> tSLASH_R         "escaped carriage return"
 %token <token> tVTAB            "escaped vertical tab"
+/* trivia, never passed to the parser, recorded only if `ParserOptions::record_trivia` is set */
+%token <token> tWHITESPACE        "whitespace"
+%token <token> tNEWLINE           "ignored newline"
+%token <token> tLINE_CONTINUATION "line continuation"
+%token <token> tCOMMENT           "comment"
+%token <token> tEMBDOC            "embedded document"
+%token <token> tEND_DATA          "__END__ data"
+%token <token> tSKIPPED           "skipped bytes"
 %token <token> tUPLUS           "unary+"
 %token <token> tUMINUS          "unary-"
 %token <token> tPOW             "**"
@@ -364,7 +380,7 @@ use crate::Loc;
     top_compstmt: top_stmts opt_terms
                     {
                         // TODO: run void_stmts
-                        $$ = Value::MaybeNode(
+                        $$ = Value::new_maybe_node(
                             self.builder.compstmt($<NodeList>1)
                         );
                     }
@@ -372,21 +388,21 @@ use crate::Loc;
 
        top_stmts: none
                     {
-                      $$ = Value::NodeList( Box::default() );
+                      $$ = Value::new_node_list( Box::default() );
                     }
                 | top_stmt
                     {
-                      $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                      $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | top_stmts terms top_stmt
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList( nodes );
+                        $$ = Value::new_node_list( nodes );
                     }
                 | error top_stmt
                     {
-                      $$ = Value::NodeList( Box::new(vec![ $<Node>2 ]) );
+                      $$ = Value::new_node_list( Box::new(vec![ $<Node>2 ]) );
                     }
                 ;
 
@@ -397,7 +413,7 @@ use crate::Loc;
                 | klBEGIN begin_block
                     {
                         let BeginBlock { begin_t, body, end_t } = $<BeginBlock>2;
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.preexe($<Token>1, begin_t, body, end_t)
                         );
                     }
@@ -429,7 +445,7 @@ use crate::Loc;
                         let else_ = Some(( $<Token>3, $<MaybeBoxedNode>4 ));
                         let ensure = $<OptEnsure>5.map(|ensure| (ensure.ensure_t, ensure.body));
 
-                        $$ = Value::MaybeNode(
+                        $$ = Value::new_maybe_node(
                             self.builder.begin_body(
                                 compound_stmt,
                                 rescue_bodies,
@@ -446,7 +462,7 @@ use crate::Loc;
                         let rescue_bodies = $<NodeList>2;
                         let ensure = $<OptEnsure>3.map(|ensure| (ensure.ensure_t, ensure.body));
 
-                        $$ = Value::MaybeNode(
+                        $$ = Value::new_maybe_node(
                             self.builder.begin_body(
                                 compound_stmt,
                                 rescue_bodies,
@@ -460,7 +476,7 @@ use crate::Loc;
         compstmt: stmts opt_terms
                     {
                         // TODO: run void_stmts
-                        $$ = Value::MaybeNode(
+                        $$ = Value::new_maybe_node(
                             self.builder.compstmt($<NodeList>1)
                         );
                     }
@@ -468,21 +484,21 @@ use crate::Loc;
 
            stmts: none
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 | stmt_or_begin
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | stmts terms stmt_or_begin
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | error
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 ;
 
@@ -507,13 +523,13 @@ use crate::Loc;
                     }
                   fitem
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.alias($<Token>1, $<BoxedNode>2, $<BoxedNode>4)
                         );
                     }
                 | kALIAS tGVAR tGVAR
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.alias(
                                 $<Token>1,
                                 self.builder.gvar($<Token>2),
@@ -523,7 +539,7 @@ use crate::Loc;
                     }
                 | kALIAS tGVAR tBACK_REF
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.alias(
                                 $<Token>1,
                                 self.builder.gvar($<Token>2),
@@ -537,7 +553,7 @@ use crate::Loc;
                     }
                 | kUNDEF undef_list
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.undef_method(
                                 $<Token>1,
                                 $<NodeList>2
@@ -546,7 +562,7 @@ use crate::Loc;
                     }
                 | stmt kIF_MOD expr_value
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.condition_mod(
                                 Some($<BoxedNode>1),
                                 None,
@@ -557,7 +573,7 @@ use crate::Loc;
                     }
                 | stmt kUNLESS_MOD expr_value
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.condition_mod(
                                 None,
                                 Some($<BoxedNode>1),
@@ -568,7 +584,7 @@ use crate::Loc;
                     }
                 | stmt kWHILE_MOD expr_value
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.loop_mod(
                                 LoopType::While,
                                 $<BoxedNode>1,
@@ -579,7 +595,7 @@ use crate::Loc;
                     }
                 | stmt kUNTIL_MOD expr_value
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.loop_mod(
                                 LoopType::Until,
                                 $<BoxedNode>1,
@@ -599,7 +615,7 @@ use crate::Loc;
                             Some($<BoxedNode>3)
                         );
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.begin_body(
                                 Some($<BoxedNode>1),
                                 vec![*rescue_body],
@@ -614,7 +630,7 @@ use crate::Loc;
                             self.warn(@1, DiagnosticMessage::EndInMethod {});
                         }
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.postexe(
                                 $<Token>1,
                                 $<Token>2,
@@ -632,7 +648,7 @@ use crate::Loc;
                         let command_call = $<BoxedNode>3;
                         self.value_expr(&command_call)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.multi_assign(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -649,7 +665,7 @@ use crate::Loc;
                         );
                         self.value_expr(&mrhs)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assign(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -678,7 +694,7 @@ use crate::Loc;
                             None,
                         ).expect("expected begin_body to return Some (compound_stmt was given)");
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.multi_assign(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -688,7 +704,7 @@ use crate::Loc;
                     }
                 | mlhs tEQL mrhs_arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.multi_assign(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -704,7 +720,7 @@ use crate::Loc;
 
     command_asgn: lhs tEQL command_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assign(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -714,7 +730,7 @@ use crate::Loc;
                     }
                 | var_lhs tOP_ASGN command_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -724,7 +740,7 @@ use crate::Loc;
                     }
                 | primary_value tLBRACK2 opt_call_args rbracket tOP_ASGN command_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 self.builder.index(
                                     $<BoxedNode>1,
@@ -739,7 +755,7 @@ use crate::Loc;
                     }
                 | primary_value call_op tIDENTIFIER tOP_ASGN command_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 self.builder.call_method(
                                     Some($<BoxedNode>1),
@@ -756,7 +772,7 @@ use crate::Loc;
                     }
                 | primary_value call_op tCONSTANT tOP_ASGN command_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 self.builder.call_method(
                                     Some($<BoxedNode>1),
@@ -780,7 +796,7 @@ use crate::Loc;
                                 $<Token>3
                             )
                         );
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 const_,
                                 $<Token>4,
@@ -790,7 +806,7 @@ use crate::Loc;
                     }
                 | primary_value tCOLON2 tIDENTIFIER tOP_ASGN command_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 self.builder.call_method(
                                     Some($<BoxedNode>1),
@@ -810,8 +826,9 @@ use crate::Loc;
                         let DefnHead { def_t, name_t } = $<DefnHead>1;
                         let TokenWithContext { token: name_t, ctx } = name_t;
                         self.validate_endless_method_name(&name_t)?;
+                        self.token_info_drop("def", &def_t.loc);
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_endless_method(
                                 def_t,
                                 name_t,
@@ -830,6 +847,7 @@ use crate::Loc;
                         let DefnHead { def_t, name_t } = $<DefnHead>1;
                         let TokenWithContext { token: name_t, ctx } = name_t;
                         self.validate_endless_method_name(&name_t)?;
+                        self.token_info_drop("def", &def_t.loc);
 
                         let rescue_body = self.builder.rescue_body(
                             $<Token>5,
@@ -847,7 +865,7 @@ use crate::Loc;
                             None,
                         );
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_endless_method(
                                 def_t,
                                 name_t,
@@ -866,8 +884,9 @@ use crate::Loc;
                         let DefsHead { def_t, definee, dot_t, name_t } = $<DefsHead>1;
                         let TokenWithContext { token: name_t, ctx } = name_t;
                         self.validate_endless_method_name(&name_t)?;
+                        self.token_info_drop("def", &def_t.loc);
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_endless_singleton(
                                 def_t,
                                 definee,
@@ -888,6 +907,7 @@ use crate::Loc;
                         let DefsHead { def_t, definee, dot_t, name_t } = $<DefsHead>1;
                         let TokenWithContext { token: name_t, ctx } = name_t;
                         self.validate_endless_method_name(&name_t)?;
+                        self.token_info_drop("def", &def_t.loc);
 
                         let rescue_body = self.builder.rescue_body(
                             $<Token>5,
@@ -905,7 +925,7 @@ use crate::Loc;
                             None,
                         );
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_endless_singleton(
                                 def_t,
                                 definee,
@@ -923,7 +943,7 @@ use crate::Loc;
                     }
                 | backref tOP_ASGN command_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -937,7 +957,7 @@ use crate::Loc;
                     {
                         let command_call = $<BoxedNode>1;
                         self.value_expr(&command_call)?;
-                        $$ = Value::Node(command_call);
+                        $$ = Value::new_node(command_call);
                     }
                 | command_call kRESCUE_MOD stmt
                     {
@@ -953,7 +973,7 @@ use crate::Loc;
                             Some($<BoxedNode>3)
                         );
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.begin_body(
                                 Some(command_call),
                                 vec![ *rescue_body ],
@@ -974,7 +994,7 @@ use crate::Loc;
                     }
                 | expr kAND expr
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.logical_op(
                                 LogicalOp::And,
                                 $<BoxedNode>1,
@@ -985,7 +1005,7 @@ use crate::Loc;
                     }
                 | expr kOR expr
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.logical_op(
                                 LogicalOp::Or,
                                 $<BoxedNode>1,
@@ -996,7 +1016,7 @@ use crate::Loc;
                     }
                 | kNOT opt_nl expr
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.not_op(
                                 $<Token>1,
                                 None,
@@ -1007,7 +1027,7 @@ use crate::Loc;
                     }
                 | tBANG command_call
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.not_op(
                                 $<Token>1,
                                 None,
@@ -1018,10 +1038,7 @@ use crate::Loc;
                     }
                 | arg tASSOC
                     {
-                        let arg = match yystack.borrow_value_at(1) {
-                            Value::Node(node) => node,
-                            other => unreachable!("expected Node, got {:?}", other)
-                        };
+                        let arg = yystack.borrow_value_at(1).as_node();
                         self.value_expr(arg)?;
 
                         self.yylexer.lex_state.set(EXPR_BEG|EXPR_LABEL);
@@ -1038,7 +1055,7 @@ use crate::Loc;
                         self.pattern_hash_keys.pop();
                         self.context.set_in_kwarg($<Bool>3);
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.match_pattern(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -1048,10 +1065,7 @@ use crate::Loc;
                     }
                 | arg kIN
                     {
-                        let arg = match yystack.borrow_value_at(1) {
-                            Value::Node(node) => node,
-                            other => unreachable!("expected Node, got {:?}", other)
-                        };
+                        let arg = yystack.borrow_value_at(1).as_node();
                         self.value_expr(arg)?;
 
                         self.yylexer.lex_state.set(EXPR_BEG|EXPR_LABEL);
@@ -1068,7 +1082,7 @@ use crate::Loc;
                         self.pattern_hash_keys.pop();
                         self.context.set_in_kwarg($<Bool>3);
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.match_pattern_p(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -1136,7 +1150,7 @@ use crate::Loc;
                     {
                         let expr = $<BoxedNode>1;
                         self.value_expr(&expr)?;
-                        $$ = Value::Node(expr);
+                        $$ = Value::new_node(expr);
                     }
                 ;
 
@@ -1174,7 +1188,7 @@ use crate::Loc;
                     }
                 | block_call call_op2 operation2 command_args
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.call_method(
                                 Some($<BoxedNode>1),
                                 Some($<Token>2),
@@ -1215,7 +1229,7 @@ use crate::Loc;
 
          command: fcall command_args       %prec tLOWEST
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.call_method(
                                 None,
                                 None,
@@ -1238,7 +1252,7 @@ use crate::Loc;
                         );
                         let CmdBraceBlock { begin_t, args_type, body, end_t } = $<CmdBraceBlock>3;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.block(
                                 method_call,
                                 begin_t,
@@ -1250,7 +1264,7 @@ use crate::Loc;
                     }
                 | primary_value call_op operation2 command_args %prec tLOWEST
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.call_method(
                                 Some($<BoxedNode>1),
                                 Some($<Token>2),
@@ -1273,7 +1287,7 @@ use crate::Loc;
                         );
                         let CmdBraceBlock { begin_t, args_type, body, end_t } = $<CmdBraceBlock>5;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.block(
                                 method_call,
                                 begin_t,
@@ -1285,7 +1299,7 @@ use crate::Loc;
                     }
                 | primary_value tCOLON2 operation2 command_args %prec tLOWEST
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.call_method(
                                 Some($<BoxedNode>1),
                                 Some($<Token>2),
@@ -1308,7 +1322,7 @@ use crate::Loc;
                         );
                         let CmdBraceBlock { begin_t, args_type, body, end_t } = $<CmdBraceBlock>5;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.block(
                                 method_call,
                                 begin_t,
@@ -1320,7 +1334,7 @@ use crate::Loc;
                     }
                 | kSUPER command_args
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Super,
                                 $<Token>1,
@@ -1332,7 +1346,7 @@ use crate::Loc;
                     }
                 | kYIELD command_args
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Yield,
                                 $<Token>1,
@@ -1344,7 +1358,7 @@ use crate::Loc;
                     }
                 | k_return call_args
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Return,
                                 $<Token>1,
@@ -1356,7 +1370,7 @@ use crate::Loc;
                     }
                 | kBREAK call_args
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Break,
                                 $<Token>1,
@@ -1368,7 +1382,7 @@ use crate::Loc;
                     }
                 | kNEXT call_args
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Next,
                                 $<Token>1,
@@ -1382,7 +1396,7 @@ use crate::Loc;
 
             mlhs: mlhs_basic
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.multi_lhs(
                                 None,
                                 $<NodeList>1,
@@ -1392,7 +1406,7 @@ use crate::Loc;
                     }
                 | tLPAREN mlhs_inner rparen
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.begin(
                                 $<Token>1,
                                 Some($<BoxedNode>2),
@@ -1404,7 +1418,7 @@ use crate::Loc;
 
       mlhs_inner: mlhs_basic
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.multi_lhs(
                                 None,
                                 $<NodeList>1,
@@ -1424,7 +1438,7 @@ use crate::Loc;
                             }
                         };
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.multi_lhs(
                                 Some($<Token>1),
                                 mlhs_items,
@@ -1442,14 +1456,14 @@ use crate::Loc;
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>2 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | mlhs_head tSTAR mlhs_node
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         let mlhs_node = *self.builder.splat($<Token>2, Some($<BoxedNode>3));
                         nodes.push(mlhs_node);
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | mlhs_head tSTAR mlhs_node tCOMMA mlhs_post
                     {
@@ -1461,14 +1475,14 @@ use crate::Loc;
                         nodes.push(mlhs_node);
                         nodes.append(&mut mlhs_post);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | mlhs_head tSTAR
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         let splat = *self.builder.splat($<Token>2, None);
                         nodes.push(splat);
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | mlhs_head tSTAR tCOMMA mlhs_post
                     {
@@ -1480,11 +1494,11 @@ use crate::Loc;
                         nodes.push(splat);
                         nodes.append(&mut mlhs_post);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | tSTAR mlhs_node
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *self.builder.splat(
@@ -1504,11 +1518,11 @@ use crate::Loc;
                         nodes.push(splat);
                         nodes.append(&mut mlhs_post);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | tSTAR
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *self.builder.splat(
@@ -1528,7 +1542,7 @@ use crate::Loc;
                         nodes.push(splat);
                         nodes.append(&mut mlhs_post);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
@@ -1538,7 +1552,7 @@ use crate::Loc;
                     }
                 | tLPAREN mlhs_inner rparen
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.begin(
                                 $<Token>1,
                                 Some($<BoxedNode>2),
@@ -1550,43 +1564,43 @@ use crate::Loc;
 
        mlhs_head: mlhs_item tCOMMA
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | mlhs_head mlhs_item tCOMMA
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>2 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
        mlhs_post: mlhs_item
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | mlhs_post tCOMMA mlhs_item
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
        mlhs_node: user_variable
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable($<BoxedNode>1)?
                         );
                     }
                 | keyword_variable
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable($<BoxedNode>1)?
                         );
                     }
                 | primary_value tLBRACK2 opt_call_args rbracket
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.index_asgn(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -1602,7 +1616,7 @@ use crate::Loc;
                             return self.yyerror(@2, DiagnosticMessage::CsendInsideMasgn {});
                         }
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.attr_asgn(
                                 $<BoxedNode>1,
                                 op_t,
@@ -1612,7 +1626,7 @@ use crate::Loc;
                     }
                 | primary_value tCOLON2 tIDENTIFIER
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.attr_asgn(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -1627,7 +1641,7 @@ use crate::Loc;
                             return self.yyerror(@2, DiagnosticMessage::CsendInsideMasgn {});
                         }
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.attr_asgn(
                                 $<BoxedNode>1,
                                 op_t,
@@ -1637,7 +1651,7 @@ use crate::Loc;
                     }
                 | primary_value tCOLON2 tCONSTANT
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable(
                                 self.builder.const_fetch(
                                     $<BoxedNode>1,
@@ -1649,7 +1663,7 @@ use crate::Loc;
                     }
                 | tCOLON3 tCONSTANT
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable(
                                 self.builder.const_global(
                                     $<Token>1,
@@ -1660,7 +1674,7 @@ use crate::Loc;
                     }
                 | backref
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable(
                                 $<BoxedNode>1
                             )?
@@ -1670,19 +1684,19 @@ use crate::Loc;
 
              lhs: user_variable
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable($<BoxedNode>1)?
                         );
                     }
                 | keyword_variable
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable($<BoxedNode>1)?
                         );
                     }
                 | primary_value tLBRACK2 opt_call_args rbracket
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.index_asgn(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -1693,7 +1707,7 @@ use crate::Loc;
                     }
                 | primary_value call_op tIDENTIFIER
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.attr_asgn(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -1703,7 +1717,7 @@ use crate::Loc;
                     }
                 | primary_value tCOLON2 tIDENTIFIER
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.attr_asgn(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -1713,7 +1727,7 @@ use crate::Loc;
                     }
                 | primary_value call_op tCONSTANT
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.attr_asgn(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -1723,7 +1737,7 @@ use crate::Loc;
                     }
                 | primary_value tCOLON2 tCONSTANT
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable(
                                 self.builder.const_fetch(
                                     $<BoxedNode>1,
@@ -1735,7 +1749,7 @@ use crate::Loc;
                     }
                 | tCOLON3 tCONSTANT
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable(
                                 self.builder.const_global(
                                     $<Token>1,
@@ -1746,7 +1760,7 @@ use crate::Loc;
                     }
                 | backref
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable(
                                 $<BoxedNode>1
                             )?
@@ -1766,19 +1780,19 @@ use crate::Loc;
 
            cpath: tCOLON3 cname
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_global($<Token>1, $<Token>2)
                         );
                     }
                 | cname
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_($<Token>1)
                         );
                     }
                 | primary_value tCOLON2 cname
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_fetch(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -1813,7 +1827,7 @@ use crate::Loc;
 
            fitem: fname
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.symbol_internal($<Token>1)
                         );
                     }
@@ -1825,7 +1839,7 @@ use crate::Loc;
 
       undef_list: fitem
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | undef_list tCOMMA
                     {
@@ -1836,7 +1850,7 @@ use crate::Loc;
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>4 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
@@ -1917,7 +1931,7 @@ use crate::Loc;
 
              arg: lhs tEQL arg_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assign(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -1927,7 +1941,7 @@ use crate::Loc;
                     }
                 | var_lhs tOP_ASGN arg_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -1937,7 +1951,7 @@ use crate::Loc;
                     }
                 | primary_value tLBRACK2 opt_call_args rbracket tOP_ASGN arg_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 self.builder.index(
                                     $<BoxedNode>1,
@@ -1952,7 +1966,7 @@ use crate::Loc;
                     }
                 | primary_value call_op tIDENTIFIER tOP_ASGN arg_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 self.builder.call_method(
                                     Some($<BoxedNode>1),
@@ -1969,7 +1983,7 @@ use crate::Loc;
                     }
                 | primary_value call_op tCONSTANT tOP_ASGN arg_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 self.builder.call_method(
                                     Some($<BoxedNode>1),
@@ -1986,7 +2000,7 @@ use crate::Loc;
                     }
                 | primary_value tCOLON2 tIDENTIFIER tOP_ASGN arg_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 self.builder.call_method(
                                     Some($<BoxedNode>1),
@@ -2010,7 +2024,7 @@ use crate::Loc;
                                 $<Token>3
                             )
                         );
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 const_,
                                 $<Token>4,
@@ -2026,7 +2040,7 @@ use crate::Loc;
                                 $<Token>2
                             )
                         );
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 const_,
                                 $<Token>3,
@@ -2036,7 +2050,7 @@ use crate::Loc;
                     }
                 | backref tOP_ASGN arg_rhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.op_assign(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -2052,7 +2066,7 @@ use crate::Loc;
                         let right = $<BoxedNode>3;
                         self.value_expr(&right)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.range_inclusive(
                                 Some(left),
                                 $<Token>2,
@@ -2068,7 +2082,7 @@ use crate::Loc;
                         let right = $<BoxedNode>3;
                         self.value_expr(&right)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.range_exclusive(
                                 Some(left),
                                 $<Token>2,
@@ -2081,7 +2095,7 @@ use crate::Loc;
                         let left = $<BoxedNode>1;
                         self.value_expr(&left)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.range_inclusive(
                                 Some(left),
                                 $<Token>2,
@@ -2094,7 +2108,7 @@ use crate::Loc;
                         let left = $<BoxedNode>1;
                         self.value_expr(&left)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.range_exclusive(
                                 Some(left),
                                 $<Token>2,
@@ -2107,7 +2121,7 @@ use crate::Loc;
                         let right = $<BoxedNode>2;
                         self.value_expr(&right)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.range_inclusive(
                                 None,
                                 $<Token>1,
@@ -2120,7 +2134,7 @@ use crate::Loc;
                         let right = $<BoxedNode>2;
                         self.value_expr(&right)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.range_exclusive(
                                 None,
                                 $<Token>1,
@@ -2130,43 +2144,43 @@ use crate::Loc;
                     }
                 | arg tPLUS arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tMINUS arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tSTAR2 arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tDIVIDE arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tPERCENT arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tPOW arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | tUMINUS_NUM simple_numeric tPOW arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.unary_op(
                                 $<Token>1,
                                 self.builder.binary_op(
@@ -2179,7 +2193,7 @@ use crate::Loc;
                     }
                 | tUPLUS arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.unary_op(
                                 $<Token>1,
                                 $<BoxedNode>2
@@ -2188,7 +2202,7 @@ use crate::Loc;
                     }
                 | tUMINUS arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.unary_op(
                                 $<Token>1,
                                 $<BoxedNode>2
@@ -2197,25 +2211,25 @@ use crate::Loc;
                     }
                 | arg tPIPE arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tCARET arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tAMPER2 arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tCMP arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
@@ -2225,31 +2239,31 @@ use crate::Loc;
                     }
                 | arg tEQ arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tEQQ arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tNEQ arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tMATCH arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.match_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tNMATCH arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -2259,7 +2273,7 @@ use crate::Loc;
                     }
                 | tBANG arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.not_op(
                                 $<Token>1,
                                 None,
@@ -2270,7 +2284,7 @@ use crate::Loc;
                     }
                 | tTILDE arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.unary_op(
                                 $<Token>1,
                                 $<BoxedNode>2
@@ -2279,19 +2293,19 @@ use crate::Loc;
                     }
                 | arg tLSHFT arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tRSHFT arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op($<BoxedNode>1, $<Token>2, $<BoxedNode>3)?
                         );
                     }
                 | arg tANDOP arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.logical_op(
                                 LogicalOp::And,
                                 $<BoxedNode>1,
@@ -2302,7 +2316,7 @@ use crate::Loc;
                     }
                 | arg tOROP arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.logical_op(
                                 LogicalOp::Or,
                                 $<BoxedNode>1,
@@ -2319,7 +2333,7 @@ use crate::Loc;
                   arg
                     {
                         self.context.set_in_defined(false);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Defined,
                                 $<Token>1,
@@ -2334,7 +2348,7 @@ use crate::Loc;
                         let expr = $<BoxedNode>1;
                         self.value_expr(&expr)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.ternary(
                                 expr,
                                 $<Token>2,
@@ -2349,8 +2363,9 @@ use crate::Loc;
                         let DefnHead { def_t, name_t } = $<DefnHead>1;
                         let TokenWithContext { token: name_t, ctx } = name_t;
                         self.validate_endless_method_name(&name_t)?;
+                        self.token_info_drop("def", &def_t.loc);
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_endless_method(
                                 def_t,
                                 name_t,
@@ -2369,6 +2384,7 @@ use crate::Loc;
                         let DefnHead { def_t, name_t } = $<DefnHead>1;
                         let TokenWithContext { token: name_t, ctx } = name_t;
                         self.validate_endless_method_name(&name_t)?;
+                        self.token_info_drop("def", &def_t.loc);
 
                         let rescue_body = self.builder.rescue_body(
                             $<Token>5,
@@ -2386,7 +2402,7 @@ use crate::Loc;
                             None,
                         );
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_endless_method(
                                 def_t,
                                 name_t,
@@ -2405,8 +2421,9 @@ use crate::Loc;
                         let DefsHead { def_t, definee, dot_t, name_t } = $<DefsHead>1;
                         let TokenWithContext { token: name_t, ctx } = name_t;
                         self.validate_endless_method_name(&name_t)?;
+                        self.token_info_drop("def", &def_t.loc);
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_endless_singleton(
                                 def_t,
                                 definee,
@@ -2427,6 +2444,7 @@ use crate::Loc;
                         let DefsHead { def_t, definee, dot_t, name_t } = $<DefsHead>1;
                         let TokenWithContext { token: name_t, ctx } = name_t;
                         self.validate_endless_method_name(&name_t)?;
+                        self.token_info_drop("def", &def_t.loc);
 
                         let rescue_body = self.builder.rescue_body(
                             $<Token>5,
@@ -2444,7 +2462,7 @@ use crate::Loc;
                             None,
                         );
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_endless_singleton(
                                 def_t,
                                 definee,
@@ -2486,7 +2504,7 @@ use crate::Loc;
 
         rel_expr: arg relop arg   %prec tGT
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -2501,7 +2519,7 @@ use crate::Loc;
                             @2,
                             DiagnosticMessage::ComparisonAfterComparison { comparison: clone_value(&op_t) }
                         );
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.binary_op(
                                 $<BoxedNode>1,
                                 op_t,
@@ -2515,13 +2533,13 @@ use crate::Loc;
                     {
                         let arg = $<BoxedNode>1;
                         self.value_expr(&arg)?;
-                        $$ = Value::Node(arg);
+                        $$ = Value::new_node(arg);
                     }
                 ;
 
        aref_args: none
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 | args trailer
                     {
@@ -2537,11 +2555,11 @@ use crate::Loc;
                                 None
                             )
                         );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | assocs trailer
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *self.builder.associate(
@@ -2559,7 +2577,7 @@ use crate::Loc;
                     {
                         let arg = $<BoxedNode>1;
                         self.value_expr(&arg)?;
-                        $$ = Value::Node(arg);
+                        $$ = Value::new_node(arg);
                     }
                 | arg kRESCUE_MOD arg
                     {
@@ -2575,7 +2593,7 @@ use crate::Loc;
                             Some($<BoxedNode>3)
                         );
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.begin_body(
                                 Some(arg),
                                 vec![ *rescue_body ],
@@ -2658,7 +2676,7 @@ use crate::Loc;
 
    opt_call_args: none
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 | call_args
                     {
@@ -2673,11 +2691,11 @@ use crate::Loc;
                         let mut nodes = $<BoxedNodeList>1;
                         let pair = *self.builder.associate(None, $<NodeList>3, None);
                         nodes.push(pair);
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | assocs tCOMMA
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *self.builder.associate(
@@ -2695,14 +2713,14 @@ use crate::Loc;
                     {
                         let command = $<Node>1;
                         self.value_expr(&command)?;
-                        $$ = Value::NodeList( Box::new(vec![ command ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ command ]) );
                     }
                 | args opt_block_arg
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.append(&mut $<NodeList>2);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | assocs opt_block_arg
                     {
@@ -2713,7 +2731,7 @@ use crate::Loc;
                         nodes.push(hash);
                         nodes.append(&mut opt_block_arg);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | args tCOMMA assocs opt_block_arg
                     {
@@ -2725,11 +2743,11 @@ use crate::Loc;
                         nodes.push(hash);
                         nodes.append(&mut opt_block_arg);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | block_arg
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 ;
 
@@ -2763,7 +2781,7 @@ use crate::Loc;
 
        block_arg: tAMPER arg_value
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.block_pass(
                                 $<Token>1,
                                 Some($<BoxedNode>2)
@@ -2776,7 +2794,7 @@ use crate::Loc;
                             return self.yyerror(@1, DiagnosticMessage::NoAnonymousBlockarg {});
                         }
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.block_pass(
                                 $<Token>1,
                                 None,
@@ -2787,21 +2805,21 @@ use crate::Loc;
 
    opt_block_arg: tCOMMA block_arg
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>2 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>2 ]) );
                     }
                 | none
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 ;
 
             args: arg_value
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | tSTAR arg_value
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *self.builder.splat(
@@ -2816,20 +2834,20 @@ use crate::Loc;
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | args tCOMMA tSTAR arg_value
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         let splat = *self.builder.splat($<Token>3, Some($<BoxedNode>4));
                         nodes.push(splat);
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
         mrhs_arg: mrhs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.array(None, $<NodeList>1, None)
                         );
                     }
@@ -2843,7 +2861,7 @@ use crate::Loc;
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | args tCOMMA tSTAR arg_value
                     {
@@ -2851,11 +2869,11 @@ use crate::Loc;
                         nodes.push(
                             *self.builder.splat($<Token>3, Some($<BoxedNode>4))
                         );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | tSTAR arg_value
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *self.builder.splat(
@@ -2910,7 +2928,7 @@ use crate::Loc;
                     }
                 | tFID
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.call_method(
                                 None,
                                 None,
@@ -2931,13 +2949,13 @@ use crate::Loc;
                     {
                         self.yylexer.cmdarg.pop();
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.begin_keyword($<Token>1, $<MaybeBoxedNode>3, $<Token>4)
                         );
                     }
                 | tLPAREN_ARG { self.yylexer.lex_state.set(EXPR_ENDARG); $<None>$ = Value::None; } rparen
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.begin(
                                 $<Token>1,
                                 None,
@@ -2947,7 +2965,7 @@ use crate::Loc;
                     }
                 | tLPAREN_ARG stmt { self.yylexer.lex_state.set(EXPR_ENDARG); $<None>$ = Value::None; } rparen
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.begin(
                                 $<Token>1,
                                 Some($<BoxedNode>2),
@@ -2957,7 +2975,7 @@ use crate::Loc;
                     }
                 | tLPAREN compstmt tRPAREN
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.begin(
                                 $<Token>1,
                                 $<MaybeBoxedNode>2,
@@ -2967,7 +2985,7 @@ use crate::Loc;
                     }
                 | primary_value tCOLON2 tCONSTANT
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_fetch(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -2977,13 +2995,13 @@ use crate::Loc;
                     }
                 | tCOLON3 tCONSTANT
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_global($<Token>1, $<Token>2)
                         );
                     }
                 | tLBRACK aref_args tRBRACK
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.array(
                                 Some($<Token>1),
                                 $<NodeList>2,
@@ -2993,7 +3011,7 @@ use crate::Loc;
                     }
                 | tLBRACE assoc_list tRCURLY
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.associate(
                                 Some($<Token>1),
                                 $<NodeList>2,
@@ -3003,7 +3021,7 @@ use crate::Loc;
                     }
                 | k_return
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Return,
                                 $<Token>1,
@@ -3015,7 +3033,7 @@ use crate::Loc;
                     }
                 | kYIELD tLPAREN2 call_args rparen
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Yield,
                                 $<Token>1,
@@ -3027,7 +3045,7 @@ use crate::Loc;
                     }
                 | kYIELD tLPAREN2 rparen
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Yield,
                                 $<Token>1,
@@ -3039,7 +3057,7 @@ use crate::Loc;
                     }
                 | kYIELD
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Yield,
                                 $<Token>1,
@@ -3057,7 +3075,7 @@ use crate::Loc;
                   expr rparen
                     {
                         self.context.set_in_defined(false);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Defined,
                                 $<Token>1,
@@ -3069,7 +3087,7 @@ use crate::Loc;
                     }
                 | kNOT tLPAREN2 expr rparen
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.not_op(
                                 $<Token>1,
                                 Some($<Token>2),
@@ -3080,7 +3098,7 @@ use crate::Loc;
                     }
                 | kNOT tLPAREN2 rparen
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.not_op(
                                 $<Token>1,
                                 Some($<Token>2),
@@ -3101,7 +3119,7 @@ use crate::Loc;
                         );
                         let BraceBlock { begin_t, args_type, body, end_t } = $<BraceBlock>2;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.block(
                                 method_call,
                                 begin_t,
@@ -3118,7 +3136,7 @@ use crate::Loc;
                 | method_call brace_block
                     {
                         let BraceBlock { begin_t, args_type, body, end_t } = $<BraceBlock>2;
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.block(
                                 $<BoxedNode>1,
                                 begin_t,
@@ -3139,7 +3157,7 @@ use crate::Loc;
                     {
                         let IfTail { keyword_t, body: else_body } = $<IfTail>5;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.condition(
                                 $<Token>1,
                                 $<BoxedNode>2,
@@ -3158,7 +3176,7 @@ use crate::Loc;
                     {
                         let (else_t, body) = $<OptElse>5.map(|else_| (Some(else_.else_t), else_.body)).unwrap_or_else(|| (None, None));
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.condition(
                                 $<Token>1,
                                 $<BoxedNode>2,
@@ -3175,7 +3193,7 @@ use crate::Loc;
                   k_end
                     {
                         let ExprValueDo { value, do_t } = $<ExprValueDo>2;
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.loop_(
                                 LoopType::While,
                                 $<Token>1,
@@ -3191,7 +3209,7 @@ use crate::Loc;
                   k_end
                     {
                         let ExprValueDo { value, do_t } = $<ExprValueDo>2;
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.loop_(
                                 LoopType::Until,
                                 $<Token>1,
@@ -3204,17 +3222,16 @@ use crate::Loc;
                     }
                 | k_case expr_value opt_terms
                     {
-                        // TODO: there's a warning that wq/parser doesn't trigger,
-                        // search for `p->case_labels`
                         $<None>$ = Value::None;
                     }
                   case_body
                   k_end
                     {
                         let CaseBody { when_bodies, opt_else } = $<CaseBody>5;
+                        self.warn_duplicated_when_clauses(&when_bodies);
                         let (else_t, else_body) = opt_else.map(|else_| (Some(else_.else_t), else_.body)).unwrap_or_else(|| (None, None));
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.case(
                                 $<Token>1,
                                 Some($<BoxedNode>2),
@@ -3227,8 +3244,8 @@ use crate::Loc;
                     }
                 | k_case opt_terms
                     {
-                        // TODO: there's a warning that wq/parser doesn't trigger,
-                        // search for `p->case_labels`
+                        // MRI doesn't check for duplicated `when` literals
+                        // if there's no value to match
                         $<None>$ = Value::None;
                     }
                   case_body
@@ -3237,7 +3254,7 @@ use crate::Loc;
                         let CaseBody { when_bodies, opt_else } = $<CaseBody>4;
                         let (else_t, else_body) = opt_else.map(|else_| (Some(else_.else_t), else_.body)).unwrap_or_else(|| (None, None));
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.case(
                                 $<Token>1,
                                 None,
@@ -3255,7 +3272,7 @@ use crate::Loc;
                         let PCaseBody { in_bodies, opt_else } = $<PCaseBody>4;
                         let (else_t, else_body) = opt_else.map(|else_| (Some(else_.else_t), else_.body)).unwrap_or_else(|| (None, None));
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.case_match(
                                 $<Token>1,
                                 $<BoxedNode>2,
@@ -3271,7 +3288,7 @@ use crate::Loc;
                   k_end
                     {
                         let ExprValueDo { value, do_t } = $<ExprValueDo>4;
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.for_(
                                 $<Token>1,
                                 $<BoxedNode>2,
@@ -3299,7 +3316,7 @@ use crate::Loc;
 
                         let Superclass { lt_t, value } = $<Superclass>3;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_class(
                                 k_class,
                                 $<BoxedNode>2,
@@ -3325,7 +3342,7 @@ use crate::Loc;
                   k_end
                     {
                         let TokenWithContext { token: k_class, ctx } = $<TokenWithContext>1;
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_sclass(
                                 k_class,
                                 $<Token>2,
@@ -3353,7 +3370,7 @@ use crate::Loc;
                             return self.yyerror(&k_module.loc, DiagnosticMessage::ModuleDefinitionInMethodBody {});
                         }
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_module(
                                 k_module,
                                 $<BoxedNode>2,
@@ -3370,7 +3387,7 @@ use crate::Loc;
                         let DefnHead { def_t, name_t } = $<DefnHead>1;
                         let TokenWithContext { token: name_t, ctx } = name_t;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_method(
                                 def_t,
                                 name_t,
@@ -3389,7 +3406,7 @@ use crate::Loc;
                         let DefsHead { def_t, definee, dot_t, name_t } = $<DefsHead>1;
                         let TokenWithContext { token: name_t, ctx } = name_t;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.def_singleton(
                                 def_t,
                                 definee,
@@ -3407,7 +3424,7 @@ use crate::Loc;
                     }
                 | kBREAK
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Break,
                                 $<Token>1,
@@ -3419,7 +3436,7 @@ use crate::Loc;
                     }
                 | kNEXT
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Next,
                                 $<Token>1,
@@ -3431,7 +3448,7 @@ use crate::Loc;
                     }
                 | kREDO
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Redo,
                                 $<Token>1,
@@ -3443,7 +3460,7 @@ use crate::Loc;
                     }
                 | kRETRY
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Retry,
                                 $<Token>1,
@@ -3459,12 +3476,13 @@ use crate::Loc;
                     {
                         let primary = $<BoxedNode>1;
                         self.value_expr(&primary)?;
-                        $$ = Value::Node(primary);
+                        $$ = Value::new_node(primary);
                     }
                 ;
 
          k_begin: kBEGIN
                     {
+                        self.token_info_push("begin", @1);
                         $$ = $1;
                     }
                 ;
@@ -3472,42 +3490,49 @@ use crate::Loc;
             k_if: kIF
                     {
                         self.warn_eol(@1, "if");
+                        self.token_info_push_if(@1);
                         $$ = $1;
                     }
                 ;
 
         k_unless: kUNLESS
                     {
+                        self.token_info_push("unless", @1);
                         $$ = $1;
                     }
                 ;
 
          k_while: kWHILE
                     {
+                        self.token_info_push("while", @1);
                         $$ = $1;
                     }
                 ;
 
          k_until: kUNTIL
                     {
+                        self.token_info_push("until", @1);
                         $$ = $1;
                     }
                 ;
 
           k_case: kCASE
                     {
+                        self.token_info_push("case", @1);
                         $$ = $1;
                     }
                 ;
 
            k_for: kFOR
                     {
+                        self.token_info_push("for", @1);
                         $$ = $1;
                     }
                 ;
 
          k_class: kCLASS
                     {
+                        self.token_info_push("class", @1);
                         $$ = Value::TokenWithContext(
                             Box::new(
                                 TokenWithContext {
@@ -3521,6 +3546,7 @@ use crate::Loc;
 
         k_module: kMODULE
                     {
+                        self.token_info_push("module", @1);
                         $$ = Value::TokenWithContext(
                             Box::new(
                                 TokenWithContext {
@@ -3534,6 +3560,7 @@ use crate::Loc;
 
            k_def: kDEF
                     {
+                        self.token_info_push("def", @1);
                         $$ = $1;
                         self.context.set_in_argdef(true);
                     }
@@ -3541,36 +3568,42 @@ use crate::Loc;
 
             k_do: kDO
                     {
+                        self.token_info_push("do", @1);
                         $$ = $1;
                     }
                 ;
 
       k_do_block: kDO_BLOCK
                     {
+                        self.token_info_push("do", @1);
                         $$ = $1;
                     }
                 ;
 
         k_rescue: kRESCUE
                     {
+                        self.token_info_warn("rescue", true, @1);
                         $$ = $1;
                     }
                 ;
 
         k_ensure: kENSURE
                     {
+                        self.token_info_warn("ensure", true, @1);
                         $$ = $1;
                     }
                 ;
 
           k_when: kWHEN
                     {
+                        self.token_info_warn("when", false, @1);
                         $$ = $1;
                     }
                 ;
 
           k_else: kELSE
                     {
+                        self.token_info_else(@1);
                         $$ = $1;
                     }
                 ;
@@ -3578,12 +3611,14 @@ use crate::Loc;
          k_elsif: kELSIF
                     {
                         self.warn_eol(@1, "elsif");
+                        self.token_info_warn("elsif", true, @1);
                         $$ = $1;
                     }
                 ;
 
            k_end: kEND
                     {
+                        self.token_info_pop("end", @1);
                         $$ = $1;
                     }
                 ;
@@ -3677,13 +3712,13 @@ use crate::Loc;
 
           f_marg: f_norm_arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.arg($<Token>1)?
                         );
                     }
                 | tLPAREN f_margs rparen
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.multi_lhs(
                                 Some($<Token>1),
                                 $<NodeList>2,
@@ -3695,13 +3730,13 @@ use crate::Loc;
 
      f_marg_list: f_marg
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | f_marg_list tCOMMA f_marg
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
@@ -3713,7 +3748,7 @@ use crate::Loc;
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_marg_list tCOMMA f_rest_marg tCOMMA f_marg_list
                     {
@@ -3725,11 +3760,11 @@ use crate::Loc;
                         nodes.push(f_rest_marg);
                         nodes.append(&mut f_marg_list);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_rest_marg
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | f_rest_marg tCOMMA f_marg_list
                     {
@@ -3740,19 +3775,19 @@ use crate::Loc;
                         nodes.push(f_rest_marg);
                         nodes.append(&mut f_marg_list);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
      f_rest_marg: tSTAR f_norm_arg
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.restarg($<Token>1, Some($<Token>2))?
                         );
                     }
                 | tSTAR
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.restarg($<Token>1, None)?
                         );
                     }
@@ -3788,25 +3823,25 @@ use crate::Loc;
                         nodes.append(&mut f_kwrest);
                         nodes.append(&mut opt_f_block_arg);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_block_kwarg opt_f_block_arg
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.append(&mut $<NodeList>2);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_any_kwrest opt_f_block_arg
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.append(&mut $<NodeList>2);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_block_arg
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 ;
 
@@ -3817,7 +3852,7 @@ opt_block_args_tail:
                     }
                 | /* none */
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 ;
 
@@ -3839,7 +3874,7 @@ opt_block_args_tail:
                         nodes.append(&mut f_rest_arg);
                         nodes.append(&mut opt_block_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_arg tCOMMA f_block_optarg tCOMMA f_rest_arg tCOMMA f_arg opt_block_args_tail
                     {
@@ -3855,7 +3890,7 @@ opt_block_args_tail:
                         nodes.append(&mut f_arg);
                         nodes.append(&mut opt_block_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_arg tCOMMA f_block_optarg opt_block_args_tail
                     {
@@ -3867,7 +3902,7 @@ opt_block_args_tail:
                         nodes.append(&mut f_block_optarg);
                         nodes.append(&mut opt_block_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_arg tCOMMA f_block_optarg tCOMMA f_arg opt_block_args_tail
                     {
@@ -3881,7 +3916,7 @@ opt_block_args_tail:
                         nodes.append(&mut f_arg);
                         nodes.append(&mut opt_block_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_arg tCOMMA f_rest_arg opt_block_args_tail
                     {
@@ -3893,7 +3928,7 @@ opt_block_args_tail:
                         nodes.append(&mut f_rest_arg);
                         nodes.append(&mut opt_block_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_arg excessed_comma
                     {
@@ -3911,7 +3946,7 @@ opt_block_args_tail:
                         nodes.append(&mut f_arg);
                         nodes.append(&mut opt_block_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_arg opt_block_args_tail
                     {
@@ -3929,7 +3964,7 @@ opt_block_args_tail:
                             nodes.append(&mut opt_block_args_tail);
                         }
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_block_optarg tCOMMA f_rest_arg opt_block_args_tail
                     {
@@ -3941,7 +3976,7 @@ opt_block_args_tail:
                         nodes.append(&mut f_rest_arg);
                         nodes.append(&mut opt_block_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_block_optarg tCOMMA f_rest_arg tCOMMA f_arg opt_block_args_tail
                     {
@@ -3955,14 +3990,14 @@ opt_block_args_tail:
                         nodes.append(&mut f_arg);
                         nodes.append(&mut opt_block_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_block_optarg opt_block_args_tail
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.append(&mut $<NodeList>2);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_block_optarg tCOMMA f_arg opt_block_args_tail
                     {
@@ -3974,14 +4009,14 @@ opt_block_args_tail:
                         nodes.append(&mut f_arg);
                         nodes.append(&mut opt_block_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_rest_arg opt_block_args_tail
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.append(&mut $<NodeList>2);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_rest_arg tCOMMA f_arg opt_block_args_tail
                     {
@@ -3993,7 +4028,7 @@ opt_block_args_tail:
                         nodes.append(&mut f_arg);
                         nodes.append(&mut opt_block_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | block_args_tail
                     {
@@ -4003,7 +4038,7 @@ opt_block_args_tail:
 
  opt_block_param: none
                     {
-                        $$ = Value::MaybeNode(
+                        $$ = Value::new_maybe_node(
                             self.builder.args(None, vec![], None)
                         );
                     }
@@ -4020,7 +4055,7 @@ opt_block_args_tail:
                         self.current_arg_stack.set(None);
                         self.context.set_in_argdef(false);
 
-                        $$ = Value::MaybeNode(
+                        $$ = Value::new_maybe_node(
                             self.builder.args(
                                 Some($<Token>1),
                                 $<NodeList>2,
@@ -4037,7 +4072,7 @@ opt_block_args_tail:
                         let mut nodes = $<NodeList>2;
                         nodes.append(&mut $<NodeList>3);
 
-                        $$ = Value::MaybeNode(
+                        $$ = Value::new_maybe_node(
                             self.builder.args(
                                 Some($<Token>1),
                                 nodes,
@@ -4050,7 +4085,7 @@ opt_block_args_tail:
 
      opt_bv_decl: opt_nl
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 | opt_nl tSEMI bv_decls opt_nl
                     {
@@ -4060,13 +4095,13 @@ opt_block_args_tail:
 
         bv_decls: bvar
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | bv_decls tCOMMA bvar
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
@@ -4074,7 +4109,7 @@ opt_block_args_tail:
                     {
                         let ident_t = $<Token>1;
                         self.static_env.declare(clone_value(&ident_t).as_str());
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.shadowarg(ident_t)?
                         );
                     }
@@ -4086,6 +4121,7 @@ opt_block_args_tail:
 
           lambda: tLAMBDA
                     {
+                        self.token_info_push("->", @1);
                         self.static_env.extend_dynamic();
                         self.max_numparam_stack.push(false);
                         $<Num>$ = Value::Num(self.yylexer.lpar_beg);
@@ -4114,10 +4150,11 @@ opt_block_args_tail:
 
                         self.max_numparam_stack.pop();
                         self.static_env.unextend();
+                        self.warn_unused_variables();
                         self.yylexer.cmdarg.pop();
                         self.context.set_in_lambda($<Context>3.in_lambda());
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.block(
                                 lambda_call,
                                 begin_t,
@@ -4137,7 +4174,7 @@ opt_block_args_tail:
                         let mut nodes = $<NodeList>2;
                         nodes.append(&mut $<NodeList>3);
 
-                        $$ = Value::MaybeNode(
+                        $$ = Value::new_maybe_node(
                             self.builder.args(
                                 Some($<Token>1),
                                 nodes,
@@ -4152,7 +4189,7 @@ opt_block_args_tail:
                         if !args.is_empty() {
                             self.max_numparam_stack.set_has_ordinary_params();
                         }
-                        $$ = Value::MaybeNode(
+                        $$ = Value::new_maybe_node(
                             self.builder.args(None, args, None)
                         );
                     }
@@ -4165,6 +4202,7 @@ opt_block_args_tail:
                     }
                   compstmt tRCURLY
                     {
+                        self.token_info_pop("}", @4);
                         self.context.set_in_lambda($<Context>2.in_lambda());
                         $$ = Value::new_lambda_body(
                             LambdaBody {
@@ -4215,7 +4253,7 @@ opt_block_args_tail:
       block_call: command do_block
                     {
                         let DoBlock { begin_t, args_type, body, end_t } = $<DoBlock>2;
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.block(
                                 $<BoxedNode>1,
                                 begin_t,
@@ -4228,7 +4266,7 @@ opt_block_args_tail:
                 | block_call call_op2 operation2 opt_paren_args
                     {
                         let OptParenArgs { begin_t, args, end_t } = $<OptParenArgs>4;
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.call_method(
                                 Some($<BoxedNode>1),
                                 Some($<Token>2),
@@ -4252,7 +4290,7 @@ opt_block_args_tail:
                         );
 
                         let BraceBlock { begin_t, args_type, body, end_t } = $<BraceBlock>5;
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.block(
                                 method_call,
                                 begin_t,
@@ -4274,7 +4312,7 @@ opt_block_args_tail:
                         );
 
                         let DoBlock { begin_t, args_type, body, end_t } = $<DoBlock>5;
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.block(
                                 method_call,
                                 begin_t,
@@ -4290,7 +4328,7 @@ opt_block_args_tail:
                     {
                         let ParenArgs { begin_t, args, end_t } = $<ParenArgs>2;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.call_method(
                                 None,
                                 None,
@@ -4305,7 +4343,7 @@ opt_block_args_tail:
                     {
                         let OptParenArgs { begin_t, args, end_t } = $<OptParenArgs>4;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.call_method(
                                 Some($<BoxedNode>1),
                                 Some($<Token>2),
@@ -4320,7 +4358,7 @@ opt_block_args_tail:
                     {
                         let ParenArgs { begin_t, args, end_t } = $<ParenArgs>4;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.call_method(
                                 Some($<BoxedNode>1),
                                 Some($<Token>2),
@@ -4333,7 +4371,7 @@ opt_block_args_tail:
                     }
                 | primary_value tCOLON2 operation3
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.call_method(
                                 Some($<BoxedNode>1),
                                 Some($<Token>2),
@@ -4348,7 +4386,7 @@ opt_block_args_tail:
                     {
                         let ParenArgs { begin_t, args, end_t } = $<ParenArgs>3;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.call_method(
                                 Some($<BoxedNode>1),
                                 Some($<Token>2),
@@ -4363,7 +4401,7 @@ opt_block_args_tail:
                     {
                         let ParenArgs { begin_t, args, end_t } = $<ParenArgs>3;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.call_method(
                                 Some($<BoxedNode>1),
                                 Some($<Token>2),
@@ -4378,7 +4416,7 @@ opt_block_args_tail:
                     {
                         let ParenArgs { begin_t, args, end_t } = $<ParenArgs>2;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Super,
                                 $<Token>1,
@@ -4390,7 +4428,7 @@ opt_block_args_tail:
                     }
                 | kSUPER
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.keyword_cmd(
                                 KeywordCmd::Zsuper,
                                 $<Token>1,
@@ -4402,7 +4440,7 @@ opt_block_args_tail:
                     }
                 | primary_value tLBRACK2 opt_call_args rbracket
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.index(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -4468,6 +4506,7 @@ opt_block_args_tail:
 
                         self.max_numparam_stack.pop();
                         self.static_env.unextend();
+                        self.warn_unused_variables();
 
                         $$ = Value::new_brace_body(
                             BraceBody {
@@ -4494,6 +4533,7 @@ opt_block_args_tail:
 
                         self.max_numparam_stack.pop();
                         self.static_env.unextend();
+                        self.warn_unused_variables();
                         self.yylexer.cmdarg.pop();
 
                         $$ = Value::new_do_body(
@@ -4504,11 +4544,11 @@ opt_block_args_tail:
 
        case_args: arg_value
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | tSTAR arg_value
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *self.builder.splat(
@@ -4523,14 +4563,14 @@ opt_block_args_tail:
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | case_args tCOMMA tSTAR arg_value
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         let splat = *self.builder.splat($<Token>3, Some($<BoxedNode>4));
                         nodes.push(splat);
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
@@ -4632,7 +4672,7 @@ opt_block_args_tail:
                     }
                 | p_expr tCOMMA
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.array_pattern(
                                 None,
                                 vec![ $<Node>1 ],
@@ -4649,25 +4689,25 @@ opt_block_args_tail:
                         nodes.push($<Node>1);
                         nodes.append(&mut elements);
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.array_pattern(None, nodes, trailing_comma, None)
                         );
                     }
                 | p_find
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.find_pattern(None, $<NodeList>1, None)
                         );
                     }
                 | p_args_tail
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.array_pattern(None, $<NodeList>1, None, None)
                         );
                     }
                 | p_kwargs
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.hash_pattern(None, $<NodeList>1, None)
                         );
                     }
@@ -4681,7 +4721,7 @@ opt_block_args_tail:
 
             p_as: p_expr tASSOC p_variable
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.match_as(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -4697,7 +4737,7 @@ opt_block_args_tail:
 
            p_alt: p_alt tPIPE p_expr_basic
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.match_alt(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -4738,7 +4778,7 @@ opt_block_args_tail:
                         self.pattern_hash_keys.pop();
                         let MatchPatternWithTrailingComma { elements, trailing_comma } = $<MatchPatternWithTrailingComma>3;
                         let pattern = self.builder.array_pattern(None, elements, trailing_comma, None);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_pattern(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -4751,7 +4791,7 @@ opt_block_args_tail:
                     {
                         self.pattern_hash_keys.pop();
                         let pattern = self.builder.find_pattern(None, $<NodeList>3, None);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_pattern(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -4764,7 +4804,7 @@ opt_block_args_tail:
                     {
                         self.pattern_hash_keys.pop();
                         let pattern = self.builder.hash_pattern(None, $<NodeList>3, None);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_pattern(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -4783,7 +4823,7 @@ opt_block_args_tail:
                             None,
                             Some(rparen.clone())
                         );
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_pattern(
                                 $<BoxedNode>1,
                                 lparen,
@@ -4797,7 +4837,7 @@ opt_block_args_tail:
                         self.pattern_hash_keys.pop();
                         let MatchPatternWithTrailingComma { elements, trailing_comma } = $<MatchPatternWithTrailingComma>3;
                         let pattern = self.builder.array_pattern(None, elements, trailing_comma, None);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_pattern(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -4810,7 +4850,7 @@ opt_block_args_tail:
                     {
                         self.pattern_hash_keys.pop();
                         let pattern = self.builder.find_pattern(None, $<NodeList>3, None);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_pattern(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -4823,7 +4863,7 @@ opt_block_args_tail:
                     {
                         self.pattern_hash_keys.pop();
                         let pattern = self.builder.hash_pattern(None, $<NodeList>3, None);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_pattern(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -4842,7 +4882,7 @@ opt_block_args_tail:
                             None,
                             Some(rparen.clone())
                         );
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_pattern(
                                 $<BoxedNode>1,
                                 lparen,
@@ -4854,7 +4894,7 @@ opt_block_args_tail:
                 | tLBRACK p_args rbracket
                     {
                         let MatchPatternWithTrailingComma { elements, trailing_comma } = $<MatchPatternWithTrailingComma>2;
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.array_pattern(
                                 Some($<Token>1),
                                 elements,
@@ -4865,7 +4905,7 @@ opt_block_args_tail:
                     }
                 | tLBRACK p_find rbracket
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.find_pattern(
                                 Some($<Token>1),
                                 $<NodeList>2,
@@ -4875,7 +4915,7 @@ opt_block_args_tail:
                     }
                 | tLBRACK rbracket
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.array_pattern(
                                 Some($<Token>1),
                                 vec![],
@@ -4894,7 +4934,7 @@ opt_block_args_tail:
                     {
                         self.pattern_hash_keys.pop();
                         self.context.set_in_kwarg($<Bool>2);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.hash_pattern(
                                 Some($<Token>1),
                                 $<NodeList>3,
@@ -4904,7 +4944,7 @@ opt_block_args_tail:
                     }
                 | tLBRACE rbrace
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.hash_pattern(
                                 Some($<Token>1),
                                 vec![],
@@ -4920,7 +4960,7 @@ opt_block_args_tail:
                   p_expr rparen
                     {
                         self.pattern_hash_keys.pop();
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.begin(
                                 $<Token>1,
                                 Some($<BoxedNode>3),
@@ -5021,7 +5061,7 @@ opt_block_args_tail:
 
      p_args_tail: p_rest
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | p_rest tCOMMA p_args_post
                     {
@@ -5030,7 +5070,7 @@ opt_block_args_tail:
                         nodes.push($<Node>1);
                         nodes.append(&mut p_args_post);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
@@ -5042,20 +5082,20 @@ opt_block_args_tail:
                         nodes.append(&mut p_args_post);
                         nodes.push($<Node>5);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
 
           p_rest: tSTAR tIDENTIFIER
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.match_rest($<Token>1, Some($<Token>2))?
                         );
                     }
                 | tSTAR
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.match_rest($<Token>1, None)?
                         );
                     }
@@ -5063,13 +5103,13 @@ opt_block_args_tail:
 
      p_args_post: p_arg
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | p_args_post tCOMMA p_arg
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
@@ -5084,7 +5124,7 @@ opt_block_args_tail:
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.append(&mut $<NodeList>3);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | p_kwarg
                     {
@@ -5102,19 +5142,19 @@ opt_block_args_tail:
 
          p_kwarg: p_kw
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | p_kwarg tCOMMA p_kw
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
             p_kw: p_kw_label p_expr
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.match_pair(
                                 $<PKwLabel>1,
                                 $<BoxedNode>2
@@ -5123,7 +5163,7 @@ opt_block_args_tail:
                     }
                 | p_kw_label
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.match_label(
                                 $<PKwLabel>1,
                             )?
@@ -5147,7 +5187,7 @@ opt_block_args_tail:
 
         p_kwrest: kwrest_mark tIDENTIFIER
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *self.builder.match_rest(
@@ -5160,7 +5200,7 @@ opt_block_args_tail:
                     }
                 | kwrest_mark
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *self.builder.match_rest(
@@ -5191,7 +5231,7 @@ opt_block_args_tail:
                 | p_kwnorest
                     {
                         let NoKwRest { kwrest_mark, k_nil } = $<NoKwRest>1;
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *self.builder.match_nil_pattern(
@@ -5216,7 +5256,7 @@ opt_block_args_tail:
                         let right = $<BoxedNode>3;
                         self.value_expr(&right)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.range_inclusive(
                                 Some(left),
                                 $<Token>2,
@@ -5232,7 +5272,7 @@ opt_block_args_tail:
                         let right = $<BoxedNode>3;
                         self.value_expr(&right)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.range_exclusive(
                                 Some(left),
                                 $<Token>2,
@@ -5245,7 +5285,7 @@ opt_block_args_tail:
                         let left = $<BoxedNode>1;
                         self.value_expr(&left)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.range_inclusive(
                                 Some(left),
                                 $<Token>2,
@@ -5258,7 +5298,7 @@ opt_block_args_tail:
                         let left = $<BoxedNode>1;
                         self.value_expr(&left)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.range_exclusive(
                                 Some(left),
                                 $<Token>2,
@@ -5283,7 +5323,7 @@ opt_block_args_tail:
                         let right = $<BoxedNode>2;
                         self.value_expr(&right)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.range_inclusive(
                                 None,
                                 $<Token>1,
@@ -5296,7 +5336,7 @@ opt_block_args_tail:
                         let right = $<BoxedNode>2;
                         self.value_expr(&right)?;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.range_exclusive(
                                 None,
                                 $<Token>1,
@@ -5340,7 +5380,7 @@ opt_block_args_tail:
                     }
                 | keyword_variable
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.accessible($<BoxedNode>1)
                         );
                     }
@@ -5352,7 +5392,7 @@ opt_block_args_tail:
 
       p_variable: tIDENTIFIER
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable(
                                 self.builder.match_var($<Token>1)?
                             )?
@@ -5373,14 +5413,14 @@ opt_block_args_tail:
                         }
 
                         let lvar = self.builder.accessible(self.builder.lvar(ident_t));
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.pin($<Token>1, lvar)
                         );
                     }
                 | tCARET nonlocal_var
                     {
                         let non_lvar = self.builder.accessible($<BoxedNode>2);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.pin(
                                 $<Token>1,
                                 non_lvar,
@@ -5396,7 +5436,7 @@ opt_block_args_tail:
                             Some($<BoxedNode>3),
                             $<Token>4
                         );
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.pin(
                                 $<Token>1,
                                 expr
@@ -5407,13 +5447,13 @@ opt_block_args_tail:
 
          p_const: tCOLON3 cname
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_global($<Token>1, $<Token>2)
                         );
                     }
                 | p_const tCOLON2 cname
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_fetch(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -5423,7 +5463,7 @@ opt_block_args_tail:
                     }
                 | tCONSTANT
                     {
-                        $$ = Value::Node(self.builder.const_($<Token>1));
+                        $$ = Value::new_node(self.builder.const_($<Token>1));
                     }
                 ;
 
@@ -5453,17 +5493,17 @@ opt_block_args_tail:
                         nodes.push(rescue_body);
                         nodes.append(&mut opt_rescue);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | none
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 ;
 
         exc_list: arg_value
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | mrhs
                     {
@@ -5471,7 +5511,7 @@ opt_block_args_tail:
                     }
                 | none
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 ;
 
@@ -5511,7 +5551,7 @@ opt_block_args_tail:
 
          strings: string
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.string_compose(
                                 None,
                                 $<NodeList>1,
@@ -5523,7 +5563,7 @@ opt_block_args_tail:
 
           string: tCHAR
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *self.builder.character($<Token>1)
@@ -5533,13 +5573,13 @@ opt_block_args_tail:
                     }
                 | string1
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | string string1
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>2 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
@@ -5553,7 +5593,7 @@ opt_block_args_tail:
                         let indent = self.yylexer.buffer.heredoc_indent;
                         self.yylexer.buffer.heredoc_indent = 0;
                         string = self.builder.heredoc_dedent(string, indent);
-                        $$ = Value::Node(string);
+                        $$ = Value::new_node(string);
                     }
                 ;
 
@@ -5567,7 +5607,7 @@ opt_block_args_tail:
                         let indent = self.yylexer.buffer.heredoc_indent;
                         self.yylexer.buffer.heredoc_indent = 0;
                         string = self.builder.heredoc_dedent(string, indent);
-                        $$ = Value::Node(string);
+                        $$ = Value::new_node(string);
                     }
                 ;
 
@@ -5575,7 +5615,7 @@ opt_block_args_tail:
                     {
                         let regexp_end = $<Token>3;
                         let opts = self.builder.regexp_options(regexp_end.clone());
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.regexp_compose(
                                 $<Token>1,
                                 $<NodeList>2,
@@ -5588,7 +5628,7 @@ opt_block_args_tail:
 
            words: tWORDS_BEG tSPACE word_list tSTRING_END
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.words_compose(
                                 $<Token>1,
                                 $<NodeList>3,
@@ -5600,7 +5640,7 @@ opt_block_args_tail:
 
        word_list: /* none */
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
 
                     }
                 | word_list word tSPACE
@@ -5609,25 +5649,25 @@ opt_block_args_tail:
                         nodes.push(
                             *self.builder.word( $<NodeList>2 )
                         );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
             word: string_content
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | word string_content
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>2 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
          symbols: tSYMBOLS_BEG tSPACE symbol_list tSTRING_END
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.symbols_compose(
                                 $<Token>1,
                                 $<NodeList>3,
@@ -5639,7 +5679,7 @@ opt_block_args_tail:
 
      symbol_list: /* none */
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 | symbol_list word tSPACE
                     {
@@ -5647,13 +5687,13 @@ opt_block_args_tail:
                         nodes.push(
                             *self.builder.word( $<NodeList>2 )
                         );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
           qwords: tQWORDS_BEG tSPACE qword_list tSTRING_END
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.words_compose(
                                 $<Token>1,
                                 $<NodeList>3,
@@ -5665,7 +5705,7 @@ opt_block_args_tail:
 
         qsymbols: tQSYMBOLS_BEG tSPACE qsym_list tSTRING_END
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.symbols_compose(
                                 $<Token>1,
                                 $<NodeList>3,
@@ -5677,7 +5717,7 @@ opt_block_args_tail:
 
       qword_list: /* none */
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 | qword_list tSTRING_CONTENT tSPACE
                     {
@@ -5685,13 +5725,13 @@ opt_block_args_tail:
                         nodes.push(
                             *self.builder.string_internal( $<Token>2 )
                         );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
        qsym_list: /* none */
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 | qsym_list tSTRING_CONTENT tSPACE
                     {
@@ -5699,49 +5739,49 @@ opt_block_args_tail:
                         nodes.push(
                             *self.builder.symbol_internal( $<Token>2 )
                         );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
  string_contents: /* none */
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 | string_contents string_content
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push($<Node>2);
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
 xstring_contents: /* none */
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 | xstring_contents string_content
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push($<Node>2);
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
  regexp_contents: /* none */
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 | regexp_contents string_content
                     {
                         let mut  nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>2 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
   string_content: tSTRING_CONTENT
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.string_internal($<Token>1)
                         );
                     }
@@ -5786,7 +5826,7 @@ xstring_contents: /* none */
                         self.yylexer.buffer.heredoc_indent = $<Num>6;
                         self.yylexer.buffer.heredoc_line_indent = -1;
 
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.begin(
                                 $<Token>1,
                                 $<MaybeBoxedNode>7,
@@ -5798,16 +5838,16 @@ xstring_contents: /* none */
 
      string_dvar: tGVAR
                     {
-                        $$ = Value::Node(self.builder.gvar($<Token>1));
+                        $$ = Value::new_node(self.builder.gvar($<Token>1));
                     }
                 | tIVAR
                     {
-                        $$ = Value::Node(self.builder.ivar($<Token>1));
+                        $$ = Value::new_node(self.builder.ivar($<Token>1));
 
                     }
                 | tCVAR
                     {
-                        $$ = Value::Node(self.builder.cvar($<Token>1));
+                        $$ = Value::new_node(self.builder.cvar($<Token>1));
                     }
                 | backref
                     {
@@ -5822,7 +5862,7 @@ xstring_contents: /* none */
             ssym: tSYMBEG sym
                     {
                         self.yylexer.lex_state.set(EXPR_END);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.symbol($<Token>1, $<Token>2)
                         );
                     }
@@ -5837,7 +5877,7 @@ xstring_contents: /* none */
             dsym: tSYMBEG string_contents tSTRING_END
                     {
                         self.yylexer.lex_state.set(EXPR_END);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.symbol_compose($<Token>1, $<NodeList>2, $<Token>3)
                         );
                     }
@@ -5849,7 +5889,7 @@ xstring_contents: /* none */
                     }
                 | tUMINUS_NUM simple_numeric   %prec tLOWEST
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.unary_num(
                                 $<Token>1,
                                 $<BoxedNode>2
@@ -5860,25 +5900,25 @@ xstring_contents: /* none */
 
   simple_numeric: tINTEGER
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.integer($<Token>1)
                         );
                     }
                 | tFLOAT
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.float($<Token>1)
                         );
                     }
                 | tRATIONAL
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.rational($<Token>1)
                         );
                     }
                 | tIMAGINARY
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.complex($<Token>1)
                         );
                     }
@@ -5886,19 +5926,19 @@ xstring_contents: /* none */
 
     nonlocal_var: tIVAR
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.ivar($<Token>1)
                         );
                     }
                 | tGVAR
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.gvar($<Token>1)
                         );
                     }
                 | tCVAR
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.cvar($<Token>1)
                         );
                     }
@@ -5906,31 +5946,31 @@ xstring_contents: /* none */
 
    user_variable: tIDENTIFIER
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.lvar($<Token>1)
                         );
                     }
                 | tIVAR
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.ivar($<Token>1)
                         );
                     }
                 | tGVAR
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.gvar($<Token>1)
                         );
                     }
                 | tCONSTANT
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.const_($<Token>1)
                         );
                     }
                 | tCVAR
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.cvar($<Token>1)
                         );
                     }
@@ -5938,43 +5978,43 @@ xstring_contents: /* none */
 
 keyword_variable: kNIL
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.nil($<Token>1)
                         );
                     }
                 | kSELF
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.self_($<Token>1)
                         );
                     }
                 | kTRUE
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.true_($<Token>1)
                         );
                     }
                 | kFALSE
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.false_($<Token>1)
                         );
                     }
                 | k__FILE__
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.__file__($<Token>1)
                         );
                     }
                 | k__LINE__
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.__line__($<Token>1)
                         );
                     }
                 | k__ENCODING__
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.__encoding__($<Token>1)
                         );
                     }
@@ -5982,13 +6022,13 @@ keyword_variable: kNIL
 
          var_ref: user_variable
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.accessible($<BoxedNode>1)
                         );
                     }
                 | keyword_variable
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.accessible($<BoxedNode>1)
                         );
                     }
@@ -5996,13 +6036,13 @@ keyword_variable: kNIL
 
          var_lhs: user_variable
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable($<BoxedNode>1)?
                         );
                     }
                 | keyword_variable
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.assignable($<BoxedNode>1)?
                         );
                     }
@@ -6010,13 +6050,13 @@ keyword_variable: kNIL
 
          backref: tNTH_REF
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.nth_ref($<Token>1)
                         );
                     }
                 | tBACK_REF
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.back_ref($<Token>1)
                         );
                     }
@@ -6049,13 +6089,13 @@ f_opt_paren_args: f_paren_args
                 | none
                     {
                         self.context.set_in_argdef(false);
-                        $$ = Value::MaybeNode(None);
+                        $$ = Value::new_maybe_node(None);
                     }
                 ;
 
     f_paren_args: tLPAREN2 f_args rparen
                     {
-                        $$ = Value::MaybeNode(
+                        $$ = Value::new_maybe_node(
                             self.builder.args(Some($<Token>1), $<NodeList>2, Some($<Token>3))
                         );
 
@@ -6079,7 +6119,7 @@ f_opt_paren_args: f_paren_args
                     {
                         self.context.set_in_kwarg($<Context>1.in_kwarg());
                         self.context.set_in_argdef(false);
-                        $$ = Value::MaybeNode(
+                        $$ = Value::new_maybe_node(
                             self.builder.args(None, $<NodeList>2, None)
                         );
                         self.yylexer.lex_state.set(EXPR_BEG);
@@ -6097,25 +6137,25 @@ f_opt_paren_args: f_paren_args
                         nodes.append(&mut f_kwrest);
                         nodes.append(&mut opt_f_block_arg);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_kwarg opt_f_block_arg
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.append(&mut $<NodeList>2);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_any_kwrest opt_f_block_arg
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.append(&mut $<NodeList>2);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_block_arg
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![ $<Node>1 ]
                             )
@@ -6125,7 +6165,7 @@ f_opt_paren_args: f_paren_args
                     {
                         let forward_arg = *self.builder.forward_arg($<Token>1);
                         self.static_env.declare_forward_args();
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![ forward_arg ]
                             )
@@ -6139,7 +6179,7 @@ f_opt_paren_args: f_paren_args
                     }
                 | /* none */
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 ;
 
@@ -6155,7 +6195,7 @@ f_opt_paren_args: f_paren_args
                         nodes.append(&mut f_rest_arg);
                         nodes.append(&mut opt_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_arg tCOMMA f_optarg tCOMMA f_rest_arg tCOMMA f_arg opt_args_tail
                     {
@@ -6171,7 +6211,7 @@ f_opt_paren_args: f_paren_args
                         nodes.append(&mut f_arg);
                         nodes.append(&mut opt_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_arg tCOMMA f_optarg opt_args_tail
                     {
@@ -6183,7 +6223,7 @@ f_opt_paren_args: f_paren_args
                         nodes.append(&mut f_optarg);
                         nodes.append(&mut opt_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_arg tCOMMA f_optarg tCOMMA f_arg opt_args_tail
                     {
@@ -6197,7 +6237,7 @@ f_opt_paren_args: f_paren_args
                         nodes.append(&mut f_arg);
                         nodes.append(&mut opt_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_arg tCOMMA f_rest_arg opt_args_tail
                     {
@@ -6209,7 +6249,7 @@ f_opt_paren_args: f_paren_args
                         nodes.append(&mut f_rest_arg);
                         nodes.append(&mut opt_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_arg tCOMMA f_rest_arg tCOMMA f_arg opt_args_tail
                     {
@@ -6223,14 +6263,14 @@ f_opt_paren_args: f_paren_args
                         nodes.append(&mut f_arg);
                         nodes.append(&mut opt_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_arg opt_args_tail
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.append(&mut $<NodeList>2);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_optarg tCOMMA f_rest_arg opt_args_tail
                     {
@@ -6242,7 +6282,7 @@ f_opt_paren_args: f_paren_args
                         nodes.append(&mut f_rest_arg);
                         nodes.append(&mut opt_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_optarg tCOMMA f_rest_arg tCOMMA f_arg opt_args_tail
                     {
@@ -6256,14 +6296,14 @@ f_opt_paren_args: f_paren_args
                         nodes.append(&mut f_arg);
                         nodes.append(&mut opt_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_optarg opt_args_tail
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.append(&mut $<NodeList>2);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_optarg tCOMMA f_arg opt_args_tail
                     {
@@ -6275,14 +6315,14 @@ f_opt_paren_args: f_paren_args
                         nodes.append(&mut f_arg);
                         nodes.append(&mut opt_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_rest_arg opt_args_tail
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.append(&mut $<NodeList>2);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | f_rest_arg tCOMMA f_arg opt_args_tail
                     {
@@ -6294,15 +6334,15 @@ f_opt_paren_args: f_paren_args
                         nodes.append(&mut f_arg);
                         nodes.append(&mut opt_args_tail);
 
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 | args_tail
                     {
-                        $$ = Value::NodeList($<BoxedNodeList>1);
+                        $$ = Value::new_node_list($<BoxedNodeList>1);
                     }
                 | /* none */
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 ;
 
@@ -6356,13 +6396,13 @@ f_opt_paren_args: f_paren_args
       f_arg_item: f_arg_asgn
                     {
                         self.current_arg_stack.set(None);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.arg($<Token>1)?
                         );
                     }
                 | tLPAREN f_margs rparen
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.multi_lhs(
                                 Some($<Token>1),
                                 $<NodeList>2,
@@ -6374,13 +6414,13 @@ f_opt_paren_args: f_paren_args
 
            f_arg: f_arg_item
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | f_arg tCOMMA f_arg_item
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
@@ -6406,7 +6446,7 @@ f_opt_paren_args: f_paren_args
                     {
                         self.current_arg_stack.set(None);
                         self.context.set_in_argdef(true);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.kwoptarg($<Token>1, $<BoxedNode>2)?
                         );
                     }
@@ -6414,7 +6454,7 @@ f_opt_paren_args: f_paren_args
                     {
                         self.current_arg_stack.set(None);
                         self.context.set_in_argdef(true);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.kwarg($<Token>1)?
                         );
                     }
@@ -6423,14 +6463,14 @@ f_opt_paren_args: f_paren_args
       f_block_kw: f_label primary_value
                     {
                         self.context.set_in_argdef(true);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.kwoptarg($<Token>1, $<BoxedNode>2)?
                         );
                     }
                 | f_label
                     {
                         self.context.set_in_argdef(true);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.kwarg($<Token>1)?
                         );
                     }
@@ -6438,26 +6478,26 @@ f_opt_paren_args: f_paren_args
 
    f_block_kwarg: f_block_kw
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | f_block_kwarg tCOMMA f_block_kw
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
 
          f_kwarg: f_kw
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | f_kwarg tCOMMA f_kw
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
@@ -6474,7 +6514,7 @@ f_opt_paren_args: f_paren_args
       f_no_kwarg: p_kwnorest
                     {
                         let NoKwRest { kwrest_mark, k_nil } = $<NoKwRest>1;
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *self.builder.kwnilarg(
@@ -6491,7 +6531,7 @@ f_opt_paren_args: f_paren_args
                     {
                         let ident_t = $<Token>2;
                         self.static_env.declare(clone_value(&ident_t).as_str());
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *(self.builder.kwrestarg($<Token>1, Some(ident_t))?)
@@ -6501,7 +6541,7 @@ f_opt_paren_args: f_paren_args
                     }
                 | kwrest_mark
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *(self.builder.kwrestarg($<Token>1, None)?)
@@ -6515,7 +6555,7 @@ f_opt_paren_args: f_paren_args
                     {
                         self.current_arg_stack.set(None);
                         self.context.set_in_argdef(true);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.optarg(
                                 $<Token>1,
                                 $<Token>2,
@@ -6529,7 +6569,7 @@ f_opt_paren_args: f_paren_args
                     {
                         self.current_arg_stack.set(None);
                         self.context.set_in_argdef(true);
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.optarg(
                                 $<Token>1,
                                 $<Token>2,
@@ -6541,25 +6581,25 @@ f_opt_paren_args: f_paren_args
 
   f_block_optarg: f_block_opt
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | f_block_optarg tCOMMA f_block_opt
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
         f_optarg: f_opt
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | f_optarg tCOMMA f_opt
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push( $<Node>3 );
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
@@ -6578,7 +6618,7 @@ f_opt_paren_args: f_paren_args
                         let ident_t = $<Token>2;
                         self.static_env.declare(clone_value(&ident_t).as_str());
 
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *(self.builder.restarg($<Token>1, Some(ident_t))?)
@@ -6588,7 +6628,7 @@ f_opt_paren_args: f_paren_args
                     }
                 | restarg_mark
                     {
-                        $$ = Value::NodeList(
+                        $$ = Value::new_node_list(
                             Box::new(
                                 vec![
                                     *(self.builder.restarg($<Token>1, None)?)
@@ -6612,7 +6652,7 @@ f_opt_paren_args: f_paren_args
                     {
                         let ident_t = $<Token>2;
                         self.static_env.declare(clone_value(&ident_t).as_str());
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.blockarg(
                                 $<Token>1,
                                 Some(ident_t),
@@ -6622,7 +6662,7 @@ f_opt_paren_args: f_paren_args
                 | blkarg_mark
                     {
                         self.static_env.declare_anonymous_blockarg();
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.blockarg(
                                 $<Token>1,
                                 None
@@ -6633,11 +6673,11 @@ f_opt_paren_args: f_paren_args
 
  opt_f_block_arg: tCOMMA f_block_arg
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>2 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>2 ]) );
                     }
                 | none
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 ;
 
@@ -6645,7 +6685,7 @@ f_opt_paren_args: f_paren_args
                     {
                         let var_ref = $<BoxedNode>1;
                         self.value_expr(&var_ref)?;
-                        $$ = Value::Node(var_ref);
+                        $$ = Value::new_node(var_ref);
                     }
                 | tLPAREN2 { self.yylexer.lex_state.set(EXPR_BEG); $<None>$ = Value::None; } expr rparen
                     {
@@ -6675,13 +6715,13 @@ f_opt_paren_args: f_paren_args
                             }
                         }
 
-                        $$ = Value::Node(expr);
+                        $$ = Value::new_node(expr);
                     }
                 ;
 
       assoc_list: none
                     {
-                        $$ = Value::NodeList( Box::default() );
+                        $$ = Value::new_node_list( Box::default() );
                     }
                 | assocs trailer
                     {
@@ -6691,19 +6731,19 @@ f_opt_paren_args: f_paren_args
 
           assocs: assoc
                     {
-                        $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                        $$ = Value::new_node_list( Box::new(vec![ $<Node>1 ]) );
                     }
                 | assocs tCOMMA assoc
                     {
                         let mut nodes = $<BoxedNodeList>1;
                         nodes.push($<Node>3);
-                        $$ = Value::NodeList(nodes);
+                        $$ = Value::new_node_list(nodes);
                     }
                 ;
 
            assoc: arg_value tASSOC arg_value
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.pair(
                                 $<BoxedNode>1,
                                 $<Token>2,
@@ -6713,7 +6753,7 @@ f_opt_paren_args: f_paren_args
                     }
                 | tLABEL arg_value
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.pair_keyword(
                                 $<Token>1,
                                 $<BoxedNode>2
@@ -6722,13 +6762,13 @@ f_opt_paren_args: f_paren_args
                     }
                 | tLABEL
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.pair_label($<Token>1)
                         );
                     }
                 | tSTRING_BEG string_contents tLABEL_END arg_value
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.pair_quoted(
                                 $<Token>1,
                                 $<NodeList>2,
@@ -6739,7 +6779,7 @@ f_opt_paren_args: f_paren_args
                     }
                 | tDSTAR arg_value
                     {
-                        $$ = Value::Node(
+                        $$ = Value::new_node(
                             self.builder.kwsplat($<Token>1, $<BoxedNode>2)
                         );
                     }
@@ -6899,13 +6939,23 @@ impl Parser {
     /// Returns an error if given `input` is invalid.
     pub fn new<TInput>(input: TInput, options: ParserOptions) -> Self
     where
-        TInput: Into<Vec<u8>>
+        TInput: Into<SourceBytes>
     {
         let ParserOptions {
             buffer_name,
             decoder,
             record_tokens,
+            record_trivia,
+            record_lex_states,
+            record_string_content_maps,
+            verbose,
+            cancellation_token,
+            timeout,
+            max_depth,
+            max_input_size,
+            max_tokens,
         } = options;
+        let limits = Limits { max_depth, max_input_size, max_tokens };
 
         let context = ParserContext::new();
         let current_arg_stack = CurrentArgStack::new();
@@ -6914,16 +6964,24 @@ impl Parser {
         let pattern_hash_keys = VariablesStack::new();
         let static_env = StaticEnvironment::new();
         let diagnostics = Diagnostics::new();
+        let string_content_maps = StringContentMaps::new();
         let tokens_pool = Pool::new();
 
-        let input: Vec<u8> = input.into();
+        let input: SourceBytes = input.into();
         let buffer_name: String = buffer_name;
 
         let mut lexer = Lexer::new(input, buffer_name, decoder);
         lexer.context = context.clone();
         lexer.static_env = static_env.clone();
         lexer.diagnostics = diagnostics.clone();
+        lexer.string_content_maps = string_content_maps.clone();
+        lexer.record_string_content_maps = record_string_content_maps;
         lexer.tokens_factory = tokens_pool.factory();
+        lexer.cancellation = Cancellation::new(cancellation_token, timeout);
+        lexer.limits = limits;
+        if let Some(reason) = limits.check_input_size(lexer.buffer.input.len()) {
+            lexer.stop(reason);
+        }
 
         let builder = Builder::new(
             static_env.clone(),
@@ -6933,7 +6991,9 @@ impl Parser {
             pattern_variables.clone(),
             pattern_hash_keys.clone(),
             diagnostics.clone(),
+            string_content_maps,
             tokens_pool.factory(),
+            verbose,
         );
 
         let last_token_type = 0;
@@ -6956,10 +7016,101 @@ impl Parser {
             diagnostics,
             yylexer: lexer,
             record_tokens,
+            record_trivia,
+            record_lex_states,
+            lex_states: vec![],
+            verbose,
+            token_info: TokenInfoStack::new(),
             tokens_pool,
         }
     }
 
+    /// Re-initializes the parser with a new `input` and `options`.
+    ///
+    /// Unlike `Parser::new` it keeps memory allocated by the previous run
+    /// (the pool of tokens, internal stacks and the token buffer of the lexer),
+    /// so it's cheaper to reuse a single parser when many inputs are parsed one by one.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lib_ruby_parser::{Parser, ParserOptions};
+    ///
+    /// let mut parser = Parser::new(b"foo".to_vec(), ParserOptions::default());
+    /// let first = parser.do_parse_in_place();
+    ///
+    /// parser.reset(b"bar + 1".to_vec(), ParserOptions::default());
+    /// let second = parser.do_parse_in_place();
+    ///
+    /// assert_eq!(first.ast.unwrap().expression().end, 3);
+    /// assert_eq!(second.ast.unwrap().expression().end, 7);
+    /// ```
+    pub fn reset<TInput>(&mut self, input: TInput, options: ParserOptions)
+    where
+        TInput: Into<SourceBytes>
+    {
+        let ParserOptions {
+            buffer_name,
+            decoder,
+            record_tokens,
+            record_trivia,
+            record_lex_states,
+            record_string_content_maps,
+            verbose,
+            cancellation_token,
+            timeout,
+            max_depth,
+            max_input_size,
+            max_tokens,
+        } = options;
+        let limits = Limits { max_depth, max_input_size, max_tokens };
+
+        // previous run could stop in the middle of a construction
+        // (if it had a syntax error), so all shared stacks are cleared
+        self.static_env.clear();
+        self.context.clear();
+        self.current_arg_stack.clear();
+        self.max_numparam_stack.clear();
+        self.pattern_variables.clear();
+        self.pattern_hash_keys.clear();
+        self.diagnostics.clear();
+        self.yylexer.string_content_maps.clear();
+        self.token_info.clear();
+        self.tokens.clear();
+        self.lex_states.clear();
+
+        self.yylexer.reset(input.into(), buffer_name, decoder);
+        self.yylexer.record_string_content_maps = record_string_content_maps;
+        self.yylexer.cancellation = Cancellation::new(cancellation_token, timeout);
+        self.yylexer.limits = limits;
+        if let Some(reason) = limits.check_input_size(self.yylexer.buffer.input.len()) {
+            self.yylexer.stop(reason);
+        }
+        self.builder = Builder::new(
+            self.static_env.clone(),
+            self.context.clone(),
+            self.current_arg_stack.clone(),
+            self.max_numparam_stack.clone(),
+            self.pattern_variables.clone(),
+            self.pattern_hash_keys.clone(),
+            self.diagnostics.clone(),
+            self.yylexer.string_content_maps.clone(),
+            self.tokens_pool.factory(),
+            verbose,
+        );
+
+        self.yy_error_verbose = true;
+        self.yynerrs = 0;
+        self.yyerrstatus_ = 0;
+        self.result = None;
+        self.last_token_type = 0;
+        self.record_tokens = record_tokens;
+        self.record_trivia = record_trivia;
+        self.record_lex_states = record_lex_states;
+        self.verbose = verbose;
+
+        self.assert_state_is_final();
+    }
+
     /// Parses given input and returns:
     ///
     /// 1. AST
@@ -6968,43 +7119,92 @@ impl Parser {
     /// 4. comments
     /// 5. magic comments
     pub fn do_parse(mut self) -> ParserResult  {
+        self.do_parse_in_place()
+    }
+
+    /// Parses given input like `do_parse`, but doesn't consume the parser,
+    /// so it can be `reset` and reused for another input.
+    pub fn do_parse_in_place(&mut self) -> ParserResult  {
         self.parse();
+        self.static_env.unextend_top_level();
+        self.warn_unused_variables();
 
-        ParserResult {
-            ast: self.result,
-            tokens: self.tokens,
-            diagnostics: self.diagnostics.take_inner(),
-            comments: self.yylexer.comments,
-            magic_comments: self.yylexer.magic_comments,
-            input: self.yylexer.buffer.input.decoded,
-        }
+        self.take_result()
     }
 
     #[doc(hidden)]
     pub fn do_parse_with_state_validation(mut self) -> ParserResult {
         self.parse();
+        self.static_env.unextend_top_level();
+        self.warn_unused_variables();
 
         self.assert_state_is_final();
 
+        self.take_result()
+    }
+
+    fn take_result(&mut self) -> ParserResult {
+        // vectors are drained (and not taken) to keep their capacity
+        // for the next run of a reused parser
+        let comments = self.yylexer.comments.drain(..).collect::<Vec<_>>();
+        let input = std::mem::take(&mut self.yylexer.buffer.input.decoded);
+        let mut tokens = self.tokens.drain(..).collect::<Vec<_>>();
+        let mut lex_states = self.lex_states.drain(..).collect::<Vec<_>>();
+        if self.record_tokens && self.record_trivia {
+            insert_trivia_with_lex_states(&mut tokens, &mut lex_states, &input, &comments);
+        }
+
+        let mut ast = self.result.take().map(Ast::from);
+        if let Some(loc) = ast.as_deref().and_then(|ast| self.yylexer.limits.find_too_deep_node(ast)) {
+            let max_depth = self.yylexer.limits.max_depth.unwrap_or_default();
+            self.diagnostics.emit(Diagnostic {
+                level: ErrorLevel::Error,
+                message: Message::NestingTooDeep { max_depth },
+                loc,
+            });
+            ast = None;
+        }
+
+        let mut diagnostics = self.diagnostics.clone().take_inner();
+        if let Some(reason) = self.yylexer.cancellation.reason() {
+            // errors emitted after cancellation are caused by the unexpected end of input
+            if let Some(idx) = diagnostics.iter().position(|d| d.message == *reason) {
+                diagnostics.truncate(idx + 1);
+            }
+            ast = None;
+        }
+
         ParserResult {
-            ast: self.result,
-            tokens: self.tokens,
-            diagnostics: self.diagnostics.take_inner(),
-            comments: self.yylexer.comments,
-            magic_comments: self.yylexer.magic_comments,
-            input: self.yylexer.buffer.input.decoded,
+            ast,
+            tokens,
+            lex_states,
+            diagnostics,
+            comments,
+            magic_comments: self.yylexer.magic_comments.drain(..).collect(),
+            string_content_maps: self.yylexer.string_content_maps.clone().take_inner(),
+            input,
         }
     }
 
-    fn warn(&mut self, loc: &Loc, message: DiagnosticMessage) {
+    fn warn(&mut self, loc: &Loc, message: impl Into<Message>) {
         let diagnostic = Diagnostic {
             level: ErrorLevel::Warning,
-            message,
+            message: message.into(),
             loc: *loc,
         };
         self.diagnostics.emit(diagnostic);
     }
 
+    fn warn_unused_variables(&mut self) {
+        let unused = self.static_env.take_unused_variables();
+        if !self.verbose {
+            return;
+        }
+        for (name, loc) in unused {
+            self.warn(&loc, Message::AssignedButUnusedVariable { name });
+        }
+    }
+
     fn yylex(&mut self) -> PoolValue<Token> {
         self.yylexer.yylex()
     }
@@ -7017,6 +7217,9 @@ impl Parser {
         if self.record_tokens {
             let mut token = token.clone();
             self.tokens.push(token.take_value());
+            if self.record_lex_states {
+                self.lex_states.push(self.yylexer.lex_state);
+            }
         }
 
         token
@@ -7032,7 +7235,7 @@ impl Parser {
             self.diagnostics.emit(
                 Diagnostic {
                     level: ErrorLevel::Error,
-                    message: DiagnosticMessage::ConstArgument {},
+                    message: DiagnosticMessage::ConstArgument {}.into(),
                     loc
                 }
             );
@@ -7051,15 +7254,15 @@ impl Parser {
         }
     }
 
-    fn yyerror(&mut self, loc: &Loc, message: DiagnosticMessage) -> Result<i32, ()> {
+    fn yyerror(&mut self, loc: &Loc, message: impl Into<Message>) -> Result<i32, ()> {
         self.yyerror1(
             message,
             *loc
         )
     }
 
-    fn yyerror1(&mut self, message: DiagnosticMessage, loc: Loc) -> Result<i32, ()> {
-        let diagnostic = Diagnostic { level: ErrorLevel::Error, message, loc };
+    fn yyerror1(&mut self, message: impl Into<Message>, loc: Loc) -> Result<i32, ()> {
+        let diagnostic = Diagnostic { level: ErrorLevel::Error, message: message.into(), loc };
         self.diagnostics.emit(diagnostic);
         Err(())
     }
@@ -7070,7 +7273,7 @@ impl Parser {
             level: ErrorLevel::Error,
             message: DiagnosticMessage::UnexpectedToken {
                 token_name: Lexer::TOKEN_NAMES[id].to_string()
-            },
+            }.into(),
             loc,
         };
         self.diagnostics.emit(diagnostic);
@@ -7082,6 +7285,57 @@ impl Parser {
         }
     }
 
+    fn warn_duplicated_when_clauses(&mut self, when_bodies: &[Node]) {
+        if !self.verbose {
+            return;
+        }
+        for (loc, first_l) in Builder::duplicated_when_clauses(when_bodies) {
+            let line = match self.yylexer.buffer.input.line_col_for_pos(first_l.begin) {
+                Some((line, _)) => line + 1,
+                None => continue,
+            };
+            self.warn(&loc, Message::DuplicatedWhenClause { line });
+        }
+    }
+
+    fn token_info_enabled(&self) -> bool {
+        self.yylexer.warn_indent.unwrap_or(self.verbose)
+    }
+
+    fn token_info_push(&mut self, token: &'static str, loc: &Loc) {
+        if self.token_info_enabled() {
+            self.token_info.push(token, loc, &self.yylexer.buffer.input);
+        }
+    }
+
+    fn token_info_push_if(&mut self, loc: &Loc) {
+        if self.token_info_enabled() {
+            self.token_info.push_if(loc, &self.yylexer.buffer.input);
+        }
+    }
+
+    fn token_info_pop(&mut self, token: &str, loc: &Loc) {
+        if let Some(message) = self.token_info.pop(token, loc, &self.yylexer.buffer.input) {
+            self.warn(loc, message);
+        }
+    }
+
+    fn token_info_drop(&mut self, token: &str, loc: &Loc) {
+        self.token_info.drop(token, loc);
+    }
+
+    fn token_info_warn(&mut self, token: &str, same: bool, loc: &Loc) {
+        if let Some(message) = self.token_info.check(token, same, loc, &self.yylexer.buffer.input) {
+            self.warn(loc, message);
+        }
+    }
+
+    fn token_info_else(&mut self, loc: &Loc) {
+        if let Some(message) = self.token_info.check_else(loc, &self.yylexer.buffer.input) {
+            self.warn(loc, message);
+        }
+    }
+
     fn value_expr(&self, node: &Node) -> Result<(), ()> {
         self.builder.value_expr(node)
     }
@@ -7114,6 +7368,7 @@ impl Parser {
 
     fn local_pop(&mut self) {
         self.static_env.unextend();
+        self.warn_unused_variables();
         self.yylexer.cmdarg.pop();
         self.yylexer.cond.pop();
         self.max_numparam_stack.pop();
@@ -1,4 +1,7 @@
+use std::time::Duration;
+
 use crate::source::Decoder;
+use crate::CancellationToken;
 
 /// Configuration of the parser
 #[derive(Debug)]
@@ -45,6 +48,59 @@ pub struct ParserOptions {
     /// When set to false `ParserResult.tokens` is guaranteed to be empty.
     /// If you don't need tokens better set it to false to speed up parsing.
     pub record_tokens: bool,
+
+    /// When set to true (together with `record_tokens`) Parser also records
+    /// trivia tokens: whitespaces, ignored newlines, line continuations, comments,
+    /// `__END__` data and skipped bytes (`tWHITESPACE`, `tNEWLINE`, `tCOMMENT`, etc).
+    ///
+    /// All tokens are sorted by their locations (including heredoc bodies),
+    /// so concatenated sources of all tokens are equal to the input.
+    /// `END_OF_INPUT` token is empty, `__END__` is a part of the `tEND_DATA` token.
+    pub record_trivia: bool,
+
+    /// When set to true (together with `record_tokens`) Parser also records
+    /// the state of the lexer after every token to `ParserResult::lex_states`,
+    /// it's used by `ripper::lex`.
+    pub record_lex_states: bool,
+
+    /// When set to true Parser records mappings between contents of string literals
+    /// and the source code to `ParserResult::string_content_maps`.
+    /// When set to false they are empty, `ParserResult::string_content_map` returns `None`
+    /// and `ParserResult::literal_encoding` can't see `\u` escapes
+    /// (so strings that are UTF-8 only because of them get the source encoding).
+    pub record_string_content_maps: bool,
+
+    /// When set to true Parser emits warnings that MRI emits
+    /// only in verbose mode (i.e. with `ruby -w`), like "assigned but unused variable".
+    pub verbose: bool,
+
+    /// Token that can be used to cancel parsing from another thread.
+    ///
+    /// Cancelled parser stops reading the input, `ParserResult::ast` is `None`
+    /// and the last diagnostic is an error with `Message::Cancelled`.
+    pub cancellation_token: Option<CancellationToken>,
+
+    /// Maximum time that parsing can take (counted from the first token).
+    ///
+    /// Once exceeded parsing stops like it has been cancelled,
+    /// but the last diagnostic has `Message::TimedOut`.
+    pub timeout: Option<Duration>,
+
+    /// Maximum nesting of parentheses, brackets and braces
+    /// and maximum depth of the AST (the root node has depth 1).
+    ///
+    /// Deeper input fails with a `Message::NestingTooDeep` error and no AST,
+    /// so recursive traversal of the returned AST can't overflow the stack
+    /// (dropping of `Ast` doesn't use recursion, so it's safe without the limit).
+    pub max_depth: Option<usize>,
+
+    /// Maximum size of the input in bytes,
+    /// larger input fails with a `Message::InputTooLarge` error without parsing.
+    pub max_input_size: Option<usize>,
+
+    /// Maximum number of tokens,
+    /// parsing of the input with more tokens stops with a `Message::TooManyTokens` error.
+    pub max_tokens: Option<usize>,
 }
 
 const DEFAULT_BUFFER_NAME: &str = "(eval)";
@@ -55,6 +111,15 @@ impl Default for ParserOptions {
             buffer_name: DEFAULT_BUFFER_NAME.to_string(),
             decoder: None,
             record_tokens: true,
+            record_trivia: false,
+            record_lex_states: false,
+            record_string_content_maps: false,
+            verbose: false,
+            cancellation_token: None,
+            timeout: None,
+            max_depth: None,
+            max_input_size: None,
+            max_tokens: None,
         }
     }
 }
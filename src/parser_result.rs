@@ -1,7 +1,15 @@
+use crate::lookup::{node_at, nodes_overlapping, NodePath};
+use crate::nodes::Str;
+use crate::scope::ScopeTree;
 use crate::source::Comment;
 use crate::source::DecodedInput;
 use crate::source::MagicComment;
+use crate::source::{find_content_map, literal_encoding, LiteralEncoding, StringContentMap};
+use crate::syntax_tree::SyntaxTree;
+use crate::Ast;
 use crate::Diagnostic;
+use crate::LexState;
+use crate::Loc;
 use crate::Node;
 use crate::Token;
 
@@ -10,12 +18,18 @@ use crate::Token;
 pub struct ParserResult {
     /// Abstract Syntax Tree that was constructed from you code.
     /// Contains `None` if the code gives no AST nodes
-    pub ast: Option<Box<Node>>,
+    pub ast: Option<Ast>,
 
     /// List of tokens returned by a Lexer and consumed by a Parser.
     /// Empty unless ParserOptions::record_tokens is set to true.
+    /// Includes trivia tokens if ParserOptions::record_trivia is set to true.
     pub tokens: Vec<Token>,
 
+    /// States of the lexer after every token, aligned with `tokens`
+    /// (trivia tokens get the state of the previous token).
+    /// Empty unless ParserOptions::record_lex_states is set to true.
+    pub lex_states: Vec<LexState>,
+
     /// List of all diagnostics (errors and warnings) that have been
     /// recorded during lexing and parsing
     pub diagnostics: Vec<Diagnostic>,
@@ -26,6 +40,11 @@ pub struct ParserResult {
     /// List of magic comments extracted from the source code.
     pub magic_comments: Vec<MagicComment>,
 
+    /// List of mappings between contents of string literals and the source code,
+    /// sorted by location. Use `ParserResult::string_content_map` to get it for a `Str` node.
+    /// Empty unless ParserOptions::record_string_content_maps is set to true.
+    pub string_content_maps: Vec<StringContentMap>,
+
     /// Input that was used for parsing.
     ///
     /// Note: this input is not necessary the same byte array that
@@ -38,14 +57,70 @@ pub struct ParserResult {
     pub input: DecodedInput,
 }
 
+impl ParserResult {
+    /// Returns mapping between bytes of the `value` of given `Str` node and the source code.
+    ///
+    /// Returns `None` if the string is empty or if it's not a literal
+    /// (e.g. a character literal `?a` or a `Str` that has been constructed manually).
+    /// Requires `ParserOptions::record_string_content_maps` to be set to true.
+    pub fn string_content_map(&self, node: &Str) -> Option<&StringContentMap> {
+        find_content_map(&self.string_content_maps, node)
+    }
+
+    /// Returns encoding of the given `Str`, `Sym` or `Regexp` node
+    /// and whether its value is valid in this encoding.
+    ///
+    /// Returns `None` for all other nodes.
+    /// `\u` escapes are detected only if `ParserOptions::record_string_content_maps` is set to true.
+    pub fn literal_encoding(&self, node: &Node) -> Option<LiteralEncoding> {
+        literal_encoding(node, &self.input, &self.string_content_maps)
+    }
+
+    /// Analyzes lexical scopes of the AST and resolves all local variables
+    /// (i.e. finds a declaration of every `Lvar`).
+    pub fn scope_tree(&self) -> ScopeTree {
+        ScopeTree::new(self.ast.as_deref(), &self.input)
+    }
+
+    /// Returns a chain of nodes from the root to the deepest node that contains `offset`,
+    /// see `lookup::node_at`
+    pub fn node_at(&self, offset: usize) -> Option<NodePath<'_>> {
+        node_at(self.ast.as_deref()?, offset)
+    }
+
+    /// Returns chains of nodes from the root to all nodes that overlap `loc`,
+    /// see `lookup::nodes_overlapping`
+    pub fn nodes_overlapping(&self, loc: &Loc) -> Vec<NodePath<'_>> {
+        match self.ast.as_deref() {
+            Some(ast) => nodes_overlapping(ast, loc),
+            None => vec![],
+        }
+    }
+
+    /// Constructs a lossless syntax tree from the AST and the tokens.
+    ///
+    /// Requires `ParserOptions::record_tokens` to be set to true,
+    /// trivia tokens are computed if `ParserOptions::record_trivia` is false.
+    pub fn syntax_tree(&self) -> SyntaxTree<'_> {
+        SyntaxTree::new(
+            self.ast.as_deref(),
+            &self.tokens,
+            &self.input,
+            &self.comments,
+        )
+    }
+}
+
 impl std::fmt::Debug for ParserResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ParserResult")
             .field("ast", &self.ast)
             .field("tokens", &self.tokens)
+            .field("lex_states", &self.lex_states)
             .field("diagnostics", &self.diagnostics)
             .field("comments", &self.comments)
             .field("magic_comments", &self.magic_comments)
+            .field("string_content_maps", &self.string_content_maps)
             .finish()
     }
 }
@@ -58,13 +133,15 @@ fn test_fmt() {
             ParserResult {
                 ast: None,
                 tokens: vec![],
+                lex_states: vec![],
                 diagnostics: vec![],
                 comments: vec![],
                 magic_comments: vec![],
+                string_content_maps: vec![],
                 input: DecodedInput::default()
             }
         ),
         // All fields except `input`
-        "ParserResult { ast: None, tokens: [], diagnostics: [], comments: [], magic_comments: [] }"
+        "ParserResult { ast: None, tokens: [], lex_states: [], diagnostics: [], comments: [], magic_comments: [], string_content_maps: [] }"
     )
 }
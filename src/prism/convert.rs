@@ -0,0 +1,3625 @@
+use std::collections::HashMap;
+
+use super::{Field, PrismNode};
+use crate::builder::is_local_variable_name;
+use crate::nodes::*;
+use crate::numeric::{parse_complex, parse_float, parse_integer, parse_rational, BigInt};
+use crate::numeric::{NumericValue, RationalValue};
+use crate::scope::ScopeTree;
+use crate::source::MagicCommentKind;
+use crate::{Loc, Node, ParserResult};
+
+/// Converts the AST to the tree of Prism nodes, the root is always a `ProgramNode`.
+///
+/// Node types, fields, flags and locations follow Prism 1.x
+/// (i.e. `IfNode#subsequent`, `ConstantPathNode#name`), with a few exceptions:
+///
+/// 1. common flags (`NEWLINE`, `STATIC_LITERAL`) and flags that depend on
+///    command line options of Ruby are never set,
+/// 2. `ImplicitRestNode` is produced only for array patterns (`in a,`),
+///    `a, = b` and `|a,|` are parsed without it,
+/// 3. nodes that can't be parsed (and so have no counterpart in Prism) like
+///    standalone `Cbase` or `RegOpt` are converted to `MissingNode`,
+/// 4. `FORCED_UTF8_ENCODING` of strings with `\u` escapes is set only if
+///    `ParserOptions::record_string_content_maps` is true.
+///
+/// ```
+/// use lib_ruby_parser::{Parser, ParserOptions};
+/// use lib_ruby_parser::prism::convert;
+///
+/// let result = Parser::new(b"foo&.bar".to_vec(), ParserOptions::default()).do_parse();
+/// let program = convert(&result);
+/// let call = &program.child("statements").unwrap().children()[0];
+/// assert_eq!(call.kind, "CallNode");
+/// assert!(call.has_flag("SAFE_NAVIGATION"));
+/// ```
+pub fn convert(result: &ParserResult) -> PrismNode {
+    let scope_tree = result.scope_tree();
+    let scopes = scope_tree
+        .scopes
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(idx, scope)| ((scope.loc.begin, scope.loc.end), idx))
+        .collect();
+    let frozen_string_literal = result
+        .magic_comments
+        .iter()
+        .rev()
+        .filter(|comment| comment.kind == MagicCommentKind::FrozenStringLiteral)
+        .filter_map(|comment| {
+            let value = &result.input.as_shared_bytes()[comment.value_l.to_range()];
+            match value.to_ascii_lowercase().as_slice() {
+                b"true" => Some(true),
+                b"false" => Some(false),
+                _ => None,
+            }
+        })
+        .next();
+
+    let mut converter = Converter {
+        result,
+        scope_tree,
+        scopes,
+        current_scope: 0,
+        frozen_string_literal,
+    };
+    converter.program()
+}
+
+struct Converter<'a> {
+    result: &'a ParserResult,
+    scope_tree: ScopeTree,
+    // (begin, end) of the node that creates a scope -> index of the scope
+    scopes: HashMap<(usize, usize), usize>,
+    current_scope: usize,
+    frozen_string_literal: Option<bool>,
+}
+
+fn node(kind: &'static str, location: Loc, fields: Vec<(&'static str, Field)>) -> PrismNode {
+    PrismNode::new(kind, location, fields)
+}
+
+fn missing(location: Loc) -> PrismNode {
+    node("MissingNode", location, vec![])
+}
+
+fn required(node: PrismNode) -> Field {
+    Field::Node(Box::new(node))
+}
+
+fn optional(node: Option<PrismNode>) -> Field {
+    Field::OptionalNode(node.map(Box::new))
+}
+
+fn constant(name: &str) -> Field {
+    Field::Constant(name.to_string())
+}
+
+fn join(first: &Loc, last: &Loc) -> Loc {
+    Loc {
+        begin: first.begin.min(last.begin),
+        end: first.end.max(last.end),
+    }
+}
+
+// Location between delimiters of the literal (or the whole literal if it has no delimiters)
+fn content_loc(begin_l: &Option<Loc>, end_l: &Option<Loc>, expression_l: &Loc) -> Loc {
+    let begin = begin_l.map(|loc| loc.end).unwrap_or(expression_l.begin);
+    let end = end_l.map(|loc| loc.begin).unwrap_or(expression_l.end);
+    Loc {
+        begin,
+        end: end.max(begin),
+    }
+}
+
+// Statements of a body, multiple statements are wrapped into `Begin` without `begin_l`
+fn statement_nodes(body: Option<&Node>) -> Vec<&Node> {
+    match body {
+        None | Some(Node::EmptyElse(_)) => vec![],
+        Some(Node::Begin(Begin {
+            statements,
+            begin_l: None,
+            ..
+        })) => statements.iter().collect(),
+        Some(node) => vec![node],
+    }
+}
+
+fn is_static_string(node: &Node) -> bool {
+    match node {
+        Node::Str(_) => true,
+        Node::Dstr(Dstr { parts, .. }) => parts.iter().all(is_static_string),
+        _ => false,
+    }
+}
+
+fn concat_values(parts: &[Node]) -> Vec<u8> {
+    let mut value = vec![];
+    for part in parts.iter() {
+        if let Node::Str(str) = part {
+            value.extend_from_slice(str.value.as_raw());
+        }
+    }
+    value
+}
+
+fn integer_base(value: &str) -> &'static str {
+    let value = value.trim_start_matches(['-', '+']).as_bytes();
+    match value {
+        [b'0', b'b' | b'B', ..] => "BINARY",
+        [b'0', b'x' | b'X', ..] => "HEXADECIMAL",
+        [b'0', b'd' | b'D', ..] => "DECIMAL",
+        [b'0', b'o' | b'O' | b'_' | b'0'..=b'9', ..] => "OCTAL",
+        _ => "DECIMAL",
+    }
+}
+
+fn integer_node(value: &str, location: Loc) -> PrismNode {
+    let value_field = Field::Integer(parse_integer(value).unwrap_or_else(BigInt::zero));
+    node(
+        "IntegerNode",
+        location,
+        vec![
+            ("flags", Field::Flags(vec![integer_base(value)])),
+            ("value", value_field),
+        ],
+    )
+}
+
+fn float_node(value: &str, location: Loc) -> PrismNode {
+    let value = parse_float(value).map(|float| float.value).unwrap_or(0.0);
+    node("FloatNode", location, vec![("value", Field::Double(value))])
+}
+
+fn rational_node(value: &str, location: Loc) -> PrismNode {
+    let RationalValue {
+        numerator,
+        denominator,
+    } = parse_rational(value).unwrap_or(RationalValue {
+        numerator: BigInt::zero(),
+        denominator: BigInt::from(1),
+    });
+    node(
+        "RationalNode",
+        location,
+        vec![
+            ("flags", Field::Flags(vec![integer_base(value)])),
+            ("numerator", Field::Integer(numerator)),
+            ("denominator", Field::Integer(denominator)),
+        ],
+    )
+}
+
+fn imaginary_node(value: &str, location: Loc) -> PrismNode {
+    let numeric_value = value.strip_suffix('i').unwrap_or(value);
+    let numeric_l = location.with_end(location.end - (value.len() - numeric_value.len()));
+    let numeric = match parse_complex(value).map(|complex| *complex.imaginary) {
+        Some(NumericValue::Rational(_)) => rational_node(numeric_value, numeric_l),
+        Some(NumericValue::Float(_)) => float_node(numeric_value, numeric_l),
+        _ => integer_node(numeric_value, numeric_l),
+    };
+    node(
+        "ImaginaryNode",
+        location,
+        vec![("numeric", required(numeric))],
+    )
+}
+
+// Rows are `LocalVariable`, `InstanceVariable`, ..., columns are `&&=`, `||=` and `op=`
+const OPERATOR_WRITE_NODES: [[&str; 3]; 8] = [
+    [
+        "LocalVariableAndWriteNode",
+        "LocalVariableOrWriteNode",
+        "LocalVariableOperatorWriteNode",
+    ],
+    [
+        "InstanceVariableAndWriteNode",
+        "InstanceVariableOrWriteNode",
+        "InstanceVariableOperatorWriteNode",
+    ],
+    [
+        "GlobalVariableAndWriteNode",
+        "GlobalVariableOrWriteNode",
+        "GlobalVariableOperatorWriteNode",
+    ],
+    [
+        "ClassVariableAndWriteNode",
+        "ClassVariableOrWriteNode",
+        "ClassVariableOperatorWriteNode",
+    ],
+    [
+        "ConstantAndWriteNode",
+        "ConstantOrWriteNode",
+        "ConstantOperatorWriteNode",
+    ],
+    [
+        "ConstantPathAndWriteNode",
+        "ConstantPathOrWriteNode",
+        "ConstantPathOperatorWriteNode",
+    ],
+    [
+        "CallAndWriteNode",
+        "CallOrWriteNode",
+        "CallOperatorWriteNode",
+    ],
+    [
+        "IndexAndWriteNode",
+        "IndexOrWriteNode",
+        "IndexOperatorWriteNode",
+    ],
+];
+
+// `&&=`, `||=` or `op=` (with the operator without trailing `=`)
+enum WriteOperator<'a> {
+    And,
+    Or,
+    Binary(&'a str),
+}
+
+impl WriteOperator<'_> {
+    fn column(&self) -> usize {
+        match self {
+            Self::And => 0,
+            Self::Or => 1,
+            Self::Binary(_) => 2,
+        }
+    }
+
+    // `operator_loc` or `binary_operator_loc`, `binary_operator` goes right after it
+    // if `binary_operator_first` is `false`
+    fn fields(&self, loc: Loc, binary_operator_first: bool) -> Vec<(&'static str, Field)> {
+        match self {
+            Self::And | Self::Or => vec![("operator_loc", Field::Location(loc))],
+            Self::Binary(operator) if binary_operator_first => vec![
+                ("binary_operator", constant(operator)),
+                ("binary_operator_loc", Field::Location(loc)),
+            ],
+            Self::Binary(_) => vec![("binary_operator_loc", Field::Location(loc))],
+        }
+    }
+
+    fn binary_operator(&self) -> Option<(&'static str, Field)> {
+        match self {
+            Self::Binary(operator) => Some(("binary_operator", constant(operator))),
+            _ => None,
+        }
+    }
+}
+
+impl Converter<'_> {
+    fn source(&self, loc: &Loc) -> &[u8] {
+        self.result
+            .input
+            .as_shared_bytes()
+            .get(loc.to_range())
+            .unwrap_or_default()
+    }
+
+    // `then` and `do` locations are also used for `;` and newlines
+    fn keyword(&self, loc: &Option<Loc>, keyword: &str) -> Option<Loc> {
+        loc.filter(|loc| self.source(loc) == keyword.as_bytes())
+    }
+
+    fn program(&mut self) -> PrismNode {
+        let ast = self.result.ast.as_deref();
+        let location = ast.map(|node| *node.expression()).unwrap_or_default();
+        let statements = self.statements(ast).unwrap_or_else(|| {
+            node(
+                "StatementsNode",
+                location,
+                vec![("body", Field::Nodes(vec![]))],
+            )
+        });
+        node(
+            "ProgramNode",
+            location,
+            vec![
+                ("locals", Field::Constants(self.locals(0))),
+                ("statements", required(statements)),
+            ],
+        )
+    }
+
+    // Names of variables declared in the scope
+    fn locals(&self, scope: usize) -> Vec<String> {
+        self.scope_tree
+            .scope_variables(&self.scope_tree.scopes[scope])
+            .filter(|variable| variable.declaration.is_some())
+            .map(|variable| variable.name.clone())
+            .collect()
+    }
+
+    // Runs `f` in the scope created by the node with the given location,
+    // returns locals of the scope and the result of `f`
+    fn in_scope<T>(&mut self, loc: &Loc, f: impl FnOnce(&mut Self) -> T) -> (Vec<String>, T) {
+        let scope = match self.scopes.get(&(loc.begin, loc.end)) {
+            Some(scope) => *scope,
+            None => return (vec![], f(self)),
+        };
+        let parent = self.current_scope;
+        self.current_scope = scope;
+        let result = f(self);
+        self.current_scope = parent;
+        (self.locals(scope), result)
+    }
+
+    // Number of scopes between the current scope and the scope of the variable
+    fn depth(&self, loc: &Loc) -> u32 {
+        let variable = match self.scope_tree.variable_at(loc) {
+            Some(variable) => variable,
+            None => return 0,
+        };
+        let mut scope = self.current_scope;
+        let mut depth = 0;
+        while scope != variable.scope {
+            match self.scope_tree.scopes[scope].parent {
+                Some(parent) => scope = parent,
+                None => return 0,
+            }
+            depth += 1;
+        }
+        depth
+    }
+
+    fn local_variable(&self, kind: &'static str, name: &str, name_l: Loc) -> PrismNode {
+        node(
+            kind,
+            name_l,
+            vec![
+                ("name", constant(name)),
+                ("depth", Field::UInt32(self.depth(&name_l))),
+            ],
+        )
+    }
+
+    fn convert_opt(&mut self, node: Option<&Node>) -> Option<PrismNode> {
+        node.map(|node| self.convert(node))
+    }
+
+    fn convert_list(&mut self, nodes: &[Node]) -> Vec<PrismNode> {
+        nodes.iter().map(|node| self.convert(node)).collect()
+    }
+
+    fn statements(&mut self, body: Option<&Node>) -> Option<PrismNode> {
+        self.statements_list(&statement_nodes(body))
+    }
+
+    fn statements_list(&mut self, nodes: &[&Node]) -> Option<PrismNode> {
+        let (first, last) = (nodes.first()?, nodes.last()?);
+        let location = join(first.expression(), last.expression());
+        let body = nodes.iter().map(|node| self.convert(node)).collect();
+        Some(node(
+            "StatementsNode",
+            location,
+            vec![("body", Field::Nodes(body))],
+        ))
+    }
+
+    // Body of the method, class, module or block, can have `rescue`/`else`/`ensure` clauses
+    fn body(&mut self, body: Option<&Node>, end_l: Option<Loc>) -> Option<PrismNode> {
+        match body {
+            Some(body @ Node::Ensure(_)) => Some(self.implicit_begin(body, end_l)),
+            Some(body @ Node::Rescue(rescue)) if !self.is_rescue_modifier(rescue) => {
+                Some(self.implicit_begin(body, end_l))
+            }
+            _ => self.statements(body),
+        }
+    }
+
+    fn implicit_begin(&mut self, body: &Node, end_l: Option<Loc>) -> PrismNode {
+        let expression_l = body.expression();
+        let location = expression_l.maybe_join(&end_l);
+        self.begin_node(None, &[body], end_l, location)
+    }
+
+    // `a rescue b`
+    fn is_rescue_modifier(&self, rescue: &Rescue) -> bool {
+        match (&rescue.body, rescue.rescue_bodies.as_slice(), &rescue.else_) {
+            (Some(body), [Node::RescueBody(rescue_body)], None) => {
+                let gap = Loc {
+                    begin: body.expression().end,
+                    end: rescue_body.keyword_l.begin,
+                };
+                rescue_body.exc_list.is_none()
+                    && rescue_body.exc_var.is_none()
+                    && !self.source(&gap).iter().any(|c| *c == b'\n' || *c == b';')
+            }
+            _ => false,
+        }
+    }
+
+    // `begin ... end` or an implicit body with `rescue`/`else`/`ensure` clauses
+    fn begin_node(
+        &mut self,
+        begin_l: Option<Loc>,
+        statements: &[&Node],
+        end_l: Option<Loc>,
+        location: Loc,
+    ) -> PrismNode {
+        let mut body = statements.to_vec();
+        let mut rescue_clause = None;
+        let mut else_clause = None;
+        let mut ensure_clause = None;
+
+        if let [Node::Ensure(ensure)] = statements {
+            let ensure_end_l =
+                end_l.unwrap_or_else(|| ensure.expression_l.with_begin(ensure.expression_l.end));
+            let ensure_statements = self.statements(ensure.ensure.as_deref());
+            ensure_clause = Some(node(
+                "EnsureNode",
+                join(&ensure.keyword_l, &ensure_end_l),
+                vec![
+                    ("ensure_keyword_loc", Field::Location(ensure.keyword_l)),
+                    ("statements", optional(ensure_statements)),
+                    ("end_keyword_loc", Field::Location(ensure_end_l)),
+                ],
+            ));
+            body = statement_nodes(ensure.body.as_deref());
+        }
+
+        if let [Node::Rescue(rescue)] = body.as_slice() {
+            if !self.is_rescue_modifier(rescue) {
+                let else_end_l =
+                    ensure_clause
+                        .as_ref()
+                        .map(|ensure| match ensure.field("ensure_keyword_loc") {
+                            Some(Field::Location(loc)) => *loc,
+                            _ => ensure.location,
+                        });
+                let else_end_l = else_end_l.or(end_l);
+
+                rescue_clause = self.rescue_clauses(&rescue.rescue_bodies);
+                if let Some(else_l) = rescue.else_l {
+                    else_clause = Some(self.else_node(else_l, rescue.else_.as_deref(), else_end_l));
+                }
+                body = statement_nodes(rescue.body.as_deref());
+            }
+        }
+
+        let statements = self.statements_list(&body);
+        node(
+            "BeginNode",
+            location,
+            vec![
+                ("begin_keyword_loc", Field::OptionalLocation(begin_l)),
+                ("statements", optional(statements)),
+                ("rescue_clause", optional(rescue_clause)),
+                ("else_clause", optional(else_clause)),
+                ("ensure_clause", optional(ensure_clause)),
+                ("end_keyword_loc", Field::OptionalLocation(end_l)),
+            ],
+        )
+    }
+
+    // Chain of `RescueNode`s linked with `subsequent`
+    fn rescue_clauses(&mut self, rescue_bodies: &[Node]) -> Option<PrismNode> {
+        let (first, rest) = rescue_bodies.split_first()?;
+        let rescue_body = match first {
+            Node::RescueBody(rescue_body) => rescue_body,
+            other => return Some(self.convert(other)),
+        };
+
+        let exceptions = match rescue_body.exc_list.as_deref() {
+            Some(Node::Array(Array {
+                elements,
+                begin_l: None,
+                ..
+            })) => self.convert_list(elements),
+            Some(exception) => vec![self.convert(exception)],
+            None => vec![],
+        };
+        let reference = rescue_body.exc_var.as_deref().map(|var| self.target(var));
+        let statements = self.statements(rescue_body.body.as_deref());
+        let subsequent = self.rescue_clauses(rest);
+
+        Some(node(
+            "RescueNode",
+            rescue_body.expression_l,
+            vec![
+                ("keyword_loc", Field::Location(rescue_body.keyword_l)),
+                ("exceptions", Field::Nodes(exceptions)),
+                ("operator_loc", Field::OptionalLocation(rescue_body.assoc_l)),
+                ("reference", optional(reference)),
+                (
+                    "then_keyword_loc",
+                    Field::OptionalLocation(self.keyword(&rescue_body.begin_l, "then")),
+                ),
+                ("statements", optional(statements)),
+                ("subsequent", optional(subsequent)),
+            ],
+        ))
+    }
+
+    fn else_node(&mut self, else_l: Loc, body: Option<&Node>, end_l: Option<Loc>) -> PrismNode {
+        let statements = self.statements(body);
+        let end = end_l
+            .map(|loc| loc.end)
+            .or_else(|| body.map(|body| body.expression().end))
+            .unwrap_or(else_l.end);
+        node(
+            "ElseNode",
+            else_l.with_end(end),
+            vec![
+                ("else_keyword_loc", Field::Location(else_l)),
+                ("statements", optional(statements)),
+                ("end_keyword_loc", Field::OptionalLocation(end_l)),
+            ],
+        )
+    }
+
+    // `FORCED_*_ENCODING` flag of the `Str`, `Sym` or `Regexp` node
+    fn forced_encoding(&self, node: &Node) -> Option<&'static str> {
+        let encoding = self.result.literal_encoding(node)?;
+        let source_encoding = self.result.input.encoding.as_deref().unwrap_or("UTF-8");
+        match encoding.name.as_str() {
+            name if name.eq_ignore_ascii_case(source_encoding) => None,
+            "UTF-8" => Some("FORCED_UTF8_ENCODING"),
+            "ASCII-8BIT" => Some("FORCED_BINARY_ENCODING"),
+            "US-ASCII" => Some("FORCED_US_ASCII_ENCODING"),
+            _ => None,
+        }
+    }
+
+    // `FROZEN`/`MUTABLE` flag of the string literal.
+    //
+    // Static parts of interpolated literals are always frozen,
+    // other literals depend on the `frozen_string_literal` magic comment
+    fn frozen_flag(&self, part: bool) -> Option<&'static str> {
+        match (part, self.frozen_string_literal) {
+            (true, _) | (false, Some(true)) => Some("FROZEN"),
+            (false, Some(false)) => Some("MUTABLE"),
+            (false, None) => None,
+        }
+    }
+
+    fn string(&self, node: &Node, str: &Str, part: bool) -> PrismNode {
+        let mut flags = vec![];
+        flags.extend(
+            self.forced_encoding(node)
+                .filter(|flag| *flag != "FORCED_US_ASCII_ENCODING"),
+        );
+        flags.extend(self.frozen_flag(part));
+        node_string(
+            "StringNode",
+            flags,
+            str.begin_l,
+            content_loc(&str.begin_l, &str.end_l, &str.expression_l),
+            str.end_l,
+            str.value.as_raw().to_vec(),
+            str.expression_l,
+        )
+    }
+
+    fn symbol(&self, node: &Node, sym: &Sym) -> PrismNode {
+        let flags = self.forced_encoding(node).into_iter().collect();
+        node_symbol(
+            flags,
+            sym.begin_l,
+            content_loc(&sym.begin_l, &sym.end_l, &sym.expression_l),
+            sym.end_l,
+            sym.name.as_raw().to_vec(),
+            sym.expression_l,
+        )
+    }
+
+    // Part of the interpolated literal
+    fn part(&mut self, part: &Node) -> PrismNode {
+        match part {
+            Node::Str(str) => self.string(part, str, str.begin_l.is_none()),
+            Node::Begin(Begin {
+                statements,
+                begin_l: Some(begin_l),
+                end_l: Some(end_l),
+                expression_l,
+            }) => {
+                let statements = self.statements_list(&statements.iter().collect::<Vec<_>>());
+                node(
+                    "EmbeddedStatementsNode",
+                    *expression_l,
+                    vec![
+                        ("opening_loc", Field::Location(*begin_l)),
+                        ("statements", optional(statements)),
+                        ("closing_loc", Field::Location(*end_l)),
+                    ],
+                )
+            }
+            Node::Ivar(_) | Node::Gvar(_) | Node::Cvar(_) | Node::BackRef(_) | Node::NthRef(_) => {
+                let variable_l = part.expression();
+                let operator_l = Loc {
+                    begin: variable_l.begin.saturating_sub(1),
+                    end: variable_l.begin,
+                };
+                node(
+                    "EmbeddedVariableNode",
+                    join(&operator_l, variable_l),
+                    vec![
+                        ("operator_loc", Field::Location(operator_l)),
+                        ("variable", required(self.convert(part))),
+                    ],
+                )
+            }
+            _ => self.convert(part),
+        }
+    }
+
+    fn parts(&mut self, parts: &[Node]) -> Vec<PrismNode> {
+        parts.iter().map(|part| self.part(part)).collect()
+    }
+
+    fn dstr(&mut self, dstr: &Dstr) -> PrismNode {
+        let all_str = dstr
+            .parts
+            .iter()
+            .all(|part| matches!(part, Node::Str(Str { begin_l: None, .. })));
+        if all_str && dstr.begin_l.is_some() {
+            // a string with line continuations, like `"a\<newline>b"`
+            let flags = self.frozen_flag(false).into_iter().collect();
+            return node_string(
+                "StringNode",
+                flags,
+                dstr.begin_l,
+                content_loc(&dstr.begin_l, &dstr.end_l, &dstr.expression_l),
+                dstr.end_l,
+                concat_values(&dstr.parts),
+                dstr.expression_l,
+            );
+        }
+
+        // only adjacent literals (like `"a" "b"`) can be frozen
+        let flags = if dstr.parts.iter().all(is_static_string) {
+            self.frozen_flag(false).into_iter().collect()
+        } else {
+            vec![]
+        };
+        let parts = self.parts(&dstr.parts);
+        node(
+            "InterpolatedStringNode",
+            dstr.expression_l,
+            vec![
+                ("flags", Field::Flags(flags)),
+                ("opening_loc", Field::OptionalLocation(dstr.begin_l)),
+                ("parts", Field::Nodes(parts)),
+                ("closing_loc", Field::OptionalLocation(dstr.end_l)),
+            ],
+        )
+    }
+
+    fn heredoc(&mut self, heredoc: &Heredoc) -> PrismNode {
+        let flags = self.frozen_flag(false).into_iter().collect::<Vec<_>>();
+        if heredoc
+            .parts
+            .iter()
+            .all(|part| matches!(part, Node::Str(_)))
+        {
+            return node_string(
+                "StringNode",
+                flags,
+                Some(heredoc.expression_l),
+                heredoc.heredoc_body_l,
+                Some(heredoc.heredoc_end_l),
+                concat_values(&heredoc.parts),
+                heredoc.expression_l,
+            );
+        }
+        let parts = self.parts(&heredoc.parts);
+        node(
+            "InterpolatedStringNode",
+            heredoc.expression_l,
+            vec![
+                ("flags", Field::Flags(vec![])),
+                (
+                    "opening_loc",
+                    Field::OptionalLocation(Some(heredoc.expression_l)),
+                ),
+                ("parts", Field::Nodes(parts)),
+                (
+                    "closing_loc",
+                    Field::OptionalLocation(Some(heredoc.heredoc_end_l)),
+                ),
+            ],
+        )
+    }
+
+    fn dsym(&mut self, dsym: &Dsym) -> PrismNode {
+        if dsym.parts.iter().all(|part| matches!(part, Node::Str(_))) {
+            let value = concat_values(&dsym.parts);
+            let flags = if value.is_ascii() {
+                vec!["FORCED_US_ASCII_ENCODING"]
+            } else {
+                vec![]
+            };
+            return node_symbol(
+                flags,
+                dsym.begin_l,
+                content_loc(&dsym.begin_l, &dsym.end_l, &dsym.expression_l),
+                dsym.end_l,
+                value,
+                dsym.expression_l,
+            );
+        }
+        let parts = self.parts(&dsym.parts);
+        node(
+            "InterpolatedSymbolNode",
+            dsym.expression_l,
+            vec![
+                ("opening_loc", Field::OptionalLocation(dsym.begin_l)),
+                ("parts", Field::Nodes(parts)),
+                ("closing_loc", Field::OptionalLocation(dsym.end_l)),
+            ],
+        )
+    }
+
+    // `` `cmd` ``, `%x(cmd)` and `<<~`CMD``
+    fn xstr(
+        &mut self,
+        parts: &[Node],
+        opening_l: Loc,
+        content_l: Loc,
+        closing_l: Loc,
+        expression_l: Loc,
+    ) -> PrismNode {
+        if parts.iter().all(|part| matches!(part, Node::Str(_))) {
+            return node_string(
+                "XStringNode",
+                vec![],
+                Some(opening_l),
+                content_l,
+                Some(closing_l),
+                concat_values(parts),
+                expression_l,
+            );
+        }
+        let parts = self.parts(parts);
+        node(
+            "InterpolatedXStringNode",
+            expression_l,
+            vec![
+                ("opening_loc", Field::Location(opening_l)),
+                ("parts", Field::Nodes(parts)),
+                ("closing_loc", Field::Location(closing_l)),
+            ],
+        )
+    }
+
+    fn regexp(&mut self, node_ref: &Node, regexp: &Regexp) -> PrismNode {
+        let (options, options_l) = match regexp.options.as_deref() {
+            Some(Node::RegOpt(RegOpt {
+                options,
+                expression_l,
+            })) => (options.clone().unwrap_or_default(), Some(*expression_l)),
+            _ => (String::new(), None),
+        };
+        let closing_l = regexp.end_l.maybe_join(&options_l);
+
+        const OPTIONS: [(char, &str); 8] = [
+            ('i', "IGNORE_CASE"),
+            ('x', "EXTENDED"),
+            ('m', "MULTI_LINE"),
+            ('o', "ONCE"),
+            ('e', "EUC_JP"),
+            ('n', "ASCII_8BIT"),
+            ('s', "WINDOWS_31J"),
+            ('u', "UTF_8"),
+        ];
+        let mut flags = OPTIONS
+            .iter()
+            .filter(|(option, _)| options.contains(*option))
+            .map(|(_, flag)| *flag)
+            .collect::<Vec<_>>();
+        if !options.contains(['e', 'n', 's', 'u']) {
+            flags.extend(self.forced_encoding(node_ref));
+        }
+
+        if regexp.parts.iter().all(|part| matches!(part, Node::Str(_))) {
+            return node(
+                "RegularExpressionNode",
+                regexp.expression_l,
+                vec![
+                    ("flags", Field::Flags(flags)),
+                    ("opening_loc", Field::Location(regexp.begin_l)),
+                    (
+                        "content_loc",
+                        Field::Location(Loc {
+                            begin: regexp.begin_l.end,
+                            end: regexp.end_l.begin,
+                        }),
+                    ),
+                    ("closing_loc", Field::Location(closing_l)),
+                    ("unescaped", Field::String(concat_values(&regexp.parts))),
+                ],
+            );
+        }
+        let parts = self.parts(&regexp.parts);
+        node(
+            "InterpolatedRegularExpressionNode",
+            regexp.expression_l,
+            vec![
+                ("flags", Field::Flags(flags)),
+                ("opening_loc", Field::Location(regexp.begin_l)),
+                ("parts", Field::Nodes(parts)),
+                ("closing_loc", Field::Location(closing_l)),
+            ],
+        )
+    }
+
+    // Converts arguments of the call, `&block` argument is returned separately
+    // (Prism stores it in the `block` field)
+    fn arguments(&mut self, args: &[Node]) -> (Option<PrismNode>, Option<PrismNode>) {
+        let (args, block) = match args.split_last() {
+            Some((block @ Node::BlockPass(_), args)) => (args, Some(self.convert(block))),
+            _ => (args, None),
+        };
+        (self.arguments_node(args), block)
+    }
+
+    fn arguments_node(&mut self, args: &[Node]) -> Option<PrismNode> {
+        let location = join(args.first()?.expression(), args.last()?.expression());
+
+        let splats = args
+            .iter()
+            .filter(|arg| matches!(arg, Node::Splat(_)))
+            .count();
+        let mut flags = vec![];
+        if args.iter().any(|arg| matches!(arg, Node::ForwardedArgs(_))) {
+            flags.push("CONTAINS_FORWARDING");
+        }
+        for arg in args.iter() {
+            if let Node::Kwargs(Kwargs { pairs, .. }) = arg {
+                flags.push("CONTAINS_KEYWORDS");
+                if pairs.iter().any(|pair| matches!(pair, Node::Kwsplat(_))) {
+                    flags.push("CONTAINS_KEYWORD_SPLAT");
+                }
+            }
+        }
+        if splats > 0 {
+            flags.push("CONTAINS_SPLAT");
+        }
+        if splats > 1 {
+            flags.push("CONTAINS_MULTIPLE_SPLATS");
+        }
+
+        let arguments = self.convert_list(args);
+        Some(node(
+            "ArgumentsNode",
+            location,
+            vec![
+                ("flags", Field::Flags(flags)),
+                ("arguments", Field::Nodes(arguments)),
+            ],
+        ))
+    }
+
+    fn call_flags(
+        &self,
+        recv: Option<&Node>,
+        safe_navigation: bool,
+        attribute_write: bool,
+    ) -> Vec<&'static str> {
+        let mut flags = vec![];
+        if safe_navigation {
+            flags.push("SAFE_NAVIGATION");
+        }
+        if attribute_write {
+            flags.push("ATTRIBUTE_WRITE");
+        }
+        if matches!(recv, Some(Node::Self_(_))) {
+            flags.push("IGNORE_VISIBILITY");
+        }
+        flags
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn call(
+        &mut self,
+        recv: Option<&Node>,
+        method_name: &str,
+        args: &[Node],
+        dot_l: Option<Loc>,
+        selector_l: Option<Loc>,
+        begin_l: Option<Loc>,
+        end_l: Option<Loc>,
+        operator_l: Option<Loc>,
+        expression_l: Loc,
+        safe_navigation: bool,
+    ) -> PrismNode {
+        let mut flags = self.call_flags(recv, safe_navigation, operator_l.is_some());
+        if recv.is_none()
+            && args.is_empty()
+            && begin_l.is_none()
+            && selector_l.is_some()
+            && is_local_variable_name(method_name)
+            && !method_name.ends_with(['?', '!', '='])
+        {
+            flags.insert(0, "VARIABLE_CALL");
+        }
+        let receiver = self.convert_opt(recv);
+        let (arguments, block) = self.arguments(args);
+        node(
+            "CallNode",
+            expression_l,
+            vec![
+                ("flags", Field::Flags(flags)),
+                ("receiver", optional(receiver)),
+                ("call_operator_loc", Field::OptionalLocation(dot_l)),
+                ("name", constant(method_name)),
+                ("message_loc", Field::OptionalLocation(selector_l)),
+                ("opening_loc", Field::OptionalLocation(begin_l)),
+                ("arguments", optional(arguments)),
+                ("closing_loc", Field::OptionalLocation(end_l)),
+                ("block", optional(block)),
+            ],
+        )
+    }
+
+    fn index(&mut self, index: &Index) -> PrismNode {
+        let flags = self.call_flags(Some(&index.recv), false, false);
+        let receiver = self.convert(&index.recv);
+        let (arguments, block) = self.arguments(&index.indexes);
+        node(
+            "CallNode",
+            index.expression_l,
+            vec![
+                ("flags", Field::Flags(flags)),
+                ("receiver", optional(Some(receiver))),
+                ("call_operator_loc", Field::OptionalLocation(None)),
+                ("name", constant("[]")),
+                (
+                    "message_loc",
+                    Field::OptionalLocation(Some(join(&index.begin_l, &index.end_l))),
+                ),
+                ("opening_loc", Field::OptionalLocation(Some(index.begin_l))),
+                ("arguments", optional(arguments)),
+                ("closing_loc", Field::OptionalLocation(Some(index.end_l))),
+                ("block", optional(block)),
+            ],
+        )
+    }
+
+    fn index_asgn(&mut self, index_asgn: &IndexAsgn) -> PrismNode {
+        let flags = self.call_flags(Some(&index_asgn.recv), false, true);
+        let receiver = self.convert(&index_asgn.recv);
+        let value = match index_asgn.value.as_deref() {
+            Some(value) => value,
+            None => {
+                let (arguments, block) = self.arguments(&index_asgn.indexes);
+                return node(
+                    "IndexTargetNode",
+                    index_asgn.expression_l,
+                    vec![
+                        ("flags", Field::Flags(flags)),
+                        ("receiver", required(receiver)),
+                        ("opening_loc", Field::Location(index_asgn.begin_l)),
+                        ("arguments", optional(arguments)),
+                        ("closing_loc", Field::Location(index_asgn.end_l)),
+                        ("block", optional(block)),
+                    ],
+                );
+            }
+        };
+
+        // value of `a[b] = c` is the last argument of `[]=`
+        let mut args = index_asgn.indexes.clone();
+        let block = match args.last() {
+            Some(Node::BlockPass(_)) => args.pop(),
+            _ => None,
+        };
+        args.push(value.clone());
+        args.extend(block);
+        let (arguments, block) = self.arguments(&args);
+        node(
+            "CallNode",
+            index_asgn.expression_l,
+            vec![
+                ("flags", Field::Flags(flags)),
+                ("receiver", optional(Some(receiver))),
+                ("call_operator_loc", Field::OptionalLocation(None)),
+                ("name", constant("[]=")),
+                (
+                    "message_loc",
+                    Field::OptionalLocation(Some(join(&index_asgn.begin_l, &index_asgn.end_l))),
+                ),
+                (
+                    "opening_loc",
+                    Field::OptionalLocation(Some(index_asgn.begin_l)),
+                ),
+                ("arguments", optional(arguments)),
+                (
+                    "closing_loc",
+                    Field::OptionalLocation(Some(index_asgn.end_l)),
+                ),
+                ("block", optional(block)),
+            ],
+        )
+    }
+
+    // `foo { }`, `foo do end`, `foo { _1 }` and `-> { }`
+    #[allow(clippy::too_many_arguments)]
+    fn block(
+        &mut self,
+        call: &Node,
+        args: Option<&Node>,
+        numargs: Option<u8>,
+        body: Option<&Node>,
+        begin_l: Loc,
+        end_l: Loc,
+        expression_l: Loc,
+    ) -> PrismNode {
+        let (locals, (parameters, body)) = self.in_scope(&expression_l, |this| {
+            let parameters = match numargs {
+                Some(maximum) => Some(node(
+                    "NumberedParametersNode",
+                    join(&begin_l, &end_l),
+                    vec![("maximum", Field::UInt32(maximum.into()))],
+                )),
+                None => this.block_parameters(args),
+            };
+            (parameters, this.body(body, Some(end_l)))
+        });
+
+        if let Node::Lambda(lambda) = call {
+            return node(
+                "LambdaNode",
+                expression_l,
+                vec![
+                    ("locals", Field::Constants(locals)),
+                    ("operator_loc", Field::Location(lambda.expression_l)),
+                    ("opening_loc", Field::Location(begin_l)),
+                    ("closing_loc", Field::Location(end_l)),
+                    ("parameters", optional(parameters)),
+                    ("body", optional(body)),
+                ],
+            );
+        }
+
+        let block = node(
+            "BlockNode",
+            join(&begin_l, &end_l),
+            vec![
+                ("locals", Field::Constants(locals)),
+                ("parameters", optional(parameters)),
+                ("body", optional(body)),
+                ("opening_loc", Field::Location(begin_l)),
+                ("closing_loc", Field::Location(end_l)),
+            ],
+        );
+        let mut call = self.convert(call);
+        call.location = expression_l;
+        if let Some(Field::Flags(flags)) = call.field_mut("flags") {
+            flags.retain(|flag| *flag != "VARIABLE_CALL");
+        }
+        if let Some(field) = call.field_mut("block") {
+            *field = optional(Some(block));
+        }
+        call
+    }
+
+    fn block_parameters(&mut self, args: Option<&Node>) -> Option<PrismNode> {
+        let args = match args {
+            Some(Node::Args(args)) => args,
+            Some(other) => return Some(self.parameter(other)),
+            None => return None,
+        };
+        let (shadowargs, params): (Vec<&Node>, Vec<&Node>) = args
+            .args
+            .iter()
+            .partition(|arg| matches!(arg, Node::Shadowarg(_)));
+
+        let parameters = self.parameters(&params);
+        let locals = shadowargs
+            .into_iter()
+            .map(|arg| self.parameter(arg))
+            .collect();
+        let location = match (args.begin_l, args.end_l, parameters.as_ref()) {
+            (Some(begin_l), Some(end_l), _) => join(&begin_l, &end_l),
+            (_, _, Some(parameters)) => parameters.location,
+            _ => args.expression_l,
+        };
+        Some(node(
+            "BlockParametersNode",
+            location,
+            vec![
+                ("parameters", optional(parameters)),
+                ("locals", Field::Nodes(locals)),
+                ("opening_loc", Field::OptionalLocation(args.begin_l)),
+                ("closing_loc", Field::OptionalLocation(args.end_l)),
+            ],
+        ))
+    }
+
+    fn def_parameters(&mut self, args: Option<&Node>) -> Option<PrismNode> {
+        match args {
+            Some(Node::Args(args)) => self.parameters(&args.args.iter().collect::<Vec<_>>()),
+            Some(other) => Some(self.parameter(other)),
+            None => None,
+        }
+    }
+
+    fn parameters(&mut self, args: &[&Node]) -> Option<PrismNode> {
+        let location = join(args.first()?.expression(), args.last()?.expression());
+
+        let mut requireds = vec![];
+        let mut optionals = vec![];
+        let mut rest = None;
+        let mut posts = vec![];
+        let mut keywords = vec![];
+        let mut keyword_rest = None;
+        let mut block = None;
+
+        for arg in args.iter() {
+            let parameter = self.parameter(arg);
+            match arg {
+                Node::Optarg(_) => optionals.push(parameter),
+                Node::Restarg(_) => rest = Some(parameter),
+                Node::Kwarg(_) | Node::Kwoptarg(_) => keywords.push(parameter),
+                Node::Kwrestarg(_) | Node::Kwnilarg(_) | Node::ForwardArg(_) => {
+                    keyword_rest = Some(parameter)
+                }
+                Node::Blockarg(_) => block = Some(parameter),
+                _ if rest.is_some() || !optionals.is_empty() => posts.push(parameter),
+                _ => requireds.push(parameter),
+            }
+        }
+
+        Some(node(
+            "ParametersNode",
+            location,
+            vec![
+                ("requireds", Field::Nodes(requireds)),
+                ("optionals", Field::Nodes(optionals)),
+                ("rest", optional(rest)),
+                ("posts", Field::Nodes(posts)),
+                ("keywords", Field::Nodes(keywords)),
+                ("keyword_rest", optional(keyword_rest)),
+                ("block", optional(block)),
+            ],
+        ))
+    }
+
+    fn parameter(&mut self, arg: &Node) -> PrismNode {
+        let flags = || ("flags", Field::Flags(vec![]));
+        match arg {
+            Node::Arg(Arg { name, expression_l }) => node(
+                "RequiredParameterNode",
+                *expression_l,
+                vec![flags(), ("name", constant(name))],
+            ),
+            Node::Procarg0(Procarg0 {
+                args,
+                begin_l: None,
+                ..
+            }) if args.len() == 1 => self.parameter(&args[0]),
+            Node::Procarg0(Procarg0 {
+                args,
+                begin_l,
+                end_l,
+                expression_l,
+            }) => self.multi_target(args, *begin_l, *end_l, *expression_l, true),
+            Node::Mlhs(Mlhs {
+                items,
+                begin_l,
+                end_l,
+                expression_l,
+            }) => self.multi_target(items, *begin_l, *end_l, *expression_l, true),
+            Node::Optarg(Optarg {
+                name,
+                default,
+                name_l,
+                operator_l,
+                expression_l,
+            }) => {
+                let value = self.convert(default);
+                node(
+                    "OptionalParameterNode",
+                    *expression_l,
+                    vec![
+                        flags(),
+                        ("name", constant(name)),
+                        ("name_loc", Field::Location(*name_l)),
+                        ("operator_loc", Field::Location(*operator_l)),
+                        ("value", required(value)),
+                    ],
+                )
+            }
+            Node::Restarg(Restarg {
+                name,
+                operator_l,
+                name_l,
+                expression_l,
+            }) => named_rest_parameter("RestParameterNode", name, name_l, operator_l, expression_l),
+            Node::Kwarg(Kwarg {
+                name,
+                name_l,
+                expression_l,
+            }) => node(
+                "RequiredKeywordParameterNode",
+                *expression_l,
+                vec![
+                    flags(),
+                    ("name", constant(name)),
+                    (
+                        "name_loc",
+                        Field::Location(name_l.with_end(expression_l.end)),
+                    ),
+                ],
+            ),
+            Node::Kwoptarg(Kwoptarg {
+                name,
+                default,
+                name_l,
+                expression_l,
+            }) => {
+                let value = self.convert(default);
+                node(
+                    "OptionalKeywordParameterNode",
+                    *expression_l,
+                    vec![
+                        flags(),
+                        ("name", constant(name)),
+                        ("name_loc", Field::Location(name_l.with_end(name_l.end + 1))),
+                        ("value", required(value)),
+                    ],
+                )
+            }
+            Node::Kwrestarg(Kwrestarg {
+                name,
+                operator_l,
+                name_l,
+                expression_l,
+            }) => named_rest_parameter(
+                "KeywordRestParameterNode",
+                name,
+                name_l,
+                operator_l,
+                expression_l,
+            ),
+            Node::Kwnilarg(Kwnilarg {
+                name_l,
+                expression_l,
+            }) => no_keywords(*name_l, *expression_l),
+            Node::ForwardArg(ForwardArg { expression_l }) => {
+                node("ForwardingParameterNode", *expression_l, vec![])
+            }
+            Node::Blockarg(Blockarg {
+                name,
+                operator_l,
+                name_l,
+                expression_l,
+            }) => {
+                named_rest_parameter("BlockParameterNode", name, name_l, operator_l, expression_l)
+            }
+            Node::Shadowarg(Shadowarg { name, expression_l }) => node(
+                "BlockLocalVariableNode",
+                *expression_l,
+                vec![flags(), ("name", constant(name))],
+            ),
+            other => self.convert(other),
+        }
+    }
+
+    // `(a, *b, c)` in parameters (when `parameters` is `true`)
+    // or on the left side of `=`, `in` of `for` and `rescue =>`
+    fn multi_target(
+        &mut self,
+        items: &[Node],
+        lparen_l: Option<Loc>,
+        rparen_l: Option<Loc>,
+        location: Loc,
+        parameters: bool,
+    ) -> PrismNode {
+        let fields = self.multi_target_fields(items, lparen_l, rparen_l, parameters);
+        node("MultiTargetNode", location, fields)
+    }
+
+    fn multi_target_fields(
+        &mut self,
+        items: &[Node],
+        lparen_l: Option<Loc>,
+        rparen_l: Option<Loc>,
+        parameters: bool,
+    ) -> Vec<(&'static str, Field)> {
+        let mut lefts = vec![];
+        let mut rest = None;
+        let mut rights = vec![];
+        for item in items.iter() {
+            match item {
+                Node::Restarg(Restarg {
+                    name,
+                    operator_l,
+                    name_l,
+                    expression_l,
+                }) if parameters => {
+                    let expression = match (name, name_l) {
+                        (Some(name), Some(name_l)) => Some(node(
+                            "RequiredParameterNode",
+                            *name_l,
+                            vec![("flags", Field::Flags(vec![])), ("name", constant(name))],
+                        )),
+                        _ => None,
+                    };
+                    rest = Some(splat(*operator_l, expression, *expression_l));
+                }
+                Node::Splat(Splat {
+                    value,
+                    operator_l,
+                    expression_l,
+                }) if !parameters => {
+                    let expression = value.as_deref().map(|value| self.target(value));
+                    rest = Some(splat(*operator_l, expression, *expression_l));
+                }
+                _ => {
+                    let target = if parameters {
+                        self.parameter(item)
+                    } else {
+                        self.target(item)
+                    };
+                    if rest.is_some() {
+                        rights.push(target)
+                    } else {
+                        lefts.push(target)
+                    }
+                }
+            }
+        }
+        vec![
+            ("lefts", Field::Nodes(lefts)),
+            ("rest", optional(rest)),
+            ("rights", Field::Nodes(rights)),
+            ("lparen_loc", Field::OptionalLocation(lparen_l)),
+            ("rparen_loc", Field::OptionalLocation(rparen_l)),
+        ]
+    }
+
+    // Target of the multiple assignment, `for` loop or `rescue => target`
+    fn target(&mut self, node: &Node) -> PrismNode {
+        match node {
+            Node::Mlhs(Mlhs {
+                items,
+                begin_l,
+                end_l,
+                expression_l,
+            }) => self.multi_target(items, *begin_l, *end_l, *expression_l, false),
+            Node::Send(Send {
+                recv: Some(recv),
+                method_name,
+                dot_l,
+                selector_l,
+                expression_l,
+                ..
+            }) => self.call_target(recv, method_name, *dot_l, *selector_l, *expression_l, false),
+            Node::CSend(CSend {
+                recv,
+                method_name,
+                dot_l,
+                selector_l,
+                expression_l,
+                ..
+            }) => self.call_target(
+                recv,
+                method_name,
+                Some(*dot_l),
+                *selector_l,
+                *expression_l,
+                true,
+            ),
+            other => self.convert(other),
+        }
+    }
+
+    fn call_target(
+        &mut self,
+        recv: &Node,
+        method_name: &str,
+        dot_l: Option<Loc>,
+        selector_l: Option<Loc>,
+        expression_l: Loc,
+        safe_navigation: bool,
+    ) -> PrismNode {
+        let flags = self.call_flags(Some(recv), safe_navigation, true);
+        let receiver = self.convert(recv);
+        node(
+            "CallTargetNode",
+            expression_l,
+            vec![
+                ("flags", Field::Flags(flags)),
+                ("receiver", required(receiver)),
+                (
+                    "call_operator_loc",
+                    Field::Location(dot_l.unwrap_or(expression_l)),
+                ),
+                ("name", constant(method_name)),
+                (
+                    "message_loc",
+                    Field::Location(selector_l.unwrap_or(expression_l)),
+                ),
+            ],
+        )
+    }
+
+    // `a.b`, `a::B` and `::A` constants, `Cbase` is not a parent
+    fn constant_path(
+        &mut self,
+        kind: &'static str,
+        scope: Option<&Node>,
+        name: &str,
+        double_colon_l: Option<Loc>,
+        name_l: Loc,
+        location: Loc,
+    ) -> PrismNode {
+        let parent = match scope {
+            None | Some(Node::Cbase(_)) => None,
+            Some(scope) => Some(self.convert(scope)),
+        };
+        node(
+            kind,
+            location,
+            vec![
+                ("parent", optional(parent)),
+                ("name", constant(name)),
+                (
+                    "delimiter_loc",
+                    Field::Location(double_colon_l.unwrap_or(name_l)),
+                ),
+                ("name_loc", Field::Location(name_l)),
+            ],
+        )
+    }
+
+    fn casgn(&mut self, casgn: &Casgn) -> PrismNode {
+        let is_path = casgn.scope.is_some() || casgn.double_colon_l.is_some();
+        let path_l = casgn.expression_l.with_end(casgn.name_l.end);
+        let value = match casgn.value.as_deref() {
+            Some(value) => self.convert(value),
+            None if is_path => {
+                return self.constant_path(
+                    "ConstantPathTargetNode",
+                    casgn.scope.as_deref(),
+                    &casgn.name,
+                    casgn.double_colon_l,
+                    casgn.name_l,
+                    casgn.expression_l,
+                )
+            }
+            None => {
+                return node(
+                    "ConstantTargetNode",
+                    casgn.expression_l,
+                    vec![("name", constant(&casgn.name))],
+                )
+            }
+        };
+        let operator_l = casgn.operator_l.unwrap_or(casgn.name_l);
+        if is_path {
+            let target = self.constant_path(
+                "ConstantPathNode",
+                casgn.scope.as_deref(),
+                &casgn.name,
+                casgn.double_colon_l,
+                casgn.name_l,
+                path_l,
+            );
+            node(
+                "ConstantPathWriteNode",
+                casgn.expression_l,
+                vec![
+                    ("target", required(target)),
+                    ("operator_loc", Field::Location(operator_l)),
+                    ("value", required(value)),
+                ],
+            )
+        } else {
+            node(
+                "ConstantWriteNode",
+                casgn.expression_l,
+                vec![
+                    ("name", constant(&casgn.name)),
+                    ("name_loc", Field::Location(casgn.name_l)),
+                    ("value", required(value)),
+                    ("operator_loc", Field::Location(operator_l)),
+                ],
+            )
+        }
+    }
+
+    // `@a = 1`, `$a = 1`, `@@a = 1` and targets of multiple assignment
+    fn variable_write(
+        &mut self,
+        prefix: &'static str,
+        name: &str,
+        value: Option<&Node>,
+        name_l: Loc,
+        operator_l: Option<Loc>,
+        expression_l: Loc,
+    ) -> PrismNode {
+        let (write_kind, target_kind) = match prefix {
+            "InstanceVariable" => ("InstanceVariableWriteNode", "InstanceVariableTargetNode"),
+            "GlobalVariable" => ("GlobalVariableWriteNode", "GlobalVariableTargetNode"),
+            _ => ("ClassVariableWriteNode", "ClassVariableTargetNode"),
+        };
+        match value {
+            Some(value) => {
+                let value = self.convert(value);
+                node(
+                    write_kind,
+                    expression_l,
+                    vec![
+                        ("name", constant(name)),
+                        ("name_loc", Field::Location(name_l)),
+                        ("value", required(value)),
+                        (
+                            "operator_loc",
+                            Field::Location(operator_l.unwrap_or(name_l)),
+                        ),
+                    ],
+                )
+            }
+            None => node(target_kind, expression_l, vec![("name", constant(name))]),
+        }
+    }
+
+    fn lvasgn(&mut self, lvasgn: &Lvasgn) -> PrismNode {
+        let value = match lvasgn.value.as_deref() {
+            Some(value) => self.convert(value),
+            None => {
+                return self.local_variable("LocalVariableTargetNode", &lvasgn.name, lvasgn.name_l)
+            }
+        };
+        node(
+            "LocalVariableWriteNode",
+            lvasgn.expression_l,
+            vec![
+                ("name", constant(&lvasgn.name)),
+                ("depth", Field::UInt32(self.depth(&lvasgn.name_l))),
+                ("name_loc", Field::Location(lvasgn.name_l)),
+                ("value", required(value)),
+                (
+                    "operator_loc",
+                    Field::Location(lvasgn.operator_l.unwrap_or(lvasgn.name_l)),
+                ),
+            ],
+        )
+    }
+
+    // `a &&= b`, `a ||= b` and `a += b` for all kinds of `a`
+    fn operator_write(
+        &mut self,
+        recv: &Node,
+        operator: WriteOperator,
+        value: &Node,
+        operator_l: Loc,
+        expression_l: Loc,
+    ) -> PrismNode {
+        let column = operator.column();
+        let value = required(self.convert(value));
+
+        match recv {
+            Node::Lvasgn(Lvasgn { name, name_l, .. }) => {
+                let mut fields = vec![("name_loc", Field::Location(*name_l))];
+                fields.extend(operator.fields(operator_l, false));
+                fields.push(("value", value));
+                fields.push(("name", constant(name)));
+                fields.extend(operator.binary_operator());
+                fields.push(("depth", Field::UInt32(self.depth(name_l))));
+                node(OPERATOR_WRITE_NODES[0][column], expression_l, fields)
+            }
+            Node::Ivasgn(Ivasgn { name, name_l, .. })
+            | Node::Gvasgn(Gvasgn { name, name_l, .. })
+            | Node::Cvasgn(Cvasgn { name, name_l, .. })
+            | Node::Casgn(Casgn {
+                name,
+                name_l,
+                scope: None,
+                double_colon_l: None,
+                ..
+            }) => {
+                let row = match recv {
+                    Node::Ivasgn(_) => 1,
+                    Node::Gvasgn(_) => 2,
+                    Node::Cvasgn(_) => 3,
+                    _ => 4,
+                };
+                let mut fields = vec![
+                    ("name", constant(name)),
+                    ("name_loc", Field::Location(*name_l)),
+                ];
+                fields.extend(operator.fields(operator_l, false));
+                fields.push(("value", value));
+                fields.extend(operator.binary_operator());
+                node(OPERATOR_WRITE_NODES[row][column], expression_l, fields)
+            }
+            Node::Casgn(casgn) => {
+                let target = self.constant_path(
+                    "ConstantPathNode",
+                    casgn.scope.as_deref(),
+                    &casgn.name,
+                    casgn.double_colon_l,
+                    casgn.name_l,
+                    casgn.expression_l,
+                );
+                let mut fields = vec![("target", required(target))];
+                fields.extend(operator.fields(operator_l, false));
+                fields.push(("value", value));
+                fields.extend(operator.binary_operator());
+                node(OPERATOR_WRITE_NODES[5][column], expression_l, fields)
+            }
+            Node::Send(Send {
+                recv: Some(call_recv),
+                method_name,
+                selector_l,
+                ..
+            })
+            | Node::CSend(CSend {
+                recv: call_recv,
+                method_name,
+                selector_l,
+                ..
+            }) => {
+                let (safe_navigation, dot_l) = match recv {
+                    Node::CSend(csend) => (true, Some(csend.dot_l)),
+                    Node::Send(send) => (false, send.dot_l),
+                    _ => (false, None),
+                };
+                let flags = self.call_flags(Some(call_recv), safe_navigation, false);
+                let receiver = self.convert(call_recv);
+                let mut fields = vec![
+                    ("flags", Field::Flags(flags)),
+                    ("receiver", optional(Some(receiver))),
+                    ("call_operator_loc", Field::OptionalLocation(dot_l)),
+                    ("message_loc", Field::OptionalLocation(*selector_l)),
+                    ("read_name", constant(method_name)),
+                    ("write_name", constant(&format!("{}=", method_name))),
+                ];
+                fields.extend(operator.fields(operator_l, true));
+                fields.push(("value", value));
+                node(OPERATOR_WRITE_NODES[6][column], expression_l, fields)
+            }
+            Node::Index(Index {
+                recv: index_recv,
+                indexes,
+                begin_l,
+                end_l,
+                ..
+            }) => {
+                let flags = self.call_flags(Some(index_recv), false, false);
+                let receiver = self.convert(index_recv);
+                let (arguments, block) = self.arguments(indexes);
+                let mut fields = vec![
+                    ("flags", Field::Flags(flags)),
+                    ("receiver", optional(Some(receiver))),
+                    ("call_operator_loc", Field::OptionalLocation(None)),
+                    ("opening_loc", Field::Location(*begin_l)),
+                    ("arguments", optional(arguments)),
+                    ("closing_loc", Field::Location(*end_l)),
+                    ("block", optional(block)),
+                ];
+                fields.extend(operator.fields(operator_l, true));
+                fields.push(("value", value));
+                node(OPERATOR_WRITE_NODES[7][column], expression_l, fields)
+            }
+            _ => missing(expression_l),
+        }
+    }
+
+    fn masgn(&mut self, masgn: &Masgn) -> PrismNode {
+        let mut fields = match &*masgn.lhs {
+            Node::Mlhs(Mlhs {
+                items,
+                begin_l,
+                end_l,
+                ..
+            }) => self.multi_target_fields(items, *begin_l, *end_l, false),
+            other => self.multi_target_fields(std::slice::from_ref(other), None, None, false),
+        };
+        let value = self.convert(&masgn.rhs);
+        fields.push(("operator_loc", Field::Location(masgn.operator_l)));
+        fields.push(("value", required(value)));
+        node("MultiWriteNode", masgn.expression_l, fields)
+    }
+
+    fn assoc(&mut self, pair: &Pair) -> PrismNode {
+        let mut key = self.convert(&pair.key);
+        let value = if pair.value.expression().begin < pair.operator_l.end {
+            // `{ a: }` shorthand, the value is the key itself
+            let value = self.convert(&pair.value);
+            node(
+                "ImplicitNode",
+                key.location,
+                vec![("value", required(value))],
+            )
+        } else {
+            self.convert(&pair.value)
+        };
+
+        let operator_l = if self.source(&pair.operator_l) == b"=>" {
+            Some(pair.operator_l)
+        } else {
+            // colon of `a: 1` and `"a": 1` is a part of the key
+            if let Some(Field::OptionalLocation(closing_l)) = key.field_mut("closing_loc") {
+                let begin = closing_l
+                    .map(|loc| loc.begin)
+                    .unwrap_or(pair.operator_l.begin);
+                *closing_l = Some(pair.operator_l.with_begin(begin));
+            }
+            key.location = join(&key.location, &pair.operator_l);
+            None
+        };
+
+        node(
+            "AssocNode",
+            pair.expression_l,
+            vec![
+                ("key", required(key)),
+                ("value", required(value)),
+                ("operator_loc", Field::OptionalLocation(operator_l)),
+            ],
+        )
+    }
+
+    fn if_node(&mut self, node_if: &If, outer_end_l: Option<Loc>) -> PrismNode {
+        let end_l = node_if.end_l.or(outer_end_l);
+        let predicate = self.convert(&node_if.cond);
+        let then_l = self.keyword(&Some(node_if.begin_l), "then");
+
+        if self.source(&node_if.keyword_l) == b"unless" {
+            let statements = self.statements(node_if.if_false.as_deref());
+            let else_clause = node_if
+                .else_l
+                .map(|else_l| self.else_node(else_l, node_if.if_true.as_deref(), end_l));
+            return node(
+                "UnlessNode",
+                node_if.expression_l,
+                vec![
+                    ("keyword_loc", Field::Location(node_if.keyword_l)),
+                    ("predicate", required(predicate)),
+                    ("then_keyword_loc", Field::OptionalLocation(then_l)),
+                    ("statements", optional(statements)),
+                    ("else_clause", optional(else_clause)),
+                    ("end_keyword_loc", Field::OptionalLocation(node_if.end_l)),
+                ],
+            );
+        }
+
+        let statements = self.statements(node_if.if_true.as_deref());
+        let subsequent = match (node_if.if_false.as_deref(), node_if.else_l) {
+            (Some(Node::If(elsif)), _) if self.source(&elsif.keyword_l) == b"elsif" => {
+                Some(self.if_node(elsif, end_l))
+            }
+            (body, Some(else_l)) => Some(self.else_node(else_l, body, end_l)),
+            (_, None) => None,
+        };
+        node(
+            "IfNode",
+            node_if.expression_l,
+            vec![
+                (
+                    "if_keyword_loc",
+                    Field::OptionalLocation(Some(node_if.keyword_l)),
+                ),
+                ("predicate", required(predicate)),
+                ("then_keyword_loc", Field::OptionalLocation(then_l)),
+                ("statements", optional(statements)),
+                ("subsequent", optional(subsequent)),
+                ("end_keyword_loc", Field::OptionalLocation(end_l)),
+            ],
+        )
+    }
+
+    // `a if b`, `a unless b` and guards of `in` clauses, `body` is `None` for `if ... end` without body
+    fn modifier_if(
+        &mut self,
+        unless: bool,
+        cond: &Node,
+        body: Option<&Node>,
+        keyword_l: Loc,
+        location: Loc,
+    ) -> PrismNode {
+        let predicate = self.convert(cond);
+        let statements = self.statements(body);
+        let (kind, keyword_field, subsequent_field) = if unless {
+            ("UnlessNode", "keyword_loc", "else_clause")
+        } else {
+            ("IfNode", "if_keyword_loc", "subsequent")
+        };
+        let keyword = if unless {
+            Field::Location(keyword_l)
+        } else {
+            Field::OptionalLocation(Some(keyword_l))
+        };
+        node(
+            kind,
+            location,
+            vec![
+                (keyword_field, keyword),
+                ("predicate", required(predicate)),
+                ("then_keyword_loc", Field::OptionalLocation(None)),
+                ("statements", optional(statements)),
+                (subsequent_field, optional(None)),
+                ("end_keyword_loc", Field::OptionalLocation(None)),
+            ],
+        )
+    }
+
+    fn ternary(&mut self, ternary: &IfTernary) -> PrismNode {
+        let predicate = self.convert(&ternary.cond);
+        let statements = self.statements(Some(&ternary.if_true));
+        let else_clause = self.else_node(ternary.colon_l, Some(&ternary.if_false), None);
+        node(
+            "IfNode",
+            ternary.expression_l,
+            vec![
+                ("if_keyword_loc", Field::OptionalLocation(None)),
+                ("predicate", required(predicate)),
+                (
+                    "then_keyword_loc",
+                    Field::OptionalLocation(Some(ternary.question_l)),
+                ),
+                ("statements", optional(statements)),
+                ("subsequent", optional(Some(else_clause))),
+                ("end_keyword_loc", Field::OptionalLocation(None)),
+            ],
+        )
+    }
+
+    // `while`/`until` loops, `post` is `true` for `begin ... end while cond`
+    #[allow(clippy::too_many_arguments)]
+    fn loop_node(
+        &mut self,
+        kind: &'static str,
+        cond: &Node,
+        body: Option<&Node>,
+        keyword_l: Loc,
+        begin_l: Option<Loc>,
+        end_l: Option<Loc>,
+        expression_l: Loc,
+        post: bool,
+    ) -> PrismNode {
+        let flags = if post { vec!["BEGIN_MODIFIER"] } else { vec![] };
+        let predicate = self.convert(cond);
+        let statements = self.statements(body);
+        node(
+            kind,
+            expression_l,
+            vec![
+                ("flags", Field::Flags(flags)),
+                ("keyword_loc", Field::Location(keyword_l)),
+                (
+                    "do_keyword_loc",
+                    Field::OptionalLocation(self.keyword(&begin_l, "do")),
+                ),
+                ("closing_loc", Field::OptionalLocation(end_l)),
+                ("predicate", required(predicate)),
+                ("statements", optional(statements)),
+            ],
+        )
+    }
+
+    fn case(&mut self, case: &Case) -> PrismNode {
+        let predicate = self.convert_opt(case.expr.as_deref());
+        let conditions = case
+            .when_bodies
+            .iter()
+            .map(|when| self.convert(when))
+            .collect();
+        let else_clause = case
+            .else_l
+            .map(|else_l| self.else_node(else_l, case.else_body.as_deref(), Some(case.end_l)));
+        node(
+            "CaseNode",
+            case.expression_l,
+            vec![
+                ("predicate", optional(predicate)),
+                ("conditions", Field::Nodes(conditions)),
+                ("else_clause", optional(else_clause)),
+                ("case_keyword_loc", Field::Location(case.keyword_l)),
+                ("end_keyword_loc", Field::Location(case.end_l)),
+            ],
+        )
+    }
+
+    fn when(&mut self, when: &When) -> PrismNode {
+        let conditions = self.convert_list(&when.patterns);
+        let statements = self.statements(when.body.as_deref());
+        node(
+            "WhenNode",
+            when.expression_l,
+            vec![
+                ("keyword_loc", Field::Location(when.keyword_l)),
+                ("conditions", Field::Nodes(conditions)),
+                (
+                    "then_keyword_loc",
+                    Field::OptionalLocation(self.keyword(&Some(when.begin_l), "then")),
+                ),
+                ("statements", optional(statements)),
+            ],
+        )
+    }
+
+    fn case_match(&mut self, case_match: &CaseMatch) -> PrismNode {
+        let predicate = self.convert(&case_match.expr);
+        let conditions = case_match
+            .in_bodies
+            .iter()
+            .map(|in_body| self.convert(in_body))
+            .collect();
+        let else_clause = case_match.else_l.map(|else_l| {
+            self.else_node(
+                else_l,
+                case_match.else_body.as_deref(),
+                Some(case_match.end_l),
+            )
+        });
+        node(
+            "CaseMatchNode",
+            case_match.expression_l,
+            vec![
+                ("predicate", optional(Some(predicate))),
+                ("conditions", Field::Nodes(conditions)),
+                ("else_clause", optional(else_clause)),
+                ("case_keyword_loc", Field::Location(case_match.keyword_l)),
+                ("end_keyword_loc", Field::Location(case_match.end_l)),
+            ],
+        )
+    }
+
+    fn in_pattern(&mut self, in_pattern: &InPattern) -> PrismNode {
+        let pattern = match in_pattern.guard.as_deref() {
+            // guard wraps the pattern into `if`/`unless` modifier
+            Some(Node::IfGuard(IfGuard {
+                cond,
+                keyword_l,
+                expression_l,
+            }))
+            | Some(Node::UnlessGuard(UnlessGuard {
+                cond,
+                keyword_l,
+                expression_l,
+            })) => {
+                let unless = matches!(in_pattern.guard.as_deref(), Some(Node::UnlessGuard(_)));
+                let location = join(in_pattern.pattern.expression(), expression_l);
+                self.modifier_if(
+                    unless,
+                    cond,
+                    Some(&in_pattern.pattern),
+                    *keyword_l,
+                    location,
+                )
+            }
+            _ => self.convert(&in_pattern.pattern),
+        };
+        let statements = self.statements(in_pattern.body.as_deref());
+        node(
+            "InNode",
+            in_pattern.expression_l,
+            vec![
+                ("pattern", required(pattern)),
+                ("statements", optional(statements)),
+                ("in_loc", Field::Location(in_pattern.keyword_l)),
+                (
+                    "then_loc",
+                    Field::OptionalLocation(self.keyword(&Some(in_pattern.begin_l), "then")),
+                ),
+            ],
+        )
+    }
+
+    // `[a, *b, c]` and `[a, b, ]` patterns
+    fn array_pattern(
+        &mut self,
+        elements: &[Node],
+        begin_l: Option<Loc>,
+        end_l: Option<Loc>,
+        expression_l: Loc,
+        with_tail: bool,
+    ) -> PrismNode {
+        let mut requireds = vec![];
+        let mut rest = None;
+        let mut posts = vec![];
+        for element in elements.iter() {
+            let pattern = self.convert(element);
+            if matches!(element, Node::MatchRest(_)) {
+                rest = Some(pattern);
+            } else if rest.is_some() {
+                posts.push(pattern);
+            } else {
+                requireds.push(pattern);
+            }
+        }
+        if with_tail && rest.is_none() {
+            // `in a,` has an implicit rest at the trailing comma
+            let last_end = elements
+                .last()
+                .map(|element| element.expression().end)
+                .unwrap_or(expression_l.begin);
+            let comma = self
+                .source(&expression_l.with_begin(last_end))
+                .iter()
+                .position(|c| *c == b',');
+            let comma_l = match comma {
+                Some(offset) => Loc {
+                    begin: last_end + offset,
+                    end: last_end + offset + 1,
+                },
+                None => expression_l.with_begin(expression_l.end),
+            };
+            rest = Some(node("ImplicitRestNode", comma_l, vec![]));
+        }
+        node(
+            "ArrayPatternNode",
+            expression_l,
+            vec![
+                ("constant", optional(None)),
+                ("requireds", Field::Nodes(requireds)),
+                ("rest", optional(rest)),
+                ("posts", Field::Nodes(posts)),
+                ("opening_loc", Field::OptionalLocation(begin_l)),
+                ("closing_loc", Field::OptionalLocation(end_l)),
+            ],
+        )
+    }
+
+    // `[*, a, *]` pattern
+    fn find_pattern(&mut self, find_pattern: &FindPattern) -> PrismNode {
+        // elements are `*left, requireds..., *right`
+        let mut elements = self.convert_list(&find_pattern.elements);
+        let location = find_pattern.expression_l;
+        let right = elements.pop().unwrap_or_else(|| missing(location));
+        let left = if elements.is_empty() {
+            missing(location)
+        } else {
+            elements.remove(0)
+        };
+        node(
+            "FindPatternNode",
+            find_pattern.expression_l,
+            vec![
+                ("constant", optional(None)),
+                ("left", required(left)),
+                ("requireds", Field::Nodes(elements)),
+                ("right", required(right)),
+                ("opening_loc", Field::OptionalLocation(find_pattern.begin_l)),
+                ("closing_loc", Field::OptionalLocation(find_pattern.end_l)),
+            ],
+        )
+    }
+
+    // `{ a: 1, b:, **rest }` pattern
+    fn hash_pattern(&mut self, hash_pattern: &HashPattern) -> PrismNode {
+        let mut elements = vec![];
+        let mut rest = None;
+        for element in hash_pattern.elements.iter() {
+            match element {
+                Node::MatchRest(MatchRest {
+                    name,
+                    operator_l,
+                    expression_l,
+                }) => {
+                    let value = self.convert_opt(name.as_deref());
+                    rest = Some(node(
+                        "AssocSplatNode",
+                        *expression_l,
+                        vec![
+                            ("value", optional(value)),
+                            ("operator_loc", Field::Location(*operator_l)),
+                        ],
+                    ));
+                }
+                Node::MatchNilPattern(_) => rest = Some(self.convert(element)),
+                Node::MatchVar(MatchVar {
+                    name,
+                    name_l,
+                    expression_l,
+                }) => {
+                    // `in { a: }` is `in { a: a }`
+                    let colon_l = expression_l.with_begin(name_l.end);
+                    let flags = if name.is_ascii() {
+                        vec!["FORCED_US_ASCII_ENCODING"]
+                    } else {
+                        vec![]
+                    };
+                    let key = node_symbol(
+                        flags,
+                        None,
+                        *name_l,
+                        Some(colon_l),
+                        name.as_bytes().to_vec(),
+                        *expression_l,
+                    );
+                    let target = self.local_variable("LocalVariableTargetNode", name, *name_l);
+                    let value = node("ImplicitNode", *name_l, vec![("value", required(target))]);
+                    elements.push(node(
+                        "AssocNode",
+                        *expression_l,
+                        vec![
+                            ("key", required(key)),
+                            ("value", required(value)),
+                            ("operator_loc", Field::OptionalLocation(None)),
+                        ],
+                    ));
+                }
+                other => elements.push(self.convert(other)),
+            }
+        }
+        node(
+            "HashPatternNode",
+            hash_pattern.expression_l,
+            vec![
+                ("constant", optional(None)),
+                ("elements", Field::Nodes(elements)),
+                ("rest", optional(rest)),
+                ("opening_loc", Field::OptionalLocation(hash_pattern.begin_l)),
+                ("closing_loc", Field::OptionalLocation(hash_pattern.end_l)),
+            ],
+        )
+    }
+
+    // `Foo(a)`, `Foo[a]`, `Foo(a:)`
+    fn const_pattern(&mut self, const_pattern: &ConstPattern) -> PrismNode {
+        let constant = self.convert(&const_pattern.const_);
+        let mut pattern = match &*const_pattern.pattern {
+            pattern @ (Node::ArrayPattern(_)
+            | Node::ArrayPatternWithTail(_)
+            | Node::FindPattern(_)
+            | Node::HashPattern(_)) => self.convert(pattern),
+            // `Foo(a)` with a single non-array pattern inside
+            other => {
+                let element = self.convert(other);
+                node(
+                    "ArrayPatternNode",
+                    const_pattern.expression_l,
+                    vec![
+                        ("constant", optional(None)),
+                        ("requireds", Field::Nodes(vec![element])),
+                        ("rest", optional(None)),
+                        ("posts", Field::Nodes(vec![])),
+                        ("opening_loc", Field::OptionalLocation(None)),
+                        ("closing_loc", Field::OptionalLocation(None)),
+                    ],
+                )
+            }
+        };
+        pattern.location = const_pattern.expression_l;
+        for (name, field) in pattern.fields.iter_mut() {
+            match *name {
+                "constant" => *field = optional(Some(constant.clone())),
+                "opening_loc" => *field = Field::OptionalLocation(Some(const_pattern.begin_l)),
+                "closing_loc" => *field = Field::OptionalLocation(Some(const_pattern.end_l)),
+                _ => {}
+            }
+        }
+        pattern
+    }
+
+    fn match_write(&mut self, match_with_lvasgn: &MatchWithLvasgn) -> PrismNode {
+        let re_l = *match_with_lvasgn.re.expression();
+        let receiver = self.convert(&match_with_lvasgn.re);
+        let arguments = self.arguments_node(std::slice::from_ref(&*match_with_lvasgn.value));
+        let call = node(
+            "CallNode",
+            match_with_lvasgn.expression_l,
+            vec![
+                ("flags", Field::Flags(vec![])),
+                ("receiver", optional(Some(receiver))),
+                ("call_operator_loc", Field::OptionalLocation(None)),
+                ("name", constant("=~")),
+                (
+                    "message_loc",
+                    Field::OptionalLocation(Some(match_with_lvasgn.operator_l)),
+                ),
+                ("opening_loc", Field::OptionalLocation(None)),
+                ("arguments", optional(arguments)),
+                ("closing_loc", Field::OptionalLocation(None)),
+                ("block", optional(None)),
+            ],
+        );
+
+        // named captures are assigned inside of the regexp literal
+        let mut captures = vec![];
+        for variable in self.scope_tree.variables.iter() {
+            for assignment in variable.assignments.iter() {
+                if re_l.begin <= assignment.begin && assignment.end <= re_l.end {
+                    captures.push((variable.name.clone(), *assignment));
+                }
+            }
+        }
+        captures.sort_by_key(|(_, loc)| loc.begin);
+        let targets = captures
+            .into_iter()
+            .map(|(name, loc)| self.local_variable("LocalVariableTargetNode", &name, loc))
+            .collect();
+
+        node(
+            "MatchWriteNode",
+            match_with_lvasgn.expression_l,
+            vec![("call", required(call)), ("targets", Field::Nodes(targets))],
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn def(
+        &mut self,
+        receiver: Option<&Node>,
+        name: &str,
+        args: Option<&Node>,
+        body: Option<&Node>,
+        keyword_l: Loc,
+        operator_l: Option<Loc>,
+        name_l: Loc,
+        assignment_l: Option<Loc>,
+        end_l: Option<Loc>,
+        expression_l: Loc,
+    ) -> PrismNode {
+        let receiver = self.convert_opt(receiver);
+        let (locals, (parameters, body)) = self.in_scope(&expression_l, |this| {
+            (this.def_parameters(args), this.body(body, end_l))
+        });
+        let (lparen_l, rparen_l) = match args {
+            Some(Node::Args(Args { begin_l, end_l, .. })) => (*begin_l, *end_l),
+            _ => (None, None),
+        };
+        node(
+            "DefNode",
+            expression_l,
+            vec![
+                ("name", constant(name)),
+                ("name_loc", Field::Location(name_l)),
+                ("receiver", optional(receiver)),
+                ("parameters", optional(parameters)),
+                ("body", optional(body)),
+                ("locals", Field::Constants(locals)),
+                ("def_keyword_loc", Field::Location(keyword_l)),
+                ("operator_loc", Field::OptionalLocation(operator_l)),
+                ("lparen_loc", Field::OptionalLocation(lparen_l)),
+                ("rparen_loc", Field::OptionalLocation(rparen_l)),
+                ("equal_loc", Field::OptionalLocation(assignment_l)),
+                ("end_keyword_loc", Field::OptionalLocation(end_l)),
+            ],
+        )
+    }
+
+    fn class(&mut self, class: &Class) -> PrismNode {
+        let constant_path = self.convert(&class.name);
+        let superclass = self.convert_opt(class.superclass.as_deref());
+        let (locals, body) = self.in_scope(&class.expression_l, |this| {
+            this.body(class.body.as_deref(), Some(class.end_l))
+        });
+        node(
+            "ClassNode",
+            class.expression_l,
+            vec![
+                ("locals", Field::Constants(locals)),
+                ("class_keyword_loc", Field::Location(class.keyword_l)),
+                ("constant_path", required(constant_path)),
+                (
+                    "inheritance_operator_loc",
+                    Field::OptionalLocation(class.operator_l),
+                ),
+                ("superclass", optional(superclass)),
+                ("body", optional(body)),
+                ("end_keyword_loc", Field::Location(class.end_l)),
+                ("name", constant(&constant_name(&class.name))),
+            ],
+        )
+    }
+
+    fn module(&mut self, module: &Module) -> PrismNode {
+        let constant_path = self.convert(&module.name);
+        let (locals, body) = self.in_scope(&module.expression_l, |this| {
+            this.body(module.body.as_deref(), Some(module.end_l))
+        });
+        node(
+            "ModuleNode",
+            module.expression_l,
+            vec![
+                ("locals", Field::Constants(locals)),
+                ("module_keyword_loc", Field::Location(module.keyword_l)),
+                ("constant_path", required(constant_path)),
+                ("body", optional(body)),
+                ("end_keyword_loc", Field::Location(module.end_l)),
+                ("name", constant(&constant_name(&module.name))),
+            ],
+        )
+    }
+
+    fn singleton_class(&mut self, sclass: &SClass) -> PrismNode {
+        let expression = self.convert(&sclass.expr);
+        let (locals, body) = self.in_scope(&sclass.expression_l, |this| {
+            this.body(sclass.body.as_deref(), Some(sclass.end_l))
+        });
+        node(
+            "SingletonClassNode",
+            sclass.expression_l,
+            vec![
+                ("locals", Field::Constants(locals)),
+                ("class_keyword_loc", Field::Location(sclass.keyword_l)),
+                ("operator_loc", Field::Location(sclass.operator_l)),
+                ("expression", required(expression)),
+                ("body", optional(body)),
+                ("end_keyword_loc", Field::Location(sclass.end_l)),
+            ],
+        )
+    }
+
+    // `break`, `next` and `return`
+    fn jump(
+        &mut self,
+        kind: &'static str,
+        args: &[Node],
+        keyword_l: Loc,
+        expression_l: Loc,
+    ) -> PrismNode {
+        let arguments = optional(self.arguments_node(args));
+        let keyword = Field::Location(keyword_l);
+        let fields = if kind == "ReturnNode" {
+            vec![("keyword_loc", keyword), ("arguments", arguments)]
+        } else {
+            vec![("arguments", arguments), ("keyword_loc", keyword)]
+        };
+        node(kind, expression_l, fields)
+    }
+
+    // `BEGIN { }` and `END { }`
+    fn execution(
+        &mut self,
+        kind: &'static str,
+        body: Option<&Node>,
+        keyword_l: Loc,
+        begin_l: Loc,
+        end_l: Loc,
+        expression_l: Loc,
+    ) -> PrismNode {
+        let (_, statements) = self.in_scope(&expression_l, |this| this.statements(body));
+        node(
+            kind,
+            expression_l,
+            vec![
+                ("statements", optional(statements)),
+                ("keyword_loc", Field::Location(keyword_l)),
+                ("opening_loc", Field::Location(begin_l)),
+                ("closing_loc", Field::Location(end_l)),
+            ],
+        )
+    }
+
+    fn convert(&mut self, node_ref: &Node) -> PrismNode {
+        let location = *node_ref.expression();
+        match node_ref {
+            Node::Alias(Alias {
+                to,
+                from,
+                keyword_l,
+                ..
+            }) => {
+                let kind = match &**to {
+                    Node::Gvar(_) | Node::BackRef(_) | Node::NthRef(_) => "AliasGlobalVariableNode",
+                    _ => "AliasMethodNode",
+                };
+                let new_name = self.convert(to);
+                let old_name = self.convert(from);
+                node(
+                    kind,
+                    location,
+                    vec![
+                        ("new_name", required(new_name)),
+                        ("old_name", required(old_name)),
+                        ("keyword_loc", Field::Location(*keyword_l)),
+                    ],
+                )
+            }
+            Node::And(And {
+                lhs,
+                rhs,
+                operator_l,
+                ..
+            })
+            | Node::Or(Or {
+                lhs,
+                rhs,
+                operator_l,
+                ..
+            })
+            | Node::MatchAlt(MatchAlt {
+                lhs,
+                rhs,
+                operator_l,
+                ..
+            }) => {
+                let kind = match node_ref {
+                    Node::And(_) => "AndNode",
+                    Node::Or(_) => "OrNode",
+                    _ => "AlternationPatternNode",
+                };
+                let left = self.convert(lhs);
+                let right = self.convert(rhs);
+                node(
+                    kind,
+                    location,
+                    vec![
+                        ("left", required(left)),
+                        ("right", required(right)),
+                        ("operator_loc", Field::Location(*operator_l)),
+                    ],
+                )
+            }
+            Node::AndAsgn(AndAsgn {
+                recv,
+                value,
+                operator_l,
+                ..
+            }) => self.operator_write(recv, WriteOperator::And, value, *operator_l, location),
+            Node::OrAsgn(OrAsgn {
+                recv,
+                value,
+                operator_l,
+                ..
+            }) => self.operator_write(recv, WriteOperator::Or, value, *operator_l, location),
+            Node::OpAsgn(OpAsgn {
+                recv,
+                operator,
+                value,
+                operator_l,
+                ..
+            }) => {
+                let operator = operator.strip_suffix('=').unwrap_or(operator);
+                self.operator_write(
+                    recv,
+                    WriteOperator::Binary(operator),
+                    value,
+                    *operator_l,
+                    location,
+                )
+            }
+            Node::Arg(_)
+            | Node::Optarg(_)
+            | Node::Restarg(_)
+            | Node::Kwarg(_)
+            | Node::Kwoptarg(_)
+            | Node::Kwrestarg(_)
+            | Node::Kwnilarg(_)
+            | Node::Blockarg(_)
+            | Node::Shadowarg(_)
+            | Node::Procarg0(_)
+            | Node::ForwardArg(_) => self.parameter(node_ref),
+            Node::Args(Args { args, .. }) => {
+                match self.parameters(&args.iter().collect::<Vec<_>>()) {
+                    Some(parameters) => parameters,
+                    None => missing(location),
+                }
+            }
+            Node::Array(Array {
+                elements,
+                begin_l,
+                end_l,
+                ..
+            }) => {
+                let flags = if elements
+                    .iter()
+                    .any(|element| matches!(element, Node::Splat(_)))
+                {
+                    vec!["CONTAINS_SPLAT"]
+                } else {
+                    vec![]
+                };
+                let elements = self.convert_list(elements);
+                node(
+                    "ArrayNode",
+                    location,
+                    vec![
+                        ("flags", Field::Flags(flags)),
+                        ("elements", Field::Nodes(elements)),
+                        ("opening_loc", Field::OptionalLocation(*begin_l)),
+                        ("closing_loc", Field::OptionalLocation(*end_l)),
+                    ],
+                )
+            }
+            Node::ArrayPattern(ArrayPattern {
+                elements,
+                begin_l,
+                end_l,
+                ..
+            }) => self.array_pattern(elements, *begin_l, *end_l, location, false),
+            Node::ArrayPatternWithTail(ArrayPatternWithTail {
+                elements,
+                begin_l,
+                end_l,
+                ..
+            }) => self.array_pattern(elements, *begin_l, *end_l, location, true),
+            Node::BackRef(BackRef { name, .. }) => node(
+                "BackReferenceReadNode",
+                location,
+                vec![("name", constant(name))],
+            ),
+            Node::NthRef(NthRef { name, .. }) => {
+                let number = name.trim_start_matches('$').parse().unwrap_or(0);
+                node(
+                    "NumberedReferenceReadNode",
+                    location,
+                    vec![("number", Field::UInt32(number))],
+                )
+            }
+            Node::Begin(Begin {
+                statements,
+                begin_l: Some(begin_l),
+                end_l: Some(end_l),
+                ..
+            }) => {
+                let body = self.statements_list(&statements.iter().collect::<Vec<_>>());
+                node(
+                    "ParenthesesNode",
+                    location,
+                    vec![
+                        ("body", optional(body)),
+                        ("opening_loc", Field::Location(*begin_l)),
+                        ("closing_loc", Field::Location(*end_l)),
+                    ],
+                )
+            }
+            Node::Begin(_) => match self.statements(Some(node_ref)) {
+                Some(statements) => statements,
+                None => missing(location),
+            },
+            Node::KwBegin(KwBegin {
+                statements,
+                begin_l,
+                end_l,
+                ..
+            }) => {
+                let statements = statements.iter().collect::<Vec<_>>();
+                self.begin_node(*begin_l, &statements, *end_l, location)
+            }
+            Node::Rescue(rescue) if self.is_rescue_modifier(rescue) => {
+                let expression = self.convert_opt(rescue.body.as_deref());
+                let (keyword_l, rescue_expression) = match rescue.rescue_bodies.first() {
+                    Some(Node::RescueBody(rescue_body)) => (
+                        rescue_body.keyword_l,
+                        self.convert_opt(rescue_body.body.as_deref()),
+                    ),
+                    _ => (location, None),
+                };
+                node(
+                    "RescueModifierNode",
+                    location,
+                    vec![
+                        (
+                            "expression",
+                            required(expression.unwrap_or_else(|| missing(location))),
+                        ),
+                        ("keyword_loc", Field::Location(keyword_l)),
+                        (
+                            "rescue_expression",
+                            required(rescue_expression.unwrap_or_else(|| missing(location))),
+                        ),
+                    ],
+                )
+            }
+            Node::Rescue(_) | Node::Ensure(_) => self.begin_node(None, &[node_ref], None, location),
+            Node::RescueBody(_) => match self.rescue_clauses(std::slice::from_ref(node_ref)) {
+                Some(rescue) => rescue,
+                None => missing(location),
+            },
+            Node::Block(Block {
+                call,
+                args,
+                body,
+                begin_l,
+                end_l,
+                ..
+            }) => self.block(
+                call,
+                args.as_deref(),
+                None,
+                body.as_deref(),
+                *begin_l,
+                *end_l,
+                location,
+            ),
+            Node::Numblock(Numblock {
+                call,
+                numargs,
+                body,
+                begin_l,
+                end_l,
+                ..
+            }) => self.block(
+                call,
+                None,
+                Some(*numargs),
+                Some(body),
+                *begin_l,
+                *end_l,
+                location,
+            ),
+            Node::BlockPass(BlockPass {
+                value, operator_l, ..
+            }) => {
+                let expression = self.convert_opt(value.as_deref());
+                node(
+                    "BlockArgumentNode",
+                    location,
+                    vec![
+                        ("expression", optional(expression)),
+                        ("operator_loc", Field::Location(*operator_l)),
+                    ],
+                )
+            }
+            Node::Break(Break {
+                args, keyword_l, ..
+            }) => self.jump("BreakNode", args, *keyword_l, location),
+            Node::Next(Next {
+                args, keyword_l, ..
+            }) => self.jump("NextNode", args, *keyword_l, location),
+            Node::Return(Return {
+                args, keyword_l, ..
+            }) => self.jump("ReturnNode", args, *keyword_l, location),
+            Node::Send(Send {
+                recv,
+                method_name,
+                args,
+                dot_l,
+                selector_l,
+                begin_l,
+                end_l,
+                operator_l,
+                ..
+            }) => self.call(
+                recv.as_deref(),
+                method_name,
+                args,
+                *dot_l,
+                *selector_l,
+                *begin_l,
+                *end_l,
+                *operator_l,
+                location,
+                false,
+            ),
+            Node::CSend(CSend {
+                recv,
+                method_name,
+                args,
+                dot_l,
+                selector_l,
+                begin_l,
+                end_l,
+                operator_l,
+                ..
+            }) => self.call(
+                Some(recv),
+                method_name,
+                args,
+                Some(*dot_l),
+                *selector_l,
+                *begin_l,
+                *end_l,
+                *operator_l,
+                location,
+                true,
+            ),
+            Node::Index(index) => self.index(index),
+            Node::IndexAsgn(index_asgn) => self.index_asgn(index_asgn),
+            Node::Super(Super {
+                args,
+                keyword_l,
+                begin_l,
+                end_l,
+                ..
+            }) => {
+                let (arguments, block) = self.arguments(args);
+                node(
+                    "SuperNode",
+                    location,
+                    vec![
+                        ("keyword_loc", Field::Location(*keyword_l)),
+                        ("lparen_loc", Field::OptionalLocation(*begin_l)),
+                        ("arguments", optional(arguments)),
+                        ("rparen_loc", Field::OptionalLocation(*end_l)),
+                        ("block", optional(block)),
+                    ],
+                )
+            }
+            Node::ZSuper(_) => node(
+                "ForwardingSuperNode",
+                location,
+                vec![("block", optional(None))],
+            ),
+            Node::Yield(Yield {
+                args,
+                keyword_l,
+                begin_l,
+                end_l,
+                ..
+            }) => {
+                let arguments = self.arguments_node(args);
+                node(
+                    "YieldNode",
+                    location,
+                    vec![
+                        ("keyword_loc", Field::Location(*keyword_l)),
+                        ("lparen_loc", Field::OptionalLocation(*begin_l)),
+                        ("arguments", optional(arguments)),
+                        ("rparen_loc", Field::OptionalLocation(*end_l)),
+                    ],
+                )
+            }
+            Node::Case(case) => self.case(case),
+            Node::CaseMatch(case_match) => self.case_match(case_match),
+            Node::When(when) => self.when(when),
+            Node::InPattern(in_pattern) => self.in_pattern(in_pattern),
+            Node::Casgn(casgn) => self.casgn(casgn),
+            Node::Const(Const {
+                scope: None,
+                name,
+                double_colon_l: None,
+                ..
+            }) => node("ConstantReadNode", location, vec![("name", constant(name))]),
+            Node::Const(Const {
+                scope,
+                name,
+                double_colon_l,
+                name_l,
+                ..
+            }) => self.constant_path(
+                "ConstantPathNode",
+                scope.as_deref(),
+                name,
+                *double_colon_l,
+                *name_l,
+                location,
+            ),
+            Node::ConstPattern(const_pattern) => self.const_pattern(const_pattern),
+            Node::Class(class) => self.class(class),
+            Node::Module(module) => self.module(module),
+            Node::SClass(sclass) => self.singleton_class(sclass),
+            Node::Def(Def {
+                name,
+                args,
+                body,
+                keyword_l,
+                name_l,
+                end_l,
+                assignment_l,
+                ..
+            }) => self.def(
+                None,
+                name,
+                args.as_deref(),
+                body.as_deref(),
+                *keyword_l,
+                None,
+                *name_l,
+                *assignment_l,
+                *end_l,
+                location,
+            ),
+            Node::Defs(Defs {
+                definee,
+                name,
+                args,
+                body,
+                keyword_l,
+                operator_l,
+                name_l,
+                assignment_l,
+                end_l,
+                ..
+            }) => self.def(
+                Some(definee),
+                name,
+                args.as_deref(),
+                body.as_deref(),
+                *keyword_l,
+                Some(*operator_l),
+                *name_l,
+                *assignment_l,
+                *end_l,
+                location,
+            ),
+            Node::Defined(Defined {
+                value,
+                keyword_l,
+                begin_l,
+                end_l,
+                ..
+            }) => {
+                let value = self.convert(value);
+                node(
+                    "DefinedNode",
+                    location,
+                    vec![
+                        ("lparen_loc", Field::OptionalLocation(*begin_l)),
+                        ("value", required(value)),
+                        ("rparen_loc", Field::OptionalLocation(*end_l)),
+                        ("keyword_loc", Field::Location(*keyword_l)),
+                    ],
+                )
+            }
+            Node::Str(str) => self.string(node_ref, str, false),
+            Node::Dstr(dstr) => self.dstr(dstr),
+            Node::Heredoc(heredoc) => self.heredoc(heredoc),
+            Node::Sym(sym) => self.symbol(node_ref, sym),
+            Node::Dsym(dsym) => self.dsym(dsym),
+            Node::Xstr(Xstr {
+                parts,
+                begin_l,
+                end_l,
+                ..
+            }) => {
+                let content_l = Loc {
+                    begin: begin_l.end,
+                    end: end_l.begin,
+                };
+                self.xstr(parts, *begin_l, content_l, *end_l, location)
+            }
+            Node::XHeredoc(XHeredoc {
+                parts,
+                heredoc_body_l,
+                heredoc_end_l,
+                ..
+            }) => self.xstr(parts, location, *heredoc_body_l, *heredoc_end_l, location),
+            Node::Regexp(regexp) => self.regexp(node_ref, regexp),
+            Node::MatchCurrentLine(MatchCurrentLine { re, .. }) => {
+                let mut regexp = self.convert(re);
+                regexp.kind = match regexp.kind {
+                    "RegularExpressionNode" => "MatchLastLineNode",
+                    "InterpolatedRegularExpressionNode" => "InterpolatedMatchLastLineNode",
+                    other => other,
+                };
+                regexp
+            }
+            Node::MatchWithLvasgn(match_with_lvasgn) => self.match_write(match_with_lvasgn),
+            Node::EFlipFlop(EFlipFlop {
+                left,
+                right,
+                operator_l,
+                ..
+            })
+            | Node::IFlipFlop(IFlipFlop {
+                left,
+                right,
+                operator_l,
+                ..
+            })
+            | Node::Erange(Erange {
+                left,
+                right,
+                operator_l,
+                ..
+            })
+            | Node::Irange(Irange {
+                left,
+                right,
+                operator_l,
+                ..
+            }) => {
+                let (kind, exclude_end) = match node_ref {
+                    Node::EFlipFlop(_) => ("FlipFlopNode", true),
+                    Node::IFlipFlop(_) => ("FlipFlopNode", false),
+                    Node::Erange(_) => ("RangeNode", true),
+                    _ => ("RangeNode", false),
+                };
+                let flags = if exclude_end {
+                    vec!["EXCLUDE_END"]
+                } else {
+                    vec![]
+                };
+                let left = self.convert_opt(left.as_deref());
+                let right = self.convert_opt(right.as_deref());
+                node(
+                    kind,
+                    location,
+                    vec![
+                        ("flags", Field::Flags(flags)),
+                        ("left", optional(left)),
+                        ("right", optional(right)),
+                        ("operator_loc", Field::Location(*operator_l)),
+                    ],
+                )
+            }
+            Node::Encoding(_) => node("SourceEncodingNode", location, vec![]),
+            Node::File(_) => {
+                let flags = self.frozen_flag(false).into_iter().collect();
+                let filepath = self.result.input.name.as_bytes().to_vec();
+                node(
+                    "SourceFileNode",
+                    location,
+                    vec![
+                        ("flags", Field::Flags(flags)),
+                        ("filepath", Field::String(filepath)),
+                    ],
+                )
+            }
+            Node::Line(_) => node("SourceLineNode", location, vec![]),
+            Node::False(_) => node("FalseNode", location, vec![]),
+            Node::True(_) => node("TrueNode", location, vec![]),
+            Node::Nil(_) => node("NilNode", location, vec![]),
+            Node::Self_(_) => node("SelfNode", location, vec![]),
+            Node::Redo(_) => node("RedoNode", location, vec![]),
+            Node::Retry(_) => node("RetryNode", location, vec![]),
+            Node::ForwardedArgs(_) => node("ForwardingArgumentsNode", location, vec![]),
+            Node::FindPattern(find_pattern) => self.find_pattern(find_pattern),
+            Node::HashPattern(hash_pattern) => self.hash_pattern(hash_pattern),
+            Node::Int(Int { value, .. }) => integer_node(value, location),
+            Node::Float(Float { value, .. }) => float_node(value, location),
+            Node::Rational(Rational { value, .. }) => rational_node(value, location),
+            Node::Complex(Complex { value, .. }) => imaginary_node(value, location),
+            Node::For(For {
+                iterator,
+                iteratee,
+                body,
+                keyword_l,
+                operator_l,
+                begin_l,
+                end_l,
+                ..
+            }) => {
+                let index = self.target(iterator);
+                let collection = self.convert(iteratee);
+                let statements = self.statements(body.as_deref());
+                node(
+                    "ForNode",
+                    location,
+                    vec![
+                        ("index", required(index)),
+                        ("collection", required(collection)),
+                        ("statements", optional(statements)),
+                        ("for_keyword_loc", Field::Location(*keyword_l)),
+                        ("in_keyword_loc", Field::Location(*operator_l)),
+                        (
+                            "do_keyword_loc",
+                            Field::OptionalLocation(self.keyword(&Some(*begin_l), "do")),
+                        ),
+                        ("end_keyword_loc", Field::Location(*end_l)),
+                    ],
+                )
+            }
+            Node::Hash(Hash {
+                pairs,
+                begin_l: Some(begin_l),
+                end_l: Some(end_l),
+                ..
+            }) => {
+                let elements = self.convert_list(pairs);
+                node(
+                    "HashNode",
+                    location,
+                    vec![
+                        ("opening_loc", Field::Location(*begin_l)),
+                        ("elements", Field::Nodes(elements)),
+                        ("closing_loc", Field::Location(*end_l)),
+                    ],
+                )
+            }
+            Node::Hash(Hash { pairs, .. }) | Node::Kwargs(Kwargs { pairs, .. }) => {
+                let symbol_keys = pairs.iter().all(|pair| {
+                    matches!(pair, Node::Pair(Pair { key, .. }) if matches!(&**key, Node::Sym(_)))
+                });
+                let flags = if symbol_keys {
+                    vec!["SYMBOL_KEYS"]
+                } else {
+                    vec![]
+                };
+                let elements = self.convert_list(pairs);
+                node(
+                    "KeywordHashNode",
+                    location,
+                    vec![
+                        ("flags", Field::Flags(flags)),
+                        ("elements", Field::Nodes(elements)),
+                    ],
+                )
+            }
+            Node::Pair(pair) => self.assoc(pair),
+            Node::Kwsplat(Kwsplat {
+                value, operator_l, ..
+            }) => {
+                let value = self.convert(value);
+                node(
+                    "AssocSplatNode",
+                    location,
+                    vec![
+                        ("value", optional(Some(value))),
+                        ("operator_loc", Field::Location(*operator_l)),
+                    ],
+                )
+            }
+            Node::If(node_if) => self.if_node(node_if, None),
+            Node::IfMod(IfMod {
+                cond,
+                if_true,
+                if_false,
+                keyword_l,
+                ..
+            }) => match (if_true, if_false) {
+                (None, Some(body)) => {
+                    self.modifier_if(true, cond, Some(body), *keyword_l, location)
+                }
+                (body, _) => self.modifier_if(false, cond, body.as_deref(), *keyword_l, location),
+            },
+            Node::IfTernary(ternary) => self.ternary(ternary),
+            Node::IfGuard(IfGuard {
+                cond, keyword_l, ..
+            }) => self.modifier_if(false, cond, None, *keyword_l, location),
+            Node::UnlessGuard(UnlessGuard {
+                cond, keyword_l, ..
+            }) => self.modifier_if(true, cond, None, *keyword_l, location),
+            Node::Ivar(Ivar { name, .. }) => node(
+                "InstanceVariableReadNode",
+                location,
+                vec![("name", constant(name))],
+            ),
+            Node::Gvar(Gvar { name, .. }) => node(
+                "GlobalVariableReadNode",
+                location,
+                vec![("name", constant(name))],
+            ),
+            Node::Cvar(Cvar { name, .. }) => node(
+                "ClassVariableReadNode",
+                location,
+                vec![("name", constant(name))],
+            ),
+            Node::Lvar(Lvar { name, .. }) => {
+                self.local_variable("LocalVariableReadNode", name, location)
+            }
+            Node::Ivasgn(Ivasgn {
+                name,
+                value,
+                name_l,
+                operator_l,
+                ..
+            }) => self.variable_write(
+                "InstanceVariable",
+                name,
+                value.as_deref(),
+                *name_l,
+                *operator_l,
+                location,
+            ),
+            Node::Gvasgn(Gvasgn {
+                name,
+                value,
+                name_l,
+                operator_l,
+                ..
+            }) => self.variable_write(
+                "GlobalVariable",
+                name,
+                value.as_deref(),
+                *name_l,
+                *operator_l,
+                location,
+            ),
+            Node::Cvasgn(Cvasgn {
+                name,
+                value,
+                name_l,
+                operator_l,
+                ..
+            }) => self.variable_write(
+                "ClassVariable",
+                name,
+                value.as_deref(),
+                *name_l,
+                *operator_l,
+                location,
+            ),
+            Node::Lvasgn(lvasgn) => self.lvasgn(lvasgn),
+            Node::Masgn(masgn) => self.masgn(masgn),
+            Node::Mlhs(_) => self.target(node_ref),
+            Node::Splat(Splat {
+                value, operator_l, ..
+            }) => {
+                let expression = self.convert_opt(value.as_deref());
+                splat(*operator_l, expression, location)
+            }
+            Node::MatchRest(MatchRest {
+                name, operator_l, ..
+            }) => {
+                let expression = self.convert_opt(name.as_deref());
+                splat(*operator_l, expression, location)
+            }
+            Node::MatchVar(MatchVar { name, name_l, .. }) => {
+                self.local_variable("LocalVariableTargetNode", name, *name_l)
+            }
+            Node::MatchNilPattern(MatchNilPattern { name_l, .. }) => no_keywords(*name_l, location),
+            Node::MatchAs(MatchAs {
+                value,
+                as_,
+                operator_l,
+                ..
+            }) => {
+                let value = self.convert(value);
+                let target = self.convert(as_);
+                node(
+                    "CapturePatternNode",
+                    location,
+                    vec![
+                        ("value", required(value)),
+                        ("target", required(target)),
+                        ("operator_loc", Field::Location(*operator_l)),
+                    ],
+                )
+            }
+            Node::MatchPattern(MatchPattern {
+                value,
+                pattern,
+                operator_l,
+                ..
+            })
+            | Node::MatchPatternP(MatchPatternP {
+                value,
+                pattern,
+                operator_l,
+                ..
+            }) => {
+                let kind = match node_ref {
+                    Node::MatchPattern(_) => "MatchRequiredNode",
+                    _ => "MatchPredicateNode",
+                };
+                let value = self.convert(value);
+                let pattern = self.convert(pattern);
+                node(
+                    kind,
+                    location,
+                    vec![
+                        ("value", required(value)),
+                        ("pattern", required(pattern)),
+                        ("operator_loc", Field::Location(*operator_l)),
+                    ],
+                )
+            }
+            Node::Pin(Pin {
+                var, selector_l, ..
+            }) => match &**var {
+                Node::Begin(Begin {
+                    statements,
+                    begin_l: Some(begin_l),
+                    end_l: Some(end_l),
+                    ..
+                }) if statements.len() == 1 => {
+                    let expression = self.convert(&statements[0]);
+                    node(
+                        "PinnedExpressionNode",
+                        location,
+                        vec![
+                            ("expression", required(expression)),
+                            ("operator_loc", Field::Location(*selector_l)),
+                            ("lparen_loc", Field::Location(*begin_l)),
+                            ("rparen_loc", Field::Location(*end_l)),
+                        ],
+                    )
+                }
+                var => {
+                    let variable = self.convert(var);
+                    node(
+                        "PinnedVariableNode",
+                        location,
+                        vec![
+                            ("variable", required(variable)),
+                            ("operator_loc", Field::Location(*selector_l)),
+                        ],
+                    )
+                }
+            },
+            Node::Postexe(Postexe {
+                body,
+                keyword_l,
+                begin_l,
+                end_l,
+                ..
+            }) => self.execution(
+                "PostExecutionNode",
+                body.as_deref(),
+                *keyword_l,
+                *begin_l,
+                *end_l,
+                location,
+            ),
+            Node::Preexe(Preexe {
+                body,
+                keyword_l,
+                begin_l,
+                end_l,
+                ..
+            }) => self.execution(
+                "PreExecutionNode",
+                body.as_deref(),
+                *keyword_l,
+                *begin_l,
+                *end_l,
+                location,
+            ),
+            Node::Undef(Undef {
+                names, keyword_l, ..
+            }) => {
+                let names = self.convert_list(names);
+                node(
+                    "UndefNode",
+                    location,
+                    vec![
+                        ("names", Field::Nodes(names)),
+                        ("keyword_loc", Field::Location(*keyword_l)),
+                    ],
+                )
+            }
+            Node::While(While {
+                cond,
+                body,
+                keyword_l,
+                begin_l,
+                end_l,
+                ..
+            }) => self.loop_node(
+                "WhileNode",
+                cond,
+                body.as_deref(),
+                *keyword_l,
+                *begin_l,
+                *end_l,
+                location,
+                false,
+            ),
+            Node::Until(Until {
+                cond,
+                body,
+                keyword_l,
+                begin_l,
+                end_l,
+                ..
+            }) => self.loop_node(
+                "UntilNode",
+                cond,
+                body.as_deref(),
+                *keyword_l,
+                *begin_l,
+                *end_l,
+                location,
+                false,
+            ),
+            Node::WhilePost(WhilePost {
+                cond,
+                body,
+                keyword_l,
+                ..
+            }) => self.loop_node(
+                "WhileNode",
+                cond,
+                Some(body),
+                *keyword_l,
+                None,
+                None,
+                location,
+                true,
+            ),
+            Node::UntilPost(UntilPost {
+                cond,
+                body,
+                keyword_l,
+                ..
+            }) => self.loop_node(
+                "UntilNode",
+                cond,
+                Some(body),
+                *keyword_l,
+                None,
+                None,
+                location,
+                true,
+            ),
+            // `->` without a block, `::` without a constant, etc.
+            Node::Lambda(_) | Node::Cbase(_) | Node::EmptyElse(_) | Node::RegOpt(_) => {
+                missing(location)
+            }
+        }
+    }
+}
+
+fn splat(operator_l: Loc, expression: Option<PrismNode>, location: Loc) -> PrismNode {
+    node(
+        "SplatNode",
+        location,
+        vec![
+            ("operator_loc", Field::Location(operator_l)),
+            ("expression", optional(expression)),
+        ],
+    )
+}
+
+// `**nil`
+fn no_keywords(keyword_l: Loc, location: Loc) -> PrismNode {
+    node(
+        "NoKeywordsParameterNode",
+        location,
+        vec![
+            (
+                "operator_loc",
+                Field::Location(location.with_end(location.begin + 2)),
+            ),
+            ("keyword_loc", Field::Location(keyword_l)),
+        ],
+    )
+}
+
+// `*a`, `**a` and `&a` parameters
+fn named_rest_parameter(
+    kind: &'static str,
+    name: &Option<String>,
+    name_l: &Option<Loc>,
+    operator_l: &Loc,
+    location: &Loc,
+) -> PrismNode {
+    node(
+        kind,
+        *location,
+        vec![
+            ("flags", Field::Flags(vec![])),
+            ("name", Field::OptionalConstant(name.clone())),
+            ("name_loc", Field::OptionalLocation(*name_l)),
+            ("operator_loc", Field::Location(*operator_l)),
+        ],
+    )
+}
+
+// Name of the class or module, i.e. `C` of `A::B::C`
+fn constant_name(name: &Node) -> String {
+    match name {
+        Node::Const(Const { name, .. }) => name.clone(),
+        _ => String::new(),
+    }
+}
+
+fn node_string(
+    kind: &'static str,
+    flags: Vec<&'static str>,
+    opening_l: Option<Loc>,
+    content_l: Loc,
+    closing_l: Option<Loc>,
+    unescaped: Vec<u8>,
+    location: Loc,
+) -> PrismNode {
+    node(
+        kind,
+        location,
+        vec![
+            ("flags", Field::Flags(flags)),
+            ("opening_loc", Field::OptionalLocation(opening_l)),
+            ("content_loc", Field::Location(content_l)),
+            ("closing_loc", Field::OptionalLocation(closing_l)),
+            ("unescaped", Field::String(unescaped)),
+        ],
+    )
+}
+
+fn node_symbol(
+    flags: Vec<&'static str>,
+    opening_l: Option<Loc>,
+    value_l: Loc,
+    closing_l: Option<Loc>,
+    unescaped: Vec<u8>,
+    location: Loc,
+) -> PrismNode {
+    node(
+        "SymbolNode",
+        location,
+        vec![
+            ("flags", Field::Flags(flags)),
+            ("opening_loc", Field::OptionalLocation(opening_l)),
+            ("value_loc", Field::OptionalLocation(Some(value_l))),
+            ("closing_loc", Field::OptionalLocation(closing_l)),
+            ("unescaped", Field::String(unescaped)),
+        ],
+    )
+}
+
+#[cfg(test)]
+fn loc(begin: usize, end: usize) -> Loc {
+    Loc { begin, end }
+}
+
+#[cfg(test)]
+fn convert_ast(
+    source: &str,
+    ast: Node,
+    magic_comments: Vec<crate::source::MagicComment>,
+) -> PrismNode {
+    let mut input = crate::source::DecodedInput::named("(test)");
+    input.update_bytes(source.as_bytes().to_vec());
+    let result = ParserResult {
+        ast: Some(crate::Ast::new(ast)),
+        tokens: vec![],
+        lex_states: vec![],
+        diagnostics: vec![],
+        comments: vec![],
+        magic_comments,
+        string_content_maps: vec![],
+        input,
+    };
+    convert(&result)
+}
+
+#[cfg(test)]
+fn first_statement(program: &PrismNode) -> &PrismNode {
+    program.child("statements").unwrap().children()[0]
+}
+
+#[cfg(test)]
+fn method_call(name: &str, expression_l: Loc) -> Node {
+    Node::Send(Send {
+        recv: None,
+        method_name: String::from(name),
+        args: vec![],
+        dot_l: None,
+        selector_l: Some(expression_l),
+        begin_l: None,
+        end_l: None,
+        operator_l: None,
+        expression_l,
+    })
+}
+
+#[test]
+fn test_safe_navigation() {
+    // a&.b
+    let ast = Node::CSend(CSend {
+        recv: Box::new(method_call("a", loc(0, 1))),
+        method_name: String::from("b"),
+        args: vec![],
+        dot_l: loc(1, 3),
+        selector_l: Some(loc(3, 4)),
+        begin_l: None,
+        end_l: None,
+        operator_l: None,
+        expression_l: loc(0, 4),
+    });
+    let program = convert_ast("a&.b", ast, vec![]);
+    assert_eq!(
+        first_statement(&program).to_json(),
+        concat!(
+            r#"{"type":"CallNode","location":{"start":0,"end":4},"flags":["SAFE_NAVIGATION"],"#,
+            r#""receiver":{"type":"CallNode","location":{"start":0,"end":1},"flags":["VARIABLE_CALL"],"#,
+            r#""receiver":null,"call_operator_loc":null,"name":"a","message_loc":{"start":0,"end":1},"#,
+            r#""opening_loc":null,"arguments":null,"closing_loc":null,"block":null},"#,
+            r#""call_operator_loc":{"start":1,"end":3},"name":"b","message_loc":{"start":3,"end":4},"#,
+            r#""opening_loc":null,"arguments":null,"closing_loc":null,"block":null}"#
+        )
+    );
+}
+
+#[test]
+fn test_frozen_string_literal() {
+    let str = || {
+        Node::Str(Str {
+            value: crate::Bytes::new(b"a".to_vec()),
+            begin_l: Some(loc(30, 31)),
+            end_l: Some(loc(32, 33)),
+            expression_l: loc(30, 33),
+        })
+    };
+    // `# frozen_string_literal: <value>`
+    let magic_comment = |value_len: usize| crate::source::MagicComment {
+        kind: MagicCommentKind::FrozenStringLiteral,
+        key_l: loc(2, 23),
+        value_l: loc(25, 25 + value_len),
+    };
+
+    let program = convert_ast(
+        "# frozen_string_literal: true\n\"a\"",
+        str(),
+        vec![magic_comment(4)],
+    );
+    let string = first_statement(&program);
+    assert_eq!(string.kind, "StringNode");
+    assert!(string.has_flag("FROZEN"));
+    assert_eq!(
+        string.field("unescaped"),
+        Some(&Field::String(b"a".to_vec()))
+    );
+    assert_eq!(
+        string.field("content_loc"),
+        Some(&Field::Location(loc(31, 32)))
+    );
+
+    let program = convert_ast(
+        "# frozen_string_literal: TRUE\n\"a\"",
+        str(),
+        vec![magic_comment(4)],
+    );
+    assert!(first_statement(&program).has_flag("FROZEN"));
+
+    let program = convert_ast(
+        "# frozen_string_literal: false\n\"a\"",
+        str(),
+        vec![magic_comment(5)],
+    );
+    assert_eq!(first_statement(&program).flags(), &["MUTABLE"]);
+
+    let program = convert_ast("\"a\"", str(), vec![]);
+    assert!(first_statement(&program).flags().is_empty());
+}
+
+#[test]
+fn test_local_variable_depth() {
+    // a = 1; b { a }
+    let ast = Node::Begin(Begin {
+        statements: vec![
+            Node::Lvasgn(Lvasgn {
+                name: String::from("a"),
+                value: Some(Box::new(Node::Int(Int {
+                    value: String::from("1"),
+                    operator_l: None,
+                    expression_l: loc(4, 5),
+                }))),
+                name_l: loc(0, 1),
+                operator_l: Some(loc(2, 3)),
+                expression_l: loc(0, 5),
+            }),
+            Node::Block(Block {
+                call: Box::new(method_call("b", loc(7, 8))),
+                args: None,
+                body: Some(Box::new(Node::Lvar(Lvar {
+                    name: String::from("a"),
+                    expression_l: loc(11, 12),
+                }))),
+                begin_l: loc(9, 10),
+                end_l: loc(13, 14),
+                expression_l: loc(7, 14),
+            }),
+        ],
+        begin_l: None,
+        end_l: None,
+        expression_l: loc(0, 14),
+    });
+    let program = convert_ast("a = 1; b { a }", ast, vec![]);
+    assert_eq!(
+        program.field("locals"),
+        Some(&Field::Constants(vec![String::from("a")]))
+    );
+
+    let statements = program.child("statements").unwrap().children();
+    assert_eq!(statements[0].kind, "LocalVariableWriteNode");
+    assert_eq!(statements[0].field("depth"), Some(&Field::UInt32(0)));
+
+    let call = statements[1];
+    assert_eq!(call.kind, "CallNode");
+    assert_eq!(call.location, loc(7, 14));
+    // `b` with a block is not a variable call
+    assert!(!call.has_flag("VARIABLE_CALL"));
+
+    let block = call.child("block").unwrap();
+    assert_eq!(block.kind, "BlockNode");
+    assert_eq!(block.location, loc(9, 14));
+    assert_eq!(block.field("locals"), Some(&Field::Constants(vec![])));
+    let read = block.child("body").unwrap().children()[0];
+    assert_eq!(read.kind, "LocalVariableReadNode");
+    assert_eq!(read.field("depth"), Some(&Field::UInt32(1)));
+}
+
+#[test]
+fn test_operator_write() {
+    // @a += 1
+    let ast = Node::OpAsgn(OpAsgn {
+        recv: Box::new(Node::Ivasgn(Ivasgn {
+            name: String::from("@a"),
+            value: None,
+            name_l: loc(0, 2),
+            operator_l: None,
+            expression_l: loc(0, 2),
+        })),
+        operator: String::from("+="),
+        value: Box::new(Node::Int(Int {
+            value: String::from("0x1"),
+            operator_l: None,
+            expression_l: loc(6, 9),
+        })),
+        operator_l: loc(3, 5),
+        expression_l: loc(0, 9),
+    });
+    let program = convert_ast("@a += 0x1", ast, vec![]);
+    assert_eq!(
+        first_statement(&program).to_json(),
+        concat!(
+            r#"{"type":"InstanceVariableOperatorWriteNode","location":{"start":0,"end":9},"#,
+            r#""name":"@a","name_loc":{"start":0,"end":2},"binary_operator_loc":{"start":3,"end":5},"#,
+            r#""value":{"type":"IntegerNode","location":{"start":6,"end":9},"flags":["HEXADECIMAL"],"value":1},"#,
+            r#""binary_operator":"+"}"#
+        )
+    );
+}
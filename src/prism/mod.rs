@@ -0,0 +1,5 @@
+mod convert;
+pub use convert::convert;
+
+mod node;
+pub use node::{Field, PrismNode};
@@ -0,0 +1,283 @@
+use crate::numeric::BigInt;
+use crate::Loc;
+
+/// Node of the Prism syntax tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrismNode {
+    /// Type of the node, like `CallNode`
+    pub kind: &'static str,
+
+    /// Location of the whole node
+    pub location: Loc,
+
+    /// Fields of the node in the order of Prism's `config.yml`,
+    /// `flags` (if the node type has them) always go first
+    pub fields: Vec<(&'static str, Field)>,
+}
+
+/// Value of the field of the Prism node
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    /// `flags`, names of the flags that are set, like `SAFE_NAVIGATION`
+    Flags(Vec<&'static str>),
+
+    /// Required child node
+    Node(Box<PrismNode>),
+
+    /// Optional child node
+    OptionalNode(Option<Box<PrismNode>>),
+
+    /// List of child nodes
+    Nodes(Vec<PrismNode>),
+
+    /// Required location, like `keyword_loc`
+    Location(Loc),
+
+    /// Optional location, like `opening_loc`
+    OptionalLocation(Option<Loc>),
+
+    /// Name of the variable, method or constant
+    Constant(String),
+
+    /// Optional name, like a name of the `*rest` parameter
+    OptionalConstant(Option<String>),
+
+    /// List of names, like `locals`
+    Constants(Vec<String>),
+
+    /// Value of the literal, like `unescaped` or `filepath`
+    String(Vec<u8>),
+
+    /// Value of the integer literal
+    Integer(BigInt),
+
+    /// Value of the float literal
+    Double(f64),
+
+    /// Small number, like `depth` of the local variable
+    UInt32(u32),
+}
+
+impl PrismNode {
+    pub(crate) fn new(
+        kind: &'static str,
+        location: Loc,
+        fields: Vec<(&'static str, Field)>,
+    ) -> Self {
+        Self {
+            kind,
+            location,
+            fields,
+        }
+    }
+
+    /// Returns a field with the given name
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields
+            .iter()
+            .find(|(field_name, _)| *field_name == name)
+            .map(|(_, field)| field)
+    }
+
+    pub(crate) fn field_mut(&mut self, name: &str) -> Option<&mut Field> {
+        self.fields
+            .iter_mut()
+            .find(|(field_name, _)| *field_name == name)
+            .map(|(_, field)| field)
+    }
+
+    /// Returns a child node stored in the field with the given name
+    /// (`None` if there's no such field or if the optional node is absent)
+    pub fn child(&self, name: &str) -> Option<&PrismNode> {
+        match self.field(name)? {
+            Field::Node(node) => Some(node),
+            Field::OptionalNode(node) => node.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns names of the flags that are set
+    pub fn flags(&self) -> &[&'static str] {
+        match self.field("flags") {
+            Some(Field::Flags(flags)) => flags,
+            _ => &[],
+        }
+    }
+
+    /// Returns `true` if the flag with the given name (like `FROZEN`) is set
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags().contains(&flag)
+    }
+
+    /// Returns all child nodes in the order of fields
+    pub fn children(&self) -> Vec<&PrismNode> {
+        let mut children = vec![];
+        for (_, field) in self.fields.iter() {
+            match field {
+                Field::Node(node) => children.push(&**node),
+                Field::OptionalNode(Some(node)) => children.push(&**node),
+                Field::Nodes(nodes) => children.extend(nodes.iter()),
+                _ => {}
+            }
+        }
+        children
+    }
+
+    /// Serializes the node the same way as `Prism::Dump`'s JSON output
+    /// (`pm_dump_json`) does it, i.e.
+    /// `{"type":"IntegerNode","location":{"start":0,"end":1},"flags":["DECIMAL"],"value":1}`.
+    ///
+    /// Bytes of strings that are not valid UTF-8 are written as `\u00XX`,
+    /// non-finite floats are written as strings (`"Infinity"`).
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        write_node(&mut out, self);
+        out
+    }
+}
+
+fn write_node(out: &mut String, node: &PrismNode) {
+    out.push_str("{\"type\":\"");
+    out.push_str(node.kind);
+    out.push_str("\",\"location\":");
+    write_location(out, &node.location);
+    for (name, field) in node.fields.iter() {
+        out.push_str(",\"");
+        out.push_str(name);
+        out.push_str("\":");
+        write_field(out, field);
+    }
+    out.push('}');
+}
+
+fn write_location(out: &mut String, loc: &Loc) {
+    out.push_str(&format!("{{\"start\":{},\"end\":{}}}", loc.begin, loc.end));
+}
+
+fn write_list<T>(out: &mut String, items: &[T], mut f: impl FnMut(&mut String, &T)) {
+    out.push('[');
+    for (idx, item) in items.iter().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+        f(out, item);
+    }
+    out.push(']');
+}
+
+fn write_field(out: &mut String, field: &Field) {
+    match field {
+        Field::Flags(flags) => {
+            write_list(out, flags, |out, flag| write_string(out, flag.as_bytes()))
+        }
+        Field::Node(node) | Field::OptionalNode(Some(node)) => write_node(out, node),
+        Field::Nodes(nodes) => write_list(out, nodes, write_node),
+        Field::Location(loc) | Field::OptionalLocation(Some(loc)) => write_location(out, loc),
+        Field::Constant(name) | Field::OptionalConstant(Some(name)) => {
+            write_string(out, name.as_bytes())
+        }
+        Field::Constants(names) => {
+            write_list(out, names, |out, name| write_string(out, name.as_bytes()))
+        }
+        Field::String(bytes) => write_string(out, bytes),
+        Field::Integer(value) => out.push_str(&value.to_string()),
+        Field::Double(value) if value.is_finite() => out.push_str(&format!("{:?}", value)),
+        Field::Double(value) => {
+            let name = if value.is_nan() {
+                "NaN"
+            } else if value.is_sign_negative() {
+                "-Infinity"
+            } else {
+                "Infinity"
+            };
+            write_string(out, name.as_bytes())
+        }
+        Field::UInt32(value) => out.push_str(&value.to_string()),
+        Field::OptionalNode(None)
+        | Field::OptionalLocation(None)
+        | Field::OptionalConstant(None) => out.push_str("null"),
+    }
+}
+
+fn write_string(out: &mut String, bytes: &[u8]) {
+    out.push('"');
+    for chunk in bytes.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                '\u{8}' => out.push_str("\\b"),
+                '\u{c}' => out.push_str("\\f"),
+                c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                    out.push_str(&format!("\\u{:04x}", c as u32))
+                }
+                c => out.push(c),
+            }
+        }
+        for byte in chunk.invalid() {
+            out.push_str(&format!("\\u{:04x}", byte));
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+fn loc(begin: usize, end: usize) -> Loc {
+    Loc { begin, end }
+}
+
+#[test]
+fn test_to_json() {
+    let receiver = PrismNode::new(
+        "IntegerNode",
+        loc(0, 1),
+        vec![
+            ("flags", Field::Flags(vec!["DECIMAL"])),
+            ("value", Field::Integer(BigInt::from(1))),
+        ],
+    );
+    let node = PrismNode::new(
+        "CallNode",
+        loc(0, 5),
+        vec![
+            ("flags", Field::Flags(vec![])),
+            ("receiver", Field::OptionalNode(Some(Box::new(receiver)))),
+            (
+                "call_operator_loc",
+                Field::OptionalLocation(Some(loc(1, 2))),
+            ),
+            ("name", Field::Constant(String::from("to_s"))),
+            ("arguments", Field::OptionalNode(None)),
+        ],
+    );
+    assert_eq!(
+        node.to_json(),
+        concat!(
+            r#"{"type":"CallNode","location":{"start":0,"end":5},"flags":[],"#,
+            r#""receiver":{"type":"IntegerNode","location":{"start":0,"end":1},"flags":["DECIMAL"],"value":1},"#,
+            r#""call_operator_loc":{"start":1,"end":2},"name":"to_s","arguments":null}"#
+        )
+    );
+    assert_eq!(node.children(), vec![node.child("receiver").unwrap()]);
+    assert!(node.child("receiver").unwrap().has_flag("DECIMAL"));
+    assert!(!node.has_flag("SAFE_NAVIGATION"));
+}
+
+#[test]
+fn test_write_string() {
+    let mut out = String::new();
+    write_string(&mut out, b"a\"b\\c\n\x01\xff\xc3\xa9");
+    assert_eq!(out, r#""a\"b\\c\n\u0001\u00ffé""#);
+}
+
+#[test]
+fn test_write_double() {
+    let mut out = String::new();
+    write_field(&mut out, &Field::Double(1.5));
+    write_field(&mut out, &Field::Double(1.0));
+    write_field(&mut out, &Field::Double(f64::INFINITY));
+    assert_eq!(out, r#"1.51.0"Infinity""#);
+}
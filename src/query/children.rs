@@ -0,0 +1,75 @@
+// Helpers for `children_gen.rs`, children are pushed in the same order as `Node::inspect` prints them
+
+use super::Value;
+use crate::{Bytes, Node};
+
+pub(super) struct Children<'a> {
+    values: Vec<Value<'a>>,
+}
+
+impl<'a> Children<'a> {
+    pub(super) fn new() -> Self {
+        Self { values: vec![] }
+    }
+
+    pub(super) fn into_values(self) -> Vec<Value<'a>> {
+        self.values
+    }
+
+    pub(super) fn node(&mut self, node: &'a Node) {
+        self.values.push(Value::Node(node))
+    }
+
+    pub(super) fn maybe_node(&mut self, node: &'a Option<Box<Node>>) {
+        if let Some(node) = node.as_deref() {
+            self.node(node)
+        }
+    }
+
+    pub(super) fn maybe_node_or_nil(&mut self, node: &'a Option<Box<Node>>) {
+        match node.as_deref() {
+            Some(node) => self.node(node),
+            None => self.values.push(Value::Nil),
+        }
+    }
+
+    pub(super) fn nodes(&mut self, nodes: &'a [Node]) {
+        for node in nodes {
+            self.node(node)
+        }
+    }
+
+    pub(super) fn str(&mut self, s: &'a str) {
+        self.values.push(Value::Str(s))
+    }
+
+    pub(super) fn maybe_str(&mut self, s: &'a Option<String>) {
+        if let Some(s) = s.as_deref() {
+            self.str(s)
+        }
+    }
+
+    pub(super) fn maybe_str_or_nil(&mut self, s: &'a Option<String>) {
+        match s.as_deref() {
+            Some(s) => self.str(s),
+            None => self.values.push(Value::Nil),
+        }
+    }
+
+    // every char is a separate child, like in `Node::inspect` of `RegOpt`
+    pub(super) fn chars(&mut self, s: &'a Option<String>) {
+        if let Some(s) = s.as_deref() {
+            for (idx, c) in s.char_indices() {
+                self.str(&s[idx..idx + c.len_utf8()])
+            }
+        }
+    }
+
+    pub(super) fn bytes(&mut self, bytes: &'a Bytes) {
+        self.values.push(Value::Bytes(bytes))
+    }
+
+    pub(super) fn u8(&mut self, n: &u8) {
+        self.values.push(Value::Integer(*n))
+    }
+}
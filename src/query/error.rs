@@ -0,0 +1,46 @@
+use crate::Loc;
+
+/// Error that is returned if a query pattern is invalid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    /// Message of the error
+    pub message: String,
+
+    /// Location of the part of the pattern that caused the error
+    pub loc: Loc,
+}
+
+impl QueryError {
+    pub(crate) fn new<S: Into<String>>(message: S, loc: Loc) -> Self {
+        Self {
+            message: message.into(),
+            loc,
+        }
+    }
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+pub(crate) const UNEXPECTED_END_OF_PATTERN: &str = "unexpected end of pattern";
+pub(crate) const EMPTY_NODE_PATTERN: &str = "node pattern must have a type";
+pub(crate) const UNTERMINATED_STRING: &str = "unterminated string";
+pub(crate) const MISPLACED_REST: &str = "`...` is allowed only in a list of children";
+pub(crate) const EMPTY_LIST: &str = "list of patterns can't be empty";
+
+pub(crate) fn unexpected_char(c: char) -> String {
+    format!("unexpected `{}`", c)
+}
+
+pub(crate) fn unknown_node_type(name: &str) -> String {
+    format!("unknown node type `{}`", name)
+}
+
+pub(crate) fn unknown_predicate(name: &str) -> String {
+    format!("unknown predicate `#{}`", name)
+}
@@ -0,0 +1,441 @@
+mod children;
+
+mod children_gen;
+pub use children_gen::children;
+pub(crate) use children_gen::NODE_TYPES;
+
+mod error;
+pub use error::QueryError;
+
+mod pattern;
+use pattern::Pattern;
+
+use std::collections::HashMap;
+
+use crate::{Bytes, Loc, Node};
+
+/// Child of the node, an item of the list that `Node::inspect` prints
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value<'a> {
+    /// Child node
+    Node(&'a Node),
+
+    /// Absent optional child node (like a receiver of `foo()`) or name (like a name of `*`)
+    Nil,
+
+    /// Name of a method/variable/constant, value of a numeric literal or a regexp option
+    Str(&'a str),
+
+    /// Value of a string or a symbol literal
+    Bytes(&'a Bytes),
+
+    /// Number of parameters of `Numblock`
+    Integer(u8),
+}
+
+impl<'a> Value<'a> {
+    /// Returns location of the node, `None` for all other values
+    pub fn loc(&self) -> Option<Loc> {
+        match self {
+            Self::Node(node) => Some(*node.expression()),
+            _ => None,
+        }
+    }
+
+    fn as_node(&self) -> Option<&'a Node> {
+        match self {
+            Self::Node(node) => Some(node),
+            _ => None,
+        }
+    }
+}
+
+/// Custom predicate that can be used in a query as `#name`
+pub type Predicate = Box<dyn Fn(&Value<'_>) -> bool>;
+
+/// Match of a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryMatch<'a> {
+    /// Matched node
+    pub node: &'a Node,
+
+    /// Location of the matched node
+    pub loc: Loc,
+
+    /// Captured values in order of their appearance in the pattern
+    pub captures: Vec<Value<'a>>,
+}
+
+/// Compiled query, a pattern that matches nodes.
+///
+/// Syntax is similar to RuboCop's `NodePattern`:
+///
+/// + `(send nil :foo ...)` matches a node: the first item is matched against the node itself
+///   and all other items are matched against its children (see `query::children`)
+/// + `send` matches a node of the given type (see `Node::str_type`)
+/// + `_` matches anything, `...` matches any number of children (only in a list of children)
+/// + `nil` matches an absent child
+/// + `:foo`, `"foo"` and `42` match names and values of literals
+/// + `{a b}` matches if any of patterns matches, `[a b]` matches if all of them match
+/// + `!a` matches if `a` doesn't match
+/// + `$a` captures a value that matches `a`
+/// + `#name` calls a predicate, `#literal` is built-in,
+///   custom predicates can be passed to `Query::with_predicates`
+/// + `` `a `` matches a node if it or any of its descendants matches `a`
+///
+/// ```
+/// use lib_ruby_parser::query::{Query, Value};
+/// use lib_ruby_parser::{Loc, Parser, ParserOptions};
+///
+/// let parser = Parser::new(b"require 'foo'; require(bar)".to_vec(), ParserOptions::default());
+/// let ast = parser.do_parse().ast.unwrap();
+///
+/// let query = Query::new("(send nil :require (str $_))").unwrap();
+/// let matches = query.find_all(&ast);
+///
+/// assert_eq!(matches.len(), 1);
+/// assert_eq!(matches[0].loc, Loc { begin: 0, end: 13 });
+/// match matches[0].captures[0] {
+///     Value::Bytes(bytes) => assert_eq!(bytes.as_raw(), b"foo"),
+///     other => panic!("unexpected capture {:?}", other),
+/// }
+/// ```
+pub struct Query {
+    pattern: Pattern,
+    predicates: HashMap<String, Predicate>,
+}
+
+impl Query {
+    /// Compiles a query that uses only built-in predicates
+    pub fn new(pattern: &str) -> Result<Self, QueryError> {
+        Self::with_predicates(pattern, HashMap::new())
+    }
+
+    /// Compiles a query that can use given custom predicates (in addition to built-in ones)
+    pub fn with_predicates(
+        pattern: &str,
+        predicates: HashMap<String, Predicate>,
+    ) -> Result<Self, QueryError> {
+        let mut all_predicates: HashMap<String, Predicate> = HashMap::new();
+        all_predicates.insert(String::from("literal"), Box::new(is_literal));
+        all_predicates.extend(predicates);
+
+        let pattern = pattern::parse(pattern, |name| all_predicates.contains_key(name))?;
+        Ok(Self {
+            pattern,
+            predicates: all_predicates,
+        })
+    }
+
+    /// Matches the node, returns captured values if it matches
+    pub fn matches<'a>(&self, node: &'a Node) -> Option<Vec<Value<'a>>> {
+        let mut captures = vec![];
+        if self.match_value(&self.pattern, &Value::Node(node), &mut captures) {
+            Some(captures)
+        } else {
+            None
+        }
+    }
+
+    /// Returns all matching nodes of the AST (including `root`), parents go before their children
+    pub fn find_all<'a>(&self, root: &'a Node) -> Vec<QueryMatch<'a>> {
+        let mut matches = vec![];
+        self.find_all_in(root, &mut matches);
+        matches
+    }
+
+    fn find_all_in<'a>(&self, node: &'a Node, matches: &mut Vec<QueryMatch<'a>>) {
+        if let Some(captures) = self.matches(node) {
+            matches.push(QueryMatch {
+                node,
+                loc: *node.expression(),
+                captures,
+            })
+        }
+        for child in children(node) {
+            if let Value::Node(child) = child {
+                self.find_all_in(child, matches);
+            }
+        }
+    }
+
+    fn match_value<'a>(
+        &self,
+        pattern: &Pattern,
+        value: &Value<'a>,
+        captures: &mut Vec<Value<'a>>,
+    ) -> bool {
+        match pattern {
+            Pattern::Any => true,
+            // `...` is matched in `match_children`
+            Pattern::Rest => false,
+            Pattern::Nil => *value == Value::Nil,
+            Pattern::Literal(literal) => match value {
+                Value::Str(s) => s.as_bytes() == literal.as_slice(),
+                Value::Bytes(bytes) => bytes.as_raw() == literal,
+                Value::Integer(n) => n.to_string().as_bytes() == literal.as_slice(),
+                Value::Node(_) | Value::Nil => false,
+            },
+            Pattern::NodeType(node_type) => value
+                .as_node()
+                .is_some_and(|node| node.str_type() == *node_type),
+            Pattern::Node(head, items) => match value.as_node() {
+                Some(node) => {
+                    let len = captures.len();
+                    let matches = self.match_value(head, value, captures)
+                        && self.match_children(items, &children(node), captures);
+                    if !matches {
+                        captures.truncate(len);
+                    }
+                    matches
+                }
+                None => false,
+            },
+            Pattern::Alternatives(patterns) => patterns.iter().any(|pattern| {
+                let len = captures.len();
+                let matches = self.match_value(pattern, value, captures);
+                if !matches {
+                    captures.truncate(len);
+                }
+                matches
+            }),
+            Pattern::All(patterns) => {
+                let len = captures.len();
+                let matches = patterns
+                    .iter()
+                    .all(|pattern| self.match_value(pattern, value, captures));
+                if !matches {
+                    captures.truncate(len);
+                }
+                matches
+            }
+            Pattern::Capture(pattern) => {
+                // outer captures go before inner captures
+                let len = captures.len();
+                captures.push(*value);
+                let matches = self.match_value(pattern, value, captures);
+                if !matches {
+                    captures.truncate(len);
+                }
+                matches
+            }
+            Pattern::Not(pattern) => {
+                let len = captures.len();
+                let matches = self.match_value(pattern, value, captures);
+                captures.truncate(len);
+                !matches
+            }
+            Pattern::Predicate(name) => self
+                .predicates
+                .get(name)
+                .is_some_and(|predicate| predicate(value)),
+            Pattern::Descendant(pattern) => match value.as_node() {
+                Some(node) => self.match_descendant(pattern, node, captures),
+                None => false,
+            },
+        }
+    }
+
+    fn match_children<'a>(
+        &self,
+        patterns: &[Pattern],
+        values: &[Value<'a>],
+        captures: &mut Vec<Value<'a>>,
+    ) -> bool {
+        match patterns.split_first() {
+            None => values.is_empty(),
+            Some((Pattern::Rest, patterns)) => (0..=values.len()).any(|skip| {
+                let len = captures.len();
+                let matches = self.match_children(patterns, &values[skip..], captures);
+                if !matches {
+                    captures.truncate(len);
+                }
+                matches
+            }),
+            Some((pattern, patterns)) => match values.split_first() {
+                Some((value, values)) => {
+                    let len = captures.len();
+                    let matches = self.match_value(pattern, value, captures)
+                        && self.match_children(patterns, values, captures);
+                    if !matches {
+                        captures.truncate(len);
+                    }
+                    matches
+                }
+                None => false,
+            },
+        }
+    }
+
+    fn match_descendant<'a>(
+        &self,
+        pattern: &Pattern,
+        node: &'a Node,
+        captures: &mut Vec<Value<'a>>,
+    ) -> bool {
+        let len = captures.len();
+        if self.match_value(pattern, &Value::Node(node), captures) {
+            return true;
+        }
+        captures.truncate(len);
+        children(node).iter().any(|child| match child {
+            Value::Node(child) => self.match_descendant(pattern, child, captures),
+            _ => false,
+        })
+    }
+}
+
+impl std::fmt::Debug for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut predicates = self.predicates.keys().collect::<Vec<_>>();
+        predicates.sort();
+        f.debug_struct("Query")
+            .field("pattern", &self.pattern)
+            .field("predicates", &predicates)
+            .finish()
+    }
+}
+
+fn is_literal(value: &Value<'_>) -> bool {
+    value.as_node().is_some_and(|node| {
+        matches!(
+            node,
+            Node::Int(_)
+                | Node::Float(_)
+                | Node::Rational(_)
+                | Node::Complex(_)
+                | Node::Str(_)
+                | Node::Sym(_)
+                | Node::Nil(_)
+                | Node::True(_)
+                | Node::False(_)
+        )
+    })
+}
+
+#[cfg(test)]
+fn test_ast() -> Node {
+    use crate::nodes::{Begin, Int, Send, Str};
+
+    // require 'foo'; require(bar, 1)
+    let loc = |begin, end| Loc { begin, end };
+    let send = |method_name: &str, args, expression_l| {
+        Node::Send(Send {
+            recv: None,
+            method_name: String::from(method_name),
+            args,
+            dot_l: None,
+            selector_l: None,
+            begin_l: None,
+            end_l: None,
+            operator_l: None,
+            expression_l,
+        })
+    };
+    Node::Begin(Begin {
+        statements: vec![
+            send(
+                "require",
+                vec![Node::Str(Str {
+                    value: Bytes::new(b"foo".to_vec()),
+                    begin_l: Some(loc(8, 9)),
+                    end_l: Some(loc(12, 13)),
+                    expression_l: loc(8, 13),
+                })],
+                loc(0, 13),
+            ),
+            send(
+                "require",
+                vec![
+                    send("bar", vec![], loc(23, 26)),
+                    Node::Int(Int {
+                        value: String::from("1"),
+                        operator_l: None,
+                        expression_l: loc(28, 29),
+                    }),
+                ],
+                loc(15, 30),
+            ),
+        ],
+        begin_l: None,
+        end_l: None,
+        expression_l: loc(0, 30),
+    })
+}
+
+#[cfg(test)]
+fn find_locs(pattern: &str, ast: &Node) -> Vec<(usize, usize)> {
+    Query::new(pattern)
+        .unwrap()
+        .find_all(ast)
+        .iter()
+        .map(|m| (m.loc.begin, m.loc.end))
+        .collect()
+}
+
+#[test]
+fn test_find_all() {
+    let ast = test_ast();
+
+    assert_eq!(
+        find_locs("(send nil :require (str _))", &ast),
+        vec![(0, 13)]
+    );
+    assert_eq!(
+        find_locs("(send nil :require ...)", &ast),
+        vec![(0, 13), (15, 30)]
+    );
+    assert_eq!(find_locs("(send _ _ ... (int 1))", &ast), vec![(15, 30)]);
+    assert_eq!(find_locs("(send nil {:bar :baz})", &ast), vec![(23, 26)]);
+    assert_eq!(
+        find_locs("[send !(send _ :require ...)]", &ast),
+        vec![(23, 26)]
+    );
+    assert_eq!(find_locs("(send nil _ #literal)", &ast), vec![(0, 13)]);
+    assert_eq!(find_locs("[begin `(int 1)]", &ast), vec![(0, 30)]);
+    assert_eq!(find_locs("(str \"bar\")", &ast), vec![]);
+}
+
+#[test]
+fn test_captures() {
+    let ast = test_ast();
+
+    let query = Query::new("$(send nil :require $_ ...)").unwrap();
+    let matches = query.find_all(&ast);
+    assert_eq!(matches.len(), 2);
+    assert_eq!(
+        matches[1]
+            .captures
+            .iter()
+            .map(|value| value.loc())
+            .collect::<Vec<_>>(),
+        vec![
+            Some(Loc { begin: 15, end: 30 }),
+            Some(Loc { begin: 23, end: 26 })
+        ]
+    );
+
+    // captures of failed alternatives are dropped
+    let query = Query::new("(send nil _ {(send nil $_ 2) (send nil $_)} ...)").unwrap();
+    let matches = query.find_all(&ast);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].captures, vec![Value::Str("bar")]);
+}
+
+#[test]
+fn test_custom_predicates() {
+    let ast = test_ast();
+
+    let mut predicates: HashMap<String, Predicate> = HashMap::new();
+    predicates.insert(
+        String::from("short"),
+        Box::new(|value| value.loc().is_some_and(|loc| loc.end - loc.begin < 5)),
+    );
+    let query = Query::with_predicates("[send #short]", predicates).unwrap();
+    assert_eq!(query.find_all(&ast).len(), 1);
+
+    assert_eq!(
+        Query::new("#short").unwrap_err().message,
+        "unknown predicate `#short`"
+    );
+}
@@ -0,0 +1,336 @@
+use super::error::*;
+use super::NODE_TYPES;
+use crate::Loc;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Pattern {
+    // `_`
+    Any,
+    // `...`
+    Rest,
+    // `nil`
+    Nil,
+    // `:foo`, `"foo"` or `42`
+    Literal(Vec<u8>),
+    // `send`
+    NodeType(&'static str),
+    // `(send nil :foo ...)`, the first pattern is matched against the node itself
+    Node(Box<Pattern>, Vec<Pattern>),
+    // `{a b}`
+    Alternatives(Vec<Pattern>),
+    // `[a b]`
+    All(Vec<Pattern>),
+    // `$a`
+    Capture(Box<Pattern>),
+    // `!a`
+    Not(Box<Pattern>),
+    // `#foo`
+    Predicate(String),
+    // `` `a ``
+    Descendant(Box<Pattern>),
+}
+
+pub(crate) fn parse<F>(source: &str, has_predicate: F) -> Result<Pattern, QueryError>
+where
+    F: Fn(&str) -> bool,
+{
+    let mut parser = Parser {
+        source: source.as_bytes(),
+        pos: 0,
+        has_predicate: &has_predicate,
+    };
+    let pattern = parser.parse_pattern()?;
+    parser.skip_whitespaces();
+    if let Some(c) = parser.peek() {
+        return Err(parser.unexpected_char(c));
+    }
+    if pattern == Pattern::Rest {
+        return Err(QueryError::new(
+            MISPLACED_REST,
+            Loc {
+                begin: 0,
+                end: source.len(),
+            },
+        ));
+    }
+    Ok(pattern)
+}
+
+struct Parser<'a> {
+    source: &'a [u8],
+    pos: usize,
+    has_predicate: &'a dyn Fn(&str) -> bool,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.pos).copied()
+    }
+
+    fn loc(&self, begin: usize) -> Loc {
+        Loc {
+            begin,
+            end: self.pos,
+        }
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn unexpected_char(&self, c: u8) -> QueryError {
+        QueryError::new(
+            unexpected_char(c as char),
+            Loc {
+                begin: self.pos,
+                end: self.pos + 1,
+            },
+        )
+    }
+
+    fn unexpected_end(&self) -> QueryError {
+        QueryError::new(
+            UNEXPECTED_END_OF_PATTERN,
+            Loc {
+                begin: self.pos,
+                end: self.pos,
+            },
+        )
+    }
+
+    // Reads chars until a whitespace or a delimiter
+    fn read_word(&mut self) -> &str {
+        let begin = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_ascii_whitespace() && !b"(){}[]$!#`\"".contains(&c))
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.source[begin..self.pos]).unwrap_or_default()
+    }
+
+    fn parse_pattern(&mut self) -> Result<Pattern, QueryError> {
+        self.skip_whitespaces();
+        let begin = self.pos;
+        let c = self.peek().ok_or_else(|| self.unexpected_end())?;
+
+        match c {
+            b'(' => {
+                self.pos += 1;
+                let mut items = self.parse_list(b')')?;
+                if items.is_empty() {
+                    return Err(QueryError::new(EMPTY_NODE_PATTERN, self.loc(begin)));
+                }
+                let head = items.remove(0);
+                if head == Pattern::Rest {
+                    return Err(QueryError::new(MISPLACED_REST, self.loc(begin)));
+                }
+                Ok(Pattern::Node(Box::new(head), items))
+            }
+            b'{' => {
+                self.pos += 1;
+                let items = self.parse_inner_list(b'}', begin)?;
+                Ok(Pattern::Alternatives(items))
+            }
+            b'[' => {
+                self.pos += 1;
+                let items = self.parse_inner_list(b']', begin)?;
+                Ok(Pattern::All(items))
+            }
+            b'$' => {
+                self.pos += 1;
+                Ok(Pattern::Capture(Box::new(self.parse_inner_pattern()?)))
+            }
+            b'!' => {
+                self.pos += 1;
+                Ok(Pattern::Not(Box::new(self.parse_inner_pattern()?)))
+            }
+            b'`' => {
+                self.pos += 1;
+                Ok(Pattern::Descendant(Box::new(self.parse_inner_pattern()?)))
+            }
+            b'#' => {
+                self.pos += 1;
+                let name = self.read_word().to_string();
+                if !(self.has_predicate)(&name) {
+                    return Err(QueryError::new(unknown_predicate(&name), self.loc(begin)));
+                }
+                Ok(Pattern::Predicate(name))
+            }
+            b'"' => self.parse_string(),
+            b':' => {
+                self.pos += 1;
+                if self.peek() == Some(b'"') {
+                    return self.parse_string();
+                }
+                // `:[]` and `:[]=` are method names, not conjunctions
+                if self.source[self.pos..].starts_with(b"[]") {
+                    self.pos += 2;
+                    if self.peek() == Some(b'=') {
+                        self.pos += 1;
+                    }
+                    return Ok(Pattern::Literal(self.source[begin + 1..self.pos].to_vec()));
+                }
+                let name = self.read_word();
+                if name.is_empty() {
+                    return Err(self.unexpected_char(b':'));
+                }
+                Ok(Pattern::Literal(name.as_bytes().to_vec()))
+            }
+            b')' | b'}' | b']' => Err(self.unexpected_char(c)),
+            _ => {
+                let word = self.read_word();
+                match word {
+                    "" => Err(self.unexpected_char(c)),
+                    "_" => Ok(Pattern::Any),
+                    "..." => Ok(Pattern::Rest),
+                    "nil" => Ok(Pattern::Nil),
+                    _ if c == b'-' || c.is_ascii_digit() => {
+                        Ok(Pattern::Literal(word.as_bytes().to_vec()))
+                    }
+                    _ => match NODE_TYPES.iter().find(|node_type| **node_type == word) {
+                        Some(node_type) => Ok(Pattern::NodeType(node_type)),
+                        None => Err(QueryError::new(unknown_node_type(word), self.loc(begin))),
+                    },
+                }
+            }
+        }
+    }
+
+    // Parses a pattern that can't be `...`
+    fn parse_inner_pattern(&mut self) -> Result<Pattern, QueryError> {
+        self.skip_whitespaces();
+        let begin = self.pos;
+        let pattern = self.parse_pattern()?;
+        if pattern == Pattern::Rest {
+            return Err(QueryError::new(MISPLACED_REST, self.loc(begin)));
+        }
+        Ok(pattern)
+    }
+
+    // Parses a list of patterns until `close`, `(` is already consumed
+    fn parse_list(&mut self, close: u8) -> Result<Vec<Pattern>, QueryError> {
+        let mut items = vec![];
+        loop {
+            self.skip_whitespaces();
+            match self.peek() {
+                None => return Err(self.unexpected_end()),
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    return Ok(items);
+                }
+                Some(_) => items.push(self.parse_pattern()?),
+            }
+        }
+    }
+
+    // Parses a non-empty list of patterns that can't be `...`
+    fn parse_inner_list(&mut self, close: u8, begin: usize) -> Result<Vec<Pattern>, QueryError> {
+        let items = self.parse_list(close)?;
+        if items.is_empty() {
+            return Err(QueryError::new(EMPTY_LIST, self.loc(begin)));
+        }
+        if items.contains(&Pattern::Rest) {
+            return Err(QueryError::new(MISPLACED_REST, self.loc(begin)));
+        }
+        Ok(items)
+    }
+
+    // Parses `"..."`, supports `\"`, `\\`, `\n` and `\t` escapes
+    fn parse_string(&mut self) -> Result<Pattern, QueryError> {
+        let begin = self.pos;
+        self.pos += 1;
+        let mut value = vec![];
+        loop {
+            match self.peek() {
+                None => return Err(QueryError::new(UNTERMINATED_STRING, self.loc(begin))),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(Pattern::Literal(value));
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let c = self
+                        .peek()
+                        .ok_or_else(|| QueryError::new(UNTERMINATED_STRING, self.loc(begin)))?;
+                    value.push(match c {
+                        b'n' => b'\n',
+                        b't' => b'\t',
+                        _ => c,
+                    });
+                    self.pos += 1;
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parse() {
+    let pattern = parse("(send nil :require (str $_) ...)", |_| false).unwrap();
+    assert_eq!(
+        pattern,
+        Pattern::Node(
+            Box::new(Pattern::NodeType("send")),
+            vec![
+                Pattern::Nil,
+                Pattern::Literal(b"require".to_vec()),
+                Pattern::Node(
+                    Box::new(Pattern::NodeType("str")),
+                    vec![Pattern::Capture(Box::new(Pattern::Any))]
+                ),
+                Pattern::Rest
+            ]
+        )
+    );
+
+    let pattern = parse("{:[] \"a\\\"\" -1 !#foo `int}", |name| name == "foo").unwrap();
+    assert_eq!(
+        pattern,
+        Pattern::Alternatives(vec![
+            Pattern::Literal(b"[]".to_vec()),
+            Pattern::Literal(b"a\"".to_vec()),
+            Pattern::Literal(b"-1".to_vec()),
+            Pattern::Not(Box::new(Pattern::Predicate(String::from("foo")))),
+            Pattern::Descendant(Box::new(Pattern::NodeType("int"))),
+        ])
+    );
+
+    let pattern = parse("(defined? _)", |_| false).unwrap();
+    assert_eq!(
+        pattern,
+        Pattern::Node(Box::new(Pattern::NodeType("defined?")), vec![Pattern::Any])
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = |source: &str| {
+        let error = parse(source, |_| false).unwrap_err();
+        (error.message, error.loc.begin, error.loc.end)
+    };
+
+    assert_eq!(
+        error("(send"),
+        (String::from(UNEXPECTED_END_OF_PATTERN), 5, 5)
+    );
+    assert_eq!(error("()"), (String::from(EMPTY_NODE_PATTERN), 0, 2));
+    assert_eq!(
+        error("(sned)"),
+        (String::from("unknown node type `sned`"), 1, 5)
+    );
+    assert_eq!(
+        error("#foo"),
+        (String::from("unknown predicate `#foo`"), 0, 4)
+    );
+    assert_eq!(error("{int ...}"), (String::from(MISPLACED_REST), 0, 9));
+    assert_eq!(error("int)"), (String::from("unexpected `)`"), 3, 4));
+    assert_eq!(error("\"abc"), (String::from(UNTERMINATED_STRING), 0, 4));
+}
@@ -0,0 +1,505 @@
+use crate::Loc;
+
+/// Node of a regexp syntax tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegexpNode {
+    /// `a|b|c`
+    Alternation(Alternation),
+
+    /// `abc`, a list of nodes that are matched one after another
+    Sequence(Sequence),
+
+    /// A single character like `a`, `\.`, `\n`, `\x41`
+    Literal(Literal),
+
+    /// `.`, `\d`, `\w`, `\s`, `\h`, `\R`, `\X` (and their negations)
+    CharType(CharType),
+
+    /// `\p{Alpha}`, `\P{Alpha}`, `\p{^Alpha}`
+    Property(Property),
+
+    /// `[a-z]`
+    CharClass(CharClass),
+
+    /// `^`, `$`, `\A`, `\z`, `\Z`, `\b`, `\B`, `\G`, `\K`
+    Anchor(Anchor),
+
+    /// `\1`, `\k<name>`, `\k<-1>`
+    Backref(Backref),
+
+    /// `\g<name>`, `\g<1>`
+    SubexpCall(SubexpCall),
+
+    /// `(...)`, `(?<name>...)`, `(?:...)`, lookarounds etc
+    Group(Group),
+
+    /// `(?(cond)yes|no)`
+    Conditional(Conditional),
+
+    /// `a*`, `a+?`, `a{1,2}`
+    Quantified(Quantified),
+
+    /// `(?imx-imx)`, applies given options to the rest of the enclosing group
+    Options(Options),
+
+    /// `(?#comment)` or `# comment` in extended mode
+    Comment(Comment),
+}
+
+impl RegexpNode {
+    /// Returns location of the whole node
+    pub fn expression(&self) -> &Loc {
+        match self {
+            Self::Alternation(node) => &node.expression_l,
+            Self::Sequence(node) => &node.expression_l,
+            Self::Literal(node) => &node.expression_l,
+            Self::CharType(node) => &node.expression_l,
+            Self::Property(node) => &node.expression_l,
+            Self::CharClass(node) => &node.expression_l,
+            Self::Anchor(node) => &node.expression_l,
+            Self::Backref(node) => &node.expression_l,
+            Self::SubexpCall(node) => &node.expression_l,
+            Self::Group(node) => &node.expression_l,
+            Self::Conditional(node) => &node.expression_l,
+            Self::Quantified(node) => &node.expression_l,
+            Self::Options(node) => &node.expression_l,
+            Self::Comment(node) => &node.expression_l,
+        }
+    }
+}
+
+/// `a|b|c`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alternation {
+    /// List of alternatives, always has at least 2 items
+    pub alternatives: Vec<RegexpNode>,
+
+    /// Locations of `|` operators
+    pub pipes_l: Vec<Loc>,
+
+    /// Location of the whole alternation
+    pub expression_l: Loc,
+}
+
+/// A list of nodes that are matched one after another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequence {
+    /// Items of the sequence, can be empty
+    pub items: Vec<RegexpNode>,
+
+    /// Location of the whole sequence
+    pub expression_l: Loc,
+}
+
+/// Kind of the literal, i.e. how it's written in the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiteralKind {
+    /// A plain character like `a`
+    Plain,
+
+    /// An escaped character that means itself, like `\.` or `\a`
+    Escaped,
+
+    /// A special escape like `\n`, `\t`, `\e`
+    Special,
+
+    /// `\x41`
+    Hex,
+
+    /// `\101`
+    Octal,
+
+    /// `\u0041`, `\u{41}`
+    Unicode,
+
+    /// `\cA`, `\C-a`
+    Control,
+
+    /// `\M-a`, `\M-\C-a`
+    Meta,
+}
+
+/// A single character
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Literal {
+    /// Code point (or a byte for `\xHH` and octal escapes)
+    pub value: u32,
+
+    /// How the character is written in the source
+    pub kind: LiteralKind,
+
+    /// Location of the character (including escape sequence)
+    pub expression_l: Loc,
+}
+
+/// Kind of the character type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharTypeKind {
+    /// `.`
+    Any,
+    /// `\d`
+    Digit,
+    /// `\D`
+    NonDigit,
+    /// `\w`
+    Word,
+    /// `\W`
+    NonWord,
+    /// `\s`
+    Space,
+    /// `\S`
+    NonSpace,
+    /// `\h`
+    HexDigit,
+    /// `\H`
+    NonHexDigit,
+    /// `\R`
+    Linebreak,
+    /// `\X`
+    GraphemeCluster,
+}
+
+/// Character type like `.` or `\d`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharType {
+    /// Kind of the character type
+    pub kind: CharTypeKind,
+
+    /// Location of the character type
+    pub expression_l: Loc,
+}
+
+/// Character property like `\p{Alpha}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    /// Name of the property, `Alpha` for `\p{Alpha}`
+    pub name: String,
+
+    /// `true` for `\P{Alpha}` and `\p{^Alpha}`
+    pub negated: bool,
+
+    /// Location of the name
+    pub name_l: Loc,
+
+    /// Location of the whole property
+    pub expression_l: Loc,
+}
+
+/// Character class like `[^a-z\d]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharClass {
+    /// `true` for `[^...]`
+    pub negated: bool,
+
+    /// Items of the class
+    pub items: Vec<ClassItem>,
+
+    /// Location of `[` (or `[^`)
+    pub begin_l: Loc,
+
+    /// Location of `]`
+    pub end_l: Loc,
+
+    /// Location of the whole class
+    pub expression_l: Loc,
+}
+
+/// Item of the character class
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassItem {
+    /// `a`
+    Literal(Literal),
+
+    /// `a-z`
+    Range(ClassRange),
+
+    /// `\d`
+    CharType(CharType),
+
+    /// `\p{Alpha}`
+    Property(Property),
+
+    /// `[:alpha:]`
+    Posix(PosixClass),
+
+    /// Nested class `[a[b]]`
+    Class(CharClass),
+
+    /// `a-z&&[^aeiou]`
+    Intersection(ClassIntersection),
+}
+
+impl ClassItem {
+    /// Returns location of the whole item
+    pub fn expression(&self) -> &Loc {
+        match self {
+            Self::Literal(item) => &item.expression_l,
+            Self::Range(item) => &item.expression_l,
+            Self::CharType(item) => &item.expression_l,
+            Self::Property(item) => &item.expression_l,
+            Self::Posix(item) => &item.expression_l,
+            Self::Class(item) => &item.expression_l,
+            Self::Intersection(item) => &item.expression_l,
+        }
+    }
+}
+
+/// Range of characters in the class, `a-z`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassRange {
+    /// Lower bound
+    pub from: Literal,
+
+    /// Upper bound
+    pub to: Literal,
+
+    /// Location of `-`
+    pub operator_l: Loc,
+
+    /// Location of the whole range
+    pub expression_l: Loc,
+}
+
+/// POSIX bracket expression `[:alpha:]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PosixClass {
+    /// Name of the class, `alpha` for `[:alpha:]`
+    pub name: String,
+
+    /// `true` for `[:^alpha:]`
+    pub negated: bool,
+
+    /// Location of the whole expression
+    pub expression_l: Loc,
+}
+
+/// Intersection of character sets, `a-z&&[^aeiou]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassIntersection {
+    /// Left-hand side
+    pub lhs: Vec<ClassItem>,
+
+    /// Right-hand side
+    pub rhs: Vec<ClassItem>,
+
+    /// Location of `&&`
+    pub operator_l: Loc,
+
+    /// Location of the whole intersection
+    pub expression_l: Loc,
+}
+
+/// Kind of the anchor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchorKind {
+    /// `^`
+    LineBegin,
+    /// `$`
+    LineEnd,
+    /// `\A`
+    StringBegin,
+    /// `\z`
+    StringEnd,
+    /// `\Z`
+    StringEndOrNewline,
+    /// `\b`
+    WordBoundary,
+    /// `\B`
+    NonWordBoundary,
+    /// `\G`
+    SearchStart,
+    /// `\K`
+    Keep,
+}
+
+/// Zero-width assertion like `^` or `\b`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anchor {
+    /// Kind of the anchor
+    pub kind: AnchorKind,
+
+    /// Location of the anchor
+    pub expression_l: Loc,
+}
+
+/// Reference to a group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupReference {
+    /// `\1`, `\k<1>`
+    Number(usize),
+
+    /// `\k<-1>`, `\g<+1>`
+    Relative(isize),
+
+    /// `\k<name>`
+    Name(String),
+}
+
+/// Backreference, `\1` or `\k<name>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backref {
+    /// Referenced group
+    pub reference: GroupReference,
+
+    /// Nest level, `Some(1)` for `\k<name+1>`
+    pub level: Option<i32>,
+
+    /// Location of the backreference
+    pub expression_l: Loc,
+}
+
+/// Subexpression call, `\g<name>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubexpCall {
+    /// Called group, `Number(0)` for the whole pattern
+    pub reference: GroupReference,
+
+    /// Location of the call
+    pub expression_l: Loc,
+}
+
+/// Kind of the group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupKind {
+    /// `(...)`
+    Capture {
+        /// Number of the group
+        index: usize,
+    },
+
+    /// `(?<name>...)` or `(?'name'...)`
+    NamedCapture {
+        /// Number of the group
+        index: usize,
+
+        /// Name of the group
+        name: String,
+
+        /// Location of the name
+        name_l: Loc,
+    },
+
+    /// `(?:...)`, or `(...)` in a regexp that has named groups
+    NonCapture,
+
+    /// `(?=...)`
+    Lookahead,
+
+    /// `(?!...)`
+    NegativeLookahead,
+
+    /// `(?<=...)`
+    Lookbehind,
+
+    /// `(?<!...)`
+    NegativeLookbehind,
+
+    /// `(?>...)`
+    Atomic,
+
+    /// `(?~...)`
+    Absent,
+
+    /// `(?imx-imx:...)`
+    Options {
+        /// Enabled options
+        on: String,
+
+        /// Disabled options
+        off: String,
+    },
+}
+
+/// Group, `(...)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    /// Kind of the group
+    pub kind: GroupKind,
+
+    /// Body of the group
+    pub body: Box<RegexpNode>,
+
+    /// Location of the opening part, `(` or `(?<name>`
+    pub begin_l: Loc,
+
+    /// Location of `)`
+    pub end_l: Loc,
+
+    /// Location of the whole group
+    pub expression_l: Loc,
+}
+
+/// Conditional group, `(?(1)yes|no)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conditional {
+    /// Group that is checked
+    pub condition: GroupReference,
+
+    /// Branch that is used if group has matched
+    pub if_true: Box<RegexpNode>,
+
+    /// Branch that is used if group has not matched
+    pub if_false: Option<Box<RegexpNode>>,
+
+    /// Location of the opening part, `(?(1)`
+    pub begin_l: Loc,
+
+    /// Location of `)`
+    pub end_l: Loc,
+
+    /// Location of the whole conditional
+    pub expression_l: Loc,
+}
+
+/// Kind of the quantifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuantifierKind {
+    /// `*`, `+`, `?`, `{n,m}`
+    Greedy,
+    /// `*?`, `+?`, `??`, `{n,m}?`
+    Lazy,
+    /// `*+`, `++`, `?+`
+    Possessive,
+}
+
+/// Quantified node, `a*`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quantified {
+    /// Repeated node
+    pub target: Box<RegexpNode>,
+
+    /// Minimal number of repetitions
+    pub min: usize,
+
+    /// Maximal number of repetitions, `None` means unbounded
+    pub max: Option<usize>,
+
+    /// Kind of the quantifier
+    pub kind: QuantifierKind,
+
+    /// Location of the quantifier (`*`, `{1,2}?`)
+    pub operator_l: Loc,
+
+    /// Location of the whole node
+    pub expression_l: Loc,
+}
+
+/// Inline options, `(?i-m)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Enabled options
+    pub on: String,
+
+    /// Disabled options
+    pub off: String,
+
+    /// Location of the options group
+    pub expression_l: Loc,
+}
+
+/// Comment, `(?#...)` or `# ...` in extended mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// Location of the comment
+    pub expression_l: Loc,
+}
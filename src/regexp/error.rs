@@ -0,0 +1,73 @@
+use crate::Loc;
+
+/// Error that is returned if regexp is invalid.
+///
+/// Messages match messages of the Onigmo engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexpError {
+    /// Message of the error
+    pub message: String,
+
+    /// Location of the part of the regexp that caused the error
+    pub loc: Loc,
+}
+
+impl RegexpError {
+    pub(crate) fn new<S: Into<String>>(message: S, loc: Loc) -> Self {
+        Self {
+            message: message.into(),
+            loc,
+        }
+    }
+}
+
+impl std::fmt::Display for RegexpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for RegexpError {}
+
+pub(crate) const END_PATTERN_AT_ESCAPE: &str = "end pattern at escape";
+pub(crate) const END_PATTERN_IN_GROUP: &str = "end pattern in group";
+pub(crate) const END_PATTERN_WITH_UNMATCHED_PARENTHESIS: &str =
+    "end pattern with unmatched parenthesis";
+pub(crate) const UNMATCHED_CLOSE_PARENTHESIS: &str = "unmatched close parenthesis";
+pub(crate) const PREMATURE_END_OF_CHAR_CLASS: &str = "premature end of char-class";
+pub(crate) const EMPTY_CHAR_CLASS: &str = "empty char-class";
+pub(crate) const EMPTY_RANGE_IN_CHAR_CLASS: &str = "empty range in char class";
+pub(crate) const INVALID_POSIX_BRACKET_TYPE: &str = "invalid POSIX bracket type";
+pub(crate) const TARGET_OF_REPEAT_OPERATOR_NOT_SPECIFIED: &str =
+    "target of repeat operator is not specified";
+pub(crate) const TARGET_OF_REPEAT_OPERATOR_INVALID: &str = "target of repeat operator is invalid";
+pub(crate) const TOO_BIG_NUMBER_FOR_REPEAT_RANGE: &str = "too big number for repeat range";
+pub(crate) const UPPER_SMALLER_THAN_LOWER_IN_REPEAT_RANGE: &str =
+    "upper is smaller than lower in repeat range";
+pub(crate) const UNDEFINED_GROUP_OPTION: &str = "undefined group option";
+pub(crate) const INVALID_CONDITION_PATTERN: &str = "invalid conditional pattern";
+pub(crate) const INVALID_LOOK_BEHIND: &str = "invalid pattern in look-behind";
+pub(crate) const INVALID_BACKREF: &str = "invalid backref number/name";
+pub(crate) const NUMBERED_BACKREF_OR_CALL_NOT_ALLOWED: &str =
+    "numbered backref/call is not allowed. (use name)";
+pub(crate) const EMPTY_GROUP_NAME: &str = "group name is empty";
+
+pub(crate) fn invalid_group_name(name: &str) -> String {
+    format!("invalid group name <{}>", name)
+}
+
+pub(crate) fn invalid_char_in_group_name(name: &str) -> String {
+    format!("invalid char in group name <{}>", name)
+}
+
+pub(crate) fn undefined_name_reference(name: &str) -> String {
+    format!("undefined name <{}> reference", name)
+}
+
+pub(crate) fn undefined_group_reference(name: &str) -> String {
+    format!("undefined group <{}> reference", name)
+}
+
+pub(crate) fn invalid_char_property_name(name: &str) -> String {
+    format!("invalid character property name {{{}}}", name)
+}
@@ -0,0 +1,492 @@
+use super::ast::*;
+use super::{parse, RegexpError, RegexpTree};
+use crate::nodes::{Begin, RegOpt, Regexp, Str};
+use crate::source::DecodedInput;
+use crate::{Loc, Node};
+
+/// Parses static parts of the regexp literal into a syntax tree.
+///
+/// Unlike `regexp::parse` all locations in the returned tree
+/// (and in the returned error) point to the Ruby source,
+/// so `/a#{"b"}c/` has three `Literal` nodes located at `a`, `b` and `c`.
+///
+/// Returns `None` if regexp has dynamic parts (like `/#{foo}/`).
+pub fn parse_regexp_literal(
+    regexp: &Regexp,
+    input: &DecodedInput,
+) -> Option<Result<RegexpTree, RegexpError>> {
+    let mut source = MappedSource::default();
+    for part in regexp.parts.iter() {
+        source.push_node(part, input)?;
+    }
+
+    let mut options = "";
+    if let Some(Node::RegOpt(RegOpt {
+        options: Some(re_options),
+        ..
+    })) = regexp.options.as_deref()
+    {
+        options = re_options;
+    }
+
+    let content_l = Loc {
+        begin: regexp.begin_l.end,
+        end: regexp.end_l.begin,
+    };
+
+    Some(match parse(&source.bytes, options) {
+        Ok(mut tree) => {
+            map_node_locs(&mut tree.root, &mut |loc| {
+                *loc = source.source_loc(*loc, &content_l)
+            });
+            Ok(tree)
+        }
+        Err(mut err) => {
+            err.loc = source.source_loc(err.loc, &content_l);
+            Err(err)
+        }
+    })
+}
+
+// Concatenated value of static parts of the regexp
+// with a location in the Ruby source for every byte
+#[derive(Debug, Default)]
+struct MappedSource {
+    bytes: Vec<u8>,
+    locs: Vec<Loc>,
+}
+
+impl MappedSource {
+    fn push_node(&mut self, node: &Node, input: &DecodedInput) -> Option<()> {
+        match node {
+            Node::Str(Str {
+                value,
+                begin_l,
+                end_l,
+                expression_l,
+            }) => {
+                // `Str` inside of the interpolation has quotes
+                let content_l = match (begin_l, end_l) {
+                    (Some(begin_l), Some(end_l)) if begin_l.end <= end_l.begin => Loc {
+                        begin: begin_l.end,
+                        end: end_l.begin,
+                    },
+                    _ => *expression_l,
+                };
+                let src = input.substr_at(content_l.begin, content_l.end)?;
+                let value = value.as_raw();
+
+                self.bytes.extend_from_slice(value);
+                align(src, content_l.begin, value, &mut self.locs);
+                Some(())
+            }
+            Node::Begin(Begin { statements, .. }) => {
+                for statement in statements.iter() {
+                    self.push_node(statement, input)?;
+                }
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    // Converts a location in `self.bytes` to a location in the Ruby source
+    fn source_loc(&self, loc: Loc, content_l: &Loc) -> Loc {
+        let begin = match self.locs.get(loc.begin) {
+            Some(byte_l) => byte_l.begin,
+            None => self.locs.last().map(|l| l.end).unwrap_or(content_l.begin),
+        };
+        let end = if loc.end > loc.begin {
+            self.locs
+                .get(loc.end - 1)
+                .map(|byte_l| byte_l.end)
+                .unwrap_or(content_l.end)
+        } else {
+            begin
+        };
+        Loc {
+            begin,
+            end: end.max(begin),
+        }
+    }
+}
+
+// Matches bytes of the string value with bytes of its source
+// and pushes a source location of every byte of the value to `locs`.
+//
+// Lexer keeps regexp escapes as is, so most of the bytes are the same,
+// except line continuations, escaped terminators (`\/` becomes `/`)
+// and control/meta chars (`\C-a` becomes `\x01`).
+// If some part can't be matched all remaining bytes point to the rest of the source.
+fn align(src: &[u8], offset: usize, value: &[u8], locs: &mut Vec<Loc>) {
+    let mut s = 0;
+    let mut v = 0;
+
+    let loc = |begin: usize, end: usize| Loc {
+        begin: offset + begin,
+        end: offset + end,
+    };
+
+    while v < value.len() && s < src.len() {
+        let byte = value[v];
+
+        if src[s..].starts_with(b"\\\n") && !value[v..].starts_with(b"\\\n") {
+            // line continuation
+            s += 2;
+            continue;
+        }
+
+        if src[s..].starts_with(b"\r\n") && byte == b'\n' {
+            s += 1;
+            continue;
+        }
+
+        if src[s] == b'\\' && value[v..].starts_with(b"\\x") {
+            if let Some(len) = control_escape_len(src, s) {
+                // `\C-a` -> `\x01`, lexer always writes 4 bytes
+                let hex_len = (value.len() - v).min(4);
+                for _ in 0..hex_len {
+                    locs.push(loc(s, s + len));
+                }
+                s += len;
+                v += hex_len;
+                continue;
+            }
+        }
+
+        if src[s] == b'\\' && byte == b'\\' && src.get(s + 1) == value.get(v + 1) {
+            // escape sequence that is kept as is, both bytes are mapped at once
+            // to not treat `\\` + newline as a line continuation
+            locs.push(loc(s, s + 1));
+            if v + 1 < value.len() {
+                locs.push(loc(s + 1, s + 2));
+            }
+            s += 2;
+            v += 2;
+            continue;
+        }
+
+        if src[s] == byte {
+            locs.push(loc(s, s + 1));
+            s += 1;
+            v += 1;
+            continue;
+        }
+
+        if src[s] == b'\\' && src.get(s + 1) == Some(&byte) {
+            // escaped terminator, `\/` -> `/`
+            locs.push(loc(s, s + 2));
+            s += 2;
+            v += 1;
+            continue;
+        }
+
+        break;
+    }
+
+    // unknown transformation (or a string with escapes inside of the interpolation)
+    let s = s.min(src.len());
+    while v < value.len() {
+        locs.push(loc(s, src.len()));
+        v += 1;
+    }
+}
+
+// Returns length of `\cx`, `\C-x`, `\M-x` (and their combinations) in the source
+fn control_escape_len(src: &[u8], s: usize) -> Option<usize> {
+    let prefix_len = match src.get(s + 1..s + 3)? {
+        [b'c', _] => 2,
+        [b'C', b'-'] | [b'M', b'-'] => 3,
+        _ => return None,
+    };
+    let target = s + prefix_len;
+    let target_len = match src.get(target)? {
+        b'\\' => control_escape_len(src, target).unwrap_or(2),
+        _ => 1,
+    };
+    Some(prefix_len + target_len)
+}
+
+fn map_node_locs<F: FnMut(&mut Loc)>(node: &mut RegexpNode, f: &mut F) {
+    match node {
+        RegexpNode::Alternation(Alternation {
+            alternatives,
+            pipes_l,
+            expression_l,
+        }) => {
+            for node in alternatives.iter_mut() {
+                map_node_locs(node, f);
+            }
+            pipes_l.iter_mut().for_each(&mut *f);
+            f(expression_l);
+        }
+        RegexpNode::Sequence(Sequence {
+            items,
+            expression_l,
+        }) => {
+            for node in items.iter_mut() {
+                map_node_locs(node, f);
+            }
+            f(expression_l);
+        }
+        RegexpNode::Literal(Literal { expression_l, .. })
+        | RegexpNode::CharType(CharType { expression_l, .. })
+        | RegexpNode::Anchor(Anchor { expression_l, .. })
+        | RegexpNode::Backref(Backref { expression_l, .. })
+        | RegexpNode::SubexpCall(SubexpCall { expression_l, .. })
+        | RegexpNode::Options(Options { expression_l, .. })
+        | RegexpNode::Comment(Comment { expression_l }) => f(expression_l),
+        RegexpNode::Property(property) => map_property_locs(property, f),
+        RegexpNode::CharClass(class) => map_class_locs(class, f),
+        RegexpNode::Group(Group {
+            kind,
+            body,
+            begin_l,
+            end_l,
+            expression_l,
+        }) => {
+            if let GroupKind::NamedCapture { name_l, .. } = kind {
+                f(name_l);
+            }
+            map_node_locs(body, f);
+            f(begin_l);
+            f(end_l);
+            f(expression_l);
+        }
+        RegexpNode::Conditional(Conditional {
+            if_true,
+            if_false,
+            begin_l,
+            end_l,
+            expression_l,
+            ..
+        }) => {
+            map_node_locs(if_true, f);
+            if let Some(if_false) = if_false {
+                map_node_locs(if_false, f);
+            }
+            f(begin_l);
+            f(end_l);
+            f(expression_l);
+        }
+        RegexpNode::Quantified(Quantified {
+            target,
+            operator_l,
+            expression_l,
+            ..
+        }) => {
+            map_node_locs(target, f);
+            f(operator_l);
+            f(expression_l);
+        }
+    }
+}
+
+fn map_property_locs<F: FnMut(&mut Loc)>(property: &mut Property, f: &mut F) {
+    f(&mut property.name_l);
+    f(&mut property.expression_l);
+}
+
+fn map_class_locs<F: FnMut(&mut Loc)>(class: &mut CharClass, f: &mut F) {
+    map_class_items_locs(&mut class.items, f);
+    f(&mut class.begin_l);
+    f(&mut class.end_l);
+    f(&mut class.expression_l);
+}
+
+fn map_class_items_locs<F: FnMut(&mut Loc)>(items: &mut [ClassItem], f: &mut F) {
+    for item in items.iter_mut() {
+        match item {
+            ClassItem::Literal(Literal { expression_l, .. })
+            | ClassItem::CharType(CharType { expression_l, .. })
+            | ClassItem::Posix(PosixClass { expression_l, .. }) => f(expression_l),
+            ClassItem::Property(property) => map_property_locs(property, f),
+            ClassItem::Class(class) => map_class_locs(class, f),
+            ClassItem::Range(ClassRange {
+                from,
+                to,
+                operator_l,
+                expression_l,
+            }) => {
+                f(&mut from.expression_l);
+                f(&mut to.expression_l);
+                f(operator_l);
+                f(expression_l);
+            }
+            ClassItem::Intersection(ClassIntersection {
+                lhs,
+                rhs,
+                operator_l,
+                expression_l,
+            }) => {
+                map_class_items_locs(lhs, f);
+                map_class_items_locs(rhs, f);
+                f(operator_l);
+                f(expression_l);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn str_part(value: &str, begin: usize, end: usize) -> Node {
+    Node::Str(Str {
+        value: crate::Bytes::new(value.as_bytes().to_vec()),
+        begin_l: None,
+        end_l: None,
+        expression_l: Loc { begin, end },
+    })
+}
+
+#[cfg(test)]
+fn parse_source(
+    source: &str,
+    parts: Vec<Node>,
+    options: Option<&str>,
+) -> Result<RegexpTree, RegexpError> {
+    let mut input = DecodedInput::named("(eval)");
+    input.update_bytes(source.as_bytes().to_vec());
+
+    let end = source.len() - options.map(|s| s.len()).unwrap_or(0) - 1;
+    let regexp = Regexp {
+        parts,
+        options: options.map(|options| {
+            Box::new(Node::RegOpt(RegOpt {
+                options: Some(options.to_string()),
+                expression_l: Loc {
+                    begin: end + 1,
+                    end: source.len(),
+                },
+            }))
+        }),
+        begin_l: Loc { begin: 0, end: 1 },
+        end_l: Loc {
+            begin: end,
+            end: end + 1,
+        },
+        expression_l: Loc {
+            begin: 0,
+            end: source.len(),
+        },
+    };
+    parse_regexp_literal(&regexp, &input).unwrap()
+}
+
+#[cfg(test)]
+fn sequence_items(tree: RegexpTree) -> Vec<RegexpNode> {
+    match tree.root {
+        RegexpNode::Sequence(Sequence { items, .. }) => items,
+        other => panic!("expected sequence, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_escaped_terminator() {
+    let tree = parse_source("/a\\/b/", vec![str_part("a/b", 1, 5)], None).unwrap();
+    let locs = sequence_items(tree)
+        .iter()
+        .map(|node| *node.expression())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        locs,
+        vec![
+            Loc { begin: 1, end: 2 },
+            Loc { begin: 2, end: 4 },
+            Loc { begin: 4, end: 5 },
+        ]
+    );
+}
+
+#[test]
+fn test_interpolation() {
+    let interpolated = Node::Begin(Begin {
+        statements: vec![Node::Str(Str {
+            value: crate::Bytes::new(b"b".to_vec()),
+            begin_l: Some(Loc { begin: 4, end: 5 }),
+            end_l: Some(Loc { begin: 6, end: 7 }),
+            expression_l: Loc { begin: 4, end: 7 },
+        })],
+        begin_l: Some(Loc { begin: 2, end: 4 }),
+        end_l: Some(Loc { begin: 7, end: 8 }),
+        expression_l: Loc { begin: 2, end: 8 },
+    });
+    let tree = parse_source(
+        "/a#{\"b\"}+/",
+        vec![str_part("a", 1, 2), interpolated, str_part("+", 8, 9)],
+        None,
+    )
+    .unwrap();
+
+    match &sequence_items(tree)[1] {
+        RegexpNode::Quantified(Quantified {
+            target,
+            operator_l,
+            expression_l,
+            ..
+        }) => {
+            assert_eq!(*target.expression(), Loc { begin: 5, end: 6 });
+            assert_eq!(*operator_l, Loc { begin: 8, end: 9 });
+            assert_eq!(*expression_l, Loc { begin: 5, end: 9 });
+        }
+        other => panic!("expected quantified node, got {:?}", other),
+    }
+
+    let dynamic = Node::Begin(Begin {
+        statements: vec![Node::Nil(crate::nodes::Nil {
+            expression_l: Loc { begin: 3, end: 6 },
+        })],
+        begin_l: Some(Loc { begin: 1, end: 3 }),
+        end_l: Some(Loc { begin: 6, end: 7 }),
+        expression_l: Loc { begin: 1, end: 7 },
+    });
+    let mut input = DecodedInput::named("(eval)");
+    input.update_bytes(b"/#{nil}/".to_vec());
+    let regexp = Regexp {
+        parts: vec![dynamic],
+        options: None,
+        begin_l: Loc { begin: 0, end: 1 },
+        end_l: Loc { begin: 7, end: 8 },
+        expression_l: Loc { begin: 0, end: 8 },
+    };
+    assert_eq!(parse_regexp_literal(&regexp, &input), None);
+}
+
+#[test]
+fn test_extended_mode_and_line_continuation() {
+    let tree = parse_source(
+        "/a\\\nb # c\n/x",
+        vec![str_part("ab # c\n", 1, 10)],
+        Some("x"),
+    )
+    .unwrap();
+    let locs = sequence_items(tree)
+        .iter()
+        .map(|node| *node.expression())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        locs,
+        vec![
+            Loc { begin: 1, end: 2 },
+            Loc { begin: 4, end: 5 },
+            Loc { begin: 6, end: 9 },
+        ]
+    );
+}
+
+#[test]
+fn test_control_chars() {
+    let tree = parse_source("/\\C-a*/", vec![str_part("\\x01*", 1, 6)], None).unwrap();
+    match &sequence_items(tree)[0] {
+        RegexpNode::Quantified(Quantified { target, .. }) => {
+            assert_eq!(*target.expression(), Loc { begin: 1, end: 5 });
+        }
+        other => panic!("expected quantified node, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_error_loc() {
+    let err = parse_source("%r{a\n(b}", vec![str_part("a\n(b", 3, 7)], None).unwrap_err();
+    assert_eq!(err.message, "end pattern with unmatched parenthesis");
+    assert_eq!(err.loc, Loc { begin: 5, end: 7 });
+}
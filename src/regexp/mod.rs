@@ -0,0 +1,11 @@
+mod ast;
+pub use ast::*;
+
+mod error;
+pub use error::RegexpError;
+
+mod parser;
+pub use parser::{parse, RegexpTree};
+
+mod literal;
+pub use literal::parse_regexp_literal;
@@ -0,0 +1,1624 @@
+use super::ast::*;
+use super::error::*;
+use crate::Loc;
+
+/// Result of parsing a regexp
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexpTree {
+    /// Root node, `Alternation` or `Sequence`
+    pub root: RegexpNode,
+
+    /// Names of named groups in order of their definition (without duplicates)
+    pub named_captures: Vec<String>,
+
+    /// Number of capturing groups.
+    ///
+    /// If regexp has named groups only named groups are capturing.
+    pub captures_count: usize,
+}
+
+/// Parses given regexp source using Onigmo syntax (the one that Ruby uses).
+///
+/// `options` is a list of regexp flags like `"mix"`,
+/// only `x` (extended mode) affects parsing.
+///
+/// All locations in the returned tree are byte offsets in `source`.
+pub fn parse(source: &[u8], options: &str) -> Result<RegexpTree, RegexpError> {
+    let mut parser = Parser {
+        source,
+        pos: 0,
+        extended: options.contains('x'),
+        groups_count: 0,
+        named_groups: vec![],
+        refs: vec![],
+    };
+
+    let mut root = parser.parse_alternation()?;
+    if parser.peek() == Some(b')') {
+        return Err(RegexpError::new(
+            UNMATCHED_CLOSE_PARENTHESIS,
+            parser.loc(parser.pos, parser.pos + 1),
+        ));
+    }
+
+    parser.validate_refs()?;
+
+    let mut named_captures: Vec<String> = vec![];
+    for name in parser.named_groups.iter() {
+        if !named_captures.contains(name) {
+            named_captures.push(name.clone());
+        }
+    }
+
+    let captures_count = if parser.named_groups.is_empty() {
+        parser.groups_count
+    } else {
+        // plain groups are not capturing if there are named groups
+        let mut index = 0;
+        renumber_named_groups(&mut root, &mut index);
+        index
+    };
+
+    Ok(RegexpTree {
+        root,
+        named_captures,
+        captures_count,
+    })
+}
+
+// Maximal number in `{n,m}`, ONIG_MAX_REPEAT_NUM
+const MAX_REPEAT_NUM: usize = 100_000;
+
+// Maximal number of a backreference, ONIG_MAX_BACKREF_NUM
+const MAX_BACKREF_NUM: usize = 1000;
+
+const POSIX_BRACKET_NAMES: &[&str] = &[
+    "alnum", "alpha", "ascii", "blank", "cntrl", "digit", "graph", "lower", "print", "punct",
+    "space", "upper", "word", "xdigit",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RefKind {
+    Backref,
+    Call,
+    Condition,
+}
+
+// Reference to a group that can only be validated
+// once the whole regexp is parsed
+#[derive(Debug)]
+struct PendingRef {
+    kind: RefKind,
+    // absolute group number or a name
+    target: GroupReference,
+    loc: Loc,
+}
+
+struct Parser<'a> {
+    source: &'a [u8],
+    pos: usize,
+
+    // `x` flag, is scoped to the current group
+    extended: bool,
+
+    // number of all `(...)` and `(?<name>...)` groups seen so far
+    groups_count: usize,
+
+    // names of named groups seen so far (can have duplicates)
+    named_groups: Vec<String>,
+
+    refs: Vec<PendingRef>,
+}
+
+impl Parser<'_> {
+    fn loc(&self, begin: usize, end: usize) -> Loc {
+        Loc { begin, end }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.source.get(self.pos + offset).copied()
+    }
+
+    // Returns a code point at the current position and its length in bytes,
+    // bytes that are not a part of a valid UTF-8 sequence are returned as is
+    fn peek_char(&self) -> Option<(u32, usize)> {
+        let lead = self.peek()?;
+        if lead.is_ascii() {
+            return Some((lead as u32, 1));
+        }
+        let len = match lead {
+            0xF0..=0xF7 => 4,
+            0xE0..=0xEF => 3,
+            0xC0..=0xDF => 2,
+            _ => 1,
+        };
+        let end = (self.pos + len).min(self.source.len());
+        match std::str::from_utf8(&self.source[self.pos..end])
+            .ok()
+            .and_then(|s| s.chars().next())
+        {
+            Some(c) => Some((c as u32, len)),
+            None => Some((lead as u32, 1)),
+        }
+    }
+
+    fn error<T, S: Into<String>>(
+        &self,
+        message: S,
+        begin: usize,
+        end: usize,
+    ) -> Result<T, RegexpError> {
+        Err(RegexpError::new(message, self.loc(begin, end)))
+    }
+
+    fn skip_extended_trivia(&mut self, items: &mut Vec<RegexpNode>) {
+        while let Some(c) = self.peek() {
+            match c {
+                b' ' | b'\t' | b'\n' | b'\r' | b'\x0c' | b'\x0b' => self.pos += 1,
+                b'#' => {
+                    let begin = self.pos;
+                    while !matches!(self.peek(), None | Some(b'\n')) {
+                        self.pos += 1;
+                    }
+                    items.push(RegexpNode::Comment(Comment {
+                        expression_l: self.loc(begin, self.pos),
+                    }));
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn parse_alternation(&mut self) -> Result<RegexpNode, RegexpError> {
+        let begin = self.pos;
+        let mut alternatives = vec![self.parse_sequence()?];
+        let mut pipes_l = vec![];
+
+        while self.peek() == Some(b'|') {
+            pipes_l.push(self.loc(self.pos, self.pos + 1));
+            self.pos += 1;
+            alternatives.push(self.parse_sequence()?);
+        }
+
+        if alternatives.len() == 1 {
+            return Ok(alternatives.pop().unwrap());
+        }
+
+        Ok(RegexpNode::Alternation(Alternation {
+            alternatives,
+            pipes_l,
+            expression_l: self.loc(begin, self.pos),
+        }))
+    }
+
+    fn parse_sequence(&mut self) -> Result<RegexpNode, RegexpError> {
+        let begin = self.pos;
+        let mut items = vec![];
+
+        loop {
+            if self.extended {
+                self.skip_extended_trivia(&mut items);
+            }
+
+            match self.peek() {
+                None | Some(b'|') | Some(b')') => break,
+                Some(b'*') | Some(b'+') | Some(b'?') => {
+                    let quantifier = self.parse_quantifier();
+                    self.apply_quantifier(&mut items, quantifier)?;
+                }
+                Some(b'{') => match self.parse_interval()? {
+                    Some(quantifier) => self.apply_quantifier(&mut items, quantifier)?,
+                    None => items.push(self.parse_plain_literal()),
+                },
+                Some(_) => self.parse_atom(&mut items)?,
+            }
+        }
+
+        Ok(RegexpNode::Sequence(Sequence {
+            items,
+            expression_l: self.loc(begin, self.pos),
+        }))
+    }
+
+    fn parse_atom(&mut self, items: &mut Vec<RegexpNode>) -> Result<(), RegexpError> {
+        let begin = self.pos;
+        let node = match self.peek() {
+            Some(b'(') => self.parse_group()?,
+            Some(b'[') => RegexpNode::CharClass(self.parse_class()?),
+            Some(b'.') => {
+                self.pos += 1;
+                RegexpNode::CharType(CharType {
+                    kind: CharTypeKind::Any,
+                    expression_l: self.loc(begin, self.pos),
+                })
+            }
+            Some(b'^') | Some(b'$') => {
+                let kind = if self.peek() == Some(b'^') {
+                    AnchorKind::LineBegin
+                } else {
+                    AnchorKind::LineEnd
+                };
+                self.pos += 1;
+                RegexpNode::Anchor(Anchor {
+                    kind,
+                    expression_l: self.loc(begin, self.pos),
+                })
+            }
+            Some(b'\\') => return self.parse_escape(false, items),
+            _ => self.parse_plain_literal(),
+        };
+        items.push(node);
+        Ok(())
+    }
+
+    fn parse_plain_literal(&mut self) -> RegexpNode {
+        let begin = self.pos;
+        let (value, len) = self.peek_char().expect("must be called before EOF");
+        self.pos += len;
+        RegexpNode::Literal(Literal {
+            value,
+            kind: LiteralKind::Plain,
+            expression_l: self.loc(begin, self.pos),
+        })
+    }
+
+    // Quantifiers
+
+    fn parse_quantifier(&mut self) -> (usize, Option<usize>, QuantifierKind, Loc) {
+        let begin = self.pos;
+        let (min, max) = match self.peek() {
+            Some(b'*') => (0, None),
+            Some(b'+') => (1, None),
+            Some(b'?') => (0, Some(1)),
+            _ => unreachable!("must be called on a quantifier"),
+        };
+        self.pos += 1;
+
+        let kind = match self.peek() {
+            Some(b'?') => QuantifierKind::Lazy,
+            Some(b'+') => QuantifierKind::Possessive,
+            _ => QuantifierKind::Greedy,
+        };
+        if kind != QuantifierKind::Greedy {
+            self.pos += 1;
+        }
+
+        (min, max, kind, self.loc(begin, self.pos))
+    }
+
+    // Parses `{n}`, `{n,}`, `{,m}`, `{n,m}`
+    // Returns `None` if it's not a valid interval (and so `{` is a plain character)
+    #[allow(clippy::type_complexity)]
+    fn parse_interval(
+        &mut self,
+    ) -> Result<Option<(usize, Option<usize>, QuantifierKind, Loc)>, RegexpError> {
+        let begin = self.pos;
+        let mut pos = self.pos + 1;
+
+        let read_number = |pos: &mut usize| -> Option<usize> {
+            let start = *pos;
+            let mut value: usize = 0;
+            while let Some(c) = self.source.get(*pos).filter(|c| c.is_ascii_digit()) {
+                value = value.saturating_mul(10).saturating_add((c - b'0') as usize);
+                *pos += 1;
+            }
+            if *pos == start {
+                None
+            } else {
+                Some(value)
+            }
+        };
+
+        let min = read_number(&mut pos);
+        let (min, max, exact) = if self.source.get(pos) == Some(&b',') {
+            pos += 1;
+            let max = read_number(&mut pos);
+            if min.is_none() && max.is_none() {
+                return Ok(None);
+            }
+            (min.unwrap_or(0), max, false)
+        } else {
+            match min {
+                Some(min) => (min, Some(min), true),
+                None => return Ok(None),
+            }
+        };
+        if self.source.get(pos) != Some(&b'}') {
+            return Ok(None);
+        }
+        pos += 1;
+
+        if min > MAX_REPEAT_NUM || max.map(|max| max > MAX_REPEAT_NUM).unwrap_or(false) {
+            return self.error(TOO_BIG_NUMBER_FOR_REPEAT_RANGE, begin, pos);
+        }
+        if let Some(max) = max {
+            if max < min {
+                return self.error(UPPER_SMALLER_THAN_LOWER_IN_REPEAT_RANGE, begin, pos);
+            }
+        }
+        self.pos = pos;
+
+        // `{n}?` is `({n})?` in Ruby, but `{n,m}?` is a lazy quantifier
+        let kind = if !exact && self.peek() == Some(b'?') {
+            self.pos += 1;
+            QuantifierKind::Lazy
+        } else {
+            QuantifierKind::Greedy
+        };
+
+        Ok(Some((min, max, kind, self.loc(begin, self.pos))))
+    }
+
+    fn apply_quantifier(
+        &mut self,
+        items: &mut Vec<RegexpNode>,
+        (min, max, kind, operator_l): (usize, Option<usize>, QuantifierKind, Loc),
+    ) -> Result<(), RegexpError> {
+        // comments are skipped, `a(?#comment)*` repeats `a`
+        let idx = items
+            .iter()
+            .rposition(|item| !matches!(item, RegexpNode::Comment(_)));
+
+        let idx = match idx {
+            Some(idx) => idx,
+            None => {
+                return self.error(
+                    TARGET_OF_REPEAT_OPERATOR_NOT_SPECIFIED,
+                    operator_l.begin,
+                    operator_l.end,
+                )
+            }
+        };
+
+        match &items[idx] {
+            // `(?i)` applies to the rest of the group, so there's nothing to repeat
+            RegexpNode::Options(_) => {
+                return self.error(
+                    TARGET_OF_REPEAT_OPERATOR_NOT_SPECIFIED,
+                    operator_l.begin,
+                    operator_l.end,
+                )
+            }
+            RegexpNode::Anchor(_)
+            | RegexpNode::Group(Group {
+                kind:
+                    GroupKind::Lookahead
+                    | GroupKind::NegativeLookahead
+                    | GroupKind::Lookbehind
+                    | GroupKind::NegativeLookbehind,
+                ..
+            }) => {
+                return self.error(
+                    TARGET_OF_REPEAT_OPERATOR_INVALID,
+                    operator_l.begin,
+                    operator_l.end,
+                )
+            }
+            _ => {}
+        }
+
+        let target = items.remove(idx);
+        let expression_l = self.loc(target.expression().begin, operator_l.end);
+        items.insert(
+            idx,
+            RegexpNode::Quantified(Quantified {
+                target: Box::new(target),
+                min,
+                max,
+                kind,
+                operator_l,
+                expression_l,
+            }),
+        );
+        Ok(())
+    }
+
+    // Groups
+
+    fn parse_group(&mut self) -> Result<RegexpNode, RegexpError> {
+        let begin = self.pos;
+        self.pos += 1;
+
+        if self.peek() != Some(b'?') {
+            self.groups_count += 1;
+            let kind = GroupKind::Capture {
+                index: self.groups_count,
+            };
+            return self.parse_group_body(kind, begin, self.extended);
+        }
+        self.pos += 1;
+
+        let kind = match self.peek() {
+            None => return self.error(END_PATTERN_IN_GROUP, begin, self.pos),
+            Some(b'#') => return self.parse_group_comment(begin),
+            Some(b'(') => return self.parse_conditional(begin),
+            Some(b'i' | b'm' | b'x' | b'a' | b'd' | b'u' | b'-') => {
+                return self.parse_options(begin)
+            }
+            Some(b':') => GroupKind::NonCapture,
+            Some(b'=') => GroupKind::Lookahead,
+            Some(b'!') => GroupKind::NegativeLookahead,
+            Some(b'>') => GroupKind::Atomic,
+            Some(b'~') => GroupKind::Absent,
+            Some(b'<') if self.peek_at(1) == Some(b'=') => {
+                self.pos += 1;
+                GroupKind::Lookbehind
+            }
+            Some(b'<') if self.peek_at(1) == Some(b'!') => {
+                self.pos += 1;
+                GroupKind::NegativeLookbehind
+            }
+            Some(b'<') => return self.parse_named_group(begin, b'>'),
+            Some(b'\'') => return self.parse_named_group(begin, b'\''),
+            Some(_) => return self.error(UNDEFINED_GROUP_OPTION, begin, self.pos + 1),
+        };
+        self.pos += 1;
+
+        self.parse_group_body(kind, begin, self.extended)
+    }
+
+    fn parse_group_body(
+        &mut self,
+        kind: GroupKind,
+        begin: usize,
+        extended: bool,
+    ) -> Result<RegexpNode, RegexpError> {
+        let begin_l = self.loc(begin, self.pos);
+
+        let outer_extended = self.extended;
+        self.extended = extended;
+        let body = self.parse_alternation()?;
+        self.extended = outer_extended;
+
+        if self.peek() != Some(b')') {
+            return self.error(
+                END_PATTERN_WITH_UNMATCHED_PARENTHESIS,
+                begin,
+                self.source.len(),
+            );
+        }
+        let end_l = self.loc(self.pos, self.pos + 1);
+        self.pos += 1;
+        let expression_l = self.loc(begin, self.pos);
+
+        match kind {
+            GroupKind::Lookbehind | GroupKind::NegativeLookbehind => {
+                let negative = kind == GroupKind::NegativeLookbehind;
+                // top-level alternatives are allowed to have different length
+                let valid = match &body {
+                    RegexpNode::Alternation(Alternation { alternatives, .. }) => alternatives
+                        .iter()
+                        .all(|node| fixed_length(node, negative).is_some()),
+                    node => fixed_length(node, negative).is_some(),
+                };
+                if !valid {
+                    return self.error(INVALID_LOOK_BEHIND, expression_l.begin, expression_l.end);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(RegexpNode::Group(Group {
+            kind,
+            body: Box::new(body),
+            begin_l,
+            end_l,
+            expression_l,
+        }))
+    }
+
+    // `(?#...)`
+    fn parse_group_comment(&mut self, begin: usize) -> Result<RegexpNode, RegexpError> {
+        loop {
+            match self.peek() {
+                None => return self.error(END_PATTERN_IN_GROUP, begin, self.pos),
+                Some(b')') => break,
+                Some(b'\\') => self.pos = (self.pos + 2).min(self.source.len()),
+                Some(_) => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        Ok(RegexpNode::Comment(Comment {
+            expression_l: self.loc(begin, self.pos),
+        }))
+    }
+
+    // `(?imx-imx)` and `(?imx-imx:...)`
+    fn parse_options(&mut self, begin: usize) -> Result<RegexpNode, RegexpError> {
+        let mut on = String::new();
+        let mut off = String::new();
+        let mut negative = false;
+
+        loop {
+            match self.peek() {
+                None => return self.error(END_PATTERN_IN_GROUP, begin, self.pos),
+                Some(b'-') => negative = true,
+                Some(c @ (b'i' | b'm' | b'x')) => {
+                    if negative {
+                        off.push(c as char)
+                    } else {
+                        on.push(c as char)
+                    }
+                }
+                Some(c @ (b'a' | b'd' | b'u')) if !negative => on.push(c as char),
+                Some(b')') | Some(b':') => break,
+                Some(_) => return self.error(UNDEFINED_GROUP_OPTION, begin, self.pos + 1),
+            }
+            self.pos += 1;
+        }
+
+        let mut extended = self.extended;
+        if on.contains('x') {
+            extended = true;
+        }
+        if off.contains('x') {
+            extended = false;
+        }
+
+        if self.peek() == Some(b')') {
+            // applies to the rest of the enclosing group
+            self.pos += 1;
+            self.extended = extended;
+            return Ok(RegexpNode::Options(Options {
+                on,
+                off,
+                expression_l: self.loc(begin, self.pos),
+            }));
+        }
+
+        self.pos += 1;
+        self.parse_group_body(GroupKind::Options { on, off }, begin, extended)
+    }
+
+    // `(?<name>...)` and `(?'name'...)`
+    fn parse_named_group(
+        &mut self,
+        begin: usize,
+        terminator: u8,
+    ) -> Result<RegexpNode, RegexpError> {
+        self.pos += 1;
+        let name_begin = self.pos;
+
+        while !matches!(self.peek(), None | Some(b')')) && self.peek() != Some(terminator) {
+            self.pos += 1;
+        }
+        let name = String::from_utf8_lossy(&self.source[name_begin..self.pos]).into_owned();
+        if self.peek() != Some(terminator) {
+            return self.error(invalid_group_name(&name), begin, self.pos);
+        }
+        let name_l = self.loc(name_begin, self.pos);
+        self.pos += 1;
+
+        if name.is_empty() {
+            return self.error(EMPTY_GROUP_NAME, begin, self.pos);
+        }
+        if name.starts_with(|c: char| c.is_ascii_digit()) {
+            return self.error(invalid_group_name(&name), name_l.begin, name_l.end);
+        }
+        if !name.chars().all(is_name_char) {
+            return self.error(invalid_char_in_group_name(&name), name_l.begin, name_l.end);
+        }
+
+        self.groups_count += 1;
+        self.named_groups.push(name.clone());
+        let kind = GroupKind::NamedCapture {
+            index: self.groups_count,
+            name,
+            name_l,
+        };
+        self.parse_group_body(kind, begin, self.extended)
+    }
+
+    // `(?(cond)yes|no)`
+    fn parse_conditional(&mut self, begin: usize) -> Result<RegexpNode, RegexpError> {
+        self.pos += 1;
+        let cond_begin = self.pos;
+
+        let condition = match self.peek() {
+            Some(open @ (b'<' | b'\'')) => {
+                self.pos += 1;
+                let (reference, _level) = self.parse_reference(open, cond_begin, true)?;
+                reference
+            }
+            Some(c) if c.is_ascii_digit() || c == b'-' || c == b'+' => {
+                while self.peek().map(|c| c != b')').unwrap_or(false) {
+                    self.pos += 1;
+                }
+                let content = &self.source[cond_begin..self.pos];
+                match parse_number_reference(content) {
+                    Some(reference) => reference,
+                    None => return self.error(INVALID_CONDITION_PATTERN, begin, self.pos),
+                }
+            }
+            _ => return self.error(INVALID_CONDITION_PATTERN, begin, self.pos),
+        };
+        if self.peek() != Some(b')') {
+            return self.error(INVALID_CONDITION_PATTERN, begin, self.pos);
+        }
+        self.pos += 1;
+        let cond_l = self.loc(cond_begin, self.pos - 1);
+        self.register_ref(RefKind::Condition, &condition, cond_l)?;
+
+        let group = self.parse_group_body(GroupKind::NonCapture, begin, self.extended)?;
+        let Group {
+            body,
+            begin_l,
+            end_l,
+            expression_l,
+            ..
+        } = match group {
+            RegexpNode::Group(group) => group,
+            _ => unreachable!("parse_group_body returns a group"),
+        };
+
+        let (if_true, if_false) = match *body {
+            RegexpNode::Alternation(Alternation {
+                mut alternatives, ..
+            }) => {
+                if alternatives.len() > 2 {
+                    return self.error(
+                        INVALID_CONDITION_PATTERN,
+                        expression_l.begin,
+                        expression_l.end,
+                    );
+                }
+                let if_false = alternatives.pop().map(Box::new);
+                let if_true = Box::new(alternatives.pop().unwrap());
+                (if_true, if_false)
+            }
+            body => (Box::new(body), None),
+        };
+
+        Ok(RegexpNode::Conditional(Conditional {
+            condition,
+            if_true,
+            if_false,
+            begin_l,
+            end_l,
+            expression_l,
+        }))
+    }
+
+    // Escapes
+
+    // Parses an escape sequence at the current position, pushes parsed node(s) to `items`
+    // (`\u{41 42}` produces multiple literals)
+    fn parse_escape(
+        &mut self,
+        in_class: bool,
+        items: &mut Vec<RegexpNode>,
+    ) -> Result<(), RegexpError> {
+        let begin = self.pos;
+        self.pos += 1;
+        let (c, len) = match self.peek_char() {
+            Some(c) => c,
+            None => return self.error(END_PATTERN_AT_ESCAPE, begin, self.pos),
+        };
+        self.pos += len;
+
+        let c = match char::from_u32(c).filter(|c| c.is_ascii()) {
+            Some(c) => c,
+            None => {
+                items.push(self.literal(c, LiteralKind::Escaped, begin));
+                return Ok(());
+            }
+        };
+
+        let node = match c {
+            't' => self.literal(0x09, LiteralKind::Special, begin),
+            'n' => self.literal(0x0a, LiteralKind::Special, begin),
+            'r' => self.literal(0x0d, LiteralKind::Special, begin),
+            'f' => self.literal(0x0c, LiteralKind::Special, begin),
+            'v' => self.literal(0x0b, LiteralKind::Special, begin),
+            'a' => self.literal(0x07, LiteralKind::Special, begin),
+            'e' => self.literal(0x1b, LiteralKind::Special, begin),
+            'b' if in_class => self.literal(0x08, LiteralKind::Special, begin),
+
+            'd' => self.char_type(CharTypeKind::Digit, begin),
+            'D' => self.char_type(CharTypeKind::NonDigit, begin),
+            'w' => self.char_type(CharTypeKind::Word, begin),
+            'W' => self.char_type(CharTypeKind::NonWord, begin),
+            's' => self.char_type(CharTypeKind::Space, begin),
+            'S' => self.char_type(CharTypeKind::NonSpace, begin),
+            'h' => self.char_type(CharTypeKind::HexDigit, begin),
+            'H' => self.char_type(CharTypeKind::NonHexDigit, begin),
+            'R' if !in_class => self.char_type(CharTypeKind::Linebreak, begin),
+            'X' if !in_class => self.char_type(CharTypeKind::GraphemeCluster, begin),
+
+            'b' => self.anchor(AnchorKind::WordBoundary, begin),
+            'B' if !in_class => self.anchor(AnchorKind::NonWordBoundary, begin),
+            'A' if !in_class => self.anchor(AnchorKind::StringBegin, begin),
+            'z' if !in_class => self.anchor(AnchorKind::StringEnd, begin),
+            'Z' if !in_class => self.anchor(AnchorKind::StringEndOrNewline, begin),
+            'G' if !in_class => self.anchor(AnchorKind::SearchStart, begin),
+            'K' if !in_class => self.anchor(AnchorKind::Keep, begin),
+
+            'x' => self.parse_hex_escape(begin),
+            'u' => return self.parse_unicode_escape(begin, items),
+            'c' | 'C' | 'M' => self.parse_control_or_meta_escape(c, begin)?,
+            'p' | 'P' if self.peek() == Some(b'{') => self.parse_property(c == 'P', begin)?,
+
+            'k' if !in_class && matches!(self.peek(), Some(b'<' | b'\'')) => {
+                let open = self.peek().unwrap();
+                self.pos += 1;
+                let (reference, level) = self.parse_reference(open, begin, true)?;
+                let expression_l = self.loc(begin, self.pos);
+                self.register_ref(RefKind::Backref, &reference, expression_l)?;
+                RegexpNode::Backref(Backref {
+                    reference,
+                    level,
+                    expression_l,
+                })
+            }
+            'g' if !in_class && matches!(self.peek(), Some(b'<' | b'\'')) => {
+                let open = self.peek().unwrap();
+                self.pos += 1;
+                let (reference, _level) = self.parse_reference(open, begin, false)?;
+                let expression_l = self.loc(begin, self.pos);
+                self.register_ref(RefKind::Call, &reference, expression_l)?;
+                RegexpNode::SubexpCall(SubexpCall {
+                    reference,
+                    expression_l,
+                })
+            }
+
+            '1'..='9' if !in_class => self.parse_backref_or_octal(begin),
+            '0'..='7' => self.parse_octal(begin),
+
+            _ => self.literal(c as u32, LiteralKind::Escaped, begin),
+        };
+
+        items.push(node);
+        Ok(())
+    }
+
+    fn literal(&self, value: u32, kind: LiteralKind, begin: usize) -> RegexpNode {
+        RegexpNode::Literal(Literal {
+            value,
+            kind,
+            expression_l: self.loc(begin, self.pos),
+        })
+    }
+
+    fn char_type(&self, kind: CharTypeKind, begin: usize) -> RegexpNode {
+        RegexpNode::CharType(CharType {
+            kind,
+            expression_l: self.loc(begin, self.pos),
+        })
+    }
+
+    fn anchor(&self, kind: AnchorKind, begin: usize) -> RegexpNode {
+        RegexpNode::Anchor(Anchor {
+            kind,
+            expression_l: self.loc(begin, self.pos),
+        })
+    }
+
+    fn read_hex_digits(&mut self, max: usize) -> Option<u32> {
+        let start = self.pos;
+        let mut value: u32 = 0;
+        while self.pos - start < max {
+            match self.peek().and_then(|c| (c as char).to_digit(16)) {
+                Some(digit) => {
+                    value = value.saturating_mul(16).saturating_add(digit);
+                    self.pos += 1;
+                }
+                None => break,
+            }
+        }
+        if self.pos == start {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    // `\xHH`, `\x{HHHHHHHH}`
+    fn parse_hex_escape(&mut self, begin: usize) -> RegexpNode {
+        if self.peek() == Some(b'{') {
+            let saved = self.pos;
+            self.pos += 1;
+            match self.read_hex_digits(8) {
+                Some(value) if self.peek() == Some(b'}') => {
+                    self.pos += 1;
+                    return self.literal(value, LiteralKind::Hex, begin);
+                }
+                _ => self.pos = saved,
+            }
+        }
+
+        match self.read_hex_digits(2) {
+            Some(value) => self.literal(value, LiteralKind::Hex, begin),
+            None => self.literal('x' as u32, LiteralKind::Escaped, begin),
+        }
+    }
+
+    // `\uHHHH`, `\u{H...}`, `\u{H... H...}`
+    fn parse_unicode_escape(
+        &mut self,
+        begin: usize,
+        items: &mut Vec<RegexpNode>,
+    ) -> Result<(), RegexpError> {
+        if self.peek() != Some(b'{') {
+            let saved = self.pos;
+            match self.read_hex_digits(4) {
+                Some(value) if self.pos - saved == 4 => {
+                    items.push(self.literal(value, LiteralKind::Unicode, begin));
+                }
+                _ => {
+                    self.pos = saved;
+                    items.push(self.literal('u' as u32, LiteralKind::Escaped, begin));
+                }
+            }
+            return Ok(());
+        }
+
+        let saved = self.pos;
+        self.pos += 1;
+        let mut literals = vec![];
+        let mut literal_begin = begin;
+        loop {
+            while self.peek() == Some(b' ') {
+                self.pos += 1;
+            }
+            if self.peek() == Some(b'}') && !literals.is_empty() {
+                self.pos += 1;
+                if let Some(RegexpNode::Literal(last)) = literals.last_mut() {
+                    last.expression_l.end = self.pos;
+                }
+                items.append(&mut literals);
+                return Ok(());
+            }
+            match self.read_hex_digits(6) {
+                Some(value) => {
+                    literals.push(self.literal(value, LiteralKind::Unicode, literal_begin));
+                    literal_begin = self.pos;
+                }
+                None => break,
+            }
+        }
+
+        // not a valid escape
+        self.pos = saved;
+        items.push(self.literal('u' as u32, LiteralKind::Escaped, begin));
+        Ok(())
+    }
+
+    // `\cx`, `\C-x`, `\M-x`, `\M-\C-x`
+    fn parse_control_or_meta_escape(
+        &mut self,
+        c: char,
+        begin: usize,
+    ) -> Result<RegexpNode, RegexpError> {
+        if c != 'c' {
+            if self.peek() != Some(b'-') {
+                return Ok(self.literal(c as u32, LiteralKind::Escaped, begin));
+            }
+            self.pos += 1;
+        }
+
+        let value = match self.peek() {
+            None => return self.error(END_PATTERN_AT_ESCAPE, begin, self.pos),
+            Some(b'\\') => {
+                let mut nested = vec![];
+                self.parse_escape(true, &mut nested)?;
+                match nested.pop() {
+                    Some(RegexpNode::Literal(Literal { value, .. })) => value,
+                    _ => return Ok(self.literal(c as u32, LiteralKind::Escaped, begin)),
+                }
+            }
+            Some(_) => {
+                let (value, len) = self.peek_char().unwrap();
+                self.pos += len;
+                value
+            }
+        };
+
+        if c == 'M' {
+            Ok(self.literal((value & 0xff) | 0x80, LiteralKind::Meta, begin))
+        } else if value == '?' as u32 {
+            Ok(self.literal(0x7f, LiteralKind::Control, begin))
+        } else {
+            Ok(self.literal(value & 0x9f, LiteralKind::Control, begin))
+        }
+    }
+
+    // `\p{Name}`, `\p{^Name}`, `\P{Name}`
+    fn parse_property(&mut self, negated: bool, begin: usize) -> Result<RegexpNode, RegexpError> {
+        self.pos += 1;
+        let mut negated = negated;
+        if self.peek() == Some(b'^') {
+            negated = !negated;
+            self.pos += 1;
+        }
+        let name_begin = self.pos;
+        while let Some(c) = self.peek() {
+            if c == b'}' {
+                break;
+            }
+            if !(c.is_ascii_alphanumeric() || c == b'_' || c == b' ' || c == b'-' || !c.is_ascii())
+            {
+                break;
+            }
+            self.pos += 1;
+        }
+        let name = String::from_utf8_lossy(&self.source[name_begin..self.pos]).into_owned();
+        if self.peek() != Some(b'}') || name.is_empty() {
+            return self.error(invalid_char_property_name(&name), begin, self.pos);
+        }
+        let name_l = self.loc(name_begin, self.pos);
+        self.pos += 1;
+
+        Ok(RegexpNode::Property(Property {
+            name,
+            negated,
+            name_l,
+            expression_l: self.loc(begin, self.pos),
+        }))
+    }
+
+    // `\1`..`\9` are always backreferences,
+    // `\10` and above are backreferences only if there are enough groups,
+    // otherwise it's an octal escape
+    fn parse_backref_or_octal(&mut self, begin: usize) -> RegexpNode {
+        let digits_begin = self.pos - 1;
+        let mut pos = digits_begin;
+        let mut number: usize = 0;
+        while let Some(c) = self.source.get(pos).filter(|c| c.is_ascii_digit()) {
+            number = number
+                .saturating_mul(10)
+                .saturating_add((c - b'0') as usize);
+            pos += 1;
+        }
+
+        if (number <= 9 || number <= self.groups_count) && number <= MAX_BACKREF_NUM {
+            self.pos = pos;
+            let expression_l = self.loc(begin, self.pos);
+            self.refs.push(PendingRef {
+                kind: RefKind::Backref,
+                target: GroupReference::Number(number),
+                loc: expression_l,
+            });
+            return RegexpNode::Backref(Backref {
+                reference: GroupReference::Number(number),
+                level: None,
+                expression_l,
+            });
+        }
+
+        match self.source[digits_begin] {
+            c @ (b'8' | b'9') => self.literal(c as u32, LiteralKind::Escaped, begin),
+            _ => self.parse_octal(begin),
+        }
+    }
+
+    // `\0`, `\012`, `\177`
+    fn parse_octal(&mut self, begin: usize) -> RegexpNode {
+        // first digit is already consumed
+        self.pos -= 1;
+        let start = self.pos;
+        let mut value: u32 = 0;
+        while self.pos - start < 3 {
+            match self.peek().filter(|c| (b'0'..=b'7').contains(c)) {
+                Some(c) => {
+                    value = value * 8 + (c - b'0') as u32;
+                    self.pos += 1;
+                }
+                None => break,
+            }
+        }
+        self.literal(value, LiteralKind::Octal, begin)
+    }
+
+    // Parses `name>`, `1>`, `-1>`, `name+1>` (with given terminator),
+    // opening `<` or `'` is already consumed
+    fn parse_reference(
+        &mut self,
+        open: u8,
+        begin: usize,
+        allow_level: bool,
+    ) -> Result<(GroupReference, Option<i32>), RegexpError> {
+        let terminator = if open == b'<' { b'>' } else { b'\'' };
+        let content_begin = self.pos;
+        while !matches!(self.peek(), None | Some(b')')) && self.peek() != Some(terminator) {
+            self.pos += 1;
+        }
+        let content = &self.source[content_begin..self.pos];
+        let content_s = String::from_utf8_lossy(content).into_owned();
+        if self.peek() != Some(terminator) {
+            return self.error(invalid_group_name(&content_s), begin, self.pos);
+        }
+        self.pos += 1;
+
+        if content.is_empty() {
+            return self.error(EMPTY_GROUP_NAME, begin, self.pos);
+        }
+
+        // nest level, `name+1`
+        let (content, level) = match split_level(content) {
+            Some((content, level)) if allow_level => (content, Some(level)),
+            _ => (content, None),
+        };
+
+        if content[0].is_ascii_digit() || content[0] == b'-' || content[0] == b'+' {
+            return match parse_number_reference(content) {
+                Some(reference) => Ok((reference, level)),
+                None => self.error(invalid_group_name(&content_s), begin, self.pos),
+            };
+        }
+
+        let name = String::from_utf8_lossy(content).into_owned();
+        if !name.chars().all(is_name_char) {
+            return self.error(invalid_char_in_group_name(&content_s), begin, self.pos);
+        }
+        Ok((GroupReference::Name(name), level))
+    }
+
+    fn register_ref(
+        &mut self,
+        kind: RefKind,
+        reference: &GroupReference,
+        loc: Loc,
+    ) -> Result<(), RegexpError> {
+        let target = match reference {
+            GroupReference::Number(number) => GroupReference::Number(*number),
+            GroupReference::Relative(offset) => {
+                // `\k<-1>` is the last group, `\g<+1>` is the next group
+                let base = if *offset < 0 {
+                    self.groups_count as isize + 1
+                } else {
+                    self.groups_count as isize
+                };
+                let number = base + offset;
+                if number <= 0 {
+                    return self.error(INVALID_BACKREF, loc.begin, loc.end);
+                }
+                GroupReference::Number(number as usize)
+            }
+            GroupReference::Name(name) => {
+                // named backreferences can only refer to groups defined before
+                if kind != RefKind::Call && !self.named_groups.contains(name) {
+                    return self.error(undefined_name_reference(name), loc.begin, loc.end);
+                }
+                GroupReference::Name(name.clone())
+            }
+        };
+        self.refs.push(PendingRef { kind, target, loc });
+        Ok(())
+    }
+
+    fn validate_refs(&self) -> Result<(), RegexpError> {
+        let has_named_groups = !self.named_groups.is_empty();
+
+        for PendingRef { kind, target, loc } in self.refs.iter() {
+            match target {
+                GroupReference::Number(0) if *kind == RefKind::Call => {
+                    // `\g<0>` is a call of the whole pattern
+                }
+                GroupReference::Number(_) if has_named_groups => {
+                    return self.error(NUMBERED_BACKREF_OR_CALL_NOT_ALLOWED, loc.begin, loc.end)
+                }
+                GroupReference::Number(number) if *number == 0 || *number > self.groups_count => {
+                    return match kind {
+                        RefKind::Call => self.error(
+                            undefined_group_reference(&number.to_string()),
+                            loc.begin,
+                            loc.end,
+                        ),
+                        _ => self.error(INVALID_BACKREF, loc.begin, loc.end),
+                    };
+                }
+                GroupReference::Name(name) if !self.named_groups.contains(name) => {
+                    return self.error(undefined_name_reference(name), loc.begin, loc.end)
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    // Character classes
+
+    fn parse_class(&mut self) -> Result<CharClass, RegexpError> {
+        let begin = self.pos;
+        self.pos += 1;
+        let negated = self.peek() == Some(b'^');
+        if negated {
+            self.pos += 1;
+        }
+        let begin_l = self.loc(begin, self.pos);
+
+        if self.peek() == Some(b']') && !self.source[self.pos + 1..].contains(&b']') {
+            return self.error(EMPTY_CHAR_CLASS, begin, self.pos + 1);
+        }
+
+        let items = self.parse_class_items(begin, true)?;
+
+        let end_l = self.loc(self.pos, self.pos + 1);
+        self.pos += 1;
+
+        Ok(CharClass {
+            negated,
+            items,
+            begin_l,
+            end_l,
+            expression_l: self.loc(begin, self.pos),
+        })
+    }
+
+    // Parses items of the class until `]` (which is not consumed)
+    fn parse_class_items(
+        &mut self,
+        class_begin: usize,
+        leading: bool,
+    ) -> Result<Vec<ClassItem>, RegexpError> {
+        let mut items = vec![];
+        // `]` right after `[` is a plain character
+        let mut leading = leading;
+
+        loop {
+            let item = match self.peek() {
+                None => {
+                    return self.error(PREMATURE_END_OF_CHAR_CLASS, class_begin, self.source.len())
+                }
+                Some(b']') if !leading => break,
+                Some(b'&') if self.peek_at(1) == Some(b'&') => {
+                    let operator_l = self.loc(self.pos, self.pos + 2);
+                    self.pos += 2;
+                    let rhs = self.parse_class_items(class_begin, false)?;
+                    let begin = items
+                        .first()
+                        .map(|item: &ClassItem| item.expression().begin)
+                        .unwrap_or(operator_l.begin);
+                    let lhs = std::mem::take(&mut items);
+                    items.push(ClassItem::Intersection(ClassIntersection {
+                        lhs,
+                        rhs,
+                        operator_l,
+                        expression_l: self.loc(begin, self.pos),
+                    }));
+                    break;
+                }
+                Some(_) => {
+                    let (item, extra) = self.parse_class_atom(class_begin)?;
+                    if !extra.is_empty() {
+                        items.push(item);
+                        items.extend(extra);
+                        leading = false;
+                        continue;
+                    }
+                    item
+                }
+            };
+            leading = false;
+
+            let from = match item {
+                ClassItem::Literal(literal)
+                    if self.peek() == Some(b'-')
+                        && !matches!(self.peek_at(1), None | Some(b']')) =>
+                {
+                    literal
+                }
+                item => {
+                    items.push(item);
+                    continue;
+                }
+            };
+
+            let operator_l = self.loc(self.pos, self.pos + 1);
+            self.pos += 1;
+            let (to, extra) = if self.peek() == Some(b'[') {
+                // `[a-[b]]`, `-` is a plain character
+                (None, vec![])
+            } else {
+                let (to, extra) = self.parse_class_atom(class_begin)?;
+                (Some(to), extra)
+            };
+
+            match to {
+                Some(ClassItem::Literal(to)) => {
+                    let expression_l = self.loc(from.expression_l.begin, to.expression_l.end);
+                    if from.value > to.value {
+                        return self.error(
+                            EMPTY_RANGE_IN_CHAR_CLASS,
+                            expression_l.begin,
+                            expression_l.end,
+                        );
+                    }
+                    items.push(ClassItem::Range(ClassRange {
+                        from,
+                        to,
+                        operator_l,
+                        expression_l,
+                    }));
+                }
+                to => {
+                    items.push(ClassItem::Literal(from));
+                    items.push(ClassItem::Literal(Literal {
+                        value: b'-' as u32,
+                        kind: LiteralKind::Plain,
+                        expression_l: operator_l,
+                    }));
+                    if let Some(to) = to {
+                        items.push(to);
+                    }
+                }
+            }
+            items.extend(extra);
+        }
+
+        Ok(items)
+    }
+
+    // Parses a single item of the class, `\u{61 62}` is the only
+    // escape that produces multiple items, extra items are returned separately
+    fn parse_class_atom(
+        &mut self,
+        class_begin: usize,
+    ) -> Result<(ClassItem, Vec<ClassItem>), RegexpError> {
+        match self.peek() {
+            Some(b'[') => {
+                if self.peek_at(1) == Some(b':') {
+                    if let Some(posix) = self.parse_posix_class()? {
+                        return Ok((ClassItem::Posix(posix), vec![]));
+                    }
+                }
+                Ok((ClassItem::Class(self.parse_class()?), vec![]))
+            }
+            Some(b'\\') => {
+                let mut nodes = vec![];
+                self.parse_escape(true, &mut nodes)?;
+                let mut items = nodes.into_iter().map(|node| match node {
+                    RegexpNode::Literal(literal) => ClassItem::Literal(literal),
+                    RegexpNode::CharType(char_type) => ClassItem::CharType(char_type),
+                    RegexpNode::Property(property) => ClassItem::Property(property),
+                    _ => unreachable!("only literals and char types are allowed in classes"),
+                });
+                let item = items.next().expect("escape produces at least one node");
+                Ok((item, items.collect()))
+            }
+            Some(_) => match self.parse_plain_literal() {
+                RegexpNode::Literal(literal) => Ok((ClassItem::Literal(literal), vec![])),
+                _ => unreachable!("parse_plain_literal returns a literal"),
+            },
+            None => self.error(PREMATURE_END_OF_CHAR_CLASS, class_begin, self.source.len()),
+        }
+    }
+
+    // `[:alpha:]`, `[:^alpha:]`
+    // Returns `None` if it's not a POSIX bracket (i.e. it's a nested class)
+    fn parse_posix_class(&mut self) -> Result<Option<PosixClass>, RegexpError> {
+        let begin = self.pos;
+        let mut pos = self.pos + 2;
+        let negated = self.source.get(pos) == Some(&b'^');
+        if negated {
+            pos += 1;
+        }
+        let name_begin = pos;
+        while self
+            .source
+            .get(pos)
+            .map(|c| c.is_ascii_alphabetic())
+            .unwrap_or(false)
+        {
+            pos += 1;
+        }
+        if name_begin == pos || !self.source[pos..].starts_with(b":]") {
+            return Ok(None);
+        }
+        let name = String::from_utf8_lossy(&self.source[name_begin..pos]).into_owned();
+        pos += 2;
+
+        if !POSIX_BRACKET_NAMES.contains(&name.as_str()) {
+            return self.error(INVALID_POSIX_BRACKET_TYPE, begin, pos);
+        }
+        self.pos = pos;
+
+        Ok(Some(PosixClass {
+            name,
+            negated,
+            expression_l: self.loc(begin, self.pos),
+        }))
+    }
+}
+
+// Group names can contain only word characters
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || !c.is_ascii()
+}
+
+// Parses `1`, `-1`, `+1`
+fn parse_number_reference(content: &[u8]) -> Option<GroupReference> {
+    let (sign, digits) = match content.first()? {
+        b'-' => (Some(-1), &content[1..]),
+        b'+' => (Some(1), &content[1..]),
+        _ => (None, content),
+    };
+    if digits.is_empty() || !digits.iter().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let number: usize = std::str::from_utf8(digits).ok()?.parse().ok()?;
+
+    match sign {
+        Some(_) if number == 0 => None,
+        Some(sign) => Some(GroupReference::Relative(sign * number as isize)),
+        None => Some(GroupReference::Number(number)),
+    }
+}
+
+// Splits `name+1` into `name` and `1`
+fn split_level(content: &[u8]) -> Option<(&[u8], i32)> {
+    let idx = content
+        .iter()
+        .rposition(|c| *c == b'+' || *c == b'-')
+        .filter(|idx| *idx > 0)?;
+    let level = &content[idx + 1..];
+    if level.is_empty() || !level.iter().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let level: i32 = std::str::from_utf8(level).ok()?.parse().ok()?;
+    let level = if content[idx] == b'-' { -level } else { level };
+    Some((&content[..idx], level))
+}
+
+// Returns a length of the text that matches given node,
+// or `None` if it's not fixed (or if node is not allowed in look-behind)
+fn fixed_length(node: &RegexpNode, negative: bool) -> Option<usize> {
+    match node {
+        RegexpNode::Alternation(Alternation { alternatives, .. }) => {
+            let mut lengths = alternatives.iter().map(|node| fixed_length(node, negative));
+            let first = lengths.next()??;
+            for length in lengths {
+                if length? != first {
+                    return None;
+                }
+            }
+            Some(first)
+        }
+        RegexpNode::Sequence(Sequence { items, .. }) => {
+            items.iter().map(|node| fixed_length(node, negative)).sum()
+        }
+        RegexpNode::CharType(CharType {
+            kind: CharTypeKind::Linebreak | CharTypeKind::GraphemeCluster,
+            ..
+        }) => None,
+        RegexpNode::Literal(_)
+        | RegexpNode::CharType(_)
+        | RegexpNode::Property(_)
+        | RegexpNode::CharClass(_) => Some(1),
+        RegexpNode::Anchor(_) | RegexpNode::Options(_) | RegexpNode::Comment(_) => Some(0),
+        RegexpNode::Backref(_) | RegexpNode::SubexpCall(_) | RegexpNode::Conditional(_) => None,
+        RegexpNode::Group(Group { kind, body, .. }) => match kind {
+            GroupKind::Lookahead
+            | GroupKind::NegativeLookahead
+            | GroupKind::Lookbehind
+            | GroupKind::NegativeLookbehind => Some(0),
+            GroupKind::Capture { .. } | GroupKind::NamedCapture { .. } if negative => None,
+            GroupKind::Absent => None,
+            _ => fixed_length(body, negative),
+        },
+        RegexpNode::Quantified(Quantified {
+            target, min, max, ..
+        }) => {
+            if Some(*min) != *max {
+                return None;
+            }
+            Some(fixed_length(target, negative)? * min)
+        }
+    }
+}
+
+// If regexp has named groups plain groups are not capturing,
+// and named groups are numbered separately
+fn renumber_named_groups(node: &mut RegexpNode, index: &mut usize) {
+    match node {
+        RegexpNode::Alternation(Alternation { alternatives, .. }) => {
+            for node in alternatives.iter_mut() {
+                renumber_named_groups(node, index)
+            }
+        }
+        RegexpNode::Sequence(Sequence { items, .. }) => {
+            for node in items.iter_mut() {
+                renumber_named_groups(node, index)
+            }
+        }
+        RegexpNode::Group(group) => {
+            match &mut group.kind {
+                GroupKind::Capture { .. } => group.kind = GroupKind::NonCapture,
+                GroupKind::NamedCapture { index: idx, .. } => {
+                    *index += 1;
+                    *idx = *index;
+                }
+                _ => {}
+            }
+            renumber_named_groups(&mut group.body, index)
+        }
+        RegexpNode::Conditional(Conditional {
+            if_true, if_false, ..
+        }) => {
+            renumber_named_groups(if_true, index);
+            if let Some(if_false) = if_false {
+                renumber_named_groups(if_false, index)
+            }
+        }
+        RegexpNode::Quantified(Quantified { target, .. }) => renumber_named_groups(target, index),
+        RegexpNode::Literal(_)
+        | RegexpNode::CharType(_)
+        | RegexpNode::Property(_)
+        | RegexpNode::CharClass(_)
+        | RegexpNode::Anchor(_)
+        | RegexpNode::Backref(_)
+        | RegexpNode::SubexpCall(_)
+        | RegexpNode::Options(_)
+        | RegexpNode::Comment(_) => {}
+    }
+}
+
+#[cfg(test)]
+fn error_message(source: &str) -> Option<String> {
+    parse(source.as_bytes(), "").err().map(|err| err.message)
+}
+
+#[test]
+fn test_named_captures() {
+    let tree = parse(b"(?<year>\\d+)-(\\d+)-(?<day>\\d+)(?<year>x)?", "").unwrap();
+    assert_eq!(tree.named_captures, vec!["year", "day"]);
+    assert_eq!(tree.captures_count, 3);
+
+    let tree = parse(b"(a)(b(c))", "").unwrap();
+    assert!(tree.named_captures.is_empty());
+    assert_eq!(tree.captures_count, 3);
+}
+
+#[test]
+fn test_locations() {
+    let tree = parse(b"a(?<n>b+)|c", "").unwrap();
+    let alternatives = match tree.root {
+        RegexpNode::Alternation(Alternation {
+            alternatives,
+            pipes_l,
+            ..
+        }) => {
+            assert_eq!(pipes_l, vec![Loc { begin: 9, end: 10 }]);
+            alternatives
+        }
+        other => panic!("expected alternation, got {:?}", other),
+    };
+    let items = match &alternatives[0] {
+        RegexpNode::Sequence(Sequence { items, .. }) => items,
+        other => panic!("expected sequence, got {:?}", other),
+    };
+    match &items[1] {
+        RegexpNode::Group(Group {
+            kind: GroupKind::NamedCapture { name, name_l, .. },
+            body,
+            begin_l,
+            end_l,
+            expression_l,
+        }) => {
+            assert_eq!(name, "n");
+            assert_eq!(*name_l, Loc { begin: 4, end: 5 });
+            assert_eq!(*begin_l, Loc { begin: 1, end: 6 });
+            assert_eq!(*end_l, Loc { begin: 8, end: 9 });
+            assert_eq!(*expression_l, Loc { begin: 1, end: 9 });
+            assert_eq!(*body.expression(), Loc { begin: 6, end: 8 });
+        }
+        other => panic!("expected named group, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_extended_mode() {
+    assert!(parse(b" ?", "").is_ok());
+    assert_eq!(
+        parse(b" ?", "x").map_err(|err| err.message),
+        Err(String::from(TARGET_OF_REPEAT_OPERATOR_NOT_SPECIFIED))
+    );
+    assert!(parse(b"a  # comment (\n  ?", "x").is_ok());
+    assert!(parse(b"(?x) a # (\n b", "").is_ok());
+    assert!(parse(b"(?x: a # (\n) b", "").is_ok());
+}
+
+#[test]
+fn test_valid_regexps() {
+    for source in [
+        "",
+        "a|",
+        "[]a]",
+        "[a-z&&[^aeiou]]",
+        "[[:alpha:][:^digit:]]",
+        "a{,2}",
+        "a{1,}?",
+        "{",
+        "a{,}",
+        "x{2}?",
+        "a**",
+        "\\1(a)",
+        "(a)\\k<1>",
+        "(?<a>.)\\k<a+0>",
+        "(?<a>.)\\g<a>",
+        "\\g<a>(?<a>.)",
+        "(?<=ab|c)",
+        "(?<!a(?:b))",
+        "(?(1)a|b)()",
+        "(?i-mx:a)",
+        "\\p{^Alnum}",
+        "\\u{41 42}*",
+        "[\\u{41 42}-z]",
+        "\\M-\\C-a",
+        "(?#comment)",
+        "\\xa8",
+        "\\10",
+    ] {
+        if let Err(err) = parse(source.as_bytes(), "") {
+            panic!("{:?} is expected to be valid, got {:?}", source, err)
+        }
+    }
+}
+
+#[test]
+fn test_errors() {
+    for (source, message) in [
+        ("?", TARGET_OF_REPEAT_OPERATOR_NOT_SPECIFIED),
+        ("a|*", TARGET_OF_REPEAT_OPERATOR_NOT_SPECIFIED),
+        ("(?i)+", TARGET_OF_REPEAT_OPERATOR_NOT_SPECIFIED),
+        ("^*", TARGET_OF_REPEAT_OPERATOR_INVALID),
+        ("(?=a)?", TARGET_OF_REPEAT_OPERATOR_INVALID),
+        ("[a", PREMATURE_END_OF_CHAR_CLASS),
+        ("[]", EMPTY_CHAR_CLASS),
+        ("[z-a]", EMPTY_RANGE_IN_CHAR_CLASS),
+        ("[[:foo:]]", INVALID_POSIX_BRACKET_TYPE),
+        ("(a", END_PATTERN_WITH_UNMATCHED_PARENTHESIS),
+        ("a)", UNMATCHED_CLOSE_PARENTHESIS),
+        ("(?", END_PATTERN_IN_GROUP),
+        ("(?#a", END_PATTERN_IN_GROUP),
+        ("(?z)", UNDEFINED_GROUP_OPTION),
+        ("(?-a)", UNDEFINED_GROUP_OPTION),
+        ("a{3,2}", UPPER_SMALLER_THAN_LOWER_IN_REPEAT_RANGE),
+        ("a{100001}", TOO_BIG_NUMBER_FOR_REPEAT_RANGE),
+        ("(?<>a)", EMPTY_GROUP_NAME),
+        ("\\1", INVALID_BACKREF),
+        ("(a)\\k<-2>", INVALID_BACKREF),
+        ("(?<a>.)(.)\\1", NUMBERED_BACKREF_OR_CALL_NOT_ALLOWED),
+        ("(?<=a+)", INVALID_LOOK_BEHIND),
+        ("(?<=a|bc(d|ef))", INVALID_LOOK_BEHIND),
+        ("(?<!(a))", INVALID_LOOK_BEHIND),
+        ("(?<=(.)\\1)", INVALID_LOOK_BEHIND),
+        ("(?(a)b)", INVALID_CONDITION_PATTERN),
+        ("(?(1)a|b|c)()", INVALID_CONDITION_PATTERN),
+        ("a\\", END_PATTERN_AT_ESCAPE),
+    ] {
+        assert_eq!(
+            error_message(source),
+            Some(String::from(message)),
+            "wrong error for {:?}",
+            source
+        );
+    }
+
+    assert_eq!(error_message("(?<1a>.)"), Some(invalid_group_name("1a")));
+    assert_eq!(error_message("(?<a"), Some(invalid_group_name("a")));
+    assert_eq!(
+        error_message("(?<a-b>.)"),
+        Some(invalid_char_in_group_name("a-b"))
+    );
+    assert_eq!(
+        error_message("\\k<a>(?<a>.)"),
+        Some(undefined_name_reference("a"))
+    );
+    assert_eq!(error_message("\\g<a>"), Some(undefined_name_reference("a")));
+    assert_eq!(
+        error_message("()\\g<2>"),
+        Some(undefined_group_reference("2"))
+    );
+    assert_eq!(error_message("\\p{}"), Some(invalid_char_property_name("")));
+    assert_eq!(
+        error_message("\\p{Alpha"),
+        Some(invalid_char_property_name("Alpha"))
+    );
+}
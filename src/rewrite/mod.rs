@@ -0,0 +1,190 @@
+mod fold_gen;
+pub use fold_gen::*;
+
+mod visitor_mut_gen;
+pub use visitor_mut_gen::*;
+
+mod expression_gen;
+pub use expression_gen::expression_mut;
+
+mod children_gen;
+pub(crate) use children_gen::{for_each_child, take_children};
+
+use crate::nodes::Nil;
+use crate::Node;
+
+/// Replaces the node in place and returns the replaced node.
+///
+/// The new node takes `expression_l` of the replaced one,
+/// so locations of its ancestors stay consistent.
+pub fn replace(node: &mut Node, mut new_node: Node) -> Node {
+    *expression_mut(&mut new_node) = *node.expression();
+    std::mem::replace(node, new_node)
+}
+
+/// Wraps the node in place, `f` receives the node and returns a wrapper
+/// (like `Begin` with the node as the only statement).
+///
+/// The wrapper takes `expression_l` of the wrapped node.
+pub fn wrap<F: FnOnce(Node) -> Node>(node: &mut Node, f: F) {
+    let node_l = *node.expression();
+    let inner = std::mem::replace(node, Node::Nil(Nil { expression_l: node_l }));
+    let mut wrapper = f(inner);
+    *expression_mut(&mut wrapper) = node_l;
+    *node = wrapper;
+}
+
+/// Takes the node out and leaves `nil` with the same `expression_l` in its place.
+///
+/// Useful for children that can't be absent (like `lhs` of `And`),
+/// optional children can be removed using `Option::take`
+/// and lists of children can be changed using `Fold::fold_nodes` or `VisitorMut::visit_nodes`.
+pub fn take(node: &mut Node) -> Node {
+    let expression_l = *node.expression();
+    std::mem::replace(node, Node::Nil(Nil { expression_l }))
+}
+
+/// Drops the node without recursion.
+///
+/// Dropping a `Node` (like any `Box`-based tree) is recursive,
+/// so a deeply nested tree (like `[[[[...]]]]` from untrusted input)
+/// can overflow the stack when it goes out of scope, this function can't.
+pub fn drop_node(node: Node) {
+    let mut stack = vec![node];
+    while let Some(mut node) = stack.pop() {
+        take_children(&mut node, &mut stack);
+        // `node` has no children at this point and is dropped here
+    }
+}
+
+#[cfg(test)]
+use crate::nodes::{Begin, Int, Lvar, Send};
+#[cfg(test)]
+use crate::Loc;
+
+// foo(a, 1)
+#[cfg(test)]
+fn test_ast() -> Node {
+    let loc = |begin, end| Loc { begin, end };
+    Node::Send(Send {
+        recv: None,
+        method_name: String::from("foo"),
+        args: vec![
+            Node::Lvar(Lvar {
+                name: String::from("a"),
+                expression_l: loc(4, 5),
+            }),
+            Node::Int(Int {
+                value: String::from("1"),
+                operator_l: None,
+                expression_l: loc(7, 8),
+            }),
+        ],
+        dot_l: None,
+        selector_l: Some(loc(0, 3)),
+        begin_l: Some(loc(3, 4)),
+        end_l: Some(loc(8, 9)),
+        operator_l: None,
+        expression_l: loc(0, 9),
+    })
+}
+
+#[test]
+fn test_fold() {
+    // replaces all integers with `nil` and removes local variables
+    struct Folder;
+    impl Fold for Folder {
+        fn on_int(&mut self, node: Int) -> Node {
+            Node::Nil(Nil {
+                expression_l: node.expression_l,
+            })
+        }
+
+        fn fold_nodes(&mut self, nodes: Vec<Node>) -> Vec<Node> {
+            nodes
+                .into_iter()
+                .filter(|node| !matches!(node, Node::Lvar(_)))
+                .map(|node| self.fold(node))
+                .collect()
+        }
+    }
+
+    let ast = Folder.fold(test_ast());
+    assert_eq!(ast.inspect(0), "s(:send, nil, \"foo\",\n  s(:nil))");
+}
+
+#[test]
+fn test_visitor_mut() {
+    // renames all local variables
+    struct Renamer;
+    impl VisitorMut for Renamer {
+        fn on_lvar(&mut self, node: &mut Lvar) {
+            node.name = String::from("b");
+        }
+    }
+
+    let mut ast = test_ast();
+    Renamer.visit(&mut ast);
+    assert_eq!(
+        ast.inspect(0),
+        "s(:send, nil, \"foo\",\n  s(:lvar, \"b\"),\n  s(:int, \"1\"))"
+    );
+}
+
+#[test]
+fn test_replace_wrap_take() {
+    let mut ast = test_ast();
+    let args = match &mut ast {
+        Node::Send(send) => &mut send.args,
+        _ => unreachable!(),
+    };
+
+    let replaced = replace(
+        &mut args[1],
+        Node::Nil(Nil {
+            expression_l: Loc::default(),
+        }),
+    );
+    assert_eq!(replaced.str_type(), "int");
+    assert_eq!(args[1].expression(), &Loc { begin: 7, end: 8 });
+
+    wrap(&mut args[0], |node| {
+        Node::Begin(Begin {
+            statements: vec![node],
+            begin_l: None,
+            end_l: None,
+            expression_l: Loc::default(),
+        })
+    });
+    assert_eq!(args[0].str_type(), "begin");
+    assert_eq!(args[0].expression(), &Loc { begin: 4, end: 5 });
+
+    let taken = take(&mut args[0]);
+    assert_eq!(taken.str_type(), "begin");
+    assert_eq!(
+        ast.inspect(0),
+        "s(:send, nil, \"foo\",\n  s(:nil),\n  s(:nil))"
+    );
+}
+
+#[test]
+fn test_drop_node() {
+    // [[[...]]] that is too deep for a recursive drop on a small stack
+    let mut ast = Node::Nil(Nil {
+        expression_l: Loc::default(),
+    });
+    for _ in 0..100_000 {
+        ast = Node::Begin(Begin {
+            statements: vec![ast],
+            begin_l: None,
+            end_l: None,
+            expression_l: Loc::default(),
+        });
+    }
+    std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(move || drop_node(ast))
+        .unwrap()
+        .join()
+        .unwrap();
+}
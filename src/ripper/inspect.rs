@@ -0,0 +1,116 @@
+/// Returns a string literal like the one that Ruby's `String#inspect`
+/// returns for a UTF-8 string with given bytes
+pub(crate) fn inspect_str(bytes: &[u8]) -> String {
+    let mut out = String::from("\"");
+    let mut rest = bytes;
+
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(valid) => {
+                push_escaped(&mut out, valid);
+                break;
+            }
+            Err(err) => {
+                let (valid, invalid) = rest.split_at(err.valid_up_to());
+                push_escaped(&mut out, std::str::from_utf8(valid).unwrap_or_default());
+                let invalid_len = err.error_len().unwrap_or(invalid.len());
+                for byte in &invalid[..invalid_len] {
+                    out.push_str(&format!("\\x{:02X}", byte));
+                }
+                rest = &invalid[invalid_len..];
+            }
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+fn push_escaped(out: &mut String, s: &str) {
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\x0C' => out.push_str("\\f"),
+            '\x0B' => out.push_str("\\v"),
+            '\x08' => out.push_str("\\b"),
+            '\x07' => out.push_str("\\a"),
+            '\x1B' => out.push_str("\\e"),
+            '\x7F' => out.push_str("\\x7F"),
+            '#' if matches!(chars.peek(), Some('{' | '$' | '@')) => out.push_str("\\#"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+}
+
+const OPERATORS: &[&str] = &[
+    "+", "-", "*", "/", "%", "**", "==", "===", "!=", "=~", "!~", "!", "<", "<=", ">", ">=", "<=>",
+    "<<", ">>", "&", "|", "^", "~", "+@", "-@", "[]", "[]=", "`",
+];
+
+/// Returns a symbol literal like the one that Ruby's `Symbol#inspect` returns
+pub(crate) fn inspect_sym(name: &str) -> String {
+    if is_simple_symbol(name) {
+        format!(":{}", name)
+    } else {
+        format!(":{}", inspect_str(name.as_bytes()))
+    }
+}
+
+fn is_simple_symbol(name: &str) -> bool {
+    if OPERATORS.contains(&name) {
+        return true;
+    }
+
+    let ident = name
+        .strip_prefix("@@")
+        .or_else(|| name.strip_prefix('@'))
+        .or_else(|| name.strip_prefix('$'));
+    let (ident, may_have_suffix) = match ident {
+        Some(ident) => (ident, false),
+        None => (name, true),
+    };
+    let ident = if may_have_suffix {
+        ident
+            .strip_suffix(['?', '!', '='])
+            .filter(|ident| !ident.ends_with(['?', '!', '=']))
+            .unwrap_or(ident)
+    } else {
+        ident
+    };
+
+    let mut chars = ident.chars();
+    match chars.next() {
+        Some(c) if c == '_' || c.is_alphabetic() || !c.is_ascii() => {}
+        _ => return false,
+    }
+    chars.all(|c| c == '_' || c.is_alphanumeric() || !c.is_ascii())
+}
+
+#[test]
+fn test_inspect_str() {
+    assert_eq!(inspect_str(b"foo"), r#""foo""#);
+    assert_eq!(inspect_str(b"a\"b\\c"), r#""a\"b\\c""#);
+    assert_eq!(inspect_str(b"\n\t\x1b\x01\x7f"), r#""\n\t\e\u0001\x7F""#);
+    assert_eq!(inspect_str(b"#{a} #b #@c"), r##""\#{a} #b \#@c""##);
+    assert_eq!(inspect_str("ü".as_bytes()), "\"ü\"");
+    assert_eq!(inspect_str(b"a\xffb"), r#""a\xFFb""#);
+}
+
+#[test]
+fn test_inspect_sym() {
+    assert_eq!(inspect_sym("foo"), ":foo");
+    assert_eq!(inspect_sym("foo?"), ":foo?");
+    assert_eq!(inspect_sym("foo="), ":foo=");
+    assert_eq!(inspect_sym("@ident"), ":@ident");
+    assert_eq!(inspect_sym("+"), ":+");
+    assert_eq!(inspect_sym("-@"), ":-@");
+    assert_eq!(inspect_sym("&&"), ":\"&&\"");
+    assert_eq!(inspect_sym("::"), ":\"::\"");
+    assert_eq!(inspect_sym("foo bar"), ":\"foo bar\"");
+}
@@ -0,0 +1,295 @@
+use super::inspect::inspect_str;
+use crate::lex_states::*;
+use crate::source::DecodedInput;
+use crate::{LexState, Loc, LocExt, ParserResult, Token};
+
+/// Token in the format of `Ripper.lex`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RipperToken {
+    /// Line of the token, starts from 1
+    pub line: usize,
+
+    /// Byte offset of the token in its line, starts from 0
+    pub column: usize,
+
+    /// Name of the scanner event, like `on_ident`
+    pub event: &'static str,
+
+    /// Source code of the token
+    pub token: String,
+
+    /// State of the lexer after the token
+    pub state: LexState,
+}
+
+impl std::fmt::Display for RipperToken {
+    /// Prints the token like `pp` prints elements of `Ripper.lex`,
+    /// i.e. `[[1, 0], :on_ident, "foo", CMDARG]`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[[{}, {}], :{}, {}, {}]",
+            self.line,
+            self.column,
+            self.event,
+            inspect_str(self.token.as_bytes()),
+            state_name(self.state)
+        )
+    }
+}
+
+/// Returns a name of the lexer state the way `Ripper::Lexer::State` prints it,
+/// i.e. `BEG|LABEL` for `EXPR_BEG | EXPR_LABEL` and `NONE` for `EXPR_NONE`
+pub fn state_name(state: LexState) -> String {
+    const NAMES: &[(i32, &str)] = &[
+        (EXPR_BEG, "BEG"),
+        (EXPR_END, "END"),
+        (EXPR_ENDARG, "ENDARG"),
+        (EXPR_ENDFN, "ENDFN"),
+        (EXPR_ARG, "ARG"),
+        (EXPR_CMDARG, "CMDARG"),
+        (EXPR_MID, "MID"),
+        (EXPR_FNAME, "FNAME"),
+        (EXPR_DOT, "DOT"),
+        (EXPR_CLASS, "CLASS"),
+        (EXPR_LABEL, "LABEL"),
+        (EXPR_LABELED, "LABELED"),
+        (EXPR_FITEM, "FITEM"),
+    ];
+
+    let names = NAMES
+        .iter()
+        .filter(|(value, _)| state.is_some(*value))
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        String::from("NONE")
+    } else {
+        names.join("|")
+    }
+}
+
+/// Converts tokens of the result of parsing to the output of `Ripper.lex`.
+///
+/// Tokens must be recorded with `record_tokens`, `record_trivia`
+/// (otherwise there are no `on_sp`, `on_comment`, `on_ignored_nl` events)
+/// and `record_lex_states` (otherwise all states are `NONE`) options.
+///
+/// Like `Ripper`, emits `on_comment` with the trailing newline,
+/// splits `=begin`/`=end` documents into `on_embdoc*` events
+/// and stops at `__END__`.
+pub fn lex(result: &ParserResult) -> Vec<RipperToken> {
+    let input = &result.input;
+    let mut tokens = vec![];
+    let mut idx = 0;
+
+    while let Some(token) = result.tokens.get(idx) {
+        let state = result
+            .lex_states
+            .get(idx)
+            .copied()
+            .unwrap_or(LexState { value: EXPR_NONE });
+        idx += 1;
+
+        if token.loc.begin >= token.loc.end {
+            continue;
+        }
+        let mut push = |event: &'static str, loc: Loc| {
+            if let Some(token) = ripper_token(input, event, loc, state) {
+                tokens.push(token)
+            }
+        };
+
+        match token.token_name() {
+            "tCOMMENT" => {
+                let mut loc = token.loc;
+                if let Some(next) = result.tokens.get(idx) {
+                    if is_newline(next) && next.loc.begin == loc.end {
+                        loc.end = next.loc.end;
+                        idx += 1;
+                    }
+                }
+                push("on_comment", loc);
+            }
+            "tEMBDOC" => {
+                let lines = split_lines(input, token.loc);
+                let last = lines.len() - 1;
+                for (line_idx, loc) in lines.into_iter().enumerate() {
+                    let event = match line_idx {
+                        0 => "on_embdoc_beg",
+                        _ if line_idx == last => "on_embdoc_end",
+                        _ => "on_embdoc",
+                    };
+                    push(event, loc);
+                }
+            }
+            "tEND_DATA" => {
+                let line = split_lines(input, token.loc)[0];
+                push("on___end__", line);
+                break;
+            }
+            name => {
+                let source = token.loc.source(input).unwrap_or_default();
+                if let Some(event) = event_name(name, &source) {
+                    push(event, token.loc)
+                }
+            }
+        }
+    }
+
+    tokens
+}
+
+fn ripper_token(
+    input: &DecodedInput,
+    event: &'static str,
+    loc: Loc,
+    state: LexState,
+) -> Option<RipperToken> {
+    let (line, column) = loc.begin_line_col(input)?;
+    Some(RipperToken {
+        line: line + 1,
+        column,
+        event,
+        token: loc.source(input)?,
+        state,
+    })
+}
+
+fn is_newline(token: &Token) -> bool {
+    matches!(token.token_name(), "tNL" | "tNEWLINE")
+}
+
+// Splits given range into lines, every line (except the last one) ends with "\n"
+fn split_lines(input: &DecodedInput, loc: Loc) -> Vec<Loc> {
+    let bytes = &input.as_shared_bytes()[loc.begin..loc.end];
+    let mut lines = vec![];
+    let mut begin = loc.begin;
+    for (offset, byte) in bytes.iter().enumerate() {
+        if *byte == b'\n' {
+            let end = loc.begin + offset + 1;
+            lines.push(Loc { begin, end });
+            begin = end;
+        }
+    }
+    if begin < loc.end {
+        lines.push(Loc {
+            begin,
+            end: loc.end,
+        });
+    }
+    lines
+}
+
+// Returns a name of the scanner event for a token with given name and source,
+// `None` means that `Ripper` doesn't emit the token
+fn event_name(token_name: &str, source: &str) -> Option<&'static str> {
+    let event = match token_name {
+        "tIDENTIFIER" | "tFID" => "on_ident",
+        "tCONSTANT" => "on_const",
+        "tIVAR" => "on_ivar",
+        "tGVAR" => "on_gvar",
+        "tCVAR" => "on_cvar",
+        "tNTH_REF" | "tBACK_REF" => "on_backref",
+        "tINTEGER" => "on_int",
+        "tFLOAT" => "on_float",
+        "tRATIONAL" => "on_rational",
+        "tIMAGINARY" => "on_imaginary",
+        "tCHAR" => "on_CHAR",
+        "tLABEL" => "on_label",
+        "tLABEL_END" => "on_label_end",
+        "tSTRING_CONTENT" => "on_tstring_content",
+        "tSTRING_BEG" if source.starts_with("<<") => "on_heredoc_beg",
+        "tSTRING_BEG" => "on_tstring_beg",
+        "tSTRING_END" if is_heredoc_end(source) => "on_heredoc_end",
+        "tSTRING_END" => "on_tstring_end",
+        "tXSTRING_BEG" | "tBACK_REF2" => "on_backtick",
+        "tREGEXP_BEG" => "on_regexp_beg",
+        "tREGEXP_END" => "on_regexp_end",
+        "tWORDS_BEG" => "on_words_beg",
+        "tQWORDS_BEG" => "on_qwords_beg",
+        "tSYMBOLS_BEG" => "on_symbols_beg",
+        "tQSYMBOLS_BEG" => "on_qsymbols_beg",
+        "tSYMBEG" => "on_symbeg",
+        "tSTRING_DBEG" => "on_embexpr_beg",
+        "tSTRING_DEND" => "on_embexpr_end",
+        "tSTRING_DVAR" => "on_embvar",
+        "tLAMBDA" => "on_tlambda",
+        "tLAMBEG" => "on_tlambeg",
+        "tLPAREN" | "tLPAREN_ARG" | "tLPAREN2" => "on_lparen",
+        "tRPAREN" => "on_rparen",
+        "tLBRACK" | "tLBRACK2" => "on_lbracket",
+        "tRBRACK" => "on_rbracket",
+        "tLBRACE" | "tLBRACE_ARG" | "tLCURLY" => "on_lbrace",
+        "tRCURLY" => "on_rbrace",
+        "tCOMMA" => "on_comma",
+        "tSEMI" => "on_semicolon",
+        "tDOT" => "on_period",
+        "tSPACE" => "on_words_sep",
+        "tNL" => "on_nl",
+        "tNEWLINE" => "on_ignored_nl",
+        "tWHITESPACE" | "tLINE_CONTINUATION" => "on_sp",
+        "tSKIPPED" | "tBACKSLASH" | "tSP" | "tSLASH_T" | "tSLASH_F" | "tSLASH_R" | "tVTAB" => {
+            return None
+        }
+        name if name.starts_with('k') => "on_kw",
+        name if name.starts_with('t') => "on_op",
+        _ => return None,
+    };
+    Some(event)
+}
+
+// Ends of heredocs contain an identifier (and a newline),
+// ends of other literals are closing quotes/brackets
+fn is_heredoc_end(source: &str) -> bool {
+    source.ends_with('\n')
+        || source
+            .trim_start()
+            .starts_with(|c: char| c == '_' || c.is_alphanumeric())
+}
+
+#[test]
+fn test_state_name() {
+    assert_eq!(state_name(LexState { value: EXPR_NONE }), "NONE");
+    assert_eq!(state_name(LexState { value: EXPR_CMDARG }), "CMDARG");
+    assert_eq!(
+        state_name(LexState {
+            value: EXPR_BEG | EXPR_LABEL
+        }),
+        "BEG|LABEL"
+    );
+}
+
+#[test]
+fn test_event_name() {
+    assert_eq!(event_name("tIDENTIFIER", "foo"), Some("on_ident"));
+    assert_eq!(event_name("kDEF", "def"), Some("on_kw"));
+    assert_eq!(event_name("kDO_BLOCK", "do"), Some("on_kw"));
+    assert_eq!(event_name("tPLUS", "+"), Some("on_op"));
+    assert_eq!(event_name("tOP_ASGN", "+="), Some("on_op"));
+    assert_eq!(event_name("tSTRING_BEG", "\""), Some("on_tstring_beg"));
+    assert_eq!(event_name("tSTRING_BEG", "<<~HERE"), Some("on_heredoc_beg"));
+    assert_eq!(event_name("tSTRING_END", "\""), Some("on_tstring_end"));
+    assert_eq!(
+        event_name("tSTRING_END", "  HERE\n"),
+        Some("on_heredoc_end")
+    );
+    assert_eq!(event_name("END_OF_INPUT", ""), None);
+}
+
+#[test]
+fn test_display() {
+    let token = RipperToken {
+        line: 1,
+        column: 4,
+        event: "on_tstring_content",
+        token: String::from("a\"b"),
+        state: LexState {
+            value: EXPR_BEG | EXPR_LABEL,
+        },
+    };
+    assert_eq!(
+        token.to_string(),
+        r#"[[1, 4], :on_tstring_content, "a\"b", BEG|LABEL]"#
+    );
+}
@@ -0,0 +1,10 @@
+mod inspect;
+
+mod lex;
+pub use lex::{lex, state_name, RipperToken};
+
+mod sexp;
+pub use sexp::Sexp;
+
+mod sexp_raw;
+pub use sexp_raw::sexp_raw;
@@ -0,0 +1,187 @@
+use super::inspect::{inspect_str, inspect_sym};
+
+/// Element of a tree in the format of `Ripper.sexp_raw`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sexp {
+    /// Parser event, like `[:program, ...]`
+    Event(&'static str, Vec<Sexp>),
+
+    /// Scanner event, like `[:@ident, "foo", [1, 0]]`
+    Token {
+        /// Name of the event (with a leading `@`)
+        event: &'static str,
+        /// Source code of the token
+        value: String,
+        /// Line of the token, starts from 1
+        line: usize,
+        /// Byte offset of the token in its line, starts from 0
+        column: usize,
+    },
+
+    /// Plain Ruby array, like a list of `assoc_new` in `assoclist_from_args`
+    List(Vec<Sexp>),
+
+    /// Ruby symbol, like an operator of `binary` or `unary`
+    Symbol(String),
+
+    /// Ruby string, like a name in `[:<node type>, *children]` of a node without a parser event
+    Str(Vec<u8>),
+
+    /// Ruby integer, like a number of parameters in `[:<node type>, *children]`
+    Integer(usize),
+
+    /// Ruby `true` or `false`
+    Bool(bool),
+
+    /// Ruby `nil`
+    Nil,
+}
+
+impl Sexp {
+    pub(crate) fn event(name: &'static str, children: Vec<Sexp>) -> Self {
+        Self::Event(name, children)
+    }
+
+    pub(crate) fn symbol(name: &str) -> Self {
+        Self::Symbol(name.to_string())
+    }
+
+    pub(crate) fn opt(sexp: Option<Sexp>) -> Self {
+        sexp.unwrap_or(Self::Nil)
+    }
+
+    fn print_inline(&self, out: &mut String) {
+        match self {
+            Self::Event(name, children) => {
+                out.push('[');
+                out.push_str(&inspect_sym(name));
+                for child in children {
+                    out.push_str(", ");
+                    child.print_inline(out);
+                }
+                out.push(']');
+            }
+            Self::Token {
+                event,
+                value,
+                line,
+                column,
+            } => {
+                out.push_str(&format!(
+                    "[{}, {}, [{}, {}]]",
+                    inspect_sym(event),
+                    inspect_str(value.as_bytes()),
+                    line,
+                    column
+                ));
+            }
+            Self::List(items) => {
+                out.push('[');
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        out.push_str(", ");
+                    }
+                    item.print_inline(out);
+                }
+                out.push(']');
+            }
+            Self::Symbol(name) => out.push_str(&inspect_sym(name)),
+            Self::Str(value) => out.push_str(&inspect_str(value)),
+            Self::Integer(value) => out.push_str(&value.to_string()),
+            Self::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            Self::Nil => out.push_str("nil"),
+        }
+    }
+
+    // Prints arrays that don't fit into the line one element per line,
+    // like `pp` does
+    fn print_pretty(&self, indent: usize, out: &mut String) {
+        const WIDTH: usize = 80;
+
+        let mut inline = String::new();
+        self.print_inline(&mut inline);
+        let (name, items) = match self {
+            Self::Event(name, children) if indent + inline.len() > WIDTH => (Some(name), children),
+            Self::List(items) if indent + inline.len() > WIDTH => (None, items),
+            _ => {
+                out.push_str(&inline);
+                return;
+            }
+        };
+
+        out.push('[');
+        if let Some(name) = name {
+            out.push_str(&inspect_sym(name));
+        }
+        for (idx, item) in items.iter().enumerate() {
+            if idx > 0 || name.is_some() {
+                out.push_str(",\n");
+                out.push_str(&" ".repeat(indent + 1));
+            }
+            item.print_pretty(indent + 1, out);
+        }
+        out.push(']');
+    }
+}
+
+impl std::fmt::Display for Sexp {
+    /// Prints the tree like `p` prints Ruby arrays,
+    /// alternate flag (`{:#}`) enables multi-line output like `pp`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        if f.alternate() {
+            self.print_pretty(0, &mut out);
+        } else {
+            self.print_inline(&mut out);
+        }
+        f.write_str(&out)
+    }
+}
+
+#[cfg(test)]
+fn ident(value: &str, line: usize, column: usize) -> Sexp {
+    Sexp::Token {
+        event: "@ident",
+        value: value.to_string(),
+        line,
+        column,
+    }
+}
+
+#[test]
+fn test_display() {
+    let sexp = Sexp::event(
+        "binary",
+        vec![
+            Sexp::event("vcall", vec![ident("a", 1, 0)]),
+            Sexp::symbol("&&"),
+            Sexp::List(vec![
+                Sexp::Nil,
+                Sexp::Bool(false),
+                Sexp::Str(b"a\n".to_vec()),
+                Sexp::Integer(1),
+            ]),
+        ],
+    );
+    assert_eq!(
+        sexp.to_string(),
+        r#"[:binary, [:vcall, [:@ident, "a", [1, 0]]], :"&&", [nil, false, "a\n", 1]]"#
+    );
+}
+
+#[test]
+fn test_display_pretty() {
+    let args = (0..4)
+        .map(|idx| Sexp::event("vcall", vec![ident("argument", 1, idx * 10)]))
+        .collect::<Vec<_>>();
+    let sexp = Sexp::event("program", vec![Sexp::List(args)]);
+    assert_eq!(
+        format!("{:#}", sexp),
+        r#"[:program,
+ [[:vcall, [:@ident, "argument", [1, 0]]],
+  [:vcall, [:@ident, "argument", [1, 10]]],
+  [:vcall, [:@ident, "argument", [1, 20]]],
+  [:vcall, [:@ident, "argument", [1, 30]]]]]"#
+    );
+    assert_eq!(format!("{:#}", Sexp::Nil), "nil");
+}
@@ -0,0 +1,1436 @@
+use super::Sexp;
+use crate::nodes::*;
+use crate::query::{self, Value};
+use crate::source::DecodedInput;
+use crate::{reserved_word, Loc, LocExt, Node, ParserResult};
+
+/// Converts the AST to the output of `Ripper.sexp_raw`.
+///
+/// Common constructions (literals, variables, assignments, method calls, blocks,
+/// control flow, method/class/module definitions) are converted to
+/// the same parser events as `Ripper` emits, other nodes
+/// (like pattern matching) are converted to `[:<node type>, *children]`
+/// with the same children as `Node::inspect` prints.
+///
+/// Returns `None` if the code has syntax errors (like `Ripper.sexp_raw` returns `nil`).
+pub fn sexp_raw(result: &ParserResult) -> Option<Sexp> {
+    if result.diagnostics.iter().any(|d| d.is_error()) {
+        return None;
+    }
+    let builder = Builder {
+        input: &result.input,
+    };
+    Some(event("program", vec![builder.stmts(result.ast.as_deref())]))
+}
+
+fn event(name: &'static str, children: Vec<Sexp>) -> Sexp {
+    Sexp::event(name, children)
+}
+
+fn list(items: Vec<Sexp>) -> Sexp {
+    if items.is_empty() {
+        Sexp::Nil
+    } else {
+        Sexp::List(items)
+    }
+}
+
+// Statements of a body, multiple statements are wrapped into `Begin` without `begin_l`
+fn statements(body: Option<&Node>) -> Vec<&Node> {
+    match body {
+        None | Some(Node::EmptyElse(_)) => vec![],
+        Some(Node::Begin(Begin {
+            statements,
+            begin_l: None,
+            ..
+        })) => statements.iter().collect(),
+        Some(node) => vec![node],
+    }
+}
+
+// Name of the scanner event for a method name or a symbol
+fn name_event(name: &str) -> &'static str {
+    if name.starts_with("@@") {
+        "@cvar"
+    } else if name.starts_with('@') {
+        "@ivar"
+    } else if name.starts_with('$') {
+        "@gvar"
+    } else if reserved_word(name.as_bytes()).is_some() {
+        "@kw"
+    } else {
+        match name.chars().next() {
+            Some(c) if c.is_uppercase() => "@const",
+            Some(c) if c == '_' || c.is_alphabetic() || !c.is_ascii() => "@ident",
+            _ => "@op",
+        }
+    }
+}
+
+fn is_unary_operator(method_name: &str) -> bool {
+    matches!(method_name, "-@" | "+@" | "!" | "~")
+}
+
+fn is_binary_operator(method_name: &str) -> bool {
+    matches!(
+        method_name,
+        "+" | "-"
+            | "*"
+            | "/"
+            | "%"
+            | "**"
+            | "=="
+            | "==="
+            | "!="
+            | "=~"
+            | "!~"
+            | "<"
+            | "<="
+            | ">"
+            | ">="
+            | "<=>"
+            | "<<"
+            | ">>"
+            | "&"
+            | "|"
+            | "^"
+    )
+}
+
+struct Builder<'a> {
+    input: &'a DecodedInput,
+}
+
+impl Builder<'_> {
+    fn source(&self, loc: Loc) -> String {
+        loc.source(self.input).unwrap_or_default()
+    }
+
+    fn token_at(&self, event: &'static str, value: String, pos: usize) -> Sexp {
+        let (line, column) = self.input.line_col_for_pos(pos).unwrap_or_default();
+        Sexp::Token {
+            event,
+            value,
+            line: line + 1,
+            column,
+        }
+    }
+
+    fn token(&self, event: &'static str, loc: Loc) -> Sexp {
+        self.token_at(event, self.source(loc), loc.begin)
+    }
+
+    fn name_token(&self, loc: Loc) -> Sexp {
+        let name = self.source(loc);
+        self.token_at(name_event(&name), name, loc.begin)
+    }
+
+    fn period(&self, dot_l: Loc) -> Sexp {
+        match self.source(dot_l).as_str() {
+            "&." => self.token("@op", dot_l),
+            "::" => Sexp::symbol("::"),
+            _ => self.token("@period", dot_l),
+        }
+    }
+
+    fn stmts(&self, body: Option<&Node>) -> Sexp {
+        self.stmts_list(&statements(body))
+    }
+
+    fn stmts_list(&self, nodes: &[&Node]) -> Sexp {
+        let mut stmts = event("stmts_new", vec![]);
+        if nodes.is_empty() {
+            return event("stmts_add", vec![stmts, event("void_stmt", vec![])]);
+        }
+        for node in nodes {
+            stmts = event("stmts_add", vec![stmts, self.node(node)]);
+        }
+        stmts
+    }
+
+    fn bodystmt(&self, body: Option<&Node>) -> Sexp {
+        let (body, ensure) = match body {
+            Some(Node::Ensure(Ensure { body, ensure, .. })) => (
+                body.as_deref(),
+                Some(event("ensure", vec![self.stmts(ensure.as_deref())])),
+            ),
+            _ => (body, None),
+        };
+        let (body, rescue, else_) = match body {
+            Some(Node::Rescue(Rescue {
+                body,
+                rescue_bodies,
+                else_,
+                ..
+            })) => (
+                body.as_deref(),
+                Some(self.rescue(rescue_bodies)),
+                else_.as_deref().map(|else_| self.stmts(Some(else_))),
+            ),
+            _ => (body, None, None),
+        };
+        event(
+            "bodystmt",
+            vec![
+                self.stmts(body),
+                Sexp::opt(rescue),
+                Sexp::opt(else_),
+                Sexp::opt(ensure),
+            ],
+        )
+    }
+
+    fn rescue(&self, rescue_bodies: &[Node]) -> Sexp {
+        match rescue_bodies.split_first() {
+            Some((
+                Node::RescueBody(RescueBody {
+                    exc_list,
+                    exc_var,
+                    body,
+                    ..
+                }),
+                rest,
+            )) => event(
+                "rescue",
+                vec![
+                    Sexp::opt(exc_list.as_deref().map(|exc_list| self.exc_list(exc_list))),
+                    Sexp::opt(exc_var.as_deref().map(|exc_var| self.target(exc_var))),
+                    self.stmts(body.as_deref()),
+                    if rest.is_empty() {
+                        Sexp::Nil
+                    } else {
+                        self.rescue(rest)
+                    },
+                ],
+            ),
+            _ => Sexp::Nil,
+        }
+    }
+
+    fn exc_list(&self, node: &Node) -> Sexp {
+        match node {
+            Node::Array(Array {
+                elements,
+                begin_l: None,
+                ..
+            }) => match elements.as_slice() {
+                [exc] if !matches!(exc, Node::Splat(_)) => Sexp::List(vec![self.node(exc)]),
+                _ => self.mrhs(elements),
+            },
+            _ => Sexp::List(vec![self.node(node)]),
+        }
+    }
+
+    fn node(&self, node: &Node) -> Sexp {
+        match node {
+            Node::Int(Int {
+                operator_l,
+                expression_l,
+                ..
+            }) => self.numeric("@int", *operator_l, *expression_l),
+            Node::Float(Float {
+                operator_l,
+                expression_l,
+                ..
+            }) => self.numeric("@float", *operator_l, *expression_l),
+            Node::Rational(Rational {
+                operator_l,
+                expression_l,
+                ..
+            }) => self.numeric("@rational", *operator_l, *expression_l),
+            Node::Complex(Complex {
+                operator_l,
+                expression_l,
+                ..
+            }) => self.numeric("@imaginary", *operator_l, *expression_l),
+
+            Node::Str(_) | Node::Dstr(_) | Node::Heredoc(_) => self.string(node),
+            Node::Xstr(Xstr { parts, .. }) | Node::XHeredoc(XHeredoc { parts, .. }) => event(
+                "xstring_literal",
+                vec![self.parts("xstring_new", "xstring_add", parts)],
+            ),
+            Node::Sym(sym) => self.symbol(sym),
+            Node::Dsym(Dsym { parts, .. }) => event(
+                "dyna_symbol",
+                vec![self.parts("string_content", "string_add", parts)],
+            ),
+            Node::Regexp(Regexp {
+                parts,
+                end_l,
+                expression_l,
+                ..
+            }) => event(
+                "regexp_literal",
+                vec![
+                    self.parts("regexp_new", "regexp_add", parts),
+                    self.token(
+                        "@regexp_end",
+                        Loc {
+                            begin: end_l.begin,
+                            end: expression_l.end,
+                        },
+                    ),
+                ],
+            ),
+            Node::Array(array) => self.array(array),
+            Node::Hash(Hash { pairs, .. }) => event(
+                "hash",
+                vec![if pairs.is_empty() {
+                    Sexp::Nil
+                } else {
+                    event("assoclist_from_args", vec![self.assocs(pairs)])
+                }],
+            ),
+            Node::Kwargs(Kwargs { pairs, .. }) => {
+                event("bare_assoc_hash", vec![self.assocs(pairs)])
+            }
+            Node::Pair(_) | Node::Kwsplat(_) => self.assoc(node),
+            Node::Irange(Irange { left, right, .. })
+            | Node::IFlipFlop(IFlipFlop { left, right, .. }) => event(
+                "dot2",
+                vec![
+                    self.opt_node(left.as_deref()),
+                    self.opt_node(right.as_deref()),
+                ],
+            ),
+            Node::Erange(Erange { left, right, .. })
+            | Node::EFlipFlop(EFlipFlop { left, right, .. }) => event(
+                "dot3",
+                vec![
+                    self.opt_node(left.as_deref()),
+                    self.opt_node(right.as_deref()),
+                ],
+            ),
+
+            Node::Lvar(Lvar { expression_l, .. }) => self.var_ref("@ident", *expression_l),
+            Node::Ivar(Ivar { expression_l, .. }) => self.var_ref("@ivar", *expression_l),
+            Node::Gvar(Gvar { expression_l, .. }) => self.var_ref("@gvar", *expression_l),
+            Node::Cvar(Cvar { expression_l, .. }) => self.var_ref("@cvar", *expression_l),
+            Node::NthRef(NthRef { expression_l, .. })
+            | Node::BackRef(BackRef { expression_l, .. }) => {
+                self.var_ref("@backref", *expression_l)
+            }
+            Node::Nil(Nil { expression_l })
+            | Node::True(True { expression_l })
+            | Node::False(False { expression_l })
+            | Node::Self_(Self_ { expression_l })
+            | Node::File(File { expression_l })
+            | Node::Line(Line { expression_l })
+            | Node::Encoding(Encoding { expression_l }) => self.var_ref("@kw", *expression_l),
+            Node::Const(Const { scope, name_l, .. }) => {
+                self.const_(scope.as_deref(), *name_l, "var_ref", "top_const_ref")
+            }
+
+            Node::Lvasgn(Lvasgn { value, .. })
+            | Node::Ivasgn(Ivasgn { value, .. })
+            | Node::Gvasgn(Gvasgn { value, .. })
+            | Node::Cvasgn(Cvasgn { value, .. })
+            | Node::Casgn(Casgn { value, .. })
+            | Node::IndexAsgn(IndexAsgn { value, .. }) => match value {
+                Some(value) => event("assign", vec![self.target(node), self.rhs(value)]),
+                None => self.target(node),
+            },
+            Node::OpAsgn(OpAsgn {
+                recv,
+                value,
+                operator_l,
+                ..
+            })
+            | Node::OrAsgn(OrAsgn {
+                recv,
+                value,
+                operator_l,
+                ..
+            })
+            | Node::AndAsgn(AndAsgn {
+                recv,
+                value,
+                operator_l,
+                ..
+            }) => event(
+                "opassign",
+                vec![
+                    self.target(recv),
+                    self.token("@op", *operator_l),
+                    self.node(value),
+                ],
+            ),
+            Node::Masgn(Masgn { lhs, rhs, .. }) => {
+                event("massign", vec![self.target(lhs), self.rhs(rhs)])
+            }
+
+            Node::Send(Send {
+                recv,
+                method_name,
+                args,
+                dot_l,
+                selector_l,
+                begin_l,
+                operator_l,
+                ..
+            }) => self.send(
+                recv.as_deref(),
+                method_name,
+                args,
+                *dot_l,
+                *selector_l,
+                *begin_l,
+                *operator_l,
+            ),
+            Node::CSend(CSend {
+                recv,
+                method_name,
+                args,
+                dot_l,
+                selector_l,
+                begin_l,
+                operator_l,
+                ..
+            }) => self.send(
+                Some(recv),
+                method_name,
+                args,
+                Some(*dot_l),
+                *selector_l,
+                *begin_l,
+                *operator_l,
+            ),
+            Node::Index(Index { recv, indexes, .. }) => {
+                event("aref", vec![self.node(recv), self.opt_call_args(indexes)])
+            }
+            Node::Block(Block {
+                call,
+                args,
+                body,
+                begin_l,
+                ..
+            }) => self.block(call, args.as_deref(), body.as_deref(), *begin_l),
+            Node::Numblock(Numblock {
+                call,
+                body,
+                begin_l,
+                ..
+            }) => self.block(call, None, Some(body), *begin_l),
+            Node::And(And {
+                lhs,
+                rhs,
+                operator_l,
+                ..
+            })
+            | Node::Or(Or {
+                lhs,
+                rhs,
+                operator_l,
+                ..
+            }) => event(
+                "binary",
+                vec![
+                    self.node(lhs),
+                    Sexp::symbol(&self.source(*operator_l)),
+                    self.node(rhs),
+                ],
+            ),
+            Node::MatchWithLvasgn(MatchWithLvasgn { re, value, .. }) => event(
+                "binary",
+                vec![self.node(re), Sexp::symbol("=~"), self.node(value)],
+            ),
+            Node::MatchCurrentLine(MatchCurrentLine { re, .. }) => self.node(re),
+            Node::Defined(Defined { value, .. }) => event("defined", vec![self.node(value)]),
+
+            Node::If(if_) => self.if_(if_),
+            Node::IfMod(IfMod {
+                cond,
+                if_true,
+                if_false,
+                ..
+            }) => match (if_true, if_false) {
+                (Some(body), _) => event("if_mod", vec![self.node(cond), self.node(body)]),
+                (None, body) => event(
+                    "unless_mod",
+                    vec![self.node(cond), self.opt_node(body.as_deref())],
+                ),
+            },
+            Node::IfTernary(IfTernary {
+                cond,
+                if_true,
+                if_false,
+                ..
+            }) => event(
+                "ifop",
+                vec![self.node(cond), self.node(if_true), self.node(if_false)],
+            ),
+            Node::While(While { cond, body, .. }) => {
+                event("while", vec![self.node(cond), self.stmts(body.as_deref())])
+            }
+            Node::Until(Until { cond, body, .. }) => {
+                event("until", vec![self.node(cond), self.stmts(body.as_deref())])
+            }
+            Node::WhilePost(WhilePost { cond, body, .. }) => {
+                event("while_mod", vec![self.node(cond), self.node(body)])
+            }
+            Node::UntilPost(UntilPost { cond, body, .. }) => {
+                event("until_mod", vec![self.node(cond), self.node(body)])
+            }
+            Node::For(For {
+                iterator,
+                iteratee,
+                body,
+                ..
+            }) => event(
+                "for",
+                vec![
+                    self.target(iterator),
+                    self.node(iteratee),
+                    self.stmts(body.as_deref()),
+                ],
+            ),
+            Node::Case(Case {
+                expr,
+                when_bodies,
+                else_body,
+                ..
+            }) => event(
+                "case",
+                vec![
+                    self.opt_node(expr.as_deref()),
+                    self.when_(when_bodies, else_body.as_deref()),
+                ],
+            ),
+
+            Node::Return(Return { args, .. }) if args.is_empty() => event("return0", vec![]),
+            Node::Return(Return { args, .. }) => event("return", vec![self.call_args(args)]),
+            Node::Break(Break { args, .. }) => event("break", vec![self.jump_args(args)]),
+            Node::Next(Next { args, .. }) => event("next", vec![self.jump_args(args)]),
+            Node::Redo(_) => event("redo", vec![]),
+            Node::Retry(_) => event("retry", vec![]),
+            Node::Yield(Yield { args, begin_l, .. }) => match begin_l {
+                Some(_) => event("yield", vec![event("paren", vec![self.jump_args(args)])]),
+                None if args.is_empty() => event("yield0", vec![]),
+                None => event("yield", vec![self.call_args(args)]),
+            },
+            Node::Super(Super { args, begin_l, .. }) => match begin_l {
+                Some(_) => event(
+                    "super",
+                    vec![event("arg_paren", vec![self.opt_call_args(args)])],
+                ),
+                None => event("super", vec![self.call_args(args)]),
+            },
+            Node::ZSuper(_) => event("zsuper", vec![]),
+
+            Node::Def(Def {
+                name_l, args, body, ..
+            }) => event(
+                "def",
+                vec![
+                    self.name_token(*name_l),
+                    self.def_params(args.as_deref()),
+                    self.bodystmt(body.as_deref()),
+                ],
+            ),
+            Node::Defs(Defs {
+                definee,
+                operator_l,
+                name_l,
+                args,
+                body,
+                ..
+            }) => event(
+                "defs",
+                vec![
+                    self.node(definee),
+                    self.period(*operator_l),
+                    self.name_token(*name_l),
+                    self.def_params(args.as_deref()),
+                    self.bodystmt(body.as_deref()),
+                ],
+            ),
+            Node::Class(Class {
+                name,
+                superclass,
+                body,
+                ..
+            }) => event(
+                "class",
+                vec![
+                    self.const_ref(name),
+                    self.opt_node(superclass.as_deref()),
+                    self.bodystmt(body.as_deref()),
+                ],
+            ),
+            Node::Module(Module { name, body, .. }) => event(
+                "module",
+                vec![self.const_ref(name), self.bodystmt(body.as_deref())],
+            ),
+            Node::SClass(SClass { expr, body, .. }) => event(
+                "sclass",
+                vec![self.node(expr), self.bodystmt(body.as_deref())],
+            ),
+            Node::Alias(Alias { to, from, .. }) => match (&**to, &**from) {
+                (Node::Gvar(_) | Node::BackRef(_) | Node::NthRef(_), _) => event(
+                    "var_alias",
+                    vec![self.gvar_token(to), self.gvar_token(from)],
+                ),
+                _ => event("alias", vec![self.node(to), self.node(from)]),
+            },
+            Node::Undef(Undef { names, .. }) => event(
+                "undef",
+                vec![Sexp::List(
+                    names.iter().map(|name| self.node(name)).collect(),
+                )],
+            ),
+            Node::Preexe(Preexe { body, .. }) => event("BEGIN", vec![self.stmts(body.as_deref())]),
+            Node::Postexe(Postexe { body, .. }) => event("END", vec![self.stmts(body.as_deref())]),
+
+            Node::Begin(Begin {
+                statements,
+                begin_l,
+                ..
+            }) => {
+                let stmts = self.stmts_list(&statements.iter().collect::<Vec<_>>());
+                match begin_l {
+                    Some(_) => event("paren", vec![stmts]),
+                    None => stmts,
+                }
+            }
+            Node::KwBegin(KwBegin { statements, .. }) => {
+                let bodystmt = match statements.as_slice() {
+                    [body @ (Node::Rescue(_) | Node::Ensure(_))] => self.bodystmt(Some(body)),
+                    _ => event(
+                        "bodystmt",
+                        vec![
+                            self.stmts_list(&statements.iter().collect::<Vec<_>>()),
+                            Sexp::Nil,
+                            Sexp::Nil,
+                            Sexp::Nil,
+                        ],
+                    ),
+                };
+                event("begin", vec![bodystmt])
+            }
+            Node::Rescue(Rescue {
+                body,
+                rescue_bodies,
+                else_: None,
+                ..
+            }) if rescue_bodies.len() == 1 => match &rescue_bodies[0] {
+                Node::RescueBody(RescueBody {
+                    body: rescue_body, ..
+                }) => event(
+                    "rescue_mod",
+                    vec![
+                        self.opt_node(body.as_deref()),
+                        self.opt_node(rescue_body.as_deref()),
+                    ],
+                ),
+                _ => self.fallback(node),
+            },
+
+            _ => self.fallback(node),
+        }
+    }
+
+    fn opt_node(&self, node: Option<&Node>) -> Sexp {
+        Sexp::opt(node.map(|node| self.node(node)))
+    }
+
+    // Children are the same as `Node::inspect` prints (see generated `query::children`)
+    fn fallback(&self, node: &Node) -> Sexp {
+        let children = query::children(node)
+            .into_iter()
+            .map(|child| match child {
+                Value::Node(child) => self.node(child),
+                Value::Nil => Sexp::Nil,
+                Value::Str(value) => Sexp::Str(value.as_bytes().to_vec()),
+                Value::Bytes(value) => Sexp::Str(value.as_raw().clone()),
+                Value::Integer(value) => Sexp::Integer(usize::from(value)),
+            })
+            .collect();
+        event(node.str_type(), children)
+    }
+
+    // `-1` is a unary minus applied to a positive literal
+    fn numeric(&self, event_name: &'static str, operator_l: Option<Loc>, loc: Loc) -> Sexp {
+        match operator_l {
+            Some(operator_l) if self.source(operator_l) == "-" => event(
+                "unary",
+                vec![
+                    Sexp::symbol("-@"),
+                    self.token(
+                        event_name,
+                        Loc {
+                            begin: operator_l.end,
+                            end: loc.end,
+                        },
+                    ),
+                ],
+            ),
+            _ => self.token(event_name, loc),
+        }
+    }
+
+    fn var_ref(&self, event_name: &'static str, loc: Loc) -> Sexp {
+        event("var_ref", vec![self.token(event_name, loc)])
+    }
+
+    fn gvar_token(&self, node: &Node) -> Sexp {
+        match node {
+            Node::BackRef(_) | Node::NthRef(_) => self.token("@backref", *node.expression()),
+            _ => self.token("@gvar", *node.expression()),
+        }
+    }
+
+    fn const_(
+        &self,
+        scope: Option<&Node>,
+        name_l: Loc,
+        unscoped: &'static str,
+        top: &'static str,
+    ) -> Sexp {
+        let name = self.token("@const", name_l);
+        match scope {
+            None => event(unscoped, vec![name]),
+            Some(Node::Cbase(_)) => event(top, vec![name]),
+            Some(scope) => {
+                let scoped = if unscoped == "var_field" {
+                    "const_path_field"
+                } else {
+                    "const_path_ref"
+                };
+                event(scoped, vec![self.node(scope), name])
+            }
+        }
+    }
+
+    fn const_ref(&self, node: &Node) -> Sexp {
+        match node {
+            Node::Const(Const { scope, name_l, .. }) => {
+                self.const_(scope.as_deref(), *name_l, "const_ref", "top_const_ref")
+            }
+            _ => self.node(node),
+        }
+    }
+
+    // Left-hand side of assignments
+    fn target(&self, node: &Node) -> Sexp {
+        match node {
+            Node::Lvasgn(Lvasgn { name_l, .. }) => self.var_field("@ident", *name_l),
+            Node::Ivasgn(Ivasgn { name_l, .. }) => self.var_field("@ivar", *name_l),
+            Node::Gvasgn(Gvasgn { name_l, .. }) => self.var_field("@gvar", *name_l),
+            Node::Cvasgn(Cvasgn { name_l, .. }) => self.var_field("@cvar", *name_l),
+            Node::Casgn(Casgn { scope, name_l, .. }) => {
+                self.const_(scope.as_deref(), *name_l, "var_field", "top_const_field")
+            }
+            Node::Index(Index { recv, indexes, .. })
+            | Node::IndexAsgn(IndexAsgn { recv, indexes, .. }) => event(
+                "aref_field",
+                vec![self.node(recv), self.opt_call_args(indexes)],
+            ),
+            Node::Send(Send {
+                recv: Some(recv),
+                dot_l: Some(dot_l),
+                selector_l: Some(selector_l),
+                ..
+            })
+            | Node::CSend(CSend {
+                recv,
+                dot_l,
+                selector_l: Some(selector_l),
+                ..
+            }) => event(
+                "field",
+                vec![
+                    self.node(recv),
+                    self.period(*dot_l),
+                    self.name_token(*selector_l),
+                ],
+            ),
+            Node::Mlhs(Mlhs { items, begin_l, .. }) => {
+                let mlhs = self.mlhs(items, |node| self.target(node));
+                match begin_l {
+                    Some(_) => event("mlhs_paren", vec![mlhs]),
+                    None => mlhs,
+                }
+            }
+            _ => self.node(node),
+        }
+    }
+
+    fn var_field(&self, event_name: &'static str, loc: Loc) -> Sexp {
+        event("var_field", vec![self.token(event_name, loc)])
+    }
+
+    fn mlhs(&self, items: &[Node], item: impl Fn(&Node) -> Sexp) -> Sexp {
+        let mut mlhs = event("mlhs_new", vec![]);
+        for node in items {
+            mlhs = match node {
+                Node::Splat(Splat { value, .. }) => event(
+                    "mlhs_add_star",
+                    vec![mlhs, Sexp::opt(value.as_deref().map(&item))],
+                ),
+                Node::Restarg(Restarg { name_l, .. }) => event(
+                    "mlhs_add_star",
+                    vec![
+                        mlhs,
+                        Sexp::opt(name_l.map(|name_l| self.token("@ident", name_l))),
+                    ],
+                ),
+                _ => event("mlhs_add", vec![mlhs, item(node)]),
+            };
+        }
+        mlhs
+    }
+
+    // Right-hand side of assignments, `a = 1, 2` has an array without brackets
+    fn rhs(&self, node: &Node) -> Sexp {
+        match node {
+            Node::Array(Array {
+                elements,
+                begin_l: None,
+                ..
+            }) => self.mrhs(elements),
+            _ => self.node(node),
+        }
+    }
+
+    fn mrhs(&self, elements: &[Node]) -> Sexp {
+        let (last, init) = match elements.split_last() {
+            Some(split) => split,
+            None => return event("mrhs_new", vec![]),
+        };
+        let mrhs = if init.is_empty() {
+            event("mrhs_new", vec![])
+        } else {
+            event("mrhs_new_from_args", vec![self.args(init)])
+        };
+        match last {
+            Node::Splat(Splat { value, .. }) => {
+                event("mrhs_add_star", vec![mrhs, self.opt_node(value.as_deref())])
+            }
+            _ => event("mrhs_add", vec![mrhs, self.node(last)]),
+        }
+    }
+
+    fn args(&self, nodes: &[Node]) -> Sexp {
+        let mut args = event("args_new", vec![]);
+        for node in nodes {
+            args = match node {
+                Node::Splat(Splat { value, .. }) => {
+                    event("args_add_star", vec![args, self.opt_node(value.as_deref())])
+                }
+                _ => event("args_add", vec![args, self.node(node)]),
+            };
+        }
+        args
+    }
+
+    fn call_args(&self, nodes: &[Node]) -> Sexp {
+        match nodes.split_last() {
+            Some((Node::BlockPass(BlockPass { value, .. }), init)) => event(
+                "args_add_block",
+                vec![self.args(init), self.opt_node(value.as_deref())],
+            ),
+            _ => event("args_add_block", vec![self.args(nodes), Sexp::Bool(false)]),
+        }
+    }
+
+    fn opt_call_args(&self, nodes: &[Node]) -> Sexp {
+        if nodes.is_empty() {
+            Sexp::Nil
+        } else {
+            self.call_args(nodes)
+        }
+    }
+
+    fn jump_args(&self, nodes: &[Node]) -> Sexp {
+        if nodes.is_empty() {
+            event("args_new", vec![])
+        } else {
+            self.call_args(nodes)
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn send(
+        &self,
+        recv: Option<&Node>,
+        method_name: &str,
+        args: &[Node],
+        dot_l: Option<Loc>,
+        selector_l: Option<Loc>,
+        begin_l: Option<Loc>,
+        operator_l: Option<Loc>,
+    ) -> Sexp {
+        if let (Some(recv), None, Some(selector_l)) = (recv, dot_l, selector_l) {
+            let selector = self.source(selector_l);
+            if args.is_empty() && is_unary_operator(method_name) {
+                let operator = if selector == "not" {
+                    "not"
+                } else {
+                    method_name
+                };
+                return event("unary", vec![Sexp::symbol(operator), self.node(recv)]);
+            }
+            if args.len() == 1 && is_binary_operator(method_name) && selector == method_name {
+                return event(
+                    "binary",
+                    vec![
+                        self.node(recv),
+                        Sexp::symbol(method_name),
+                        self.node(&args[0]),
+                    ],
+                );
+            }
+        }
+
+        if let (Some(recv), Some(dot_l), Some(selector_l), Some(_), [value]) =
+            (recv, dot_l, selector_l, operator_l, args)
+        {
+            let field = event(
+                "field",
+                vec![
+                    self.node(recv),
+                    self.period(dot_l),
+                    self.name_token(selector_l),
+                ],
+            );
+            return event("assign", vec![field, self.rhs(value)]);
+        }
+
+        let name = match selector_l {
+            Some(selector_l) => self.name_token(selector_l),
+            // `foo.()`
+            None => Sexp::symbol("call"),
+        };
+        let recv = recv.map(|recv| (self.node(recv), dot_l.map(|dot_l| self.period(dot_l))));
+
+        match (recv, begin_l) {
+            (None, None) if args.is_empty() => event("vcall", vec![name]),
+            (None, None) => event("command", vec![name, self.call_args(args)]),
+            (Some((recv, period)), None) if !args.is_empty() => event(
+                "command_call",
+                vec![recv, Sexp::opt(period), name, self.call_args(args)],
+            ),
+            (recv, begin_l) => {
+                let call = match recv {
+                    Some((recv, period)) => event("call", vec![recv, Sexp::opt(period), name]),
+                    None => event("fcall", vec![name]),
+                };
+                match begin_l {
+                    Some(_) => event(
+                        "method_add_arg",
+                        vec![call, event("arg_paren", vec![self.opt_call_args(args)])],
+                    ),
+                    None => call,
+                }
+            }
+        }
+    }
+
+    fn block(&self, call: &Node, args: Option<&Node>, body: Option<&Node>, begin_l: Loc) -> Sexp {
+        let brace = self.source(begin_l) == "{";
+        let body = if brace {
+            self.stmts(body)
+        } else {
+            self.bodystmt(body)
+        };
+
+        if let Node::Lambda(_) = call {
+            let params = match args {
+                Some(Node::Args(Args {
+                    args,
+                    begin_l: Some(_),
+                    ..
+                })) => event("paren", vec![self.params(args)]),
+                Some(Node::Args(Args { args, .. })) => self.params(args),
+                _ => self.params(&[]),
+            };
+            return event("lambda", vec![params, body]);
+        }
+
+        let block_var = match args {
+            Some(Node::Args(Args { args, .. })) => {
+                let shadow_args = args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        Node::Shadowarg(Shadowarg { expression_l, .. }) => {
+                            Some(self.token("@ident", *expression_l))
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                event(
+                    "block_var",
+                    vec![
+                        self.params(args),
+                        if shadow_args.is_empty() {
+                            Sexp::Bool(false)
+                        } else {
+                            Sexp::List(shadow_args)
+                        },
+                    ],
+                )
+            }
+            Some(args) => self.node(args),
+            None => Sexp::Nil,
+        };
+        let block = event(
+            if brace { "brace_block" } else { "do_block" },
+            vec![block_var, body],
+        );
+
+        let call = match call {
+            // `foo { }` is a call with empty arguments
+            Node::Send(Send {
+                recv: None,
+                args,
+                begin_l: None,
+                selector_l: Some(selector_l),
+                ..
+            }) if args.is_empty() => event(
+                "method_add_arg",
+                vec![
+                    event("fcall", vec![self.name_token(*selector_l)]),
+                    event("args_new", vec![]),
+                ],
+            ),
+            _ => self.node(call),
+        };
+        event("method_add_block", vec![call, block])
+    }
+
+    fn def_params(&self, args: Option<&Node>) -> Sexp {
+        match args {
+            Some(Node::Args(Args {
+                args,
+                begin_l: Some(_),
+                ..
+            })) => event("paren", vec![self.params(args)]),
+            Some(Node::Args(Args { args, .. })) => self.params(args),
+            _ => self.params(&[]),
+        }
+    }
+
+    fn params(&self, args: &[Node]) -> Sexp {
+        let mut pre = vec![];
+        let mut opts = vec![];
+        let mut rest = None;
+        let mut post = vec![];
+        let mut kws = vec![];
+        let mut kwrest = None;
+        let mut block = None;
+
+        for arg in args {
+            match arg {
+                Node::Optarg(Optarg {
+                    name_l, default, ..
+                }) => opts.push(Sexp::List(vec![
+                    self.token("@ident", *name_l),
+                    self.node(default),
+                ])),
+                Node::Restarg(Restarg { name_l, .. }) => {
+                    rest = Some(event(
+                        "rest_param",
+                        vec![Sexp::opt(name_l.map(|name_l| self.token("@ident", name_l)))],
+                    ))
+                }
+                Node::ForwardArg(_) => rest = Some(event("args_forward", vec![])),
+                Node::Kwarg(Kwarg { name, name_l, .. }) => kws.push(Sexp::List(vec![
+                    self.token_at("@label", format!("{}:", name), name_l.begin),
+                    Sexp::Bool(false),
+                ])),
+                Node::Kwoptarg(Kwoptarg {
+                    name,
+                    name_l,
+                    default,
+                    ..
+                }) => kws.push(Sexp::List(vec![
+                    self.token_at("@label", format!("{}:", name), name_l.begin),
+                    self.node(default),
+                ])),
+                Node::Kwrestarg(Kwrestarg { name_l, .. }) => {
+                    kwrest = Some(event(
+                        "kwrest_param",
+                        vec![Sexp::opt(name_l.map(|name_l| self.token("@ident", name_l)))],
+                    ))
+                }
+                Node::Kwnilarg(_) => kwrest = Some(event("nokw_param", vec![Sexp::Nil])),
+                Node::Blockarg(Blockarg { name_l, .. }) => {
+                    block = Some(event(
+                        "blockarg",
+                        vec![Sexp::opt(name_l.map(|name_l| self.token("@ident", name_l)))],
+                    ))
+                }
+                Node::Shadowarg(_) => {}
+                _ if rest.is_some() || !opts.is_empty() => post.push(self.param(arg)),
+                _ => pre.push(self.param(arg)),
+            }
+        }
+
+        event(
+            "params",
+            vec![
+                list(pre),
+                list(opts),
+                Sexp::opt(rest),
+                list(post),
+                list(kws),
+                Sexp::opt(kwrest),
+                Sexp::opt(block),
+            ],
+        )
+    }
+
+    // Required parameter, possibly destructured like `(a, b)`
+    fn param(&self, node: &Node) -> Sexp {
+        match node {
+            Node::Arg(Arg { expression_l, .. }) => self.token("@ident", *expression_l),
+            Node::Procarg0(Procarg0 {
+                args,
+                begin_l: None,
+                ..
+            }) if args.len() == 1 => self.param(&args[0]),
+            Node::Procarg0(Procarg0 { args: items, .. }) | Node::Mlhs(Mlhs { items, .. }) => event(
+                "mlhs_paren",
+                vec![self.mlhs(items, |item| self.param(item))],
+            ),
+            _ => self.node(node),
+        }
+    }
+
+    fn if_(&self, if_: &If) -> Sexp {
+        let If {
+            cond,
+            if_true,
+            if_false,
+            keyword_l,
+            ..
+        } = if_;
+        match self.source(*keyword_l).as_str() {
+            // bodies of `unless` are swapped in the AST
+            "unless" => event(
+                "unless",
+                vec![
+                    self.node(cond),
+                    self.stmts(if_false.as_deref()),
+                    self.else_(if_true.as_deref()),
+                ],
+            ),
+            keyword => event(
+                if keyword == "elsif" { "elsif" } else { "if" },
+                vec![
+                    self.node(cond),
+                    self.stmts(if_true.as_deref()),
+                    self.else_(if_false.as_deref()),
+                ],
+            ),
+        }
+    }
+
+    fn else_(&self, body: Option<&Node>) -> Sexp {
+        match body {
+            None => Sexp::Nil,
+            Some(Node::If(if_)) if self.source(if_.keyword_l) == "elsif" => self.if_(if_),
+            Some(body) => event("else", vec![self.stmts(Some(body))]),
+        }
+    }
+
+    fn when_(&self, when_bodies: &[Node], else_body: Option<&Node>) -> Sexp {
+        match when_bodies.split_first() {
+            Some((Node::When(When { patterns, body, .. }), rest)) => event(
+                "when",
+                vec![
+                    self.args(patterns),
+                    self.stmts(body.as_deref()),
+                    self.when_(rest, else_body),
+                ],
+            ),
+            _ => self.else_(else_body),
+        }
+    }
+
+    fn string(&self, node: &Node) -> Sexp {
+        match node {
+            Node::Str(Str {
+                begin_l: Some(begin_l),
+                expression_l,
+                ..
+            }) if self.source(*begin_l) == "?" => self.token("@CHAR", *expression_l),
+            // `"a" "b"`
+            Node::Dstr(Dstr {
+                parts,
+                begin_l: None,
+                ..
+            }) if !parts.is_empty() && parts.iter().all(is_quoted_string) => {
+                let mut parts = parts.iter().map(|part| self.string(part));
+                let first = parts.next().unwrap_or(Sexp::Nil);
+                parts.fold(first, |acc, part| event("string_concat", vec![acc, part]))
+            }
+            Node::Str(Str {
+                begin_l: Some(begin_l),
+                end_l: Some(end_l),
+                ..
+            }) => {
+                let mut content = event("string_content", vec![]);
+                if begin_l.end < end_l.begin {
+                    let loc = Loc {
+                        begin: begin_l.end,
+                        end: end_l.begin,
+                    };
+                    content = event(
+                        "string_add",
+                        vec![content, self.token("@tstring_content", loc)],
+                    );
+                }
+                event("string_literal", vec![content])
+            }
+            Node::Dstr(Dstr { parts, .. }) | Node::Heredoc(Heredoc { parts, .. }) => event(
+                "string_literal",
+                vec![self.parts("string_content", "string_add", parts)],
+            ),
+            _ => event(
+                "string_literal",
+                vec![self.parts("string_content", "string_add", std::slice::from_ref(node))],
+            ),
+        }
+    }
+
+    // Contents of strings, symbols, regexps and words
+    fn parts(&self, new: &'static str, add: &'static str, parts: &[Node]) -> Sexp {
+        let mut acc = event(new, vec![]);
+        for part in parts {
+            acc = match part {
+                Node::Dstr(Dstr {
+                    parts,
+                    begin_l: None,
+                    ..
+                }) => parts.iter().fold(acc, |acc, part| {
+                    event(add, vec![acc, self.string_part(part)])
+                }),
+                _ => event(add, vec![acc, self.string_part(part)]),
+            }
+        }
+        acc
+    }
+
+    fn string_part(&self, node: &Node) -> Sexp {
+        match node {
+            Node::Str(Str { expression_l, .. }) | Node::Sym(Sym { expression_l, .. }) => {
+                self.token("@tstring_content", *expression_l)
+            }
+            Node::Begin(Begin { statements, .. }) => event(
+                "string_embexpr",
+                vec![self.stmts_list(&statements.iter().collect::<Vec<_>>())],
+            ),
+            Node::Ivar(_) | Node::Gvar(_) | Node::Cvar(_) | Node::BackRef(_) | Node::NthRef(_) => {
+                event("string_dvar", vec![self.node(node)])
+            }
+            _ => self.node(node),
+        }
+    }
+
+    fn symbol(&self, sym: &Sym) -> Sexp {
+        let Sym {
+            begin_l,
+            end_l,
+            expression_l,
+            ..
+        } = sym;
+        match (begin_l, end_l) {
+            // `:foo`
+            (Some(begin_l), _) if self.source(*begin_l) == ":" => {
+                let loc = Loc {
+                    begin: begin_l.end,
+                    end: expression_l.end,
+                };
+                event(
+                    "symbol_literal",
+                    vec![event("symbol", vec![self.name_token(loc)])],
+                )
+            }
+            // `:"foo"`, `%s(foo)`
+            (Some(begin_l), Some(end_l)) => {
+                let mut content = event("string_content", vec![]);
+                if begin_l.end < end_l.begin {
+                    let loc = Loc {
+                        begin: begin_l.end,
+                        end: end_l.begin,
+                    };
+                    content = event(
+                        "string_add",
+                        vec![content, self.token("@tstring_content", loc)],
+                    );
+                }
+                event("dyna_symbol", vec![content])
+            }
+            // `foo:` in hashes
+            (None, Some(end_l)) if self.source(*end_l) == ":" => {
+                self.token("@label", *expression_l)
+            }
+            // `foo` in `alias` and `undef`
+            _ => event("symbol_literal", vec![self.name_token(*expression_l)]),
+        }
+    }
+
+    fn array(&self, array: &Array) -> Sexp {
+        let Array {
+            elements, begin_l, ..
+        } = array;
+        let begin_l = match begin_l {
+            Some(begin_l) => *begin_l,
+            None => return self.mrhs(elements),
+        };
+        let begin = self.source(begin_l);
+        let words = match begin.get(..2) {
+            Some("%w") => Some(("qwords_new", "qwords_add", false)),
+            Some("%i") => Some(("qsymbols_new", "qsymbols_add", false)),
+            Some("%W") => Some(("words_new", "words_add", true)),
+            Some("%I") => Some(("symbols_new", "symbols_add", true)),
+            _ => None,
+        };
+
+        let content = match words {
+            Some((new, add, interpolated)) => {
+                let mut acc = event(new, vec![]);
+                for element in elements {
+                    let word = match element {
+                        Node::Dstr(Dstr { parts, .. }) | Node::Dsym(Dsym { parts, .. })
+                            if interpolated =>
+                        {
+                            self.parts("word_new", "word_add", parts)
+                        }
+                        _ if interpolated => {
+                            self.parts("word_new", "word_add", std::slice::from_ref(element))
+                        }
+                        _ => self.string_part(element),
+                    };
+                    acc = event(add, vec![acc, word]);
+                }
+                acc
+            }
+            None if elements.is_empty() => Sexp::Nil,
+            None => self.args(elements),
+        };
+        event("array", vec![content])
+    }
+
+    fn assocs(&self, pairs: &[Node]) -> Sexp {
+        Sexp::List(pairs.iter().map(|pair| self.assoc(pair)).collect())
+    }
+
+    fn assoc(&self, node: &Node) -> Sexp {
+        match node {
+            Node::Pair(Pair { key, value, .. }) => {
+                event("assoc_new", vec![self.node(key), self.node(value)])
+            }
+            Node::Kwsplat(Kwsplat { value, .. }) => event("assoc_splat", vec![self.node(value)]),
+            _ => self.node(node),
+        }
+    }
+}
+
+fn is_quoted_string(node: &Node) -> bool {
+    match node {
+        Node::Str(Str { begin_l, .. }) | Node::Dstr(Dstr { begin_l, .. }) => begin_l.is_some(),
+        Node::Heredoc(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+fn loc(begin: usize, end: usize) -> Loc {
+    Loc { begin, end }
+}
+
+#[cfg(test)]
+fn convert(source: &str, ast: Node) -> String {
+    let mut input = DecodedInput::named("(test)");
+    input.update_bytes(source.as_bytes().to_vec());
+    let builder = Builder { input: &input };
+    event("program", vec![builder.stmts(Some(&ast))]).to_string()
+}
+
+#[test]
+fn test_binary() {
+    // a + 1
+    let ast = Node::Send(Send {
+        recv: Some(Box::new(Node::Lvar(Lvar {
+            name: String::from("a"),
+            expression_l: loc(0, 1),
+        }))),
+        method_name: String::from("+"),
+        args: vec![Node::Int(Int {
+            value: String::from("1"),
+            operator_l: None,
+            expression_l: loc(4, 5),
+        })],
+        dot_l: None,
+        selector_l: Some(loc(2, 3)),
+        begin_l: None,
+        end_l: None,
+        operator_l: None,
+        expression_l: loc(0, 5),
+    });
+    assert_eq!(
+        convert("a + 1", ast),
+        r#"[:program, [:stmts_add, [:stmts_new], [:binary, [:var_ref, [:@ident, "a", [1, 0]]], :+, [:@int, "1", [1, 4]]]]]"#
+    );
+}
+
+#[test]
+fn test_command() {
+    // puts "hi"
+    let ast = Node::Send(Send {
+        recv: None,
+        method_name: String::from("puts"),
+        args: vec![Node::Str(Str {
+            value: crate::Bytes::new(b"hi".to_vec()),
+            begin_l: Some(loc(5, 6)),
+            end_l: Some(loc(8, 9)),
+            expression_l: loc(5, 9),
+        })],
+        dot_l: None,
+        selector_l: Some(loc(0, 4)),
+        begin_l: None,
+        end_l: None,
+        operator_l: None,
+        expression_l: loc(0, 9),
+    });
+    assert_eq!(
+        convert("puts \"hi\"", ast),
+        r#"[:program, [:stmts_add, [:stmts_new], [:command, [:@ident, "puts", [1, 0]], [:args_add_block, [:args_add, [:args_new], [:string_literal, [:string_add, [:string_content], [:@tstring_content, "hi", [1, 6]]]]], false]]]]"#
+    );
+}
+
+#[test]
+fn test_negative_int() {
+    // x = -1
+    let ast = Node::Lvasgn(Lvasgn {
+        name: String::from("x"),
+        value: Some(Box::new(Node::Int(Int {
+            value: String::from("-1"),
+            operator_l: Some(loc(4, 5)),
+            expression_l: loc(4, 6),
+        }))),
+        name_l: loc(0, 1),
+        operator_l: Some(loc(2, 3)),
+        expression_l: loc(0, 6),
+    });
+    assert_eq!(
+        convert("x = -1", ast),
+        r#"[:program, [:stmts_add, [:stmts_new], [:assign, [:var_field, [:@ident, "x", [1, 0]]], [:unary, :-@, [:@int, "1", [1, 5]]]]]]"#
+    );
+}
+
+#[test]
+fn test_fallback() {
+    // `in` patterns are not converted
+    let ast = Node::MatchVar(MatchVar {
+        name: String::from("a"),
+        name_l: loc(0, 1),
+        expression_l: loc(0, 1),
+    });
+    assert_eq!(
+        convert("a", ast),
+        r#"[:program, [:stmts_add, [:stmts_new], [:match_var, "a"]]]"#
+    );
+}
@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+
+use super::{Declaration, DeclarationKind, Scope, ScopeKind, ScopeTree, Variable};
+use crate::builder::is_local_variable_name;
+use crate::nodes::*;
+use crate::regexp::{
+    parse_regexp_literal, Alternation, Conditional, Group, GroupKind, Quantified, RegexpNode,
+    Sequence,
+};
+use crate::source::DecodedInput;
+use crate::traverse::visitor::*;
+use crate::{Loc, Node};
+
+pub(crate) struct Analyzer<'a> {
+    scopes: Vec<Scope>,
+    variables: Vec<Variable>,
+    locs: HashMap<(usize, usize), usize>,
+    current: usize,
+    input: &'a DecodedInput,
+}
+
+impl<'a> Analyzer<'a> {
+    pub(crate) fn analyze(ast: Option<&Node>, input: &'a DecodedInput) -> ScopeTree {
+        let top_level = Scope {
+            kind: ScopeKind::TopLevel,
+            loc: ast.map(|node| *node.expression()).unwrap_or_default(),
+            parent: None,
+            variables: vec![],
+        };
+        let mut analyzer = Self {
+            scopes: vec![top_level],
+            variables: vec![],
+            locs: HashMap::new(),
+            current: 0,
+            input,
+        };
+        if let Some(ast) = ast {
+            analyzer.visit(ast);
+        }
+        ScopeTree {
+            scopes: analyzer.scopes,
+            variables: analyzer.variables,
+            locs: analyzer.locs,
+        }
+    }
+
+    fn with_scope<F: FnOnce(&mut Self)>(&mut self, kind: ScopeKind, loc: Loc, f: F) {
+        let parent = self.current;
+        self.scopes.push(Scope {
+            kind,
+            loc,
+            parent: Some(parent),
+            variables: vec![],
+        });
+        self.current = self.scopes.len() - 1;
+        f(self);
+        self.current = parent;
+    }
+
+    // Finds a variable that is visible in the current scope,
+    // returns the outermost visible scope if there's no such variable
+    fn lookup(&self, name: &str) -> Result<usize, usize> {
+        let mut scope_idx = self.current;
+        loop {
+            let scope = &self.scopes[scope_idx];
+            for idx in scope.variables.iter() {
+                if self.variables[*idx].name == name {
+                    return Ok(*idx);
+                }
+            }
+            match scope.parent {
+                Some(parent) if scope.kind.is_soft() => scope_idx = parent,
+                _ => return Err(scope_idx),
+            }
+        }
+    }
+
+    fn add_variable(
+        &mut self,
+        name: &str,
+        scope: usize,
+        declaration: Option<Declaration>,
+    ) -> usize {
+        let idx = self.variables.len();
+        self.variables.push(Variable {
+            name: name.to_string(),
+            scope,
+            declaration,
+            assignments: vec![],
+            references: vec![],
+        });
+        self.scopes[scope].variables.push(idx);
+        idx
+    }
+
+    // Arguments always declare a new variable (that shadows variables of the enclosing scope)
+    fn declare(&mut self, name: &str, loc: Loc, kind: DeclarationKind) {
+        let declaration = Declaration { kind, loc };
+        let idx = self.add_variable(name, self.current, Some(declaration));
+        self.locs.insert((loc.begin, loc.end), idx);
+    }
+
+    // Assignments declare a new variable only if it's not visible yet
+    fn assign(&mut self, name: &str, loc: Loc, kind: DeclarationKind) {
+        let declaration = Declaration { kind, loc };
+        let idx = match self.lookup(name) {
+            Ok(idx) => idx,
+            Err(_) => self.add_variable(name, self.current, Some(declaration)),
+        };
+        let variable = &mut self.variables[idx];
+        variable.declaration.get_or_insert(declaration);
+        variable.assignments.push(loc);
+        self.locs.insert((loc.begin, loc.end), idx);
+    }
+
+    fn reference(&mut self, name: &str, loc: Loc) {
+        let idx = match self.lookup(name) {
+            Ok(idx) => idx,
+            // declared outside of the parsed code
+            Err(scope) => self.add_variable(name, scope, None),
+        };
+        self.variables[idx].references.push(loc);
+        self.locs.insert((loc.begin, loc.end), idx);
+    }
+
+    fn visit_opt(&mut self, node: &Option<Box<Node>>) {
+        if let Some(node) = node {
+            self.visit(node);
+        }
+    }
+
+    fn declare_named_captures(&mut self, re: &Node) {
+        let regexp = match re {
+            Node::Regexp(regexp) => regexp,
+            _ => return,
+        };
+        let tree = match parse_regexp_literal(regexp, self.input) {
+            Some(Ok(tree)) => tree,
+            _ => return,
+        };
+        let mut captures = vec![];
+        collect_named_captures(&tree.root, &mut captures);
+        for (name, name_l) in captures {
+            // MRI doesn't declare variables for named captures like `(?<Foo>)`
+            if tree.named_captures.contains(&name) && is_local_variable_name(&name) {
+                self.assign(&name, name_l, DeclarationKind::NamedCapture);
+            }
+        }
+    }
+}
+
+impl Visitor for Analyzer<'_> {
+    fn on_def(&mut self, node: &Def) {
+        self.with_scope(ScopeKind::Def, node.expression_l, |this| {
+            visit_def(this, node)
+        });
+    }
+
+    fn on_defs(&mut self, node: &Defs) {
+        // definee is evaluated in the enclosing scope
+        self.visit(&node.definee);
+        self.with_scope(ScopeKind::Def, node.expression_l, |this| {
+            this.visit_opt(&node.args);
+            this.visit_opt(&node.body);
+        });
+    }
+
+    fn on_class(&mut self, node: &Class) {
+        self.visit(&node.name);
+        self.visit_opt(&node.superclass);
+        self.with_scope(ScopeKind::Class, node.expression_l, |this| {
+            this.visit_opt(&node.body)
+        });
+    }
+
+    fn on_s_class(&mut self, node: &SClass) {
+        self.visit(&node.expr);
+        self.with_scope(ScopeKind::SClass, node.expression_l, |this| {
+            this.visit_opt(&node.body)
+        });
+    }
+
+    fn on_module(&mut self, node: &Module) {
+        self.visit(&node.name);
+        self.with_scope(ScopeKind::Module, node.expression_l, |this| {
+            this.visit_opt(&node.body)
+        });
+    }
+
+    fn on_block(&mut self, node: &Block) {
+        let kind = match &*node.call {
+            Node::Lambda(_) => ScopeKind::Lambda,
+            call => {
+                self.visit(call);
+                ScopeKind::Block
+            }
+        };
+        self.with_scope(kind, node.expression_l, |this| {
+            this.visit_opt(&node.args);
+            this.visit_opt(&node.body);
+        });
+    }
+
+    fn on_numblock(&mut self, node: &Numblock) {
+        let kind = match &*node.call {
+            Node::Lambda(_) => ScopeKind::Lambda,
+            call => {
+                self.visit(call);
+                ScopeKind::Block
+            }
+        };
+        self.with_scope(kind, node.expression_l, |this| {
+            for n in 1..=node.numargs {
+                let name = format!("_{}", n);
+                let declaration = Declaration {
+                    kind: DeclarationKind::NumberedParameter,
+                    loc: node.expression_l,
+                };
+                this.add_variable(&name, this.current, Some(declaration));
+            }
+            this.visit(&node.body);
+        });
+    }
+
+    fn on_postexe(&mut self, node: &Postexe) {
+        self.with_scope(ScopeKind::Block, node.expression_l, |this| {
+            this.visit_opt(&node.body)
+        });
+    }
+
+    fn on_lvasgn(&mut self, node: &Lvasgn) {
+        self.assign(&node.name, node.name_l, DeclarationKind::Lvasgn);
+        visit_lvasgn(self, node);
+    }
+
+    fn on_match_var(&mut self, node: &MatchVar) {
+        self.assign(&node.name, node.name_l, DeclarationKind::MatchVar);
+    }
+
+    fn on_lvar(&mut self, node: &Lvar) {
+        self.reference(&node.name, node.expression_l);
+    }
+
+    fn on_match_with_lvasgn(&mut self, node: &MatchWithLvasgn) {
+        visit_match_with_lvasgn(self, node);
+        self.declare_named_captures(&node.re);
+    }
+
+    fn on_arg(&mut self, node: &Arg) {
+        self.declare(&node.name, node.expression_l, DeclarationKind::Arg);
+    }
+
+    fn on_optarg(&mut self, node: &Optarg) {
+        self.declare(&node.name, node.name_l, DeclarationKind::Arg);
+        visit_optarg(self, node);
+    }
+
+    fn on_restarg(&mut self, node: &Restarg) {
+        if let (Some(name), Some(name_l)) = (&node.name, node.name_l) {
+            self.declare(name, name_l, DeclarationKind::Arg);
+        }
+    }
+
+    fn on_kwarg(&mut self, node: &Kwarg) {
+        self.declare(&node.name, node.name_l, DeclarationKind::Arg);
+    }
+
+    fn on_kwoptarg(&mut self, node: &Kwoptarg) {
+        self.declare(&node.name, node.name_l, DeclarationKind::Arg);
+        visit_kwoptarg(self, node);
+    }
+
+    fn on_kwrestarg(&mut self, node: &Kwrestarg) {
+        if let (Some(name), Some(name_l)) = (&node.name, node.name_l) {
+            self.declare(name, name_l, DeclarationKind::Arg);
+        }
+    }
+
+    fn on_blockarg(&mut self, node: &Blockarg) {
+        if let (Some(name), Some(name_l)) = (&node.name, node.name_l) {
+            self.declare(name, name_l, DeclarationKind::Arg);
+        }
+    }
+
+    fn on_shadowarg(&mut self, node: &Shadowarg) {
+        self.declare(&node.name, node.expression_l, DeclarationKind::Arg);
+    }
+}
+
+fn collect_named_captures(node: &RegexpNode, captures: &mut Vec<(String, Loc)>) {
+    match node {
+        RegexpNode::Alternation(Alternation { alternatives, .. }) => {
+            for node in alternatives.iter() {
+                collect_named_captures(node, captures);
+            }
+        }
+        RegexpNode::Sequence(Sequence { items, .. }) => {
+            for node in items.iter() {
+                collect_named_captures(node, captures);
+            }
+        }
+        RegexpNode::Group(Group { kind, body, .. }) => {
+            if let GroupKind::NamedCapture { name, name_l, .. } = kind {
+                if !captures.iter().any(|(known, _)| known == name) {
+                    captures.push((name.clone(), *name_l));
+                }
+            }
+            collect_named_captures(body, captures);
+        }
+        RegexpNode::Conditional(Conditional {
+            if_true, if_false, ..
+        }) => {
+            collect_named_captures(if_true, captures);
+            if let Some(if_false) = if_false {
+                collect_named_captures(if_false, captures);
+            }
+        }
+        RegexpNode::Quantified(Quantified { target, .. }) => {
+            collect_named_captures(target, captures);
+        }
+        _ => {}
+    }
+}
+
+#[test]
+fn test_analyze() {
+    // a = 1; foo { |x| a; x }
+    let loc = |begin, end| Loc { begin, end };
+    let ast = Node::Begin(Begin {
+        statements: vec![
+            Node::Lvasgn(Lvasgn {
+                name: String::from("a"),
+                value: Some(Box::new(Node::Int(Int {
+                    value: String::from("1"),
+                    operator_l: None,
+                    expression_l: loc(4, 5),
+                }))),
+                name_l: loc(0, 1),
+                operator_l: Some(loc(2, 3)),
+                expression_l: loc(0, 5),
+            }),
+            Node::Block(Block {
+                call: Box::new(Node::Send(Send {
+                    recv: None,
+                    method_name: String::from("foo"),
+                    args: vec![],
+                    dot_l: None,
+                    selector_l: Some(loc(7, 10)),
+                    begin_l: None,
+                    end_l: None,
+                    operator_l: None,
+                    expression_l: loc(7, 10),
+                })),
+                args: Some(Box::new(Node::Args(Args {
+                    args: vec![Node::Arg(Arg {
+                        name: String::from("x"),
+                        expression_l: loc(14, 15),
+                    })],
+                    expression_l: loc(13, 16),
+                    begin_l: Some(loc(13, 14)),
+                    end_l: Some(loc(15, 16)),
+                }))),
+                body: Some(Box::new(Node::Begin(Begin {
+                    statements: vec![
+                        Node::Lvar(Lvar {
+                            name: String::from("a"),
+                            expression_l: loc(17, 18),
+                        }),
+                        Node::Lvar(Lvar {
+                            name: String::from("x"),
+                            expression_l: loc(20, 21),
+                        }),
+                    ],
+                    begin_l: None,
+                    end_l: None,
+                    expression_l: loc(17, 21),
+                }))),
+                begin_l: loc(11, 12),
+                end_l: loc(22, 23),
+                expression_l: loc(7, 23),
+            }),
+        ],
+        begin_l: None,
+        end_l: None,
+        expression_l: loc(0, 23),
+    });
+
+    let input = DecodedInput::named("(test_analyze)");
+    let tree = ScopeTree::new(Some(&ast), &input);
+
+    assert_eq!(tree.scopes.len(), 2);
+    assert_eq!(tree.scopes[1].kind, ScopeKind::Block);
+    assert_eq!(tree.scopes[1].parent, Some(0));
+
+    let a = tree.variable_at(&loc(17, 18)).unwrap();
+    assert_eq!(a.name, "a");
+    assert_eq!(a.scope, 0);
+    assert_eq!(
+        a.declaration,
+        Some(Declaration {
+            kind: DeclarationKind::Lvasgn,
+            loc: loc(0, 1)
+        })
+    );
+    assert_eq!(a.assignments, vec![loc(0, 1)]);
+    assert_eq!(a.references, vec![loc(17, 18)]);
+
+    let x = tree.variable_at(&loc(20, 21)).unwrap();
+    assert_eq!(x.name, "x");
+    assert_eq!(x.scope, 1);
+    assert_eq!(x.declaration.unwrap().kind, DeclarationKind::Arg);
+    assert_eq!(
+        tree.scope_variables(&tree.scopes[1])
+            .map(|variable| variable.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["x"]
+    );
+}
+
+#[test]
+fn test_hard_scope() {
+    // a = 1; def m; a; end
+    let loc = |begin, end| Loc { begin, end };
+    let ast = Node::Begin(Begin {
+        statements: vec![
+            Node::Lvasgn(Lvasgn {
+                name: String::from("a"),
+                value: None,
+                name_l: loc(0, 1),
+                operator_l: None,
+                expression_l: loc(0, 1),
+            }),
+            Node::Def(Def {
+                name: String::from("m"),
+                args: None,
+                body: Some(Box::new(Node::Lvar(Lvar {
+                    name: String::from("a"),
+                    expression_l: loc(14, 15),
+                }))),
+                keyword_l: loc(7, 10),
+                name_l: loc(11, 12),
+                end_l: Some(loc(17, 20)),
+                assignment_l: None,
+                expression_l: loc(7, 20),
+            }),
+        ],
+        begin_l: None,
+        end_l: None,
+        expression_l: loc(0, 20),
+    });
+
+    let input = DecodedInput::named("(test_hard_scope)");
+    let tree = ScopeTree::new(Some(&ast), &input);
+
+    // `a` in `def` is not the top level `a`
+    let a = tree.variable_at(&loc(14, 15)).unwrap();
+    assert_eq!(a.scope, 1);
+    assert_eq!(a.declaration, None);
+    assert_eq!(tree.top_level().variables, vec![0]);
+}
@@ -0,0 +1,166 @@
+mod analyzer;
+
+use std::collections::HashMap;
+
+use crate::source::DecodedInput;
+use crate::{Loc, Node};
+
+/// Kind of the lexical scope
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// The whole file
+    TopLevel,
+
+    /// `def foo; end` or `def self.foo; end`
+    Def,
+
+    /// `class Foo; end`
+    Class,
+
+    /// `class << self; end`
+    SClass,
+
+    /// `module Foo; end`
+    Module,
+
+    /// `foo { }`, `foo do end`, `foo { _1 }` or `END { }`
+    Block,
+
+    /// `-> { }`
+    Lambda,
+}
+
+impl ScopeKind {
+    /// Returns `true` if variables of the enclosing scope are visible in this scope
+    /// (i.e. for blocks and lambdas)
+    pub fn is_soft(&self) -> bool {
+        matches!(self, Self::Block | Self::Lambda)
+    }
+}
+
+/// Lexical scope of local variables
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scope {
+    /// Kind of the scope
+    pub kind: ScopeKind,
+
+    /// Location of the node that creates the scope
+    /// (i.e. the whole `def`/`class`/block), or of the whole AST for the top level scope
+    pub loc: Loc,
+
+    /// Index of the enclosing scope in `ScopeTree::scopes`, `None` for the top level scope
+    pub parent: Option<usize>,
+
+    /// Indices of variables declared in this scope in `ScopeTree::variables`
+    pub variables: Vec<usize>,
+}
+
+/// Kind of the node that declares a local variable
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeclarationKind {
+    /// `Lvasgn`, `a = 1`
+    Lvasgn,
+
+    /// Any named argument of the method or block (`Arg`, `Optarg`, `Kwarg`, `Restarg`, etc)
+    Arg,
+
+    /// `MatchVar`, `in a` in pattern matching
+    MatchVar,
+
+    /// Named group of the regexp literal that is matched with `=~`, `/(?<a>.)/ =~ b`
+    NamedCapture,
+
+    /// Numbered parameter of the block, `_1`
+    NumberedParameter,
+}
+
+/// Place where a local variable is declared
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Declaration {
+    /// Kind of the declaring node
+    pub kind: DeclarationKind,
+
+    /// Location of the variable name in the declaring node.
+    ///
+    /// For numbered parameters it's a location of the whole block.
+    pub loc: Loc,
+}
+
+/// Local variable
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variable {
+    /// Name of the variable
+    pub name: String,
+
+    /// Index of the scope that owns the variable in `ScopeTree::scopes`
+    pub scope: usize,
+
+    /// Node that declares the variable.
+    ///
+    /// `None` if the variable is declared outside of the parsed code
+    /// (i.e. using `StaticEnvironment::declare` before parsing) and never assigned.
+    pub declaration: Option<Declaration>,
+
+    /// Locations of names of all `Lvasgn` and `MatchVar` nodes
+    /// that assign the variable (including the declaration)
+    pub assignments: Vec<Loc>,
+
+    /// Locations of all `Lvar` nodes that refer to the variable
+    pub references: Vec<Loc>,
+}
+
+/// Result of the scope analysis: all lexical scopes of the AST
+/// and all local variables with their declarations and references.
+///
+/// ```
+/// use lib_ruby_parser::{Loc, Parser, ParserOptions};
+///
+/// let parser = Parser::new(b"a = 1; foo { a }".to_vec(), ParserOptions::default());
+/// let scope_tree = parser.do_parse().scope_tree();
+///
+/// let variable = scope_tree.variable_at(&Loc { begin: 13, end: 14 }).unwrap();
+/// assert_eq!(variable.name, "a");
+/// assert_eq!(variable.declaration.unwrap().loc, Loc { begin: 0, end: 1 });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeTree {
+    /// List of all scopes in order of their appearance,
+    /// the first one is the top level scope
+    pub scopes: Vec<Scope>,
+
+    /// List of all variables in order of their declaration
+    pub variables: Vec<Variable>,
+
+    // (begin, end) of every known location of the variable -> index of the variable
+    locs: HashMap<(usize, usize), usize>,
+}
+
+impl ScopeTree {
+    /// Analyzes given AST, `input` is used to find named captures of regexps
+    pub fn new(ast: Option<&Node>, input: &DecodedInput) -> Self {
+        analyzer::Analyzer::analyze(ast, input)
+    }
+
+    /// Returns the top level scope
+    pub fn top_level(&self) -> &Scope {
+        &self.scopes[0]
+    }
+
+    /// Returns a variable that is declared, assigned or referenced at the given location,
+    /// i.e. `loc` must be a location of the `Lvar` node or of the variable name
+    /// in `Lvasgn`, argument or `MatchVar` node.
+    pub fn variable_at(&self, loc: &Loc) -> Option<&Variable> {
+        let idx = self.locs.get(&(loc.begin, loc.end))?;
+        self.variables.get(*idx)
+    }
+
+    /// Returns variables declared in the given scope
+    pub fn scope_variables<'a>(&'a self, scope: &'a Scope) -> impl Iterator<Item = &'a Variable> {
+        scope.variables.iter().map(move |idx| &self.variables[*idx])
+    }
+}
@@ -4,6 +4,8 @@ use crate::maybe_byte::*;
 use crate::source::input::Input;
 use crate::source::Decoder;
 use crate::source::InputError;
+use crate::source::SourceBytes;
+use crate::{Bytes, TokenValue};
 
 #[derive(Debug, Default)]
 pub(crate) struct Buffer {
@@ -49,7 +51,7 @@ impl Buffer {
     const CTRL_Z_CHAR: u8 = 0x1a;
     const CTRL_D_CHAR: u8 = 0x04;
 
-    pub(crate) fn new(name: String, bytes: Vec<u8>, decoder: Option<Decoder>) -> Self {
+    pub(crate) fn new(name: String, bytes: SourceBytes, decoder: Option<Decoder>) -> Self {
         let mut input = Input::new(name, decoder);
 
         input.update_bytes(bytes);
@@ -222,6 +224,10 @@ impl Buffer {
         self.input.substr_at(start, end)
     }
 
+    pub(crate) fn token_value(&self, value: Option<Bytes>, start: usize, end: usize) -> TokenValue {
+        self.input.token_value(value, start, end)
+    }
+
     pub(crate) fn was_bol(&self) -> bool {
         self.pcur == self.pbeg + 1
     }
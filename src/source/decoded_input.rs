@@ -1,8 +1,10 @@
+use crate::source::OffsetMap;
+use crate::source::SourceBytes;
 use crate::source::SourceLine;
+use crate::{Bytes, TokenValue};
 
 /// Decoded input
 #[derive(Debug, Default)]
-#[repr(C)]
 pub struct DecodedInput {
     /// Name of the input
     pub name: String,
@@ -10,8 +12,21 @@ pub struct DecodedInput {
     /// Lines list
     pub lines: Vec<SourceLine>,
 
-    /// Decoded bytes
-    pub bytes: Vec<u8>,
+    /// Decoded bytes, shared with the caller
+    /// if input has been given as `SourceBytes::Shared` (or other non-owned variant)
+    /// and has not been re-encoded
+    pub bytes: SourceBytes,
+
+    /// Mapping between decoded bytes and bytes of the original input.
+    ///
+    /// Empty if input has not been re-encoded by a `Decoder`.
+    pub offset_map: OffsetMap,
+
+    /// Encoding of the input set by the magic `encoding:` comment
+    /// (like `"KOI8-R"` or `"ASCII-8BIT"`).
+    ///
+    /// `None` if there's no such comment, i.e. input is in UTF-8.
+    pub encoding: Option<String>,
 }
 
 impl DecodedInput {
@@ -28,15 +43,19 @@ impl DecodedInput {
 
     /// Converts itself into owned vector of bytes
     pub fn into_bytes(self) -> Vec<u8> {
-        self.bytes
+        self.bytes.into_vec()
     }
 
-    pub(crate) fn take_bytes(&mut self) -> Vec<u8> {
+    pub(crate) fn take_bytes(&mut self) -> SourceBytes {
         std::mem::take(&mut self.bytes)
     }
 
     /// Populates `Input` with a given byte array
-    pub fn update_bytes(&mut self, bytes: Vec<u8>) {
+    pub fn update_bytes<T>(&mut self, bytes: T)
+    where
+        T: Into<SourceBytes>,
+    {
+        let bytes: SourceBytes = bytes.into();
         let mut line = SourceLine {
             start: 0,
             end: 0,
@@ -62,6 +81,14 @@ impl DecodedInput {
 
         self.bytes = bytes;
         self.lines = lines;
+        self.offset_map = OffsetMap::default();
+    }
+
+    /// Populates `Input` with a given byte array
+    /// that has been produced by a `Decoder` from the original input
+    pub(crate) fn update_decoded_bytes(&mut self, bytes: SourceBytes, offset_map: OffsetMap) {
+        self.update_bytes(bytes);
+        self.offset_map = offset_map;
     }
 
     /// Returns (line, col) pair for a given byte offset.
@@ -97,6 +124,19 @@ impl DecodedInput {
         }
     }
 
+    // Value of the token at `start..end`: `value` if it's different from the source of the token,
+    // otherwise (or if there's no `value`) a slice of the input
+    pub(crate) fn token_value(&self, value: Option<Bytes>, start: usize, end: usize) -> TokenValue {
+        match (value, self.substr_at(start, end)) {
+            (Some(value), Some(source)) if !self.bytes.is_shared() || value.as_raw() != source => {
+                TokenValue::owned(value)
+            }
+            (Some(value), None) => TokenValue::owned(value),
+            (_, Some(_)) => TokenValue::shared(&self.bytes, start, end),
+            (None, None) => TokenValue::default(),
+        }
+    }
+
     pub(crate) fn len(&self) -> usize {
         self.bytes.len()
     }
@@ -1,3 +1,5 @@
+use crate::source::{OffsetMap, SourceBytes};
+
 /// An enum with all possible kinds of errors that can be returned
 /// from a decoder
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -64,20 +66,69 @@ impl DecoderResult {
 /// in the `ParserResult::diagnostics` vector.
 pub type DecoderFn = dyn Fn(String, Vec<u8>) -> DecoderResult;
 
+/// Decoding function that records offsets
+///
+/// Takes encoding name, initial input and an empty `OffsetMap` as arguments
+/// and returns the same `DecoderResult` as `DecoderFn`. While decoding it must
+/// call `OffsetMap::record` for every re-encoded range of bytes (in order),
+/// all bytes between recorded ranges must be copied as is.
+pub type DecoderWithOffsetsFn = dyn Fn(String, &[u8], &mut OffsetMap) -> DecoderResult;
+
+enum DecoderKind {
+    Plain(Box<DecoderFn>),
+    WithOffsets(Box<DecoderWithOffsetsFn>),
+}
+
 /// Custom decoder, a wrapper around a function
 pub struct Decoder {
-    f: Box<DecoderFn>,
+    kind: DecoderKind,
 }
 
 impl Decoder {
-    /// Constructs a rewriter based on a given function
+    /// Constructs a decoder based on a given function.
+    ///
+    /// Such decoder doesn't report which bytes it has re-encoded,
+    /// so `DecodedInput::offset_map` is computed after decoding
+    /// by aligning original and decoded inputs (see `Decoder::with_offsets`).
     pub fn new(f: Box<DecoderFn>) -> Self {
-        Self { f }
+        Self {
+            kind: DecoderKind::Plain(f),
+        }
+    }
+
+    /// Constructs a decoder based on a given function
+    /// that records re-encoded ranges while decoding.
+    ///
+    /// Unlike `Decoder::new` it doesn't copy the input
+    /// and gives precise `DecodedInput::offset_map`.
+    pub fn with_offsets(f: Box<DecoderWithOffsetsFn>) -> Self {
+        Self {
+            kind: DecoderKind::WithOffsets(f),
+        }
     }
 
-    pub(crate) fn call(&self, encoding: String, input: Vec<u8>) -> DecoderResult {
-        let f = &*self.f;
-        f(encoding, input)
+    pub(crate) fn decode(
+        &self,
+        encoding: String,
+        input: &[u8],
+    ) -> Result<(Vec<u8>, OffsetMap), InputError> {
+        match &self.kind {
+            DecoderKind::Plain(f) => {
+                let decoded = f(encoding, input.to_vec()).into_result()?;
+                let offset_map = OffsetMap::compute(input, &decoded);
+                Ok((decoded, offset_map))
+            }
+            DecoderKind::WithOffsets(f) => {
+                let mut offset_map = OffsetMap::default();
+                let decoded = f(encoding, input, &mut offset_map).into_result()?;
+                if !offset_map.is_valid(input.len(), decoded.len()) {
+                    return Err(InputError::DecodingError(
+                        "decoder has recorded offsets that don't match decoded input".to_string(),
+                    ));
+                }
+                Ok((decoded, offset_map))
+            }
+        }
     }
 }
 
@@ -87,15 +138,105 @@ impl std::fmt::Debug for Decoder {
     }
 }
 
-pub fn decode_input(input: Vec<u8>, enc: String, decoder: &mut Option<Decoder>) -> DecoderResult {
+pub fn decode_input(
+    input: SourceBytes,
+    enc: String,
+    decoder: &mut Option<Decoder>,
+) -> Result<(SourceBytes, OffsetMap), InputError> {
     match enc.to_uppercase().as_str() {
-        "UTF-8" | "ASCII-8BIT" | "BINARY" => DecoderResult::Ok(input),
+        "UTF-8" | "ASCII-8BIT" | "BINARY" => Ok((input, OffsetMap::default())),
         _ => {
-            if let Some(f) = decoder.as_mut() {
-                f.call(enc, input)
+            if let Some(decoder) = decoder.as_mut() {
+                let (decoded, offset_map) = decoder.decode(enc, &input)?;
+                Ok((SourceBytes::Owned(decoded), offset_map))
             } else {
-                DecoderResult::Err(InputError::UnsupportedEncoding(enc))
+                Err(InputError::UnsupportedEncoding(enc))
             }
         }
     }
 }
+
+#[cfg(test)]
+use crate::Loc;
+
+// Decodes "表" and "示" from Shift_JIS, copies everything else
+#[cfg(test)]
+fn decode_shift_jis(_encoding: String, input: &[u8], offset_map: &mut OffsetMap) -> DecoderResult {
+    let mut decoded = vec![];
+    let mut pos = 0;
+    while pos < input.len() {
+        let chr = match &input[pos..] {
+            [0x95, 0x5C, ..] => "表",
+            [0x8E, 0xA6, ..] => "示",
+            _ => {
+                decoded.push(input[pos]);
+                pos += 1;
+                continue;
+            }
+        };
+        offset_map.record(
+            Loc {
+                begin: pos,
+                end: pos + 2,
+            },
+            Loc {
+                begin: decoded.len(),
+                end: decoded.len() + chr.len(),
+            },
+        );
+        decoded.extend_from_slice(chr.as_bytes());
+        pos += 2;
+    }
+    DecoderResult::Ok(decoded)
+}
+
+#[test]
+fn test_decoder_with_offsets() {
+    let mut decoder = Some(Decoder::with_offsets(Box::new(decode_shift_jis)));
+    let input = SourceBytes::Static(b"s = \"\x95\x5C\x8E\xA6\"");
+    let (decoded, offset_map) = decode_input(input, "Shift_JIS".to_string(), &mut decoder).unwrap();
+
+    assert_eq!(&*decoded, "s = \"表示\"".as_bytes());
+    // adjacent multibyte chars are not merged
+    assert_eq!(
+        offset_map.original_loc(Loc { begin: 8, end: 11 }),
+        Loc { begin: 7, end: 9 }
+    );
+    assert_eq!(
+        offset_map.original_loc(Loc { begin: 11, end: 12 }),
+        Loc { begin: 9, end: 10 }
+    );
+}
+
+#[test]
+fn test_decoder_without_offsets() {
+    fn decode(encoding: String, input: Vec<u8>) -> DecoderResult {
+        let mut offset_map = OffsetMap::default();
+        decode_shift_jis(encoding, &input, &mut offset_map)
+    }
+
+    let mut decoder = Some(Decoder::new(Box::new(decode)));
+    let input = SourceBytes::Static(b"s = \"\x95\x5C\x8E\xA6\"");
+    let (_, offset_map) = decode_input(input, "Shift_JIS".to_string(), &mut decoder).unwrap();
+
+    // computed after decoding, both chars are merged into a single range
+    assert_eq!(
+        offset_map.original_loc(Loc { begin: 8, end: 11 }),
+        Loc { begin: 5, end: 9 }
+    );
+}
+
+#[test]
+fn test_decoder_with_invalid_offsets() {
+    fn decode(_encoding: String, _input: &[u8], offset_map: &mut OffsetMap) -> DecoderResult {
+        offset_map.record(Loc { begin: 0, end: 1 }, Loc { begin: 0, end: 3 });
+        DecoderResult::Ok(b"decoded".to_vec())
+    }
+
+    let mut decoder = Some(Decoder::with_offsets(Box::new(decode)));
+    let input = SourceBytes::Static(b"\xFF");
+    assert!(matches!(
+        decode_input(input, "Shift_JIS".to_string(), &mut decoder),
+        Err(InputError::DecodingError(_))
+    ));
+}
@@ -1,10 +1,11 @@
 use crate::source::Decoder;
+use crate::source::SourceBytes;
 use crate::source::SourceLine;
 use crate::source::{decode_input, DecodedInput, InputError};
+use crate::{Bytes, TokenValue};
 
 /// Representation of the source code.
 #[derive(Debug, Default)]
-#[repr(C)]
 pub struct Input {
     pub(crate) decoded: DecodedInput,
     decoder: Option<Decoder>,
@@ -23,7 +24,10 @@ impl Input {
     }
 
     /// Populates `Input` with a given byte array
-    pub fn update_bytes(&mut self, bytes: Vec<u8>) {
+    pub fn update_bytes<T>(&mut self, bytes: T)
+    where
+        T: Into<SourceBytes>,
+    {
         self.decoded.update_bytes(bytes)
     }
 
@@ -39,6 +43,10 @@ impl Input {
         self.decoded.substr_at(start, end)
     }
 
+    pub(crate) fn token_value(&self, value: Option<Bytes>, start: usize, end: usize) -> TokenValue {
+        self.decoded.token_value(value, start, end)
+    }
+
     /// Returns (line, col) pair for a given byte offset.
     ///
     /// Returns None if given offset is out of range.
@@ -63,13 +71,18 @@ impl Input {
     }
 
     pub(crate) fn set_encoding(&mut self, encoding: &str) -> Result<(), InputError> {
-        let new_input = decode_input(
+        let (new_input, offset_map) = decode_input(
             self.decoded.take_bytes(),
             String::from(encoding),
             &mut self.decoder,
-        )
-        .into_result()?;
-        self.update_bytes(new_input);
+        )?;
+        self.decoded.update_decoded_bytes(new_input, offset_map);
+        let encoding = encoding.to_uppercase();
+        self.decoded.encoding = Some(match encoding.as_str() {
+            "BINARY" => String::from("ASCII-8BIT"),
+            "ASCII" => String::from("US-ASCII"),
+            _ => encoding,
+        });
         Ok(())
     }
 
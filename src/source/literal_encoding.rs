@@ -0,0 +1,219 @@
+use super::string_content_map::{content_map_at, str_content_l};
+use super::{DecodedInput, StringContentMap};
+use crate::nodes::{RegOpt, Regexp, Str, Sym};
+use crate::{Loc, Node};
+
+/// Encoding of the string, symbol or regexp literal
+/// (i.e. what `#encoding` returns for it in Ruby)
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiteralEncoding {
+    /// Name of the encoding, like `"UTF-8"`, `"US-ASCII"` or `"ASCII-8BIT"`
+    pub name: String,
+
+    /// `true` if the value is a valid byte sequence in this encoding
+    /// (i.e. what `#valid_encoding?` returns for it in Ruby).
+    ///
+    /// Values in encodings that are re-encoded by a `Decoder`
+    /// are checked after decoding, i.e. as UTF-8.
+    pub valid: bool,
+}
+
+impl LiteralEncoding {
+    fn new(name: &str, value: &[u8]) -> Self {
+        let valid = match name {
+            "ASCII-8BIT" => true,
+            "US-ASCII" => value.is_ascii(),
+            _ => std::str::from_utf8(value).is_ok(),
+        };
+        Self {
+            name: name.to_string(),
+            valid,
+        }
+    }
+}
+
+/// Computes encoding of the given `Str`, `Sym` or `Regexp` node
+pub(crate) fn literal_encoding(
+    node: &Node,
+    input: &DecodedInput,
+    maps: &[StringContentMap],
+) -> Option<LiteralEncoding> {
+    let source_encoding = input.encoding.as_deref().unwrap_or("UTF-8");
+
+    match node {
+        Node::Str(str) => {
+            let value = str.value.as_raw();
+            let map = content_map_at(maps, &str_content_l(str));
+            let forced_utf8 = has_utf8_escapes(value, map, input);
+            Some(string_encoding(value, forced_utf8, source_encoding))
+        }
+        Node::Sym(sym) => {
+            let value = sym.name.as_raw();
+            if value.is_ascii() {
+                return Some(LiteralEncoding::new("US-ASCII", value));
+            }
+            let map = content_map_at(maps, &sym_content_l(sym));
+            let forced_utf8 = has_utf8_escapes(value, map, input);
+            Some(string_encoding(value, forced_utf8, source_encoding))
+        }
+        Node::Regexp(regexp) => Some(regexp_encoding(regexp, source_encoding)),
+        _ => None,
+    }
+}
+
+// Mirrors `parser_str_new` from MRI
+fn string_encoding(value: &[u8], forced_utf8: bool, source_encoding: &str) -> LiteralEncoding {
+    let name = if value.is_ascii() {
+        source_encoding
+    } else if forced_utf8 {
+        "UTF-8"
+    } else if source_encoding == "US-ASCII" {
+        "ASCII-8BIT"
+    } else {
+        source_encoding
+    };
+    LiteralEncoding::new(name, value)
+}
+
+// Returns `true` if the content has non-ASCII chars produced by `\u` escapes
+fn has_utf8_escapes(value: &[u8], map: Option<&StringContentMap>, input: &DecodedInput) -> bool {
+    let map = match map {
+        Some(map) => map,
+        None => return false,
+    };
+    map.ranges.iter().any(|range| {
+        let content = value.get(range.content.begin..range.content.end);
+        let source = input.substr_at(range.source.begin, range.source.end);
+        match (content, source) {
+            (Some(content), Some(source)) => !content.is_ascii() && source.starts_with(b"\\u"),
+            _ => false,
+        }
+    })
+}
+
+fn sym_content_l(node: &Sym) -> Loc {
+    match (&node.begin_l, &node.end_l) {
+        (Some(begin_l), Some(end_l)) => Loc {
+            begin: begin_l.end,
+            end: end_l.begin,
+        },
+        _ => node.expression_l,
+    }
+}
+
+// Mirrors `rb_reg_preprocess` and `reg_fragment_setenc` from MRI,
+// unlike strings regexps keep their escape sequences
+fn regexp_encoding(regexp: &Regexp, source_encoding: &str) -> LiteralEncoding {
+    let mut source = vec![];
+    for part in regexp.parts.iter() {
+        // interpolated parts are not known during parsing
+        if let Node::Str(Str { value, .. }) = part {
+            source.extend_from_slice(value.as_raw());
+        }
+    }
+    let options = match regexp.options.as_deref() {
+        Some(Node::RegOpt(RegOpt {
+            options: Some(options),
+            ..
+        })) => options.as_str(),
+        _ => "",
+    };
+    let escapes = RegexpEscapes::scan(&source);
+
+    let name = if options.contains('n') {
+        if !source.is_ascii() || escapes.non_ascii_byte {
+            "ASCII-8BIT"
+        } else {
+            "US-ASCII"
+        }
+    } else if options.contains('u') {
+        "UTF-8"
+    } else if options.contains('e') {
+        "EUC-JP"
+    } else if options.contains('s') {
+        "Windows-31J"
+    } else if !source.is_ascii() {
+        source_encoding
+    } else if escapes.unicode {
+        "UTF-8"
+    } else {
+        "US-ASCII"
+    };
+    LiteralEncoding::new(name, &source)
+}
+
+#[derive(Debug, Default)]
+struct RegexpEscapes {
+    // `\u` escapes
+    unicode: bool,
+    // `\x80`..`\xFF` or `\200`..`\377` escapes
+    non_ascii_byte: bool,
+}
+
+impl RegexpEscapes {
+    fn scan(source: &[u8]) -> Self {
+        let mut result = Self::default();
+        let mut idx = 0;
+        while idx + 1 < source.len() {
+            if source[idx] != b'\\' {
+                idx += 1;
+                continue;
+            }
+            let rest = &source[idx + 2..];
+            match source[idx + 1] {
+                b'u' => result.unicode = true,
+                b'x' if rest.len() >= 2
+                    && rest[0].is_ascii_hexdigit()
+                    && rest[1].is_ascii_hexdigit()
+                    && rest[0] >= b'8' =>
+                {
+                    result.non_ascii_byte = true
+                }
+                b'2' | b'3'
+                    if rest.len() >= 2 && rest[..2].iter().all(|c| (b'0'..=b'7').contains(c)) =>
+                {
+                    result.non_ascii_byte = true
+                }
+                _ => {}
+            }
+            // skips escaped char, including `\\`
+            idx += 2;
+        }
+        result
+    }
+}
+
+#[test]
+fn test_string_encoding() {
+    assert_eq!(
+        string_encoding(b"abc", false, "UTF-8"),
+        LiteralEncoding::new("UTF-8", b"abc")
+    );
+    assert_eq!(string_encoding(b"abc", false, "US-ASCII").name, "US-ASCII");
+    assert_eq!(
+        string_encoding(b"\xFF", false, "US-ASCII").name,
+        "ASCII-8BIT"
+    );
+    assert_eq!(
+        string_encoding("é".as_bytes(), true, "KOI8-R").name,
+        "UTF-8"
+    );
+
+    let invalid = string_encoding(b"\xFF", false, "UTF-8");
+    assert_eq!(invalid.name, "UTF-8");
+    assert!(!invalid.valid);
+    assert!(string_encoding(b"\xFF", false, "ASCII-8BIT").valid);
+}
+
+#[test]
+fn test_regexp_escapes() {
+    let escapes = RegexpEscapes::scan(b"a\\u00e9");
+    assert!(escapes.unicode);
+    assert!(!escapes.non_ascii_byte);
+
+    assert!(RegexpEscapes::scan(b"\\xa8").non_ascii_byte);
+    assert!(RegexpEscapes::scan(b"\\377").non_ascii_byte);
+    assert!(!RegexpEscapes::scan(b"\\x7F").non_ascii_byte);
+    assert!(!RegexpEscapes::scan(b"\\\\u").unicode);
+}
@@ -3,13 +3,23 @@ mod comment;
 mod decoded_input;
 pub(crate) mod decoder;
 mod input;
+mod literal_encoding;
 mod magic_comment;
+mod offset_map;
+mod source_bytes;
 mod source_line;
+mod string_content_map;
 
 pub use comment::{Comment, CommentType};
 pub use decoded_input::DecodedInput;
 pub(crate) use decoder::decode_input;
 pub use decoder::{Decoder, DecoderResult, InputError};
 pub use input::Input;
+pub(crate) use literal_encoding::literal_encoding;
+pub use literal_encoding::LiteralEncoding;
 pub use magic_comment::{MagicComment, MagicCommentKind};
+pub use offset_map::{OffsetMap, ReencodedRange};
+pub use source_bytes::SourceBytes;
 pub use source_line::SourceLine;
+pub(crate) use string_content_map::{find_content_map, ContentMapBuilder, StringContentMaps};
+pub use string_content_map::{ContentRange, StringContentMap};
@@ -0,0 +1,343 @@
+use crate::Loc;
+
+/// A single run of bytes that has been re-encoded by a `Decoder`,
+/// i.e. a range that has different contents (and potentially different length)
+/// in the original and in the decoded input.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReencodedRange {
+    /// Range of bytes in the original input
+    pub original: Loc,
+
+    /// Range of bytes in the decoded input
+    pub decoded: Loc,
+}
+
+/// Mapping between byte offsets in the decoded input
+/// (the one that all `Loc`s point to) and the original input
+/// that has been given to the `Parser`.
+///
+/// Is empty if input has not been re-encoded
+/// (i.e. if it is `UTF-8` or `ASCII-8BIT/BINARY`).
+///
+/// Bytes outside of `ranges` are mapped one-to-one (with a shift).
+/// Bytes inside of a re-encoded range can't be mapped precisely,
+/// and so any offset inside of it is mapped to its boundaries.
+#[repr(C)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OffsetMap {
+    /// Sorted list of re-encoded ranges
+    pub ranges: Vec<ReencodedRange>,
+}
+
+impl OffsetMap {
+    /// Builds a mapping between `original` and `decoded` bytes
+    /// after decoding, a fallback for decoders constructed with `Decoder::new`
+    /// that don't record offsets (`Decoder::with_offsets` records them instead).
+    ///
+    /// Relies on the fact that Ruby supports only ASCII-compatible source encodings,
+    /// so ASCII bytes (and in particular line breaks) are kept as is by decoders.
+    /// Lines that can't be aligned are treated as a single re-encoded range.
+    pub(crate) fn compute(original: &[u8], decoded: &[u8]) -> Self {
+        let mut ranges = vec![];
+
+        if original == decoded {
+            return Self { ranges };
+        }
+
+        let original_lines = original.split_inclusive(|c| *c == b'\n');
+        let decoded_lines = decoded.split_inclusive(|c| *c == b'\n');
+
+        if original_lines.clone().count() != decoded_lines.clone().count() {
+            // decoder has changed the structure of the input, can't align anything
+            push_range(&mut ranges, 0, original.len(), 0, decoded.len());
+            return Self { ranges };
+        }
+
+        let mut original_pos = 0;
+        let mut decoded_pos = 0;
+        for (original_line, decoded_line) in original_lines.zip(decoded_lines) {
+            let mut line_ranges = vec![];
+            if align_line(original_line, decoded_line, &mut line_ranges) {
+                for (o_begin, o_end, d_begin, d_end) in line_ranges {
+                    push_range(
+                        &mut ranges,
+                        original_pos + o_begin,
+                        original_pos + o_end,
+                        decoded_pos + d_begin,
+                        decoded_pos + d_end,
+                    );
+                }
+            } else {
+                push_range(
+                    &mut ranges,
+                    original_pos,
+                    original_pos + original_line.len(),
+                    decoded_pos,
+                    decoded_pos + decoded_line.len(),
+                );
+            }
+            original_pos += original_line.len();
+            decoded_pos += decoded_line.len();
+        }
+
+        Self { ranges }
+    }
+
+    /// Records that `original` bytes of the input have been decoded into `decoded` bytes.
+    ///
+    /// Is called by decoders constructed with `Decoder::with_offsets`,
+    /// ranges must be recorded in order.
+    pub fn record(&mut self, original: Loc, decoded: Loc) {
+        if original.is_empty() && decoded.is_empty() {
+            return;
+        }
+        self.ranges.push(ReencodedRange { original, decoded })
+    }
+
+    // Checks that recorded ranges are sorted and that bytes
+    // between them (which are copied as is) have the same length
+    pub(crate) fn is_valid(&self, original_len: usize, decoded_len: usize) -> bool {
+        let mut original_pos = 0;
+        let mut decoded_pos = 0;
+        for ReencodedRange { original, decoded } in &self.ranges {
+            if original.begin < original_pos
+                || decoded.begin < decoded_pos
+                || original.end < original.begin
+                || decoded.end < decoded.begin
+                || original.begin - original_pos != decoded.begin - decoded_pos
+            {
+                return false;
+            }
+            original_pos = original.end;
+            decoded_pos = decoded.end;
+        }
+        original_pos <= original_len
+            && decoded_pos <= decoded_len
+            && original_len - original_pos == decoded_len - decoded_pos
+    }
+
+    /// Returns `true` if decoded input is byte-to-byte equal to the original input
+    pub fn is_identity(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Maps `begin` of some decoded range to the original input.
+    ///
+    /// If `pos` points inside of a re-encoded range returns the beginning of this range.
+    pub fn original_begin(&self, pos: usize) -> usize {
+        match self.lookup(pos) {
+            Lookup::Inside(range) => range.original.begin,
+            Lookup::After(range) => range.original.end + (pos - range.decoded.end),
+            Lookup::BeforeAll => pos,
+        }
+    }
+
+    /// Maps `end` of some decoded range to the original input.
+    ///
+    /// If `pos` points inside of a re-encoded range returns the end of this range.
+    pub fn original_end(&self, pos: usize) -> usize {
+        match self.lookup(pos) {
+            Lookup::Inside(range) if pos == range.decoded.begin => range.original.begin,
+            Lookup::Inside(range) => range.original.end,
+            Lookup::After(range) => range.original.end + (pos - range.decoded.end),
+            Lookup::BeforeAll => pos,
+        }
+    }
+
+    /// Maps given decoded `Loc` to the original input.
+    ///
+    /// Returned range covers all original bytes that produced decoded bytes in `loc`.
+    pub fn original_loc(&self, loc: Loc) -> Loc {
+        let begin = self.original_begin(loc.begin);
+        let end = self.original_end(loc.end).max(begin);
+        Loc { begin, end }
+    }
+
+    fn lookup(&self, pos: usize) -> Lookup<'_> {
+        // index of the first range that starts after `pos`
+        let idx = self
+            .ranges
+            .partition_point(|range| range.decoded.begin <= pos);
+        if idx == 0 {
+            return Lookup::BeforeAll;
+        }
+        let range = &self.ranges[idx - 1];
+        if pos < range.decoded.end {
+            Lookup::Inside(range)
+        } else {
+            Lookup::After(range)
+        }
+    }
+}
+
+enum Lookup<'a> {
+    BeforeAll,
+    Inside(&'a ReencodedRange),
+    After(&'a ReencodedRange),
+}
+
+fn push_range(
+    ranges: &mut Vec<ReencodedRange>,
+    original_begin: usize,
+    original_end: usize,
+    decoded_begin: usize,
+    decoded_end: usize,
+) {
+    if let Some(last) = ranges.last_mut() {
+        if last.original.end == original_begin && last.decoded.end == decoded_begin {
+            // adjacent ranges, merge them
+            last.original.end = original_end;
+            last.decoded.end = decoded_end;
+            return;
+        }
+    }
+    ranges.push(ReencodedRange {
+        original: Loc {
+            begin: original_begin,
+            end: original_end,
+        },
+        decoded: Loc {
+            begin: decoded_begin,
+            end: decoded_end,
+        },
+    })
+}
+
+// Aligns a single line by matching ASCII bytes of both inputs,
+// every non-ASCII UTF-8 character of the decoded line consumes
+// a non-ASCII byte of the original line (and all following bytes
+// that don't match the next decoded byte).
+//
+// Returns `false` if the line can't be aligned.
+fn align_line(
+    original: &[u8],
+    decoded: &[u8],
+    ranges: &mut Vec<(usize, usize, usize, usize)>,
+) -> bool {
+    let mut o = 0;
+    let mut d = 0;
+
+    while d < decoded.len() {
+        let byte = decoded[d];
+
+        if byte.is_ascii() {
+            if original.get(o) != Some(&byte) {
+                return false;
+            }
+            o += 1;
+            d += 1;
+            continue;
+        }
+
+        // non-ASCII character in decoded input
+        let char_len = utf8_char_len(byte);
+        let d_begin = d;
+        d = (d + char_len).min(decoded.len());
+
+        let o_begin = o;
+        match original.get(o) {
+            Some(c) if !c.is_ascii() => o += 1,
+            _ => return false,
+        }
+        let next_decoded = decoded.get(d);
+        while o < original.len() && Some(&original[o]) != next_decoded {
+            if next_decoded.map(|c| !c.is_ascii()).unwrap_or(false) && !original[o].is_ascii() {
+                // next char is also a multibyte char, we can't tell where the
+                // current one ends, so it will be merged with the next one
+                break;
+            }
+            o += 1;
+        }
+
+        ranges.push((o_begin, o, d_begin, d));
+    }
+
+    o == original.len()
+}
+
+fn utf8_char_len(lead: u8) -> usize {
+    match lead {
+        0xF0..=0xF7 => 4,
+        0xE0..=0xEF => 3,
+        0xC0..=0xDF => 2,
+        _ => 1,
+    }
+}
+
+#[test]
+fn test_identity() {
+    let map = OffsetMap::compute(b"foo = 1\nbar", b"foo = 1\nbar");
+    assert!(map.is_identity());
+    assert_eq!(
+        map.original_loc(Loc { begin: 2, end: 9 }),
+        Loc { begin: 2, end: 9 }
+    );
+}
+
+#[test]
+fn test_single_byte_encoding() {
+    // "\xE9t\xE9" in Latin-1 is "été" in UTF-8
+    let original = b"a = '\xE9t\xE9'\nb";
+    let decoded = "a = 'été'\nb".as_bytes();
+    let map = OffsetMap::compute(original, decoded);
+
+    assert_eq!(
+        map.ranges,
+        vec![
+            ReencodedRange {
+                original: Loc { begin: 5, end: 6 },
+                decoded: Loc { begin: 5, end: 7 },
+            },
+            ReencodedRange {
+                original: Loc { begin: 7, end: 8 },
+                decoded: Loc { begin: 8, end: 10 },
+            },
+        ]
+    );
+
+    // 'été'
+    assert_eq!(
+        map.original_loc(Loc { begin: 4, end: 11 }),
+        Loc { begin: 4, end: 9 }
+    );
+    // t
+    assert_eq!(
+        map.original_loc(Loc { begin: 7, end: 8 }),
+        Loc { begin: 6, end: 7 }
+    );
+    // b
+    assert_eq!(
+        map.original_loc(Loc { begin: 12, end: 13 }),
+        Loc { begin: 10, end: 11 }
+    );
+}
+
+#[test]
+fn test_multibyte_encoding() {
+    // "表" in Shift_JIS is 0x95 0x5C, the trailing byte is an ASCII backslash
+    let original = b"s = \"\x95\x5C\"";
+    let decoded = "s = \"表\"".as_bytes();
+    let map = OffsetMap::compute(original, decoded);
+
+    assert_eq!(
+        map.ranges,
+        vec![ReencodedRange {
+            original: Loc { begin: 5, end: 7 },
+            decoded: Loc { begin: 5, end: 8 },
+        }]
+    );
+    assert_eq!(
+        map.original_loc(Loc { begin: 4, end: 9 }),
+        Loc { begin: 4, end: 8 }
+    );
+}
+
+#[test]
+fn test_unaligned_input() {
+    let map = OffsetMap::compute(b"foo", b"# encoding: us-ascii\ndecoded");
+    assert_eq!(
+        map.original_loc(Loc { begin: 21, end: 28 }),
+        Loc { begin: 0, end: 3 }
+    );
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+/// Bytes of the source code.
+///
+/// Can be either owned by the parser or shared with the caller,
+/// in the latter case input is not copied during parsing
+/// and `ParserResult::input` refers to the same memory.
+/// Shared input is copied only if it has to be re-encoded
+/// (i.e. if it has a magic `encoding:` comment that is not UTF-8).
+#[derive(Clone)]
+pub enum SourceBytes {
+    /// Owned vector of bytes
+    Owned(Vec<u8>),
+
+    /// Reference-counted slice of bytes
+    Shared(Arc<[u8]>),
+
+    /// Static slice of bytes (like a string literal or a leaked buffer)
+    Static(&'static [u8]),
+
+    /// Any reference-counted storage of bytes (like a memory-mapped file)
+    External(Arc<dyn AsRef<[u8]> + Send + Sync>),
+}
+
+impl SourceBytes {
+    /// Returns a slice of bytes
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            Self::Owned(bytes) => bytes.as_slice(),
+            Self::Shared(bytes) => bytes,
+            Self::Static(bytes) => bytes,
+            Self::External(bytes) => bytes.as_ref().as_ref(),
+        }
+    }
+
+    /// Returns `true` if bytes are not owned
+    pub fn is_shared(&self) -> bool {
+        !matches!(self, Self::Owned(_))
+    }
+
+    /// Converts itself into owned vector of bytes,
+    /// copies bytes only if they are shared
+    pub fn into_vec(self) -> Vec<u8> {
+        match self {
+            Self::Owned(bytes) => bytes,
+            other => other.as_slice().to_vec(),
+        }
+    }
+}
+
+impl Default for SourceBytes {
+    fn default() -> Self {
+        Self::Owned(vec![])
+    }
+}
+
+impl std::fmt::Debug for SourceBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            Self::Owned(_) => "Owned",
+            Self::Shared(_) => "Shared",
+            Self::Static(_) => "Static",
+            Self::External(_) => "External",
+        };
+        f.debug_tuple(kind).field(&self.as_slice()).finish()
+    }
+}
+
+impl PartialEq for SourceBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for SourceBytes {}
+
+impl std::ops::Deref for SourceBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for SourceBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl From<Vec<u8>> for SourceBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::Owned(bytes)
+    }
+}
+
+impl From<String> for SourceBytes {
+    fn from(s: String) -> Self {
+        Self::Owned(s.into_bytes())
+    }
+}
+
+impl From<Box<[u8]>> for SourceBytes {
+    fn from(bytes: Box<[u8]>) -> Self {
+        Self::Owned(bytes.into_vec())
+    }
+}
+
+impl From<Arc<[u8]>> for SourceBytes {
+    fn from(bytes: Arc<[u8]>) -> Self {
+        Self::Shared(bytes)
+    }
+}
+
+impl From<&Arc<[u8]>> for SourceBytes {
+    fn from(bytes: &Arc<[u8]>) -> Self {
+        Self::Shared(Arc::clone(bytes))
+    }
+}
+
+/// Copies given bytes, use `SourceBytes::Static` or `SourceBytes::Shared` to avoid copying
+impl From<&[u8]> for SourceBytes {
+    fn from(bytes: &[u8]) -> Self {
+        Self::Owned(bytes.to_vec())
+    }
+}
+
+/// Copies given bytes, use `SourceBytes::Static` to avoid copying
+impl<const N: usize> From<&[u8; N]> for SourceBytes {
+    fn from(bytes: &[u8; N]) -> Self {
+        Self::Owned(bytes.to_vec())
+    }
+}
+
+/// Copies given string, use `SourceBytes::Static` to avoid copying
+impl From<&str> for SourceBytes {
+    fn from(s: &str) -> Self {
+        Self::Owned(s.as_bytes().to_vec())
+    }
+}
+
+/// Copies given string
+impl From<&String> for SourceBytes {
+    fn from(s: &String) -> Self {
+        Self::Owned(s.as_bytes().to_vec())
+    }
+}
+
+#[test]
+fn test_source_bytes_shared() {
+    let bytes: Arc<[u8]> = Arc::from(&b"foo"[..]);
+    let source = SourceBytes::from(&bytes);
+    assert!(source.is_shared());
+    assert_eq!(Arc::strong_count(&bytes), 2);
+    assert!(std::ptr::eq(source.as_slice(), &*bytes));
+    assert_eq!(source.into_vec(), b"foo".to_vec());
+
+    let source = SourceBytes::External(Arc::new(String::from("bar")));
+    assert_eq!(&*source, b"bar");
+    assert_eq!(source, SourceBytes::from("bar"));
+    assert!(!SourceBytes::from("bar").is_shared());
+}
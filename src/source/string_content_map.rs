@@ -0,0 +1,284 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::nodes::Str;
+use crate::Loc;
+
+/// A single run of bytes of the string literal content
+/// and the source bytes that produced it.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    /// Range of bytes in the content (i.e. in `Str::value`)
+    pub content: Loc,
+
+    /// Range of bytes in the source
+    pub source: Loc,
+}
+
+impl ContentRange {
+    // `true` if every content byte is a copy of the source byte (i.e. it's not an escape sequence)
+    fn is_verbatim(&self) -> bool {
+        self.content.size() == self.source.size()
+    }
+}
+
+/// Mapping between bytes of the string literal content
+/// (i.e. value of the `Str` node) and the source.
+///
+/// Escape sequences (like `\u{1F600}` or `\x41`), line continuations,
+/// `\r\n` line endings and dedented whitespace of squiggly heredocs
+/// make them different, so content offsets can't be simply shifted
+/// to get a source location.
+#[repr(C)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StringContentMap {
+    /// Location of the whole content in the source,
+    /// i.e. `expression_l` of the `Str` without quotes
+    pub content_l: Loc,
+
+    /// Sorted list of ranges, covers all bytes of the content
+    pub ranges: Vec<ContentRange>,
+}
+
+impl StringContentMap {
+    /// Maps offset of the content byte to the source.
+    ///
+    /// If the byte has been produced by an escape sequence
+    /// returns the beginning of this escape sequence.
+    pub fn source_offset(&self, pos: usize) -> Option<usize> {
+        let range = self.range_at(pos)?;
+        if range.is_verbatim() {
+            Some(range.source.begin + (pos - range.content.begin))
+        } else {
+            Some(range.source.begin)
+        }
+    }
+
+    /// Maps given range of the content to the source.
+    ///
+    /// Returned range covers all source bytes that produced content bytes in `content`,
+    /// i.e. if it includes a part of the escape sequence the whole escape sequence is included.
+    pub fn source_loc(&self, content: Loc) -> Option<Loc> {
+        let begin = self.source_offset(content.begin)?;
+        if content.end <= content.begin {
+            return Some(Loc { begin, end: begin });
+        }
+        let last = self.range_at(content.end - 1)?;
+        let end = if last.is_verbatim() {
+            last.source.begin + (content.end - last.content.begin)
+        } else {
+            last.source.end
+        };
+        Some(Loc { begin, end })
+    }
+
+    fn range_at(&self, pos: usize) -> Option<&ContentRange> {
+        let idx = self
+            .ranges
+            .partition_point(|range| range.content.end <= pos);
+        self.ranges
+            .get(idx)
+            .filter(|range| range.content.begin <= pos)
+    }
+
+    // Drops first `count` bytes of the content (that's what `heredoc_dedent` does)
+    pub(crate) fn remove_prefix(&mut self, count: usize) {
+        let mut ranges = vec![];
+        for range in self.ranges.drain(..) {
+            if range.content.end <= count {
+                continue;
+            }
+            let mut range = range;
+            if range.content.begin < count {
+                // dedent removes only whitespaces that are never escaped
+                let skip = count - range.content.begin;
+                range.content.begin = count;
+                range.source.begin += skip;
+            }
+            range.content.begin -= count;
+            range.content.end -= count;
+            ranges.push(range);
+        }
+        self.ranges = ranges;
+    }
+}
+
+/// Builder of the `StringContentMap` that lives in the `TokenBuf`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ContentMapBuilder {
+    ranges: Vec<ContentRange>,
+    pending: Option<(usize, usize)>,
+}
+
+impl ContentMapBuilder {
+    // Marks that all bytes that will be added starting from `content_begin`
+    // come from the source starting at `source_begin`
+    pub(crate) fn begin(&mut self, content_begin: usize, source_begin: usize) {
+        self.pending = Some((content_begin, source_begin));
+    }
+
+    // Closes a range that has been started by `begin`
+    pub(crate) fn end(&mut self, content_end: usize, source_end: usize) {
+        if let Some((content_begin, source_begin)) = self.pending.take() {
+            self.push(
+                Loc {
+                    begin: content_begin,
+                    end: content_end,
+                },
+                Loc {
+                    begin: source_begin,
+                    end: source_end,
+                },
+            );
+        }
+    }
+
+    pub(crate) fn push(&mut self, content: Loc, source: Loc) {
+        if content.end <= content.begin {
+            return;
+        }
+        let range = ContentRange { content, source };
+        if let Some(last) = self.ranges.last_mut() {
+            if last.is_verbatim()
+                && range.is_verbatim()
+                && last.content.end == content.begin
+                && last.source.end == source.begin
+            {
+                last.content.end = content.end;
+                last.source.end = source.end;
+                return;
+            }
+        }
+        self.ranges.push(range);
+    }
+
+    pub(crate) fn shift(&mut self, by: usize) {
+        for range in self.ranges.iter_mut() {
+            range.content.begin += by;
+            range.content.end += by;
+        }
+        self.pending = None;
+    }
+
+    pub(crate) fn build(&self, content_l: Loc) -> StringContentMap {
+        StringContentMap {
+            content_l,
+            ranges: self.ranges.clone(),
+        }
+    }
+}
+
+/// List of content maps of all string literals,
+/// shared between `Lexer` (that records them) and `Builder` (that dedents heredocs)
+#[derive(Debug, Default, Clone)]
+pub(crate) struct StringContentMaps {
+    list: Rc<RefCell<Vec<StringContentMap>>>,
+}
+
+impl StringContentMaps {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn push(&self, map: StringContentMap) {
+        self.list.borrow_mut().push(map)
+    }
+
+    pub(crate) fn remove_prefix(&self, content_l: &Loc, count: usize) {
+        if let Some(map) = self
+            .list
+            .borrow_mut()
+            .iter_mut()
+            .rev()
+            .find(|map| map.content_l == *content_l)
+        {
+            map.remove_prefix(count)
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        self.list.borrow_mut().clear()
+    }
+
+    pub(crate) fn take_inner(self) -> Vec<StringContentMap> {
+        let mut list = self.list.replace(vec![]);
+        // heredoc bodies are lexed after the rest of the line
+        list.sort_by_key(|map| map.content_l.begin);
+        list
+    }
+}
+
+/// Returns location of the content of given `Str` node (i.e. without quotes)
+pub(crate) fn str_content_l(node: &Str) -> Loc {
+    match (&node.begin_l, &node.end_l) {
+        (Some(begin_l), Some(end_l)) => Loc {
+            begin: begin_l.end,
+            end: end_l.begin,
+        },
+        _ => node.expression_l,
+    }
+}
+
+/// Finds content map of given `Str` node in a sorted list of maps
+pub(crate) fn find_content_map<'a>(
+    maps: &'a [StringContentMap],
+    node: &Str,
+) -> Option<&'a StringContentMap> {
+    content_map_at(maps, &str_content_l(node))
+}
+
+/// Finds content map with given location of the content in a sorted list of maps
+pub(crate) fn content_map_at<'a>(
+    maps: &'a [StringContentMap],
+    content_l: &Loc,
+) -> Option<&'a StringContentMap> {
+    let idx = maps.partition_point(|map| map.content_l.begin < content_l.begin);
+    maps.get(idx).filter(|map| map.content_l == *content_l)
+}
+
+#[test]
+fn test_source_loc() {
+    // "a\x41bc"
+    let mut builder = ContentMapBuilder::default();
+    builder.push(Loc { begin: 0, end: 1 }, Loc { begin: 1, end: 2 });
+    builder.push(Loc { begin: 1, end: 2 }, Loc { begin: 2, end: 6 });
+    builder.push(Loc { begin: 2, end: 3 }, Loc { begin: 6, end: 7 });
+    builder.push(Loc { begin: 3, end: 4 }, Loc { begin: 7, end: 8 });
+    let map = builder.build(Loc { begin: 1, end: 8 });
+
+    assert_eq!(map.ranges.len(), 3);
+    assert_eq!(map.source_offset(0), Some(1));
+    assert_eq!(map.source_offset(1), Some(2));
+    assert_eq!(map.source_offset(3), Some(7));
+    assert_eq!(map.source_offset(4), None);
+    assert_eq!(
+        map.source_loc(Loc { begin: 1, end: 2 }),
+        Some(Loc { begin: 2, end: 6 })
+    );
+    assert_eq!(
+        map.source_loc(Loc { begin: 2, end: 4 }),
+        Some(Loc { begin: 6, end: 8 })
+    );
+    assert_eq!(
+        map.source_loc(Loc { begin: 0, end: 4 }),
+        Some(Loc { begin: 1, end: 8 })
+    );
+}
+
+#[test]
+fn test_remove_prefix() {
+    // "    abc\n" with 2 bytes dedented
+    let mut builder = ContentMapBuilder::default();
+    builder.push(Loc { begin: 0, end: 8 }, Loc { begin: 10, end: 18 });
+    let mut map = builder.build(Loc { begin: 10, end: 18 });
+    map.remove_prefix(2);
+
+    assert_eq!(
+        map.ranges,
+        vec![ContentRange {
+            content: Loc { begin: 0, end: 6 },
+            source: Loc { begin: 12, end: 18 }
+        }]
+    );
+}
@@ -2,6 +2,8 @@ use std::cell::RefCell;
 use std::collections::BTreeSet;
 use std::rc::Rc;
 
+use crate::Loc;
+
 /// Stack of local variables in nested scopes
 ///
 /// Each scope represents a Ruby scope:
@@ -25,6 +27,25 @@ use std::rc::Rc;
 pub struct StaticEnvironment {
     variables: Rc<RefCell<BTreeSet<String>>>,
     stack: Rc<RefCell<Vec<BTreeSet<String>>>>,
+    locals: Rc<RefCell<Vec<LocalsFrame>>>,
+    unused: Rc<RefCell<Vec<(String, Loc)>>>,
+}
+
+// Variables that are declared in a single scope
+// (unlike `StaticEnvironment::variables` it doesn't include inherited variables)
+#[derive(Debug, Clone, Default)]
+struct LocalsFrame {
+    dynamic: bool,
+    variables: Vec<Local>,
+}
+
+#[derive(Debug, Clone)]
+struct Local {
+    name: String,
+    // location of the first assignment, `None` for arguments
+    // (and other variables that are never reported as unused)
+    assigned_at: Option<Loc>,
+    used: bool,
 }
 
 const FORWARD_ARGS: &str = "FORWARD_ARGS";
@@ -36,6 +57,8 @@ impl StaticEnvironment {
         Self {
             variables: Rc::new(RefCell::new(BTreeSet::new())),
             stack: Rc::new(RefCell::new(vec![])),
+            locals: Rc::new(RefCell::new(vec![LocalsFrame::default()])),
+            unused: Rc::new(RefCell::new(vec![])),
         }
     }
 
@@ -43,12 +66,24 @@ impl StaticEnvironment {
         self.stack.borrow().is_empty()
     }
 
+    // Removes all variables and scopes (but keeps allocated memory),
+    // so the environment is the same as a new one
+    pub(crate) fn clear(&self) {
+        self.variables.borrow_mut().clear();
+        self.stack.borrow_mut().clear();
+        let mut locals = self.locals.borrow_mut();
+        locals.clear();
+        locals.push(LocalsFrame::default());
+        self.unused.borrow_mut().clear();
+    }
+
     /// Performs a push, doesn't inherit previously declared variables in the new scope
     ///
     /// Handles class/module scopes
     pub fn extend_static(&self) {
         let variables = std::mem::take(&mut *self.variables.borrow_mut());
         self.stack.borrow_mut().push(variables);
+        self.push_locals(false);
     }
 
     /// Performs a push, inherits previously declared variables in the new scope
@@ -58,6 +93,7 @@ impl StaticEnvironment {
         self.stack
             .borrow_mut()
             .push(self.variables.borrow().clone());
+        self.push_locals(true);
     }
 
     /// Performs pop
@@ -67,11 +103,95 @@ impl StaticEnvironment {
             .borrow_mut()
             .pop()
             .expect("expected static_env to have at least one frame");
+        self.pop_locals();
     }
 
     /// Declares a new variable in the current scope
     pub fn declare(&self, name: &str) {
         self.variables.borrow_mut().insert(name.to_string());
+        self.push_local(name, None);
+    }
+
+    // Declares a variable that is assigned at `loc` (unless it's already declared),
+    // unlike arguments such variables are reported if they are never used
+    pub(crate) fn declare_assigned(&self, name: &str, loc: Loc) {
+        if self.is_declared(name) {
+            return;
+        }
+        self.variables.borrow_mut().insert(name.to_string());
+        self.push_local(name, Some(loc));
+    }
+
+    // Marks a variable visible in the current scope as used (i.e. read)
+    pub(crate) fn mark_as_used(&self, name: &str) {
+        for frame in self.locals.borrow_mut().iter_mut().rev() {
+            if let Some(local) = frame
+                .variables
+                .iter_mut()
+                .rev()
+                .find(|local| local.name == name)
+            {
+                local.used = true;
+                return;
+            }
+            if !frame.dynamic {
+                return;
+            }
+        }
+    }
+
+    // Pops the top level scope (that is never popped by `unextend`),
+    // all its unused variables become available via `take_unused_variables`
+    pub(crate) fn unextend_top_level(&self) {
+        while !self.locals.borrow().is_empty() {
+            self.pop_locals();
+        }
+    }
+
+    // Returns name and location of the first assignment of every variable
+    // that has been assigned but never used in scopes popped so far
+    pub(crate) fn take_unused_variables(&self) -> Vec<(String, Loc)> {
+        std::mem::take(&mut *self.unused.borrow_mut())
+    }
+
+    fn push_locals(&self, dynamic: bool) {
+        self.locals.borrow_mut().push(LocalsFrame {
+            dynamic,
+            variables: vec![],
+        });
+    }
+
+    fn pop_locals(&self) {
+        let frame = match self.locals.borrow_mut().pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+        for local in frame.variables {
+            match local.assigned_at {
+                // like MRI, variables starting with `_` are considered "private"
+                Some(loc) if !local.used && !local.name.starts_with('_') => {
+                    self.unused.borrow_mut().push((local.name, loc))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn push_local(&self, name: &str, assigned_at: Option<Loc>) {
+        let mut locals = self.locals.borrow_mut();
+        if locals.is_empty() {
+            locals.push(LocalsFrame::default());
+        }
+        if let Some(frame) = locals.last_mut() {
+            if frame.variables.iter().any(|local| local.name == name) {
+                return;
+            }
+            frame.variables.push(Local {
+                name: name.to_string(),
+                assigned_at,
+                used: false,
+            });
+        }
     }
 
     /// Returns `true` if variable with a given `name` is declared in the current scope
@@ -141,3 +261,41 @@ fn test_unextend() {
     assert!(env.is_declared("foo"));
     assert!(!env.is_declared("bar"));
 }
+
+#[test]
+fn test_unused_variables() {
+    let env = StaticEnvironment::new();
+    let loc = |begin| Loc {
+        begin,
+        end: begin + 1,
+    };
+
+    env.declare_assigned("a", loc(0));
+    env.declare_assigned("b", loc(1));
+    env.declare_assigned("_c", loc(2));
+
+    env.extend_dynamic();
+    // block argument shadows outer `a`
+    env.declare("a");
+    env.mark_as_used("a");
+    env.mark_as_used("b");
+    // `b` is not re-declared
+    env.declare_assigned("b", loc(3));
+    env.declare_assigned("d", loc(4));
+    env.unextend();
+    assert_eq!(
+        env.take_unused_variables(),
+        vec![(String::from("d"), loc(4))]
+    );
+
+    env.extend_static();
+    // outer variables are not visible in `def`
+    env.mark_as_used("a");
+    env.unextend();
+
+    env.unextend_top_level();
+    assert_eq!(
+        env.take_unused_variables(),
+        vec![(String::from("a"), loc(0))]
+    );
+}
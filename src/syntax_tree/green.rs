@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use super::SyntaxKind;
+use crate::parser::token_name;
+
+/// Immutable node of the syntax tree,
+/// doesn't know its position and its parent (see `SyntaxNode`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GreenNode {
+    kind: SyntaxKind,
+    ast_index: Option<usize>,
+    text_len: usize,
+    children: Vec<GreenElement>,
+}
+
+impl GreenNode {
+    pub(crate) fn new(
+        kind: SyntaxKind,
+        ast_index: Option<usize>,
+        children: Vec<GreenElement>,
+    ) -> Self {
+        let text_len = children.iter().map(|child| child.text_len()).sum();
+        Self {
+            kind,
+            ast_index,
+            text_len,
+            children,
+        }
+    }
+
+    /// Kind of the node
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    /// Index of the matching AST node in `SyntaxTree::ast_nodes`,
+    /// `None` for the root node and nodes that don't have AST nodes (like `ArgumentList`)
+    pub fn ast_index(&self) -> Option<usize> {
+        self.ast_index
+    }
+
+    /// Length of the source code of the node (in bytes)
+    pub fn text_len(&self) -> usize {
+        self.text_len
+    }
+
+    /// Child nodes and tokens
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+}
+
+/// Immutable token of the syntax tree (including trivia tokens)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GreenToken {
+    token_type: i32,
+    text: Vec<u8>,
+}
+
+impl GreenToken {
+    pub(crate) fn new(token_type: i32, text: Vec<u8>) -> Self {
+        Self { token_type, text }
+    }
+
+    /// Type of the token, same as `Token::token_type`
+    pub fn token_type(&self) -> i32 {
+        self.token_type
+    }
+
+    /// Name of the token, same as `Token::token_name`
+    pub fn token_name(&self) -> &'static str {
+        token_name(self.token_type)
+    }
+
+    /// Source code of the token
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+}
+
+/// Child of the `GreenNode`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GreenElement {
+    /// Child node
+    Node(Arc<GreenNode>),
+
+    /// Child token
+    Token(Arc<GreenToken>),
+}
+
+impl GreenElement {
+    /// Length of the source code of the element (in bytes)
+    pub fn text_len(&self) -> usize {
+        match self {
+            Self::Node(node) => node.text_len(),
+            Self::Token(token) => token.text().len(),
+        }
+    }
+}
+
+#[cfg(test)]
+use crate::Lexer;
+
+#[test]
+fn test_green_node() {
+    let token = |token_type, text: &[u8]| {
+        GreenElement::Token(Arc::new(GreenToken::new(token_type, text.to_vec())))
+    };
+    // foo(1)
+    let args = GreenNode::new(
+        SyntaxKind::ArgumentList,
+        None,
+        vec![
+            token(Lexer::tLPAREN2, b"("),
+            token(Lexer::tINTEGER, b"1"),
+            token(Lexer::tRPAREN, b")"),
+        ],
+    );
+    let send = GreenNode::new(
+        SyntaxKind::Send,
+        Some(0),
+        vec![
+            token(Lexer::tIDENTIFIER, b"foo"),
+            GreenElement::Node(Arc::new(args.clone())),
+        ],
+    );
+
+    assert_eq!(args.text_len(), 3);
+    assert_eq!(args.ast_index(), None);
+    assert_eq!(send.kind(), SyntaxKind::Send);
+    assert_eq!(send.ast_index(), Some(0));
+    assert_eq!(send.text_len(), 6);
+    assert_eq!(send.children().len(), 2);
+    assert_eq!(send.children()[1], GreenElement::Node(Arc::new(args)));
+
+    match &send.children()[0] {
+        GreenElement::Token(token) => {
+            assert_eq!(token.token_type(), Lexer::tIDENTIFIER);
+            assert_eq!(token.token_name(), "tIDENTIFIER");
+            assert_eq!(token.text(), b"foo");
+        }
+        other => panic!("expected token, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_empty_green_node() {
+    let node = GreenNode::new(SyntaxKind::Root, None, vec![]);
+    assert_eq!(node.text_len(), 0);
+    assert_eq!(GreenElement::Node(Arc::new(node)).text_len(), 0);
+}
@@ -0,0 +1,576 @@
+mod green;
+pub use green::{GreenElement, GreenNode, GreenToken};
+
+mod red;
+pub use red::{SyntaxElement, SyntaxNode, SyntaxToken};
+
+mod kind_gen;
+pub use kind_gen::SyntaxKind;
+
+use std::sync::Arc;
+
+use crate::insert_trivia;
+use crate::nodes::{
+    Block, CSend, Case, CaseMatch, If, InPattern, Index, IndexAsgn, Numblock, Rescue, RescueBody,
+    Send, Super, When, Yield,
+};
+use crate::rewrite::for_each_child;
+use crate::source::{Comment, DecodedInput};
+use crate::{Loc, Node, Token};
+
+/// Lossless syntax tree, a view of the AST with all tokens attached.
+///
+/// It's not a concrete syntax tree of grammar rules: it's built after parsing
+/// from locations of AST nodes and tokens. Every node of the tree contains all tokens
+/// (including trivia like whitespaces and comments) within its location,
+/// so the text of the root node is equal to the input.
+///
+/// There's a node for every AST node (with `SyntaxKind` of the AST node)
+/// and a few nodes that are derived from locations of their AST nodes:
+///
+/// + `ArgumentList` for arguments of method calls, `super`, `yield` and indexes
+/// + `Then` and `Else` for branches of `if`/`unless`/`case`/`when`/`in`/`rescue`
+/// + `DoBody` and `BraceBody` for `do ... end` and `{ ... }` parts of blocks
+///
+/// Tokens of other constructions (like `elsif` branches) belong to the closest enclosing node.
+/// Heredoc bodies belong to the innermost node that contains them by position.
+#[derive(Debug)]
+pub struct SyntaxTree<'a> {
+    root: SyntaxNode,
+    ast_nodes: Vec<&'a Node>,
+}
+
+impl<'a> SyntaxTree<'a> {
+    /// Constructs a tree from the AST and the tokens of the same input.
+    ///
+    /// Trivia tokens are computed if `tokens` don't include them.
+    pub fn new(
+        ast: Option<&'a Node>,
+        tokens: &[Token],
+        input: &DecodedInput,
+        comments: &[Comment],
+    ) -> Self {
+        let source = input.as_shared_bytes();
+
+        let mut ast_nodes = vec![];
+        let mut items = vec![];
+        if let Some(ast) = ast {
+            collect_items(ast, source, &mut ast_nodes, &mut items);
+        }
+
+        let mut tokens = tokens.to_vec();
+        insert_trivia(&mut tokens, input, comments);
+
+        let green = build(&items, &tokens, source);
+        Self {
+            root: SyntaxNode::new_root(Arc::new(green)),
+            ast_nodes,
+        }
+    }
+
+    /// Root node of the tree, has kind `SyntaxKind::Root` and covers the whole input
+    pub fn root(&self) -> &SyntaxNode {
+        &self.root
+    }
+
+    /// All AST nodes in pre-order, `GreenNode::ast_index` is an index in this list
+    pub fn ast_nodes(&self) -> &[&'a Node] {
+        &self.ast_nodes
+    }
+
+    /// Returns an AST node that matches given node of the tree,
+    /// `None` for the root node and nodes that don't have AST nodes (like `ArgumentList`)
+    pub fn ast_node(&self, node: &SyntaxNode) -> Option<&'a Node> {
+        node.green()
+            .ast_index()
+            .and_then(|idx| self.ast_nodes.get(idx).copied())
+    }
+}
+
+// Node of the tree that is not constructed yet
+struct Item {
+    kind: SyntaxKind,
+    ast_index: Option<usize>,
+    loc: Loc,
+}
+
+fn collect_items<'a>(
+    node: &'a Node,
+    source: &[u8],
+    nodes: &mut Vec<&'a Node>,
+    items: &mut Vec<Item>,
+) {
+    items.push(Item {
+        kind: SyntaxKind::of_node(node),
+        ast_index: Some(nodes.len()),
+        loc: *node.expression(),
+    });
+    nodes.push(node);
+    collect_clauses(node, source, items);
+    for_each_child(node, &mut |child| {
+        collect_items(child, source, nodes, items)
+    });
+}
+
+fn collect_clauses(node: &Node, source: &[u8], items: &mut Vec<Item>) {
+    let mut push = |kind, begin: usize, end: usize| {
+        if begin < end {
+            items.push(Item {
+                kind,
+                ast_index: None,
+                loc: Loc { begin, end },
+            })
+        }
+    };
+    // end of the clause that starts with given token
+    let end_of = |begin_l: &Loc, body: Option<&Node>| {
+        body.map_or(begin_l.end, |body| body.expression().end.max(begin_l.end))
+    };
+    let is_keyword = |loc: &Loc, keyword: &[u8]| source.get(loc.begin..loc.end) == Some(keyword);
+
+    match node {
+        Node::Send(Send {
+            method_name,
+            args,
+            begin_l,
+            end_l,
+            operator_l: None,
+            ..
+        })
+        | Node::CSend(CSend {
+            method_name,
+            args,
+            begin_l,
+            end_l,
+            operator_l: None,
+            ..
+        }) if is_method_name(method_name) => {
+            if let Some(loc) = arguments_loc(begin_l, end_l, args) {
+                push(SyntaxKind::ArgumentList, loc.begin, loc.end);
+            }
+        }
+        Node::Super(Super {
+            args,
+            begin_l,
+            end_l,
+            ..
+        })
+        | Node::Yield(Yield {
+            args,
+            begin_l,
+            end_l,
+            ..
+        }) => {
+            if let Some(loc) = arguments_loc(begin_l, end_l, args) {
+                push(SyntaxKind::ArgumentList, loc.begin, loc.end);
+            }
+        }
+        Node::Index(Index { begin_l, end_l, .. })
+        | Node::IndexAsgn(IndexAsgn { begin_l, end_l, .. }) => {
+            push(SyntaxKind::ArgumentList, begin_l.begin, end_l.end)
+        }
+        Node::If(If {
+            if_true,
+            if_false,
+            begin_l,
+            else_l,
+            ..
+        }) => {
+            // `unless` has its `then` branch in `if_false`
+            let branches = [if_true.as_deref(), if_false.as_deref()];
+            let then_body = branches.into_iter().flatten().find(|branch| match else_l {
+                Some(else_l) => branch.expression().begin < else_l.begin,
+                None => true,
+            });
+            push(SyntaxKind::Then, begin_l.begin, end_of(begin_l, then_body));
+
+            if let Some(else_l) = else_l.filter(|else_l| is_keyword(else_l, b"else")) {
+                let else_body = branches
+                    .into_iter()
+                    .flatten()
+                    .find(|branch| branch.expression().begin >= else_l.end);
+                push(SyntaxKind::Else, else_l.begin, end_of(&else_l, else_body));
+            }
+        }
+        Node::When(When { body, begin_l, .. })
+        | Node::InPattern(InPattern { body, begin_l, .. }) => push(
+            SyntaxKind::Then,
+            begin_l.begin,
+            end_of(begin_l, body.as_deref()),
+        ),
+        Node::RescueBody(RescueBody {
+            body,
+            begin_l: Some(begin_l),
+            ..
+        }) => push(
+            SyntaxKind::Then,
+            begin_l.begin,
+            end_of(begin_l, body.as_deref()),
+        ),
+        Node::Case(Case {
+            else_body,
+            else_l: Some(else_l),
+            ..
+        })
+        | Node::CaseMatch(CaseMatch {
+            else_body,
+            else_l: Some(else_l),
+            ..
+        })
+        | Node::Rescue(Rescue {
+            else_: else_body,
+            else_l: Some(else_l),
+            ..
+        }) => push(
+            SyntaxKind::Else,
+            else_l.begin,
+            end_of(else_l, else_body.as_deref()),
+        ),
+        Node::Block(Block { begin_l, end_l, .. })
+        | Node::Numblock(Numblock { begin_l, end_l, .. }) => {
+            let kind = if is_keyword(begin_l, b"do") {
+                SyntaxKind::DoBody
+            } else {
+                SyntaxKind::BraceBody
+            };
+            push(kind, begin_l.begin, end_l.end)
+        }
+        _ => {}
+    }
+}
+
+// Operators (like `a + b` or `!a`) and setters (like `a.b = c`) have no argument lists
+fn is_method_name(method_name: &str) -> bool {
+    method_name
+        .chars()
+        .next()
+        .is_some_and(|c| c == '_' || c.is_alphabetic() || !c.is_ascii())
+}
+
+fn arguments_loc(begin_l: &Option<Loc>, end_l: &Option<Loc>, args: &[Node]) -> Option<Loc> {
+    match (begin_l, end_l, args.first(), args.last()) {
+        (Some(begin_l), Some(end_l), _, _) => Some(begin_l.join(end_l)),
+        (_, _, Some(first), Some(last)) => Some(first.expression().join(last.expression())),
+        _ => None,
+    }
+}
+
+struct Frame {
+    kind: SyntaxKind,
+    ast_index: Option<usize>,
+    end: usize,
+    children: Vec<GreenElement>,
+}
+
+struct Builder<'a> {
+    items: &'a [Item],
+    stack: Vec<Frame>,
+}
+
+impl Builder<'_> {
+    fn top(&mut self) -> &mut Frame {
+        self.stack.last_mut().expect("root frame is never closed")
+    }
+
+    fn close_until(&mut self, pos: usize) {
+        while self.stack.len() > 1 && self.top().end <= pos {
+            self.close();
+        }
+    }
+
+    fn close(&mut self) {
+        let frame = self.stack.pop().expect("root frame is never closed");
+        let node = green_node(frame);
+        self.top().children.push(GreenElement::Node(Arc::new(node)));
+    }
+
+    fn open(&mut self, idx: usize) {
+        let item = &self.items[idx];
+        let frame = Frame {
+            kind: item.kind,
+            ast_index: item.ast_index,
+            end: item.loc.end,
+            children: vec![],
+        };
+        self.close_until(item.loc.begin);
+        if item.loc.begin == item.loc.end {
+            let node = green_node(frame);
+            self.top().children.push(GreenElement::Node(Arc::new(node)));
+        } else if item.loc.end <= self.top().end {
+            self.stack.push(frame);
+        }
+        // nodes that partially overlap the current node are skipped
+    }
+}
+
+fn green_node(frame: Frame) -> GreenNode {
+    GreenNode::new(frame.kind, frame.ast_index, frame.children)
+}
+
+fn build(items: &[Item], tokens: &[Token], source: &[u8]) -> GreenNode {
+    // outer nodes go before nested nodes with the same begin,
+    // AST nodes go before their clauses with the same location
+    let mut order = (0..items.len()).collect::<Vec<_>>();
+    order.sort_by_key(|idx| {
+        let loc = items[*idx].loc;
+        (loc.begin, std::cmp::Reverse(loc.end), *idx)
+    });
+    let mut order = order.into_iter().peekable();
+
+    let mut builder = Builder {
+        items,
+        stack: vec![Frame {
+            kind: SyntaxKind::Root,
+            ast_index: None,
+            end: usize::MAX,
+            children: vec![],
+        }],
+    };
+
+    let mut pos = 0;
+    for token in tokens {
+        if token.loc.begin == token.loc.end || token.loc.begin < pos {
+            continue;
+        }
+        let begin = token.loc.begin;
+        let end = token.loc.end.min(source.len());
+
+        while let Some(idx) = order.next_if(|idx| items[*idx].loc.begin <= begin) {
+            builder.open(idx);
+        }
+        builder.close_until(begin);
+
+        let token = GreenToken::new(token.token_type, source[begin..end].to_vec());
+        builder
+            .top()
+            .children
+            .push(GreenElement::Token(Arc::new(token)));
+        pos = end;
+    }
+
+    for idx in order {
+        builder.open(idx);
+    }
+    while builder.stack.len() > 1 {
+        builder.close();
+    }
+    let root = builder.stack.pop().expect("root frame is never closed");
+    green_node(root)
+}
+
+#[cfg(test)]
+use crate::nodes::{Int, Lvar};
+#[cfg(test)]
+use crate::Lexer;
+
+#[cfg(test)]
+fn build_tree<'a>(source: &[u8], ast: &'a Node) -> SyntaxTree<'a> {
+    let pool = alloc_from_pool::Pool::new();
+    let mut lexer = Lexer::new(source, "(test)", None);
+    lexer.tokens_factory = pool.factory();
+    let tokens = lexer.tokenize_until_eof();
+    SyntaxTree::new(
+        Some(ast),
+        &tokens,
+        &lexer.buffer.input.decoded,
+        &lexer.comments,
+    )
+}
+
+#[cfg(test)]
+fn descendants(tree: &SyntaxTree) -> Vec<(SyntaxKind, String)> {
+    tree.root()
+        .descendants()
+        .iter()
+        .map(|node| (node.kind(), String::from_utf8(node.text()).unwrap()))
+        .collect()
+}
+
+#[cfg(test)]
+fn lvar(name: &str, begin: usize) -> Node {
+    Node::Lvar(Lvar {
+        name: String::from(name),
+        expression_l: Loc {
+            begin,
+            end: begin + name.len(),
+        },
+    })
+}
+
+#[test]
+fn test_syntax_tree() {
+    // foo(a, 1) # c
+    let source = b"foo(a, 1) # c\n";
+    let loc = |begin, end| Loc { begin, end };
+    let ast = Node::Send(Send {
+        recv: None,
+        method_name: String::from("foo"),
+        args: vec![
+            Node::Lvar(Lvar {
+                name: String::from("a"),
+                expression_l: loc(4, 5),
+            }),
+            Node::Int(Int {
+                value: String::from("1"),
+                operator_l: None,
+                expression_l: loc(7, 8),
+            }),
+        ],
+        dot_l: None,
+        selector_l: Some(loc(0, 3)),
+        begin_l: Some(loc(3, 4)),
+        end_l: Some(loc(8, 9)),
+        operator_l: None,
+        expression_l: loc(0, 9),
+    });
+
+    let tree = build_tree(source, &ast);
+
+    let root = tree.root();
+    assert_eq!(root.kind(), SyntaxKind::Root);
+    assert_eq!(root.text(), source);
+    assert_eq!(root.loc(), loc(0, source.len()));
+    assert!(tree.ast_node(root).is_none());
+
+    assert_eq!(
+        descendants(&tree),
+        vec![
+            (SyntaxKind::Root, String::from("foo(a, 1) # c\n")),
+            (SyntaxKind::Send, String::from("foo(a, 1)")),
+            (SyntaxKind::ArgumentList, String::from("(a, 1)")),
+            (SyntaxKind::Lvar, String::from("a")),
+            (SyntaxKind::Int, String::from("1")),
+        ]
+    );
+
+    let send = &root.children()[0];
+    assert_eq!(send.text(), b"foo(a, 1)");
+    assert!(std::ptr::eq(tree.ast_node(send).unwrap(), &ast));
+    let tokens = send
+        .tokens()
+        .iter()
+        .map(|token| token.token_name())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tokens,
+        vec![
+            "tIDENTIFIER",
+            "tLPAREN2",
+            "tIDENTIFIER",
+            "tCOMMA",
+            "tWHITESPACE",
+            "tINTEGER",
+            "tRPAREN"
+        ]
+    );
+
+    let args = &send.children()[0];
+    assert!(tree.ast_node(args).is_none());
+    let int = &args.children()[1];
+    assert_eq!(int.loc(), loc(7, 8));
+    assert_eq!(tree.ast_node(int).unwrap().str_type(), "int");
+    assert_eq!(int.ancestors().count(), 3);
+
+    let trivia = root
+        .children_with_tokens()
+        .iter()
+        .filter_map(|child| match child {
+            SyntaxElement::Token(token) => Some((token.token_name(), token.text().to_vec())),
+            SyntaxElement::Node(_) => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        trivia,
+        vec![
+            ("tWHITESPACE", b" ".to_vec()),
+            ("tCOMMENT", b"# c".to_vec()),
+            ("tNL", b"\n".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn test_syntax_tree_blocks() {
+    let loc = |begin, end| Loc { begin, end };
+    // foo do a end
+    // foo { a }
+    let block = |begin_l: Loc, end_l: Loc| {
+        Node::Block(Block {
+            call: Box::new(Node::Send(Send {
+                recv: None,
+                method_name: String::from("foo"),
+                args: vec![],
+                dot_l: None,
+                selector_l: Some(loc(0, 3)),
+                begin_l: None,
+                end_l: None,
+                operator_l: None,
+                expression_l: loc(0, 3),
+            })),
+            args: None,
+            body: Some(Box::new(lvar("a", begin_l.end + 1))),
+            begin_l,
+            end_l,
+            expression_l: loc(0, end_l.end),
+        })
+    };
+
+    let ast = block(loc(4, 6), loc(9, 12));
+    assert_eq!(
+        descendants(&build_tree(b"foo do a end\n", &ast)),
+        vec![
+            (SyntaxKind::Root, String::from("foo do a end\n")),
+            (SyntaxKind::Block, String::from("foo do a end")),
+            (SyntaxKind::Send, String::from("foo")),
+            (SyntaxKind::DoBody, String::from("do a end")),
+            (SyntaxKind::Lvar, String::from("a")),
+        ]
+    );
+
+    let ast = block(loc(4, 5), loc(8, 9));
+    assert_eq!(
+        descendants(&build_tree(b"foo { a }\n", &ast))[3],
+        (SyntaxKind::BraceBody, String::from("{ a }"))
+    );
+}
+
+#[test]
+fn test_syntax_tree_if() {
+    let loc = |begin, end| Loc { begin, end };
+    // branches of `unless` are swapped
+    for (keyword, swap) in [("if", false), ("unless", true)] {
+        let source = format!("{} a then b else c end\n", keyword);
+        let k = keyword.len();
+        let (then_body, else_body) = (lvar("b", k + 8), lvar("c", k + 15));
+        let (if_true, if_false) = if swap {
+            (else_body, then_body)
+        } else {
+            (then_body, else_body)
+        };
+        let ast = Node::If(If {
+            cond: Box::new(lvar("a", k + 1)),
+            if_true: Some(Box::new(if_true)),
+            if_false: Some(Box::new(if_false)),
+            keyword_l: loc(0, k),
+            begin_l: loc(k + 3, k + 7),
+            else_l: Some(loc(k + 10, k + 14)),
+            end_l: Some(loc(k + 17, k + 20)),
+            expression_l: loc(0, k + 20),
+        });
+
+        let nodes = descendants(&build_tree(source.as_bytes(), &ast))
+            .into_iter()
+            .skip(2)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            nodes,
+            vec![
+                (SyntaxKind::Lvar, String::from("a")),
+                (SyntaxKind::Then, String::from("then b")),
+                (SyntaxKind::Lvar, String::from("b")),
+                (SyntaxKind::Else, String::from("else c")),
+                (SyntaxKind::Lvar, String::from("c")),
+            ]
+        );
+    }
+}
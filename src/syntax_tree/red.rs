@@ -0,0 +1,297 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::{GreenElement, GreenNode, GreenToken, SyntaxKind};
+use crate::Loc;
+
+#[derive(Debug)]
+struct NodeData {
+    green: Arc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    offset: usize,
+}
+
+/// Cursor over a `GreenNode` that knows its position and its parent.
+///
+/// Cursors are cheap to clone and are created on demand during traversal.
+#[derive(Debug, Clone)]
+pub struct SyntaxNode {
+    data: Rc<NodeData>,
+}
+
+impl SyntaxNode {
+    pub(crate) fn new_root(green: Arc<GreenNode>) -> Self {
+        Self {
+            data: Rc::new(NodeData {
+                green,
+                parent: None,
+                offset: 0,
+            }),
+        }
+    }
+
+    /// Underlying green node
+    pub fn green(&self) -> &Arc<GreenNode> {
+        &self.data.green
+    }
+
+    /// Kind of the node, see `GreenNode::kind`
+    pub fn kind(&self) -> SyntaxKind {
+        self.data.green.kind()
+    }
+
+    /// Location of the node, includes all nested tokens (with trivia)
+    pub fn loc(&self) -> Loc {
+        Loc {
+            begin: self.data.offset,
+            end: self.data.offset + self.data.green.text_len(),
+        }
+    }
+
+    /// Parent node, `None` for the root node
+    pub fn parent(&self) -> Option<&SyntaxNode> {
+        self.data.parent.as_ref()
+    }
+
+    /// Returns all ancestors of the node, from the direct parent to the root
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> {
+        std::iter::successors(self.parent().cloned(), |node| node.parent().cloned())
+    }
+
+    /// Child nodes and tokens
+    pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.data.offset;
+        let mut children = vec![];
+        for child in self.data.green.children() {
+            let child_offset = offset;
+            offset += child.text_len();
+            children.push(match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode {
+                    data: Rc::new(NodeData {
+                        green: Arc::clone(green),
+                        parent: Some(self.clone()),
+                        offset: child_offset,
+                    }),
+                }),
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    green: Arc::clone(green),
+                    parent: self.clone(),
+                    offset: child_offset,
+                }),
+            });
+        }
+        children
+    }
+
+    /// Child nodes
+    pub fn children(&self) -> Vec<SyntaxNode> {
+        self.children_with_tokens()
+            .into_iter()
+            .filter_map(|child| match child {
+                SyntaxElement::Node(node) => Some(node),
+                SyntaxElement::Token(_) => None,
+            })
+            .collect()
+    }
+
+    /// Returns the node and all its descendant nodes, parents go before their children
+    pub fn descendants(&self) -> Vec<SyntaxNode> {
+        let mut result = vec![self.clone()];
+        for child in self.children() {
+            result.extend(child.descendants());
+        }
+        result
+    }
+
+    /// Returns all tokens of the node (including tokens of descendant nodes) in source order
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut result = vec![];
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => result.extend(node.tokens()),
+                SyntaxElement::Token(token) => result.push(token),
+            }
+        }
+        result
+    }
+
+    /// Source code of the node (with trivia)
+    pub fn text(&self) -> Vec<u8> {
+        let mut text = Vec::with_capacity(self.data.green.text_len());
+        collect_text(&self.data.green, &mut text);
+        text
+    }
+}
+
+fn collect_text(green: &GreenNode, text: &mut Vec<u8>) {
+    for child in green.children() {
+        match child {
+            GreenElement::Node(node) => collect_text(node, text),
+            GreenElement::Token(token) => text.extend_from_slice(token.text()),
+        }
+    }
+}
+
+/// Cursor over a `GreenToken` that knows its position and its parent
+#[derive(Debug, Clone)]
+pub struct SyntaxToken {
+    green: Arc<GreenToken>,
+    parent: SyntaxNode,
+    offset: usize,
+}
+
+impl SyntaxToken {
+    /// Underlying green token
+    pub fn green(&self) -> &Arc<GreenToken> {
+        &self.green
+    }
+
+    /// Type of the token, same as `Token::token_type`
+    pub fn token_type(&self) -> i32 {
+        self.green.token_type()
+    }
+
+    /// Name of the token, same as `Token::token_name`
+    pub fn token_name(&self) -> &'static str {
+        self.green.token_name()
+    }
+
+    /// Location of the token
+    pub fn loc(&self) -> Loc {
+        Loc {
+            begin: self.offset,
+            end: self.offset + self.green.text().len(),
+        }
+    }
+
+    /// Source code of the token
+    pub fn text(&self) -> &[u8] {
+        self.green.text()
+    }
+
+    /// Node that owns the token
+    pub fn parent(&self) -> &SyntaxNode {
+        &self.parent
+    }
+}
+
+/// Child of the `SyntaxNode`
+#[derive(Debug, Clone)]
+pub enum SyntaxElement {
+    /// Child node
+    Node(SyntaxNode),
+
+    /// Child token
+    Token(SyntaxToken),
+}
+
+#[cfg(test)]
+use crate::Lexer;
+
+// foo(1) # c
+#[cfg(test)]
+fn test_tree() -> SyntaxNode {
+    let token = |token_type, text: &[u8]| {
+        GreenElement::Token(Arc::new(GreenToken::new(token_type, text.to_vec())))
+    };
+    let node = |kind, ast_index, children| {
+        GreenElement::Node(Arc::new(GreenNode::new(kind, ast_index, children)))
+    };
+    let args = node(
+        SyntaxKind::ArgumentList,
+        None,
+        vec![
+            token(Lexer::tLPAREN2, b"("),
+            node(SyntaxKind::Int, Some(1), vec![token(Lexer::tINTEGER, b"1")]),
+            token(Lexer::tRPAREN, b")"),
+        ],
+    );
+    let send = node(
+        SyntaxKind::Send,
+        Some(0),
+        vec![token(Lexer::tIDENTIFIER, b"foo"), args],
+    );
+    let root = GreenNode::new(
+        SyntaxKind::Root,
+        None,
+        vec![
+            send,
+            token(Lexer::tWHITESPACE, b" "),
+            token(Lexer::tCOMMENT, b"# c"),
+        ],
+    );
+    SyntaxNode::new_root(Arc::new(root))
+}
+
+#[test]
+fn test_syntax_node() {
+    let loc = |begin, end| Loc { begin, end };
+    let root = test_tree();
+    assert_eq!(root.kind(), SyntaxKind::Root);
+    assert_eq!(root.loc(), loc(0, 10));
+    assert_eq!(root.text(), b"foo(1) # c");
+    assert!(root.parent().is_none());
+    assert_eq!(root.ancestors().count(), 0);
+
+    let children = root.children_with_tokens();
+    assert_eq!(children.len(), 3);
+    match &children[2] {
+        SyntaxElement::Token(token) => {
+            assert_eq!(token.token_name(), "tCOMMENT");
+            assert_eq!(token.loc(), loc(7, 10));
+        }
+        other => panic!("expected token, got {:?}", other),
+    }
+
+    let send = &root.children()[0];
+    assert_eq!(send.kind(), SyntaxKind::Send);
+    assert_eq!(send.green().ast_index(), Some(0));
+    assert_eq!(send.loc(), loc(0, 6));
+    assert_eq!(send.text(), b"foo(1)");
+    assert_eq!(send.parent().unwrap().kind(), SyntaxKind::Root);
+
+    let int = &send.children()[0].children()[0];
+    assert_eq!(int.kind(), SyntaxKind::Int);
+    assert_eq!(int.loc(), loc(4, 5));
+    assert_eq!(
+        int.ancestors().map(|node| node.kind()).collect::<Vec<_>>(),
+        vec![SyntaxKind::ArgumentList, SyntaxKind::Send, SyntaxKind::Root]
+    );
+}
+
+#[test]
+fn test_descendants_and_tokens() {
+    let root = test_tree();
+    assert_eq!(
+        root.descendants()
+            .iter()
+            .map(|node| node.kind())
+            .collect::<Vec<_>>(),
+        vec![
+            SyntaxKind::Root,
+            SyntaxKind::Send,
+            SyntaxKind::ArgumentList,
+            SyntaxKind::Int
+        ]
+    );
+
+    let tokens = root.tokens();
+    assert_eq!(
+        tokens
+            .iter()
+            .map(|token| (token.token_name(), token.loc().begin, token.text()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("tIDENTIFIER", 0, &b"foo"[..]),
+            ("tLPAREN2", 3, b"("),
+            ("tINTEGER", 4, b"1"),
+            ("tRPAREN", 5, b")"),
+            ("tWHITESPACE", 6, b" "),
+            ("tCOMMENT", 7, b"# c"),
+        ]
+    );
+    // tokens know the node that owns them
+    assert_eq!(tokens[2].parent().kind(), SyntaxKind::Int);
+    assert_eq!(tokens[2].token_type(), Lexer::tINTEGER);
+    assert_eq!(tokens[5].parent().kind(), SyntaxKind::Root);
+}
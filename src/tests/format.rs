@@ -0,0 +1,61 @@
+use crate::format::{format, FormatError, FormatOptions};
+use crate::tests::test_helpers::{fixture_inputs, parse_fixture_input};
+use crate::{ParserOptions, ParserResult};
+
+fn parse(input: &[u8]) -> ParserResult {
+    let options = ParserOptions {
+        buffer_name: "(format)".into(),
+        ..Default::default()
+    };
+    parse_fixture_input(input, options)
+}
+
+fn inspect(result: &ParserResult) -> String {
+    result
+        .ast
+        .as_ref()
+        .map(|node| node.inspect(0))
+        .unwrap_or_else(|| "nil".to_string())
+}
+
+#[test]
+fn test_format_fixtures() {
+    let options = FormatOptions::default();
+
+    for fixture in fixture_inputs() {
+        let path = &fixture.path;
+        let result = parse(fixture.input.as_bytes());
+        let formatted = match format(&result, &options) {
+            Ok(formatted) => formatted,
+            Err(FormatError::SyntaxError(error)) => {
+                assert!(
+                    fixture.expects_errors,
+                    "unexpected error in {:?}: {:?}",
+                    path, error
+                );
+                continue;
+            }
+            Err(err) => panic!("failed to format {:?}: {:?}", path, err),
+        };
+        assert!(
+            !fixture.expects_errors,
+            "{:?} expects errors, but it's formatted",
+            path
+        );
+
+        let reparsed = parse(formatted.as_bytes());
+        assert_eq!(
+            inspect(&reparsed),
+            inspect(&result),
+            "formatting of {:?} changes AST:\n{}",
+            path,
+            formatted
+        );
+        assert_eq!(
+            format(&reparsed, &options).as_ref(),
+            Ok(&formatted),
+            "formatting of {:?} is not idempotent",
+            path
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use alloc_from_pool::Pool;
+
+use crate::{Lexer, Limits, Message, Parser, ParserOptions, ParserResult};
+
+// Regression cases found by the fuzzer: inputs that used to overflow the stack
+// (while parsing or when the AST is dropped)
+struct Case {
+    name: &'static str,
+    input: fn() -> String,
+    options: fn() -> ParserOptions,
+    expected: Message,
+}
+
+fn limits() -> ParserOptions {
+    ParserOptions {
+        record_tokens: false,
+        max_depth: Some(1_000),
+        max_input_size: Some(1_000_000),
+        max_tokens: Some(300_000),
+        ..Default::default()
+    }
+}
+
+const CASES: &[Case] = &[
+    Case {
+        name: "unclosed_brackets",
+        input: || "[".repeat(100_000),
+        options: limits,
+        expected: Message::NestingTooDeep { max_depth: 1_000 },
+    },
+    Case {
+        name: "unclosed_parens",
+        input: || "(".repeat(100_000),
+        options: limits,
+        expected: Message::NestingTooDeep { max_depth: 1_000 },
+    },
+    Case {
+        name: "closed_brackets",
+        input: || format!("{}{}", "[".repeat(50_000), "]".repeat(50_000)),
+        options: limits,
+        expected: Message::NestingTooDeep { max_depth: 1_000 },
+    },
+    Case {
+        name: "unclosed_hashes",
+        input: || "{a: ".repeat(50_000),
+        options: limits,
+        expected: Message::NestingTooDeep { max_depth: 1_000 },
+    },
+    Case {
+        name: "long_binary_operation",
+        input: || format!("1{}", " + 1".repeat(200_000)),
+        options: limits,
+        expected: Message::TooManyTokens {
+            max_tokens: 300_000,
+        },
+    },
+    Case {
+        // no parentheses, but the AST is deep
+        name: "deep_not",
+        input: || format!("{}a", "!".repeat(100_000)),
+        options: limits,
+        expected: Message::NestingTooDeep { max_depth: 1_000 },
+    },
+    Case {
+        name: "deep_ternary",
+        input: || format!("{}1", "a ? 1 : ".repeat(50_000)),
+        options: limits,
+        expected: Message::NestingTooDeep { max_depth: 1_000 },
+    },
+    Case {
+        name: "large_input",
+        input: || "# comment\n".repeat(200_000),
+        options: limits,
+        expected: Message::InputTooLarge {
+            max_input_size: 1_000_000,
+        },
+    },
+];
+
+// Runs on a thread with a small stack, so unbounded recursion fails the test
+fn parse_on_small_stack(input: String, options: fn() -> ParserOptions) -> ParserResult {
+    std::thread::Builder::new()
+        .stack_size(4 * 1024 * 1024)
+        .spawn(move || Parser::new(input, options()).do_parse())
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+#[test]
+fn test_limits_fuzz_regressions() {
+    for case in CASES {
+        let result = parse_on_small_stack((case.input)(), case.options);
+        assert!(result.ast.is_none(), "{}: expected no AST", case.name);
+        assert_eq!(
+            result.diagnostics.last().map(|d| &d.message),
+            Some(&case.expected),
+            "{}: {:?}",
+            case.name,
+            result.diagnostics
+        );
+    }
+}
+
+#[test]
+fn test_limits_allow_regular_code() {
+    let result = parse_on_small_stack(String::from("foo([[1], {a: (2)}])"), limits);
+    assert!(result.ast.is_some());
+    assert!(result.diagnostics.is_empty());
+}
+
+#[test]
+fn test_deep_input_with_default_options() {
+    let inputs = [
+        format!("{}1{}", "[".repeat(100_000), "]".repeat(100_000)),
+        format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000)),
+        format!("a{}", ".b".repeat(100_000)),
+        format!("{}a", "!".repeat(100_000)),
+        // warnings and checks of conditions
+        format!("if a{} then end", " && a".repeat(100_000)),
+        format!(
+            "if {}'a'{} then end",
+            "(".repeat(50_000),
+            ")".repeat(50_000)
+        ),
+        // value of the last statement must be checked for `return` and friends
+        format!("{}1{} + 1", "(".repeat(100_000), ")".repeat(100_000)),
+    ];
+    for input in inputs {
+        let result = parse_on_small_stack(input, ParserOptions::default);
+        assert!(result.ast.is_some(), "{:?}", result.diagnostics);
+        std::thread::Builder::new()
+            .stack_size(512 * 1024)
+            .spawn(move || drop(result))
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_deep_input_with_syntax_error() {
+    // the parser drops deep nodes that are left on its stack
+    let input = format!("{}1{}", "[".repeat(100_000), "]".repeat(100_001));
+    let result = parse_on_small_stack(input, ParserOptions::default);
+    assert!(!result.diagnostics.is_empty());
+}
+
+#[test]
+fn test_lexer_stops_on_limits() {
+    let tokenize = |input: &str, limits: Limits| {
+        let pool = Pool::new();
+        let mut lexer = Lexer::new(input, "(limits)", None);
+        lexer.tokens_factory = pool.factory();
+        lexer.limits = limits;
+        let tokens = lexer.tokenize_until_eof();
+        let diagnostics = lexer.diagnostics.take_inner();
+        (tokens.len(), diagnostics)
+    };
+
+    let (tokens, diagnostics) = tokenize(
+        "[[[[[[",
+        Limits {
+            max_depth: Some(3),
+            ..Default::default()
+        },
+    );
+    // the 4th bracket exceeds the limit, END_OF_INPUT is not included
+    assert_eq!(tokens, 4);
+    assert_eq!(
+        diagnostics[0].message,
+        Message::NestingTooDeep { max_depth: 3 }
+    );
+
+    let (tokens, diagnostics) = tokenize(
+        "a b c d e",
+        Limits {
+            max_tokens: Some(2),
+            ..Default::default()
+        },
+    );
+    assert_eq!(tokens, 2);
+    assert_eq!(
+        diagnostics[0].message,
+        Message::TooManyTokens { max_tokens: 2 }
+    );
+}
@@ -1,3 +1,10 @@
+mod format;
 mod lexer;
+mod limits;
+mod node_types;
 mod parser;
+mod prism;
+mod ripper;
+mod syntax_tree;
 mod test_helpers;
+mod trivia;
@@ -0,0 +1,131 @@
+// Checks the table from `codegen/rust/node_types.rs` against the AST crate:
+// `gen.rs` builds nodes using all fields from the table
+// and generated traversals are compared with `traverse::visitor::Visitor`.
+mod gen;
+use gen::sample_nodes;
+
+use crate::fields::{self, FieldValue};
+use crate::nodes::Nil;
+use crate::query::{self, Value};
+use crate::rewrite::{for_each_child, take_children};
+use crate::syntax_tree::SyntaxKind;
+use crate::traverse::visitor::Visitor;
+use crate::{Loc, Node};
+
+// Every child is a `Nil` with a unique location, all other locations are `0...0`
+fn nodes() -> Vec<Node> {
+    let mut next = 0;
+    sample_nodes(|| {
+        next += 1;
+        Node::Nil(Nil {
+            expression_l: Loc {
+                begin: next,
+                end: next + 1,
+            },
+        })
+    })
+}
+
+// Collects locations of children in the order of `traverse::visitor::Visitor`
+#[derive(Default)]
+struct ChildrenCollector {
+    children: Vec<Loc>,
+}
+
+impl Visitor for ChildrenCollector {
+    fn on_nil(&mut self, node: &Nil) {
+        // `Nil` from `sample_nodes` is not a child
+        if node.expression_l.begin != 0 {
+            self.children.push(node.expression_l)
+        }
+    }
+}
+
+fn visitor_children(node: &Node) -> Vec<Loc> {
+    let mut collector = ChildrenCollector::default();
+    collector.visit(node);
+    collector.children
+}
+
+#[test]
+fn test_for_each_child() {
+    for node in nodes() {
+        let mut children = vec![];
+        for_each_child(&node, &mut |child| children.push(*child.expression()));
+        assert_eq!(children, visitor_children(&node), "{}", node.str_type());
+    }
+}
+
+#[test]
+fn test_take_children() {
+    for node in nodes() {
+        let expected = visitor_children(&node);
+        let str_type = node.str_type();
+        let mut node = node;
+        let mut children = vec![];
+        take_children(&mut node, &mut children);
+        let children = children.iter().map(|child| *child.expression());
+        assert_eq!(children.collect::<Vec<_>>(), expected, "{}", str_type);
+    }
+}
+
+#[test]
+fn test_query_children() {
+    for node in nodes() {
+        let children = query::children(&node)
+            .iter()
+            .filter_map(Value::loc)
+            .collect::<Vec<_>>();
+        assert_eq!(children, visitor_children(&node), "{}", node.str_type());
+    }
+}
+
+#[test]
+fn test_str_types() {
+    let mut str_types = nodes()
+        .iter()
+        .map(|node| node.str_type())
+        .collect::<Vec<_>>();
+    str_types.sort_unstable();
+    str_types.dedup();
+    assert_eq!(str_types, query::NODE_TYPES);
+}
+
+#[test]
+fn test_syntax_kinds() {
+    for node in nodes() {
+        assert_eq!(SyntaxKind::of_node(&node).name(), node.str_type());
+    }
+}
+
+#[test]
+fn test_fields() {
+    for node in nodes() {
+        let mut children = vec![];
+        for (_, value) in fields::fields(&node) {
+            match value {
+                FieldValue::Node(child) | FieldValue::MaybeNode(Some(child)) => {
+                    children.push(*child.expression())
+                }
+                FieldValue::Nodes(nodes) => {
+                    children.extend(nodes.iter().map(|child| *child.expression()))
+                }
+                _ => {}
+            }
+        }
+        let str_type = node.str_type();
+        assert_eq!(children, visitor_children(&node), "{}", str_type);
+        assert_eq!(
+            fields::fields(&node).count(),
+            fields::field_names(&node).len(),
+            "{}",
+            str_type
+        );
+        assert_eq!(
+            fields::field(&node, "expression_l"),
+            Some(FieldValue::Loc(*node.expression())),
+            "{}",
+            str_type
+        );
+    }
+}
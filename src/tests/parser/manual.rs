@@ -80,6 +80,7 @@ fixture_file!("src/tests/fixtures/parser/manual", test_marg_combinations_9);
 fixture_file!("src/tests/fixtures/parser/manual", test_range_endless_1);
 fixture_file!("src/tests/fixtures/parser/manual", test_regex_error_0);
 fixture_file!("src/tests/fixtures/parser/manual", test_regex_error_1);
+fixture_file!("src/tests/fixtures/parser/manual", test_regex_error_2);
 fixture_file!("src/tests/fixtures/parser/manual", test_regexp_encoding_0);
 fixture_file!("src/tests/fixtures/parser/manual", unterminated_heredoc_id);
 fixture_file!(
@@ -166,3 +167,44 @@ fixture_file!(
     "src/tests/fixtures/parser/manual",
     casematch_with_multiple_ins_and_else
 );
+fixture_file!("src/tests/fixtures/parser/manual", utf8_mixed_within_source);
+fixture_file!(
+    "src/tests/fixtures/parser/manual",
+    assigned_but_unused_variable_in_def
+);
+fixture_file!(
+    "src/tests/fixtures/parser/manual",
+    assigned_but_unused_variable_in_block
+);
+fixture_file!(
+    "src/tests/fixtures/parser/manual",
+    assigned_but_unused_variable_with_binding
+);
+fixture_file!(
+    "src/tests/fixtures/parser/manual",
+    assigned_but_unused_variable_at_top_level
+);
+fixture_file!("src/tests/fixtures/parser/manual", mismatched_indentations);
+fixture_file!(
+    "src/tests/fixtures/parser/manual",
+    mismatched_indentations_with_else
+);
+fixture_file!(
+    "src/tests/fixtures/parser/manual",
+    mismatched_indentations_after_endless_def
+);
+fixture_file!(
+    "src/tests/fixtures/parser/manual",
+    mismatched_indentations_disabled
+);
+fixture_file!(
+    "src/tests/fixtures/parser/manual",
+    useless_use_in_void_context
+);
+fixture_file!("src/tests/fixtures/parser/manual", unused_literal_ignored);
+fixture_file!("src/tests/fixtures/parser/manual", literal_in_condition);
+fixture_file!(
+    "src/tests/fixtures/parser/manual",
+    literal_in_condition_non_verbose
+);
+fixture_file!("src/tests/fixtures/parser/manual", duplicated_when_clause);
@@ -32,6 +32,17 @@ fn parse(input: &[u8]) -> ParserResult {
     parser.do_parse()
 }
 
+fn parse_with_content_maps(input: &[u8]) -> ParserResult {
+    let options = ParserOptions {
+        buffer_name: "(eval)".into(),
+        record_tokens: false,
+        record_string_content_maps: true,
+        ..Default::default()
+    };
+    let parser = Parser::new(input, options);
+    parser.do_parse()
+}
+
 #[test]
 fn test_magic_comment() {
     let fixture = std::fs::read("src/tests/fixtures/magic_comments.rb").unwrap();
@@ -73,3 +84,396 @@ fn test_magic_comment() {
         ]
     );
 }
+
+#[test]
+fn test_original_loc_after_decoding() {
+    use crate::source::{Decoder, DecoderResult};
+    use crate::LocExt;
+
+    // Latin-1 to UTF-8
+    fn decode(_encoding: String, input: Vec<u8>) -> DecoderResult {
+        DecoderResult::Ok(input.into_iter().map(|c| c as char).collect::<String>().into())
+    }
+
+    let source = b"# encoding: iso-8859-1\n'\xE9t\xE9' + 'b'".to_vec();
+    let options = ParserOptions {
+        decoder: Some(Decoder::new(Box::new(decode))),
+        ..Default::default()
+    };
+    let ParserResult { ast, input, .. } = Parser::new(source.clone(), options).do_parse();
+    let ast = ast.unwrap();
+
+    let loc = *ast.expression();
+    assert_eq!(loc, Loc { begin: 23, end: 36 });
+    assert_eq!(loc.original_loc(&input), Some(Loc { begin: 23, end: 34 }));
+    assert_eq!(
+        loc.original_source(&input, &source),
+        Some(&b"'\xE9t\xE9' + 'b'"[..])
+    );
+}
+
+#[test]
+fn test_regexp_literal_locations() {
+    use crate::regexp::{parse_regexp_literal, Group, GroupKind, RegexpNode, Sequence};
+
+    let ParserResult { ast, input, .. } = parse(b"%r{(?<y>\\d+) # c\n}x");
+    let regexp = match ast.as_deref() {
+        Some(crate::Node::Regexp(regexp)) => regexp,
+        other => panic!("expected regexp, got {:?}", other),
+    };
+    let tree = parse_regexp_literal(regexp, &input).unwrap().unwrap();
+    assert_eq!(tree.named_captures, vec!["y"]);
+
+    let items = match tree.root {
+        RegexpNode::Sequence(Sequence { items, .. }) => items,
+        other => panic!("expected sequence, got {:?}", other),
+    };
+    match &items[0] {
+        RegexpNode::Group(Group {
+            kind: GroupKind::NamedCapture { name_l, .. },
+            body,
+            expression_l,
+            ..
+        }) => {
+            assert_eq!(*name_l, Loc { begin: 6, end: 7 });
+            assert_eq!(*body.expression(), Loc { begin: 8, end: 11 });
+            assert_eq!(*expression_l, Loc { begin: 3, end: 12 });
+        }
+        other => panic!("expected named group, got {:?}", other),
+    }
+    assert_eq!(*items[1].expression(), Loc { begin: 13, end: 16 });
+}
+
+#[test]
+fn test_numeric_values() {
+    use crate::nodes::Array;
+    use crate::numeric::NumericValue;
+    use crate::Node;
+
+    fn describe(value: &NumericValue) -> String {
+        match value {
+            NumericValue::Integer(value) => format!("Integer({})", value),
+            NumericValue::Float(value) => {
+                format!("Float({:?}, {})", value.value, value.out_of_range)
+            }
+            NumericValue::Rational(value) => {
+                format!("Rational({}/{})", value.numerator, value.denominator)
+            }
+            NumericValue::Complex(value) => format!("Complex({})", describe(&value.imaginary)),
+        }
+    }
+
+    let ParserResult { ast, .. } = parse(b"[0x_FF, -017, -1.5, 0.5i, 0e1i, 0.5r, -0.5ri, 1e999]");
+    let elements = match ast.as_deref() {
+        Some(Node::Array(Array { elements, .. })) => elements,
+        other => panic!("expected Array, got {:?}", other),
+    };
+    let values = elements
+        .iter()
+        .map(|node| match NumericValue::from_node(node) {
+            Some(value) => describe(&value),
+            None => format!("None({})", node.str_type()),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        values,
+        vec![
+            "Integer(255)",
+            "Integer(-15)",
+            "Float(-1.5, false)",
+            "Complex(Float(0.5, false))",
+            "Complex(Float(0.0, false))",
+            "Rational(1/2)",
+            "Complex(Rational(-1/2))",
+            "Float(inf, true)",
+        ]
+    );
+}
+
+#[test]
+fn test_string_content_map() {
+    use crate::nodes::{Heredoc, Str};
+    use crate::Node;
+
+    // not recorded by default
+    assert!(parse(b"\"a\\u{1F600} word\"")
+        .string_content_maps
+        .is_empty());
+
+    let result = parse_with_content_maps(b"\"a\\u{1F600} word\"");
+    let node = match result.ast.as_deref() {
+        Some(Node::Str(node)) => node,
+        other => panic!("expected str, got {:?}", other),
+    };
+    let map = result.string_content_map(node).unwrap();
+    assert_eq!(map.content_l, Loc { begin: 1, end: 16 });
+    // "word"
+    assert_eq!(
+        map.source_loc(Loc { begin: 6, end: 10 }),
+        Some(Loc { begin: 12, end: 16 })
+    );
+    // emoji
+    assert_eq!(
+        map.source_loc(Loc { begin: 1, end: 5 }),
+        Some(Loc { begin: 2, end: 11 })
+    );
+
+    let result = parse_with_content_maps(b"<<~E\n    x\\ty\nE\n");
+    let node = match result.ast.as_deref() {
+        Some(Node::Heredoc(Heredoc { parts, .. })) => match &parts[..] {
+            [Node::Str(node @ Str { .. })] => node,
+            other => panic!("expected single str part, got {:?}", other),
+        },
+        other => panic!("expected heredoc, got {:?}", other),
+    };
+    assert_eq!(node.value.as_raw(), b"x\ty\n");
+    let map = result.string_content_map(node).unwrap();
+    assert_eq!(map.source_offset(0), Some(9));
+    assert_eq!(
+        map.source_loc(Loc { begin: 1, end: 3 }),
+        Some(Loc { begin: 10, end: 13 })
+    );
+}
+
+#[test]
+fn test_literal_encoding() {
+    use crate::source::LiteralEncoding;
+
+    fn literal_encoding(input: &[u8]) -> LiteralEncoding {
+        let result = parse_with_content_maps(input);
+        let node = result.ast.as_deref().expect("expected a literal");
+        result.literal_encoding(node).expect("expected a literal")
+    }
+
+    fn encoding(name: &str, valid: bool) -> LiteralEncoding {
+        LiteralEncoding {
+            name: name.to_string(),
+            valid,
+        }
+    }
+
+    assert_eq!(literal_encoding(b"\"abc\""), encoding("UTF-8", true));
+    assert_eq!(literal_encoding(b"\"\\xFF\""), encoding("UTF-8", false));
+    assert_eq!(
+        literal_encoding(b"# encoding: ascii-8bit\n\"\\xFF\""),
+        encoding("ASCII-8BIT", true)
+    );
+    assert_eq!(
+        literal_encoding(b"# encoding: ascii-8bit\n\"\\u00e9\""),
+        encoding("UTF-8", true)
+    );
+    assert_eq!(literal_encoding(b":abc"), encoding("US-ASCII", true));
+    assert_eq!(
+        literal_encoding(":\"\u{e9}\"".as_bytes()),
+        encoding("UTF-8", true)
+    );
+    assert_eq!(literal_encoding(b"/abc/"), encoding("US-ASCII", true));
+    assert_eq!(literal_encoding(b"/\\u00e9/"), encoding("UTF-8", true));
+    assert_eq!(literal_encoding(b"/\\xa8/n"), encoding("ASCII-8BIT", true));
+}
+
+#[test]
+fn test_scope_tree() {
+    use crate::scope::{DeclarationKind, ScopeKind};
+
+    let result = parse(
+        b"a = 1\nfoo { |a| a }\ndef m(b); b; end\n/(?<c>.)/ =~ 'x'; c\ncase 1; in d then d; end\n",
+    );
+    let tree = result.scope_tree();
+    let declaration_of = |begin, end| {
+        let variable = tree.variable_at(&Loc { begin, end }).unwrap();
+        let declaration = variable.declaration.unwrap();
+        (
+            variable.name.as_str(),
+            tree.scopes[variable.scope].kind,
+            declaration.kind,
+            declaration.loc,
+        )
+    };
+
+    // block argument shadows top level variable
+    assert_eq!(
+        declaration_of(16, 17),
+        (
+            "a",
+            ScopeKind::Block,
+            DeclarationKind::Arg,
+            Loc { begin: 13, end: 14 }
+        )
+    );
+    assert!(tree
+        .variable_at(&Loc { begin: 0, end: 1 })
+        .unwrap()
+        .references
+        .is_empty());
+
+    assert_eq!(
+        declaration_of(30, 31),
+        (
+            "b",
+            ScopeKind::Def,
+            DeclarationKind::Arg,
+            Loc { begin: 26, end: 27 }
+        )
+    );
+    assert_eq!(
+        declaration_of(55, 56),
+        (
+            "c",
+            ScopeKind::TopLevel,
+            DeclarationKind::NamedCapture,
+            Loc { begin: 41, end: 42 }
+        )
+    );
+    assert_eq!(
+        declaration_of(75, 76),
+        (
+            "d",
+            ScopeKind::TopLevel,
+            DeclarationKind::MatchVar,
+            Loc { begin: 68, end: 69 }
+        )
+    );
+}
+
+#[test]
+fn test_invalid_multibyte_char() {
+    let result = parse(b"\"a\xFFb\"");
+    let messages = result
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.render_message(), diagnostic.loc))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![(
+            String::from("invalid multibyte char (UTF-8)"),
+            Loc { begin: 2, end: 3 }
+        )]
+    );
+
+    // any byte is valid in binary source
+    let result = parse(b"# encoding: ascii-8bit\n\"a\xFFb\"");
+    assert!(result.diagnostics.is_empty());
+}
+
+#[test]
+fn test_shared_input() {
+    use crate::source::SourceBytes;
+    use std::sync::Arc;
+
+    let source: Arc<[u8]> = Arc::from(&b"foo(1)"[..]);
+    let ParserResult { ast, input, .. } = Parser::new(
+        SourceBytes::Shared(Arc::clone(&source)),
+        ParserOptions::default(),
+    )
+    .do_parse();
+    assert!(ast.is_some());
+    assert!(input.bytes.is_shared());
+    assert!(std::ptr::eq(input.as_shared_bytes(), &*source));
+
+    // binary input is not re-encoded and stays shared
+    let source: Arc<[u8]> = Arc::from(&b"# encoding: binary\nfoo(1)"[..]);
+    let ParserResult { input, .. } = Parser::new(
+        SourceBytes::Shared(Arc::clone(&source)),
+        ParserOptions::default(),
+    )
+    .do_parse();
+    assert!(input.bytes.is_shared());
+}
+
+#[test]
+fn test_shared_token_values() {
+    use crate::source::SourceBytes;
+
+    let source: &'static [u8] = b"foo(\"a\\n\", bar)";
+    let result = Parser::new(SourceBytes::Static(source), ParserOptions::default()).do_parse();
+    let value = |name: &str| {
+        result
+            .tokens
+            .iter()
+            .find(|token| token.token_name() == name)
+            .map(|token| &token.token_value)
+            .unwrap()
+    };
+
+    assert!(value("tIDENTIFIER").is_shared());
+    assert!(std::ptr::eq(value("tIDENTIFIER").as_bytes(), &source[0..3]));
+    // unescaped
+    assert_eq!(value("tSTRING_CONTENT").as_bytes(), b"a\n");
+    assert!(!value("tSTRING_CONTENT").is_shared());
+
+    // owned input is not shared with tokens
+    let result = Parser::new(source.to_vec(), ParserOptions::default()).do_parse();
+    assert!(result
+        .tokens
+        .iter()
+        .all(|token| !token.token_value.is_shared()));
+}
+
+#[test]
+fn test_reset() {
+    let inputs: [&[u8]; 4] = [
+        b"def foo(a); a + 1; end",
+        // leaves internal stacks non-empty
+        b"def foo(a, b = [1, { c: ",
+        b"x = 1; foo { |y| y }",
+        b"# encoding: binary\nbar",
+    ];
+
+    let mut parser = Parser::new(inputs[0], ParserOptions::default());
+    for (idx, input) in inputs.iter().enumerate() {
+        if idx > 0 {
+            parser.reset(*input, ParserOptions::default());
+        }
+        let reused = parser.do_parse_in_place();
+        let fresh = Parser::new(*input, ParserOptions::default()).do_parse();
+
+        assert_eq!(reused.ast, fresh.ast);
+        assert_eq!(reused.tokens, fresh.tokens);
+        assert_eq!(reused.diagnostics, fresh.diagnostics);
+        assert_eq!(reused.comments, fresh.comments);
+        assert_eq!(reused.magic_comments, fresh.magic_comments);
+        assert_eq!(reused.input.as_shared_bytes(), *input);
+    }
+}
+
+#[test]
+fn test_cancellation() {
+    use crate::{CancellationToken, ErrorLevel, Message};
+    use std::time::Duration;
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let options = ParserOptions {
+        cancellation_token: Some(token),
+        ..Default::default()
+    };
+    let result = Parser::new(b"def foo; 1 + 2; end".to_vec(), options).do_parse();
+    assert!(result.ast.is_none());
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].level, ErrorLevel::Error);
+    assert_eq!(result.diagnostics[0].message, Message::Cancelled {});
+
+    let options = ParserOptions {
+        timeout: Some(Duration::ZERO),
+        ..Default::default()
+    };
+    let result = Parser::new(b"def foo; 1 + 2; end".to_vec(), options).do_parse();
+    assert!(result.ast.is_none());
+    assert_eq!(
+        result.diagnostics[0].message,
+        Message::TimedOut { timeout_ms: 0 }
+    );
+
+    // not cancelled
+    let options = ParserOptions {
+        cancellation_token: Some(CancellationToken::new()),
+        timeout: Some(Duration::from_secs(60)),
+        ..Default::default()
+    };
+    let result = Parser::new(b"def foo; 1 + 2; end".to_vec(), options).do_parse();
+    assert!(result.ast.is_some());
+    assert!(result.diagnostics.is_empty());
+}
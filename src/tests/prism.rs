@@ -0,0 +1,158 @@
+use crate::prism::{convert, Field, PrismNode};
+use crate::tests::test_helpers::{fixture_inputs, parse_fixture_input, read_fixture};
+use crate::{Loc, ParserOptions, ParserResult};
+
+fn parse(input: &[u8]) -> ParserResult {
+    let options = ParserOptions {
+        buffer_name: "(prism)".into(),
+        record_string_content_maps: true,
+        ..Default::default()
+    };
+    parse_fixture_input(input, options)
+}
+
+fn find<'a>(node: &'a PrismNode, kind: &str) -> Option<&'a PrismNode> {
+    if node.kind == kind {
+        return Some(node);
+    }
+    node.children()
+        .into_iter()
+        .find_map(|child| find(child, kind))
+}
+
+fn assert_valid(node: &PrismNode, input_len: usize, path: &std::path::Path) {
+    assert_ne!(node.kind, "MissingNode", "MissingNode in {:?}", path);
+    assert!(
+        node.location.begin <= node.location.end && node.location.end <= input_len,
+        "{} has invalid location {:?} in {:?}",
+        node.kind,
+        node.location,
+        path
+    );
+    for child in node.children() {
+        assert_valid(child, input_len, path);
+    }
+}
+
+#[test]
+fn test_prism_fixtures() {
+    for fixture in fixture_inputs() {
+        let path = &fixture.path;
+        let result = parse(fixture.input.as_bytes());
+        let has_errors = result
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.is_error());
+        assert_eq!(
+            has_errors, fixture.expects_errors,
+            "errors of {:?} don't match the fixture",
+            path
+        );
+        if has_errors {
+            continue;
+        }
+
+        let program = convert(&result);
+        assert_valid(&program, fixture.input.len(), path);
+        assert!(program.to_json().starts_with(r#"{"type":"ProgramNode","#));
+    }
+}
+
+// Output of `Prism.parse(input).value.to_json` for a sample of fixtures
+// from `src/tests/fixtures/parser/manual`, see `scripts/prism_fixtures.rb`
+const PRISM_FIXTURES_DIR: &str = "src/tests/fixtures/prism";
+
+// Drops what the converter doesn't produce (see "Prism compatibility" in README)
+fn normalize_prism_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            fields.remove("node_id");
+            if let Some(serde_json::Value::Array(flags)) = fields.get_mut("flags") {
+                flags.retain(|flag| flag != "NEWLINE" && flag != "STATIC_LITERAL");
+            }
+            fields.values_mut().for_each(normalize_prism_json);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(normalize_prism_json),
+        _ => {}
+    }
+}
+
+fn read_prism_json(json: &str, path: &std::path::Path) -> serde_json::Value {
+    let mut value = serde_json::from_str(json)
+        .unwrap_or_else(|err| panic!("invalid JSON of {:?}: {}", path, err));
+    normalize_prism_json(&mut value);
+    value
+}
+
+#[test]
+fn test_prism_expected_json() {
+    let mut paths = std::fs::read_dir(PRISM_FIXTURES_DIR)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert!(!paths.is_empty(), "no fixtures in {:?}", PRISM_FIXTURES_DIR);
+
+    for path in paths {
+        let name = path.file_stem().unwrap();
+        let fixture =
+            read_fixture(std::path::Path::new("src/tests/fixtures/parser/manual").join(name));
+        let expected = read_prism_json(&std::fs::read_to_string(&path).unwrap(), &path);
+
+        let program = convert(&parse(fixture.input.as_bytes()));
+        let actual = read_prism_json(&program.to_json(), &path);
+
+        assert_eq!(actual, expected, "output of {:?} doesn't match Prism", path);
+    }
+}
+
+#[test]
+fn test_prism_frozen_string_literal() {
+    let program = convert(&parse(b"# frozen_string_literal: true\n\"a\"\n\"#{a}b\""));
+    let statements = program.child("statements").unwrap().children();
+    assert_eq!(statements[0].kind, "StringNode");
+    assert!(statements[0].has_flag("FROZEN"));
+    assert_eq!(statements[1].kind, "InterpolatedStringNode");
+    assert!(!statements[1].has_flag("FROZEN"));
+}
+
+#[test]
+fn test_prism_safe_navigation() {
+    let program = convert(&parse(b"foo&.bar = 1"));
+    let call = find(&program, "CallNode").unwrap();
+    assert_eq!(call.flags(), &["SAFE_NAVIGATION", "ATTRIBUTE_WRITE"]);
+    assert_eq!(
+        call.child("receiver").unwrap().kind,
+        "LocalVariableReadNode"
+    );
+}
+
+#[test]
+fn test_prism_variable_call() {
+    let program = convert(&parse("_a; \u{e9}; a?".as_bytes()));
+    let statements = program.child("statements").unwrap().children();
+    assert!(statements[0].has_flag("VARIABLE_CALL"));
+    assert!(statements[1].has_flag("VARIABLE_CALL"));
+    assert!(!statements[2].has_flag("VARIABLE_CALL"));
+}
+
+#[test]
+fn test_prism_locations() {
+    let program = convert(&parse(b"def m(a, b = 1, *c, d:, &e)\n  a\nend"));
+    let def = find(&program, "DefNode").unwrap();
+    assert_eq!(
+        def.field("locals"),
+        Some(&Field::Constants(
+            ["a", "b", "c", "d", "e"]
+                .iter()
+                .map(|name| name.to_string())
+                .collect()
+        ))
+    );
+    let keyword = find(def, "RequiredKeywordParameterNode").unwrap();
+    // Prism includes the colon into `name_loc`
+    assert_eq!(
+        keyword.field("name_loc"),
+        Some(&Field::Location(Loc { begin: 20, end: 22 }))
+    );
+}
@@ -0,0 +1,107 @@
+use crate::ripper::{lex, sexp_raw};
+use crate::{Parser, ParserOptions, ParserResult};
+
+fn parse(input: &str) -> ParserResult {
+    let options = ParserOptions {
+        buffer_name: "(ripper)".into(),
+        record_tokens: true,
+        record_trivia: true,
+        record_lex_states: true,
+        ..Default::default()
+    };
+    Parser::new(input, options).do_parse()
+}
+
+fn assert_lex(input: &str, expected: &[&str]) {
+    let tokens = lex(&parse(input))
+        .iter()
+        .map(|token| token.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(tokens, expected, "wrong Ripper.lex output of {:?}", input);
+}
+
+fn assert_sexp(input: &str, expected: &str) {
+    let sexp = sexp_raw(&parse(input)).map(|sexp| sexp.to_string());
+    assert_eq!(
+        sexp.as_deref(),
+        Some(expected),
+        "wrong Ripper.sexp_raw output of {:?}",
+        input
+    );
+}
+
+#[test]
+fn test_lex() {
+    assert_lex(
+        "a = 1 # c\nb\n",
+        &[
+            r#"[[1, 0], :on_ident, "a", CMDARG]"#,
+            r#"[[1, 1], :on_sp, " ", CMDARG]"#,
+            r#"[[1, 2], :on_op, "=", BEG]"#,
+            r#"[[1, 3], :on_sp, " ", BEG]"#,
+            r#"[[1, 4], :on_int, "1", END]"#,
+            r#"[[1, 5], :on_sp, " ", END]"#,
+            r##"[[1, 6], :on_comment, "# c\n", END]"##,
+            r#"[[2, 0], :on_ident, "b", CMDARG]"#,
+            r#"[[2, 1], :on_nl, "\n", BEG]"#,
+        ],
+    );
+}
+
+#[test]
+fn test_lex_embdoc() {
+    assert_lex(
+        "=begin\nx\n=end\n",
+        &[
+            r#"[[1, 0], :on_embdoc_beg, "=begin\n", BEG]"#,
+            r#"[[2, 0], :on_embdoc, "x\n", BEG]"#,
+            r#"[[3, 0], :on_embdoc_end, "=end\n", BEG]"#,
+        ],
+    );
+}
+
+#[test]
+fn test_lex_without_lex_states() {
+    let result = Parser::new("a", ParserOptions::default()).do_parse();
+    let tokens = lex(&result)
+        .iter()
+        .map(|token| token.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(tokens, [r#"[[1, 0], :on_ident, "a", NONE]"#]);
+}
+
+#[test]
+fn test_sexp_raw_def() {
+    assert_sexp(
+        "def foo(a, b = 1)\n  a + b\nend\n",
+        concat!(
+            r#"[:program, [:stmts_add, [:stmts_new], [:def, [:@ident, "foo", [1, 4]], "#,
+            r#"[:paren, [:params, [[:@ident, "a", [1, 8]]], [[[:@ident, "b", [1, 11]], [:@int, "1", [1, 15]]]], nil, nil, nil, nil, nil]], "#,
+            r#"[:bodystmt, [:stmts_add, [:stmts_new], "#,
+            r#"[:binary, [:var_ref, [:@ident, "a", [2, 2]]], :+, [:var_ref, [:@ident, "b", [2, 6]]]]], nil, nil, nil]]]]"#
+        ),
+    );
+}
+
+#[test]
+fn test_sexp_raw_command() {
+    assert_sexp(
+        "puts \"hi #{name}\"",
+        concat!(
+            r#"[:program, [:stmts_add, [:stmts_new], [:command, [:@ident, "puts", [1, 0]], "#,
+            r#"[:args_add_block, [:args_add, [:args_new], [:string_literal, "#,
+            r#"[:string_add, [:string_add, [:string_content], [:@tstring_content, "hi ", [1, 6]]], "#,
+            r#"[:string_embexpr, [:stmts_add, [:stmts_new], [:vcall, [:@ident, "name", [1, 11]]]]]]]], false]]]]"#
+        ),
+    );
+}
+
+#[test]
+fn test_sexp_raw_empty() {
+    assert_sexp("", "[:program, [:stmts_add, [:stmts_new], [:void_stmt]]]");
+}
+
+#[test]
+fn test_sexp_raw_syntax_error() {
+    assert_eq!(sexp_raw(&parse("foo(")), None);
+}
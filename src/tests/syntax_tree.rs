@@ -0,0 +1,154 @@
+use crate::syntax_tree::{SyntaxKind, SyntaxNode, SyntaxTree};
+use crate::tests::test_helpers::{fixture_inputs, parse_fixture_input};
+use crate::{Parser, ParserOptions};
+
+fn options() -> ParserOptions {
+    ParserOptions {
+        buffer_name: "(syntax_tree)".into(),
+        record_tokens: true,
+        ..Default::default()
+    }
+}
+
+// Kinds and source code of all nodes, except the root node
+fn nodes(input: &str) -> Vec<(SyntaxKind, String)> {
+    let result = Parser::new(input, options()).do_parse();
+    assert_eq!(result.diagnostics, vec![], "{:?}", input);
+    let tree = result.syntax_tree();
+    tree.root()
+        .descendants()
+        .iter()
+        .skip(1)
+        .map(|node| (node.kind(), String::from_utf8(node.text()).unwrap()))
+        .collect()
+}
+
+fn node(kind: SyntaxKind, text: &str) -> (SyntaxKind, String) {
+    (kind, String::from(text))
+}
+
+fn assert_ast_nodes(node: &SyntaxNode, tree: &SyntaxTree, name: &str) {
+    match tree.ast_node(node) {
+        Some(ast) => assert_eq!(node.kind(), SyntaxKind::of_node(ast), "{}", name),
+        None => assert!(
+            matches!(
+                node.kind(),
+                SyntaxKind::Root
+                    | SyntaxKind::ArgumentList
+                    | SyntaxKind::Then
+                    | SyntaxKind::Else
+                    | SyntaxKind::DoBody
+                    | SyntaxKind::BraceBody
+            ),
+            "{:?} has no AST node in {}",
+            node.kind(),
+            name
+        ),
+    }
+    for child in node.children() {
+        assert_ast_nodes(&child, tree, name);
+    }
+}
+
+#[test]
+fn test_lossless_fixtures() {
+    for fixture in fixture_inputs() {
+        let name = format!("{}", fixture.path.display());
+        let result = parse_fixture_input(fixture.input.as_bytes(), options());
+        let tree = result.syntax_tree();
+        assert_eq!(
+            tree.root().text(),
+            fixture.input.as_bytes(),
+            "text of the root node is not equal to the input of {}",
+            name
+        );
+        assert_ast_nodes(tree.root(), &tree, &name);
+    }
+}
+
+#[test]
+fn test_argument_lists() {
+    assert_eq!(
+        nodes("foo(1, 2)"),
+        vec![
+            node(SyntaxKind::Send, "foo(1, 2)"),
+            node(SyntaxKind::ArgumentList, "(1, 2)"),
+            node(SyntaxKind::Int, "1"),
+            node(SyntaxKind::Int, "2"),
+        ]
+    );
+    assert_eq!(
+        nodes("a.foo 1, 2")[2],
+        node(SyntaxKind::ArgumentList, "1, 2")
+    );
+    assert_eq!(
+        nodes("def foo; yield 1; end")[2],
+        node(SyntaxKind::ArgumentList, "1")
+    );
+    assert_eq!(nodes("super(1)")[1], node(SyntaxKind::ArgumentList, "(1)"));
+    assert_eq!(nodes("[1][0]")[3], node(SyntaxKind::ArgumentList, "[0]"));
+
+    // operators and setters have no argument lists
+    for input in ["1 + 2", "!1", "self.foo = 1"] {
+        assert!(
+            nodes(input)
+                .iter()
+                .all(|(kind, _)| *kind != SyntaxKind::ArgumentList),
+            "{:?}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_branches() {
+    assert_eq!(
+        nodes("if 1 then 2 else 3 end"),
+        vec![
+            node(SyntaxKind::If, "if 1 then 2 else 3 end"),
+            node(SyntaxKind::Int, "1"),
+            node(SyntaxKind::Then, "then 2"),
+            node(SyntaxKind::Int, "2"),
+            node(SyntaxKind::Else, "else 3"),
+            node(SyntaxKind::Int, "3"),
+        ]
+    );
+    assert_eq!(
+        nodes("unless 1; 2; else 3; end")[2..],
+        [
+            node(SyntaxKind::Then, "; 2"),
+            node(SyntaxKind::Int, "2"),
+            node(SyntaxKind::Else, "else 3"),
+            node(SyntaxKind::Int, "3"),
+        ]
+    );
+    assert_eq!(
+        nodes("case 1\nwhen 2 then 3\nelse 4\nend")[2..],
+        [
+            node(SyntaxKind::When, "when 2 then 3"),
+            node(SyntaxKind::Int, "2"),
+            node(SyntaxKind::Then, "then 3"),
+            node(SyntaxKind::Int, "3"),
+            node(SyntaxKind::Else, "else 4"),
+            node(SyntaxKind::Int, "4"),
+        ]
+    );
+}
+
+#[test]
+fn test_blocks() {
+    assert_eq!(
+        nodes("foo do |a| end"),
+        vec![
+            node(SyntaxKind::Block, "foo do |a| end"),
+            node(SyntaxKind::Send, "foo"),
+            node(SyntaxKind::DoBody, "do |a| end"),
+            node(SyntaxKind::Args, "|a|"),
+            node(SyntaxKind::Arg, "a"),
+        ]
+    );
+    assert_eq!(
+        nodes("foo { _1 }")[2],
+        node(SyntaxKind::BraceBody, "{ _1 }")
+    );
+}
@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use crate::{Parser, ParserOptions, ParserResult};
+
+// Generated fixtures exist only after `make codegen-tests`,
+// without them only manual fixtures (~70 files) are used
+const FIXTURE_DIRS: &[&str] = &[
+    "src/tests/fixtures/parser/manual",
+    "src/tests/fixtures/parser/gen",
+];
+
+/// Input of the parser fixture
+pub(crate) struct FixtureInput {
+    pub(crate) path: PathBuf,
+    pub(crate) input: String,
+    /// `true` if the fixture expects errors in its `--DIAGNOSTIC` section
+    pub(crate) expects_errors: bool,
+}
+
+/// Returns inputs of all parser fixtures, sorted by path.
+///
+/// Used by tests that check something for every input (like formatting or trivia).
+/// Note: generated fixtures are skipped if `make codegen-tests` wasn't run.
+pub(crate) fn fixture_inputs() -> Vec<FixtureInput> {
+    let mut fixtures = vec![];
+    for dir in FIXTURE_DIRS {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            // generated fixtures are optional
+            Err(_) => continue,
+        };
+        for entry in entries {
+            let path = entry.unwrap().path();
+            fixtures.push(read_fixture(path));
+        }
+    }
+    fixtures.sort_by(|a, b| a.path.cmp(&b.path));
+    assert!(!fixtures.is_empty(), "no fixtures in {:?}", FIXTURE_DIRS);
+    fixtures
+}
+
+/// Reads input of a single parser fixture
+pub(crate) fn read_fixture(path: PathBuf) -> FixtureInput {
+    let content =
+        std::fs::read_to_string(&path).unwrap_or_else(|_| panic!("failed to read file {:?}", path));
+    let section = |name: &str| {
+        content
+            .lines()
+            .skip_while(|line| *line != name)
+            .skip(1)
+            .take_while(|line| !line.starts_with("--"))
+            .collect::<Vec<_>>()
+    };
+
+    let input = section("--INPUT")
+        .into_iter()
+        .filter(|line| !line.starts_with("// "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let expects_errors = section("--DIAGNOSTIC")
+        .iter()
+        .any(|line| line.contains("(error)"));
+
+    FixtureInput {
+        path,
+        input,
+        expects_errors,
+    }
+}
+
+/// Parses input of the fixture, `foo`, `bar` and `baz` are declared as local variables
+/// (just like in `src/tests/parser/fixture.rs`)
+pub(crate) fn parse_fixture_input(input: &[u8], options: ParserOptions) -> ParserResult {
+    let parser = Parser::new(input, options);
+    parser.static_env.declare("foo");
+    parser.static_env.declare("bar");
+    parser.static_env.declare("baz");
+    parser.do_parse()
+}
@@ -3,3 +3,6 @@ pub(crate) use loc_matcher::LocMatcher;
 
 mod diagnostic_matcher;
 pub(crate) use diagnostic_matcher::render_diagnostic_for_testing;
+
+mod fixture_inputs;
+pub(crate) use fixture_inputs::{fixture_inputs, parse_fixture_input, read_fixture};
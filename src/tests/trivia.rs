@@ -0,0 +1,51 @@
+use crate::tests::test_helpers::{fixture_inputs, parse_fixture_input};
+use crate::ParserOptions;
+
+// Checks that tokens (including trivia) go one after another and cover the whole input
+fn assert_lossless(input: &[u8], name: &str) {
+    let options = ParserOptions {
+        buffer_name: "(trivia)".into(),
+        record_tokens: true,
+        record_trivia: true,
+        ..Default::default()
+    };
+    let tokens = parse_fixture_input(input, options).tokens;
+
+    let mut pos = 0;
+    for token in tokens.iter() {
+        assert_eq!(
+            token.loc.begin, pos,
+            "{:?} doesn't follow the previous token in {}",
+            token, name
+        );
+        pos = token.loc.end;
+    }
+    assert_eq!(pos, input.len(), "tokens don't cover {}", name);
+}
+
+#[test]
+fn test_lossless_fixtures() {
+    for fixture in fixture_inputs() {
+        let name = fixture.path.display();
+        assert_lossless(fixture.input.as_bytes(), &format!("{}", name));
+
+        let input = format!("{}\n", fixture.input).replace('\n', "\r\n");
+        assert_lossless(input.as_bytes(), &format!("{} with CRLF", name));
+    }
+}
+
+#[test]
+fn test_lossless_heredocs() {
+    let inputs = [
+        "a = <<A + <<B\nx\nA\ny\nB\n",
+        "foo(<<~A, <<-B)\r\n  x\r\nA\r\n  y\r\n  B\r\nbar\r\n",
+        "<<A\n#{<<B}\nx\nB\nA\n",
+        "<<~'A'.strip\n  a\\n\n\n  b\nA\n",
+        "<<`A`\nls\nA\n",
+        "x = <<A\nbody\nA\n__END__\ndata\n",
+        "<<A\nunterminated\n",
+    ];
+    for input in inputs {
+        assert_lossless(input.as_bytes(), &format!("{:?}", input));
+    }
+}
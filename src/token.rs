@@ -1,9 +1,8 @@
 use crate::parser::token_name;
-use crate::{Bytes, Loc};
+use crate::{Loc, TokenValue};
 
 /// A token that is emitted by a lexer and consumed by a parser
 #[derive(Clone, PartialEq, Eq)]
-#[repr(C)]
 pub struct Token {
     /// Numeric representation of the token type,
     /// e.g. 42 (for example) for tINTEGER
@@ -11,7 +10,7 @@ pub struct Token {
 
     /// Value of the token,
     /// e.g "42" for 42
-    pub token_value: Bytes,
+    pub token_value: TokenValue,
 
     /// Location of the token
     pub loc: Loc,
@@ -19,18 +18,18 @@ pub struct Token {
 
 impl Token {
     /// Returns a byte array of the token value
-    pub fn as_bytes(&self) -> &Vec<u8> {
-        self.token_value.as_raw()
+    pub fn as_bytes(&self) -> &[u8] {
+        self.token_value.as_bytes()
     }
 
     /// Consumes a token and returns an owned byte array of the token value
     pub fn into_bytes(self) -> Vec<u8> {
-        self.token_value.into_raw()
+        self.token_value.into_bytes().into_raw()
     }
 
     /// Converts token value into `&str`
     pub fn as_str_lossy(&self) -> Result<&str, std::str::Utf8Error> {
-        std::str::from_utf8(self.token_value.as_raw())
+        std::str::from_utf8(self.token_value.as_bytes())
     }
 
     /// Converts token to a string, replaces unknown chars to `U+FFFD`
@@ -70,7 +69,7 @@ impl std::fmt::Debug for Token {
 fn new_token() -> Token {
     Token {
         token_type: crate::Lexer::tINTEGER,
-        token_value: Bytes::new(vec![42]),
+        token_value: TokenValue::from(vec![42]),
         loc: Loc { begin: 1, end: 2 },
     }
 }
@@ -78,7 +77,7 @@ fn new_token() -> Token {
 #[test]
 fn test_as_bytes() {
     let token = new_token();
-    assert_eq!(token.as_bytes(), &vec![42]);
+    assert_eq!(token.as_bytes(), &[42]);
 }
 
 #[test]
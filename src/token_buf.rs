@@ -1,14 +1,29 @@
+use crate::source::ContentMapBuilder;
 use crate::Bytes;
 
+// Encoding of the string literal content that is implied by its chars,
+// mirrors `enc` that MRI passes to `tokadd_string`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenEncoding {
+    // non-ASCII chars of the source
+    Source,
+    // non-ASCII `\u` escapes
+    Utf8,
+}
+
 #[derive(Debug, Clone, Default)]
 pub(crate) struct TokenBuf {
     pub(crate) bytes: Bytes,
+    pub(crate) content_map: ContentMapBuilder,
+    pub(crate) encoding: Option<TokenEncoding>,
 }
 
 impl TokenBuf {
     pub(crate) fn new(bytes: &[u8]) -> Self {
         Self {
             bytes: Bytes::new(Vec::from(bytes)),
+            content_map: ContentMapBuilder::default(),
+            encoding: None,
         }
     }
 
@@ -30,6 +45,24 @@ impl TokenBuf {
         let mut tmp = part.to_vec();
         tmp.extend(self.bytes.as_raw().iter());
         self.bytes.set_raw(tmp);
+        self.content_map.shift(part.len());
+    }
+
+    // Bytes that are added after this call are produced by source bytes starting at `source_begin`
+    pub(crate) fn begin_source(&mut self, source_begin: usize) {
+        self.content_map.begin(self.len(), source_begin)
+    }
+
+    // Bytes that have been added since `begin_source` are produced by source bytes ending at `source_end`
+    pub(crate) fn end_source(&mut self, source_end: usize) {
+        self.content_map.end(self.len(), source_end)
+    }
+
+    // Appends bytes that are copied from the source as is
+    pub(crate) fn append_source(&mut self, bytes: &[u8], source_begin: usize) {
+        self.begin_source(source_begin);
+        self.append(bytes);
+        self.end_source(source_begin + bytes.len());
     }
 
     pub(crate) fn borrow_string(&self) -> Result<&str, &[u8]> {
@@ -44,7 +77,9 @@ impl TokenBuf {
     }
 
     pub(crate) fn clear(&mut self) {
-        self.bytes.clear()
+        self.bytes.clear();
+        self.content_map = ContentMapBuilder::default();
+        self.encoding = None;
     }
 }
 
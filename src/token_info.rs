@@ -0,0 +1,191 @@
+use crate::source::Input;
+use crate::{Loc, Message};
+
+const TAB_WIDTH: usize = 8;
+
+/// Position of the keyword that opens a block (like `def` or `if`),
+/// mirrors `token_info` from MRI
+#[derive(Debug, Clone)]
+pub(crate) struct TokenInfo {
+    token: &'static str,
+    loc: Loc,
+    // 0-based
+    line: usize,
+    // 1-based column of the keyword with expanded tabs
+    indent: usize,
+    // `true` if there's something other than whitespaces before the keyword
+    nonspc: bool,
+}
+
+impl TokenInfo {
+    // Mirrors `token_info_setup` from MRI
+    pub(crate) fn new(token: &'static str, loc: &Loc, input: &Input) -> Option<Self> {
+        let (line, _) = input.line_col_for_pos(loc.begin)?;
+        let line_start = input.line_at(line).start;
+
+        let mut indent = 1;
+        let mut nonspc = false;
+        for byte in input.substr_at(line_start, loc.begin)? {
+            if *byte == b'\t' {
+                indent = ((indent - 1) / TAB_WIDTH + 1) * TAB_WIDTH;
+            }
+            indent += 1;
+            if *byte != b' ' && *byte != b'\t' {
+                nonspc = true;
+            }
+        }
+
+        Some(Self {
+            token,
+            loc: *loc,
+            line,
+            indent,
+            nonspc,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct TokenInfoStack {
+    stack: Vec<TokenInfo>,
+}
+
+impl TokenInfoStack {
+    pub(crate) fn new() -> Self {
+        Self { stack: vec![] }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.stack.clear()
+    }
+
+    pub(crate) fn push(&mut self, token: &'static str, loc: &Loc, input: &Input) {
+        if let Some(token_info) = TokenInfo::new(token, loc, input) {
+            self.stack.push(token_info)
+        }
+    }
+
+    // Pushes `if`, an `if` that directly follows `else` (i.e. `else if`)
+    // is checked as if it starts the line
+    pub(crate) fn push_if(&mut self, loc: &Loc, input: &Input) {
+        let mut token_info = match TokenInfo::new("if", loc, input) {
+            Some(token_info) => token_info,
+            None => return,
+        };
+        if token_info.nonspc {
+            if let Some(prev) = self.stack.last().filter(|prev| prev.token == "else") {
+                let between = input.substr_at(prev.loc.end, loc.begin).unwrap_or_default();
+                if between.iter().all(|byte| byte.is_ascii_whitespace()) {
+                    token_info.nonspc = false;
+                }
+            }
+        }
+        self.stack.push(token_info)
+    }
+
+    pub(crate) fn pop(&mut self, token: &str, loc: &Loc, input: &Input) -> Option<Message> {
+        let token_info = self.stack.pop()?;
+        Self::warn(&token_info, token, true, loc, input)
+    }
+
+    // Pops an opening keyword that doesn't need a closing one (i.e. `def` of the endless method)
+    pub(crate) fn drop(&mut self, token: &str, loc: &Loc) {
+        if let Some(token_info) = self.stack.last() {
+            if token_info.token == token && token_info.loc == *loc {
+                self.stack.pop();
+            }
+        }
+    }
+
+    // Checks an intermediate keyword (like `rescue` or `when`) against the innermost block
+    pub(crate) fn check(
+        &mut self,
+        token: &str,
+        same: bool,
+        loc: &Loc,
+        input: &Input,
+    ) -> Option<Message> {
+        let token_info = self.stack.last()?;
+        Self::warn(token_info, token, same, loc, input)
+    }
+
+    // `else` of everything except `case` replaces its block,
+    // so that `end` is checked against `else`
+    pub(crate) fn check_else(&mut self, loc: &Loc, input: &Input) -> Option<Message> {
+        let same = self.stack.last()?.token != "case";
+        let message = self.check("else", same, loc, input);
+        if same {
+            if let Some(token_info) = TokenInfo::new("else", loc, input) {
+                if !token_info.nonspc {
+                    if let Some(top) = self.stack.last_mut() {
+                        *top = token_info;
+                    }
+                }
+            }
+        }
+        message
+    }
+
+    // Mirrors `token_info_warn` from MRI
+    fn warn(beg: &TokenInfo, token: &str, same: bool, loc: &Loc, input: &Input) -> Option<Message> {
+        let end = TokenInfo::new("", loc, input)?;
+        if beg.line == end.line {
+            // one-line block
+            return None;
+        }
+        if beg.nonspc || end.nonspc {
+            // keyword in the middle of a line
+            return None;
+        }
+        if beg.indent == end.indent {
+            return None;
+        }
+        if !same && beg.indent < end.indent {
+            return None;
+        }
+        Some(Message::MismatchedIndentations {
+            token: token.to_string(),
+            opening_token: beg.token.to_string(),
+            opening_line: beg.line + 1,
+            opening_l: beg.loc,
+        })
+    }
+}
+
+#[cfg(test)]
+fn input(source: &str) -> Input {
+    let mut input = Input::new("(test)", None);
+    input.update_bytes(source.as_bytes().to_vec());
+    input
+}
+
+#[test]
+fn test_indent() {
+    let input = input("foo\n  def\n\t end");
+    let def = TokenInfo::new("def", &Loc { begin: 6, end: 9 }, &input).unwrap();
+    assert_eq!((def.line, def.indent, def.nonspc), (1, 3, false));
+    let end = TokenInfo::new("end", &Loc { begin: 12, end: 15 }, &input).unwrap();
+    assert_eq!((end.line, end.indent, end.nonspc), (2, 10, false));
+    let foo = TokenInfo::new("foo", &Loc { begin: 1, end: 3 }, &input).unwrap();
+    assert!(foo.nonspc);
+}
+
+#[test]
+fn test_mismatched_indentations() {
+    let input = input("def m\n  end\nif a\nend");
+    let mut stack = TokenInfoStack::new();
+
+    stack.push("def", &Loc { begin: 0, end: 3 }, &input);
+    assert_eq!(
+        stack.pop("end", &Loc { begin: 8, end: 11 }, &input),
+        Some(Message::MismatchedIndentations {
+            token: String::from("end"),
+            opening_token: String::from("def"),
+            opening_line: 1,
+            opening_l: Loc { begin: 0, end: 3 }
+        })
+    );
+
+    stack.push("if", &Loc { begin: 12, end: 14 }, &input);
+    assert_eq!(stack.pop("end", &Loc { begin: 18, end: 21 }, &input), None);
+}
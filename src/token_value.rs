@@ -0,0 +1,156 @@
+use crate::source::SourceBytes;
+use crate::Bytes;
+
+/// Value of a token.
+///
+/// If the input is shared with the caller (i.e. it's not `SourceBytes::Owned`)
+/// and the value is equal to the source of the token (i.e. the lexer didn't unescape it)
+/// it refers to the input buffer, otherwise it owns its bytes.
+#[derive(Clone)]
+pub struct TokenValue {
+    repr: Repr,
+}
+
+#[derive(Clone)]
+enum Repr {
+    Owned(Bytes),
+    Shared {
+        input: SourceBytes,
+        begin: usize,
+        end: usize,
+    },
+}
+
+impl TokenValue {
+    /// Constructs a value that owns given bytes
+    pub fn owned(bytes: Bytes) -> Self {
+        Self {
+            repr: Repr::Owned(bytes),
+        }
+    }
+
+    /// Constructs a value that refers to `begin..end` of the input,
+    /// bytes are copied if the input is owned.
+    ///
+    /// Panics if the range is out of bounds of the input.
+    pub fn shared(input: &SourceBytes, begin: usize, end: usize) -> Self {
+        if !input.is_shared() {
+            return Self::owned(Bytes::new(input[begin..end].to_vec()));
+        }
+        assert!(
+            begin <= end && end <= input.len(),
+            "range {}..{} is out of bounds of the input",
+            begin,
+            end
+        );
+        Self {
+            repr: Repr::Shared {
+                input: input.clone(),
+                begin,
+                end,
+            },
+        }
+    }
+
+    /// Returns `true` if the value refers to the shared input
+    pub fn is_shared(&self) -> bool {
+        matches!(self.repr, Repr::Shared { .. })
+    }
+
+    /// Returns a byte array of the value
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.repr {
+            Repr::Owned(bytes) => bytes.as_raw(),
+            Repr::Shared { input, begin, end } => &input[*begin..*end],
+        }
+    }
+
+    /// Converts itself into owned bytes, copies bytes only if they are shared
+    pub fn into_bytes(self) -> Bytes {
+        match self.repr {
+            Repr::Owned(bytes) => bytes,
+            Repr::Shared { .. } => Bytes::new(self.as_bytes().to_vec()),
+        }
+    }
+
+    /// Converts value to a string, replaces unknown chars to `U+FFFD`
+    pub fn to_string_lossy(&self) -> String {
+        String::from_utf8_lossy(self.as_bytes()).into_owned()
+    }
+
+    /// Converts value to a string
+    pub fn to_string(&self) -> Result<String, std::string::FromUtf8Error> {
+        String::from_utf8(self.as_bytes().to_vec())
+    }
+
+    /// Consumes itself and converts it into a string
+    pub fn into_string(self) -> Result<String, std::string::FromUtf8Error> {
+        self.into_bytes().into_string()
+    }
+}
+
+impl Default for TokenValue {
+    fn default() -> Self {
+        Self::owned(Bytes::default())
+    }
+}
+
+impl std::fmt::Debug for TokenValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.is_shared() { "Shared" } else { "Owned" };
+        f.debug_tuple(kind).field(&self.as_bytes()).finish()
+    }
+}
+
+impl PartialEq for TokenValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for TokenValue {}
+
+impl std::ops::Deref for TokenValue {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsRef<[u8]> for TokenValue {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl From<Bytes> for TokenValue {
+    fn from(bytes: Bytes) -> Self {
+        Self::owned(bytes)
+    }
+}
+
+impl From<Vec<u8>> for TokenValue {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::owned(Bytes::new(bytes))
+    }
+}
+
+#[test]
+fn test_shared() {
+    let input = SourceBytes::Static(b"foo bar");
+    let value = TokenValue::shared(&input, 4, 7);
+    assert!(value.is_shared());
+    assert_eq!(value.as_bytes(), b"bar");
+    assert!(std::ptr::eq(value.as_bytes(), &input[4..7]));
+    assert_eq!(value.clone().into_bytes(), Bytes::new(b"bar".to_vec()));
+    assert_eq!(value, TokenValue::from(b"bar".to_vec()));
+}
+
+#[test]
+fn test_owned_input_is_copied() {
+    let input = SourceBytes::from("foo bar");
+    let value = TokenValue::shared(&input, 0, 3);
+    assert!(!value.is_shared());
+    assert_eq!(value.as_bytes(), b"foo");
+}
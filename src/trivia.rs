@@ -0,0 +1,262 @@
+use crate::source::{Comment, CommentType, DecodedInput};
+use crate::{LexState, Lexer, Loc, Token};
+
+/// Inserts trivia tokens (whitespaces, newlines, comments, etc) between given tokens,
+/// so that concatenated sources of all tokens are equal to the input.
+///
+/// Tokens are sorted by their locations, i.e. tokens of heredoc bodies
+/// (that are emitted by the lexer out of order) are moved to their places.
+pub(crate) fn insert_trivia(tokens: &mut Vec<Token>, input: &DecodedInput, comments: &[Comment]) {
+    insert_trivia_with_lex_states(tokens, &mut vec![], input, comments)
+}
+
+/// The same as `insert_trivia`, but also keeps `lex_states` (if it's not empty)
+/// aligned with `tokens`, trivia tokens get the state of the previous token.
+pub(crate) fn insert_trivia_with_lex_states(
+    tokens: &mut Vec<Token>,
+    lex_states: &mut Vec<LexState>,
+    input: &DecodedInput,
+    comments: &[Comment],
+) {
+    let source = input.as_shared_bytes();
+    let mut comments = comments.iter().collect::<Vec<_>>();
+    comments.sort_by_key(|comment| comment.location.begin);
+
+    let record_lex_states = !lex_states.is_empty();
+    let mut pairs = std::mem::take(tokens)
+        .into_iter()
+        .zip(
+            std::mem::take(lex_states)
+                .into_iter()
+                .map(Some)
+                .chain(std::iter::repeat(None)),
+        )
+        .collect::<Vec<_>>();
+    pairs.sort_by_key(|(token, _)| (token.loc.begin, token.loc.end));
+
+    let mut result = Vec::with_capacity(pairs.len() * 2);
+    let mut pos = 0;
+    let mut lex_state = LexState::default();
+    for (mut token, state) in pairs {
+        if token.token_type == Lexer::END_OF_INPUT {
+            // `__END__` is a part of the data trivia
+            token.loc.end = token.loc.begin;
+        }
+        if token.loc.begin > pos {
+            push_trivia(&mut result, input, &comments, pos, token.loc.begin);
+        }
+        pos = pos.max(token.loc.end);
+        if record_lex_states {
+            lex_states.resize(result.len(), lex_state);
+            lex_state = state.unwrap_or(lex_state);
+            lex_states.push(lex_state);
+        }
+        result.push(token);
+    }
+    if pos < source.len() {
+        push_trivia(&mut result, input, &comments, pos, source.len());
+    }
+    if record_lex_states {
+        lex_states.resize(result.len(), lex_state);
+    }
+
+    *tokens = result;
+}
+
+fn push_trivia(
+    tokens: &mut Vec<Token>,
+    input: &DecodedInput,
+    comments: &[&Comment],
+    mut pos: usize,
+    end: usize,
+) {
+    let source = input.as_shared_bytes();
+    while pos < end {
+        let (token_type, len) = next_trivia(source, comments, pos, end);
+        let len = len.clamp(1, end - pos);
+        tokens.push(Token {
+            token_type,
+            token_value: input.token_value(None, pos, pos + len),
+            loc: Loc {
+                begin: pos,
+                end: pos + len,
+            },
+        });
+        pos += len;
+    }
+}
+
+// Returns type and length of the trivia that starts at `pos`
+fn next_trivia(source: &[u8], comments: &[&Comment], pos: usize, end: usize) -> (i32, usize) {
+    let rest = &source[pos..end];
+    let is_bol = pos == 0 || source[pos - 1] == b'\n';
+
+    if let Ok(idx) = comments.binary_search_by_key(&pos, |comment| comment.location.begin) {
+        let comment = comments[idx];
+        return match comment.kind {
+            CommentType::Document => (Lexer::tEMBDOC, comment.location.end - pos),
+            _ => {
+                // some inline comments include a trailing newline, it's a separate trivia
+                let mut end = comment.location.end;
+                if source[pos..end].ends_with(b"\n") {
+                    end -= 1;
+                    if source[pos..end].ends_with(b"\r") {
+                        end -= 1;
+                    }
+                }
+                (Lexer::tCOMMENT, end - pos)
+            }
+        };
+    }
+
+    match rest {
+        [b'#', ..] => {
+            // shebang
+            let len = rest.iter().position(|c| *c == b'\n').unwrap_or(rest.len());
+            (Lexer::tCOMMENT, len)
+        }
+        [b'\n', ..] => (Lexer::tNEWLINE, 1),
+        [b'\r', b'\n', ..] => (Lexer::tNEWLINE, 2),
+        [b'\\', b'\n', ..] => (Lexer::tLINE_CONTINUATION, 2),
+        [b'\\', b'\r', b'\n', ..] => (Lexer::tLINE_CONTINUATION, 3),
+        _ if is_bol && is_end_data(rest) => (Lexer::tEND_DATA, rest.len()),
+        [c, ..] if is_whitespace(*c) => {
+            // stops before `\r` that may start `\r\n`,
+            // a single `\r` is consumed as a whitespace
+            let len = rest
+                .iter()
+                .position(|c| !is_whitespace(*c) || *c == b'\r')
+                .unwrap_or(rest.len());
+            (Lexer::tWHITESPACE, len)
+        }
+        _ => {
+            // bytes that are skipped by the lexer, like invalid chars or `\0`/`^D` (and everything after them)
+            let len = rest
+                .iter()
+                .position(|c| is_whitespace(*c) || *c == b'\n')
+                .unwrap_or(rest.len());
+            (Lexer::tSKIPPED, len)
+        }
+    }
+}
+
+fn is_whitespace(c: u8) -> bool {
+    matches!(c, b' ' | b'\t' | b'\x0c' | b'\x0b' | b'\r')
+}
+
+fn is_end_data(rest: &[u8]) -> bool {
+    match rest.strip_prefix(b"__END__") {
+        Some(after) => after.is_empty() || after.starts_with(b"\n") || after.starts_with(b"\r\n"),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+fn tokenize_with_trivia(source: &[u8]) -> Vec<Token> {
+    let pool = alloc_from_pool::Pool::new();
+    let mut lexer = Lexer::new(source, "(test)", None);
+    lexer.tokens_factory = pool.factory();
+    let mut tokens = lexer.tokenize_until_eof();
+    insert_trivia(&mut tokens, &lexer.buffer.input.decoded, &lexer.comments);
+    tokens
+}
+
+#[test]
+fn test_lossless() {
+    let source =
+        b"# comment\nfoo(<<~A, 1) # c\n  x\nA\n=begin\ndoc\n=end\nbar \\\n\tbaz\r\n__END__\ndata\n";
+    let tokens = tokenize_with_trivia(source);
+
+    let mut rebuilt = vec![];
+    let mut pos = 0;
+    for token in tokens.iter() {
+        assert_eq!(token.loc.begin, pos, "{:?}", tokens);
+        rebuilt.extend_from_slice(&source[token.loc.begin..token.loc.end]);
+        pos = token.loc.end;
+    }
+    assert_eq!(rebuilt, source);
+}
+
+#[test]
+fn test_trivia_types() {
+    let source = b"# comment\nfoo # c\n=begin\ndoc\n=end\nbar \\\n\tbaz\r\n__END__\ndata\n";
+    let trivia = tokenize_with_trivia(source)
+        .into_iter()
+        .filter(|token| {
+            token.token_type >= Lexer::tWHITESPACE && token.token_type <= Lexer::tSKIPPED
+        })
+        .map(|token| (token.token_name(), token.to_string_lossy()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        trivia,
+        vec![
+            ("tCOMMENT", String::from("# comment")),
+            ("tNEWLINE", String::from("\n")),
+            ("tWHITESPACE", String::from(" ")),
+            ("tCOMMENT", String::from("# c")),
+            ("tEMBDOC", String::from("=begin\ndoc\n=end\n")),
+            ("tWHITESPACE", String::from(" ")),
+            ("tLINE_CONTINUATION", String::from("\\\n")),
+            ("tWHITESPACE", String::from("\t")),
+            // `tNL` token of `\r\n` is located at `\r`
+            ("tNEWLINE", String::from("\n")),
+            ("tEND_DATA", String::from("__END__\ndata\n")),
+        ]
+    );
+}
+
+#[test]
+fn test_crlf() {
+    // The lexer skips `\r` of `\r\n` (like `parser_cr` in MRI) and emits `tNL`
+    // with the location of `\r` and the length of 1 (see `Lexer::yylex`).
+    // Locations of tokens from the lexer are never changed here (they are also used by the parser
+    // and diagnostics), so the remaining `\n` becomes a separate `tNEWLINE` trivia.
+    let tokens = tokenize_with_trivia(b"foo\r\nbar")
+        .into_iter()
+        .map(|token| (token.token_name(), token.loc.begin, token.loc.end))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        tokens,
+        vec![
+            ("tIDENTIFIER", 0, 3),
+            ("tNL", 3, 4),
+            ("tNEWLINE", 4, 5),
+            ("tIDENTIFIER", 5, 8),
+        ]
+    );
+}
+
+#[test]
+fn test_trivia_lex_states() {
+    let source = b"foo # c\n  bar(1)\n";
+    let pool = alloc_from_pool::Pool::new();
+    let mut lexer = Lexer::new(source, "(test)", None);
+    lexer.tokens_factory = pool.factory();
+    let mut tokens = lexer.tokenize_until_eof();
+    let mut lex_states = (0..tokens.len())
+        .map(|idx| LexState { value: 1 << idx })
+        .collect::<Vec<_>>();
+    let expected = lex_states.clone();
+
+    insert_trivia_with_lex_states(
+        &mut tokens,
+        &mut lex_states,
+        &lexer.buffer.input.decoded,
+        &lexer.comments,
+    );
+
+    assert_eq!(lex_states.len(), tokens.len());
+    let mut previous = LexState::default();
+    let mut states_of_tokens = vec![];
+    for (token, state) in tokens.iter().zip(lex_states.iter()) {
+        if token.token_type >= Lexer::tWHITESPACE && token.token_type <= Lexer::tSKIPPED {
+            assert_eq!(*state, previous, "{:?}", token);
+        } else {
+            states_of_tokens.push(*state);
+            previous = *state;
+        }
+    }
+    assert_eq!(states_of_tokens, expected);
+}
@@ -18,6 +18,10 @@ impl VariablesStack {
         self.stack.borrow().is_empty()
     }
 
+    pub(crate) fn clear(&self) {
+        self.stack.borrow_mut().clear()
+    }
+
     pub(crate) fn push(&self) {
         self.stack.borrow_mut().push(BTreeSet::new())
     }
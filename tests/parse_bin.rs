@@ -0,0 +1,66 @@
+// Tests of the `parse` binary, run them with `cargo test --features=bin-parse`
+#![cfg(feature = "bin-parse")]
+
+use std::path::PathBuf;
+use std::process::Command;
+
+struct TmpDir(PathBuf);
+
+impl TmpDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn write(&self, filename: &str, content: &str) {
+        std::fs::write(self.0.join(filename), content).unwrap()
+    }
+
+    fn path(&self, filename: &str) -> String {
+        self.0.join(filename).to_str().unwrap().to_string()
+    }
+}
+
+impl Drop for TmpDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+fn run_parse(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_parse"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_query_with_glob() {
+    let dir = TmpDir::new("lib-ruby-parser-query-with-glob");
+    dir.write("a.rb", "require 'foo'\nbar\n");
+    dir.write("b.rb", "x = 1\n\n  require \"baz\"\n");
+    dir.write("c.txt", "require 'ignored'\n");
+
+    let stdout = run_parse(&[
+        "--query",
+        "(send nil :require (str $_))",
+        "--glob",
+        &dir.path("*.rb"),
+    ]);
+
+    assert_eq!(
+        stdout,
+        format!(
+            "{}:1:1: require 'foo'\n    $1 = foo\n{}:3:3: require \"baz\"\n    $1 = baz\n",
+            dir.path("a.rb"),
+            dir.path("b.rb")
+        )
+    );
+}